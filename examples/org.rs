@@ -1,6 +1,17 @@
 use std::collections::HashMap;
 
-use crux_es::{backlog::*, event_store::*};
+use std::fmt;
+use std::time::SystemTime;
+
+use crux_es::{
+    aggregate::Aggregate,
+    backlog::*,
+    envelope::Envelope,
+    event_store::*,
+    repository::Repository,
+    services::Services,
+    workflow::{Workflow, WorkflowInstance},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct OrgId(String);
@@ -8,6 +19,7 @@ struct OrgId(String);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct UserId(String);
 
+#[allow(dead_code)]
 struct Org {
     id: OrgId,
     name: String,
@@ -46,6 +58,7 @@ impl OrgService {
 #[derive(Debug, Clone)]
 struct UserData(pub String);
 
+#[allow(dead_code)]
 struct User {
     id: UserId,
     data: UserData,
@@ -76,6 +89,13 @@ impl UserService {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct UserAddId(String);
 
+impl fmt::Display for UserAddId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 enum UserAddBacklogStatus {
     Created(UserData, OrgId),
@@ -84,6 +104,7 @@ enum UserAddBacklogStatus {
     UserAdded(UserId, OrgId),
 }
 
+#[derive(Clone)]
 struct UserAddBacklog {
     id: UserAddId,
     status: UserAddBacklogStatus,
@@ -140,148 +161,212 @@ impl Backlog for UserAddBacklog {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum PersistableEventId {
-    UserAdd(UserAddId),
-}
-
 #[derive(Debug, Clone)]
 enum PersistableEvent {
     UserAddCreated(UserAddCreatedEvent),
     UserAdd(UserAddEvent),
 }
 
-struct OnMemoryEventStore {
-    uncommitted_events: HashMap<PersistableEventId, Vec<PersistableEvent>>,
-    is_transaction_active: bool,
-    events: HashMap<PersistableEventId, Vec<PersistableEvent>>,
+impl PersistableEvent {
+    fn user_add_id(&self) -> &UserAddId {
+        match self {
+            PersistableEvent::UserAddCreated(event) => &event.id,
+            PersistableEvent::UserAdd(UserAddEvent::Reserved(id, ..))
+            | PersistableEvent::UserAdd(UserAddEvent::UserCreated(id, ..))
+            | PersistableEvent::UserAdd(UserAddEvent::UserAdded(id, ..)) => id,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct OnMemoryEventStoreError;
-impl std::fmt::Display for OnMemoryEventStoreError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "OnMemoryEventStoreError")
-    }
+/// The user addition process, rebuilt from its events, so that a failed step can be
+/// compensated from the steps persisted before it.
+#[derive(Clone, Default)]
+struct UserAddProcess {
+    backlog: Option<UserAddBacklog>,
 }
-impl std::error::Error for OnMemoryEventStoreError {}
-
-impl EventStore for OnMemoryEventStore {
-    type Persistable = PersistableEvent;
-    type Error = OnMemoryEventStoreError;
-
-    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
-        for event in events {
-            match event {
-                PersistableEvent::UserAddCreated(event) => {
-                    let id = PersistableEventId::UserAdd(event.id.clone());
-                    let events = self.uncommitted_events.entry(id).or_default();
-                    events.push(PersistableEvent::UserAddCreated(event.clone()));
-                }
-                PersistableEvent::UserAdd(event) => {
-                    let id = match event {
-                        UserAddEvent::Reserved(id, _) => PersistableEventId::UserAdd(id.clone()),
-                        UserAddEvent::UserCreated(id, _, _) => {
-                            PersistableEventId::UserAdd(id.clone())
-                        }
-                        UserAddEvent::UserAdded(id, _, _) => {
-                            PersistableEventId::UserAdd(id.clone())
-                        }
-                    };
-                    let events = self.uncommitted_events.entry(id).or_default();
-                    events.push(PersistableEvent::UserAdd(event.clone()));
+
+impl Aggregate for UserAddProcess {
+    type Id = UserAddId;
+    type Event = PersistableEvent;
+
+    fn aggregate_type() -> &'static str {
+        "user_add"
+    }
+
+    fn event_type(event: &PersistableEvent) -> String {
+        match event {
+            PersistableEvent::UserAddCreated(_) => "UserAddCreated",
+            PersistableEvent::UserAdd(UserAddEvent::Reserved(..)) => "Reserved",
+            PersistableEvent::UserAdd(UserAddEvent::UserCreated(..)) => "UserCreated",
+            PersistableEvent::UserAdd(UserAddEvent::UserAdded(..)) => "UserAdded",
+        }
+        .to_string()
+    }
+
+    fn apply(&mut self, event: &PersistableEvent) {
+        match event {
+            PersistableEvent::UserAddCreated(event) => {
+                self.backlog = Some(UserAddBacklog::create(event.clone()));
+            }
+            PersistableEvent::UserAdd(event) => {
+                if let Some(backlog) = self.backlog.as_mut() {
+                    backlog.resolve(event.clone());
                 }
             }
         }
-        Ok(())
     }
 }
 
-impl TransactionManager for OnMemoryEventStore {
-    type Error = OnMemoryEventStoreError;
+type UserAddRepository<S> = Repository<UserAddProcess, S>;
+
+/// Persist the event of a step in its own unit of work, before the next external call.
+fn record<S>(services: &mut Services, event: PersistableEvent) -> Result<(), String>
+where
+    S: EventStore<Persistable = Envelope<PersistableEvent>, Error = OnMemoryEventStoreError>
+        + EventLoader<
+            StreamId = String,
+            Persistable = Envelope<PersistableEvent>,
+            Error = OnMemoryEventStoreError,
+        > + TransactionManager<Error = OnMemoryEventStoreError>
+        + 'static,
+{
+    let repository = services
+        .get_mut::<UserAddRepository<S>>()
+        .map_err(|e| e.to_string())?;
+    let id = event.user_add_id().clone();
+    let mut unit_of_work = repository.unit_of_work();
+    unit_of_work
+        .record(&id, &[event])
+        .map_err(|e| e.to_string())?;
+    unit_of_work.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
 
-    fn begin(&mut self) -> Result<(), Self::Error> {
-        self.is_transaction_active = true;
-        Ok(())
-    }
+/// Data of a user addition, from which the workflow builds the commands of its steps.
+struct UserAddData {
+    id: UserAddId,
+    data: UserData,
+    org_id: OrgId,
+    user_id: Option<UserId>,
+}
+
+/// Command of a step of the user addition, dispatched to the services.
+enum UserAddCommand {
+    Reserve(UserAddId, OrgId),
+    Create(UserAddId, UserData, OrgId),
+    Add(UserAddId, OrgId, UserId),
+}
+
+/// Outcome of a command: the event of the step, or why it failed.
+type UserAddOutcome = Result<UserAddEvent, String>;
 
-    fn commit(&mut self) -> Result<(), Self::Error> {
-        if !self.is_transaction_active {
-            return Err(OnMemoryEventStoreError);
+fn user_add_workflow() -> Workflow<UserAddData, UserAddCommand, UserAddOutcome> {
+    crux_es::workflow! {
+        "user-add";
+        step "reserve" {
+            command: |d: &UserAddData| UserAddCommand::Reserve(d.id.clone(), d.org_id.clone()),
+            succeeds_on: |e| matches!(e, Ok(UserAddEvent::Reserved(..))),
+            fails_on: Result::is_err,
         }
-        for (id, unc_events) in self.uncommitted_events.drain() {
-            let events = self.events.entry(id).or_default();
-            events.extend(unc_events);
+        step "create" {
+            command: |d: &UserAddData| {
+                UserAddCommand::Create(d.id.clone(), d.data.clone(), d.org_id.clone())
+            },
+            succeeds_on: |e| matches!(e, Ok(UserAddEvent::UserCreated(..))),
+            fails_on: Result::is_err,
+            record: |d, e| {
+                if let Ok(UserAddEvent::UserCreated(_, user_id, _)) = e {
+                    d.user_id = Some(user_id.clone());
+                }
+            },
+        }
+        step "add" {
+            command: |d: &UserAddData| {
+                UserAddCommand::Add(d.id.clone(), d.org_id.clone(), d.user_id.clone().unwrap())
+            },
+            succeeds_on: |e| matches!(e, Ok(UserAddEvent::UserAdded(..))),
+            fails_on: Result::is_err,
         }
-        self.is_transaction_active = false;
-        Ok(())
     }
+}
 
-    fn rollback(&mut self) -> Result<(), Self::Error> {
-        if !self.is_transaction_active {
-            return Err(OnMemoryEventStoreError);
+/// Dispatch the command to the services, returning the event of the step.
+fn execute(services: &mut Services, command: UserAddCommand) -> UserAddOutcome {
+    match command {
+        UserAddCommand::Reserve(id, org_id) => {
+            services
+                .get_mut::<OrgService>()
+                .map_err(|e| e.to_string())?
+                .reserve_user(org_id.clone(), id.clone())?;
+            Ok(UserAddEvent::Reserved(id, org_id))
+        }
+        UserAddCommand::Create(id, data, org_id) => {
+            let user_id = services
+                .get_mut::<UserService>()
+                .map_err(|e| e.to_string())?
+                .create_user(data.clone(), org_id)?;
+            Ok(UserAddEvent::UserCreated(id, user_id, data))
+        }
+        UserAddCommand::Add(id, org_id, user_id) => {
+            services
+                .get_mut::<OrgService>()
+                .map_err(|e| e.to_string())?
+                .add_user(org_id.clone(), user_id.clone())?;
+            Ok(UserAddEvent::UserAdded(id, user_id, org_id))
         }
-        self.is_transaction_active = false;
-        Ok(())
     }
 }
 
-fn create_user<ES: EventStore<Persistable = PersistableEvent> + TransactionManager>(
+fn create_user<S>(
     userdata: UserData,
     org_id: OrgId,
-    us: &mut UserService,
-    os: &mut OrgService,
-    es: &mut ES,
-) -> Result<String, String> {
+    services: &mut Services,
+) -> Result<String, String>
+where
+    S: EventStore<Persistable = Envelope<PersistableEvent>, Error = OnMemoryEventStoreError>
+        + EventLoader<
+            StreamId = String,
+            Persistable = Envelope<PersistableEvent>,
+            Error = OnMemoryEventStoreError,
+        > + TransactionManager<Error = OnMemoryEventStoreError>
+        + 'static,
+{
     let user_add_id = UserAddId(userdata.0.clone());
     let event = UserAddCreatedEvent {
         id: user_add_id.clone(),
         data: userdata.clone(),
         org_id: org_id.clone(),
     };
-    let _backlog = UserAddBacklog::create(event.clone());
-    es.begin().map_err(|e| e.to_string())?;
-    es.save(&[PersistableEvent::UserAddCreated(event.clone())])
-        .map_err(|e| e.to_string())?;
-    es.commit().map_err(|e| e.to_string())?;
+    record::<S>(services, PersistableEvent::UserAddCreated(event))?;
 
-    os.reserve_user(org_id.clone(), user_add_id.clone())
-        .map_err(|e| e.to_string())?;
-    es.begin().map_err(|e| e.to_string())?;
-    es.save(&[PersistableEvent::UserAdd(UserAddEvent::Reserved(
-        user_add_id.clone(),
-        org_id.clone(),
-    ))])
-    .map_err(|e| e.to_string())?;
-    es.commit().map_err(|e| e.to_string())?;
-
-    let user_id = us
-        .create_user(userdata.clone(), org_id.clone())
-        .map_err(|e| e.to_string())?;
-    es.begin().map_err(|e| e.to_string())?;
-    es.save(&[PersistableEvent::UserAdd(UserAddEvent::UserCreated(
-        user_add_id.clone(),
-        user_id.clone(),
-        userdata.clone(),
-    ))])
-    .map_err(|e| e.to_string())?;
-    es.commit().map_err(|e| e.to_string())?;
-
-    os.add_user(org_id.clone(), user_id.clone())
-        .map_err(|e| e.to_string())?;
-    es.begin().map_err(|e| e.to_string())?;
-    es.save(&[PersistableEvent::UserAdd(UserAddEvent::UserAdded(
-        user_add_id.clone(),
-        user_id.clone(),
+    let workflow = user_add_workflow();
+    let data = UserAddData {
+        id: user_add_id.clone(),
+        data: userdata,
         org_id,
-    ))])
-    .map_err(|e| e.to_string())?;
-    es.commit().map_err(|e| e.to_string())?;
-    Ok(user_add_id.0)
+        user_id: None,
+    };
+    let mut instance = WorkflowInstance::new(user_add_id.0.clone(), data);
+    let now = SystemTime::now();
+    let mut commands = workflow.start(&mut instance, now);
+    let mut failure = None;
+    // The steps run one after the other, so there is at most one command at a time.
+    while let Some(command) = commands.pop() {
+        let outcome = execute(services, command);
+        match &outcome {
+            Ok(event) => record::<S>(services, PersistableEvent::UserAdd(event.clone()))?,
+            Err(e) => failure = Some(e.clone()),
+        }
+        commands.extend(workflow.handle(&mut instance, &outcome, now));
+    }
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(user_add_id.0),
+    }
 }
 
 fn main() {
-    let mut us = UserService {
+    let us = UserService {
         users: HashMap::new(),
     };
 
@@ -298,25 +383,41 @@ fn main() {
     };
     os.orgs.insert(org_id.clone(), org);
 
-    let mut es = OnMemoryEventStore {
-        uncommitted_events: HashMap::new(),
-        is_transaction_active: false,
-        events: HashMap::new(),
-    };
+    let repository = UserAddRepository::new(OnMemoryEventStore::<PersistableEvent>::new());
+    let mut services = Services::new().with(us).with(os).with(repository);
 
     let userdata = UserData("user-1".to_string());
-    let user_add_id = create_user(userdata, org_id.clone(), &mut us, &mut os, &mut es).unwrap();
+    let user_add_id = create_user::<OnMemoryEventStore<PersistableEvent>>(
+        userdata,
+        org_id.clone(),
+        &mut services,
+    )
+    .unwrap();
     println!("User Add ID: {}", user_add_id);
 
     let userdata = UserData("user-2".to_string());
-    let user_add_id = create_user(userdata, org_id.clone(), &mut us, &mut os, &mut es).unwrap();
+    let user_add_id = create_user::<OnMemoryEventStore<PersistableEvent>>(
+        userdata,
+        org_id.clone(),
+        &mut services,
+    )
+    .unwrap();
     println!("User Add ID: {}", user_add_id);
 
     let userdata = UserData("user-3".to_string());
-    let user_add_id = create_user(userdata, org_id.clone(), &mut us, &mut os, &mut es).unwrap();
+    let user_add_id = create_user::<OnMemoryEventStore<PersistableEvent>>(
+        userdata,
+        org_id.clone(),
+        &mut services,
+    )
+    .unwrap();
     println!("User Add ID: {}", user_add_id);
 
     let userdata = UserData("user-4".to_string());
-    let user_add_id = create_user(userdata, org_id.clone(), &mut us, &mut os, &mut es);
+    let user_add_id = create_user::<OnMemoryEventStore<PersistableEvent>>(
+        userdata,
+        org_id.clone(),
+        &mut services,
+    );
     assert_eq!(user_add_id, Err("Max users reached".to_string()));
 }
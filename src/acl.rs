@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Subscription;
+use crate::command_bus::CommandBus;
+use crate::dead_letter::{DeadLetter, DeadLetterStore};
+
+/// Types which translate events of an external system into commands of the bounded context.
+pub trait Translator<ExternalEvent, Command> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Translate the event. Returns `None` for events which are irrelevant to the context.
+    fn translate(&self, event: &ExternalEvent) -> Result<Option<Command>, Self::Error>;
+}
+
+/// Outcome of processing a single external event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AclOutcome {
+    /// No event was available.
+    Idle,
+    /// The event was translated and dispatched.
+    Dispatched,
+    /// The event was irrelevant and skipped.
+    Ignored,
+    /// The event could not be translated and was routed to the dead letter store.
+    DeadLettered,
+}
+
+/// Runner consuming an external subscription, translating and dispatching its events.
+pub struct AclRunner<S, T, B, D> {
+    subscription: S,
+    translator: T,
+    bus: B,
+    dead_letters: D,
+}
+
+impl<S, T, B, D> AclRunner<S, T, B, D> {
+    /// Create a new runner.
+    pub fn new(subscription: S, translator: T, bus: B, dead_letters: D) -> Self {
+        Self {
+            subscription,
+            translator,
+            bus,
+            dead_letters,
+        }
+    }
+
+    /// Get the command bus.
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    /// Get the dead letter store.
+    pub fn dead_letters(&self) -> &D {
+        &self.dead_letters
+    }
+
+    /// Process the next external event.
+    ///
+    /// An event whose command fails to dispatch is routed to the dead letter store before the
+    /// error is returned, since the subscription will not deliver it again.
+    pub fn run_once<E, C>(&mut self) -> Result<AclOutcome, AclRunnerError<S, B, D, E, C>>
+    where
+        S: Subscription<E>,
+        T: Translator<E, C>,
+        B: CommandBus<C>,
+        D: DeadLetterStore<E>,
+    {
+        let event = match self.subscription.poll().map_err(AclError::Subscription)? {
+            Some(event) => event,
+            None => return Ok(AclOutcome::Idle),
+        };
+        match self.translator.translate(&event) {
+            Ok(Some(command)) => {
+                if let Err(e) = self.bus.dispatch(command) {
+                    self.dead_letters
+                        .put(DeadLetter {
+                            message: event,
+                            reason: e.to_string(),
+                        })
+                        .map_err(AclError::DeadLetter)?;
+                    return Err(AclError::Dispatch(e));
+                }
+                Ok(AclOutcome::Dispatched)
+            }
+            Ok(None) => Ok(AclOutcome::Ignored),
+            Err(e) => {
+                self.dead_letters
+                    .put(DeadLetter {
+                        message: event,
+                        reason: e.to_string(),
+                    })
+                    .map_err(AclError::DeadLetter)?;
+                Ok(AclOutcome::DeadLettered)
+            }
+        }
+    }
+
+    /// Process external events until the subscription is idle, returning the number of events processed.
+    pub fn run_until_idle<E, C>(&mut self) -> Result<usize, AclRunnerError<S, B, D, E, C>>
+    where
+        S: Subscription<E>,
+        T: Translator<E, C>,
+        B: CommandBus<C>,
+        D: DeadLetterStore<E>,
+    {
+        let mut processed = 0;
+        while self.run_once()? != AclOutcome::Idle {
+            processed += 1;
+        }
+        Ok(processed)
+    }
+}
+
+/// Error returned by an [`AclRunner`] built from the given components.
+pub type AclRunnerError<S, B, D, E, C> = AclError<
+    <S as Subscription<E>>::Error,
+    <B as CommandBus<C>>::Error,
+    <D as DeadLetterStore<E>>::Error,
+>;
+
+/// Error returned by the [`AclRunner`].
+#[derive(Debug)]
+pub enum AclError<SE, BE, DE> {
+    /// The subscription failed.
+    Subscription(SE),
+    /// The command bus failed to dispatch the translated command.
+    Dispatch(BE),
+    /// The dead letter store failed.
+    DeadLetter(DE),
+}
+
+impl<SE: Error, BE: Error, DE: Error> fmt::Display for AclError<SE, BE, DE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AclError::Subscription(e) => write!(f, "subscription error: {}", e),
+            AclError::Dispatch(e) => write!(f, "dispatch error: {}", e),
+            AclError::DeadLetter(e) => write!(f, "dead letter store error: {}", e),
+        }
+    }
+}
+
+impl<SE: Error, BE: Error, DE: Error> Error for AclError<SE, BE, DE> {}
@@ -0,0 +1,134 @@
+use super::*;
+
+use crate::broker::{OnMemoryBroker, Publisher};
+use crate::dead_letter::OnMemoryDeadLetterStore;
+
+#[derive(Debug, Clone, PartialEq)]
+enum CrmEvent {
+    ContactCreated { email: String },
+    ContactViewed,
+    Malformed,
+}
+
+#[derive(Debug, PartialEq)]
+struct RegisterUser(String);
+
+#[derive(Debug)]
+struct MappingError;
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MappingError")
+    }
+}
+
+impl Error for MappingError {}
+
+struct CrmTranslator;
+
+impl Translator<CrmEvent, RegisterUser> for CrmTranslator {
+    type Error = MappingError;
+
+    fn translate(&self, event: &CrmEvent) -> Result<Option<RegisterUser>, Self::Error> {
+        match event {
+            CrmEvent::ContactCreated { email } => Ok(Some(RegisterUser(email.clone()))),
+            CrmEvent::ContactViewed => Ok(None),
+            CrmEvent::Malformed => Err(MappingError),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RecordingBus {
+    commands: Vec<RegisterUser>,
+    failing: bool,
+}
+
+impl CommandBus<RegisterUser> for RecordingBus {
+    type Response = ();
+    type Error = MappingError;
+
+    fn dispatch(&mut self, command: RegisterUser) -> Result<Self::Response, Self::Error> {
+        if self.failing {
+            return Err(MappingError);
+        }
+        self.commands.push(command);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_acl_runner() {
+    let mut broker = OnMemoryBroker::new();
+    broker
+        .publish(&CrmEvent::ContactCreated {
+            email: "alice@example.com".to_string(),
+        })
+        .unwrap();
+    broker.publish(&CrmEvent::ContactViewed).unwrap();
+    broker.publish(&CrmEvent::Malformed).unwrap();
+
+    let mut runner = AclRunner::new(
+        broker,
+        CrmTranslator,
+        RecordingBus::default(),
+        OnMemoryDeadLetterStore::new(),
+    );
+    assert_eq!(runner.run_once().unwrap(), AclOutcome::Dispatched);
+    assert_eq!(runner.run_once().unwrap(), AclOutcome::Ignored);
+    assert_eq!(runner.run_once().unwrap(), AclOutcome::DeadLettered);
+    assert_eq!(runner.run_once().unwrap(), AclOutcome::Idle);
+
+    assert_eq!(
+        runner.bus().commands,
+        vec![RegisterUser("alice@example.com".to_string())]
+    );
+    let letters = runner.dead_letters().letters();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].message, CrmEvent::Malformed);
+    assert_eq!(letters[0].reason, "MappingError");
+}
+
+#[test]
+fn test_run_until_idle() {
+    let mut broker = OnMemoryBroker::new();
+    broker.publish(&CrmEvent::ContactViewed).unwrap();
+    broker.publish(&CrmEvent::ContactViewed).unwrap();
+
+    let mut runner = AclRunner::new(
+        broker,
+        CrmTranslator,
+        RecordingBus::default(),
+        OnMemoryDeadLetterStore::new(),
+    );
+    assert_eq!(runner.run_until_idle().unwrap(), 2);
+}
+
+#[test]
+fn test_dead_letter_on_dispatch_failure() {
+    let mut broker = OnMemoryBroker::new();
+    let event = CrmEvent::ContactCreated {
+        email: "alice@example.com".to_string(),
+    };
+    broker.publish(&event).unwrap();
+
+    let mut runner = AclRunner::new(
+        broker,
+        CrmTranslator,
+        RecordingBus {
+            failing: true,
+            ..RecordingBus::default()
+        },
+        OnMemoryDeadLetterStore::new(),
+    );
+    assert!(matches!(
+        runner.run_once(),
+        Err(AclError::Dispatch(MappingError))
+    ));
+    assert_eq!(runner.run_once().unwrap(), AclOutcome::Idle);
+
+    let letters = runner.dead_letters().letters();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].message, event);
+    assert_eq!(letters[0].reason, "MappingError");
+}
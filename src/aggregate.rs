@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests;
+
+use std::any::{self, Any};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::envelope::Envelope;
+
+/// Types which represent an aggregate, whose state is rebuilt by applying the events of its
+/// stream in order.
+pub trait Aggregate {
+    /// Associated Type representing the ID of the aggregate.
+    type Id: fmt::Display;
+    /// Associated Type representing the events of the aggregate.
+    type Event;
+
+    /// Get the name of the aggregate type, used as the category of its streams.
+    fn aggregate_type() -> &'static str;
+    /// Get the name of the event type.
+    fn event_type(event: &Self::Event) -> String;
+    /// Apply the event to the state.
+    fn apply(&mut self, event: &Self::Event);
+    /// Get the ID of the stream of the aggregate, `{aggregate_type}-{id}` by default.
+    fn stream_id(id: &Self::Id) -> String {
+        format!("{}-{}", Self::aggregate_type(), id)
+    }
+}
+
+/// A type-erased event, so that one store can hold the events of any aggregate.
+#[derive(Clone)]
+pub struct AnyEvent {
+    event: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+impl AnyEvent {
+    /// Erase the type of the event.
+    pub fn new<T: Any + Send + Sync>(event: T) -> Self {
+        Self {
+            event: Arc::new(event),
+            type_name: any::type_name::<T>(),
+        }
+    }
+
+    /// Get the event if it is of type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.event.downcast_ref()
+    }
+
+    /// Get the Rust type name of the event.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Debug for AnyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AnyEvent({})", self.type_name)
+    }
+}
+
+impl Envelope<AnyEvent> {
+    /// Get the envelope with its payload typed, if it is of type `T`.
+    pub fn downcast<T: Any + Clone>(&self) -> Option<Envelope<T>> {
+        let payload = self.payload.downcast_ref::<T>()?.clone();
+        Some(self.clone().map(|_| payload))
+    }
+}
+
+/// An event of the aggregate `A`, together with the ID of the aggregate.
+pub struct AggregateEventEnvelope<A: Aggregate> {
+    /// ID of the aggregate.
+    pub aggregate_id: A::Id,
+    /// The event.
+    pub event: A::Event,
+}
+
+impl<A: Aggregate> AggregateEventEnvelope<A> {
+    /// Create an envelope of the event of the aggregate.
+    pub fn new(aggregate_id: A::Id, event: A::Event) -> Self {
+        Self {
+            aggregate_id,
+            event,
+        }
+    }
+
+    /// Get the ID of the stream of the aggregate.
+    pub fn stream_id(&self) -> String {
+        A::stream_id(&self.aggregate_id)
+    }
+
+    /// Wrap the event in an envelope of its stream. The version is assigned by the store.
+    pub fn into_envelope<P>(self) -> Envelope<P>
+    where
+        A::Event: Into<P>,
+    {
+        let stream_id = self.stream_id();
+        let event_type = A::event_type(&self.event);
+        Envelope::new(stream_id, 0, event_type, self.event.into())
+    }
+}
+
+impl<A: Aggregate> Clone for AggregateEventEnvelope<A>
+where
+    A::Id: Clone,
+    A::Event: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.aggregate_id.clone(), self.event.clone())
+    }
+}
+
+impl<A: Aggregate> fmt::Debug for AggregateEventEnvelope<A>
+where
+    A::Id: fmt::Debug,
+    A::Event: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AggregateEventEnvelope")
+            .field("aggregate_id", &self.aggregate_id)
+            .field("event", &self.event)
+            .finish()
+    }
+}
+
+/// Events of every aggregate can be saved in a store of [`AnyEvent`] envelopes.
+impl<A> From<AggregateEventEnvelope<A>> for Envelope<AnyEvent>
+where
+    A: Aggregate,
+    A::Event: Any + Send + Sync,
+{
+    fn from(envelope: AggregateEventEnvelope<A>) -> Self {
+        let stream_id = envelope.stream_id();
+        let event_type = A::event_type(&envelope.event);
+        Envelope::new(stream_id, 0, event_type, AnyEvent::new(envelope.event))
+    }
+}
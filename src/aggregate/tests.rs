@@ -0,0 +1,92 @@
+use super::*;
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore};
+
+struct Org;
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrgEvent {
+    Created(String),
+    UserReserved(u32),
+}
+
+impl Aggregate for Org {
+    type Id = u32;
+    type Event = OrgEvent;
+
+    fn aggregate_type() -> &'static str {
+        "org"
+    }
+
+    fn event_type(event: &OrgEvent) -> String {
+        match event {
+            OrgEvent::Created(_) => "OrgCreated".to_string(),
+            OrgEvent::UserReserved(_) => "UserReserved".to_string(),
+        }
+    }
+
+    fn apply(&mut self, _event: &OrgEvent) {}
+}
+
+struct UserAdd;
+
+#[derive(Debug, Clone, PartialEq)]
+struct UserAddCreated {
+    org: u32,
+}
+
+impl Aggregate for UserAdd {
+    type Id = String;
+    type Event = UserAddCreated;
+
+    fn aggregate_type() -> &'static str {
+        "user_add"
+    }
+
+    fn event_type(_: &UserAddCreated) -> String {
+        "UserAddCreated".to_string()
+    }
+
+    fn apply(&mut self, _event: &UserAddCreated) {}
+}
+
+#[test]
+fn test_store_accepts_events_of_any_aggregate() {
+    let mut store = OnMemoryEventStore::<AnyEvent>::new();
+    store
+        .save(&[
+            AggregateEventEnvelope::<Org>::new(1, OrgEvent::Created("Acme".to_string())).into(),
+            AggregateEventEnvelope::<UserAdd>::new("u-1".to_string(), UserAddCreated { org: 1 })
+                .into(),
+            AggregateEventEnvelope::<Org>::new(1, OrgEvent::UserReserved(7)).into(),
+        ])
+        .unwrap();
+
+    let org = store.load(&Org::stream_id(&1)).unwrap();
+    assert_eq!(
+        org.iter()
+            .map(|e| e.event_type.as_str())
+            .collect::<Vec<_>>(),
+        vec!["OrgCreated", "UserReserved"]
+    );
+    let reserved = org[1].downcast::<OrgEvent>().unwrap();
+    assert_eq!(reserved.payload, OrgEvent::UserReserved(7));
+    assert_eq!(reserved.stream_id, "org-1");
+    assert_eq!(reserved.version, 2);
+    assert!(org[1].downcast::<UserAddCreated>().is_none());
+
+    let user_add = store.load(&"user_add-u-1".to_string()).unwrap();
+    assert_eq!(
+        user_add[0].downcast::<UserAddCreated>().unwrap().payload,
+        UserAddCreated { org: 1 }
+    );
+    assert!(format!("{:?}", user_add[0].payload).contains("UserAddCreated"));
+}
+
+#[test]
+fn test_into_typed_envelope() {
+    let envelope: Envelope<OrgEvent> =
+        AggregateEventEnvelope::<Org>::new(3, OrgEvent::Created("Acme".to_string()))
+            .into_envelope();
+    assert_eq!(envelope.stream_id, "org-3");
+    assert_eq!(envelope.event_type, "OrgCreated");
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+use std::time::{Duration, SystemTime};
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, EventStore};
+use crate::retention::{Archive, PrunableEventStore};
+use crate::runtime::HousekeepingJob;
+use crate::sharding::category_of;
+use crate::snapshot::{Snapshot, SnapshotStore};
+
+/// Type of the control event left in the stream of an archived aggregate.
+pub const ARCHIVED_EVENT_TYPE: &str = "Archived";
+
+/// Types which represent an aggregate that can be archived once inactive.
+pub trait Archivable: Aggregate {
+    /// Get the control event marking the aggregate as archived at the version.
+    fn archived_event(version: u64) -> Self::Event;
+}
+
+type TimestampFn<E> = Box<dyn Fn(&Envelope<E>) -> Option<SystemTime>>;
+
+/// Housekeeping job archiving the aggregates of type `A` without events for a while.
+///
+/// The state of an inactive aggregate is snapshotted at its last version, its events are moved
+/// to the archive, and an [`ARCHIVED_EVENT_TYPE`] control event is appended, so that its stream
+/// only keeps the control event on the hot path.
+pub struct InactivityArchiver<A: Aggregate, N> {
+    inactive_after: Duration,
+    timestamp: TimestampFn<A::Event>,
+    snapshots: N,
+    archive: Box<dyn Archive<A::Event>>,
+    aggregate: PhantomData<A>,
+}
+
+impl<A: Archivable, N: SnapshotStore<A>> InactivityArchiver<A, N> {
+    /// Create an archiver of the aggregates inactive for `inactive_after`, reading the time of
+    /// the events with `timestamp`.
+    pub fn new(
+        inactive_after: Duration,
+        timestamp: impl Fn(&Envelope<A::Event>) -> Option<SystemTime> + 'static,
+        snapshots: N,
+        archive: impl Archive<A::Event> + 'static,
+    ) -> Self {
+        Self {
+            inactive_after,
+            timestamp: Box::new(timestamp),
+            snapshots,
+            archive: Box::new(archive),
+            aggregate: PhantomData,
+        }
+    }
+
+    /// Get the snapshot store.
+    pub fn snapshots(&self) -> &N {
+        &self.snapshots
+    }
+
+    /// Archive the inactive aggregates of the store, returning the number archived.
+    ///
+    /// The events are only deleted once archived and followed by the control event, so a
+    /// failure leaves them in the stream, to be archived again by the next run.
+    pub fn archive_inactive<S>(&mut self, store: &mut S, now: SystemTime) -> Result<usize, String>
+    where
+        A: Default,
+        A::Event: Clone,
+        S: PrunableEventStore<A::Event>
+            + EventStore<Persistable = Envelope<A::Event>>
+            + EventLoader<StreamId = String, Persistable = Envelope<A::Event>>,
+    {
+        let mut archived = 0;
+        for stream_id in store.stream_ids() {
+            if category_of(&stream_id) != A::aggregate_type() {
+                continue;
+            }
+            let events = EventLoader::load(store, &stream_id).map_err(|e| e.to_string())?;
+            let last = match events.last() {
+                Some(last) if last.event_type != ARCHIVED_EVENT_TYPE => last,
+                _ => continue,
+            };
+            let inactive = (self.timestamp)(last).is_some_and(|at| at + self.inactive_after <= now);
+            if !inactive {
+                continue;
+            }
+
+            let mut state = A::default();
+            for event in &events {
+                state.apply(&event.payload);
+            }
+            let version = last.version;
+            self.snapshots
+                .save(Snapshot {
+                    stream_id: stream_id.clone(),
+                    version,
+                    state,
+                })
+                .map_err(|e| e.to_string())?;
+            self.archive.archive(events)?;
+            let control = A::archived_event(version);
+            store
+                .save(&[Envelope::new(
+                    stream_id.clone(),
+                    version + 1,
+                    ARCHIVED_EVENT_TYPE,
+                    control,
+                )])
+                .map_err(|e| e.to_string())?;
+            store.delete_where(&stream_id, &mut |event| event.version <= version);
+            archived += 1;
+        }
+        Ok(archived)
+    }
+}
+
+impl<A, N, S> HousekeepingJob<S> for InactivityArchiver<A, N>
+where
+    A: Archivable + Default,
+    A::Event: Clone,
+    N: SnapshotStore<A>,
+    S: PrunableEventStore<A::Event>
+        + EventStore<Persistable = Envelope<A::Event>>
+        + EventLoader<StreamId = String, Persistable = Envelope<A::Event>>,
+{
+    fn name(&self) -> &'static str {
+        "archiving"
+    }
+
+    fn run(&mut self, store: &mut S, now: SystemTime) -> Result<usize, String> {
+        self.archive_inactive(store, now)
+    }
+}
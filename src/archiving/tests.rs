@@ -0,0 +1,159 @@
+use std::time::UNIX_EPOCH;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::event::Event;
+use crate::event_store::OnMemoryEventStore;
+use crate::runtime::CruxRuntime;
+use crate::snapshot::OnMemorySnapshotStore;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Cart {
+    items: u32,
+    archived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CartEvent {
+    Added(u32),
+    Archived(u64),
+}
+
+impl Aggregate for Cart {
+    type Id = u32;
+    type Event = CartEvent;
+
+    fn aggregate_type() -> &'static str {
+        "cart"
+    }
+
+    fn event_type(event: &CartEvent) -> String {
+        match event {
+            CartEvent::Added(_) => "Added".to_string(),
+            CartEvent::Archived(_) => ARCHIVED_EVENT_TYPE.to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &CartEvent) {
+        match event {
+            CartEvent::Added(items) => self.items += items,
+            CartEvent::Archived(_) => self.archived = true,
+        }
+    }
+}
+
+impl Archivable for Cart {
+    fn archived_event(version: u64) -> CartEvent {
+        CartEvent::Archived(version)
+    }
+}
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+fn add(store: &mut OnMemoryEventStore<CartEvent>, stream_id: &str, items: u32, seconds: u64) {
+    store
+        .save(&[
+            Envelope::new(stream_id, 0, "Added", CartEvent::Added(items))
+                .with_occurred_at(at(seconds)),
+        ])
+        .unwrap();
+}
+
+type Archiver = InactivityArchiver<Cart, OnMemorySnapshotStore<Cart>>;
+
+fn archiver() -> Archiver {
+    InactivityArchiver::new(
+        Duration::from_secs(100),
+        |event: &Envelope<CartEvent>| event.occurred_at(),
+        OnMemorySnapshotStore::new(),
+        Vec::new(),
+    )
+}
+
+#[test]
+fn test_archive_inactive_aggregates() {
+    let mut store = OnMemoryEventStore::new();
+    add(&mut store, "cart-1", 2, 0);
+    add(&mut store, "cart-1", 3, 10);
+    add(&mut store, "cart-2", 1, 80);
+    add(&mut store, "order-1", 1, 0);
+
+    let mut archiver = archiver();
+    assert_eq!(archiver.archive_inactive(&mut store, at(150)), Ok(1));
+
+    let events = EventLoader::load(&store, &"cart-1".to_string()).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, ARCHIVED_EVENT_TYPE);
+    assert_eq!(
+        (events[0].version, &events[0].payload),
+        (3, &CartEvent::Archived(2))
+    );
+    let snapshot = archiver.snapshots().load("cart-1").unwrap().unwrap();
+    assert_eq!((snapshot.version, snapshot.state.items), (2, 5));
+    assert_eq!(
+        EventLoader::load(&store, &"cart-2".to_string())
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        EventLoader::load(&store, &"order-1".to_string())
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Archived aggregates are not archived again.
+    assert_eq!(archiver.archive_inactive(&mut store, at(1000)), Ok(1));
+    assert_eq!(
+        EventLoader::load(&store, &"cart-1".to_string())
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_runtime_job() {
+    let mut store = OnMemoryEventStore::new();
+    add(&mut store, "cart-1", 2, 0);
+    let clock = ManualClock::new(at(500));
+    let mut runtime = CruxRuntime::new(store, clock).with_job(archiver());
+
+    let reports = runtime.run_housekeeping();
+    assert_eq!(reports[0].name, "archiving");
+    assert_eq!(reports[0].result, Ok(1));
+}
+
+struct FailingArchive;
+
+impl Archive<CartEvent> for FailingArchive {
+    fn archive(&mut self, _: Vec<Envelope<CartEvent>>) -> Result<(), String> {
+        Err("archive unavailable".to_string())
+    }
+}
+
+#[test]
+fn test_keep_events_when_archive_fails() {
+    let mut store = OnMemoryEventStore::new();
+    add(&mut store, "cart-1", 2, 0);
+    add(&mut store, "cart-1", 3, 10);
+    let mut archiver = InactivityArchiver::<Cart, _>::new(
+        Duration::from_secs(100),
+        |event: &Envelope<CartEvent>| event.occurred_at(),
+        OnMemorySnapshotStore::new(),
+        FailingArchive,
+    );
+
+    assert_eq!(
+        archiver.archive_inactive(&mut store, at(150)),
+        Err("archive unavailable".to_string())
+    );
+    let events = EventLoader::load(&store, &"cart-1".to_string()).unwrap();
+    assert_eq!(
+        events.iter().map(|e| &e.payload).collect::<Vec<_>>(),
+        [&CartEvent::Added(2), &CartEvent::Added(3)]
+    );
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::Clock;
+use crate::command_bus::CommandBus;
+use crate::envelope::Envelope;
+use crate::event_store::EventStore;
+
+/// Types which describe a command in audit records.
+pub trait Auditable {
+    /// Get the name of the command.
+    fn command_name(&self) -> String;
+    /// Get the principal issuing the command.
+    fn principal(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Outcome of an audited command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditOutcome {
+    /// The command succeeded.
+    Succeeded,
+    /// The command failed with the error.
+    Failed(String),
+}
+
+/// Record of a dispatched command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Principal who issued the command.
+    pub principal: Option<String>,
+    /// Name of the command.
+    pub command: String,
+    /// Outcome of the command.
+    pub outcome: AuditOutcome,
+    /// Time the command was dispatched at.
+    pub started_at: SystemTime,
+    /// Time the command took.
+    pub duration: Duration,
+}
+
+/// Types which receive audit records.
+pub trait AuditSink {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Record the command.
+    fn record(&mut self, record: AuditRecord) -> Result<(), Self::Error>;
+}
+
+/// Audit sink appending the records to a dedicated stream of an event store.
+pub struct StreamAuditSink<S> {
+    store: S,
+    stream_id: String,
+}
+
+impl<S> StreamAuditSink<S> {
+    /// Create a sink appending to the stream.
+    pub fn new(store: S, stream_id: impl Into<String>) -> Self {
+        Self {
+            store,
+            stream_id: stream_id.into(),
+        }
+    }
+
+    /// Get the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S> AuditSink for StreamAuditSink<S>
+where
+    S: EventStore<Persistable = Envelope<AuditRecord>>,
+{
+    type Error = S::Error;
+
+    fn record(&mut self, record: AuditRecord) -> Result<(), Self::Error> {
+        let event_type = match record.outcome {
+            AuditOutcome::Succeeded => "CommandSucceeded",
+            AuditOutcome::Failed(_) => "CommandFailed",
+        };
+        self.store
+            .save(&[Envelope::new(self.stream_id.clone(), 0, event_type, record)])
+    }
+}
+
+/// Command bus middleware emitting an audit record for every dispatched command.
+pub struct AuditedBus<B, A, K> {
+    bus: B,
+    sink: A,
+    clock: K,
+}
+
+impl<B, A, K> AuditedBus<B, A, K> {
+    /// Wrap the bus, recording to the sink.
+    pub fn new(bus: B, sink: A, clock: K) -> Self {
+        Self { bus, sink, clock }
+    }
+
+    /// Get the sink.
+    pub fn sink(&self) -> &A {
+        &self.sink
+    }
+}
+
+impl<B, A, K, C> CommandBus<C> for AuditedBus<B, A, K>
+where
+    B: CommandBus<C>,
+    A: AuditSink,
+    K: Clock,
+    C: Auditable,
+{
+    type Response = B::Response;
+    type Error = AuditError<B::Error, A::Error>;
+
+    /// Dispatch the command, then record it. A command whose record cannot be written returns
+    /// an error even though it was executed, so that no command goes unaudited silently.
+    fn dispatch(&mut self, command: C) -> Result<Self::Response, Self::Error> {
+        let principal = command.principal();
+        let name = command.command_name();
+        let started_at = self.clock.now();
+        let result = self.bus.dispatch(command);
+        let duration = self
+            .clock
+            .now()
+            .duration_since(started_at)
+            .unwrap_or_default();
+        let outcome = match &result {
+            Ok(_) => AuditOutcome::Succeeded,
+            Err(e) => AuditOutcome::Failed(e.to_string()),
+        };
+        self.sink
+            .record(AuditRecord {
+                principal,
+                command: name,
+                outcome,
+                started_at,
+                duration,
+            })
+            .map_err(AuditError::Sink)?;
+        result.map_err(AuditError::Bus)
+    }
+}
+
+/// Error returned by the [`AuditedBus`].
+#[derive(Debug)]
+pub enum AuditError<BE, SE> {
+    /// The wrapped bus failed.
+    Bus(BE),
+    /// The audit record could not be written.
+    Sink(SE),
+}
+
+impl<BE: Error, SE: Error> fmt::Display for AuditError<BE, SE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::Bus(e) => write!(f, "bus error: {}", e),
+            AuditError::Sink(e) => write!(f, "audit sink error: {}", e),
+        }
+    }
+}
+
+impl<BE: Error, SE: Error> Error for AuditError<BE, SE> {}
@@ -0,0 +1,87 @@
+use super::*;
+use crate::clock::ManualClock;
+use crate::event_store::{EventLoader, OnMemoryEventStore};
+
+struct AddUser {
+    admin: String,
+    user: String,
+}
+
+impl Auditable for AddUser {
+    fn command_name(&self) -> String {
+        "AddUser".to_string()
+    }
+
+    fn principal(&self) -> Option<String> {
+        Some(self.admin.clone())
+    }
+}
+
+#[derive(Debug)]
+struct MaxUsersReached;
+
+impl fmt::Display for MaxUsersReached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Max users reached")
+    }
+}
+
+impl Error for MaxUsersReached {}
+
+struct OrgBus {
+    clock: ManualClock,
+    users: Vec<String>,
+    max_users: usize,
+}
+
+impl CommandBus<AddUser> for OrgBus {
+    type Response = ();
+    type Error = MaxUsersReached;
+
+    fn dispatch(&mut self, command: AddUser) -> Result<Self::Response, Self::Error> {
+        self.clock.advance(Duration::from_millis(20));
+        if self.users.len() >= self.max_users {
+            return Err(MaxUsersReached);
+        }
+        self.users.push(command.user);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_audited_bus() {
+    let clock = ManualClock::default();
+    let bus = OrgBus {
+        clock: clock.clone(),
+        users: Vec::new(),
+        max_users: 1,
+    };
+    let sink = StreamAuditSink::new(OnMemoryEventStore::new(), "audit");
+    let mut bus = AuditedBus::new(bus, sink, clock);
+
+    let add = |user: &str| AddUser {
+        admin: "admin".to_string(),
+        user: user.to_string(),
+    };
+    bus.dispatch(add("alice")).unwrap();
+    assert!(matches!(bus.dispatch(add("bob")), Err(AuditError::Bus(_))));
+
+    let records = bus.sink().store().load(&"audit".to_string()).unwrap();
+    assert_eq!(
+        records
+            .iter()
+            .map(|r| r.event_type.as_str())
+            .collect::<Vec<_>>(),
+        vec!["CommandSucceeded", "CommandFailed"]
+    );
+    assert_eq!(
+        records[1].payload,
+        AuditRecord {
+            principal: Some("admin".to_string()),
+            command: "AddUser".to_string(),
+            outcome: AuditOutcome::Failed("Max users reached".to_string()),
+            started_at: SystemTime::UNIX_EPOCH + Duration::from_millis(20),
+            duration: Duration::from_millis(20),
+        }
+    );
+}
@@ -1,6 +1,15 @@
 #[cfg(test)]
 mod tests;
 
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::time::SystemTime;
+
+use crate::page::{offset_page, start_offset, Page, PageRequest, Sort};
+
 /// Types which represent a backlog.
 pub trait Backlog {
     /// Associated Type representing the ID of the backlog.
@@ -20,4 +29,248 @@ pub trait Backlog {
     fn resolve(&mut self, event: Self::ResolveEvent) -> &Self::Status;
     /// Get the status of the backlog.
     fn status(&self) -> &Self::Status;
+    /// Get the time by which the backlog should be completed, if any.
+    fn due_at(&self) -> Option<SystemTime> {
+        None
+    }
+    /// Check whether the backlog needs no further work.
+    fn is_completed(&self) -> bool {
+        false
+    }
+}
+
+/// Event recorded for a backlog.
+#[derive(Debug)]
+pub enum BacklogEvent<B: Backlog> {
+    /// The backlog was created.
+    Created(B::CreateEvent),
+    /// The backlog was resolved.
+    Resolved(B::Id, B::ResolveEvent),
+}
+
+impl<B> Clone for BacklogEvent<B>
+where
+    B: Backlog,
+    B::Id: Clone,
+    B::CreateEvent: Clone,
+    B::ResolveEvent: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            BacklogEvent::Created(event) => BacklogEvent::Created(event.clone()),
+            BacklogEvent::Resolved(id, event) => BacklogEvent::Resolved(id.clone(), event.clone()),
+        }
+    }
+}
+
+/// Types which represent a store answering queries over a collection of backlogs.
+pub trait BacklogStore<B: Backlog> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Find the backlogs whose status matches the predicate, in creation order.
+    fn find_by(
+        &self,
+        predicate: &dyn Fn(&B::Status) -> bool,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<B::Id>, Self::Error>;
+
+    /// Find the backlogs in the given status, in creation order.
+    fn find_by_status(
+        &self,
+        status: &B::Status,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<B::Id>, Self::Error>
+    where
+        B::Status: PartialEq,
+    {
+        self.find_by(&|s| s == status, offset, limit)
+    }
+
+    /// Find a page of the backlogs whose status matches the predicate, in creation order.
+    ///
+    /// A request with a sort fails with [`FindPageError::UnsupportedSort`], as backlogs are
+    /// only listed in creation order. A cursor which was not returned by this method yields an
+    /// empty page.
+    fn find_page(
+        &self,
+        predicate: &dyn Fn(&B::Status) -> bool,
+        request: &PageRequest,
+    ) -> Result<Page<B::Id>, FindPageError<Self::Error>> {
+        if let Some(sort) = &request.sort {
+            return Err(FindPageError::UnsupportedSort(sort.clone()));
+        }
+        let offset = match start_offset(request) {
+            Some(offset) => offset,
+            None => return Ok(offset_page(Vec::new(), 0, request.limit)),
+        };
+        let ids = self
+            .find_by(predicate, offset, request.limit.saturating_add(1))
+            .map_err(FindPageError::Store)?;
+        Ok(offset_page(ids, offset, request.limit))
+    }
+
+    /// Find the uncompleted backlogs which are due at `now`, in creation order.
+    fn find_overdue(
+        &self,
+        now: SystemTime,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<B::Id>, Self::Error>;
+}
+
+/// Backlog store keeping the events and the projected backlogs in memory.
+pub struct OnMemoryBacklogStore<B: Backlog> {
+    events: Vec<BacklogEvent<B>>,
+    order: Vec<B::Id>,
+    backlogs: HashMap<B::Id, B>,
+}
+
+impl<B> OnMemoryBacklogStore<B>
+where
+    B: Backlog,
+    B::Id: Clone + Eq + Hash,
+    B::CreateEvent: Clone,
+    B::ResolveEvent: Clone,
+{
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            order: Vec::new(),
+            backlogs: HashMap::new(),
+        }
+    }
+
+    /// Rebuild a store by replaying the events.
+    pub fn from_events(events: impl IntoIterator<Item = BacklogEvent<B>>) -> Self {
+        let mut store = Self::new();
+        for event in events {
+            store.apply(event);
+        }
+        store
+    }
+
+    /// Record the creation of a backlog.
+    pub fn create(&mut self, event: B::CreateEvent) -> B::Id {
+        self.apply(BacklogEvent::Created(event))
+    }
+
+    /// Record the resolution of a backlog. Returns `None` if the backlog does not exist.
+    pub fn resolve(&mut self, id: &B::Id, event: B::ResolveEvent) -> Option<&B::Status> {
+        if !self.backlogs.contains_key(id) {
+            return None;
+        }
+        self.apply(BacklogEvent::Resolved(id.clone(), event));
+        self.backlogs.get(id).map(|b| b.status())
+    }
+
+    /// Get the backlog.
+    pub fn get(&self, id: &B::Id) -> Option<&B> {
+        self.backlogs.get(id)
+    }
+
+    /// Get the recorded events.
+    pub fn events(&self) -> &[BacklogEvent<B>] {
+        &self.events
+    }
+
+    fn apply(&mut self, event: BacklogEvent<B>) -> B::Id {
+        let id = match &event {
+            BacklogEvent::Created(create) => {
+                let backlog = B::create(create.clone());
+                let id = backlog.id();
+                if self.backlogs.insert(id.clone(), backlog).is_none() {
+                    self.order.push(id.clone());
+                }
+                id
+            }
+            BacklogEvent::Resolved(id, resolve) => {
+                if let Some(backlog) = self.backlogs.get_mut(id) {
+                    backlog.resolve(resolve.clone());
+                }
+                id.clone()
+            }
+        };
+        self.events.push(event);
+        id
+    }
+
+    fn select(&self, predicate: impl Fn(&B) -> bool, offset: usize, limit: usize) -> Vec<B::Id> {
+        self.order
+            .iter()
+            .filter(|id| predicate(&self.backlogs[*id]))
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+impl<B> Default for OnMemoryBacklogStore<B>
+where
+    B: Backlog,
+    B::Id: Clone + Eq + Hash,
+    B::CreateEvent: Clone,
+    B::ResolveEvent: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
+
+impl<B> BacklogStore<B> for OnMemoryBacklogStore<B>
+where
+    B: Backlog,
+    B::Id: Clone + Eq + Hash,
+    B::CreateEvent: Clone,
+    B::ResolveEvent: Clone,
+{
+    type Error = Infallible;
+
+    fn find_by(
+        &self,
+        predicate: &dyn Fn(&B::Status) -> bool,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<B::Id>, Self::Error> {
+        Ok(self.select(|b| predicate(b.status()), offset, limit))
+    }
+
+    fn find_overdue(
+        &self,
+        now: SystemTime,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<B::Id>, Self::Error> {
+        Ok(self.select(
+            |b| !b.is_completed() && b.due_at().is_some_and(|due| due <= now),
+            offset,
+            limit,
+        ))
+    }
+}
+
+/// Error returned by [`BacklogStore::find_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindPageError<E> {
+    /// The store failed.
+    Store(E),
+    /// The request has a sort, while backlogs are only listed in creation order.
+    UnsupportedSort(Sort),
+}
+
+impl<E: Error> fmt::Display for FindPageError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FindPageError::Store(e) => write!(f, "store error: {}", e),
+            FindPageError::UnsupportedSort(sort) => {
+                write!(f, "backlogs cannot be sorted by {}", sort.field)
+            }
+        }
+    }
+}
+
+impl<E: Error> Error for FindPageError<E> {}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::page::{PageRequest, SortOrder};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct OrderId(String);
@@ -32,6 +33,7 @@ struct OrderResolveData {
     action: OrderAction,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 struct OrderResolveEvent {
     id: OrderId,
@@ -96,3 +98,126 @@ fn test_order_backlog() {
     let status = order.resolve(resolve_event);
     assert_eq!(*status, OrderStatus::Delivered);
 }
+
+#[derive(Debug, Clone)]
+struct Task {
+    id: u32,
+    status: TaskStatus,
+    due_at: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TaskStatus {
+    Pending,
+    Done,
+}
+
+#[derive(Debug, Clone)]
+struct CreateTaskEvent {
+    id: u32,
+    due_at: SystemTime,
+}
+
+#[derive(Debug, Clone)]
+struct CompleteTaskEvent;
+
+impl Backlog for Task {
+    type Id = u32;
+    type Status = TaskStatus;
+    type CreateEvent = CreateTaskEvent;
+    type ResolveEvent = CompleteTaskEvent;
+
+    fn id(&self) -> Self::Id {
+        self.id
+    }
+
+    fn create(event: Self::CreateEvent) -> Self {
+        Task {
+            id: event.id,
+            status: TaskStatus::Pending,
+            due_at: event.due_at,
+        }
+    }
+
+    fn resolve(&mut self, _event: Self::ResolveEvent) -> &Self::Status {
+        self.status = TaskStatus::Done;
+        &self.status
+    }
+
+    fn status(&self) -> &Self::Status {
+        &self.status
+    }
+
+    fn due_at(&self) -> Option<SystemTime> {
+        Some(self.due_at)
+    }
+
+    fn is_completed(&self) -> bool {
+        self.status == TaskStatus::Done
+    }
+}
+
+#[test]
+fn test_backlog_store_queries() {
+    use std::time::Duration;
+
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+    let mut store = OnMemoryBacklogStore::<Task>::new();
+    for id in 1..=4 {
+        store.create(CreateTaskEvent {
+            id,
+            due_at: now + Duration::from_secs(10) * id - Duration::from_secs(25),
+        });
+    }
+    assert_eq!(
+        store.resolve(&1, CompleteTaskEvent),
+        Some(&TaskStatus::Done)
+    );
+    assert_eq!(store.resolve(&9, CompleteTaskEvent), None);
+
+    assert_eq!(
+        store.find_by_status(&TaskStatus::Pending, 0, 10).unwrap(),
+        vec![2, 3, 4]
+    );
+    assert_eq!(
+        store.find_by_status(&TaskStatus::Pending, 1, 1).unwrap(),
+        vec![3]
+    );
+    assert_eq!(
+        store.find_by_status(&TaskStatus::Done, 0, 10).unwrap(),
+        vec![1]
+    );
+    // Task 1 is due but completed, task 3 and 4 are not due yet.
+    assert_eq!(store.find_overdue(now, 0, 10).unwrap(), vec![2]);
+
+    let pending = |s: &TaskStatus| *s == TaskStatus::Pending;
+    let request = PageRequest::first(2);
+    let page = store.find_page(&pending, &request).unwrap();
+    assert_eq!(page.items, vec![2, 3]);
+    let page = store
+        .find_page(&pending, &request.next(&page).unwrap())
+        .unwrap();
+    assert_eq!(page.items, vec![4]);
+    assert!(!page.has_more());
+
+    // Sorted requests are rejected rather than served in creation order.
+    let sorted = PageRequest::first(2).sorted_by("due_at", SortOrder::Descending);
+    assert_eq!(
+        store.find_page(&pending, &sorted),
+        Err(FindPageError::UnsupportedSort(sorted.sort.clone().unwrap()))
+    );
+}
+
+#[test]
+fn test_backlog_store_from_events() {
+    let due_at = SystemTime::UNIX_EPOCH;
+    let mut store = OnMemoryBacklogStore::<Task>::new();
+    store.create(CreateTaskEvent { id: 1, due_at });
+    store.create(CreateTaskEvent { id: 2, due_at });
+    store.resolve(&2, CompleteTaskEvent);
+
+    let rebuilt = OnMemoryBacklogStore::<Task>::from_events(store.events().to_vec());
+    assert_eq!(rebuilt.get(&1).unwrap().status, TaskStatus::Pending);
+    assert_eq!(rebuilt.get(&2).unwrap().status, TaskStatus::Done);
+    assert_eq!(rebuilt.find_overdue(due_at, 0, 10).unwrap(), vec![1]);
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+use crate::broker::FlowControl;
+use crate::query::ResponseStream;
+
+/// Consumer of a subscription buffering at most `capacity` messages.
+///
+/// Once the buffer is full, the subscription is paused instead of buffering further, and it
+/// is resumed once the buffer is drained down to the resume threshold.
+///
+/// The subscription is polled rather than notifying of new messages, so when consumed as a
+/// [`ResponseStream`] with no message available, the task is woken again after a backoff,
+/// doubling while the subscription stays empty.
+pub struct BoundedConsumer<S, M> {
+    subscription: S,
+    buffer: VecDeque<M>,
+    capacity: usize,
+    resume_at: usize,
+    paused: bool,
+    pauses: usize,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+    wake_scheduled: Arc<AtomicBool>,
+}
+
+impl<S: FlowControl<M>, M> BoundedConsumer<S, M> {
+    /// Consume the subscription, resuming it once the buffer is half drained.
+    pub fn new(subscription: S, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            subscription,
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            resume_at: capacity / 2,
+            paused: false,
+            pauses: 0,
+            min_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+            backoff: Duration::from_millis(1),
+            wake_scheduled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Wait between `min` and `max` before polling an empty subscription again when consumed
+    /// as a [`ResponseStream`].
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        self.backoff = min;
+        self
+    }
+
+    /// Resume the subscription once at most `resume_at` messages are buffered.
+    pub fn with_resume_at(mut self, resume_at: usize) -> Self {
+        self.resume_at = resume_at.min(self.capacity - 1);
+        self
+    }
+
+    /// Get the number of buffered messages.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Check whether the subscription is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Get the number of times the subscription was paused.
+    pub fn pauses(&self) -> usize {
+        self.pauses
+    }
+
+    /// Receive messages until the buffer is full or none is available, returning the number
+    /// of messages received.
+    pub fn fill(&mut self) -> Result<usize, S::Error> {
+        let mut received = 0;
+        while !self.paused && self.buffer.len() < self.capacity {
+            match self.subscription.poll()? {
+                Some(message) => {
+                    self.buffer.push_back(message);
+                    received += 1;
+                }
+                None => break,
+            }
+        }
+        if !self.paused && self.buffer.len() >= self.capacity {
+            self.subscription.pause()?;
+            self.paused = true;
+            self.pauses += 1;
+        }
+        Ok(received)
+    }
+
+    /// Take the next message, receiving more when the buffer is empty.
+    pub fn next_message(&mut self) -> Result<Option<M>, S::Error> {
+        if self.buffer.is_empty() {
+            self.fill()?;
+        }
+        let message = self.buffer.pop_front();
+        if self.paused && self.buffer.len() <= self.resume_at {
+            self.subscription.resume()?;
+            self.paused = false;
+        }
+        Ok(message)
+    }
+}
+
+impl<S: FlowControl<M> + Unpin, M: Unpin> ResponseStream for BoundedConsumer<S, M> {
+    type Item = Result<M, S::Error>;
+
+    /// Yield the next message, staying pending while none is available and waking the task
+    /// after the backoff.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.next_message() {
+            Ok(Some(message)) => {
+                this.backoff = this.min_backoff;
+                Poll::Ready(Some(Ok(message)))
+            }
+            Ok(None) => {
+                if !this.wake_scheduled.swap(true, Ordering::AcqRel) {
+                    let backoff = this.backoff;
+                    let scheduled = this.wake_scheduled.clone();
+                    let waker = cx.waker().clone();
+                    thread::spawn(move || {
+                        thread::sleep(backoff);
+                        scheduled.store(false, Ordering::Release);
+                        waker.wake();
+                    });
+                    this.backoff = this.backoff.saturating_mul(2).min(this.max_backoff);
+                }
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Some(Err(e))),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::future::Future;
+use std::task::Waker;
+
+use super::*;
+use crate::broker::{OnMemoryBroker, Publisher};
+
+fn broker(messages: usize) -> OnMemoryBroker<usize> {
+    let mut broker = OnMemoryBroker::new();
+    for i in 0..messages {
+        broker.publish(&i).unwrap();
+    }
+    broker
+}
+
+#[test]
+fn test_pause_and_resume() {
+    let mut consumer = BoundedConsumer::new(broker(10), 4).with_resume_at(1);
+    assert_eq!(consumer.fill().unwrap(), 4);
+    assert!(consumer.is_paused());
+    // Paused: nothing more is buffered.
+    assert_eq!(consumer.fill().unwrap(), 0);
+    assert_eq!(consumer.buffered(), 4);
+
+    assert_eq!(consumer.next_message().unwrap(), Some(0));
+    assert_eq!(consumer.next_message().unwrap(), Some(1));
+    assert!(consumer.is_paused());
+    assert_eq!(consumer.next_message().unwrap(), Some(2));
+    assert!(!consumer.is_paused());
+
+    let rest = std::iter::from_fn(|| consumer.next_message().unwrap()).collect::<Vec<_>>();
+    assert_eq!(rest, [3, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(consumer.pauses(), 2);
+    assert_eq!(consumer.buffered(), 0);
+}
+
+#[test]
+fn test_consume_asynchronously() {
+    let mut consumer = BoundedConsumer::new(broker(3), 2);
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut received = Vec::new();
+    loop {
+        match std::pin::pin!(consumer.next()).poll(&mut cx) {
+            Poll::Ready(Some(message)) => received.push(message.unwrap()),
+            Poll::Ready(None) => unreachable!(),
+            Poll::Pending => break,
+        }
+    }
+    assert_eq!(received, [0, 1, 2]);
+}
+
+struct CountingWaker(std::sync::atomic::AtomicUsize);
+
+impl std::task::Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_wake_after_backoff_when_empty() {
+    let mut consumer = BoundedConsumer::new(broker(0), 2)
+        .with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+    let counter = Arc::new(CountingWaker(Default::default()));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    assert!(std::pin::pin!(consumer.next()).poll(&mut cx).is_pending());
+    // Polling again before the backoff elapsed schedules no further wake-up.
+    assert!(std::pin::pin!(consumer.next()).poll(&mut cx).is_pending());
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,274 @@
+#[cfg(test)]
+mod tests;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::command_bus::CommandBus;
+use crate::envelope::Envelope;
+use crate::interceptor::Interceptor;
+
+/// Metadata key carrying the W3C `baggage` header.
+pub const BAGGAGE_METADATA_KEY: &str = "baggage";
+
+/// OpenTelemetry baggage: entries of the originating context, such as the tenant or the
+/// experiment flags, which downstream processing should honor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baggage {
+    entries: BTreeMap<String, String>,
+}
+
+impl Baggage {
+    /// Create empty baggage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the entry.
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.entries.insert(key.into(), value.into());
+        self
+    }
+
+    /// Get the value of the entry.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Get the entries, sorted by key.
+    pub fn entries(&self) -> &BTreeMap<String, String> {
+        &self.entries
+    }
+
+    /// Check whether there is no entry.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Parse a `baggage` header. The properties of the entries are dropped.
+    pub fn parse(header: &str) -> Result<Self, BaggageError> {
+        let invalid = || BaggageError::Invalid(header.to_string());
+        let mut entries = BTreeMap::new();
+        for member in header.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+            let pair = member.split(';').next().unwrap_or_default();
+            let (key, value) = pair.split_once('=').ok_or_else(invalid)?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(invalid());
+            }
+            let value = percent_decode(value.trim()).ok_or_else(invalid)?;
+            entries.insert(key.to_string(), value);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Format the baggage as a `baggage` header.
+    pub fn header(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'!' | b'#'..=b'+' | b'-'..=b':' | b'<'..=b'[' | b']'..=b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Types which carry baggage, such as envelopes through their metadata.
+pub trait BaggageCarrier {
+    /// Extract the baggage, if any valid one is carried.
+    fn baggage(&self) -> Option<Baggage>;
+    /// Inject the baggage.
+    fn set_baggage(&mut self, baggage: &Baggage);
+}
+
+impl<E> BaggageCarrier for Envelope<E> {
+    fn baggage(&self) -> Option<Baggage> {
+        Baggage::parse(self.metadata.get(BAGGAGE_METADATA_KEY)?).ok()
+    }
+
+    fn set_baggage(&mut self, baggage: &Baggage) {
+        self.metadata
+            .insert(BAGGAGE_METADATA_KEY.to_string(), baggage.header());
+    }
+}
+
+thread_local! {
+    /// Baggage of the operations in progress on the thread, by scope.
+    static CURRENT: RefCell<HashMap<usize, Baggage>> = RefCell::new(HashMap::new());
+}
+
+/// Baggage of the operation in progress, shared between the command bus setting it and the
+/// components reading it, such as the [`BaggageInterceptor`].
+///
+/// The baggage is kept per thread, so operations running concurrently on other threads with
+/// clones of the scope do not see it.
+#[derive(Debug, Clone, Default)]
+pub struct BaggageScope {
+    id: Arc<()>,
+}
+
+impl BaggageScope {
+    /// Create a scope without baggage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the baggage of the operation in progress on the thread.
+    pub fn current(&self) -> Option<Baggage> {
+        CURRENT.with(|current| current.borrow().get(&self.key()).cloned())
+    }
+
+    /// Run the operation with the baggage, e.g. a consumer handling an event with the baggage
+    /// it carries. The previous baggage is restored afterwards, even if the operation panics.
+    pub fn in_scope<T>(&self, baggage: Baggage, operation: impl FnOnce() -> T) -> T {
+        let key = self.key();
+        let previous = CURRENT.with(|current| current.borrow_mut().insert(key, baggage));
+        let _restore = RestoreBaggage { key, previous };
+        operation()
+    }
+
+    fn key(&self) -> usize {
+        Arc::as_ptr(&self.id) as usize
+    }
+}
+
+/// Guard restoring the baggage a scope had before an operation.
+struct RestoreBaggage {
+    key: usize,
+    previous: Option<Baggage>,
+}
+
+impl Drop for RestoreBaggage {
+    fn drop(&mut self) {
+        // The thread-local is gone if the thread is exiting, and its baggage with it.
+        let _ = CURRENT.try_with(|current| {
+            let mut current = current.borrow_mut();
+            match self.previous.take() {
+                Some(previous) => current.insert(self.key, previous),
+                None => current.remove(&self.key),
+            }
+        });
+    }
+}
+
+/// A command together with the baggage of the context it was issued in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contextual<C> {
+    /// The command.
+    pub command: C,
+    /// The baggage of the originating context.
+    pub baggage: Baggage,
+}
+
+impl<C> Contextual<C> {
+    /// Issue the command with the baggage.
+    pub fn new(command: C, baggage: Baggage) -> Self {
+        Self { command, baggage }
+    }
+}
+
+/// Command bus dispatching commands within the scope of their baggage.
+pub struct BaggageCommandBus<B> {
+    inner: B,
+    scope: BaggageScope,
+}
+
+impl<B> BaggageCommandBus<B> {
+    /// Dispatch the commands to the bus within the scope.
+    pub fn new(inner: B, scope: BaggageScope) -> Self {
+        Self { inner, scope }
+    }
+
+    /// Get the wrapped bus.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<B: CommandBus<C>, C> CommandBus<Contextual<C>> for BaggageCommandBus<B> {
+    type Response = B::Response;
+    type Error = B::Error;
+
+    fn dispatch(&mut self, command: Contextual<C>) -> Result<Self::Response, Self::Error> {
+        let inner = &mut self.inner;
+        self.scope
+            .in_scope(command.baggage, || inner.dispatch(command.command))
+    }
+}
+
+/// Interceptor adding the baggage of the scope to the appended events, so that it reaches
+/// their consumers. Events already carrying baggage keep it.
+pub struct BaggageInterceptor {
+    scope: BaggageScope,
+}
+
+impl BaggageInterceptor {
+    /// Propagate the baggage of the scope.
+    pub fn new(scope: BaggageScope) -> Self {
+        Self { scope }
+    }
+}
+
+impl<E> Interceptor<Envelope<E>> for BaggageInterceptor {
+    fn before_append(&self, events: &mut Vec<Envelope<E>>) -> Result<(), String> {
+        let baggage = match self.scope.current() {
+            Some(baggage) if !baggage.is_empty() => baggage,
+            _ => return Ok(()),
+        };
+        for event in events.iter_mut() {
+            if !event.metadata.contains_key(BAGGAGE_METADATA_KEY) {
+                event.set_baggage(&baggage);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when parsing baggage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BaggageError {
+    /// The `baggage` header is malformed.
+    Invalid(String),
+}
+
+impl fmt::Display for BaggageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaggageError::Invalid(header) => write!(f, "invalid baggage: {}", header),
+        }
+    }
+}
+
+impl Error for BaggageError {}
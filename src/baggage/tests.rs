@@ -0,0 +1,94 @@
+use std::convert::Infallible;
+
+use super::*;
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore};
+use crate::interceptor::InterceptedEventStore;
+
+#[test]
+fn test_baggage_header() {
+    let baggage = Baggage::new()
+        .with("tenant", "acme")
+        .with("experiment", "new checkout,v2");
+    assert_eq!(
+        baggage.header(),
+        "experiment=new%20checkout%2Cv2,tenant=acme"
+    );
+    assert_eq!(Baggage::parse(&baggage.header()), Ok(baggage));
+
+    let parsed = Baggage::parse(" tenant = acme ;ttl=60, region=eu ").unwrap();
+    assert_eq!(parsed.get("tenant"), Some("acme"));
+    assert_eq!(parsed.get("region"), Some("eu"));
+    assert_eq!(
+        Baggage::parse("tenant"),
+        Err(BaggageError::Invalid("tenant".to_string()))
+    );
+    assert!(Baggage::parse("tenant=%zz").is_err());
+}
+
+/// Bus recording an event for each command.
+struct SignupBus {
+    store: InterceptedEventStore<OnMemoryEventStore<String>, Envelope<String>>,
+}
+
+impl CommandBus<String> for SignupBus {
+    type Response = ();
+    type Error = Infallible;
+
+    fn dispatch(&mut self, user: String) -> Result<(), Infallible> {
+        let event = Envelope::new(format!("user-{}", user), 0, "SignedUp", user);
+        self.store.save(&[event]).unwrap();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_baggage_propagation() {
+    let scope = BaggageScope::new();
+    let store = InterceptedEventStore::new(OnMemoryEventStore::new())
+        .with(BaggageInterceptor::new(scope.clone()));
+    let mut bus = BaggageCommandBus::new(SignupBus { store }, scope.clone());
+
+    let baggage = Baggage::new().with("tenant", "acme").with("flag", "on");
+    bus.dispatch(Contextual::new("ada".to_string(), baggage.clone()))
+        .unwrap();
+    bus.dispatch(Contextual::new("bob".to_string(), Baggage::new()))
+        .unwrap();
+    assert_eq!(scope.current(), None);
+
+    let store = bus.inner().store.inner();
+    let event = store.load(&"user-ada".to_string()).unwrap().remove(0);
+    assert_eq!(event.baggage(), Some(baggage.clone()));
+    assert_eq!(
+        store.load(&"user-bob".to_string()).unwrap()[0].baggage(),
+        None
+    );
+
+    // The consumer handles the event within its originating context.
+    let tenant = scope.in_scope(event.baggage().unwrap(), || {
+        scope.current().unwrap().get("tenant").map(str::to_string)
+    });
+    assert_eq!(tenant.as_deref(), Some("acme"));
+}
+
+#[test]
+fn test_baggage_scope_per_thread() {
+    let scope = BaggageScope::new();
+    let baggage = Baggage::new().with("tenant", "acme");
+    scope.in_scope(baggage.clone(), || {
+        let other = scope.clone();
+        let seen = std::thread::spawn(move || other.current()).join().unwrap();
+        assert_eq!(seen, None);
+        assert_eq!(BaggageScope::new().current(), None);
+        assert_eq!(scope.current(), Some(baggage.clone()));
+    });
+
+    let outer = Baggage::new().with("tenant", "outer");
+    scope.in_scope(outer.clone(), || {
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            scope.in_scope(baggage.clone(), || panic!("handler failed"))
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(scope.current(), Some(outer.clone()));
+    });
+    assert_eq!(scope.current(), None);
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::{Duration, SystemTime};
+
+use crate::clock::Clock;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+
+/// Thresholds at which buffered events are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Write once this many events are buffered.
+    pub max_events: usize,
+    /// Write once the oldest buffered event has waited this long.
+    pub max_delay: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_events: 100,
+            max_delay: Duration::from_millis(10),
+        }
+    }
+}
+
+/// Event store decorator coalescing the appends of successive commands into batched writes,
+/// for network stores where a round trip costs more than its payload.
+///
+/// Events are written in the order they were saved, so the order within each stream is kept.
+/// Buffered events are not visible to readers until written: call [`flush`](Self::flush)
+/// before reading your own writes.
+///
+/// A successful [`save`](EventStore::save) only means the events are buffered. They are
+/// durable once a flush succeeds, which happens when a threshold is reached, on
+/// [`tick`](Self::tick), on [`flush`](Self::flush), and on drop. A failure to flush on drop
+/// cannot be reported and loses the buffered events, so call [`flush`](Self::flush) before
+/// dropping the store when the outcome matters.
+pub struct BatchingEventStore<S: EventStore, K> {
+    store: S,
+    clock: K,
+    config: BatchConfig,
+    buffer: Vec<S::Persistable>,
+    oldest: Option<SystemTime>,
+}
+
+impl<S: EventStore, K: Clock> BatchingEventStore<S, K> {
+    /// Wrap the store.
+    pub fn new(store: S, clock: K, config: BatchConfig) -> Self {
+        Self {
+            store,
+            clock,
+            config,
+            buffer: Vec::new(),
+            oldest: None,
+        }
+    }
+
+    /// Write the buffered events if the oldest has waited the maximum delay, returning the
+    /// number written. Call it periodically so that a quiet period does not hold events back.
+    pub fn tick(&mut self) -> Result<usize, S::Error> {
+        match self.oldest {
+            Some(oldest) if oldest + self.config.max_delay <= self.clock.now() => self.flush(),
+            _ => Ok(0),
+        }
+    }
+}
+
+impl<S: EventStore, K> BatchingEventStore<S, K> {
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the number of buffered events.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Write the buffered events in a single call, returning the number written. They stay
+    /// buffered if the write fails.
+    pub fn flush(&mut self) -> Result<usize, S::Error> {
+        if self.buffer.is_empty() {
+            return Ok(0);
+        }
+        self.store.save(&self.buffer)?;
+        self.oldest = None;
+        Ok(std::mem::take(&mut self.buffer).len())
+    }
+}
+
+impl<S: EventStore, K> Drop for BatchingEventStore<S, K> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl<S, K> EventStore for BatchingEventStore<S, K>
+where
+    S: EventStore,
+    S::Persistable: Clone,
+    K: Clock,
+{
+    type Persistable = S::Persistable;
+    type Error = S::Error;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.oldest.get_or_insert_with(|| self.clock.now());
+        self.buffer.extend_from_slice(events);
+        if self.buffer.len() >= self.config.max_events {
+            self.flush()?;
+        } else {
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    /// Flush the buffered events, then append directly, since the expected versions can only
+    /// be checked against written events.
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.flush().map_err(AppendError::Store)?;
+        self.store.append_multi(appends)
+    }
+}
+
+impl<S: EventStore + EventLoader, K> EventLoader for BatchingEventStore<S, K> {
+    type StreamId = <S as EventLoader>::StreamId;
+    type Persistable = <S as EventLoader>::Persistable;
+    type Error = <S as EventLoader>::Error;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.store.read_multi(stream_ids)
+    }
+}
@@ -0,0 +1,154 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::envelope::Envelope;
+use crate::event_store::{OnMemoryEventStore, OnMemoryEventStoreError};
+
+/// Store counting the writes made to it.
+#[derive(Default)]
+struct CountingStore {
+    inner: OnMemoryEventStore<u32>,
+    writes: Cell<usize>,
+}
+
+impl EventStore for CountingStore {
+    type Persistable = Envelope<u32>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.writes.set(self.writes.get() + 1);
+        self.inner.save(events)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.writes.set(self.writes.get() + 1);
+        self.inner.append_multi(appends)
+    }
+}
+
+/// Store shared with the test, to inspect it once the batching store is dropped.
+#[derive(Clone, Default)]
+struct SharedStore(Rc<RefCell<OnMemoryEventStore<u32>>>);
+
+impl EventStore for SharedStore {
+    type Persistable = Envelope<u32>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.0.borrow_mut().save(events)
+    }
+}
+
+impl EventLoader for CountingStore {
+    type StreamId = String;
+    type Persistable = Envelope<u32>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner.load(stream_id)
+    }
+}
+
+fn store(clock: &ManualClock) -> BatchingEventStore<CountingStore, ManualClock> {
+    BatchingEventStore::new(
+        CountingStore::default(),
+        clock.clone(),
+        BatchConfig {
+            max_events: 4,
+            max_delay: Duration::from_millis(50),
+        },
+    )
+}
+
+fn event(stream_id: &str, payload: u32) -> Envelope<u32> {
+    Envelope::new(stream_id, 0, "Happened", payload)
+}
+
+#[test]
+fn test_flush_on_size() {
+    let clock = ManualClock::default();
+    let mut store = store(&clock);
+    for i in 0..6 {
+        let stream_id = if i % 2 == 0 { "a-1" } else { "b-1" };
+        store.save(&[event(stream_id, i)]).unwrap();
+    }
+    assert_eq!(store.inner().writes.get(), 1);
+    assert_eq!(store.buffered(), 2);
+    assert_eq!(store.load(&"a-1".to_string()).unwrap().len(), 2);
+
+    assert_eq!(store.flush().unwrap(), 2);
+    assert_eq!(store.flush().unwrap(), 0);
+    assert_eq!(store.inner().writes.get(), 2);
+    let a = store.load(&"a-1".to_string()).unwrap();
+    assert_eq!(
+        a.iter().map(|e| (e.version, e.payload)).collect::<Vec<_>>(),
+        vec![(1, 0), (2, 2), (3, 4)]
+    );
+}
+
+#[test]
+fn test_flush_on_delay() {
+    let clock = ManualClock::default();
+    let mut store = store(&clock);
+    store.save(&[event("a-1", 0)]).unwrap();
+    clock.advance(Duration::from_millis(30));
+    assert_eq!(store.tick().unwrap(), 0);
+    store.save(&[event("a-1", 1)]).unwrap();
+    assert_eq!(store.buffered(), 2);
+
+    // The delay runs from the oldest buffered event.
+    clock.advance(Duration::from_millis(20));
+    assert_eq!(store.tick().unwrap(), 2);
+    assert_eq!(store.inner().writes.get(), 1);
+
+    store.save(&[event("a-1", 2)]).unwrap();
+    clock.advance(Duration::from_millis(60));
+    store.save(&[event("a-1", 3)]).unwrap();
+    assert_eq!(store.buffered(), 0);
+    assert_eq!(store.inner().writes.get(), 2);
+}
+
+#[test]
+fn test_flush_on_drop() {
+    let shared = SharedStore::default();
+    let mut store = BatchingEventStore::new(
+        shared.clone(),
+        ManualClock::default(),
+        BatchConfig::default(),
+    );
+    store.save(&[event("a-1", 0), event("a-1", 1)]).unwrap();
+    assert_eq!(shared.0.borrow().stream_len("a-1"), 0);
+
+    drop(store);
+    assert_eq!(shared.0.borrow().stream_len("a-1"), 2);
+}
+
+#[test]
+fn test_append_multi_after_buffered_events() {
+    let clock = ManualClock::default();
+    let mut store = store(&clock);
+    store.save(&[event("a-1", 0)]).unwrap();
+    store
+        .append_multi(&[(
+            "a-1".to_string(),
+            ExpectedVersion::Exact(1),
+            vec![event("a-1", 1)],
+        )])
+        .unwrap();
+    assert_eq!(store.buffered(), 0);
+    assert_eq!(store.inner().writes.get(), 2);
+    assert_eq!(
+        store
+            .load(&"a-1".to_string())
+            .unwrap()
+            .iter()
+            .map(|e| (e.version, e.payload))
+            .collect::<Vec<_>>(),
+        vec![(1, 0), (2, 1)]
+    );
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, EventStore};
+
+/// Type of the events creating the streams of entities which existed before adopting events.
+pub const MIGRATED_EVENT_TYPE: &str = "Migrated";
+/// Metadata key recording where a migrated entity was captured from, such as its table.
+pub const MIGRATION_SOURCE_METADATA_KEY: &str = "migration_source";
+
+type MapFn<A, R> =
+    Box<dyn Fn(&R) -> Result<(<A as Aggregate>::Id, <A as Aggregate>::Event), String>>;
+
+/// Summary of a [`Bootstrap`] run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BootstrapReport {
+    /// Number of streams created.
+    pub created: usize,
+    /// Number of rows whose stream already existed, e.g. created by a previous run.
+    pub skipped: usize,
+    /// Rows which could not be mapped, as their index and the reason.
+    pub failed: Vec<(usize, String)>,
+}
+
+/// Tool capturing the state of existing CRUD rows into a [`MIGRATED_EVENT_TYPE`] event per
+/// entity, so that a live system can adopt event sourcing without losing its history.
+///
+/// Runs are idempotent: rows whose stream already exists are skipped, so that an interrupted
+/// bootstrap can be run again.
+pub struct Bootstrap<A: Aggregate, R> {
+    source: String,
+    map: MapFn<A, R>,
+    batch_size: usize,
+}
+
+impl<A: Aggregate, R> Bootstrap<A, R> {
+    /// Create a bootstrap of the rows of the source, mapping each row to the ID of its
+    /// aggregate and the event capturing its state.
+    pub fn new(
+        source: impl Into<String>,
+        map: impl Fn(&R) -> Result<(A::Id, A::Event), String> + 'static,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            map: Box::new(map),
+            batch_size: 100,
+        }
+    }
+
+    /// Save the events by batches of the size, 100 by default.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Create the streams of the rows.
+    pub fn run<S, E>(
+        &self,
+        store: &mut S,
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<BootstrapReport, E>
+    where
+        S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>, Error = E>
+            + EventStore<Persistable = Envelope<A::Event>, Error = E>,
+    {
+        let mut report = BootstrapReport::default();
+        let mut seen = HashSet::new();
+        let mut batch = Vec::new();
+        for (index, row) in rows.into_iter().enumerate() {
+            let (id, event) = match (self.map)(&row) {
+                Ok(mapped) => mapped,
+                Err(reason) => {
+                    report.failed.push((index, reason));
+                    continue;
+                }
+            };
+            let stream_id = A::stream_id(&id);
+            if !seen.insert(stream_id.clone()) || !store.load(&stream_id)?.is_empty() {
+                report.skipped += 1;
+                continue;
+            }
+            batch.push(
+                Envelope::new(stream_id, 0, MIGRATED_EVENT_TYPE, event)
+                    .with_metadata(MIGRATION_SOURCE_METADATA_KEY, self.source.clone()),
+            );
+            if batch.len() >= self.batch_size {
+                store.save(&batch)?;
+                report.created += batch.len();
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            store.save(&batch)?;
+            report.created += batch.len();
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,103 @@
+use super::*;
+use crate::event_store::OnMemoryEventStore;
+use crate::repository::Repository;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct User {
+    name: String,
+    active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum UserEvent {
+    Migrated { name: String, active: bool },
+    Deactivated,
+}
+
+impl Aggregate for User {
+    type Id = u32;
+    type Event = UserEvent;
+
+    fn aggregate_type() -> &'static str {
+        "user"
+    }
+
+    fn event_type(event: &UserEvent) -> String {
+        match event {
+            UserEvent::Migrated { .. } => MIGRATED_EVENT_TYPE.to_string(),
+            UserEvent::Deactivated => "Deactivated".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &UserEvent) {
+        match event {
+            UserEvent::Migrated { name, active } => {
+                self.name = name.clone();
+                self.active = *active;
+            }
+            UserEvent::Deactivated => self.active = false,
+        }
+    }
+}
+
+/// Row of the legacy `users` table: ID, name and status.
+type UserRow = (u32, &'static str, &'static str);
+
+fn bootstrap() -> Bootstrap<User, UserRow> {
+    Bootstrap::new("users", |(id, name, status): &UserRow| {
+        let active = match *status {
+            "active" => true,
+            "inactive" => false,
+            other => return Err(format!("unknown status: {}", other)),
+        };
+        Ok((
+            *id,
+            UserEvent::Migrated {
+                name: name.to_string(),
+                active,
+            },
+        ))
+    })
+    .batch_size(2)
+}
+
+#[test]
+fn test_bootstrap_rows() {
+    let rows = [
+        (1, "alice", "active"),
+        (2, "bob", "inactive"),
+        (3, "carol", "banned"),
+        (1, "alice", "active"),
+        (4, "dave", "active"),
+    ];
+    let mut store = OnMemoryEventStore::new();
+
+    let report = bootstrap().run(&mut store, rows).unwrap();
+    assert_eq!(
+        report,
+        BootstrapReport {
+            created: 3,
+            skipped: 1,
+            failed: vec![(2, "unknown status: banned".to_string())],
+        }
+    );
+    let events = store.load(&"user-2".to_string()).unwrap();
+    assert_eq!(events[0].event_type, MIGRATED_EVENT_TYPE);
+    assert_eq!(
+        events[0].metadata.get(MIGRATION_SOURCE_METADATA_KEY),
+        Some(&"users".to_string())
+    );
+
+    // The migrated aggregates are used like any other, and a second run creates nothing.
+    let mut repository = Repository::<User, _>::new(store);
+    repository
+        .execute(&1, |_| vec![UserEvent::Deactivated])
+        .unwrap();
+    let user = &repository.load(&1).unwrap().unwrap().state;
+    assert_eq!((user.name.as_str(), user.active), ("alice", false));
+
+    let mut store = OnMemoryEventStore::new();
+    bootstrap().run(&mut store, rows).unwrap();
+    let report = bootstrap().run(&mut store, rows).unwrap();
+    assert_eq!((report.created, report.skipped), (0, 4));
+}
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerPublisher};
+use crate::clock::Clock;
+use crate::enrich::EnrichingPublisher;
+use crate::envelope::Envelope;
+use crate::event_store::ReadOnlyEventStore;
+use crate::visibility::ExternalPublisher;
+
+/// Types which publish messages to a broker.
+pub trait Publisher<M> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Publish the message.
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error>;
+}
+
+/// Combinators wrapping a publisher in additional behavior.
+pub trait PublisherExt<M>: Publisher<M> + Sized {
+    /// Guard the publisher with a circuit breaker.
+    fn with_circuit_breaker<K: Clock>(
+        self,
+        breaker: CircuitBreaker<K>,
+    ) -> CircuitBreakerPublisher<Self, K> {
+        CircuitBreakerPublisher::new(self, breaker)
+    }
+
+    /// Refuse to publish internal events through the publisher.
+    fn external(self) -> ExternalPublisher<Self> {
+        ExternalPublisher::new(self)
+    }
+
+    /// Enrich the events before publishing them, with the enrichers added to the wrapper.
+    fn enriched<E>(self) -> EnrichingPublisher<Self, E> {
+        EnrichingPublisher::new(self)
+    }
+}
+
+impl<M, P: Publisher<M>> PublisherExt<M> for P {}
+
+/// Types which represent a subscription to a broker.
+pub trait Subscription<M> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Receive the next message, if any is available.
+    fn poll(&mut self) -> Result<Option<M>, Self::Error>;
+}
+
+/// Types which represent a subscription whose consumption can be paused, so that the broker
+/// stops delivering while consumers fall behind.
+pub trait FlowControl<M>: Subscription<M> {
+    /// Stop receiving messages until resumed.
+    fn pause(&mut self) -> Result<(), Self::Error>;
+    /// Receive messages again.
+    fn resume(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Broker keeping the published messages in a memory queue.
+///
+/// The broker is its own subscription: polling pops messages in publication order.
+#[derive(Debug)]
+pub struct OnMemoryBroker<M> {
+    queue: VecDeque<M>,
+    paused: bool,
+}
+
+impl<M> OnMemoryBroker<M> {
+    /// Create an empty broker.
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            paused: false,
+        }
+    }
+
+    /// Get the number of messages waiting to be polled.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check whether no message is waiting to be polled.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl<M> Default for OnMemoryBroker<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Clone> Publisher<M> for OnMemoryBroker<M> {
+    type Error = Infallible;
+
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error> {
+        self.queue.push_back(message.clone());
+        Ok(())
+    }
+}
+
+impl<M> Subscription<M> for OnMemoryBroker<M> {
+    type Error = Infallible;
+
+    fn poll(&mut self) -> Result<Option<M>, Self::Error> {
+        if self.paused {
+            return Ok(None);
+        }
+        Ok(self.queue.pop_front())
+    }
+}
+
+impl<M> FlowControl<M> for OnMemoryBroker<M> {
+    fn pause(&mut self) -> Result<(), Self::Error> {
+        self.paused = true;
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        self.paused = false;
+        Ok(())
+    }
+}
+
+type FilterFn<E> = Box<dyn Fn(&Envelope<E>) -> bool>;
+
+/// Outcome of a [`Republisher`] run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Republished {
+    /// Number of events published again.
+    pub published: usize,
+    /// Position of the last event read, to acknowledge on behalf of the consumer.
+    pub last_position: Option<u64>,
+}
+
+/// Tool re-reading the events after a consumer's last acknowledged position from the store,
+/// and publishing them again, to recover a consumer which lost messages.
+///
+/// Events are published to the broker, or to the consumer directly if it is a publisher
+/// itself. Consumers receiving events they already handled must be idempotent.
+pub struct Republisher<E> {
+    batch_size: usize,
+    filter: Option<FilterFn<E>>,
+}
+
+impl<E> Republisher<E> {
+    /// Create a republisher reading the store by batches of the size.
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            filter: None,
+        }
+    }
+
+    /// Only publish the events matching the filter, such as the categories the consumer
+    /// subscribes to.
+    pub fn filter(mut self, filter: impl Fn(&Envelope<E>) -> bool + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Publish the events after `acknowledged` again, or every event if the consumer never
+    /// acknowledged any, until the head of the store.
+    pub fn republish<S, P>(
+        &self,
+        store: &S,
+        acknowledged: Option<u64>,
+        publisher: &mut P,
+    ) -> Result<Republished, RepublishError<S::Error, P::Error>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<E>>,
+        P: Publisher<Envelope<E>>,
+    {
+        let mut republished = Republished {
+            published: 0,
+            last_position: acknowledged,
+        };
+        let mut from = acknowledged.map_or(0, |position| position + 1);
+        loop {
+            let events = store
+                .read_all(from, self.batch_size)
+                .map_err(RepublishError::Store)?;
+            for event in &events {
+                if self.filter.as_ref().is_none_or(|filter| filter(event)) {
+                    publisher
+                        .publish(event)
+                        .map_err(|e| RepublishError::Publish {
+                            position: event.position,
+                            error: e,
+                        })?;
+                    republished.published += 1;
+                }
+                republished.last_position = Some(event.position);
+                from = event.position + 1;
+            }
+            if events.len() < self.batch_size {
+                return Ok(republished);
+            }
+        }
+    }
+}
+
+/// Error returned by the [`Republisher`].
+#[derive(Debug)]
+pub enum RepublishError<SE, PE> {
+    /// The event store failed.
+    Store(SE),
+    /// The event at the position could not be published; the events before it were.
+    Publish {
+        /// Position of the event.
+        position: u64,
+        /// The error of the publisher.
+        error: PE,
+    },
+}
+
+impl<SE: Error, PE: Error> fmt::Display for RepublishError<SE, PE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepublishError::Store(e) => write!(f, "store error: {}", e),
+            RepublishError::Publish { position, error } => {
+                write!(
+                    f,
+                    "failed to republish the event at {}: {}",
+                    position, error
+                )
+            }
+        }
+    }
+}
+
+impl<SE: Error, PE: Error> Error for RepublishError<SE, PE> {}
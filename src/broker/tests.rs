@@ -0,0 +1,60 @@
+use super::*;
+
+#[test]
+fn test_on_memory_broker() {
+    let mut broker = OnMemoryBroker::new();
+    broker.publish(&"user-added".to_string()).unwrap();
+    broker.publish(&"user-removed".to_string()).unwrap();
+    assert_eq!(broker.len(), 2);
+
+    assert_eq!(broker.poll().unwrap(), Some("user-added".to_string()));
+    assert_eq!(broker.poll().unwrap(), Some("user-removed".to_string()));
+    assert_eq!(broker.poll().unwrap(), None);
+    assert!(broker.is_empty());
+}
+
+#[test]
+fn test_republisher() {
+    use crate::event_store::{EventStore, OnMemoryEventStore};
+
+    let mut store = OnMemoryEventStore::new();
+    for (stream_id, event) in [
+        ("org-1", "UserAdded"),
+        ("billing-1", "Invoiced"),
+        ("org-1", "UserRemoved"),
+        ("org-2", "UserAdded"),
+        ("billing-1", "Paid"),
+    ] {
+        store
+            .save(&[Envelope::new(stream_id, 0, event, event.to_string())])
+            .unwrap();
+    }
+
+    // The consumer acknowledged the first event, then lost the messages after it.
+    let mut broker = OnMemoryBroker::new();
+    let republisher =
+        Republisher::new(2).filter(|e: &Envelope<String>| e.stream_id.starts_with("org-"));
+    let republished = republisher.republish(&store, Some(0), &mut broker).unwrap();
+    assert_eq!(
+        republished,
+        Republished {
+            published: 2,
+            last_position: Some(4)
+        }
+    );
+    let received = std::iter::from_fn(|| broker.poll().unwrap())
+        .map(|e| e.payload)
+        .collect::<Vec<_>>();
+    assert_eq!(received, ["UserRemoved", "UserAdded"]);
+
+    // Up to date: nothing to publish.
+    let republished = republisher.republish(&store, Some(4), &mut broker).unwrap();
+    assert_eq!(
+        (republished.published, republished.last_position),
+        (0, Some(4))
+    );
+    let republished = Republisher::new(10)
+        .republish(&store, None, &mut broker)
+        .unwrap();
+    assert_eq!(republished.published, 5);
+}
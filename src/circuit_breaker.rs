@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::broker::Publisher;
+use crate::clock::Clock;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+
+/// State of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls are rejected until the time.
+    Open { until: SystemTime },
+    /// A probe call goes through: its outcome closes or reopens the circuit.
+    HalfOpen,
+}
+
+type StateChangeHook = Box<dyn Fn(CircuitState, CircuitState)>;
+
+/// Circuit breaker rejecting calls after consecutive failures, to stop a failing backend from
+/// cascading.
+pub struct CircuitBreaker<K> {
+    clock: K,
+    failure_threshold: u32,
+    open_for: Duration,
+    consecutive_failures: u32,
+    state: CircuitState,
+    hooks: Vec<StateChangeHook>,
+}
+
+impl<K: Clock> CircuitBreaker<K> {
+    /// Create a breaker opening after `failure_threshold` consecutive failures, and probing
+    /// the backend again after `open_for`. An `open_for` going past the latest time the clock
+    /// can represent keeps the circuit open until that time.
+    pub fn new(clock: K, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            clock,
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            consecutive_failures: 0,
+            state: CircuitState::Closed,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Register a hook called with the previous and the new state on every state change.
+    pub fn on_state_change(mut self, hook: impl Fn(CircuitState, CircuitState) + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Get the state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Run the call through the breaker.
+    pub fn call<T, E>(&mut self, f: impl FnOnce() -> Result<T, E>) -> Result<T, CircuitError<E>> {
+        if let CircuitState::Open { until } = self.state {
+            if self.clock.now() < until {
+                return Err(CircuitError::Open);
+            }
+            self.transition(CircuitState::HalfOpen);
+        }
+        match f() {
+            Ok(value) => {
+                self.consecutive_failures = 0;
+                self.transition(CircuitState::Closed);
+                Ok(value)
+            }
+            Err(e) => {
+                self.consecutive_failures += 1;
+                if self.state == CircuitState::HalfOpen
+                    || self.consecutive_failures >= self.failure_threshold
+                {
+                    let until = saturating_add(self.clock.now(), self.open_for);
+                    self.transition(CircuitState::Open { until });
+                }
+                Err(CircuitError::Inner(e))
+            }
+        }
+    }
+
+    fn transition(&mut self, state: CircuitState) {
+        let previous = self.state;
+        if previous == state {
+            return;
+        }
+        self.state = state;
+        for hook in &self.hooks {
+            hook(previous, state);
+        }
+    }
+}
+
+/// Add the duration to the time, saturating at the latest time which can be represented.
+fn saturating_add(mut time: SystemTime, duration: Duration) -> SystemTime {
+    let mut remaining = duration;
+    let mut step = duration;
+    while !step.is_zero() {
+        match time.checked_add(step) {
+            Some(later) if step <= remaining => {
+                time = later;
+                remaining -= step;
+            }
+            _ => step /= 2,
+        }
+    }
+    time
+}
+
+/// Event store guarded by a circuit breaker.
+pub struct CircuitBreakerStore<S, K> {
+    store: S,
+    breaker: RefCell<CircuitBreaker<K>>,
+}
+
+impl<S, K: Clock> CircuitBreakerStore<S, K> {
+    /// Guard the store with the breaker.
+    pub fn new(store: S, breaker: CircuitBreaker<K>) -> Self {
+        Self {
+            store,
+            breaker: RefCell::new(breaker),
+        }
+    }
+
+    /// Get the state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        self.breaker.borrow().state()
+    }
+
+    /// Get the guarded store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S: EventStore, K: Clock> EventStore for CircuitBreakerStore<S, K> {
+    type Persistable = S::Persistable;
+    type Error = CircuitError<<S as EventStore>::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let store = &mut self.store;
+        self.breaker.get_mut().call(|| store.save(events))
+    }
+
+    /// Only failures of the store count towards opening the circuit, not version conflicts.
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let store = &mut self.store;
+        let outcome = self
+            .breaker
+            .get_mut()
+            .call(|| match store.append_multi(appends) {
+                Err(AppendError::Store(e)) => Err(e),
+                outcome => Ok(outcome),
+            })
+            .map_err(AppendError::Store)?;
+        outcome.map_err(|e| e.map_store(CircuitError::Inner))
+    }
+}
+
+impl<S: EventLoader, K: Clock> EventLoader for CircuitBreakerStore<S, K> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = CircuitError<<S as EventLoader>::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.breaker
+            .borrow_mut()
+            .call(|| self.store.load(stream_id))
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.breaker
+            .borrow_mut()
+            .call(|| self.store.load_after(stream_id, version))
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.breaker
+            .borrow_mut()
+            .call(|| self.store.read_multi(stream_ids))
+    }
+}
+
+/// Publisher guarded by a circuit breaker.
+pub struct CircuitBreakerPublisher<P, K> {
+    publisher: P,
+    breaker: CircuitBreaker<K>,
+}
+
+impl<P, K: Clock> CircuitBreakerPublisher<P, K> {
+    /// Guard the publisher with the breaker.
+    pub fn new(publisher: P, breaker: CircuitBreaker<K>) -> Self {
+        Self { publisher, breaker }
+    }
+
+    /// Get the state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        self.breaker.state()
+    }
+}
+
+impl<M, P: Publisher<M>, K: Clock> Publisher<M> for CircuitBreakerPublisher<P, K> {
+    type Error = CircuitError<P::Error>;
+
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error> {
+        let publisher = &mut self.publisher;
+        self.breaker.call(|| publisher.publish(message))
+    }
+}
+
+/// Error returned through a circuit breaker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError<E> {
+    /// The circuit is open: the call was not attempted.
+    Open,
+    /// The call failed.
+    Inner(E),
+}
+
+impl<E: Error> fmt::Display for CircuitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Open => write!(f, "circuit is open"),
+            CircuitError::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for CircuitError<E> {}
@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::*;
+use crate::broker::PublisherExt;
+use crate::clock::ManualClock;
+use crate::event_store::{EventStoreExt, ExpectedVersion};
+
+#[derive(Debug)]
+struct Unavailable;
+
+impl fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unavailable")
+    }
+}
+
+impl Error for Unavailable {}
+
+#[derive(Default)]
+struct FlakyStore {
+    failing: bool,
+    calls: usize,
+    events: HashMap<String, Vec<String>>,
+}
+
+impl EventStore for FlakyStore {
+    type Persistable = String;
+    type Error = Unavailable;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.calls += 1;
+        if self.failing {
+            return Err(Unavailable);
+        }
+        self.events
+            .entry("stream".to_string())
+            .or_default()
+            .extend(events.iter().cloned());
+        Ok(())
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.calls += 1;
+        if self.failing {
+            return Err(AppendError::Store(Unavailable));
+        }
+        for (stream_id, expected, _) in appends {
+            let actual = self.events.get(stream_id).map_or(0, Vec::len) as u64;
+            if !expected.matches(actual) {
+                return Err(AppendError::WrongExpectedVersion {
+                    stream_id: stream_id.clone(),
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+        for (stream_id, _, events) in appends {
+            self.events
+                .entry(stream_id.clone())
+                .or_default()
+                .extend(events.iter().cloned());
+        }
+        Ok(())
+    }
+}
+
+impl EventLoader for FlakyStore {
+    type StreamId = String;
+    type Persistable = String;
+    type Error = Unavailable;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        if self.failing {
+            return Err(Unavailable);
+        }
+        Ok(self.events.get(stream_id).cloned().unwrap_or_default())
+    }
+}
+
+struct FailingPublisher;
+
+impl Publisher<String> for FailingPublisher {
+    type Error = Unavailable;
+
+    fn publish(&mut self, _message: &String) -> Result<(), Self::Error> {
+        Err(Unavailable)
+    }
+}
+
+#[test]
+fn test_circuit_breaker_store() {
+    let clock = ManualClock::default();
+    let transitions = Rc::new(RefCell::new(Vec::new()));
+    let recorded = transitions.clone();
+    let breaker = CircuitBreaker::new(clock.clone(), 2, Duration::from_secs(30))
+        .on_state_change(move |from, to| recorded.borrow_mut().push((from, to)));
+    let mut store = FlakyStore {
+        failing: true,
+        ..Default::default()
+    }
+    .with_circuit_breaker(breaker);
+
+    let event = ["e".to_string()];
+    assert!(matches!(store.save(&event), Err(CircuitError::Inner(_))));
+    assert_eq!(store.state(), CircuitState::Closed);
+    assert!(matches!(store.save(&event), Err(CircuitError::Inner(_))));
+    let until = clock.now() + Duration::from_secs(30);
+    assert_eq!(store.state(), CircuitState::Open { until });
+
+    // While open, the store is not called.
+    assert!(matches!(store.save(&event), Err(CircuitError::Open)));
+    assert!(matches!(
+        store.load(&"stream".to_string()),
+        Err(CircuitError::Open)
+    ));
+    assert_eq!(store.inner().calls, 2);
+
+    // The half-open probe fails and reopens the circuit.
+    clock.advance(Duration::from_secs(30));
+    assert!(matches!(store.save(&event), Err(CircuitError::Inner(_))));
+    assert!(matches!(store.state(), CircuitState::Open { .. }));
+
+    assert_eq!(
+        *transitions.borrow(),
+        vec![
+            (CircuitState::Closed, CircuitState::Open { until }),
+            (CircuitState::Open { until }, CircuitState::HalfOpen),
+            (
+                CircuitState::HalfOpen,
+                CircuitState::Open {
+                    until: until + Duration::from_secs(30)
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_circuit_breaker_append_multi() {
+    let clock = ManualClock::default();
+    let breaker = CircuitBreaker::new(clock.clone(), 1, Duration::from_secs(30));
+    let mut store = FlakyStore::default().with_circuit_breaker(breaker);
+    let append = |expected| [("stream".to_string(), expected, vec!["e".to_string()])];
+
+    store
+        .append_multi(&append(ExpectedVersion::NoStream))
+        .unwrap();
+    // A version conflict is the outcome of a healthy store.
+    assert!(matches!(
+        store.append_multi(&append(ExpectedVersion::NoStream)),
+        Err(AppendError::WrongExpectedVersion { actual: 1, .. })
+    ));
+    assert_eq!(store.state(), CircuitState::Closed);
+
+    store.store.failing = true;
+    assert!(matches!(
+        store.append_multi(&append(ExpectedVersion::Exact(1))),
+        Err(AppendError::Store(CircuitError::Inner(Unavailable)))
+    ));
+    assert!(matches!(
+        store.append_multi(&append(ExpectedVersion::Exact(1))),
+        Err(AppendError::Store(CircuitError::Open))
+    ));
+    assert_eq!(store.inner().calls, 3);
+}
+
+#[test]
+fn test_circuit_breaker_closes_after_successful_probe() {
+    let clock = ManualClock::default();
+    let mut breaker = CircuitBreaker::new(clock.clone(), 1, Duration::from_secs(5));
+    assert!(matches!(
+        breaker.call(|| Err::<(), _>(Unavailable)),
+        Err(CircuitError::Inner(Unavailable))
+    ));
+    assert!(matches!(
+        breaker.call(|| Ok::<_, Unavailable>(1)),
+        Err(CircuitError::Open)
+    ));
+
+    clock.advance(Duration::from_secs(5));
+    assert!(matches!(breaker.call(|| Ok::<_, Unavailable>(1)), Ok(1)));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn test_circuit_breaker_publisher() {
+    let clock = ManualClock::default();
+    let mut publisher = FailingPublisher.with_circuit_breaker(CircuitBreaker::new(
+        clock,
+        1,
+        Duration::from_secs(5),
+    ));
+    assert!(matches!(
+        publisher.publish(&"m".to_string()),
+        Err(CircuitError::Inner(Unavailable))
+    ));
+    assert!(matches!(
+        publisher.publish(&"m".to_string()),
+        Err(CircuitError::Open)
+    ));
+}
+
+#[test]
+fn test_circuit_breaker_saturates_open_for() {
+    let clock = ManualClock::default();
+    let mut breaker = CircuitBreaker::new(clock.clone(), 1, Duration::MAX);
+    assert!(matches!(
+        breaker.call(|| Err::<(), _>("down")),
+        Err(CircuitError::Inner("down"))
+    ));
+    let until = match breaker.state() {
+        CircuitState::Open { until } => until,
+        state => panic!("unexpected state: {:?}", state),
+    };
+    assert!(until.checked_add(Duration::from_secs(1)).is_none());
+
+    // The circuit stays open for as long as the clock can tell.
+    clock.advance(Duration::from_secs(100 * 365 * 24 * 60 * 60));
+    assert!(matches!(
+        breaker.call(|| Ok::<_, &str>(())),
+        Err(CircuitError::Open)
+    ));
+}
@@ -0,0 +1,14 @@
+use super::*;
+
+#[test]
+fn test_manual_clock() {
+    let clock = ManualClock::default();
+    let shared = clock.clone();
+    assert_eq!(clock.now(), SystemTime::UNIX_EPOCH);
+
+    shared.advance(Duration::from_secs(5));
+    assert_eq!(clock.now(), SystemTime::UNIX_EPOCH + Duration::from_secs(5));
+
+    clock.set(SystemTime::UNIX_EPOCH);
+    assert_eq!(shared.now(), SystemTime::UNIX_EPOCH);
+}
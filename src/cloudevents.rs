@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// Version of the CloudEvents specification implemented by this module.
+pub const SPEC_VERSION: &str = "1.0";
+
+const HEADER_PREFIX: &str = "ce-";
+const CONTENT_TYPE_HEADER: &str = "content-type";
+
+/// An event wrapped in a CloudEvents v1.0 envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudEvent {
+    /// Identifier of the event, unique per source.
+    pub id: String,
+    /// Context in which the event happened.
+    pub source: String,
+    /// Type of the event.
+    pub event_type: String,
+    /// Subject of the event in the context of the source.
+    pub subject: Option<String>,
+    /// Timestamp of the occurrence in RFC 3339 format.
+    pub time: Option<String>,
+    /// Content type of `data`.
+    pub data_content_type: Option<String>,
+    /// Serialized payload of the event.
+    pub data: Vec<u8>,
+    /// Extension attributes.
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl CloudEvent {
+    /// Create a new CloudEvent with the required attributes.
+    pub fn new(
+        id: impl Into<String>,
+        source: impl Into<String>,
+        event_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            source: source.into(),
+            event_type: event_type.into(),
+            subject: None,
+            time: None,
+            data_content_type: None,
+            data: Vec::new(),
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    /// Encode the event in binary content mode, returning the headers and the body.
+    pub fn to_binary(&self) -> (BTreeMap<String, String>, Vec<u8>) {
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            format!("{}specversion", HEADER_PREFIX),
+            SPEC_VERSION.to_string(),
+        );
+        headers.insert(format!("{}id", HEADER_PREFIX), self.id.clone());
+        headers.insert(format!("{}source", HEADER_PREFIX), self.source.clone());
+        headers.insert(format!("{}type", HEADER_PREFIX), self.event_type.clone());
+        if let Some(subject) = &self.subject {
+            headers.insert(format!("{}subject", HEADER_PREFIX), subject.clone());
+        }
+        if let Some(time) = &self.time {
+            headers.insert(format!("{}time", HEADER_PREFIX), time.clone());
+        }
+        if let Some(content_type) = &self.data_content_type {
+            headers.insert(CONTENT_TYPE_HEADER.to_string(), content_type.clone());
+        }
+        for (name, value) in &self.extensions {
+            headers.insert(format!("{}{}", HEADER_PREFIX, name), value.clone());
+        }
+        (headers, self.data.clone())
+    }
+
+    /// Decode an event received in binary content mode.
+    pub fn from_binary(
+        headers: &BTreeMap<String, String>,
+        data: Vec<u8>,
+    ) -> Result<Self, CloudEventError> {
+        let mut attributes = BTreeMap::new();
+        let mut data_content_type = None;
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            if name == CONTENT_TYPE_HEADER {
+                data_content_type = Some(value.clone());
+            } else if let Some(attribute) = name.strip_prefix(HEADER_PREFIX) {
+                attributes.insert(attribute.to_string(), value.clone());
+            }
+        }
+
+        let spec_version = attributes
+            .remove("specversion")
+            .ok_or(CloudEventError::MissingAttribute("specversion"))?;
+        if spec_version != SPEC_VERSION {
+            return Err(CloudEventError::UnsupportedSpecVersion(spec_version));
+        }
+        let id = attributes
+            .remove("id")
+            .ok_or(CloudEventError::MissingAttribute("id"))?;
+        let source = attributes
+            .remove("source")
+            .ok_or(CloudEventError::MissingAttribute("source"))?;
+        let event_type = attributes
+            .remove("type")
+            .ok_or(CloudEventError::MissingAttribute("type"))?;
+        let subject = attributes.remove("subject");
+        let time = attributes.remove("time");
+        for name in attributes.keys() {
+            if !is_valid_extension_name(name) {
+                return Err(CloudEventError::InvalidExtensionName(name.clone()));
+            }
+        }
+
+        Ok(Self {
+            id,
+            source,
+            event_type,
+            subject,
+            time,
+            data_content_type,
+            data,
+            extensions: attributes,
+        })
+    }
+}
+
+fn is_valid_extension_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+/// Types which can be published as a CloudEvent.
+pub trait ToCloudEvent {
+    /// Wrap the event in a CloudEvent emitted from the given source.
+    fn to_cloud_event(&self, source: &str) -> CloudEvent;
+}
+
+/// Types which can be built from an incoming CloudEvent.
+pub trait TryFromCloudEvent: Sized {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Build the event from the CloudEvent.
+    fn try_from_cloud_event(event: &CloudEvent) -> Result<Self, Self::Error>;
+}
+
+/// Error returned when decoding a CloudEvent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloudEventError {
+    /// A required attribute is missing.
+    MissingAttribute(&'static str),
+    /// The spec version is not supported.
+    UnsupportedSpecVersion(String),
+    /// An extension attribute has an invalid name.
+    InvalidExtensionName(String),
+}
+
+impl fmt::Display for CloudEventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloudEventError::MissingAttribute(name) => {
+                write!(f, "missing required attribute: {}", name)
+            }
+            CloudEventError::UnsupportedSpecVersion(version) => {
+                write!(f, "unsupported spec version: {}", version)
+            }
+            CloudEventError::InvalidExtensionName(name) => {
+                write!(f, "invalid extension attribute name: {}", name)
+            }
+        }
+    }
+}
+
+impl Error for CloudEventError {}
@@ -0,0 +1,91 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+struct UserAdded {
+    user_add_id: String,
+    user_id: String,
+}
+
+impl ToCloudEvent for UserAdded {
+    fn to_cloud_event(&self, source: &str) -> CloudEvent {
+        let mut event = CloudEvent::new(self.user_add_id.clone(), source, "UserAdded");
+        event.subject = Some(self.user_id.clone());
+        event.data_content_type = Some("text/plain".to_string());
+        event.data = self.user_id.clone().into_bytes();
+        event
+    }
+}
+
+#[derive(Debug)]
+struct UserAddedDecodeError;
+
+impl fmt::Display for UserAddedDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UserAddedDecodeError")
+    }
+}
+
+impl Error for UserAddedDecodeError {}
+
+impl TryFromCloudEvent for UserAdded {
+    type Error = UserAddedDecodeError;
+
+    fn try_from_cloud_event(event: &CloudEvent) -> Result<Self, Self::Error> {
+        if event.event_type != "UserAdded" {
+            return Err(UserAddedDecodeError);
+        }
+        Ok(UserAdded {
+            user_add_id: event.id.clone(),
+            user_id: String::from_utf8(event.data.clone()).map_err(|_| UserAddedDecodeError)?,
+        })
+    }
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let event = UserAdded {
+        user_add_id: "user-add-1".to_string(),
+        user_id: "user-1".to_string(),
+    };
+    let mut cloud_event = event.to_cloud_event("/orgs/org-1");
+    cloud_event
+        .extensions
+        .insert("traceparent".to_string(), "00-abc-def-01".to_string());
+
+    let (headers, data) = cloud_event.to_binary();
+    assert_eq!(headers.get("ce-specversion").unwrap(), "1.0");
+    assert_eq!(headers.get("ce-type").unwrap(), "UserAdded");
+    assert_eq!(headers.get("content-type").unwrap(), "text/plain");
+
+    let decoded = CloudEvent::from_binary(&headers, data).unwrap();
+    assert_eq!(decoded, cloud_event);
+    assert_eq!(UserAdded::try_from_cloud_event(&decoded).unwrap(), event);
+}
+
+#[test]
+fn test_from_binary_rejects_invalid_envelopes() {
+    let mut headers = BTreeMap::new();
+    headers.insert("ce-specversion".to_string(), "1.0".to_string());
+    headers.insert("ce-id".to_string(), "1".to_string());
+    headers.insert("ce-type".to_string(), "UserAdded".to_string());
+    assert_eq!(
+        CloudEvent::from_binary(&headers, Vec::new()),
+        Err(CloudEventError::MissingAttribute("source"))
+    );
+
+    headers.insert("ce-source".to_string(), "/orgs".to_string());
+    headers.insert("ce-specversion".to_string(), "0.3".to_string());
+    assert_eq!(
+        CloudEvent::from_binary(&headers, Vec::new()),
+        Err(CloudEventError::UnsupportedSpecVersion("0.3".to_string()))
+    );
+
+    headers.insert("ce-specversion".to_string(), "1.0".to_string());
+    headers.insert("ce-trace_id".to_string(), "x".to_string());
+    assert_eq!(
+        CloudEvent::from_binary(&headers, Vec::new()),
+        Err(CloudEventError::InvalidExtensionName(
+            "trace_id".to_string()
+        ))
+    );
+}
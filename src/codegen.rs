@@ -0,0 +1,393 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::json;
+
+/// A field of a command or an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldModel {
+    /// Name of the field.
+    pub name: String,
+    /// Rust type of the field.
+    pub type_name: String,
+}
+
+/// A command or an event, with its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageModel {
+    /// Name of the command or event.
+    pub name: String,
+    /// Fields of the command or event.
+    pub fields: Vec<FieldModel>,
+}
+
+/// An aggregate with its commands and events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateModel {
+    /// Name of the aggregate type.
+    pub name: String,
+    /// Commands handled by the aggregate.
+    pub commands: Vec<MessageModel>,
+    /// Events of the aggregate.
+    pub events: Vec<MessageModel>,
+}
+
+/// An event model, typically written during event storming, to generate code from.
+///
+/// The model is described one declaration per line, `#` starting a comment:
+///
+/// ```text
+/// aggregate Order
+///   command PlaceOrder(customer: String, total: u64)
+///   event OrderPlaced(customer: String, total: u64)
+///   event OrderShipped
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventModel {
+    /// The aggregates of the model.
+    pub aggregates: Vec<AggregateModel>,
+}
+
+impl EventModel {
+    /// Parse the description of the model.
+    pub fn parse(source: &str) -> Result<Self, CodegenError> {
+        let mut model = EventModel::default();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let syntax = |message: &str| CodegenError::Syntax {
+                line: line_number,
+                message: message.to_string(),
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (keyword, rest) = line
+                .split_once(' ')
+                .ok_or_else(|| syntax("expected a name"))?;
+            let rest = rest.trim();
+            if keyword == "aggregate" {
+                if !is_identifier(rest) {
+                    return Err(syntax("invalid aggregate name"));
+                }
+                model.aggregates.push(AggregateModel {
+                    name: rest.to_string(),
+                    commands: Vec::new(),
+                    events: Vec::new(),
+                });
+                continue;
+            }
+            let aggregate = model
+                .aggregates
+                .last_mut()
+                .ok_or_else(|| syntax("expected an aggregate first"))?;
+            let message = parse_message(rest).map_err(|message| syntax(&message))?;
+            match keyword {
+                "command" => aggregate.commands.push(message),
+                "event" => aggregate.events.push(message),
+                _ => return Err(syntax(&format!("unknown keyword: {}", keyword))),
+            }
+        }
+        Ok(model)
+    }
+
+    /// Generate the Rust source of the model, for a build script to write into `OUT_DIR`.
+    ///
+    /// For every aggregate, it emits the command and event enums, a `{Name}Handlers` trait with
+    /// one method per event for the user to implement on the state of the aggregate, an
+    /// aggregate struct wrapping that state with an
+    /// [`Aggregate`](crate::aggregate::Aggregate) implementation dispatching the events to the
+    /// handlers, and a function registering the event types to an
+    /// [`EventTypeRegistry`](crate::registry::EventTypeRegistry).
+    pub fn generate(&self) -> String {
+        let mut out = String::from("// Generated from the event model. Do not edit.\n");
+        for aggregate in &self.aggregates {
+            out.push('\n');
+            generate_aggregate(&mut out, aggregate);
+        }
+        out
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_message(declaration: &str) -> Result<MessageModel, String> {
+    let (name, fields) = match declaration.split_once('(') {
+        Some((name, fields)) => {
+            let fields = fields
+                .strip_suffix(')')
+                .ok_or_else(|| "expected `)`".to_string())?;
+            (name.trim(), split_fields(fields)?)
+        }
+        None => (declaration, Vec::new()),
+    };
+    if !is_identifier(name) {
+        return Err(format!("invalid name: {}", name));
+    }
+    Ok(MessageModel {
+        name: name.to_string(),
+        fields,
+    })
+}
+
+fn split_fields(fields: &str) -> Result<Vec<FieldModel>, String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in fields.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&fields[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&fields[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (name, type_name) = part
+                .split_once(':')
+                .ok_or_else(|| format!("expected `name: Type` in `{}`", part))?;
+            let name = name.trim();
+            if !is_identifier(name) {
+                return Err(format!("invalid field name: {}", name));
+            }
+            Ok(FieldModel {
+                name: name.to_string(),
+                type_name: type_name.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn json_type(type_name: &str) -> &'static str {
+    match type_name {
+        "String" | "&str" | "&'static str" | "char" => "string",
+        "bool" => "boolean",
+        "f32" | "f64" => "number",
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" => "integer",
+        t if t.starts_with("Vec<") => "array",
+        _ => "object",
+    }
+}
+
+fn json_schema(message: &MessageModel) -> String {
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+    for field in &message.fields {
+        let inner = field
+            .type_name
+            .strip_prefix("Option<")
+            .and_then(|t| t.strip_suffix('>'));
+        if inner.is_none() {
+            required.push(json::escape(&field.name));
+        }
+        properties.push(format!(
+            "{}:{{\"type\":\"{}\"}}",
+            json::escape(&field.name),
+            json_type(inner.unwrap_or(&field.type_name))
+        ));
+    }
+    format!(
+        "{{\"type\":\"object\",\"properties\":{{{}}},\"required\":[{}]}}",
+        properties.join(","),
+        required.join(",")
+    )
+}
+
+fn generate_enum(out: &mut String, name: &str, messages: &[MessageModel]) {
+    out.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+    out.push_str(&format!("pub enum {} {{\n", name));
+    for message in messages {
+        if message.fields.is_empty() {
+            out.push_str(&format!("    {},\n", message.name));
+            continue;
+        }
+        out.push_str(&format!("    {} {{\n", message.name));
+        for field in &message.fields {
+            out.push_str(&format!("        {}: {},\n", field.name, field.type_name));
+        }
+        out.push_str("    },\n");
+    }
+    out.push_str("}\n");
+}
+
+fn generate_aggregate(out: &mut String, aggregate: &AggregateModel) {
+    let name = &aggregate.name;
+    let event_enum = format!("{}Event", name);
+    let handlers = format!("{}Handlers", name);
+    let pattern = |message: &MessageModel| {
+        if message.fields.is_empty() {
+            format!("{}::{}", event_enum, message.name)
+        } else {
+            let fields = message
+                .fields
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>();
+            format!(
+                "{}::{} {{ {} }}",
+                event_enum,
+                message.name,
+                fields.join(", ")
+            )
+        }
+    };
+    // Matching on the dereferenced event also covers an aggregate without events.
+    let scrutinee = if aggregate.events.is_empty() {
+        "*event"
+    } else {
+        "event"
+    };
+
+    generate_enum(out, &format!("{}Command", name), &aggregate.commands);
+    out.push('\n');
+    generate_enum(out, &event_enum, &aggregate.events);
+    out.push('\n');
+
+    out.push_str(&format!(
+        "/// Handlers applying the events of the `{}` aggregate to its state.\n\
+         pub trait {} {{\n",
+        name, handlers
+    ));
+    for event in &aggregate.events {
+        let parameters = event
+            .fields
+            .iter()
+            .map(|f| format!(", {}: &{}", f.name, f.type_name))
+            .collect::<String>();
+        out.push_str(&format!(
+            "    fn on_{}(&mut self{});\n",
+            snake_case(&event.name),
+            parameters
+        ));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!(
+        "/// The `{}` aggregate, applying its events to a state implementing [`{}`].\n\
+         #[derive(Debug, Clone, Default)]\n\
+         pub struct {}<S> {{\n\
+         \x20   pub state: S,\n\
+         }}\n\n",
+        name, handlers, name
+    ));
+
+    out.push_str(&format!(
+        "impl<S: {}> crux_es::aggregate::Aggregate for {}<S> {{\n\
+         \x20   type Id = String;\n\
+         \x20   type Event = {};\n\n\
+         \x20   fn aggregate_type() -> &'static str {{\n\
+         \x20       \"{}\"\n\
+         \x20   }}\n\n\
+         \x20   fn event_type(event: &{}) -> String {{\n\
+         \x20       match {} {{\n",
+        handlers,
+        name,
+        event_enum,
+        snake_case(name),
+        event_enum,
+        scrutinee
+    ));
+    for event in &aggregate.events {
+        out.push_str(&format!(
+            "            {}::{}{} => \"{}\".to_string(),\n",
+            event_enum,
+            event.name,
+            if event.fields.is_empty() {
+                ""
+            } else {
+                " { .. }"
+            },
+            event.name
+        ));
+    }
+    out.push_str(&format!(
+        "        }}\n    }}\n\n    fn apply(&mut self, event: &{}) {{\n        match {} {{\n",
+        event_enum, scrutinee
+    ));
+    for event in &aggregate.events {
+        let arguments = event
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+        out.push_str(&format!(
+            "            {} => self.state.on_{}({}),\n",
+            pattern(event),
+            snake_case(&event.name),
+            arguments.join(", ")
+        ));
+    }
+    out.push_str("        }\n    }\n}\n\n");
+
+    out.push_str(&format!(
+        "pub fn register_{}_events(\n\
+         \x20   {}registry: &mut crux_es::registry::EventTypeRegistry,\n\
+         ) -> Result<(), crux_es::registry::RegistryError> {{\n",
+        snake_case(name),
+        if aggregate.events.is_empty() { "_" } else { "" }
+    ));
+    for event in &aggregate.events {
+        out.push_str(&format!(
+            "    registry.register_descriptor(crux_es::registry::EventTypeDescriptor {{\n\
+             \x20       name: \"{}\".to_string(),\n\
+             \x20       version: 1,\n\
+             \x20       schema: {:?}.to_string(),\n\
+             \x20   }})?;\n",
+            event.name,
+            json_schema(event)
+        ));
+    }
+    out.push_str("    Ok(())\n}\n");
+}
+
+/// Error returned when parsing an event model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    /// The line of the description is invalid.
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Syntax { line, message } => {
+                write!(f, "syntax error at line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl Error for CodegenError {}
@@ -0,0 +1,130 @@
+use super::*;
+
+const MODEL: &str = "
+# Orders of the shop.
+aggregate ShopOrder
+  command PlaceOrder(customer: String, lines: Vec<(String, u32)>)
+  event OrderPlaced(customer: String, note: Option<String>)
+  event OrderShipped
+";
+
+#[test]
+fn test_parse() {
+    let model = EventModel::parse(MODEL).unwrap();
+    let order = &model.aggregates[0];
+    assert_eq!(order.name, "ShopOrder");
+    assert_eq!(
+        order.commands[0].fields[1],
+        FieldModel {
+            name: "lines".to_string(),
+            type_name: "Vec<(String, u32)>".to_string()
+        }
+    );
+    assert_eq!(order.events.len(), 2);
+    assert!(order.events[1].fields.is_empty());
+
+    assert_eq!(
+        EventModel::parse("event Orphan"),
+        Err(CodegenError::Syntax {
+            line: 1,
+            message: "expected an aggregate first".to_string()
+        })
+    );
+    assert_eq!(
+        EventModel::parse("aggregate A\n  event B(x)")
+            .unwrap_err()
+            .to_string(),
+        "syntax error at line 2: expected `name: Type` in `x`"
+    );
+}
+
+#[test]
+fn test_generate() {
+    let source = EventModel::parse(MODEL).unwrap().generate();
+    assert_eq!(
+        source,
+        r#"// Generated from the event model. Do not edit.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShopOrderCommand {
+    PlaceOrder {
+        customer: String,
+        lines: Vec<(String, u32)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShopOrderEvent {
+    OrderPlaced {
+        customer: String,
+        note: Option<String>,
+    },
+    OrderShipped,
+}
+
+/// Handlers applying the events of the `ShopOrder` aggregate to its state.
+pub trait ShopOrderHandlers {
+    fn on_order_placed(&mut self, customer: &String, note: &Option<String>);
+    fn on_order_shipped(&mut self);
+}
+
+/// The `ShopOrder` aggregate, applying its events to a state implementing [`ShopOrderHandlers`].
+#[derive(Debug, Clone, Default)]
+pub struct ShopOrder<S> {
+    pub state: S,
+}
+
+impl<S: ShopOrderHandlers> crux_es::aggregate::Aggregate for ShopOrder<S> {
+    type Id = String;
+    type Event = ShopOrderEvent;
+
+    fn aggregate_type() -> &'static str {
+        "shop_order"
+    }
+
+    fn event_type(event: &ShopOrderEvent) -> String {
+        match event {
+            ShopOrderEvent::OrderPlaced { .. } => "OrderPlaced".to_string(),
+            ShopOrderEvent::OrderShipped => "OrderShipped".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &ShopOrderEvent) {
+        match event {
+            ShopOrderEvent::OrderPlaced { customer, note } => self.state.on_order_placed(customer, note),
+            ShopOrderEvent::OrderShipped => self.state.on_order_shipped(),
+        }
+    }
+}
+
+pub fn register_shop_order_events(
+    registry: &mut crux_es::registry::EventTypeRegistry,
+) -> Result<(), crux_es::registry::RegistryError> {
+    registry.register_descriptor(crux_es::registry::EventTypeDescriptor {
+        name: "OrderPlaced".to_string(),
+        version: 1,
+        schema: "{\"type\":\"object\",\"properties\":{\"customer\":{\"type\":\"string\"},\"note\":{\"type\":\"string\"}},\"required\":[\"customer\"]}".to_string(),
+    })?;
+    registry.register_descriptor(crux_es::registry::EventTypeDescriptor {
+        name: "OrderShipped".to_string(),
+        version: 1,
+        schema: "{\"type\":\"object\",\"properties\":{},\"required\":[]}".to_string(),
+    })?;
+    Ok(())
+}
+"#
+    );
+}
+
+#[test]
+fn test_generate_aggregate_without_events() {
+    let source = EventModel::parse("aggregate Counter\n  command Reset\n")
+        .unwrap()
+        .generate();
+    assert!(source.contains("pub enum CounterEvent {\n}\n"));
+    assert!(source.contains("pub trait CounterHandlers {\n}\n"));
+    assert!(source.contains(
+        "    fn apply(&mut self, event: &CounterEvent) {\n        match *event {\n        }\n"
+    ));
+    assert!(source.contains("    _registry: &mut crux_es::registry::EventTypeRegistry,\n"));
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Types which represent a bus dispatching commands to their handler.
+pub trait CommandBus<Command> {
+    /// Associated Type representing the response type.
+    type Response;
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Dispatch the command.
+    fn dispatch(&mut self, command: Command) -> Result<Self::Response, Self::Error>;
+}
+
+type Request<C, R, E> = (C, Sender<Result<R, E>>);
+
+/// Cloneable handle sending commands to a single consumer loop which owns the bus.
+///
+/// Commands from every clone are dispatched one at a time, in the order they reach the loop,
+/// so the bus itself needs no synchronization. The loop stops once every handle is dropped.
+pub struct CommandBusHandle<C, R, E> {
+    sender: Sender<Request<C, R, E>>,
+}
+
+impl<C, R, E> CommandBusHandle<C, R, E>
+where
+    C: Send + 'static,
+    R: Send + 'static,
+    E: Send + 'static,
+{
+    /// Move the bus to a consumer thread and get a handle on it.
+    pub fn spawn<B>(mut bus: B) -> Self
+    where
+        B: CommandBus<C, Response = R, Error = E> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<Request<C, R, E>>();
+        thread::spawn(move || {
+            for (command, reply) in receiver {
+                let _ = reply.send(bus.dispatch(command));
+            }
+        });
+        Self { sender }
+    }
+
+    /// Send the command to the consumer loop without waiting for the response.
+    pub fn send(&self, command: C) -> Result<Pending<R, E>, CommandBusHandleError<E>> {
+        let (reply, response) = mpsc::channel();
+        self.sender
+            .send((command, reply))
+            .map_err(|_| CommandBusHandleError::Stopped)?;
+        Ok(Pending(response))
+    }
+
+    /// Send the command to the consumer loop and wait for the response.
+    pub fn request(&self, command: C) -> Result<R, CommandBusHandleError<E>> {
+        self.send(command)?.wait()
+    }
+}
+
+impl<C, R, E> Clone for CommandBusHandle<C, R, E> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<C, R, E> CommandBus<C> for CommandBusHandle<C, R, E>
+where
+    C: Send + 'static,
+    R: Send + 'static,
+    E: Error + Send + 'static,
+{
+    type Response = R;
+    type Error = CommandBusHandleError<E>;
+
+    fn dispatch(&mut self, command: C) -> Result<Self::Response, Self::Error> {
+        self.request(command)
+    }
+}
+
+/// Response of a command sent through a [`CommandBusHandle`], not received yet.
+#[derive(Debug)]
+pub struct Pending<R, E>(Receiver<Result<R, E>>);
+
+impl<R, E> Pending<R, E> {
+    /// Wait for the response of the command.
+    pub fn wait(self) -> Result<R, CommandBusHandleError<E>> {
+        self.0
+            .recv()
+            .map_err(|_| CommandBusHandleError::Stopped)?
+            .map_err(CommandBusHandleError::Bus)
+    }
+}
+
+/// Error returned by the [`CommandBusHandle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandBusHandleError<E> {
+    /// The consumer loop stopped before processing the command.
+    Stopped,
+    /// The bus failed to dispatch the command.
+    Bus(E),
+}
+
+impl<E: Error> fmt::Display for CommandBusHandleError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandBusHandleError::Stopped => write!(f, "command bus loop stopped"),
+            CommandBusHandleError::Bus(e) => write!(f, "bus error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for CommandBusHandleError<E> {}
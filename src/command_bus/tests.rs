@@ -0,0 +1,66 @@
+use std::thread;
+
+use super::*;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Overdrawn;
+
+impl fmt::Display for Overdrawn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "overdrawn")
+    }
+}
+
+impl Error for Overdrawn {}
+
+/// Bus keeping a balance, unsynchronized on purpose.
+struct AccountBus(i64);
+
+impl CommandBus<i64> for AccountBus {
+    type Response = i64;
+    type Error = Overdrawn;
+
+    fn dispatch(&mut self, amount: i64) -> Result<Self::Response, Self::Error> {
+        if self.0 + amount < 0 {
+            return Err(Overdrawn);
+        }
+        self.0 += amount;
+        Ok(self.0)
+    }
+}
+
+#[test]
+fn test_request_from_many_tasks() {
+    let handle = CommandBusHandle::spawn(AccountBus(0));
+
+    let tasks = (0..4)
+        .map(|_| {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                for _ in 0..25 {
+                    handle.request(1).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.join().unwrap();
+    }
+
+    assert_eq!(handle.request(0), Ok(100));
+    assert_eq!(
+        handle.request(-101),
+        Err(CommandBusHandleError::Bus(Overdrawn))
+    );
+}
+
+#[test]
+fn test_send_and_dispatch() {
+    let mut handle = CommandBusHandle::spawn(AccountBus(10));
+
+    let first = handle.send(5).unwrap();
+    let second = handle.send(-20).unwrap();
+    assert_eq!(handle.dispatch(-15), Ok(0));
+    assert_eq!(first.wait(), Ok(15));
+    assert_eq!(second.wait(), Err(CommandBusHandleError::Bus(Overdrawn)));
+}
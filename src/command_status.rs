@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::{Clock, SystemClock};
+use crate::command_bus::CommandBus;
+use crate::dedup::ExpiringDedup;
+
+/// Processing status of a command handled asynchronously.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandStatus<E> {
+    /// The command was accepted and waits to be processed.
+    Accepted,
+    /// The command is being processed.
+    Processing,
+    /// The command succeeded, emitting the events.
+    Succeeded(Vec<E>),
+    /// The command failed with the error.
+    Failed(String),
+}
+
+impl<E> CommandStatus<E> {
+    /// Get whether the command finished, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, CommandStatus::Succeeded(_) | CommandStatus::Failed(_))
+    }
+}
+
+/// A command together with the ID clients use to poll its status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedCommand<C> {
+    /// ID of the command.
+    pub id: String,
+    /// The command.
+    pub command: C,
+}
+
+impl<C> TrackedCommand<C> {
+    /// Create a tracked command.
+    pub fn new(id: impl Into<String>, command: C) -> Self {
+        Self {
+            id: id.into(),
+            command,
+        }
+    }
+}
+
+/// Types which represent the read model of command statuses.
+pub trait CommandStatusStore<E> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Set the status of the command.
+    fn set(&mut self, command_id: &str, status: CommandStatus<E>) -> Result<(), Self::Error>;
+    /// Get the status of the command.
+    fn get(&self, command_id: &str) -> Result<Option<CommandStatus<E>>, Self::Error>;
+}
+
+/// Command status store keeping the statuses in memory, with the time the commands finished at.
+#[derive(Debug)]
+pub struct OnMemoryCommandStatusStore<E, K = SystemClock> {
+    statuses: HashMap<String, (CommandStatus<E>, Option<SystemTime>)>,
+    clock: K,
+}
+
+impl<E> OnMemoryCommandStatusStore<E> {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<E, K: Clock> OnMemoryCommandStatusStore<E, K> {
+    /// Create an empty store, timing the commands with the clock.
+    pub fn with_clock(clock: K) -> Self {
+        Self {
+            statuses: HashMap::new(),
+            clock,
+        }
+    }
+}
+
+impl<E> Default for OnMemoryCommandStatusStore<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Clone, K: Clock> CommandStatusStore<E> for OnMemoryCommandStatusStore<E, K> {
+    type Error = Infallible;
+
+    fn set(&mut self, command_id: &str, status: CommandStatus<E>) -> Result<(), Self::Error> {
+        let finished_at = status.is_finished().then(|| self.clock.now());
+        self.statuses
+            .insert(command_id.to_string(), (status, finished_at));
+        Ok(())
+    }
+
+    fn get(&self, command_id: &str) -> Result<Option<CommandStatus<E>>, Self::Error> {
+        Ok(self
+            .statuses
+            .get(command_id)
+            .map(|(status, _)| status.clone()))
+    }
+}
+
+/// Statuses of unfinished commands never expire.
+impl<E, K> ExpiringDedup for OnMemoryCommandStatusStore<E, K> {
+    type Error = Infallible;
+
+    fn sweep(&mut self, now: SystemTime, ttl: Duration) -> Result<usize, Self::Error> {
+        let before = self.statuses.len();
+        self.statuses.retain(|_, (_, finished_at)| {
+            finished_at
+                .and_then(|at| at.checked_add(ttl))
+                .is_none_or(|expiry| expiry > now)
+        });
+        Ok(before - self.statuses.len())
+    }
+}
+
+/// Command bus middleware maintaining the status of tracked commands.
+///
+/// The wrapped bus responds with the events emitted by the command.
+pub struct StatusTrackingBus<B, S> {
+    bus: B,
+    statuses: S,
+}
+
+impl<B, S> StatusTrackingBus<B, S> {
+    /// Wrap the bus, maintaining the statuses in the store.
+    pub fn new(bus: B, statuses: S) -> Self {
+        Self { bus, statuses }
+    }
+
+    /// Record that the command was accepted, before it is queued for processing.
+    pub fn accept<E>(&mut self, command_id: &str) -> Result<(), S::Error>
+    where
+        S: CommandStatusStore<E>,
+    {
+        self.statuses.set(command_id, CommandStatus::Accepted)
+    }
+
+    /// Get the status of the command.
+    pub fn status<E>(&self, command_id: &str) -> Result<Option<CommandStatus<E>>, S::Error>
+    where
+        S: CommandStatusStore<E>,
+    {
+        self.statuses.get(command_id)
+    }
+
+    /// Get the wrapped bus.
+    pub fn inner(&self) -> &B {
+        &self.bus
+    }
+}
+
+impl<B, S, C, E> CommandBus<TrackedCommand<C>> for StatusTrackingBus<B, S>
+where
+    B: CommandBus<C, Response = Vec<E>>,
+    S: CommandStatusStore<E>,
+    E: Clone,
+{
+    type Response = Vec<E>;
+    type Error = CommandStatusError<B::Error, S::Error>;
+
+    fn dispatch(&mut self, tracked: TrackedCommand<C>) -> Result<Self::Response, Self::Error> {
+        self.statuses
+            .set(&tracked.id, CommandStatus::Processing)
+            .map_err(CommandStatusError::Store)?;
+        let result = self.bus.dispatch(tracked.command);
+        let status = match &result {
+            Ok(events) => CommandStatus::Succeeded(events.clone()),
+            Err(e) => CommandStatus::Failed(e.to_string()),
+        };
+        self.statuses
+            .set(&tracked.id, status)
+            .map_err(CommandStatusError::Store)?;
+        result.map_err(CommandStatusError::Bus)
+    }
+}
+
+/// Error returned by the [`StatusTrackingBus`].
+#[derive(Debug)]
+pub enum CommandStatusError<BE, SE> {
+    /// The wrapped bus failed.
+    Bus(BE),
+    /// The status could not be written.
+    Store(SE),
+}
+
+impl<BE: Error, SE: Error> fmt::Display for CommandStatusError<BE, SE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandStatusError::Bus(e) => write!(f, "bus error: {}", e),
+            CommandStatusError::Store(e) => write!(f, "command status store error: {}", e),
+        }
+    }
+}
+
+impl<BE: Error, SE: Error> Error for CommandStatusError<BE, SE> {}
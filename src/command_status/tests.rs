@@ -0,0 +1,108 @@
+use super::*;
+use crate::inbox::{process, Inbox, InboxMessage, MessageTranslator, OnMemoryInbox};
+
+#[derive(Debug, Clone, PartialEq)]
+enum AccountEvent {
+    Opened(String),
+}
+
+struct OpenAccount(String);
+
+#[derive(Debug)]
+struct DuplicateAccount;
+
+impl fmt::Display for DuplicateAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate account")
+    }
+}
+
+impl Error for DuplicateAccount {}
+
+#[derive(Default)]
+struct AccountBus {
+    opened: Vec<String>,
+}
+
+impl CommandBus<OpenAccount> for AccountBus {
+    type Response = Vec<AccountEvent>;
+    type Error = DuplicateAccount;
+
+    fn dispatch(&mut self, command: OpenAccount) -> Result<Self::Response, Self::Error> {
+        if self.opened.contains(&command.0) {
+            return Err(DuplicateAccount);
+        }
+        self.opened.push(command.0.clone());
+        Ok(vec![AccountEvent::Opened(command.0)])
+    }
+}
+
+struct RequestTranslator;
+
+impl MessageTranslator<TrackedCommand<String>> for RequestTranslator {
+    type Command = TrackedCommand<OpenAccount>;
+    type Error = Infallible;
+
+    fn translate(&self, message: &TrackedCommand<String>) -> Result<Self::Command, Self::Error> {
+        Ok(TrackedCommand::new(
+            message.id.clone(),
+            OpenAccount(message.command.clone()),
+        ))
+    }
+}
+
+#[test]
+fn test_status_transitions() {
+    let mut bus = StatusTrackingBus::new(AccountBus::default(), OnMemoryCommandStatusStore::new());
+    bus.accept("cmd-1").unwrap();
+    assert_eq!(bus.status("cmd-1").unwrap(), Some(CommandStatus::Accepted));
+    assert_eq!(bus.status::<AccountEvent>("cmd-2").unwrap(), None);
+
+    bus.dispatch(TrackedCommand::new("cmd-1", OpenAccount("a".to_string())))
+        .unwrap();
+    let status = bus.status("cmd-1").unwrap().unwrap();
+    assert!(status.is_finished());
+    assert_eq!(
+        status,
+        CommandStatus::Succeeded(vec![AccountEvent::Opened("a".to_string())])
+    );
+
+    assert!(bus
+        .dispatch(TrackedCommand::new("cmd-2", OpenAccount("a".to_string())))
+        .is_err());
+    assert_eq!(
+        bus.status("cmd-2").unwrap(),
+        Some(CommandStatus::Failed("duplicate account".to_string()))
+    );
+}
+
+#[test]
+fn test_poll_commands_processed_through_inbox() {
+    let mut inbox = OnMemoryInbox::new();
+    let mut bus = StatusTrackingBus::new(AccountBus::default(), OnMemoryCommandStatusStore::new());
+    for (id, name) in [("cmd-1", "a"), ("cmd-2", "a")] {
+        let request = TrackedCommand::new(id, name.to_string());
+        inbox
+            .receive(InboxMessage {
+                dedup_key: id.to_string(),
+                message: request,
+            })
+            .unwrap();
+        bus.accept(id).unwrap();
+    }
+    assert!(!bus
+        .status::<AccountEvent>("cmd-1")
+        .unwrap()
+        .unwrap()
+        .is_finished());
+
+    process(&mut inbox, &RequestTranslator, &mut bus).unwrap();
+    assert!(matches!(
+        bus.status::<AccountEvent>("cmd-1").unwrap(),
+        Some(CommandStatus::Succeeded(_))
+    ));
+    assert!(matches!(
+        bus.status::<AccountEvent>("cmd-2").unwrap(),
+        Some(CommandStatus::Failed(_))
+    ));
+}
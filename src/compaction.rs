@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::envelope::Envelope;
+use crate::event_store::EventLoader;
+use crate::retention::PrunableEventStore;
+
+type SizeFn<E> = Box<dyn Fn(&Envelope<E>) -> usize>;
+type TimestampFn<E> = Box<dyn Fn(&Envelope<E>) -> Option<SystemTime>>;
+
+/// Statistics of a stream gathered by the [`CompactionAnalyzer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamStats {
+    /// ID of the stream.
+    pub stream_id: String,
+    /// Number of events of the stream.
+    pub events: usize,
+    /// Total size of the events of the stream, in bytes.
+    pub size: usize,
+    /// Number of times the stream was loaded, if reads are tracked.
+    pub reads: Option<u64>,
+    /// Time of the last event of the stream, if known.
+    pub last_event_at: Option<SystemTime>,
+}
+
+/// Compaction recommended for a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionAction {
+    /// Snapshot the stream, which is long to replay.
+    Snapshot,
+    /// Archive the stream, which has been inactive for long.
+    Archive,
+}
+
+/// Stream recommended for compaction, with the priority of the work.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recommendation {
+    /// The recommended compaction.
+    pub action: CompactionAction,
+    /// Priority of the work, higher first: events replayed for snapshots, weighted by reads,
+    /// and bytes reclaimed for archives.
+    pub priority: u64,
+    /// Statistics of the stream.
+    pub stats: StreamStats,
+}
+
+/// Report of a [`CompactionAnalyzer`] scan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// Number of streams scanned.
+    pub streams: usize,
+    /// Number of events scanned.
+    pub events: usize,
+    /// Total size of the events scanned, in bytes.
+    pub size: usize,
+    /// Recommended compactions, by decreasing priority.
+    pub recommendations: Vec<Recommendation>,
+}
+
+impl CompactionReport {
+    /// Get the recommended compactions of the kind, by decreasing priority.
+    pub fn of(&self, action: CompactionAction) -> impl Iterator<Item = &Recommendation> {
+        self.recommendations
+            .iter()
+            .filter(move |r| r.action == action)
+    }
+}
+
+/// Analyzer scanning a store for the streams which would benefit from snapshots or archiving.
+pub struct CompactionAnalyzer<E> {
+    size: SizeFn<E>,
+    snapshot_after: usize,
+    archive: Option<(Duration, TimestampFn<E>)>,
+}
+
+impl<E> CompactionAnalyzer<E> {
+    /// Create an analyzer measuring the size of the events with `size`, recommending snapshots
+    /// of the streams of at least `snapshot_after` events.
+    pub fn new(snapshot_after: usize, size: impl Fn(&Envelope<E>) -> usize + 'static) -> Self {
+        Self {
+            size: Box::new(size),
+            snapshot_after,
+            archive: None,
+        }
+    }
+
+    /// Recommend archiving the streams without events for `inactive_after`, reading the time
+    /// of the events with `timestamp`.
+    pub fn archive_after(
+        mut self,
+        inactive_after: Duration,
+        timestamp: impl Fn(&Envelope<E>) -> Option<SystemTime> + 'static,
+    ) -> Self {
+        self.archive = Some((inactive_after, Box::new(timestamp)));
+        self
+    }
+
+    /// Scan the store, weighting snapshots by the reads tracked by `reads`, if any.
+    pub fn analyze<S>(
+        &self,
+        store: &S,
+        reads: Option<&HashMap<String, u64>>,
+        now: SystemTime,
+    ) -> Result<CompactionReport, S::Error>
+    where
+        S: PrunableEventStore<E> + EventLoader<StreamId = String, Persistable = Envelope<E>>,
+    {
+        let mut report = CompactionReport::default();
+        for stream_id in store.stream_ids() {
+            let events = store.load(&stream_id)?;
+            let stats = StreamStats {
+                events: events.len(),
+                size: events.iter().map(|e| (self.size)(e)).sum(),
+                reads: reads.map(|reads| reads.get(&stream_id).copied().unwrap_or(0)),
+                last_event_at: self
+                    .archive
+                    .as_ref()
+                    .and_then(|(_, timestamp)| events.last().and_then(timestamp)),
+                stream_id,
+            };
+            report.streams += 1;
+            report.events += stats.events;
+            report.size += stats.size;
+
+            let inactive = self
+                .archive
+                .as_ref()
+                .is_some_and(|(after, _)| stats.last_event_at.is_some_and(|at| at + *after <= now));
+            if inactive {
+                report.recommendations.push(Recommendation {
+                    action: CompactionAction::Archive,
+                    priority: stats.size as u64,
+                    stats,
+                });
+            } else if stats.events >= self.snapshot_after.max(1) {
+                report.recommendations.push(Recommendation {
+                    action: CompactionAction::Snapshot,
+                    priority: stats.events as u64 * (1 + stats.reads.unwrap_or(0)),
+                    stats,
+                });
+            }
+        }
+        report.recommendations.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| a.stats.stream_id.cmp(&b.stats.stream_id))
+        });
+        Ok(report)
+    }
+}
+
+/// Event loader counting the loads of each stream, to weight the recommendations of the
+/// [`CompactionAnalyzer`] by read frequency.
+pub struct ReadTrackingLoader<S> {
+    inner: S,
+    reads: RefCell<HashMap<String, u64>>,
+}
+
+impl<S> ReadTrackingLoader<S> {
+    /// Track the reads of the loader.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            reads: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Get the number of loads of each stream since the last reset.
+    pub fn reads(&self) -> HashMap<String, u64> {
+        self.reads.borrow().clone()
+    }
+
+    /// Forget the tracked reads, e.g. to measure over a sliding window.
+    pub fn reset(&self) {
+        self.reads.borrow_mut().clear();
+    }
+
+    /// Get the wrapped loader.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the wrapped loader mutably.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    fn track(&self, stream_id: &str) {
+        *self
+            .reads
+            .borrow_mut()
+            .entry(stream_id.to_string())
+            .or_default() += 1;
+    }
+}
+
+impl<S: EventLoader<StreamId = String>> EventLoader for ReadTrackingLoader<S> {
+    type StreamId = String;
+    type Persistable = S::Persistable;
+    type Error = S::Error;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.track(stream_id);
+        self.inner.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &String,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.track(stream_id);
+        self.inner.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[String],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        for stream_id in stream_ids {
+            self.track(stream_id);
+        }
+        self.inner.read_multi(stream_ids)
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::UNIX_EPOCH;
+
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+fn event(stream_id: &str, at_secs: u64, body: &str) -> Envelope<String> {
+    Envelope::new(stream_id, 0, "Noted", body.to_string()).with_metadata("at", at_secs.to_string())
+}
+
+fn at(event: &Envelope<String>) -> Option<SystemTime> {
+    let secs = event.metadata.get("at")?.parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[test]
+fn test_recommendations() {
+    let mut store = OnMemoryEventStore::new();
+    for i in 0..5 {
+        store.save(&[event("note-long", 100 + i, "x")]).unwrap();
+    }
+    for i in 0..4 {
+        store.save(&[event("note-read", 100 + i, "x")]).unwrap();
+    }
+    store.save(&[event("note-short", 100, "x")]).unwrap();
+    store
+        .save(&[event("note-old", 0, "a large and long forgotten note")])
+        .unwrap();
+    store.save(&[event("note-old2", 10, "small")]).unwrap();
+
+    let loader = ReadTrackingLoader::new(store);
+    for _ in 0..2 {
+        loader.load(&"note-read".to_string()).unwrap();
+    }
+    loader
+        .read_multi(&["note-read".to_string(), "note-long".to_string()])
+        .unwrap();
+    assert_eq!(loader.reads()["note-read"], 3);
+
+    let analyzer = CompactionAnalyzer::new(4, |e: &Envelope<String>| e.payload.len())
+        .archive_after(Duration::from_secs(60), at);
+    let report = analyzer
+        .analyze(
+            loader.inner(),
+            Some(&loader.reads()),
+            UNIX_EPOCH + Duration::from_secs(110),
+        )
+        .unwrap();
+    assert_eq!((report.streams, report.events), (5, 12));
+    assert_eq!(report.size, 10 + 31 + 5);
+
+    let summary = report
+        .recommendations
+        .iter()
+        .map(|r| (r.stats.stream_id.as_str(), r.action, r.priority))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("note-old", CompactionAction::Archive, 31),
+            ("note-read", CompactionAction::Snapshot, 16),
+            ("note-long", CompactionAction::Snapshot, 10),
+            ("note-old2", CompactionAction::Archive, 5),
+        ]
+    );
+    assert_eq!(report.of(CompactionAction::Archive).count(), 2);
+
+    // Without read tracking nor archiving, only long streams are reported.
+    let report = CompactionAnalyzer::new(4, |_: &Envelope<String>| 1)
+        .analyze(loader.inner(), None, UNIX_EPOCH)
+        .unwrap();
+    let streams = report
+        .of(CompactionAction::Snapshot)
+        .map(|r| (r.stats.stream_id.as_str(), r.stats.reads))
+        .collect::<Vec<_>>();
+    assert_eq!(streams, [("note-long", None), ("note-read", None)]);
+}
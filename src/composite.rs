@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::hash::Hash;
+
+use crate::envelope::Envelope;
+use crate::projection::Projection;
+use crate::sharding::category_of;
+
+/// Contribution of an event to a join.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoinInput<K, P, C> {
+    /// The referenced row was created or updated.
+    Parent { key: K, value: P },
+    /// The referenced row was removed.
+    ParentRemoved(K),
+    /// The referencing row was created or updated.
+    Child { id: String, parent: K, value: C },
+    /// The referencing row was removed.
+    ChildRemoved(String),
+    /// The event does not affect the join.
+    Ignore,
+}
+
+/// Types which describe a join maintained by a [`CompositeProjection`], such as users joined
+/// with the name of their org.
+pub trait JoinProjector {
+    /// Associated Type representing the event type.
+    type Event;
+    /// Associated Type representing the key of the referenced rows.
+    type Key: Clone + Eq + Hash;
+    /// Associated Type representing the referenced row.
+    type Parent;
+    /// Associated Type representing the referencing row.
+    type Child;
+    /// Associated Type representing the joined row.
+    type Row;
+
+    /// Get the categories of the streams the join consumes.
+    fn categories(&self) -> Vec<&'static str>;
+    /// Tell how the event affects the join.
+    fn classify(
+        &self,
+        event: &Envelope<Self::Event>,
+    ) -> JoinInput<Self::Key, Self::Parent, Self::Child>;
+    /// Build the joined row.
+    fn join(&self, child: &Self::Child, parent: &Self::Parent) -> Self::Row;
+}
+
+/// Projection consuming events from several categories and maintaining a denormalized join
+/// table.
+///
+/// Streams of different categories are not ordered relative to each other, so a referencing
+/// row may arrive before the row it references: it is buffered until the referenced row
+/// exists, and joined then.
+pub struct CompositeProjection<J: JoinProjector> {
+    projector: J,
+    parents: HashMap<J::Key, J::Parent>,
+    children: BTreeMap<String, (J::Key, J::Child)>,
+    rows: BTreeMap<String, J::Row>,
+}
+
+impl<J: JoinProjector> CompositeProjection<J> {
+    /// Create an empty join table.
+    pub fn new(projector: J) -> Self {
+        Self {
+            projector,
+            parents: HashMap::new(),
+            children: BTreeMap::new(),
+            rows: BTreeMap::new(),
+        }
+    }
+
+    /// Get the joined row.
+    pub fn row(&self, id: &str) -> Option<&J::Row> {
+        self.rows.get(id)
+    }
+
+    /// Get the joined rows, by ID.
+    pub fn rows(&self) -> &BTreeMap<String, J::Row> {
+        &self.rows
+    }
+
+    /// Get the IDs of the rows buffered until the row they reference exists.
+    pub fn pending(&self) -> Vec<&str> {
+        self.children
+            .iter()
+            .filter(|(_, (key, _))| !self.parents.contains_key(key))
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    fn rejoin(&mut self, key: &J::Key) {
+        let parent = self.parents.get(key);
+        for (id, (child_key, child)) in &self.children {
+            if child_key != key {
+                continue;
+            }
+            match parent {
+                Some(parent) => {
+                    self.rows
+                        .insert(id.clone(), self.projector.join(child, parent));
+                }
+                None => {
+                    self.rows.remove(id);
+                }
+            }
+        }
+    }
+}
+
+impl<J: JoinProjector> Projection for CompositeProjection<J> {
+    type Event = J::Event;
+    type Error = Infallible;
+
+    fn apply(&mut self, event: &Envelope<Self::Event>) -> Result<(), Self::Error> {
+        if !self
+            .projector
+            .categories()
+            .contains(&category_of(&event.stream_id))
+        {
+            return Ok(());
+        }
+        match self.projector.classify(event) {
+            JoinInput::Parent { key, value } => {
+                self.parents.insert(key.clone(), value);
+                self.rejoin(&key);
+            }
+            JoinInput::ParentRemoved(key) => {
+                self.parents.remove(&key);
+                self.rejoin(&key);
+            }
+            JoinInput::Child { id, parent, value } => {
+                match self.parents.get(&parent) {
+                    Some(p) => {
+                        self.rows.insert(id.clone(), self.projector.join(&value, p));
+                    }
+                    None => {
+                        self.rows.remove(&id);
+                    }
+                }
+                self.children.insert(id, (parent, value));
+            }
+            JoinInput::ChildRemoved(id) => {
+                self.children.remove(&id);
+                self.rows.remove(&id);
+            }
+            JoinInput::Ignore => {}
+        }
+        Ok(())
+    }
+}
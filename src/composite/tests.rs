@@ -0,0 +1,109 @@
+use super::*;
+
+#[derive(Debug, Clone)]
+enum Event {
+    OrgCreated(String),
+    OrgRenamed(String),
+    UserJoined { name: String, org: String },
+    UserLeft,
+    InvoiceSent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct UserRow {
+    name: String,
+    org_name: String,
+}
+
+struct UsersWithOrg;
+
+impl JoinProjector for UsersWithOrg {
+    type Event = Event;
+    type Key = String;
+    type Parent = String;
+    type Child = String;
+    type Row = UserRow;
+
+    fn categories(&self) -> Vec<&'static str> {
+        vec!["org", "user"]
+    }
+
+    fn classify(&self, event: &Envelope<Event>) -> JoinInput<String, String, String> {
+        match &event.payload {
+            Event::OrgCreated(name) | Event::OrgRenamed(name) => JoinInput::Parent {
+                key: event.stream_id.clone(),
+                value: name.clone(),
+            },
+            Event::UserJoined { name, org } => JoinInput::Child {
+                id: event.stream_id.clone(),
+                parent: org.clone(),
+                value: name.clone(),
+            },
+            Event::UserLeft => JoinInput::ChildRemoved(event.stream_id.clone()),
+            Event::InvoiceSent => JoinInput::Ignore,
+        }
+    }
+
+    fn join(&self, child: &String, parent: &String) -> UserRow {
+        UserRow {
+            name: child.clone(),
+            org_name: parent.clone(),
+        }
+    }
+}
+
+fn event(stream_id: &str, payload: Event) -> Envelope<Event> {
+    Envelope::new(stream_id, 1, "Event", payload)
+}
+
+fn joined(name: &str, org_name: &str) -> UserRow {
+    UserRow {
+        name: name.to_string(),
+        org_name: org_name.to_string(),
+    }
+}
+
+#[test]
+fn test_join_with_out_of_order_arrival() {
+    let mut projection = CompositeProjection::new(UsersWithOrg);
+    let user_joined = |name: &str| Event::UserJoined {
+        name: name.to_string(),
+        org: "org-1".to_string(),
+    };
+
+    // The user arrives before the org it references.
+    projection
+        .apply(&event("user-1", user_joined("alice")))
+        .unwrap();
+    assert_eq!(projection.row("user-1"), None);
+    assert_eq!(projection.pending(), ["user-1"]);
+
+    projection
+        .apply(&event("org-1", Event::OrgCreated("Acme".to_string())))
+        .unwrap();
+    assert_eq!(projection.row("user-1"), Some(&joined("alice", "Acme")));
+    assert!(projection.pending().is_empty());
+
+    projection
+        .apply(&event("user-2", user_joined("bob")))
+        .unwrap();
+    projection
+        .apply(&event("org-1", Event::OrgRenamed("Acme Corp".to_string())))
+        .unwrap();
+    assert_eq!(
+        projection.row("user-1"),
+        Some(&joined("alice", "Acme Corp"))
+    );
+    assert_eq!(projection.row("user-2"), Some(&joined("bob", "Acme Corp")));
+
+    projection.apply(&event("user-1", Event::UserLeft)).unwrap();
+    projection
+        .apply(&event("invoice-1", Event::InvoiceSent))
+        .unwrap();
+    // Events of other categories are not consumed.
+    projection
+        .apply(&event("audit-1", Event::OrgCreated("Other".to_string())))
+        .unwrap();
+    assert_eq!(projection.rows().len(), 1);
+    assert_eq!(projection.row("user-2"), Some(&joined("bob", "Acme Corp")));
+}
@@ -0,0 +1,237 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Publisher;
+use crate::envelope::Envelope;
+use crate::sharding::stable_hash;
+
+/// A message delivered to a member of a consumer group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery<M> {
+    /// Offset of the message in the topic, used to acknowledge it.
+    pub offset: u64,
+    /// Key of the messages delivered in order, such as the stream ID.
+    pub ordering_key: String,
+    /// The message.
+    pub message: M,
+}
+
+/// Types which deliver the messages of a topic to consumer groups, such as Kafka consumer
+/// groups, SQS FIFO message groups or NATS durable consumers.
+///
+/// Every group receives every message, and the members of a group share them. Messages of the
+/// same ordering key are delivered to one member at a time, in order: the next one is only
+/// delivered once the previous one is acknowledged.
+pub trait ConsumerGroup<M> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Add the member to the group, rebalancing the ordering keys across its members.
+    fn join(&mut self, group: &str, member: &str) -> Result<(), Self::Error>;
+    /// Remove the member from the group. Its unacknowledged messages are delivered again to
+    /// the members taking over their ordering keys.
+    fn leave(&mut self, group: &str, member: &str) -> Result<(), Self::Error>;
+    /// Receive the next message for the member of the group, if any is available.
+    fn poll(&mut self, group: &str, member: &str) -> Result<Option<Delivery<M>>, Self::Error>;
+    /// Acknowledge the message delivered to the member of the group.
+    fn ack(&mut self, group: &str, member: &str, offset: u64) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug, Default)]
+struct GroupState {
+    members: Vec<String>,
+    /// Unacknowledged deliveries, by offset, with the member they were delivered to.
+    in_flight: BTreeMap<u64, String>,
+    acked: BTreeSet<u64>,
+    /// Offset below which every message is acknowledged.
+    committed: u64,
+}
+
+impl GroupState {
+    fn owner(&self, ordering_key: &str) -> Option<&str> {
+        if self.members.is_empty() {
+            return None;
+        }
+        let index = stable_hash(ordering_key) % self.members.len() as u64;
+        Some(&self.members[index as usize])
+    }
+
+    fn member(&self, group: &str, member: &str) -> Result<(), ConsumerGroupError> {
+        if self.members.iter().any(|m| m == member) {
+            return Ok(());
+        }
+        Err(ConsumerGroupError::NotMember {
+            group: group.to_string(),
+            member: member.to_string(),
+        })
+    }
+}
+
+type OrderingKeyFn<M> = Box<dyn Fn(&M) -> String>;
+
+/// Topic keeping the published messages in memory, consumed through consumer groups.
+///
+/// Ordering keys are assigned to the members of a group by hashing, so that they move between
+/// members only when the membership changes.
+pub struct OnMemoryTopic<M> {
+    log: Vec<(String, M)>,
+    groups: HashMap<String, GroupState>,
+    ordering_key: OrderingKeyFn<M>,
+}
+
+impl<M> OnMemoryTopic<M> {
+    /// Create an empty topic, reading the ordering key of the messages with `ordering_key`.
+    pub fn new(ordering_key: impl Fn(&M) -> String + 'static) -> Self {
+        Self {
+            log: Vec::new(),
+            groups: HashMap::new(),
+            ordering_key: Box::new(ordering_key),
+        }
+    }
+
+    /// Get the number of published messages.
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    /// Check whether no message was published.
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+    }
+
+    /// Get the offset below which the group acknowledged every message.
+    pub fn committed(&self, group: &str) -> u64 {
+        self.groups.get(group).map_or(0, |g| g.committed)
+    }
+
+    fn group_mut(&mut self, group: &str) -> Result<&mut GroupState, ConsumerGroupError> {
+        self.groups
+            .get_mut(group)
+            .ok_or_else(|| ConsumerGroupError::UnknownGroup(group.to_string()))
+    }
+}
+
+impl<E> OnMemoryTopic<Envelope<E>> {
+    /// Create an empty topic of envelopes, delivered in order per stream.
+    pub fn per_stream() -> Self {
+        Self::new(|envelope: &Envelope<E>| envelope.stream_id.clone())
+    }
+}
+
+impl<M: Clone> Publisher<M> for OnMemoryTopic<M> {
+    type Error = ConsumerGroupError;
+
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error> {
+        let ordering_key = (self.ordering_key)(message);
+        self.log.push((ordering_key, message.clone()));
+        Ok(())
+    }
+}
+
+impl<M: Clone> ConsumerGroup<M> for OnMemoryTopic<M> {
+    type Error = ConsumerGroupError;
+
+    fn join(&mut self, group: &str, member: &str) -> Result<(), Self::Error> {
+        let state = self.groups.entry(group.to_string()).or_default();
+        if !state.members.iter().any(|m| m == member) {
+            state.members.push(member.to_string());
+            state.members.sort();
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self, group: &str, member: &str) -> Result<(), Self::Error> {
+        let state = self.group_mut(group)?;
+        state.member(group, member)?;
+        state.members.retain(|m| m != member);
+        state.in_flight.retain(|_, m| m != member);
+        Ok(())
+    }
+
+    fn poll(&mut self, group: &str, member: &str) -> Result<Option<Delivery<M>>, Self::Error> {
+        let state = self
+            .groups
+            .get_mut(group)
+            .ok_or_else(|| ConsumerGroupError::UnknownGroup(group.to_string()))?;
+        state.member(group, member)?;
+        let mut blocked = BTreeSet::new();
+        for (offset, (ordering_key, message)) in
+            self.log.iter().enumerate().skip(state.committed as usize)
+        {
+            let offset = offset as u64;
+            if state.acked.contains(&offset) || blocked.contains(ordering_key) {
+                continue;
+            }
+            // An earlier message of the key is unacknowledged: the next ones wait for it.
+            blocked.insert(ordering_key);
+            if state.in_flight.contains_key(&offset) || state.owner(ordering_key) != Some(member) {
+                continue;
+            }
+            state.in_flight.insert(offset, member.to_string());
+            return Ok(Some(Delivery {
+                offset,
+                ordering_key: ordering_key.clone(),
+                message: message.clone(),
+            }));
+        }
+        Ok(None)
+    }
+
+    fn ack(&mut self, group: &str, member: &str, offset: u64) -> Result<(), Self::Error> {
+        let state = self.group_mut(group)?;
+        state.member(group, member)?;
+        if state.in_flight.get(&offset).map(String::as_str) != Some(member) {
+            return Err(ConsumerGroupError::NotDelivered {
+                member: member.to_string(),
+                offset,
+            });
+        }
+        state.in_flight.remove(&offset);
+        state.acked.insert(offset);
+        while state.acked.remove(&state.committed) {
+            state.committed += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by the [`OnMemoryTopic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsumerGroupError {
+    /// No member ever joined the group.
+    UnknownGroup(String),
+    /// The member is not part of the group.
+    NotMember { group: String, member: String },
+    /// The message is not awaiting an acknowledgement from the member.
+    NotDelivered { member: String, offset: u64 },
+}
+
+impl fmt::Display for ConsumerGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsumerGroupError::UnknownGroup(group) => {
+                write!(f, "unknown consumer group: {}", group)
+            }
+            ConsumerGroupError::NotMember { group, member } => {
+                write!(
+                    f,
+                    "{} is not a member of the consumer group {}",
+                    member, group
+                )
+            }
+            ConsumerGroupError::NotDelivered { member, offset } => {
+                write!(
+                    f,
+                    "message {} is not awaiting an ack from {}",
+                    offset, member
+                )
+            }
+        }
+    }
+}
+
+impl Error for ConsumerGroupError {}
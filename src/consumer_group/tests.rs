@@ -0,0 +1,91 @@
+use super::*;
+
+fn topic() -> OnMemoryTopic<Envelope<u32>> {
+    let mut topic = OnMemoryTopic::per_stream();
+    for (stream_id, payload) in [
+        ("order-1", 1),
+        ("order-2", 1),
+        ("order-1", 2),
+        ("order-3", 1),
+    ] {
+        topic
+            .publish(&Envelope::new(stream_id, 0, "Happened", payload))
+            .unwrap();
+    }
+    topic
+}
+
+fn drain(topic: &mut OnMemoryTopic<Envelope<u32>>, group: &str, member: &str) -> Vec<u64> {
+    let mut offsets = Vec::new();
+    while let Some(delivery) = topic.poll(group, member).unwrap() {
+        topic.ack(group, member, delivery.offset).unwrap();
+        offsets.push(delivery.offset);
+    }
+    offsets
+}
+
+#[test]
+fn test_every_group_receives_every_message() {
+    let mut topic = topic();
+    topic.join("billing", "b1").unwrap();
+    topic.join("shipping", "s1").unwrap();
+
+    assert_eq!(drain(&mut topic, "billing", "b1"), vec![0, 1, 2, 3]);
+    assert_eq!(drain(&mut topic, "shipping", "s1"), vec![0, 1, 2, 3]);
+    assert_eq!(topic.committed("billing"), 4);
+    assert_eq!(
+        topic.poll("audit", "a1"),
+        Err(ConsumerGroupError::UnknownGroup("audit".to_string()))
+    );
+}
+
+#[test]
+fn test_ordered_per_key() {
+    let mut topic = topic();
+    topic.join("billing", "b1").unwrap();
+
+    let first = topic.poll("billing", "b1").unwrap().unwrap();
+    let second = topic.poll("billing", "b1").unwrap().unwrap();
+    let third = topic.poll("billing", "b1").unwrap().unwrap();
+    // The second event of order-1 waits for the first one to be acknowledged.
+    assert_eq!([first.offset, second.offset, third.offset], [0, 1, 3]);
+    assert_eq!(topic.poll("billing", "b1").unwrap(), None);
+
+    topic.ack("billing", "b1", 0).unwrap();
+    let next = topic.poll("billing", "b1").unwrap().unwrap();
+    assert_eq!((next.offset, next.message.payload), (2, 2));
+    assert_eq!(
+        topic.ack("billing", "b1", 0),
+        Err(ConsumerGroupError::NotDelivered {
+            member: "b1".to_string(),
+            offset: 0,
+        })
+    );
+}
+
+#[test]
+fn test_rebalance_on_leave() {
+    let mut topic = topic();
+    topic.join("billing", "b1").unwrap();
+    topic.join("billing", "b2").unwrap();
+
+    let mut b1 = Vec::new();
+    while let Some(delivery) = topic.poll("billing", "b1").unwrap() {
+        b1.push(delivery.ordering_key);
+    }
+    // Ordering keys are split by hash: b1 owns order-2, b2 owns order-1 and order-3.
+    assert_eq!(b1, vec!["order-2".to_string()]);
+    assert_eq!(drain(&mut topic, "billing", "b2"), vec![0, 2, 3]);
+
+    // The unacknowledged message of b1 is delivered to b2 once b1 leaves.
+    topic.leave("billing", "b1").unwrap();
+    assert_eq!(drain(&mut topic, "billing", "b2"), vec![1]);
+    assert_eq!(topic.committed("billing"), 4);
+    assert_eq!(
+        topic.poll("billing", "b1"),
+        Err(ConsumerGroupError::NotMember {
+            group: "billing".to_string(),
+            member: "b1".to_string(),
+        })
+    );
+}
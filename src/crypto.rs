@@ -0,0 +1,621 @@
+#[cfg(test)]
+mod tests;
+
+const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Incremental SHA-512 hasher.
+#[derive(Clone)]
+pub(crate) struct Sha512 {
+    state: [u64; 8],
+    buffer: Vec<u8>,
+    length: u128,
+}
+
+impl Sha512 {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: IV,
+            buffer: Vec::with_capacity(128),
+            length: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.length += data.len() as u128;
+        self.buffer.extend_from_slice(data);
+        let blocks = self.buffer.len() / 128;
+        for i in 0..blocks {
+            let block: [u8; 128] = self.buffer[i * 128..(i + 1) * 128].try_into().unwrap();
+            compress(&mut self.state, &block);
+        }
+        self.buffer.drain(..blocks * 128);
+        self
+    }
+
+    pub(crate) fn finish(&self) -> [u8; 64] {
+        let mut state = self.state;
+        let mut tail = self.buffer.clone();
+        tail.push(0x80);
+        while tail.len() % 128 != 112 {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&(self.length * 8).to_be_bytes());
+        for block in tail.chunks(128) {
+            compress(&mut state, block.try_into().unwrap());
+        }
+        let mut out = [0; 64];
+        for (chunk, word) in out.chunks_mut(8).zip(state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+fn compress(state: &mut [u64; 8], block: &[u8; 128]) {
+    let mut w = [0u64; 80];
+    for (i, chunk) in block.chunks(8).enumerate() {
+        w[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..80 {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// Compute the SHA-512 digest of the parts, concatenated.
+pub(crate) fn sha512(parts: &[&[u8]]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finish()
+}
+
+/// Encode the bytes as lowercase hexadecimal.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode lowercase or uppercase hexadecimal.
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Compute the SHA-1 digest of the data, as required by the WebSocket handshake.
+#[cfg(feature = "ws")]
+pub(crate) fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, chunk) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let t = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+    let mut out = [0; 20];
+    for (chunk, word) in out.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+/// Encode the bytes in standard padded base64.
+#[cfg(feature = "ws")]
+pub(crate) fn to_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// Ed25519 below is ported from TweetNaCl.
+
+// Field element of GF(2^255 - 19), as 16 limbs of 16 bits.
+type Gf = [i64; 16];
+
+const GF0: Gf = [0; 16];
+const GF1: Gf = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+const D: Gf = [
+    0x78a3, 0x1359, 0x4dca, 0x75eb, 0xd8ab, 0x4141, 0x0a4d, 0x0070, 0xe898, 0x7779, 0x4079, 0x8cc7,
+    0xfe73, 0x2b6f, 0x6cee, 0x5203,
+];
+const D2: Gf = [
+    0xf159, 0x26b2, 0x9b94, 0xebd6, 0xb156, 0x8283, 0x149a, 0x00e0, 0xd130, 0xeef3, 0x80f2, 0x198e,
+    0xfce7, 0x56df, 0xd9dc, 0x2406,
+];
+const X: Gf = [
+    0xd51a, 0x8f25, 0x2d60, 0xc956, 0xa7b2, 0x9525, 0xc760, 0x692c, 0xdc5c, 0xfdd6, 0xe231, 0xc0a4,
+    0x53fe, 0xcd6e, 0x36d3, 0x2169,
+];
+const Y: Gf = [
+    0x6658, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666, 0x6666,
+    0x6666, 0x6666, 0x6666, 0x6666,
+];
+const I: Gf = [
+    0xa0b0, 0x4a0e, 0x1b27, 0xc4ee, 0xe478, 0xad2f, 0x1806, 0x2f43, 0xd7a7, 0x3dfb, 0x0099, 0x2b4d,
+    0xdf0b, 0x4fc1, 0x2480, 0x2b83,
+];
+const L: [i64; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+];
+
+fn car25519(o: &mut Gf) {
+    for i in 0..16 {
+        o[i] += 1 << 16;
+        let c = o[i] >> 16;
+        if i < 15 {
+            o[i + 1] += c - 1;
+        } else {
+            o[0] += 38 * (c - 1);
+        }
+        o[i] -= c << 16;
+    }
+}
+
+fn sel25519(p: &mut Gf, q: &mut Gf, b: i64) {
+    let c = !(b - 1);
+    for i in 0..16 {
+        let t = c & (p[i] ^ q[i]);
+        p[i] ^= t;
+        q[i] ^= t;
+    }
+}
+
+fn pack25519(n: &Gf) -> [u8; 32] {
+    let mut t = *n;
+    car25519(&mut t);
+    car25519(&mut t);
+    car25519(&mut t);
+    let mut m = GF0;
+    for _ in 0..2 {
+        m[0] = t[0] - 0xffed;
+        for i in 1..15 {
+            m[i] = t[i] - 0xffff - ((m[i - 1] >> 16) & 1);
+            m[i - 1] &= 0xffff;
+        }
+        m[15] = t[15] - 0x7fff - ((m[14] >> 16) & 1);
+        let b = (m[15] >> 16) & 1;
+        m[14] &= 0xffff;
+        sel25519(&mut t, &mut m, 1 - b);
+    }
+    let mut o = [0; 32];
+    for i in 0..16 {
+        o[2 * i] = (t[i] & 0xff) as u8;
+        o[2 * i + 1] = (t[i] >> 8) as u8;
+    }
+    o
+}
+
+fn neq25519(a: &Gf, b: &Gf) -> bool {
+    pack25519(a) != pack25519(b)
+}
+
+fn par25519(a: &Gf) -> u8 {
+    pack25519(a)[0] & 1
+}
+
+fn unpack25519(n: &[u8; 32]) -> Gf {
+    let mut o = GF0;
+    for i in 0..16 {
+        o[i] = n[2 * i] as i64 + ((n[2 * i + 1] as i64) << 8);
+    }
+    o[15] &= 0x7fff;
+    o
+}
+
+fn add25519(a: &Gf, b: &Gf) -> Gf {
+    let mut o = GF0;
+    for i in 0..16 {
+        o[i] = a[i] + b[i];
+    }
+    o
+}
+
+fn sub25519(a: &Gf, b: &Gf) -> Gf {
+    let mut o = GF0;
+    for i in 0..16 {
+        o[i] = a[i] - b[i];
+    }
+    o
+}
+
+fn mul25519(a: &Gf, b: &Gf) -> Gf {
+    let mut t = [0i64; 31];
+    for i in 0..16 {
+        for j in 0..16 {
+            t[i + j] += a[i] * b[j];
+        }
+    }
+    for i in 0..15 {
+        t[i] += 38 * t[i + 16];
+    }
+    let mut o = GF0;
+    o.copy_from_slice(&t[..16]);
+    car25519(&mut o);
+    car25519(&mut o);
+    o
+}
+
+fn square25519(a: &Gf) -> Gf {
+    mul25519(a, a)
+}
+
+fn inv25519(i: &Gf) -> Gf {
+    let mut c = *i;
+    for a in (0..=253).rev() {
+        c = square25519(&c);
+        if a != 2 && a != 4 {
+            c = mul25519(&c, i);
+        }
+    }
+    c
+}
+
+fn pow2523(i: &Gf) -> Gf {
+    let mut c = *i;
+    for a in (0..=250).rev() {
+        c = square25519(&c);
+        if a != 1 {
+            c = mul25519(&c, i);
+        }
+    }
+    c
+}
+
+// Point in extended coordinates.
+type Point = [Gf; 4];
+
+fn point_add(p: &mut Point, q: &Point) {
+    let a = mul25519(&sub25519(&p[1], &p[0]), &sub25519(&q[1], &q[0]));
+    let b = mul25519(&add25519(&p[0], &p[1]), &add25519(&q[0], &q[1]));
+    let c = mul25519(&mul25519(&p[3], &q[3]), &D2);
+    let d = mul25519(&p[2], &q[2]);
+    let d = add25519(&d, &d);
+    let e = sub25519(&b, &a);
+    let f = sub25519(&d, &c);
+    let g = add25519(&d, &c);
+    let h = add25519(&b, &a);
+    p[0] = mul25519(&e, &f);
+    p[1] = mul25519(&h, &g);
+    p[2] = mul25519(&g, &f);
+    p[3] = mul25519(&e, &h);
+}
+
+fn cswap(p: &mut Point, q: &mut Point, b: u8) {
+    for i in 0..4 {
+        sel25519(&mut p[i], &mut q[i], b as i64);
+    }
+}
+
+fn pack_point(p: &Point) -> [u8; 32] {
+    let zi = inv25519(&p[2]);
+    let tx = mul25519(&p[0], &zi);
+    let ty = mul25519(&p[1], &zi);
+    let mut r = pack25519(&ty);
+    r[31] ^= par25519(&tx) << 7;
+    r
+}
+
+fn scalarmult(q: &mut Point, s: &[u8]) -> Point {
+    let mut p = [GF0, GF1, GF1, GF0];
+    for i in (0..256).rev() {
+        let b = (s[i / 8] >> (i & 7)) & 1;
+        cswap(&mut p, q, b);
+        point_add(q, &p);
+        let doubled = p;
+        point_add(&mut p, &doubled);
+        cswap(&mut p, q, b);
+    }
+    p
+}
+
+fn scalarbase(s: &[u8]) -> Point {
+    let mut q = [X, Y, GF1, mul25519(&X, &Y)];
+    scalarmult(&mut q, s)
+}
+
+fn unpackneg(p: &[u8; 32]) -> Option<Point> {
+    let mut r = [GF0, unpack25519(p), GF1, GF0];
+    let num = square25519(&r[1]);
+    let den = mul25519(&num, &D);
+    let num = sub25519(&num, &r[2]);
+    let den = add25519(&r[2], &den);
+    let den2 = square25519(&den);
+    let den4 = square25519(&den2);
+    let den6 = mul25519(&den4, &den2);
+    let mut t = mul25519(&den6, &num);
+    t = mul25519(&t, &den);
+    t = pow2523(&t);
+    t = mul25519(&t, &num);
+    t = mul25519(&t, &den);
+    t = mul25519(&t, &den);
+    r[0] = mul25519(&t, &den);
+
+    let chk = mul25519(&square25519(&r[0]), &den);
+    if neq25519(&chk, &num) {
+        r[0] = mul25519(&r[0], &I);
+    }
+    let chk = mul25519(&square25519(&r[0]), &den);
+    if neq25519(&chk, &num) {
+        return None;
+    }
+    if par25519(&r[0]) == (p[31] >> 7) {
+        r[0] = sub25519(&GF0, &r[0]);
+    }
+    r[3] = mul25519(&r[0], &r[1]);
+    Some(r)
+}
+
+fn mod_l(x: &mut [i64; 64]) -> [u8; 32] {
+    for i in (32..64).rev() {
+        let mut carry = 0;
+        let mut j = i - 32;
+        while j < i - 12 {
+            x[j] += carry - 16 * x[i] * L[j - (i - 32)];
+            carry = (x[j] + 128) >> 8;
+            x[j] -= carry << 8;
+            j += 1;
+        }
+        x[j] += carry;
+        x[i] = 0;
+    }
+    let mut carry = 0;
+    for j in 0..32 {
+        x[j] += carry - (x[31] >> 4) * L[j];
+        carry = x[j] >> 8;
+        x[j] &= 255;
+    }
+    for j in 0..32 {
+        x[j] -= carry * L[j];
+    }
+    let mut r = [0; 32];
+    for i in 0..32 {
+        x[i + 1] += x[i] >> 8;
+        r[i] = (x[i] & 255) as u8;
+    }
+    r
+}
+
+fn reduce(digest: &[u8; 64]) -> [u8; 32] {
+    let mut x = [0i64; 64];
+    for (x, b) in x.iter_mut().zip(digest) {
+        *x = *b as i64;
+    }
+    mod_l(&mut x)
+}
+
+fn expand(seed: &[u8; 32]) -> [u8; 64] {
+    let mut d = sha512(&[seed]);
+    d[0] &= 248;
+    d[31] &= 127;
+    d[31] |= 64;
+    d
+}
+
+/// Derive the Ed25519 public key of the secret seed.
+pub(crate) fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+    pack_point(&scalarbase(&expand(seed)[..32]))
+}
+
+/// Sign the message with the Ed25519 secret seed.
+pub(crate) fn ed25519_sign(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+    let d = expand(seed);
+    let public_key = pack_point(&scalarbase(&d[..32]));
+    let r = reduce(&sha512(&[&d[32..], message]));
+    let big_r = pack_point(&scalarbase(&r));
+    let h = reduce(&sha512(&[&big_r, &public_key, message]));
+    let mut x = [0i64; 64];
+    for i in 0..32 {
+        x[i] = r[i] as i64;
+    }
+    for i in 0..32 {
+        for j in 0..32 {
+            x[i + j] += h[i] as i64 * d[j] as i64;
+        }
+    }
+    let s = mod_l(&mut x);
+    let mut signature = [0; 64];
+    signature[..32].copy_from_slice(&big_r);
+    signature[32..].copy_from_slice(&s);
+    signature
+}
+
+/// Check that the little-endian scalar is reduced modulo the group order `L`.
+fn is_canonical_scalar(s: &[u8]) -> bool {
+    for i in (0..32).rev() {
+        let (byte, order) = (i64::from(s[i]), L[i]);
+        if byte != order {
+            return byte < order;
+        }
+    }
+    false
+}
+
+/// Verify the Ed25519 signature of the message.
+///
+/// Signatures whose scalar `S` is not below the group order are rejected, as required by
+/// RFC 8032, so that a signature cannot be altered into another valid one.
+pub(crate) fn ed25519_verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    if !is_canonical_scalar(&signature[32..]) {
+        return false;
+    }
+    let mut q = match unpackneg(public_key) {
+        Some(q) => q,
+        None => return false,
+    };
+    let h = reduce(&sha512(&[&signature[..32], public_key, message]));
+    let mut p = scalarmult(&mut q, &h);
+    let sb = scalarbase(&signature[32..]);
+    point_add(&mut p, &sb);
+    pack_point(&p)[..] == signature[..32]
+}
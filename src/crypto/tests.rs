@@ -0,0 +1,146 @@
+use super::*;
+
+/// Vectors of FIPS 180-2 and of the NIST examples, as `(message, digest)`.
+const SHA512_VECTORS: [(&str, &str); 4] = [
+    (
+        "",
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+         47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+    ),
+    (
+        "abc",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    ),
+    (
+        "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c335\
+         96fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+    ),
+    (
+        "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+         hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+        "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+         501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+    ),
+];
+
+#[test]
+fn test_sha512() {
+    for (message, digest) in SHA512_VECTORS {
+        assert_eq!(
+            to_hex(&sha512(&[message.as_bytes()])),
+            digest,
+            "{}",
+            message
+        );
+    }
+
+    // One million repetitions of 'a', hashed in chunks crossing the block boundaries.
+    let long = vec![b'a'; 1_000_000];
+    let mut hasher = Sha512::new();
+    for chunk in long.chunks(7) {
+        hasher.update(chunk);
+    }
+    assert_eq!(
+        to_hex(&hasher.finish()),
+        "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb\
+         de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+    );
+}
+
+/// Vectors of RFC 8032, section 7.1, as `(secret key, public key, message, signature)`. The
+/// message of TEST SHA(abc) is the SHA-512 digest of "abc". TEST 1024, whose 1023-byte message
+/// is not reproduced here, is left out.
+const ED25519_VECTORS: [(&str, &str, &str, &str); 4] = [
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+         5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+         085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac\
+         18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+    (
+        "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+        "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b589\
+         09351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+    ),
+];
+
+#[test]
+fn test_ed25519_rfc8032_vectors() {
+    for (secret_key, public_key, message, signature) in ED25519_VECTORS {
+        let seed: [u8; 32] = from_hex(secret_key).unwrap().try_into().unwrap();
+        let message = from_hex(message).unwrap();
+        let expected: [u8; 32] = from_hex(public_key).unwrap().try_into().unwrap();
+        assert_eq!(ed25519_public_key(&seed), expected, "{}", secret_key);
+        let signed = ed25519_sign(&seed, &message);
+        assert_eq!(to_hex(&signed), signature, "{}", secret_key);
+        assert!(ed25519_verify(&expected, &message, &signed));
+
+        let mut tampered = message.clone();
+        tampered.push(0);
+        assert!(!ed25519_verify(&expected, &tampered, &signed));
+        let mut forged = signed;
+        forged[0] ^= 1;
+        assert!(!ed25519_verify(&expected, &message, &forged));
+    }
+}
+
+#[test]
+fn test_ed25519_reject_non_canonical_scalar() {
+    let seed = [7; 32];
+    let public_key = ed25519_public_key(&seed);
+    let signature = ed25519_sign(&seed, b"message");
+    assert!(ed25519_verify(&public_key, b"message", &signature));
+
+    // Adding the group order to S yields the same point, but a non-canonical encoding.
+    let mut malleated = signature;
+    let mut carry = 0;
+    for i in 0..32 {
+        let sum = i64::from(signature[32 + i]) + L[i] + carry;
+        malleated[32 + i] = (sum & 0xff) as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(carry, 0);
+    assert!(!ed25519_verify(&public_key, b"message", &malleated));
+
+    let mut order = [0; 32];
+    for (byte, l) in order.iter_mut().zip(L) {
+        *byte = l as u8;
+    }
+    assert!(!is_canonical_scalar(&order));
+    order[0] -= 1;
+    assert!(is_canonical_scalar(&order));
+}
+
+#[cfg(feature = "ws")]
+#[test]
+fn test_sha1_and_base64() {
+    assert_eq!(
+        to_hex(&sha1(b"abc")),
+        "a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+    // Handshake example of RFC 6455.
+    let key = b"dGhlIHNhbXBsZSBub25jZQ==258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    assert_eq!(to_base64(&sha1(key)), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    assert_eq!(to_base64(b"ab"), "YWI=");
+    assert_eq!(to_base64(b"a"), "YQ==");
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests;
+
+use std::convert::Infallible;
+use std::error::Error;
+
+/// A message which could not be processed, with the reason of the failure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadLetter<M> {
+    /// The message which could not be processed.
+    pub message: M,
+    /// Description of the failure.
+    pub reason: String,
+}
+
+/// Types which represent a store for messages which could not be processed.
+pub trait DeadLetterStore<M> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Park the dead letter.
+    fn put(&mut self, letter: DeadLetter<M>) -> Result<(), Self::Error>;
+    /// Take all the parked dead letters, e.g. to retry them.
+    fn drain(&mut self) -> Result<Vec<DeadLetter<M>>, Self::Error>;
+}
+
+/// Dead letter store keeping the letters in memory.
+#[derive(Debug)]
+pub struct OnMemoryDeadLetterStore<M> {
+    letters: Vec<DeadLetter<M>>,
+}
+
+impl<M> OnMemoryDeadLetterStore<M> {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self {
+            letters: Vec::new(),
+        }
+    }
+
+    /// Get the parked dead letters.
+    pub fn letters(&self) -> &[DeadLetter<M>] {
+        &self.letters
+    }
+}
+
+impl<M> Default for OnMemoryDeadLetterStore<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> DeadLetterStore<M> for OnMemoryDeadLetterStore<M> {
+    type Error = Infallible;
+
+    fn put(&mut self, letter: DeadLetter<M>) -> Result<(), Self::Error> {
+        self.letters.push(letter);
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<Vec<DeadLetter<M>>, Self::Error> {
+        Ok(std::mem::take(&mut self.letters))
+    }
+}
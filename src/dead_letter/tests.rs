@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn test_on_memory_dead_letter_store() {
+    let mut store = OnMemoryDeadLetterStore::new();
+    store
+        .put(DeadLetter {
+            message: 1,
+            reason: "boom".to_string(),
+        })
+        .unwrap();
+    assert_eq!(store.letters().len(), 1);
+
+    let letters = store.drain().unwrap();
+    assert_eq!(letters[0].message, 1);
+    assert_eq!(letters[0].reason, "boom");
+    assert!(store.letters().is_empty());
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::time::{Duration, SystemTime};
+
+use crate::runtime::HousekeepingJob;
+
+/// Types which keep deduplication records, such as received message keys or command IDs, that
+/// can be forgotten once finished for a while.
+///
+/// Records are timed from when they finished, by the clock of the store, which should agree
+/// with the clock the sweeps are run with.
+pub trait ExpiringDedup {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Delete the records finished for at least the TTL at `now`, returning the number deleted.
+    fn sweep(&mut self, now: SystemTime, ttl: Duration) -> Result<usize, Self::Error>;
+}
+
+/// Housekeeping job expiring the finished deduplication records of the store.
+///
+/// A duplicate received after its record expired is processed again, so the TTL should
+/// exceed the longest redelivery window of the senders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DedupExpiry {
+    ttl: Duration,
+}
+
+impl DedupExpiry {
+    /// Create a job keeping the finished records for the TTL.
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl }
+    }
+
+    /// Get the TTL of the finished records.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+}
+
+impl<S: ExpiringDedup> HousekeepingJob<S> for DedupExpiry {
+    fn name(&self) -> &'static str {
+        "dedup_expiry"
+    }
+
+    fn run(&mut self, store: &mut S, now: SystemTime) -> Result<usize, String> {
+        store.sweep(now, self.ttl).map_err(|e| e.to_string())
+    }
+}
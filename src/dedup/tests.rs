@@ -0,0 +1,92 @@
+use super::*;
+use crate::clock::{Clock, ManualClock};
+use crate::command_status::{CommandStatus, CommandStatusStore, OnMemoryCommandStatusStore};
+use crate::inbox::{Inbox, InboxMessage, InboxStatus, OnMemoryInbox};
+use crate::runtime::{CruxRuntime, JobReport};
+
+fn message(key: &str) -> InboxMessage<String> {
+    InboxMessage {
+        dedup_key: key.to_string(),
+        message: key.to_string(),
+    }
+}
+
+#[test]
+fn test_expire_inbox_records() {
+    let clock = ManualClock::default();
+    let mut runtime = CruxRuntime::new(OnMemoryInbox::with_clock(clock.clone()), clock.clone())
+        .with_job(DedupExpiry::new(Duration::from_secs(60)));
+    let inbox = runtime.store_mut();
+    for key in ["m1", "m2", "m3"] {
+        inbox.receive(message(key)).unwrap();
+    }
+    inbox.mark_processed("m1").unwrap();
+    inbox.mark_failed("m2", "invalid".to_string()).unwrap();
+
+    let expired = |runtime: &mut CruxRuntime<OnMemoryInbox<String, ManualClock>, ManualClock>| {
+        runtime.run_housekeeping()[0].clone()
+    };
+    assert_eq!(
+        expired(&mut runtime),
+        JobReport {
+            name: "dedup_expiry",
+            result: Ok(0),
+        }
+    );
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(expired(&mut runtime).result, Ok(0));
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(expired(&mut runtime).result, Ok(2));
+
+    // The pending message is kept, and the expired keys can be received again.
+    let inbox = runtime.store_mut();
+    assert_eq!(inbox.status("m3").unwrap(), Some(InboxStatus::Pending));
+    assert_eq!(inbox.status("m1").unwrap(), None);
+    assert!(inbox.receive(message("m1")).unwrap());
+
+    // Records are timed from their processing, not from the first sweep seeing them.
+    inbox.mark_processed("m3").unwrap();
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(expired(&mut runtime).result, Ok(1));
+}
+
+#[test]
+fn test_expire_inbox_records_with_overflowing_ttl() {
+    let mut inbox = OnMemoryInbox::with_clock(ManualClock::default());
+    inbox.receive(message("m1")).unwrap();
+    inbox.mark_processed("m1").unwrap();
+    assert_eq!(inbox.sweep(SystemTime::UNIX_EPOCH, Duration::MAX), Ok(0));
+    assert_eq!(inbox.status("m1").unwrap(), Some(InboxStatus::Processed));
+}
+
+#[test]
+fn test_expire_command_statuses() {
+    let clock = ManualClock::default();
+    let start = clock.now();
+    let ttl = Duration::from_secs(60);
+    let mut statuses = OnMemoryCommandStatusStore::<u32, _>::with_clock(clock.clone());
+    statuses
+        .set("c1", CommandStatus::Succeeded(vec![1]))
+        .unwrap();
+    statuses.set("c2", CommandStatus::Processing).unwrap();
+    statuses
+        .set("c3", CommandStatus::Failed("conflict".to_string()))
+        .unwrap();
+
+    assert_eq!(statuses.sweep(start, ttl), Ok(0));
+    // Finishing later restarts the timer of the record.
+    clock.advance(Duration::from_secs(30));
+    statuses
+        .set("c3", CommandStatus::Succeeded(vec![3]))
+        .unwrap();
+    assert_eq!(statuses.sweep(start + ttl, ttl), Ok(1));
+    assert_eq!(statuses.get("c1").unwrap(), None);
+    assert_eq!(statuses.get("c2").unwrap(), Some(CommandStatus::Processing));
+    assert_eq!(statuses.sweep(clock.now() + ttl, ttl), Ok(1));
+    assert_eq!(statuses.get("c3").unwrap(), None);
+
+    statuses
+        .set("c4", CommandStatus::Failed("conflict".to_string()))
+        .unwrap();
+    assert_eq!(statuses.sweep(clock.now(), Duration::MAX), Ok(0));
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, RequireTransaction, TransactionManager,
+    TransactionalError,
+};
+
+/// Types which write the current state of aggregates, such as the row of a table still queried
+/// by legacy readers.
+///
+/// Writes happen within the transaction of the event store, so the writer must take part in
+/// it, e.g. by sharing the database connection of the store.
+pub trait StateWriter<A: Aggregate> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Insert or replace the state of the aggregate, at the version of its stream.
+    fn upsert(&mut self, id: &A::Id, version: u64, state: &A) -> Result<(), Self::Error>;
+}
+
+impl<A: Aggregate, S: StateWriter<A>> StateWriter<A> for RequireTransaction<S> {
+    type Error = TransactionalError<S::Error>;
+
+    /// Fails unless a transaction is active, and poisons it if the write fails.
+    fn upsert(&mut self, id: &A::Id, version: u64, state: &A) -> Result<(), Self::Error> {
+        self.write(|inner| inner.upsert(id, version, state))
+    }
+}
+
+type ExecuteError<A, S, E> =
+    DualWriteError<E, <S as TransactionManager>::Error, <S as StateWriter<A>>::Error>;
+
+/// Repository persisting aggregates both as events and as their current state, in one
+/// transaction, for teams moving from state-based persistence to the event log gradually.
+///
+/// Aggregates are rebuilt from their events; the state is only written for other readers.
+pub struct StateAndEventsRepository<A, S> {
+    store: S,
+    aggregate: PhantomData<A>,
+}
+
+impl<A, S> StateAndEventsRepository<A, S> {
+    /// Create a repository over the store, which also writes the states.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            aggregate: PhantomData,
+        }
+    }
+
+    /// Get the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<A, S, E> StateAndEventsRepository<A, S>
+where
+    A: Aggregate + Default,
+    A::Event: Clone,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>, Error = E>
+        + EventStore<Persistable = Envelope<A::Event>, Error = E>
+        + TransactionManager
+        + StateWriter<A>,
+{
+    /// Decide the events of the aggregate from its state, `None` for an aggregate without
+    /// events, then append them and write the resulting state in one transaction. Returns the
+    /// saved events.
+    ///
+    /// The stream is loaded within the transaction, and the events are appended at the
+    /// version it was loaded at, so a concurrent append fails the execution instead of being
+    /// overwritten in the state.
+    pub fn execute(
+        &mut self,
+        id: &A::Id,
+        decide: impl FnOnce(Option<&A>) -> Vec<A::Event>,
+    ) -> Result<Vec<A::Event>, ExecuteError<A, S, E>> {
+        self.store.begin().map_err(DualWriteError::Transaction)?;
+        match self.write(id, decide) {
+            Ok(events) => {
+                self.store.commit().map_err(DualWriteError::Transaction)?;
+                Ok(events)
+            }
+            Err(e) => {
+                self.store.rollback().map_err(DualWriteError::Transaction)?;
+                Err(e)
+            }
+        }
+    }
+
+    fn write(
+        &mut self,
+        id: &A::Id,
+        decide: impl FnOnce(Option<&A>) -> Vec<A::Event>,
+    ) -> Result<Vec<A::Event>, ExecuteError<A, S, E>> {
+        let stream_id = A::stream_id(id);
+        let history = self.store.load(&stream_id).map_err(DualWriteError::Store)?;
+        let mut state = A::default();
+        for event in &history {
+            state.apply(&event.payload);
+        }
+        let events = decide((!history.is_empty()).then_some(&state));
+        if events.is_empty() {
+            return Ok(events);
+        }
+        let expected = history.last().map_or(0, |e| e.version);
+        let mut version = expected;
+        let envelopes = events
+            .iter()
+            .map(|event| {
+                state.apply(event);
+                version += 1;
+                Envelope::new(stream_id.clone(), 0, A::event_type(event), event.clone())
+            })
+            .collect::<Vec<_>>();
+        self.store
+            .append_multi(&[(stream_id, ExpectedVersion::Exact(expected), envelopes)])
+            .map_err(DualWriteError::Append)?;
+        self.store
+            .upsert(id, version, &state)
+            .map_err(DualWriteError::State)?;
+        Ok(events)
+    }
+}
+
+/// Error returned by the [`StateAndEventsRepository`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DualWriteError<E, TE, WE> {
+    /// The store failed to load the events.
+    Store(E),
+    /// The events could not be appended, e.g. because the stream changed since it was
+    /// loaded.
+    Append(AppendError<E>),
+    /// The transaction failed.
+    Transaction(TE),
+    /// The state could not be written; the events were rolled back.
+    State(WE),
+}
+
+impl<E: Error, TE: Error, WE: Error> fmt::Display for DualWriteError<E, TE, WE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DualWriteError::Store(e) => write!(f, "store error: {}", e),
+            DualWriteError::Append(e) => write!(f, "{}", e),
+            DualWriteError::Transaction(e) => write!(f, "transaction error: {}", e),
+            DualWriteError::State(e) => write!(f, "state writer error: {}", e),
+        }
+    }
+}
+
+impl<E: Error, TE: Error, WE: Error> Error for DualWriteError<E, TE, WE> {}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::event_store::{
+    require_transaction, OnMemoryEventStore, OnMemoryEventStoreError, TransactionState,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Account {
+    balance: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AccountEvent {
+    Deposited(i64),
+}
+
+impl Aggregate for Account {
+    type Id = u32;
+    type Event = AccountEvent;
+
+    fn aggregate_type() -> &'static str {
+        "account"
+    }
+
+    fn event_type(_: &AccountEvent) -> String {
+        "Deposited".to_string()
+    }
+
+    fn apply(&mut self, event: &AccountEvent) {
+        let AccountEvent::Deposited(amount) = event;
+        self.balance += amount;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct NegativeBalance;
+
+impl fmt::Display for NegativeBalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "negative balance violates the table constraint")
+    }
+}
+
+impl Error for NegativeBalance {}
+
+/// Store keeping the `accounts` table of the legacy readers next to the events, staging its
+/// rows in the transaction of the events.
+#[derive(Default)]
+struct LegacyStore {
+    events: OnMemoryEventStore<AccountEvent>,
+    accounts: HashMap<u32, (u64, i64)>,
+    staged: Vec<(u32, u64, i64)>,
+    // Number of events hidden from loads, as if appended concurrently after the load.
+    unseen: usize,
+}
+
+impl EventStore for LegacyStore {
+    type Persistable = Envelope<AccountEvent>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.events.save(events)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.events.append_multi(appends)
+    }
+}
+
+impl EventLoader for LegacyStore {
+    type StreamId = String;
+    type Persistable = Envelope<AccountEvent>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let mut events = self.events.load(stream_id)?;
+        events.truncate(events.len().saturating_sub(self.unseen));
+        Ok(events)
+    }
+}
+
+impl TransactionManager for LegacyStore {
+    type Error = OnMemoryEventStoreError;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.events.begin()
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.events.commit()?;
+        for (id, version, balance) in self.staged.drain(..) {
+            self.accounts.insert(id, (version, balance));
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.staged.clear();
+        self.events.rollback()
+    }
+}
+
+impl StateWriter<Account> for LegacyStore {
+    type Error = NegativeBalance;
+
+    fn upsert(&mut self, id: &u32, version: u64, state: &Account) -> Result<(), Self::Error> {
+        if state.balance < 0 {
+            return Err(NegativeBalance);
+        }
+        self.staged.push((*id, version, state.balance));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_events_and_state() {
+    let mut repository = StateAndEventsRepository::<Account, _>::new(LegacyStore::default());
+
+    repository
+        .execute(&1, |account| {
+            assert!(account.is_none());
+            vec![AccountEvent::Deposited(100), AccountEvent::Deposited(20)]
+        })
+        .unwrap();
+    repository
+        .execute(&1, |account| {
+            assert_eq!(account.unwrap().balance, 120);
+            vec![AccountEvent::Deposited(5)]
+        })
+        .unwrap();
+
+    assert_eq!(repository.store().accounts.get(&1), Some(&(3, 125)));
+    assert_eq!(repository.store().events.stream_len("account-1"), 3);
+    assert!(repository.execute(&1, |_| Vec::new()).unwrap().is_empty());
+}
+
+#[test]
+fn test_roll_back_events_when_state_fails() {
+    let mut repository = StateAndEventsRepository::<Account, _>::new(LegacyStore::default());
+    repository
+        .execute(&1, |_| vec![AccountEvent::Deposited(10)])
+        .unwrap();
+
+    assert_eq!(
+        repository.execute(&1, |_| vec![AccountEvent::Deposited(-50)]),
+        Err(DualWriteError::State(NegativeBalance))
+    );
+    assert_eq!(repository.store().accounts.get(&1), Some(&(1, 10)));
+    assert_eq!(repository.store().events.stream_len("account-1"), 1);
+}
+
+#[test]
+fn test_require_transaction() {
+    let store = require_transaction(LegacyStore::default());
+    let mut repository = StateAndEventsRepository::<Account, _>::new(store);
+    repository
+        .execute(&1, |_| vec![AccountEvent::Deposited(10)])
+        .unwrap();
+    assert_eq!(
+        repository.execute(&1, |_| vec![AccountEvent::Deposited(-50)]),
+        Err(DualWriteError::State(TransactionalError::Store(
+            NegativeBalance
+        )))
+    );
+    assert_eq!(repository.store().state(), TransactionState::Idle);
+    assert_eq!(repository.store().inner().accounts.get(&1), Some(&(1, 10)));
+}
+
+#[test]
+fn test_reject_concurrent_append() {
+    let mut repository = StateAndEventsRepository::<Account, _>::new(LegacyStore::default());
+    repository
+        .execute(&1, |_| {
+            vec![AccountEvent::Deposited(10), AccountEvent::Deposited(5)]
+        })
+        .unwrap();
+
+    repository.store.unseen = 1;
+    assert_eq!(
+        repository.execute(&1, |_| vec![AccountEvent::Deposited(1)]),
+        Err(DualWriteError::Append(AppendError::WrongExpectedVersion {
+            stream_id: "account-1".to_string(),
+            expected: ExpectedVersion::Exact(1),
+            actual: 2,
+        }))
+    );
+    assert_eq!(repository.store().accounts.get(&1), Some(&(2, 15)));
+    assert_eq!(repository.store().events.stream_len("account-1"), 2);
+}
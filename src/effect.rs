@@ -0,0 +1,349 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::aggregate::Aggregate;
+use crate::clock::Clock;
+use crate::envelope::Envelope;
+use crate::event_id::EventId;
+use crate::event_store::{EventLoader, EventStore, TransactionManager};
+use crate::repository::{Repository, RepositoryError};
+
+/// A side effect, such as an email or a payment, waiting to be executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEffect<F> {
+    /// ID of the effect, for handlers to execute it idempotently.
+    pub id: String,
+    /// The effect.
+    pub effect: F,
+    /// Number of failed attempts.
+    pub attempts: u32,
+    /// Time from which the effect can be attempted.
+    pub due_at: SystemTime,
+    /// Error of the last failed attempt.
+    pub last_error: Option<String>,
+}
+
+impl<F> PendingEffect<F> {
+    /// Create an effect due at the time, with a newly generated ID.
+    pub fn new(effect: F, due_at: SystemTime) -> Self {
+        Self {
+            id: EventId::generate().to_string(),
+            effect,
+            attempts: 0,
+            due_at,
+            last_error: None,
+        }
+    }
+}
+
+/// Execution status of an effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EffectStatus {
+    /// The effect waits to be executed, or retried.
+    Pending,
+    /// The effect was executed.
+    Succeeded,
+    /// The effect failed every attempt, with the last error.
+    Failed(String),
+}
+
+/// Types which represent a store persisting effects until they are executed.
+pub trait EffectStore<F> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Persist the effects.
+    fn enqueue(&mut self, effects: Vec<PendingEffect<F>>) -> Result<(), Self::Error>;
+    /// Get the pending effects due at `now`, oldest first.
+    fn due(&self, now: SystemTime, limit: usize) -> Result<Vec<PendingEffect<F>>, Self::Error>;
+    /// Record the effect as executed.
+    fn succeed(&mut self, id: &str) -> Result<(), Self::Error>;
+    /// Record a failed attempt, scheduling the next one at the time.
+    fn retry(&mut self, id: &str, due_at: SystemTime, error: String) -> Result<(), Self::Error>;
+    /// Record that the effect is given up.
+    fn fail(&mut self, id: &str, error: String) -> Result<(), Self::Error>;
+    /// Get the status of the effect.
+    fn status(&self, id: &str) -> Result<Option<EffectStatus>, Self::Error>;
+}
+
+/// Types which execute effects.
+pub trait EffectHandler<F> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Execute the effect. Effects are executed at least once, so `id` should be used to
+    /// deduplicate them on the receiving side.
+    fn execute(&mut self, id: &str, effect: &F) -> Result<(), Self::Error>;
+}
+
+impl<F, E: Error, H: FnMut(&str, &F) -> Result<(), E>> EffectHandler<F> for H {
+    type Error = E;
+
+    fn execute(&mut self, id: &str, effect: &F) -> Result<(), Self::Error> {
+        self(id, effect)
+    }
+}
+
+/// Decide the events and effects of the aggregate from its state, `None` for an aggregate
+/// without events, then save the events and persist the effects in one transaction. Returns the
+/// saved events.
+///
+/// The store persists the effects as an outbox next to the events, e.g. in a table of the same
+/// database, so that the effects are persisted if and only if the events are. They are executed
+/// later by an [`EffectRunner`], so that aggregates stay free of side effects.
+pub fn execute_with_effects<A, S, E, TE, F, NE>(
+    repository: &mut Repository<A, S>,
+    now: SystemTime,
+    id: &A::Id,
+    decide: impl FnOnce(Option<&A>) -> (Vec<A::Event>, Vec<F>),
+) -> Result<Vec<A::Event>, EffectError<E, TE, NE>>
+where
+    A: Aggregate + Default,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>, Error = E>
+        + EventStore<Persistable = Envelope<A::Event>, Error = E>
+        + TransactionManager<Error = TE>
+        + EffectStore<F, Error = NE>,
+    A::Event: Clone,
+{
+    let state = repository
+        .load(id)
+        .map_err(|e| EffectError::Execution(RepositoryError::Store(e)))?;
+    let (events, effects) = decide(state.map(|loaded| &loaded.state));
+    if events.is_empty() && effects.is_empty() {
+        return Ok(events);
+    }
+    let stream_id = A::stream_id(id);
+    let envelopes = events
+        .iter()
+        .map(|event| Envelope::new(stream_id.clone(), 0, A::event_type(event), event.clone()))
+        .collect::<Vec<_>>();
+    let pending = effects
+        .into_iter()
+        .map(|effect| PendingEffect::new(effect, now))
+        .collect();
+
+    let store = repository.store_mut();
+    store.begin().map_err(EffectError::Transaction)?;
+    let written = match store.save(&envelopes) {
+        Ok(()) => store.enqueue(pending).map_err(EffectError::Store),
+        Err(e) => Err(EffectError::Execution(RepositoryError::Store(e))),
+    };
+    if let Err(e) = written {
+        store.rollback().map_err(EffectError::Transaction)?;
+        return Err(e);
+    }
+    if let Err(e) = store.commit() {
+        let _ = store.rollback();
+        return Err(EffectError::Transaction(e));
+    }
+    repository.evict(id);
+    Ok(events)
+}
+
+/// Summary of an [`EffectRunner::run_due`] run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EffectReport {
+    /// Number of effects executed.
+    pub succeeded: usize,
+    /// Number of effects scheduled for another attempt.
+    pub retried: usize,
+    /// Number of effects given up.
+    pub failed: usize,
+}
+
+/// Runner executing the due effects, retrying failed ones with an exponential backoff.
+pub struct EffectRunner<H, K> {
+    handler: H,
+    clock: K,
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl<H, K: Clock> EffectRunner<H, K> {
+    /// Create a runner attempting effects up to 5 times, waiting 1 second before the first
+    /// retry.
+    pub fn new(handler: H, clock: K) -> Self {
+        Self {
+            handler,
+            clock,
+            max_attempts: 5,
+            backoff: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the number of attempts before an effect is given up.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set the delay before the first retry, doubled on every further retry.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Get the handler.
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Execute up to `limit` due effects.
+    pub fn run_due<F, N>(&mut self, effects: &mut N, limit: usize) -> Result<EffectReport, N::Error>
+    where
+        H: EffectHandler<F>,
+        N: EffectStore<F>,
+    {
+        let now = self.clock.now();
+        let mut report = EffectReport::default();
+        for pending in effects.due(now, limit)? {
+            let error = match self.handler.execute(&pending.id, &pending.effect) {
+                Ok(()) => {
+                    effects.succeed(&pending.id)?;
+                    report.succeeded += 1;
+                    continue;
+                }
+                Err(e) => e.to_string(),
+            };
+            let attempts = pending.attempts + 1;
+            if attempts >= self.max_attempts {
+                effects.fail(&pending.id, error)?;
+                report.failed += 1;
+            } else {
+                let delay = self.backoff.saturating_mul(1 << (attempts - 1).min(16));
+                effects.retry(&pending.id, now + delay, error)?;
+                report.retried += 1;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Effect store keeping the effects in memory.
+#[derive(Debug)]
+pub struct OnMemoryEffectStore<F> {
+    effects: Vec<(PendingEffect<F>, EffectStatus)>,
+}
+
+impl<F> OnMemoryEffectStore<F> {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+        }
+    }
+
+    /// Get the effects with their status, in order of enqueuing.
+    pub fn effects(&self) -> &[(PendingEffect<F>, EffectStatus)] {
+        &self.effects
+    }
+
+    fn find_mut(
+        &mut self,
+        id: &str,
+    ) -> Result<&mut (PendingEffect<F>, EffectStatus), EffectStoreError> {
+        self.effects
+            .iter_mut()
+            .find(|(e, _)| e.id == id)
+            .ok_or_else(|| EffectStoreError::NotFound(id.to_string()))
+    }
+}
+
+impl<F> Default for OnMemoryEffectStore<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Clone> EffectStore<F> for OnMemoryEffectStore<F> {
+    type Error = EffectStoreError;
+
+    fn enqueue(&mut self, effects: Vec<PendingEffect<F>>) -> Result<(), Self::Error> {
+        self.effects
+            .extend(effects.into_iter().map(|e| (e, EffectStatus::Pending)));
+        Ok(())
+    }
+
+    fn due(&self, now: SystemTime, limit: usize) -> Result<Vec<PendingEffect<F>>, Self::Error> {
+        Ok(self
+            .effects
+            .iter()
+            .filter(|(e, s)| *s == EffectStatus::Pending && e.due_at <= now)
+            .take(limit)
+            .map(|(e, _)| e.clone())
+            .collect())
+    }
+
+    fn succeed(&mut self, id: &str) -> Result<(), Self::Error> {
+        self.find_mut(id)?.1 = EffectStatus::Succeeded;
+        Ok(())
+    }
+
+    fn retry(&mut self, id: &str, due_at: SystemTime, error: String) -> Result<(), Self::Error> {
+        let (effect, _) = self.find_mut(id)?;
+        effect.attempts += 1;
+        effect.due_at = due_at;
+        effect.last_error = Some(error);
+        Ok(())
+    }
+
+    fn fail(&mut self, id: &str, error: String) -> Result<(), Self::Error> {
+        let (effect, status) = self.find_mut(id)?;
+        effect.attempts += 1;
+        effect.last_error = Some(error.clone());
+        *status = EffectStatus::Failed(error);
+        Ok(())
+    }
+
+    fn status(&self, id: &str) -> Result<Option<EffectStatus>, Self::Error> {
+        Ok(self
+            .effects
+            .iter()
+            .find(|(e, _)| e.id == id)
+            .map(|(_, s)| s.clone()))
+    }
+}
+
+/// Error returned by the [`OnMemoryEffectStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EffectStoreError {
+    /// No effect with the given ID exists.
+    NotFound(String),
+}
+
+impl fmt::Display for EffectStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EffectStoreError::NotFound(id) => write!(f, "effect not found: {}", id),
+        }
+    }
+}
+
+impl Error for EffectStoreError {}
+
+/// Error returned by [`execute_with_effects`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EffectError<E, TE, NE> {
+    /// The events could not be loaded or saved, and the transaction was rolled back.
+    Execution(RepositoryError<E>),
+    /// The transaction could not be begun, committed or rolled back.
+    Transaction(TE),
+    /// The effects could not be persisted, and the transaction was rolled back.
+    Store(NE),
+}
+
+impl<E: Error, TE: Error, NE: Error> fmt::Display for EffectError<E, TE, NE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EffectError::Execution(e) => write!(f, "execution error: {}", e),
+            EffectError::Transaction(e) => write!(f, "transaction error: {}", e),
+            EffectError::Store(e) => write!(f, "effect store error: {}", e),
+        }
+    }
+}
+
+impl<E: Error, TE: Error, NE: Error> Error for EffectError<E, TE, NE> {}
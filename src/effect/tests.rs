@@ -0,0 +1,243 @@
+use std::fmt;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::event_store::{OnMemoryEventStore, OnMemoryEventStoreError};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Order {
+    placed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderEvent {
+    Placed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderEffect {
+    SendConfirmation(String),
+}
+
+impl Aggregate for Order {
+    type Id = u32;
+    type Event = OrderEvent;
+
+    fn aggregate_type() -> &'static str {
+        "order"
+    }
+
+    fn event_type(_event: &OrderEvent) -> String {
+        "Placed".to_string()
+    }
+
+    fn apply(&mut self, _event: &OrderEvent) {
+        self.placed = true;
+    }
+}
+
+#[derive(Debug)]
+struct MailerDown;
+
+impl fmt::Display for MailerDown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mailer down")
+    }
+}
+
+impl Error for MailerDown {}
+
+/// Store keeping the effects as an outbox next to the events, staging them in the transaction
+/// of the events.
+#[derive(Default)]
+struct OutboxStore {
+    events: OnMemoryEventStore<OrderEvent>,
+    effects: OnMemoryEffectStore<OrderEffect>,
+    staged: Vec<PendingEffect<OrderEffect>>,
+    fail_enqueue: bool,
+}
+
+impl EventStore for OutboxStore {
+    type Persistable = Envelope<OrderEvent>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.events.save(events)
+    }
+}
+
+impl EventLoader for OutboxStore {
+    type StreamId = String;
+    type Persistable = Envelope<OrderEvent>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.events.load(stream_id)
+    }
+}
+
+impl TransactionManager for OutboxStore {
+    type Error = OnMemoryEventStoreError;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.events.begin()
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.events.commit()?;
+        self.effects
+            .enqueue(self.staged.drain(..).collect())
+            .unwrap();
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.staged.clear();
+        self.events.rollback()
+    }
+}
+
+impl EffectStore<OrderEffect> for OutboxStore {
+    type Error = EffectStoreError;
+
+    fn enqueue(&mut self, effects: Vec<PendingEffect<OrderEffect>>) -> Result<(), Self::Error> {
+        if self.fail_enqueue {
+            return Err(EffectStoreError::NotFound("outbox".to_string()));
+        }
+        self.staged.extend(effects);
+        Ok(())
+    }
+
+    fn due(
+        &self,
+        now: SystemTime,
+        limit: usize,
+    ) -> Result<Vec<PendingEffect<OrderEffect>>, Self::Error> {
+        self.effects.due(now, limit)
+    }
+
+    fn succeed(&mut self, id: &str) -> Result<(), Self::Error> {
+        self.effects.succeed(id)
+    }
+
+    fn retry(&mut self, id: &str, due_at: SystemTime, error: String) -> Result<(), Self::Error> {
+        self.effects.retry(id, due_at, error)
+    }
+
+    fn fail(&mut self, id: &str, error: String) -> Result<(), Self::Error> {
+        self.effects.fail(id, error)
+    }
+
+    fn status(&self, id: &str) -> Result<Option<EffectStatus>, Self::Error> {
+        self.effects.status(id)
+    }
+}
+
+fn place(_: Option<&Order>) -> (Vec<OrderEvent>, Vec<OrderEffect>) {
+    (
+        vec![OrderEvent::Placed],
+        vec![OrderEffect::SendConfirmation(
+            "alice@example.com".to_string(),
+        )],
+    )
+}
+
+#[test]
+fn test_roll_back_events_when_effects_fail() {
+    let clock = ManualClock::default();
+    let mut repository = Repository::<Order, _>::new(OutboxStore {
+        fail_enqueue: true,
+        ..OutboxStore::default()
+    });
+    assert_eq!(
+        execute_with_effects(&mut repository, clock.now(), &1, place),
+        Err(EffectError::Store(EffectStoreError::NotFound(
+            "outbox".to_string()
+        )))
+    );
+    assert!(repository.load(&1).unwrap().is_none());
+    assert!(repository.store().effects.effects().is_empty());
+}
+
+#[test]
+fn test_effects_are_persisted_and_retried() {
+    let clock = ManualClock::default();
+    let mut repository = Repository::<Order, _>::new(OutboxStore::default());
+    let events = execute_with_effects(&mut repository, clock.now(), &1, place).unwrap();
+    assert_eq!(events, [OrderEvent::Placed]);
+    assert!(repository.load(&1).unwrap().unwrap().state.placed);
+    let effects = repository.store_mut();
+
+    let mut failures = 2;
+    let mut sent = Vec::new();
+    let mut runner = EffectRunner::new(
+        |_id: &str, effect: &OrderEffect| {
+            if failures > 0 {
+                failures -= 1;
+                return Err(MailerDown);
+            }
+            sent.push(effect.clone());
+            Ok(())
+        },
+        clock.clone(),
+    );
+
+    let retried = EffectReport {
+        retried: 1,
+        ..Default::default()
+    };
+    assert_eq!(runner.run_due(effects, 10).unwrap(), retried);
+    // Not due before the backoff elapsed.
+    assert_eq!(
+        runner.run_due(effects, 10).unwrap(),
+        EffectReport::default()
+    );
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(runner.run_due(effects, 10).unwrap(), retried);
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(
+        runner.run_due(effects, 10).unwrap(),
+        EffectReport::default()
+    );
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(
+        runner.run_due(effects, 10).unwrap(),
+        EffectReport {
+            succeeded: 1,
+            ..Default::default()
+        }
+    );
+    drop(runner);
+    assert_eq!(sent.len(), 1);
+    let (effect, status) = &effects.effects.effects()[0];
+    assert_eq!(*status, EffectStatus::Succeeded);
+    assert_eq!(effect.attempts, 2);
+    assert_eq!(effect.last_error, Some("mailer down".to_string()));
+}
+
+#[test]
+fn test_effect_given_up_after_max_attempts() {
+    let clock = ManualClock::default();
+    let mut effects = OnMemoryEffectStore::new();
+    effects
+        .enqueue(vec![PendingEffect::new("charge", clock.now())])
+        .unwrap();
+    let id = effects.effects()[0].0.id.clone();
+    let mut runner = EffectRunner::new(|_: &str, _: &&str| Err(MailerDown), clock.clone())
+        .with_max_attempts(2)
+        .with_backoff(Duration::ZERO);
+
+    runner.run_due(&mut effects, 10).unwrap();
+    assert_eq!(
+        runner.run_due(&mut effects, 10).unwrap(),
+        EffectReport {
+            failed: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        effects.status(&id).unwrap(),
+        Some(EffectStatus::Failed("mailer down".to_string()))
+    );
+    assert!(effects.due(clock.now(), 10).unwrap().is_empty());
+}
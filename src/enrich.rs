@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Publisher;
+use crate::envelope::Envelope;
+
+/// Types which augment events with data of read models before they are published outside,
+/// such as the name of the organization a user was added to.
+pub trait Enricher<E> {
+    /// Add data to the event. The event is not published if it fails.
+    fn enrich(&self, event: &mut Envelope<E>) -> Result<(), String>;
+}
+
+impl<E, F: Fn(&mut Envelope<E>) -> Result<(), String>> Enricher<E> for F {
+    fn enrich(&self, event: &mut Envelope<E>) -> Result<(), String> {
+        self(event)
+    }
+}
+
+/// Publisher enriching the events with the enrichers declared for their type, before
+/// publishing them with the wrapped publisher.
+///
+/// Events of types without enrichers are published unchanged. The stored events are never
+/// altered, so wrap the publisher given to a [`Republisher`](crate::broker::Republisher) to
+/// enrich events as they are relayed.
+pub struct EnrichingPublisher<P, E> {
+    inner: P,
+    enrichers: HashMap<String, Vec<Box<dyn Enricher<E>>>>,
+}
+
+impl<P, E> EnrichingPublisher<P, E> {
+    /// Wrap the publisher, without enrichers.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            enrichers: HashMap::new(),
+        }
+    }
+
+    /// Enrich the events of the type. Enrichers of a type run in the order they were added.
+    pub fn enrich(
+        mut self,
+        event_type: impl Into<String>,
+        enricher: impl Enricher<E> + 'static,
+    ) -> Self {
+        self.enrichers
+            .entry(event_type.into())
+            .or_default()
+            .push(Box::new(enricher));
+        self
+    }
+
+    /// Get the wrapped publisher.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P, E> Publisher<Envelope<E>> for EnrichingPublisher<P, E>
+where
+    P: Publisher<Envelope<E>>,
+    E: Clone,
+{
+    type Error = EnrichError<P::Error>;
+
+    fn publish(&mut self, message: &Envelope<E>) -> Result<(), Self::Error> {
+        let enrichers = match self.enrichers.get(&message.event_type) {
+            Some(enrichers) => enrichers,
+            None => return self.inner.publish(message).map_err(EnrichError::Inner),
+        };
+        let mut message = message.clone();
+        for enricher in enrichers {
+            enricher
+                .enrich(&mut message)
+                .map_err(|reason| EnrichError::Enrichment {
+                    event_type: message.event_type.clone(),
+                    reason,
+                })?;
+        }
+        self.inner.publish(&message).map_err(EnrichError::Inner)
+    }
+}
+
+/// Error returned by the [`EnrichingPublisher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrichError<E> {
+    /// An enricher of the event failed, e.g. as its read model lacks the data.
+    Enrichment {
+        /// Type of the event.
+        event_type: String,
+        /// Why the enricher failed.
+        reason: String,
+    },
+    /// The wrapped publisher failed.
+    Inner(E),
+}
+
+impl<E: Error> fmt::Display for EnrichError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnrichError::Enrichment { event_type, reason } => {
+                write!(f, "failed to enrich {}: {}", event_type, reason)
+            }
+            EnrichError::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for EnrichError<E> {}
@@ -0,0 +1,115 @@
+use std::rc::Rc;
+
+use super::*;
+use crate::broker::{OnMemoryBroker, PublisherExt, Republisher, Subscription};
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore};
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrgEvent {
+    Created {
+        name: String,
+    },
+    UserAdded {
+        user_id: u32,
+        org_name: Option<String>,
+    },
+}
+
+/// Read model of the names of the organizations, keyed by stream.
+type OrgNames = Rc<HashMap<String, String>>;
+
+fn org_name(names: OrgNames) -> impl Fn(&mut Envelope<OrgEvent>) -> Result<(), String> {
+    move |event| {
+        let name = names
+            .get(&event.stream_id)
+            .ok_or_else(|| format!("no name for {}", event.stream_id))?;
+        if let OrgEvent::UserAdded { org_name, .. } = &mut event.payload {
+            *org_name = Some(name.clone());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_enrich_relayed_events() {
+    let mut store = OnMemoryEventStore::new();
+    for (stream_id, event_type, event) in [
+        (
+            "org-1",
+            "Created",
+            OrgEvent::Created {
+                name: "Acme".to_string(),
+            },
+        ),
+        (
+            "org-1",
+            "UserAdded",
+            OrgEvent::UserAdded {
+                user_id: 7,
+                org_name: None,
+            },
+        ),
+    ] {
+        store
+            .save(&[Envelope::new(stream_id, 0, event_type, event)])
+            .unwrap();
+    }
+    let names = Rc::new(HashMap::from([("org-1".to_string(), "Acme".to_string())]));
+
+    let mut publisher = OnMemoryBroker::new()
+        .enriched()
+        .enrich("UserAdded", org_name(names.clone()))
+        .enrich("UserAdded", |event: &mut Envelope<OrgEvent>| {
+            event
+                .metadata
+                .insert("enriched".to_string(), "true".to_string());
+            Ok(())
+        });
+    Republisher::new(10)
+        .republish(&store, None, &mut publisher)
+        .unwrap();
+
+    let mut broker = publisher.inner;
+    let created = broker.poll().unwrap().unwrap();
+    assert!(!created.metadata.contains_key("enriched"));
+    let added = broker.poll().unwrap().unwrap();
+    assert_eq!(
+        added.payload,
+        OrgEvent::UserAdded {
+            user_id: 7,
+            org_name: Some("Acme".to_string())
+        }
+    );
+    assert_eq!(added.metadata["enriched"], "true");
+    // The stored event is left unchanged.
+    assert_eq!(
+        store.load(&"org-1".to_string()).unwrap()[1].payload,
+        OrgEvent::UserAdded {
+            user_id: 7,
+            org_name: None
+        }
+    );
+}
+
+#[test]
+fn test_failed_enrichment_is_not_published() {
+    let mut publisher = EnrichingPublisher::new(OnMemoryBroker::new())
+        .enrich("UserAdded", org_name(Rc::new(HashMap::new())));
+    let event = Envelope::new(
+        "org-2",
+        0,
+        "UserAdded",
+        OrgEvent::UserAdded {
+            user_id: 1,
+            org_name: None,
+        },
+    );
+    assert_eq!(
+        publisher.publish(&event),
+        Err(EnrichError::Enrichment {
+            event_type: "UserAdded".to_string(),
+            reason: "no name for org-2".to_string()
+        })
+    );
+    assert!(publisher.inner().is_empty());
+}
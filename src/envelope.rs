@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use crate::event_id::EventId;
+
+/// Metadata key recording when the event occurred, in RFC 3339 format.
+pub const OCCURRED_AT_METADATA_KEY: &str = "occurred_at";
+
+/// Metadata attached to an event, as key-value pairs.
+pub type Metadata = BTreeMap<String, String>;
+
+/// An event together with its stream position and metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope<E> {
+    /// Unique ID of the event.
+    pub id: EventId,
+    /// ID of the stream the event belongs to.
+    pub stream_id: String,
+    /// Version of the stream after the event, starting at 1.
+    pub version: u64,
+    /// Position of the event in the global log, starting at 0, assigned by the store.
+    pub position: u64,
+    /// Name of the event type.
+    pub event_type: String,
+    /// The event.
+    pub payload: E,
+    /// Metadata of the event.
+    pub metadata: Metadata,
+}
+
+impl<E> Envelope<E> {
+    /// Create an envelope without metadata, with a newly generated ID.
+    pub fn new(
+        stream_id: impl Into<String>,
+        version: u64,
+        event_type: impl Into<String>,
+        payload: E,
+    ) -> Self {
+        Self {
+            id: EventId::generate(),
+            stream_id: stream_id.into(),
+            version,
+            position: 0,
+            event_type: event_type.into(),
+            payload,
+            metadata: Metadata::new(),
+        }
+    }
+
+    /// Set the ID of the event.
+    pub fn with_id(mut self, id: EventId) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Add a metadata entry to the envelope.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Transform the payload, keeping the ID, position and metadata.
+    pub fn map<F>(self, f: impl FnOnce(E) -> F) -> Envelope<F> {
+        Envelope {
+            id: self.id,
+            stream_id: self.stream_id,
+            version: self.version,
+            position: self.position,
+            event_type: self.event_type,
+            payload: f(self.payload),
+            metadata: self.metadata,
+        }
+    }
+}
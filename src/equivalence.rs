@@ -0,0 +1,268 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::{AppendError, EventStore, ExpectedVersion, ReadOnlyEventStore};
+use crate::rng::SeededRng;
+
+/// Operation run against both stores by the [`EquivalenceHarness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreOp {
+    /// Save the number of events to the stream.
+    Save { stream_id: String, count: usize },
+    /// Append the number of events to each stream at once, expecting their versions.
+    AppendMulti(Vec<(String, ExpectedVersion, usize)>),
+    /// Load the stream.
+    Load(String),
+    /// Read the log from the position.
+    ReadAll { from: u64, limit: usize },
+}
+
+/// An event as observed through a store, the parts every backend must agree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedEvent<E> {
+    /// ID of the stream.
+    pub stream_id: String,
+    /// Version of the event in its stream.
+    pub version: u64,
+    /// Position of the event in the log.
+    pub position: u64,
+    /// Type of the event.
+    pub event_type: String,
+    /// The event.
+    pub payload: E,
+}
+
+impl<E: Clone> From<&Envelope<E>> for ObservedEvent<E> {
+    fn from(envelope: &Envelope<E>) -> Self {
+        Self {
+            stream_id: envelope.stream_id.clone(),
+            version: envelope.version,
+            position: envelope.position,
+            event_type: envelope.event_type.clone(),
+            payload: envelope.payload.clone(),
+        }
+    }
+}
+
+/// Observable outcome of a [`StoreOp`]. Errors are compared by kind only, since each backend
+/// has its own error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Observation<E> {
+    /// Outcome of a save: whether it succeeded.
+    Saved(bool),
+    /// Outcome of an atomic append.
+    Appended(Result<(), AppendError<()>>),
+    /// Events loaded, or `None` if loading failed.
+    Loaded(Option<Vec<ObservedEvent<E>>>),
+    /// Events read, or `None` if reading failed.
+    Read(Option<Vec<ObservedEvent<E>>>),
+}
+
+/// Harness checking that two event stores behave the same, e.g. a new backend against the
+/// [`OnMemoryEventStore`](crate::event_store::OnMemoryEventStore) reference.
+///
+/// A random sequence of operations, generated from the seed, is run against both stores, and
+/// their observations compared after every step. The same seed always yields the same
+/// operations, so that a divergence can be replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EquivalenceHarness {
+    seed: u64,
+    streams: u64,
+    operations: usize,
+}
+
+impl EquivalenceHarness {
+    /// Create a harness running 100 operations over 4 streams.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: 4,
+            operations: 100,
+        }
+    }
+
+    /// Spread the operations over the number of streams.
+    pub fn streams(mut self, streams: u64) -> Self {
+        self.streams = streams.max(1);
+        self
+    }
+
+    /// Run the number of operations.
+    pub fn operations(mut self, operations: usize) -> Self {
+        self.operations = operations;
+        self
+    }
+
+    /// Generate the operations of the seed.
+    pub fn generate(&self) -> Vec<StoreOp> {
+        let mut rng = SeededRng::new(self.seed);
+        let stream = |rng: &mut SeededRng| format!("stream-{}", rng.below(self.streams));
+        (0..self.operations)
+            .map(|_| match rng.below(4) {
+                0 => StoreOp::Save {
+                    stream_id: stream(&mut rng),
+                    count: 1 + rng.below(3) as usize,
+                },
+                1 => {
+                    let appends = (0..1 + rng.below(2))
+                        .map(|_| {
+                            let expected = match rng.below(3) {
+                                0 => ExpectedVersion::Any,
+                                1 => ExpectedVersion::NoStream,
+                                _ => ExpectedVersion::Exact(rng.below(6)),
+                            };
+                            (stream(&mut rng), expected, 1 + rng.below(2) as usize)
+                        })
+                        .collect();
+                    StoreOp::AppendMulti(appends)
+                }
+                2 => StoreOp::Load(stream(&mut rng)),
+                _ => StoreOp::ReadAll {
+                    from: rng.below(10),
+                    limit: 1 + rng.below(5) as usize,
+                },
+            })
+            .collect()
+    }
+
+    /// Run the operations against both stores, creating the `n`th event of the run with
+    /// `event`. Returns the number of operations run, or the first divergence.
+    pub fn run<L, R, E>(
+        &self,
+        left: &mut L,
+        right: &mut R,
+        event: impl Fn(usize) -> E,
+    ) -> Result<usize, Box<Divergence<E>>>
+    where
+        L: EventStore<Persistable = Envelope<E>>
+            + ReadOnlyEventStore<StreamId = String, Persistable = Envelope<E>>,
+        R: EventStore<Persistable = Envelope<E>>
+            + ReadOnlyEventStore<StreamId = String, Persistable = Envelope<E>>,
+        E: Clone + PartialEq,
+    {
+        let mut created = 0;
+        let mut envelopes = |stream_id: &str, count: usize| {
+            (0..count)
+                .map(|_| {
+                    created += 1;
+                    Envelope::new(stream_id, 0, "Generated", event(created - 1))
+                })
+                .collect::<Vec<_>>()
+        };
+        let operations = self.generate();
+        for (step, op) in operations.iter().enumerate() {
+            let (left_observed, right_observed) = match op {
+                StoreOp::Save { stream_id, count } => {
+                    let events = envelopes(stream_id, *count);
+                    (
+                        Observation::Saved(EventStore::save(left, &events).is_ok()),
+                        Observation::Saved(EventStore::save(right, &events).is_ok()),
+                    )
+                }
+                StoreOp::AppendMulti(appends) => {
+                    let appends = appends
+                        .iter()
+                        .map(|(stream_id, expected, count)| {
+                            (stream_id.clone(), *expected, envelopes(stream_id, *count))
+                        })
+                        .collect::<Vec<_>>();
+                    (
+                        Observation::Appended(
+                            left.append_multi(&appends).map_err(|e| e.map_store(|_| ())),
+                        ),
+                        Observation::Appended(
+                            right
+                                .append_multi(&appends)
+                                .map_err(|e| e.map_store(|_| ())),
+                        ),
+                    )
+                }
+                StoreOp::Load(stream_id) => (
+                    Observation::Loaded(observe(left.load(stream_id))),
+                    Observation::Loaded(observe(right.load(stream_id))),
+                ),
+                StoreOp::ReadAll { from, limit } => (
+                    Observation::Read(observe(left.read_all(*from, *limit))),
+                    Observation::Read(observe(right.read_all(*from, *limit))),
+                ),
+            };
+            if left_observed != right_observed {
+                return Err(Box::new(Divergence {
+                    seed: self.seed,
+                    step,
+                    operations: operations[..=step].to_vec(),
+                    left: left_observed,
+                    right: right_observed,
+                }));
+            }
+        }
+        Ok(operations.len())
+    }
+
+    /// Run the harness for `cases` consecutive seeds from the seed, against fresh stores
+    /// created for each case. Returns the first divergence.
+    pub fn check<L, R, E>(
+        &self,
+        cases: u64,
+        mut left: impl FnMut() -> L,
+        mut right: impl FnMut() -> R,
+        event: impl Fn(usize) -> E,
+    ) -> Result<(), Box<Divergence<E>>>
+    where
+        L: EventStore<Persistable = Envelope<E>>
+            + ReadOnlyEventStore<StreamId = String, Persistable = Envelope<E>>,
+        R: EventStore<Persistable = Envelope<E>>
+            + ReadOnlyEventStore<StreamId = String, Persistable = Envelope<E>>,
+        E: Clone + PartialEq,
+    {
+        for case in 0..cases {
+            let harness = Self {
+                seed: self.seed.wrapping_add(case),
+                ..*self
+            };
+            harness.run(&mut left(), &mut right(), &event)?;
+        }
+        Ok(())
+    }
+}
+
+fn observe<E: Clone, SE>(result: Result<Vec<Envelope<E>>, SE>) -> Option<Vec<ObservedEvent<E>>> {
+    result
+        .ok()
+        .map(|events| events.iter().map(ObservedEvent::from).collect())
+}
+
+/// First operation after which two stores observed different outcomes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence<E> {
+    /// Seed of the operations.
+    pub seed: u64,
+    /// Index of the diverging operation.
+    pub step: usize,
+    /// Operations run up to the diverging one, to replay it.
+    pub operations: Vec<StoreOp>,
+    /// Outcome observed through the left store.
+    pub left: Observation<E>,
+    /// Outcome observed through the right store.
+    pub right: Observation<E>,
+}
+
+impl<E: fmt::Debug> fmt::Display for Divergence<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stores diverged at step {} of seed {} ({:?}): {:?} != {:?}",
+            self.step,
+            self.seed,
+            self.operations.last(),
+            self.left,
+            self.right
+        )
+    }
+}
+
+impl<E: fmt::Debug> Error for Divergence<E> {}
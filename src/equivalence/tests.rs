@@ -0,0 +1,71 @@
+use super::*;
+use crate::event_store::{EventLoader, OnMemoryEventStore, OnMemoryEventStoreError};
+
+/// Port of the in-memory store which does not support atomic appends.
+#[derive(Default)]
+struct PartialPort(OnMemoryEventStore<u32>);
+
+impl EventStore for PartialPort {
+    type Persistable = Envelope<u32>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.0.save(events)
+    }
+}
+
+impl EventLoader for PartialPort {
+    type StreamId = String;
+    type Persistable = Envelope<u32>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.load(stream_id)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[String],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.0.read_multi(stream_ids)
+    }
+}
+
+impl ReadOnlyEventStore for PartialPort {
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.read_all(from, limit)
+    }
+}
+
+#[test]
+fn test_equivalent_stores() {
+    let harness = EquivalenceHarness::new(7).streams(3).operations(50);
+    assert_eq!(harness.generate(), harness.generate());
+    assert_eq!(
+        harness.check(20, OnMemoryEventStore::new, OnMemoryEventStore::new, |n| n
+            as u32),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_divergence() {
+    let harness = EquivalenceHarness::new(7);
+    let divergence = harness
+        .run(
+            &mut OnMemoryEventStore::new(),
+            &mut PartialPort::default(),
+            |n| n as u32,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        divergence.operations.last(),
+        Some(StoreOp::AppendMulti(_))
+    ));
+    assert_eq!(divergence.operations.len(), divergence.step + 1);
+    assert_eq!(
+        divergence.right,
+        Observation::Appended(Err(AppendError::Unsupported))
+    );
+    assert_ne!(divergence.left, divergence.right);
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::SystemTime;
+
+use crate::envelope::{Envelope, OCCURRED_AT_METADATA_KEY};
+use crate::rfc3339;
+
+/// Types which represent a domain event.
+///
+/// Implementing it lets generic machinery, such as stores, brokers and projections, route and
+/// describe events without a bespoke enum per use.
+pub trait Event {
+    /// Get the name of the event type.
+    fn event_type(&self) -> &str;
+    /// Get the ID of the stream the event belongs to.
+    fn stream_id(&self) -> String;
+    /// Get the time the event occurred at, if known.
+    fn occurred_at(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+impl<E> Event for Envelope<E> {
+    fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    fn stream_id(&self) -> String {
+        self.stream_id.clone()
+    }
+
+    /// Read the time from the `occurred_at` metadata, in RFC 3339 format.
+    fn occurred_at(&self) -> Option<SystemTime> {
+        self.metadata
+            .get(OCCURRED_AT_METADATA_KEY)
+            .and_then(|value| rfc3339::parse(value))
+    }
+}
+
+impl<E: Event> Envelope<E> {
+    /// Wrap the event, taking the stream ID, event type and occurrence time from it. The
+    /// version is assigned by the store.
+    pub fn from_event(event: E) -> Self {
+        let envelope = Envelope::new(event.stream_id(), 0, event.event_type().to_string(), event);
+        match envelope.payload.occurred_at() {
+            Some(at) => envelope.with_occurred_at(at),
+            None => envelope,
+        }
+    }
+}
+
+impl<E> Envelope<E> {
+    /// Record the time the event occurred at in the metadata.
+    pub fn with_occurred_at(self, at: SystemTime) -> Self {
+        self.with_metadata(OCCURRED_AT_METADATA_KEY, rfc3339::format(at))
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::*;
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore};
+
+#[derive(Debug, Clone, PartialEq)]
+enum CartEvent {
+    ItemAdded { cart: u32, at: SystemTime },
+    CheckedOut { cart: u32 },
+}
+
+impl Event for CartEvent {
+    fn event_type(&self) -> &str {
+        match self {
+            CartEvent::ItemAdded { .. } => "ItemAdded",
+            CartEvent::CheckedOut { .. } => "CheckedOut",
+        }
+    }
+
+    fn stream_id(&self) -> String {
+        match self {
+            CartEvent::ItemAdded { cart, .. } | CartEvent::CheckedOut { cart } => {
+                format!("cart-{}", cart)
+            }
+        }
+    }
+
+    fn occurred_at(&self) -> Option<SystemTime> {
+        match self {
+            CartEvent::ItemAdded { at, .. } => Some(*at),
+            CartEvent::CheckedOut { .. } => None,
+        }
+    }
+}
+
+#[test]
+fn test_envelope_from_event() {
+    let at = UNIX_EPOCH + Duration::from_millis(1_704_067_200_250);
+    let envelope = Envelope::from_event(CartEvent::ItemAdded { cart: 1, at });
+    assert_eq!(envelope.stream_id, "cart-1");
+    assert_eq!(envelope.event_type, "ItemAdded");
+    assert_eq!(
+        envelope.metadata[OCCURRED_AT_METADATA_KEY],
+        "2024-01-01T00:00:00.250Z"
+    );
+    assert_eq!(Event::occurred_at(&envelope), Some(at));
+
+    let envelope = Envelope::from_event(CartEvent::CheckedOut { cart: 1 });
+    assert!(envelope.metadata.is_empty());
+    assert_eq!(Event::occurred_at(&envelope), None);
+}
+
+#[test]
+fn test_generic_machinery_over_events() {
+    fn save_all<S, E>(store: &mut S, events: Vec<E>)
+    where
+        S: EventStore<Persistable = Envelope<E>>,
+        E: Event,
+    {
+        let envelopes = events
+            .into_iter()
+            .map(Envelope::from_event)
+            .collect::<Vec<_>>();
+        store.save(&envelopes).unwrap();
+    }
+
+    let mut store = OnMemoryEventStore::new();
+    save_all(
+        &mut store,
+        vec![
+            CartEvent::ItemAdded {
+                cart: 1,
+                at: UNIX_EPOCH,
+            },
+            CartEvent::CheckedOut { cart: 2 },
+            CartEvent::CheckedOut { cart: 1 },
+        ],
+    );
+    let events = store.load(&"cart-1".to_string()).unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.event_type()).collect::<Vec<_>>(),
+        vec!["ItemAdded", "CheckedOut"]
+    );
+    assert_eq!(events[1].version, 2);
+}
+
+#[test]
+fn test_occurred_at_parses_rfc3339() {
+    let envelope = Envelope::new("cart-1", 1, "CheckedOut", ())
+        .with_metadata(OCCURRED_AT_METADATA_KEY, "2024-03-01T09:30:00.5+09:00");
+    assert_eq!(
+        Event::occurred_at(&envelope),
+        Some(UNIX_EPOCH + Duration::from_millis(1_709_253_000_500))
+    );
+    for invalid in ["2024-03-01", "2024-13-01T00:00:00Z", "2024-03-01T00:00:00"] {
+        let envelope = envelope
+            .clone()
+            .with_metadata(OCCURRED_AT_METADATA_KEY, invalid);
+        assert_eq!(Event::occurred_at(&envelope), None, "{}", invalid);
+    }
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests;
+
+use crate::registry::EventTypeRegistry;
+
+type Node<'a> = (&'static str, &'a str);
+
+#[derive(Debug, Clone)]
+struct CommandNode {
+    name: String,
+    produces: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct HandlerNode {
+    name: String,
+    consumes: Vec<String>,
+    dispatches: Vec<String>,
+}
+
+/// Graph of which commands produce which events and which handlers consume them, exported
+/// as DOT or Mermaid to document the event flows of the application.
+///
+/// Events are taken from the type registry, and commands and handlers (sagas, policies,
+/// projections) are declared alongside their registration.
+#[derive(Debug, Clone, Default)]
+pub struct EventFlow {
+    events: Vec<String>,
+    commands: Vec<CommandNode>,
+    handlers: Vec<HandlerNode>,
+}
+
+impl EventFlow {
+    /// Create a graph holding the event types of the registry.
+    pub fn from_registry(registry: &EventTypeRegistry) -> Self {
+        let mut events = registry
+            .descriptors()
+            .iter()
+            .map(|d| d.name.clone())
+            .collect::<Vec<_>>();
+        events.sort();
+        events.dedup();
+        Self {
+            events,
+            ..Self::default()
+        }
+    }
+
+    /// Declare the events the command produces.
+    pub fn command(mut self, name: impl Into<String>, produces: &[&str]) -> Self {
+        self.commands.push(CommandNode {
+            name: name.into(),
+            produces: produces.iter().map(|e| e.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Declare the events the handler consumes and the commands it dispatches.
+    pub fn handler(
+        mut self,
+        name: impl Into<String>,
+        consumes: &[&str],
+        dispatches: &[&str],
+    ) -> Self {
+        self.handlers.push(HandlerNode {
+            name: name.into(),
+            consumes: consumes.iter().map(|e| e.to_string()).collect(),
+            dispatches: dispatches.iter().map(|c| c.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Get the events referenced by commands or handlers but missing from the registry.
+    pub fn unregistered_events(&self) -> Vec<&str> {
+        let mut unregistered = Vec::new();
+        let referenced = self
+            .commands
+            .iter()
+            .flat_map(|c| &c.produces)
+            .chain(self.handlers.iter().flat_map(|h| &h.consumes));
+        for event in referenced {
+            if !self.events.contains(event) && !unregistered.contains(&event.as_str()) {
+                unregistered.push(event.as_str());
+            }
+        }
+        unregistered
+    }
+
+    /// Render the graph in the DOT language of Graphviz.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph event_flow {\n    rankdir=LR;\n");
+        for command in self.command_names() {
+            out.push_str(&format!(
+                "    {} [label={}, shape=box];\n",
+                dot_id("command", command),
+                dot_string(command)
+            ));
+        }
+        for event in self.event_names() {
+            out.push_str(&format!(
+                "    {} [label={}, shape=ellipse];\n",
+                dot_id("event", event),
+                dot_string(event)
+            ));
+        }
+        for handler in &self.handlers {
+            out.push_str(&format!(
+                "    {} [label={}, shape=hexagon];\n",
+                dot_id("handler", &handler.name),
+                dot_string(&handler.name)
+            ));
+        }
+        for (from, to) in self.edges() {
+            out.push_str(&format!(
+                "    {} -> {};\n",
+                dot_id(from.0, from.1),
+                dot_id(to.0, to.1)
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the graph as a Mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        let commands = self.command_names();
+        let events = self.event_names();
+        let id = |kind: &str, name: &str| match kind {
+            "command" => format!(
+                "c{}",
+                commands.iter().position(|c| *c == name).unwrap_or_default()
+            ),
+            "event" => format!(
+                "e{}",
+                events.iter().position(|e| *e == name).unwrap_or_default()
+            ),
+            _ => format!(
+                "h{}",
+                self.handlers
+                    .iter()
+                    .position(|h| h.name == name)
+                    .unwrap_or_default()
+            ),
+        };
+        let mut out = String::from("flowchart LR\n");
+        for command in &commands {
+            out.push_str(&format!(
+                "    {}[{}]\n",
+                id("command", command),
+                mermaid_string(command)
+            ));
+        }
+        for event in &events {
+            out.push_str(&format!(
+                "    {}([{}])\n",
+                id("event", event),
+                mermaid_string(event)
+            ));
+        }
+        for handler in &self.handlers {
+            out.push_str(&format!(
+                "    {}{{{{{}}}}}\n",
+                id("handler", &handler.name),
+                mermaid_string(&handler.name)
+            ));
+        }
+        for (from, to) in self.edges() {
+            out.push_str(&format!(
+                "    {} --> {}\n",
+                id(from.0, from.1),
+                id(to.0, to.1)
+            ));
+        }
+        out
+    }
+
+    fn command_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        let declared = self.commands.iter().map(|c| &c.name);
+        for name in declared.chain(self.handlers.iter().flat_map(|h| &h.dispatches)) {
+            if !names.contains(&name.as_str()) {
+                names.push(name.as_str());
+            }
+        }
+        names
+    }
+
+    fn event_names(&self) -> Vec<&str> {
+        let mut names = self.events.iter().map(String::as_str).collect::<Vec<_>>();
+        names.extend(self.unregistered_events());
+        names
+    }
+
+    fn edges(&self) -> Vec<(Node<'_>, Node<'_>)> {
+        let mut edges = Vec::new();
+        for command in &self.commands {
+            for event in &command.produces {
+                edges.push((
+                    ("command", command.name.as_str()),
+                    ("event", event.as_str()),
+                ));
+            }
+        }
+        for handler in &self.handlers {
+            for event in &handler.consumes {
+                edges.push((
+                    ("event", event.as_str()),
+                    ("handler", handler.name.as_str()),
+                ));
+            }
+            for command in &handler.dispatches {
+                edges.push((
+                    ("handler", handler.name.as_str()),
+                    ("command", command.as_str()),
+                ));
+            }
+        }
+        edges
+    }
+}
+
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn dot_id(kind: &str, name: &str) -> String {
+    dot_string(&format!("{}:{}", kind, name))
+}
+
+fn mermaid_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
@@ -0,0 +1,80 @@
+use super::*;
+
+use crate::registry::EventTypeDescriptor;
+
+fn flow() -> EventFlow {
+    let mut registry = EventTypeRegistry::new();
+    for (name, version) in [("OrderPlaced", 1), ("OrderShipped", 1), ("OrderPlaced", 2)] {
+        registry
+            .register_descriptor(EventTypeDescriptor {
+                name: name.to_string(),
+                version,
+                schema: "{}".to_string(),
+            })
+            .unwrap();
+    }
+    EventFlow::from_registry(&registry)
+        .command("PlaceOrder", &["OrderPlaced"])
+        .command("ShipOrder", &["OrderShipped"])
+        .handler("Shipping", &["OrderPlaced"], &["ShipOrder"])
+        .handler(
+            "Billing",
+            &["OrderShipped", "InvoiceSent"],
+            &["SendInvoice"],
+        )
+}
+
+#[test]
+fn test_unregistered_events() {
+    assert_eq!(flow().unregistered_events(), ["InvoiceSent"]);
+}
+
+#[test]
+fn test_to_dot() {
+    assert_eq!(
+        flow().to_dot(),
+        r#"digraph event_flow {
+    rankdir=LR;
+    "command:PlaceOrder" [label="PlaceOrder", shape=box];
+    "command:ShipOrder" [label="ShipOrder", shape=box];
+    "command:SendInvoice" [label="SendInvoice", shape=box];
+    "event:OrderPlaced" [label="OrderPlaced", shape=ellipse];
+    "event:OrderShipped" [label="OrderShipped", shape=ellipse];
+    "event:InvoiceSent" [label="InvoiceSent", shape=ellipse];
+    "handler:Shipping" [label="Shipping", shape=hexagon];
+    "handler:Billing" [label="Billing", shape=hexagon];
+    "command:PlaceOrder" -> "event:OrderPlaced";
+    "command:ShipOrder" -> "event:OrderShipped";
+    "event:OrderPlaced" -> "handler:Shipping";
+    "handler:Shipping" -> "command:ShipOrder";
+    "event:OrderShipped" -> "handler:Billing";
+    "event:InvoiceSent" -> "handler:Billing";
+    "handler:Billing" -> "command:SendInvoice";
+}
+"#
+    );
+}
+
+#[test]
+fn test_to_mermaid() {
+    assert_eq!(
+        flow().to_mermaid(),
+        r#"flowchart LR
+    c0["PlaceOrder"]
+    c1["ShipOrder"]
+    c2["SendInvoice"]
+    e0(["OrderPlaced"])
+    e1(["OrderShipped"])
+    e2(["InvoiceSent"])
+    h0{{"Shipping"}}
+    h1{{"Billing"}}
+    c0 --> e0
+    c1 --> e1
+    e0 --> h0
+    h0 --> c1
+    e1 --> h1
+    e2 --> h1
+    h1 --> c2
+"#
+    );
+}
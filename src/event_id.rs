@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RAND_B_MASK: u128 = (1 << 62) - 1;
+const RAND_A_MASK: u128 = (1 << 12) - 1;
+
+static LAST_GENERATED: Mutex<u128> = Mutex::new(0);
+
+/// Unique ID of an event, a UUID. IDs are generated as UUIDv7, so their order follows their
+/// creation time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventId(u128);
+
+impl EventId {
+    /// The nil UUID, all bits zero.
+    pub const NIL: EventId = EventId(0);
+
+    /// Generate a UUIDv7 for the current time.
+    ///
+    /// IDs generated by the process are strictly increasing, even within the same millisecond.
+    pub fn generate() -> Self {
+        let candidate = Self::v7(SystemTime::now(), random_bits());
+        let mut last = LAST_GENERATED.lock().unwrap_or_else(|e| e.into_inner());
+        let id = if candidate.0 > *last {
+            candidate
+        } else {
+            EventId(*last).successor()
+        };
+        *last = id.0;
+        id
+    }
+
+    /// Create a UUIDv7 from the time and random bits, for deterministic generation.
+    ///
+    /// Only the 74 low bits of `random` are used.
+    pub fn v7(time: SystemTime, random: u128) -> Self {
+        let millis = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+            & ((1 << 48) - 1);
+        let rand_a = (random >> 62) & RAND_A_MASK;
+        let rand_b = random & RAND_B_MASK;
+        EventId(millis << 80 | 0x7 << 76 | rand_a << 64 | 0b10 << 62 | rand_b)
+    }
+
+    /// Create an ID from its 128 bits.
+    pub fn from_u128(value: u128) -> Self {
+        EventId(value)
+    }
+
+    /// Get the 128 bits of the ID.
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+
+    /// Get the UUID version of the ID.
+    pub fn version(&self) -> u8 {
+        ((self.0 >> 76) & 0xf) as u8
+    }
+
+    /// Get the time the ID was created at, if it is a UUIDv7.
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        if self.version() != 7 {
+            return None;
+        }
+        let millis = (self.0 >> 80) as u64;
+        Some(UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    // Next UUIDv7 in order, incrementing the random bits and then the time.
+    fn successor(self) -> Self {
+        let millis = self.0 >> 80;
+        let rand_a = (self.0 >> 64) & RAND_A_MASK;
+        let rand_b = self.0 & RAND_B_MASK;
+        let (millis, rand_a, rand_b) = if rand_b < RAND_B_MASK {
+            (millis, rand_a, rand_b + 1)
+        } else if rand_a < RAND_A_MASK {
+            (millis, rand_a + 1, 0)
+        } else {
+            (millis + 1, 0, 0)
+        };
+        let time = UNIX_EPOCH + Duration::from_millis(millis as u64);
+        Self::v7(time, rand_a << 62 | rand_b)
+    }
+}
+
+impl Default for EventId {
+    fn default() -> Self {
+        Self::generate()
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+}
+
+impl FromStr for EventId {
+    type Err = EventIdError;
+
+    /// Parse the hyphenated form of a UUID.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || EventIdError::Invalid(s.to_string());
+        let groups = s.split('-').map(str::len).collect::<Vec<_>>();
+        if groups != [8, 4, 4, 4, 12] {
+            return Err(invalid());
+        }
+        let hex = s.replace('-', "");
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        u128::from_str_radix(&hex, 16)
+            .map(EventId)
+            .map_err(|_| invalid())
+    }
+}
+
+fn random_bits() -> u128 {
+    let state = RandomState::new();
+    let mut high = state.build_hasher();
+    high.write_u8(0);
+    let mut low = state.build_hasher();
+    low.write_u8(1);
+    (high.finish() as u128) << 64 | low.finish() as u128
+}
+
+/// Error type of [`EventId`] parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventIdError {
+    /// The string is not a hyphenated UUID.
+    Invalid(String),
+}
+
+impl fmt::Display for EventIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventIdError::Invalid(s) => write!(f, "invalid event id: {}", s),
+        }
+    }
+}
+
+impl Error for EventIdError {}
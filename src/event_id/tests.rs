@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn test_v7_layout_and_timestamp() {
+    let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let id = EventId::v7(time, u128::MAX);
+    assert_eq!(id.version(), 7);
+    assert_eq!(id.timestamp(), Some(time));
+    // Variant bits are 0b10.
+    assert_eq!((id.as_u128() >> 62) & 0b11, 0b10);
+    assert_eq!(EventId::NIL.timestamp(), None);
+}
+
+#[test]
+fn test_generated_ids_are_ordered() {
+    let ids = (0..1000).map(|_| EventId::generate()).collect::<Vec<_>>();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert!(ids.iter().all(|id| id.version() == 7));
+
+    let earlier = EventId::v7(UNIX_EPOCH + Duration::from_millis(1), u128::MAX);
+    let later = EventId::v7(UNIX_EPOCH + Duration::from_millis(2), 0);
+    assert!(earlier < later);
+}
+
+#[test]
+fn test_successor_carries_into_time() {
+    let time = UNIX_EPOCH + Duration::from_millis(5);
+    let id = EventId::v7(time, u128::MAX).successor();
+    assert_eq!(id.timestamp(), Some(UNIX_EPOCH + Duration::from_millis(6)));
+    assert_eq!(id.version(), 7);
+}
+
+#[test]
+fn test_display_and_parse() {
+    let id = EventId::from_u128(0x0189_3c5e_1a2b_7c3d_8e4f_5a6b_7c8d_9e0f);
+    assert_eq!(id.to_string(), "01893c5e-1a2b-7c3d-8e4f-5a6b7c8d9e0f");
+    assert_eq!("01893c5e-1a2b-7c3d-8e4f-5a6b7c8d9e0f".parse(), Ok(id));
+    assert_eq!(
+        "01893c5e1a2b7c3d8e4f5a6b7c8d9e0f".parse::<EventId>(),
+        Err(EventIdError::Invalid(
+            "01893c5e1a2b7c3d8e4f5a6b7c8d9e0f".to_string()
+        ))
+    );
+    assert!("01893c5e-1a2b-7c3d-8e4f-5a6b7c8d9e0g"
+        .parse::<EventId>()
+        .is_err());
+}
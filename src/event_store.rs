@@ -1,7 +1,14 @@
 #[cfg(test)]
 mod tests;
 
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerStore};
+use crate::clock::Clock;
+use crate::envelope::Envelope;
+use crate::interceptor::{InterceptedEventStore, Interceptor};
 
 /// Types which have transaction management capabilities.
 pub trait TransactionManager {
@@ -27,8 +34,116 @@ pub trait EventStore {
 
     /// Save the events.
     fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error>;
+
+    /// Append events to several streams atomically: either every append succeeds, or none does.
+    ///
+    /// Backends which cannot append to several streams atomically keep the default
+    /// implementation, which returns [`AppendError::Unsupported`].
+    fn append_multi(
+        &mut self,
+        _appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        Err(AppendError::Unsupported)
+    }
+}
+
+/// Version a stream is expected to be at when appending to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedVersion {
+    /// Append regardless of the version of the stream.
+    Any,
+    /// The stream must not exist.
+    NoStream,
+    /// The stream must be at the version.
+    Exact(u64),
+}
+
+impl ExpectedVersion {
+    /// Check whether a stream at the version satisfies the expectation.
+    pub fn matches(&self, version: u64) -> bool {
+        match self {
+            ExpectedVersion::Any => true,
+            ExpectedVersion::NoStream => version == 0,
+            ExpectedVersion::Exact(expected) => *expected == version,
+        }
+    }
+}
+
+/// Error returned when appending to streams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppendError<E> {
+    /// The backend does not support the operation.
+    Unsupported,
+    /// A stream is not at the expected version.
+    WrongExpectedVersion {
+        stream_id: String,
+        expected: ExpectedVersion,
+        actual: u64,
+    },
+    /// The backend failed.
+    Store(E),
+}
+
+impl<E> AppendError<E> {
+    /// Transform the error of the backend.
+    pub fn map_store<F>(self, f: impl FnOnce(E) -> F) -> AppendError<F> {
+        match self {
+            AppendError::Unsupported => AppendError::Unsupported,
+            AppendError::WrongExpectedVersion {
+                stream_id,
+                expected,
+                actual,
+            } => AppendError::WrongExpectedVersion {
+                stream_id,
+                expected,
+                actual,
+            },
+            AppendError::Store(e) => AppendError::Store(f(e)),
+        }
+    }
+}
+
+impl<E: Error> fmt::Display for AppendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppendError::Unsupported => write!(f, "operation not supported by the event store"),
+            AppendError::WrongExpectedVersion {
+                stream_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "stream {} is at version {}, expected {:?}",
+                stream_id, actual, expected
+            ),
+            AppendError::Store(e) => write!(f, "store error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for AppendError<E> {}
+
+/// Combinators wrapping an event store in additional behavior.
+pub trait EventStoreExt: Sized {
+    /// Guard the store with a circuit breaker.
+    fn with_circuit_breaker<K: Clock>(
+        self,
+        breaker: CircuitBreaker<K>,
+    ) -> CircuitBreakerStore<Self, K> {
+        CircuitBreakerStore::new(self, breaker)
+    }
+
+    /// Run the interceptor around the store.
+    fn with_interceptor<P>(
+        self,
+        interceptor: impl Interceptor<P> + 'static,
+    ) -> InterceptedEventStore<Self, P> {
+        InterceptedEventStore::new(self).with(interceptor)
+    }
 }
 
+impl<S: EventStore> EventStoreExt for S {}
+
 /// Types which represent a handler for a query to the event store.
 pub trait QueryHandler<Query> {
     /// Associated Type representing the response type.
@@ -39,3 +154,650 @@ pub trait QueryHandler<Query> {
     /// Handle the query.
     fn handle(&self, query: Query) -> Result<Self::Response, Self::Error>;
 }
+
+/// Types which represent an event store that can load the events of a stream.
+pub trait EventLoader {
+    /// Associated Type representing the ID of a stream.
+    type StreamId;
+    /// Associated Type representing the persisted event.
+    type Persistable;
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Load the events of the stream, in order.
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error>;
+
+    /// Load the events of the stream after the version, in order, e.g. to catch up from a
+    /// snapshot.
+    ///
+    /// The default implementation loads the whole stream, so callers must still skip the events
+    /// up to the version. Backends able to read a range of a stream should override it, and
+    /// wrappers should forward it.
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        _version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.load(stream_id)
+    }
+
+    /// Load the events of several streams, grouped per stream in the order of `stream_ids`.
+    ///
+    /// The default implementation loads the streams one after another. Backends able to read
+    /// several streams in one round trip should override it, and wrappers should forward it.
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        stream_ids.iter().map(|id| self.load(id)).collect()
+    }
+}
+
+/// Types which represent an event store that can only be read.
+///
+/// Projections, debuggers and query services should be given such a handle, which cannot
+/// append events.
+pub trait ReadOnlyEventStore: EventLoader {
+    /// Read the events of all the streams in global order, starting at the position.
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error>;
+
+    /// Get a read-only view of the store.
+    fn read_only(&self) -> ReadOnly<'_, Self>
+    where
+        Self: Sized,
+    {
+        ReadOnly(self)
+    }
+}
+
+/// Types which represent an event store telling the position of the end of its global log.
+pub trait LogHead: ReadOnlyEventStore {
+    /// Get the position the next event will be written at.
+    fn head_position(&self) -> Result<u64, Self::Error>;
+}
+
+/// How fresh the events read from a store must be.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReadConsistency {
+    /// The read reflects every event appended up to the fencing position of the caller, e.g.
+    /// its own writes, at the cost of reading from the primary while replicas lag behind.
+    #[default]
+    Strong,
+    /// The read may miss recent events, and is served by whichever copy is cheapest.
+    Eventual,
+}
+
+/// Types which represent an event store whose loads can trade freshness for load on its
+/// primary, such as stores with read replicas.
+pub trait ConsistentLoader: EventLoader {
+    /// Load the events of the stream, as fresh as the consistency requires.
+    fn load_consistent(
+        &self,
+        stream_id: &Self::StreamId,
+        consistency: ReadConsistency,
+    ) -> Result<Vec<Self::Persistable>, Self::Error>;
+}
+
+/// Types which represent an event store maintaining secondary indexes over the metadata of
+/// the events, so that cross-stream lookups do not scan the whole log.
+pub trait IndexedEventStore: EventLoader {
+    /// Read the events whose metadata maps the indexed key to the value, in global order.
+    fn read_by_index(&self, key: &str, value: &str) -> Result<Vec<Self::Persistable>, Self::Error>;
+}
+
+/// Read-only view of an event store, exposing none of its write operations.
+#[derive(Debug)]
+pub struct ReadOnly<'a, S>(&'a S);
+
+impl<S> Clone for ReadOnly<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for ReadOnly<'_, S> {}
+
+impl<S: EventLoader> EventLoader for ReadOnly<'_, S> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = S::Error;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.0.read_multi(stream_ids)
+    }
+}
+
+impl<S: ReadOnlyEventStore> ReadOnlyEventStore for ReadOnly<'_, S> {
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.read_all(from, limit)
+    }
+}
+
+impl<S: LogHead> LogHead for ReadOnly<'_, S> {
+    fn head_position(&self) -> Result<u64, Self::Error> {
+        self.0.head_position()
+    }
+}
+
+impl<S: IndexedEventStore> IndexedEventStore for ReadOnly<'_, S> {
+    fn read_by_index(&self, key: &str, value: &str) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.read_by_index(key, value)
+    }
+}
+
+/// State of the transaction of a [`RequireTransaction`] store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionState {
+    /// No transaction is active.
+    Idle,
+    /// A transaction is active.
+    Active,
+    /// A write failed within the active transaction, which can only be rolled back.
+    Poisoned,
+}
+
+/// Event store enforcing the transaction discipline: events are only saved within an active
+/// transaction, and a transaction is committed or rolled back once.
+///
+/// A failed write poisons the transaction, whose commit is refused, so that a partial write
+/// is never persisted.
+#[derive(Debug)]
+pub struct RequireTransaction<S> {
+    inner: S,
+    state: TransactionState,
+}
+
+/// Wrap the store, refusing saves outside of a transaction.
+pub fn require_transaction<S>(store: S) -> RequireTransaction<S> {
+    RequireTransaction {
+        inner: store,
+        state: TransactionState::Idle,
+    }
+}
+
+impl<S> RequireTransaction<S> {
+    /// Get the state of the transaction.
+    pub fn state(&self) -> TransactionState {
+        self.state
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwrap the store.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Check that a transaction is active and not poisoned, before a write.
+    pub fn check_active(&self) -> Result<(), TransactionStateError> {
+        match self.state {
+            TransactionState::Idle => Err(TransactionStateError::NotActive),
+            TransactionState::Active => Ok(()),
+            TransactionState::Poisoned => Err(TransactionStateError::Poisoned),
+        }
+    }
+
+    /// Run the write within the active transaction, poisoning it if the write fails.
+    pub fn write<T, E>(
+        &mut self,
+        write: impl FnOnce(&mut S) -> Result<T, E>,
+    ) -> Result<T, TransactionalError<E>> {
+        self.check_active().map_err(TransactionalError::State)?;
+        write(&mut self.inner).map_err(|e| {
+            self.state = TransactionState::Poisoned;
+            TransactionalError::Store(e)
+        })
+    }
+}
+
+impl<S: EventStore> EventStore for RequireTransaction<S> {
+    type Persistable = S::Persistable;
+    type Error = TransactionalError<S::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.write(|inner| inner.save(events))
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.check_active()
+            .map_err(|e| AppendError::Store(TransactionalError::State(e)))?;
+        self.inner.append_multi(appends).map_err(|e| {
+            self.state = TransactionState::Poisoned;
+            e.map_store(TransactionalError::Store)
+        })
+    }
+}
+
+impl<S: TransactionManager> TransactionManager for RequireTransaction<S> {
+    type Error = TransactionalError<S::Error>;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        if self.state != TransactionState::Idle {
+            return Err(TransactionalError::State(
+                TransactionStateError::AlreadyActive,
+            ));
+        }
+        self.inner.begin().map_err(TransactionalError::Store)?;
+        self.state = TransactionState::Active;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.check_active().map_err(TransactionalError::State)?;
+        self.inner.commit().map_err(|e| {
+            self.state = TransactionState::Poisoned;
+            TransactionalError::Store(e)
+        })?;
+        self.state = TransactionState::Idle;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        if self.state == TransactionState::Idle {
+            return Err(TransactionalError::State(TransactionStateError::NotActive));
+        }
+        self.state = TransactionState::Idle;
+        self.inner.rollback().map_err(TransactionalError::Store)
+    }
+}
+
+/// Reads are allowed outside of transactions.
+impl<S: EventLoader> EventLoader for RequireTransaction<S> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = TransactionalError<S::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner
+            .load(stream_id)
+            .map_err(TransactionalError::Store)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner
+            .load_after(stream_id, version)
+            .map_err(TransactionalError::Store)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.inner
+            .read_multi(stream_ids)
+            .map_err(TransactionalError::Store)
+    }
+}
+
+/// Misuse of transactions detected by [`RequireTransaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStateError {
+    /// A write, commit or rollback happened while no transaction is active.
+    NotActive,
+    /// A transaction was begun while another one is active.
+    AlreadyActive,
+    /// The transaction was committed, or written to, after a write failed within it.
+    Poisoned,
+}
+
+impl fmt::Display for TransactionStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionStateError::NotActive => write!(f, "no transaction is active"),
+            TransactionStateError::AlreadyActive => write!(f, "a transaction is already active"),
+            TransactionStateError::Poisoned => {
+                write!(f, "the transaction is poisoned by a failed write")
+            }
+        }
+    }
+}
+
+impl Error for TransactionStateError {}
+
+/// Error returned by [`RequireTransaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionalError<E> {
+    /// The transaction discipline was broken.
+    State(TransactionStateError),
+    /// The wrapped store failed.
+    Store(E),
+}
+
+impl<E: Error> fmt::Display for TransactionalError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionalError::State(e) => write!(f, "{}", e),
+            TransactionalError::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for TransactionalError<E> {}
+
+/// Event store keeping envelopes in memory, with stream versions and transactions.
+///
+/// Outside of a transaction, saved events are visible immediately. Inside a transaction, they
+/// are visible once committed.
+#[derive(Debug)]
+pub struct OnMemoryEventStore<E> {
+    log: Vec<Option<Envelope<E>>>,
+    streams: HashMap<String, Vec<usize>>,
+    versions: HashMap<String, u64>,
+    indexes: HashMap<String, HashMap<String, Vec<usize>>>,
+    uncommitted: Option<Vec<Envelope<E>>>,
+}
+
+impl<E: Clone> OnMemoryEventStore<E> {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self {
+            log: Vec::new(),
+            streams: HashMap::new(),
+            versions: HashMap::new(),
+            indexes: HashMap::new(),
+            uncommitted: None,
+        }
+    }
+
+    /// Maintain an index over the metadata key, for [`IndexedEventStore::read_by_index`].
+    /// Events already in the store are indexed too.
+    pub fn with_index(mut self, key: impl Into<String>) -> Self {
+        let key = key.into();
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, event) in self.log.iter().enumerate() {
+            if let Some(value) = event.as_ref().and_then(|e| e.metadata.get(&key)) {
+                index.entry(value.clone()).or_default().push(i);
+            }
+        }
+        self.indexes.insert(key, index);
+        self
+    }
+
+    /// Get the version of the stream, including the uncommitted events.
+    pub fn version(&self, stream_id: &str) -> u64 {
+        let committed = self.versions.get(stream_id).copied().unwrap_or_default();
+        let uncommitted = self
+            .uncommitted
+            .iter()
+            .flatten()
+            .filter(|e| e.stream_id == stream_id)
+            .count();
+        committed + uncommitted as u64
+    }
+
+    /// Get the IDs of the streams holding events.
+    pub fn stream_ids(&self) -> Vec<String> {
+        let mut ids = self
+            .streams
+            .iter()
+            .filter(|(_, indices)| !indices.is_empty())
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Get the number of committed events held by the stream.
+    pub fn stream_len(&self, stream_id: &str) -> usize {
+        self.streams.get(stream_id).map_or(0, Vec::len)
+    }
+
+    /// Delete the events of the stream matching the predicate, returning them.
+    ///
+    /// The version of the stream and the positions of the other events are unchanged.
+    pub fn delete_where(
+        &mut self,
+        stream_id: &str,
+        mut predicate: impl FnMut(&Envelope<E>) -> bool,
+    ) -> Vec<Envelope<E>> {
+        let indices = match self.streams.get_mut(stream_id) {
+            Some(indices) => indices,
+            None => return Vec::new(),
+        };
+        let mut deleted = Vec::new();
+        indices.retain(|i| match &self.log[*i] {
+            Some(event) if predicate(event) => {
+                deleted.extend(self.log[*i].take());
+                false
+            }
+            _ => true,
+        });
+        deleted
+    }
+
+    /// Get the position the next committed event will be written at.
+    pub fn head(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    fn append(&mut self, events: &[Envelope<E>]) {
+        for event in events {
+            let mut event = event.clone();
+            event.version = self.version(&event.stream_id) + 1;
+            match self.uncommitted.as_mut() {
+                Some(uncommitted) => uncommitted.push(event),
+                None => self.write(event),
+            }
+        }
+    }
+
+    fn write(&mut self, mut event: Envelope<E>) {
+        event.position = self.head();
+        self.streams
+            .entry(event.stream_id.clone())
+            .or_default()
+            .push(self.log.len());
+        self.versions.insert(event.stream_id.clone(), event.version);
+        for (key, index) in &mut self.indexes {
+            if let Some(value) = event.metadata.get(key) {
+                index.entry(value.clone()).or_default().push(self.log.len());
+            }
+        }
+        self.log.push(Some(event));
+    }
+}
+
+impl<E: Clone> Default for OnMemoryEventStore<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Clone> EventStore for OnMemoryEventStore<E> {
+    type Persistable = Envelope<E>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.append(events);
+        Ok(())
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        for (stream_id, expected, _) in appends {
+            let actual = self.version(stream_id);
+            if !expected.matches(actual) {
+                return Err(AppendError::WrongExpectedVersion {
+                    stream_id: stream_id.clone(),
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+        for (stream_id, _, events) in appends {
+            let events = events
+                .iter()
+                .map(|e| Envelope {
+                    stream_id: stream_id.clone(),
+                    ..e.clone()
+                })
+                .collect::<Vec<_>>();
+            self.append(&events);
+        }
+        Ok(())
+    }
+}
+
+impl<E: Clone> EventLoader for OnMemoryEventStore<E> {
+    type StreamId = String;
+    type Persistable = Envelope<E>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        Ok(self
+            .streams
+            .get(stream_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.log[*i].clone())
+            .collect())
+    }
+
+    /// Clone only the events after the version, found by binary search as the versions of a
+    /// stream are increasing.
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let indices = self.streams.get(stream_id).map_or(&[][..], Vec::as_slice);
+        let start = indices.partition_point(|i| {
+            self.log[*i]
+                .as_ref()
+                .is_some_and(|event| event.version <= version)
+        });
+        Ok(indices[start..]
+            .iter()
+            .filter_map(|i| self.log[*i].clone())
+            .collect())
+    }
+}
+
+impl<E: Clone> ConsistentLoader for OnMemoryEventStore<E> {
+    /// Every read is strongly consistent, as there is a single copy of the events.
+    fn load_consistent(
+        &self,
+        stream_id: &Self::StreamId,
+        _consistency: ReadConsistency,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.load(stream_id)
+    }
+}
+
+impl<E: Clone> ReadOnlyEventStore for OnMemoryEventStore<E> {
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        Ok(self
+            .log
+            .iter()
+            .skip(from as usize)
+            .flatten()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+impl<E: Clone> LogHead for OnMemoryEventStore<E> {
+    fn head_position(&self) -> Result<u64, Self::Error> {
+        Ok(self.head())
+    }
+}
+
+impl<E: Clone> IndexedEventStore for OnMemoryEventStore<E> {
+    /// Fails with [`OnMemoryEventStoreError::UnknownIndex`] if no index was declared over the
+    /// key with [`OnMemoryEventStore::with_index`].
+    fn read_by_index(&self, key: &str, value: &str) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let index = self
+            .indexes
+            .get(key)
+            .ok_or_else(|| OnMemoryEventStoreError::UnknownIndex(key.to_string()))?;
+        Ok(index
+            .get(value)
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.log[*i].clone())
+            .collect())
+    }
+}
+
+impl<E: Clone> TransactionManager for OnMemoryEventStore<E> {
+    type Error = OnMemoryEventStoreError;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        if self.uncommitted.is_some() {
+            return Err(OnMemoryEventStoreError::TransactionAlreadyActive);
+        }
+        self.uncommitted = Some(Vec::new());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        let uncommitted = self
+            .uncommitted
+            .take()
+            .ok_or(OnMemoryEventStoreError::NoActiveTransaction)?;
+        for event in uncommitted {
+            self.write(event);
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.uncommitted
+            .take()
+            .ok_or(OnMemoryEventStoreError::NoActiveTransaction)?;
+        Ok(())
+    }
+}
+
+/// Error returned by the [`OnMemoryEventStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnMemoryEventStoreError {
+    /// A transaction was begun while another one is active.
+    TransactionAlreadyActive,
+    /// A transaction was committed or rolled back while none is active.
+    NoActiveTransaction,
+    /// No index is maintained over the metadata key.
+    UnknownIndex(String),
+}
+
+impl fmt::Display for OnMemoryEventStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnMemoryEventStoreError::TransactionAlreadyActive => {
+                write!(f, "a transaction is already active")
+            }
+            OnMemoryEventStoreError::NoActiveTransaction => write!(f, "no transaction is active"),
+            OnMemoryEventStoreError::UnknownIndex(key) => write!(f, "no index over key: {}", key),
+        }
+    }
+}
+
+impl Error for OnMemoryEventStoreError {}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use super::*;
+use crate::event_store::OnMemoryEventStore as EnvelopeEventStore;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct OrderId(String);
@@ -65,9 +66,11 @@ struct PaymentResolveEvent {
     data: PaymentAction,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
-struct OnMemoryEventMetadata((String));
+struct OnMemoryEventMetadata(String);
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 enum OnMemoryPersistableEvent {
     OrderCreate(CreateOrderEvent, OnMemoryEventMetadata),
@@ -116,7 +119,7 @@ impl EventStore for OnMemoryEventStore {
                     OnMemoryPersistableEventId::Payment(event.id.clone())
                 }
             };
-            let events = self.events.entry(id).or_insert_with(Vec::new);
+            let events = self.events.entry(id).or_default();
             events.push(event.clone());
         }
         Ok(())
@@ -319,3 +322,315 @@ fn test_payment_backlog() {
     let payment = event_store.handle(query).unwrap().unwrap();
     assert_eq!(payment.status, PaymentStatus(0));
 }
+
+impl EventLoader for OnMemoryEventStore {
+    type StreamId = OnMemoryPersistableEventId;
+    type Persistable = OnMemoryPersistableEvent;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        Ok(self.events.get(stream_id).cloned().unwrap_or_default())
+    }
+}
+
+#[test]
+fn test_load_stream() {
+    let order_id = OrderId("order-1".to_string());
+    let mut event_store = OnMemoryEventStore::new();
+    event_store.begin().unwrap();
+    event_store
+        .save(&[OnMemoryPersistableEvent::OrderCreate(
+            CreateOrderEvent {
+                id: order_id.clone(),
+            },
+            OnMemoryEventMetadata("".to_string()),
+        )])
+        .unwrap();
+    event_store.commit().unwrap();
+
+    let events = event_store
+        .load(&OnMemoryPersistableEventId::Order(order_id))
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    let events = event_store
+        .load(&OnMemoryPersistableEventId::Payment(PaymentId(
+            "payment-1".to_string(),
+        )))
+        .unwrap();
+    assert!(events.is_empty());
+}
+
+fn envelope(stream_id: &str, event: &str) -> Envelope<String> {
+    Envelope::new(stream_id, 0, event, event.to_string())
+}
+
+#[test]
+fn test_envelope_store_transactions() {
+    let mut event_store = EnvelopeEventStore::new();
+    event_store.save(&[envelope("org-1", "Created")]).unwrap();
+
+    event_store.begin().unwrap();
+    event_store.save(&[envelope("org-1", "Renamed")]).unwrap();
+    assert_eq!(event_store.version("org-1"), 2);
+    assert_eq!(event_store.load(&"org-1".to_string()).unwrap().len(), 1);
+    event_store.rollback().unwrap();
+    assert_eq!(event_store.version("org-1"), 1);
+
+    event_store.begin().unwrap();
+    event_store.save(&[envelope("org-1", "Renamed")]).unwrap();
+    event_store.commit().unwrap();
+    let events = event_store.load(&"org-1".to_string()).unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.version).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(
+        event_store.commit(),
+        Err(crate::event_store::OnMemoryEventStoreError::NoActiveTransaction)
+    );
+}
+
+#[test]
+fn test_append_multi() {
+    let mut event_store = EnvelopeEventStore::new();
+    event_store
+        .append_multi(&[
+            (
+                "org-1".to_string(),
+                ExpectedVersion::NoStream,
+                vec![envelope("", "Created")],
+            ),
+            (
+                "user-1".to_string(),
+                ExpectedVersion::NoStream,
+                vec![envelope("", "Created"), envelope("", "Joined")],
+            ),
+        ])
+        .unwrap();
+    assert_eq!(event_store.version("org-1"), 1);
+    assert_eq!(event_store.version("user-1"), 2);
+    assert_eq!(
+        event_store.load(&"user-1".to_string()).unwrap()[1].stream_id,
+        "user-1"
+    );
+
+    let result = event_store.append_multi(&[
+        (
+            "org-1".to_string(),
+            ExpectedVersion::Exact(1),
+            vec![envelope("", "UserAdded")],
+        ),
+        (
+            "user-1".to_string(),
+            ExpectedVersion::Exact(1),
+            vec![envelope("", "Left")],
+        ),
+    ]);
+    assert_eq!(
+        result,
+        Err(AppendError::WrongExpectedVersion {
+            stream_id: "user-1".to_string(),
+            expected: ExpectedVersion::Exact(1),
+            actual: 2,
+        })
+    );
+    assert_eq!(event_store.version("org-1"), 1);
+}
+
+#[test]
+fn test_append_multi_unsupported() {
+    let mut event_store = OnMemoryEventStore::new();
+    assert!(matches!(
+        event_store.append_multi(&[]),
+        Err(AppendError::Unsupported)
+    ));
+}
+
+#[test]
+fn test_read_only_view() {
+    fn count_all<S: ReadOnlyEventStore>(store: &S) -> usize {
+        store.read_all(0, usize::MAX).unwrap().len()
+    }
+
+    let mut event_store = EnvelopeEventStore::new();
+    event_store
+        .save(&[
+            envelope("org-1", "Created"),
+            envelope("user-1", "Created"),
+            envelope("org-1", "UserAdded"),
+        ])
+        .unwrap();
+
+    let view = event_store.read_only();
+    assert_eq!(count_all(&view), 3);
+    let events = view.read_all(1, 10).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.position, e.stream_id.as_str(), e.version))
+            .collect::<Vec<_>>(),
+        vec![(1, "user-1", 1), (2, "org-1", 2)]
+    );
+    assert_eq!(view.load(&"org-1".to_string()).unwrap().len(), 2);
+}
+
+#[test]
+fn test_read_multi() {
+    let mut event_store = EnvelopeEventStore::new();
+    event_store
+        .save(&[
+            envelope("org-1", "Created"),
+            envelope("user-1", "Created"),
+            envelope("org-1", "UserAdded"),
+        ])
+        .unwrap();
+
+    let streams = event_store
+        .read_only()
+        .read_multi(&[
+            "user-1".to_string(),
+            "user-2".to_string(),
+            "org-1".to_string(),
+        ])
+        .unwrap();
+    assert_eq!(
+        streams
+            .iter()
+            .map(|events| events.iter().map(|e| e.version).collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        vec![vec![1], vec![], vec![1, 2]]
+    );
+}
+
+#[test]
+fn test_read_by_index() {
+    let customer = |stream_id: &str, event: &str, customer: &str| {
+        envelope(stream_id, event).with_metadata("customer_id", customer)
+    };
+    let mut event_store = EnvelopeEventStore::new();
+    event_store
+        .save(&[customer("order-1", "Placed", "c-1")])
+        .unwrap();
+    let mut event_store = event_store.with_index("customer_id");
+    event_store.begin().unwrap();
+    event_store
+        .save(&[
+            customer("payment-1", "Paid", "c-1"),
+            customer("order-2", "Placed", "c-2"),
+            envelope("order-1", "Shipped"),
+        ])
+        .unwrap();
+    assert_eq!(
+        event_store
+            .read_by_index("customer_id", "c-1")
+            .unwrap()
+            .len(),
+        1
+    );
+    event_store.commit().unwrap();
+
+    let events = event_store
+        .read_only()
+        .read_by_index("customer_id", "c-1")
+        .unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.stream_id.as_str(), e.payload.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("order-1", "Placed"), ("payment-1", "Paid")]
+    );
+    assert!(event_store
+        .read_by_index("customer_id", "c-3")
+        .unwrap()
+        .is_empty());
+
+    event_store.delete_where("payment-1", |_| true);
+    assert_eq!(
+        event_store
+            .read_by_index("customer_id", "c-1")
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        event_store.read_by_index("tenant", "t-1"),
+        Err(crate::event_store::OnMemoryEventStoreError::UnknownIndex(
+            "tenant".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_require_transaction() {
+    let mut event_store = require_transaction(EnvelopeEventStore::new());
+    assert_eq!(
+        event_store.save(&[envelope("org-1", "Created")]),
+        Err(TransactionalError::State(TransactionStateError::NotActive))
+    );
+
+    event_store.begin().unwrap();
+    assert_eq!(
+        event_store.begin(),
+        Err(TransactionalError::State(
+            TransactionStateError::AlreadyActive
+        ))
+    );
+    event_store.save(&[envelope("org-1", "Created")]).unwrap();
+    event_store.commit().unwrap();
+    assert_eq!(
+        event_store.commit(),
+        Err(TransactionalError::State(TransactionStateError::NotActive))
+    );
+    assert_eq!(event_store.inner().stream_len("org-1"), 1);
+
+    // A failed write poisons the transaction: only a rollback ends it.
+    event_store.begin().unwrap();
+    event_store.save(&[envelope("org-1", "Renamed")]).unwrap();
+    let conflict = [(
+        "org-1".to_string(),
+        ExpectedVersion::NoStream,
+        vec![envelope("org-1", "Created")],
+    )];
+    assert!(event_store.append_multi(&conflict).is_err());
+    assert_eq!(event_store.state(), TransactionState::Poisoned);
+    assert_eq!(
+        event_store.commit(),
+        Err(TransactionalError::State(TransactionStateError::Poisoned))
+    );
+    event_store.rollback().unwrap();
+    assert_eq!(event_store.state(), TransactionState::Idle);
+    assert_eq!(event_store.load(&"org-1".to_string()).unwrap().len(), 1);
+}
+
+#[test]
+fn test_envelope_store_load_after() {
+    let mut event_store = EnvelopeEventStore::new();
+    event_store
+        .save(&[
+            envelope("org-1", "Created"),
+            envelope("org-2", "Created"),
+            envelope("org-1", "Renamed"),
+            envelope("org-1", "Moved"),
+            envelope("org-1", "Closed"),
+        ])
+        .unwrap();
+    event_store.delete_where("org-1", |e| e.event_type == "Moved");
+
+    let versions = |version| {
+        event_store
+            .load_after(&"org-1".to_string(), version)
+            .unwrap()
+            .iter()
+            .map(|e| e.version)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(versions(0), [1, 2, 4]);
+    assert_eq!(versions(2), [4]);
+    assert_eq!(versions(3), [4]);
+    assert!(versions(4).is_empty());
+    assert!(event_store
+        .load_after(&"org-3".to_string(), 0)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::ReadOnlyEventStore;
+use crate::projection::Projection;
+
+struct Registered<E, PE> {
+    name: String,
+    depends_on: Vec<String>,
+    projection: Box<dyn Projection<Event = E, Error = PE>>,
+    position: u64,
+}
+
+/// Registry of projections fed from the same batches of events, such as read models derived
+/// from the tables of other read models.
+///
+/// A projection declares the projections it reads from, and every event is applied to them
+/// before it, so that it never sees their tables behind the event it applies.
+///
+/// Each projection keeps the position of the next event to apply to it, so that the batch of
+/// a failed run can be run again without applying its events twice to the projections which
+/// already applied them.
+pub struct ProjectionFanOut<E, PE> {
+    projections: Vec<Registered<E, PE>>,
+    position: u64,
+}
+
+impl<E, PE: Error> ProjectionFanOut<E, PE> {
+    /// Create an empty registry starting at the beginning of the log.
+    pub fn new() -> Self {
+        Self {
+            projections: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Register the projection under the name, reading from the projections named by
+    /// `depends_on`, which may be registered later.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        depends_on: &[&str],
+        projection: impl Projection<Event = E, Error = PE> + 'static,
+    ) -> Result<(), DependencyError> {
+        let name = name.into();
+        if self.projections.iter().any(|p| p.name == name) {
+            return Err(DependencyError::Duplicate(name));
+        }
+        self.projections.push(Registered {
+            name,
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            projection: Box::new(projection),
+            position: self.position,
+        });
+        Ok(())
+    }
+
+    /// Get the position of the next event to apply.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get the position of the next event to apply to the named projection.
+    pub fn projection_position(&self, name: &str) -> Option<u64> {
+        self.projections
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.position)
+    }
+
+    /// Get the names of the projections in the order events are applied to them:
+    /// dependencies first, then registration order.
+    pub fn order(&self) -> Result<Vec<&str>, DependencyError> {
+        Ok(self
+            .resolve()?
+            .into_iter()
+            .map(|i| self.projections[i].name.as_str())
+            .collect())
+    }
+
+    fn resolve(&self) -> Result<Vec<usize>, DependencyError> {
+        let mut dependencies = Vec::with_capacity(self.projections.len());
+        for p in &self.projections {
+            let mut indices = Vec::with_capacity(p.depends_on.len());
+            for dependency in &p.depends_on {
+                let index = self
+                    .projections
+                    .iter()
+                    .position(|q| &q.name == dependency)
+                    .ok_or_else(|| DependencyError::Unknown {
+                        projection: p.name.clone(),
+                        dependency: dependency.clone(),
+                    })?;
+                indices.push(index);
+            }
+            dependencies.push(indices);
+        }
+
+        let mut order = Vec::with_capacity(self.projections.len());
+        let mut placed = vec![false; self.projections.len()];
+        while order.len() < self.projections.len() {
+            let next = (0..self.projections.len())
+                .find(|&i| !placed[i] && dependencies[i].iter().all(|&d| placed[d]));
+            match next {
+                Some(i) => {
+                    placed[i] = true;
+                    order.push(i);
+                }
+                None => {
+                    let cycle = (0..self.projections.len())
+                        .filter(|&i| !placed[i])
+                        .map(|i| self.projections[i].name.clone())
+                        .collect();
+                    return Err(DependencyError::Cycle(cycle));
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// Apply the next batch of events to every projection, returning the number of events
+    /// applied.
+    pub fn run_batch<S>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<usize, FanOutError<S::Error, PE>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<E>>,
+    {
+        let order = self.resolve().map_err(FanOutError::Dependency)?;
+        let events = store
+            .read_all(self.position, batch_size)
+            .map_err(FanOutError::Store)?;
+        for event in &events {
+            for &i in &order {
+                let registered = &mut self.projections[i];
+                if event.position < registered.position {
+                    continue;
+                }
+                registered
+                    .projection
+                    .apply(event)
+                    .map_err(|error| FanOutError::Projection {
+                        name: registered.name.clone(),
+                        error,
+                    })?;
+                registered.position = event.position + 1;
+            }
+            self.position = event.position + 1;
+        }
+        Ok(events.len())
+    }
+}
+
+impl<E, PE: Error> Default for ProjectionFanOut<E, PE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned when the dependencies between projections cannot be ordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyError {
+    /// A projection was registered twice under the name.
+    Duplicate(String),
+    /// A projection depends on a projection which is not registered.
+    Unknown {
+        /// Name of the dependent projection.
+        projection: String,
+        /// Name of the missing dependency.
+        dependency: String,
+    },
+    /// The named projections are in a cycle of dependencies, or depend on one.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyError::Duplicate(name) => {
+                write!(f, "projection registered twice: {}", name)
+            }
+            DependencyError::Unknown {
+                projection,
+                dependency,
+            } => write!(
+                f,
+                "projection {} depends on unknown projection {}",
+                projection, dependency
+            ),
+            DependencyError::Cycle(names) => {
+                write!(f, "projections in a dependency cycle: {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for DependencyError {}
+
+/// Error returned when running a [`ProjectionFanOut`].
+#[derive(Debug)]
+pub enum FanOutError<SE, PE> {
+    /// The dependencies between projections cannot be ordered.
+    Dependency(DependencyError),
+    /// The event store failed.
+    Store(SE),
+    /// The named projection failed to apply an event.
+    Projection {
+        /// Name of the projection.
+        name: String,
+        /// The error of the projection.
+        error: PE,
+    },
+}
+
+impl<SE: Error, PE: Error> fmt::Display for FanOutError<SE, PE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FanOutError::Dependency(e) => write!(f, "dependency error: {}", e),
+            FanOutError::Store(e) => write!(f, "store error: {}", e),
+            FanOutError::Projection { name, error } => {
+                write!(f, "projection {} error: {}", name, error)
+            }
+        }
+    }
+}
+
+impl<SE: Error, PE: Error> Error for FanOutError<SE, PE> {}
@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+#[derive(Debug, Clone)]
+enum ShopEvent {
+    OrderPlaced { customer: String, amount: u64 },
+}
+
+type Table = Rc<RefCell<HashMap<String, u64>>>;
+
+/// Table of the total spent by each customer.
+struct CustomerTotals {
+    totals: Table,
+}
+
+impl Projection for CustomerTotals {
+    type Event = ShopEvent;
+    type Error = Infallible;
+
+    fn apply(&mut self, event: &Envelope<ShopEvent>) -> Result<(), Infallible> {
+        let ShopEvent::OrderPlaced { customer, amount } = &event.payload;
+        *self
+            .totals
+            .borrow_mut()
+            .entry(customer.clone())
+            .or_default() += amount;
+        Ok(())
+    }
+}
+
+/// Table of the customers who spent at least 100, read from the totals table.
+struct VipCustomers {
+    totals: Table,
+    vips: Rc<RefCell<Vec<String>>>,
+}
+
+impl Projection for VipCustomers {
+    type Event = ShopEvent;
+    type Error = Infallible;
+
+    fn apply(&mut self, event: &Envelope<ShopEvent>) -> Result<(), Infallible> {
+        let ShopEvent::OrderPlaced { customer, .. } = &event.payload;
+        let total = self.totals.borrow().get(customer).copied().unwrap_or(0);
+        let mut vips = self.vips.borrow_mut();
+        if total >= 100 && !vips.contains(customer) {
+            vips.push(customer.clone());
+        }
+        Ok(())
+    }
+}
+
+struct Noop;
+
+impl Projection for Noop {
+    type Event = ShopEvent;
+    type Error = Infallible;
+
+    fn apply(&mut self, _event: &Envelope<ShopEvent>) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+fn order_placed(customer: &str, amount: u64) -> Envelope<ShopEvent> {
+    let payload = ShopEvent::OrderPlaced {
+        customer: customer.to_string(),
+        amount,
+    };
+    Envelope::new(format!("order-{}", customer), 0, "OrderPlaced", payload)
+}
+
+#[test]
+fn test_dependencies_are_applied_first() {
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[
+            order_placed("alice", 60),
+            order_placed("bob", 10),
+            order_placed("alice", 50),
+        ])
+        .unwrap();
+    let totals = Table::default();
+    let vips = Rc::new(RefCell::new(Vec::new()));
+
+    // Registered before the table it reads from.
+    let mut fan_out = ProjectionFanOut::new();
+    fan_out
+        .register(
+            "vip_customers",
+            &["customer_totals"],
+            VipCustomers {
+                totals: totals.clone(),
+                vips: vips.clone(),
+            },
+        )
+        .unwrap();
+    fan_out
+        .register(
+            "customer_totals",
+            &[],
+            CustomerTotals {
+                totals: totals.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        fan_out.order().unwrap(),
+        ["customer_totals", "vip_customers"]
+    );
+
+    assert_eq!(fan_out.run_batch(&store, 10).unwrap(), 3);
+    assert_eq!(fan_out.position(), 3);
+    assert_eq!(totals.borrow()["alice"], 110);
+    assert_eq!(*vips.borrow(), ["alice"]);
+}
+
+#[test]
+fn test_invalid_dependencies() {
+    let mut fan_out = ProjectionFanOut::new();
+    fan_out.register("a", &["b"], Noop).unwrap();
+    assert_eq!(
+        fan_out.register("a", &[], Noop),
+        Err(DependencyError::Duplicate("a".to_string()))
+    );
+    assert_eq!(
+        fan_out.order(),
+        Err(DependencyError::Unknown {
+            projection: "a".to_string(),
+            dependency: "b".to_string()
+        })
+    );
+
+    fan_out.register("b", &["c"], Noop).unwrap();
+    fan_out.register("c", &["b"], Noop).unwrap();
+    fan_out.register("d", &[], Noop).unwrap();
+    assert_eq!(
+        fan_out.order(),
+        Err(DependencyError::Cycle(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string()
+        ]))
+    );
+    let store = OnMemoryEventStore::<ShopEvent>::new();
+    assert!(matches!(
+        fan_out.run_batch(&store, 10),
+        Err(FanOutError::Dependency(DependencyError::Cycle(_)))
+    ));
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Unavailable;
+
+impl fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unavailable")
+    }
+}
+
+impl Error for Unavailable {}
+
+/// Projection failing once at the position, e.g. while its database restarts.
+struct FailingOnce<P> {
+    inner: P,
+    fail_at: Option<u64>,
+}
+
+impl<P: Projection<Event = ShopEvent, Error = Infallible>> Projection for FailingOnce<P> {
+    type Event = ShopEvent;
+    type Error = Unavailable;
+
+    fn apply(&mut self, event: &Envelope<ShopEvent>) -> Result<(), Unavailable> {
+        if self.fail_at == Some(event.position) {
+            self.fail_at = None;
+            return Err(Unavailable);
+        }
+        let Ok(()) = self.inner.apply(event);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_retry_batch_after_failure() {
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[order_placed("alice", 60), order_placed("alice", 50)])
+        .unwrap();
+    let totals = Table::default();
+    let vips = Rc::new(RefCell::new(Vec::new()));
+
+    let mut fan_out = ProjectionFanOut::new();
+    fan_out
+        .register(
+            "customer_totals",
+            &[],
+            FailingOnce {
+                inner: CustomerTotals {
+                    totals: totals.clone(),
+                },
+                fail_at: None,
+            },
+        )
+        .unwrap();
+    fan_out
+        .register(
+            "vip_customers",
+            &["customer_totals"],
+            FailingOnce {
+                inner: VipCustomers {
+                    totals: totals.clone(),
+                    vips: vips.clone(),
+                },
+                fail_at: Some(1),
+            },
+        )
+        .unwrap();
+
+    assert!(matches!(
+        fan_out.run_batch(&store, 10),
+        Err(FanOutError::Projection { name, error: Unavailable }) if name == "vip_customers"
+    ));
+    assert_eq!(fan_out.position(), 1);
+    assert_eq!(fan_out.projection_position("customer_totals"), Some(2));
+    assert_eq!(fan_out.projection_position("vip_customers"), Some(1));
+
+    // The totals already applied the second event, so only the VIPs apply it again.
+    assert_eq!(fan_out.run_batch(&store, 10).unwrap(), 1);
+    assert_eq!(fan_out.position(), 2);
+    assert_eq!(totals.borrow()["alice"], 110);
+    assert_eq!(*vips.borrow(), ["alice"]);
+}
@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::broker::{Publisher, Subscription};
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, TransactionManager,
+};
+use crate::rng::SeededRng;
+
+/// Faults injected by the [`FaultyEventStore`] and [`FaultyBroker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultConfig {
+    /// Seed of the random generator deciding when faults happen.
+    pub seed: u64,
+    /// Probability that a call fails without reaching the wrapped component.
+    pub error_rate: f64,
+    /// Probability that saving a batch writes only a prefix of it before failing.
+    pub partial_batch_rate: f64,
+    /// Delay added before every call.
+    pub latency: Duration,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            error_rate: 0.0,
+            partial_batch_rate: 0.0,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+struct Injector {
+    config: FaultConfig,
+    rng: RefCell<SeededRng>,
+}
+
+impl Injector {
+    fn new(config: FaultConfig) -> Self {
+        Self {
+            config,
+            rng: RefCell::new(SeededRng::new(config.seed)),
+        }
+    }
+
+    fn before_call<E>(&self) -> Result<(), FaultError<E>> {
+        if !self.config.latency.is_zero() {
+            std::thread::sleep(self.config.latency);
+        }
+        if self.rng.borrow_mut().chance(self.config.error_rate) {
+            return Err(FaultError::Injected);
+        }
+        Ok(())
+    }
+
+    fn partial_batch(&self, len: usize) -> Option<usize> {
+        let mut rng = self.rng.borrow_mut();
+        if len < 2 || !rng.chance(self.config.partial_batch_rate) {
+            return None;
+        }
+        Some(1 + rng.below(len as u64 - 1) as usize)
+    }
+}
+
+/// Event store decorator injecting faults, for resilience testing.
+pub struct FaultyEventStore<S> {
+    store: S,
+    injector: Injector,
+}
+
+impl<S> FaultyEventStore<S> {
+    /// Wrap the store.
+    pub fn new(store: S, config: FaultConfig) -> Self {
+        Self {
+            store,
+            injector: Injector::new(config),
+        }
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S: EventStore> EventStore for FaultyEventStore<S> {
+    type Persistable = S::Persistable;
+    type Error = FaultError<<S as EventStore>::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.injector.before_call()?;
+        if let Some(written) = self.injector.partial_batch(events.len()) {
+            self.store
+                .save(&events[..written])
+                .map_err(FaultError::Inner)?;
+            return Err(FaultError::PartialBatch { written });
+        }
+        self.store.save(events).map_err(FaultError::Inner)
+    }
+
+    /// Appends to several streams are atomic, so they fail as a whole rather than partially.
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.injector.before_call().map_err(AppendError::Store)?;
+        self.store
+            .append_multi(appends)
+            .map_err(|e| e.map_store(FaultError::Inner))
+    }
+}
+
+impl<S: EventLoader> EventLoader for FaultyEventStore<S> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = FaultError<<S as EventLoader>::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.injector.before_call()?;
+        self.store.load(stream_id).map_err(FaultError::Inner)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.injector.before_call()?;
+        self.store
+            .load_after(stream_id, version)
+            .map_err(FaultError::Inner)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.injector.before_call()?;
+        self.store.read_multi(stream_ids).map_err(FaultError::Inner)
+    }
+}
+
+impl<S: TransactionManager> TransactionManager for FaultyEventStore<S> {
+    type Error = FaultError<S::Error>;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.injector.before_call()?;
+        self.store.begin().map_err(FaultError::Inner)
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.injector.before_call()?;
+        self.store.commit().map_err(FaultError::Inner)
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.store.rollback().map_err(FaultError::Inner)
+    }
+}
+
+/// Broker decorator injecting faults, for resilience testing.
+pub struct FaultyBroker<B> {
+    broker: B,
+    injector: Injector,
+}
+
+impl<B> FaultyBroker<B> {
+    /// Wrap the broker.
+    pub fn new(broker: B, config: FaultConfig) -> Self {
+        Self {
+            broker,
+            injector: Injector::new(config),
+        }
+    }
+
+    /// Get the wrapped broker.
+    pub fn inner(&self) -> &B {
+        &self.broker
+    }
+}
+
+impl<M, B: Publisher<M>> Publisher<M> for FaultyBroker<B> {
+    type Error = FaultError<B::Error>;
+
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error> {
+        self.injector.before_call()?;
+        self.broker.publish(message).map_err(FaultError::Inner)
+    }
+}
+
+impl<M, B: Subscription<M>> Subscription<M> for FaultyBroker<B> {
+    type Error = FaultError<B::Error>;
+
+    fn poll(&mut self) -> Result<Option<M>, Self::Error> {
+        self.injector.before_call()?;
+        self.broker.poll().map_err(FaultError::Inner)
+    }
+}
+
+/// Error returned by the faulty decorators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultError<E> {
+    /// A fault was injected: the wrapped component was not called.
+    Injected,
+    /// Only the first `written` events of the batch were saved.
+    PartialBatch { written: usize },
+    /// The wrapped component failed.
+    Inner(E),
+}
+
+impl<E: Error> fmt::Display for FaultError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultError::Injected => write!(f, "injected fault"),
+            FaultError::PartialBatch { written } => {
+                write!(f, "injected partial batch failure after {} events", written)
+            }
+            FaultError::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for FaultError<E> {}
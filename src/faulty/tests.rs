@@ -0,0 +1,109 @@
+use std::time::Instant;
+
+use super::*;
+use crate::broker::OnMemoryBroker;
+use crate::envelope::Envelope;
+use crate::event_store::{OnMemoryEventStore, ReadOnlyEventStore};
+
+fn events(count: usize) -> Vec<Envelope<usize>> {
+    (0..count)
+        .map(|i| Envelope::new("stream", 0, "Counted", i))
+        .collect()
+}
+
+#[test]
+fn test_faulty_event_store_errors() {
+    let mut store = FaultyEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultConfig {
+            seed: 1,
+            error_rate: 0.5,
+            ..Default::default()
+        },
+    );
+    let results = (0..100)
+        .map(|_| store.save(&events(1)).is_ok())
+        .collect::<Vec<_>>();
+    let succeeded = results.iter().filter(|ok| **ok).count();
+    assert!((30..70).contains(&succeeded));
+    assert_eq!(
+        store.inner().read_all(0, usize::MAX).unwrap().len(),
+        succeeded
+    );
+}
+
+#[test]
+fn test_faulty_event_store_partial_batches() {
+    let mut store = FaultyEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultConfig {
+            partial_batch_rate: 1.0,
+            ..Default::default()
+        },
+    );
+    let written = match store.save(&events(5)) {
+        Err(FaultError::PartialBatch { written }) => written,
+        other => panic!("unexpected result: {:?}", other),
+    };
+    assert!((1..5).contains(&written));
+    assert_eq!(store.load(&"stream".to_string()).unwrap().len(), written);
+    // A single event cannot be split.
+    assert!(store.save(&events(1)).is_ok());
+}
+
+#[test]
+fn test_faulty_event_store_append_multi() {
+    let mut store = FaultyEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultConfig {
+            partial_batch_rate: 1.0,
+            ..Default::default()
+        },
+    );
+    let appends = [("stream".to_string(), ExpectedVersion::NoStream, events(3))];
+    store.append_multi(&appends).unwrap();
+    assert_eq!(store.inner().stream_len("stream"), 3);
+    assert!(matches!(
+        store.append_multi(&appends),
+        Err(AppendError::WrongExpectedVersion { actual: 3, .. })
+    ));
+
+    let mut store = FaultyEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultConfig {
+            error_rate: 1.0,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        store.append_multi(&appends),
+        Err(AppendError::Store(FaultError::Injected))
+    );
+    assert_eq!(store.inner().stream_len("stream"), 0);
+}
+
+#[test]
+fn test_faulty_broker() {
+    let latency = Duration::from_millis(5);
+    let mut broker = FaultyBroker::new(
+        OnMemoryBroker::new(),
+        FaultConfig {
+            latency,
+            ..Default::default()
+        },
+    );
+    let started = Instant::now();
+    broker.publish(&"m".to_string()).unwrap();
+    assert_eq!(broker.poll().unwrap(), Some("m".to_string()));
+    assert!(started.elapsed() >= latency * 2);
+
+    let mut broker = FaultyBroker::new(
+        OnMemoryBroker::<String>::new(),
+        FaultConfig {
+            error_rate: 1.0,
+            ..Default::default()
+        },
+    );
+    assert_eq!(broker.publish(&"m".to_string()), Err(FaultError::Injected));
+    assert!(broker.inner().is_empty());
+}
@@ -0,0 +1,279 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Subscription;
+use crate::event_store::QueryHandler;
+use crate::json;
+
+/// Arguments of a GraphQL field, by name.
+pub type Arguments = HashMap<String, String>;
+
+type Resolver = Box<dyn Fn(&Arguments) -> Result<String, String>>;
+
+/// A field of a GraphQL type, or an argument of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphQlField {
+    /// Name of the field.
+    pub name: &'static str,
+    /// GraphQL type of the field, such as `String!` or `[Order!]!`.
+    pub type_ref: &'static str,
+}
+
+impl GraphQlField {
+    /// Create a field.
+    pub fn new(name: &'static str, type_ref: &'static str) -> Self {
+        Self { name, type_ref }
+    }
+}
+
+/// Types which represent a read model exposed as a GraphQL object type.
+pub trait GraphQlObject {
+    /// Get the name of the GraphQL type.
+    fn type_name() -> &'static str;
+    /// Get the fields of the GraphQL type.
+    fn fields() -> Vec<GraphQlField>;
+    /// Serialize the object as a JSON object with the fields.
+    fn to_json(&self) -> String;
+}
+
+/// Types which can be returned by a GraphQL field.
+pub trait GraphQlOutput {
+    /// Get the GraphQL type returned.
+    fn type_ref() -> String;
+    /// Get the object type returned, to declare it in the schema.
+    fn object_type() -> Option<(&'static str, Vec<GraphQlField>)>;
+    /// Serialize the value as JSON.
+    fn to_json(&self) -> String;
+}
+
+impl<T: GraphQlObject> GraphQlOutput for T {
+    fn type_ref() -> String {
+        format!("{}!", T::type_name())
+    }
+
+    fn object_type() -> Option<(&'static str, Vec<GraphQlField>)> {
+        Some((T::type_name(), T::fields()))
+    }
+
+    fn to_json(&self) -> String {
+        GraphQlObject::to_json(self)
+    }
+}
+
+impl<T: GraphQlObject> GraphQlOutput for Option<T> {
+    fn type_ref() -> String {
+        T::type_name().to_string()
+    }
+
+    fn object_type() -> Option<(&'static str, Vec<GraphQlField>)> {
+        Some((T::type_name(), T::fields()))
+    }
+
+    fn to_json(&self) -> String {
+        self.as_ref()
+            .map_or_else(|| "null".to_string(), GraphQlObject::to_json)
+    }
+}
+
+impl<T: GraphQlObject> GraphQlOutput for Vec<T> {
+    fn type_ref() -> String {
+        format!("[{}!]!", T::type_name())
+    }
+
+    fn object_type() -> Option<(&'static str, Vec<GraphQlField>)> {
+        Some((T::type_name(), T::fields()))
+    }
+
+    fn to_json(&self) -> String {
+        let items = self.iter().map(GraphQlObject::to_json).collect::<Vec<_>>();
+        format!("[{}]", items.join(","))
+    }
+}
+
+struct RootField {
+    name: &'static str,
+    arguments: Vec<GraphQlField>,
+    type_ref: String,
+}
+
+/// GraphQL schema exposing read-model queries as query fields, and broker subscriptions as
+/// subscription fields.
+///
+/// The schema is generated from the read-model types in SDL, to be served by a GraphQL
+/// server which resolves the root fields with [`execute`](Self::execute) and the
+/// subscriptions with [`GraphQlSubscription`].
+///
+/// The crate does not depend on a GraphQL server, so this neither parses nor validates
+/// GraphQL documents: the server, e.g. a dynamic schema of async-graphql built from the
+/// [`sdl`](Self::sdl), parses the request and delegates each root field here by name.
+#[derive(Default)]
+pub struct GraphQlSchema {
+    queries: Vec<(RootField, Resolver)>,
+    subscriptions: Vec<RootField>,
+    types: Vec<(&'static str, Vec<GraphQlField>)>,
+}
+
+impl GraphQlSchema {
+    /// Create an empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expose the query handler as a query field. `to_query` builds the query from the
+    /// arguments of the field, failing with a message for invalid arguments.
+    pub fn query<Q, H>(
+        mut self,
+        name: &'static str,
+        arguments: Vec<GraphQlField>,
+        handler: H,
+        to_query: impl Fn(&Arguments) -> Result<Q, String> + 'static,
+    ) -> Self
+    where
+        H: QueryHandler<Q> + 'static,
+        H::Response: GraphQlOutput,
+    {
+        self.declare_type::<H::Response>();
+        let field = RootField {
+            name,
+            arguments,
+            type_ref: H::Response::type_ref(),
+        };
+        let resolver: Resolver = Box::new(move |arguments| {
+            let query = to_query(arguments)?;
+            let response = handler.handle(query).map_err(|e| e.to_string())?;
+            Ok(response.to_json())
+        });
+        self.queries.push((field, resolver));
+        self
+    }
+
+    /// Declare a subscription field delivering messages of type `M`.
+    pub fn subscription<M: GraphQlOutput>(
+        mut self,
+        name: &'static str,
+        arguments: Vec<GraphQlField>,
+    ) -> Self {
+        self.declare_type::<M>();
+        self.subscriptions.push(RootField {
+            name,
+            arguments,
+            type_ref: M::type_ref(),
+        });
+        self
+    }
+
+    /// Resolve the query field, returning the GraphQL response as JSON.
+    pub fn execute(&self, field: &str, arguments: &Arguments) -> Result<String, GraphQlError> {
+        let (_, resolver) = self
+            .queries
+            .iter()
+            .find(|(f, _)| f.name == field)
+            .ok_or_else(|| GraphQlError::UnknownField(field.to_string()))?;
+        let value = resolver(arguments).map_err(GraphQlError::Resolver)?;
+        Ok(response(field, &value))
+    }
+
+    /// Get the schema in the GraphQL schema definition language.
+    pub fn sdl(&self) -> String {
+        let mut blocks = Vec::new();
+        for (name, fields) in [
+            (
+                "Query",
+                self.queries.iter().map(|(f, _)| f).collect::<Vec<_>>(),
+            ),
+            ("Subscription", self.subscriptions.iter().collect()),
+        ] {
+            if fields.is_empty() {
+                continue;
+            }
+            let lines = fields
+                .iter()
+                .map(|f| {
+                    let arguments = f
+                        .arguments
+                        .iter()
+                        .map(|a| format!("{}: {}", a.name, a.type_ref))
+                        .collect::<Vec<_>>();
+                    if arguments.is_empty() {
+                        format!("  {}: {}\n", f.name, f.type_ref)
+                    } else {
+                        format!("  {}({}): {}\n", f.name, arguments.join(", "), f.type_ref)
+                    }
+                })
+                .collect::<String>();
+            blocks.push(format!("type {} {{\n{}}}\n", name, lines));
+        }
+        for (name, fields) in &self.types {
+            let lines = fields
+                .iter()
+                .map(|f| format!("  {}: {}\n", f.name, f.type_ref))
+                .collect::<String>();
+            blocks.push(format!("type {} {{\n{}}}\n", name, lines));
+        }
+        blocks.join("\n")
+    }
+
+    fn declare_type<T: GraphQlOutput>(&mut self) {
+        if let Some((name, fields)) = T::object_type() {
+            if !self.types.iter().any(|(n, _)| *n == name) {
+                self.types.push((name, fields));
+            }
+        }
+    }
+}
+
+/// Broker subscription delivering its messages as GraphQL subscription responses.
+pub struct GraphQlSubscription<S> {
+    field: &'static str,
+    subscription: S,
+}
+
+impl<S> GraphQlSubscription<S> {
+    /// Deliver the messages of the subscription as the field.
+    pub fn new(field: &'static str, subscription: S) -> Self {
+        Self {
+            field,
+            subscription,
+        }
+    }
+
+    /// Get the next response as JSON, if a message is available.
+    pub fn poll<M>(&mut self) -> Result<Option<String>, S::Error>
+    where
+        S: Subscription<M>,
+        M: GraphQlOutput,
+    {
+        Ok(self
+            .subscription
+            .poll()?
+            .map(|message| response(self.field, &message.to_json())))
+    }
+}
+
+fn response(field: &str, value: &str) -> String {
+    format!("{{\"data\":{{{}:{}}}}}", json::escape(field), value)
+}
+
+/// Error returned when resolving a GraphQL field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphQlError {
+    /// No query field has the name.
+    UnknownField(String),
+    /// The arguments were invalid or the query handler failed.
+    Resolver(String),
+}
+
+impl fmt::Display for GraphQlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphQlError::UnknownField(name) => write!(f, "unknown field: {}", name),
+            GraphQlError::Resolver(reason) => write!(f, "resolver error: {}", reason),
+        }
+    }
+}
+
+impl Error for GraphQlError {}
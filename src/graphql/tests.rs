@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use super::*;
+use crate::broker::{OnMemoryBroker, Publisher};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Order {
+    id: String,
+    status: String,
+}
+
+impl GraphQlObject for Order {
+    fn type_name() -> &'static str {
+        "Order"
+    }
+
+    fn fields() -> Vec<GraphQlField> {
+        vec![
+            GraphQlField::new("id", "ID!"),
+            GraphQlField::new("status", "String!"),
+        ]
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"id\":{},\"status\":{}}}",
+            json::escape(&self.id),
+            json::escape(&self.status)
+        )
+    }
+}
+
+struct OrderQuery(String);
+
+struct Orders(HashMap<String, Order>);
+
+impl QueryHandler<OrderQuery> for Orders {
+    type Response = Option<Order>;
+    type Error = Infallible;
+
+    fn handle(&self, query: OrderQuery) -> Result<Option<Order>, Infallible> {
+        Ok(self.0.get(&query.0).cloned())
+    }
+}
+
+fn order(id: &str, status: &str) -> Order {
+    Order {
+        id: id.to_string(),
+        status: status.to_string(),
+    }
+}
+
+fn schema() -> GraphQlSchema {
+    let orders = Orders(HashMap::from([("1".to_string(), order("1", "Pending"))]));
+    GraphQlSchema::new()
+        .query(
+            "order",
+            vec![GraphQlField::new("id", "ID!")],
+            orders,
+            |arguments| {
+                arguments
+                    .get("id")
+                    .map(|id| OrderQuery(id.clone()))
+                    .ok_or_else(|| "missing argument: id".to_string())
+            },
+        )
+        .subscription::<Order>("orderUpdated", Vec::new())
+}
+
+#[test]
+fn test_sdl() {
+    assert_eq!(
+        schema().sdl(),
+        "type Query {\n  order(id: ID!): Order\n}\n\n\
+         type Subscription {\n  orderUpdated: Order!\n}\n\n\
+         type Order {\n  id: ID!\n  status: String!\n}\n"
+    );
+}
+
+#[test]
+fn test_execute() {
+    let schema = schema();
+    let arguments = Arguments::from([("id".to_string(), "1".to_string())]);
+    assert_eq!(
+        schema.execute("order", &arguments),
+        Ok("{\"data\":{\"order\":{\"id\":\"1\",\"status\":\"Pending\"}}}".to_string())
+    );
+    let arguments = Arguments::from([("id".to_string(), "2".to_string())]);
+    assert_eq!(
+        schema.execute("order", &arguments),
+        Ok("{\"data\":{\"order\":null}}".to_string())
+    );
+    assert_eq!(
+        schema.execute("order", &Arguments::new()),
+        Err(GraphQlError::Resolver("missing argument: id".to_string()))
+    );
+    assert_eq!(
+        schema.execute("orders", &Arguments::new()),
+        Err(GraphQlError::UnknownField("orders".to_string()))
+    );
+}
+
+#[test]
+fn test_subscription() {
+    let mut broker = OnMemoryBroker::new();
+    broker.publish(&order("1", "Shipped")).unwrap();
+    let mut subscription = GraphQlSubscription::new("orderUpdated", broker);
+    assert_eq!(
+        subscription.poll::<Order>(),
+        Ok(Some(
+            "{\"data\":{\"orderUpdated\":{\"id\":\"1\",\"status\":\"Shipped\"}}}".to_string()
+        ))
+    );
+    assert_eq!(subscription.poll::<Order>(), Ok(None));
+}
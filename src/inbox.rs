@@ -0,0 +1,284 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::{Clock, SystemClock};
+use crate::command_bus::CommandBus;
+use crate::dedup::ExpiringDedup;
+use crate::envelope::Envelope;
+use crate::event_id::EventId;
+use crate::trace_context::{TraceCarrier, Tracer};
+
+/// A message received from an external system, keyed for deduplication.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboxMessage<M> {
+    /// Key identifying the message across redeliveries.
+    pub dedup_key: String,
+    /// The received message.
+    pub message: M,
+}
+
+impl<M> InboxMessage<M> {
+    /// Create a message deduplicated by the ID of the event it carries.
+    pub fn for_event(id: EventId, message: M) -> Self {
+        Self {
+            dedup_key: id.to_string(),
+            message,
+        }
+    }
+}
+
+impl<E> InboxMessage<Envelope<E>> {
+    /// Create a message carrying the envelope, deduplicated by its event ID.
+    pub fn from_envelope(envelope: Envelope<E>) -> Self {
+        Self::for_event(envelope.id, envelope)
+    }
+}
+
+/// Processing status of a message in the inbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InboxStatus {
+    /// The message is waiting to be processed.
+    Pending,
+    /// The message has been translated and dispatched.
+    Processed,
+    /// The message could not be processed.
+    Failed(String),
+}
+
+/// Types which represent an inbox persisting external messages before they are processed.
+pub trait Inbox<M> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Persist the message. Returns `false` if a message with the same dedup key was already received.
+    fn receive(&mut self, message: InboxMessage<M>) -> Result<bool, Self::Error>;
+    /// Get the messages waiting to be processed, in order of reception.
+    fn pending(&self) -> Result<Vec<InboxMessage<M>>, Self::Error>;
+    /// Get the status of the message.
+    fn status(&self, dedup_key: &str) -> Result<Option<InboxStatus>, Self::Error>;
+    /// Mark the message as processed.
+    fn mark_processed(&mut self, dedup_key: &str) -> Result<(), Self::Error>;
+    /// Mark the message as failed.
+    fn mark_failed(&mut self, dedup_key: &str, reason: String) -> Result<(), Self::Error>;
+}
+
+/// Types which translate external messages into commands.
+pub trait MessageTranslator<M> {
+    /// Associated Type representing the command type.
+    type Command;
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Translate the message into a command.
+    fn translate(&self, message: &M) -> Result<Self::Command, Self::Error>;
+}
+
+/// Summary of a processing run of the inbox.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InboxReport {
+    /// Number of messages dispatched successfully.
+    pub processed: usize,
+    /// Number of messages marked as failed.
+    pub failed: usize,
+}
+
+/// Translate the pending messages of the inbox and dispatch them to the command bus.
+///
+/// Messages which fail to translate or dispatch are marked as failed and are not retried.
+pub fn process<M, I, T, B>(
+    inbox: &mut I,
+    translator: &T,
+    bus: &mut B,
+) -> Result<InboxReport, I::Error>
+where
+    I: Inbox<M>,
+    T: MessageTranslator<M>,
+    B: CommandBus<T::Command>,
+{
+    let mut report = InboxReport::default();
+    for message in inbox.pending()? {
+        let result = dispatch_message(&message, translator, bus);
+        record_result(inbox, &message.dedup_key, result, &mut report)?;
+    }
+    Ok(report)
+}
+
+/// Process the pending messages of the inbox like [`process`], within a span of the tracer
+/// per message, child of the trace context the message carries.
+pub fn process_traced<M, I, T, B, R>(
+    inbox: &mut I,
+    translator: &T,
+    bus: &mut B,
+    tracer: &mut R,
+) -> Result<InboxReport, I::Error>
+where
+    M: TraceCarrier,
+    I: Inbox<M>,
+    T: MessageTranslator<M>,
+    B: CommandBus<T::Command>,
+    R: Tracer,
+{
+    let mut report = InboxReport::default();
+    for message in inbox.pending()? {
+        let parent = message.message.trace_context();
+        let span = tracer.start_span("inbox.process", parent.as_ref());
+        let result = dispatch_message(&message, translator, bus);
+        tracer.end_span(&span, result.as_ref().err().map(String::as_str));
+        record_result(inbox, &message.dedup_key, result, &mut report)?;
+    }
+    Ok(report)
+}
+
+fn dispatch_message<M, T, B>(
+    message: &InboxMessage<M>,
+    translator: &T,
+    bus: &mut B,
+) -> Result<(), String>
+where
+    T: MessageTranslator<M>,
+    B: CommandBus<T::Command>,
+{
+    let command = translator
+        .translate(&message.message)
+        .map_err(|e| e.to_string())?;
+    bus.dispatch(command).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn record_result<M, I: Inbox<M>>(
+    inbox: &mut I,
+    dedup_key: &str,
+    result: Result<(), String>,
+    report: &mut InboxReport,
+) -> Result<(), I::Error> {
+    match result {
+        Ok(()) => {
+            inbox.mark_processed(dedup_key)?;
+            report.processed += 1;
+        }
+        Err(reason) => {
+            inbox.mark_failed(dedup_key, reason)?;
+            report.failed += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Inbox keeping the messages in memory, with the time they were processed or failed at.
+#[derive(Debug)]
+pub struct OnMemoryInbox<M, K = SystemClock> {
+    messages: Vec<(InboxMessage<M>, InboxStatus, Option<SystemTime>)>,
+    clock: K,
+}
+
+impl<M> OnMemoryInbox<M> {
+    /// Create an empty inbox.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<M, K: Clock> OnMemoryInbox<M, K> {
+    /// Create an empty inbox, timing the messages with the clock.
+    pub fn with_clock(clock: K) -> Self {
+        Self {
+            messages: Vec::new(),
+            clock,
+        }
+    }
+
+    fn finish(&mut self, dedup_key: &str, status: InboxStatus) -> Result<(), InboxError> {
+        let now = self.clock.now();
+        let (_, current, finished_at) = self
+            .messages
+            .iter_mut()
+            .find(|(m, _, _)| m.dedup_key == dedup_key)
+            .ok_or_else(|| InboxError::NotFound(dedup_key.to_string()))?;
+        *current = status;
+        *finished_at = Some(now);
+        Ok(())
+    }
+}
+
+impl<M> Default for OnMemoryInbox<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Clone, K: Clock> Inbox<M> for OnMemoryInbox<M, K> {
+    type Error = InboxError;
+
+    fn receive(&mut self, message: InboxMessage<M>) -> Result<bool, Self::Error> {
+        if self
+            .messages
+            .iter()
+            .any(|(m, _, _)| m.dedup_key == message.dedup_key)
+        {
+            return Ok(false);
+        }
+        self.messages.push((message, InboxStatus::Pending, None));
+        Ok(true)
+    }
+
+    fn pending(&self) -> Result<Vec<InboxMessage<M>>, Self::Error> {
+        Ok(self
+            .messages
+            .iter()
+            .filter(|(_, s, _)| *s == InboxStatus::Pending)
+            .map(|(m, _, _)| m.clone())
+            .collect())
+    }
+
+    fn status(&self, dedup_key: &str) -> Result<Option<InboxStatus>, Self::Error> {
+        Ok(self
+            .messages
+            .iter()
+            .find(|(m, _, _)| m.dedup_key == dedup_key)
+            .map(|(_, s, _)| s.clone()))
+    }
+
+    fn mark_processed(&mut self, dedup_key: &str) -> Result<(), Self::Error> {
+        self.finish(dedup_key, InboxStatus::Processed)
+    }
+
+    fn mark_failed(&mut self, dedup_key: &str, reason: String) -> Result<(), Self::Error> {
+        self.finish(dedup_key, InboxStatus::Failed(reason))
+    }
+}
+
+/// Pending messages never expire, so that no message is lost.
+impl<M, K> ExpiringDedup for OnMemoryInbox<M, K> {
+    type Error = InboxError;
+
+    fn sweep(&mut self, now: SystemTime, ttl: Duration) -> Result<usize, Self::Error> {
+        let before = self.messages.len();
+        self.messages.retain(|(_, _, finished_at)| {
+            finished_at
+                .and_then(|at| at.checked_add(ttl))
+                .is_none_or(|expiry| expiry > now)
+        });
+        Ok(before - self.messages.len())
+    }
+}
+
+/// Error returned by the [`OnMemoryInbox`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InboxError {
+    /// No message with the given dedup key exists.
+    NotFound(String),
+}
+
+impl fmt::Display for InboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InboxError::NotFound(key) => write!(f, "inbox message not found: {}", key),
+        }
+    }
+}
+
+impl Error for InboxError {}
@@ -0,0 +1,109 @@
+use super::*;
+
+#[derive(Debug, Clone)]
+struct WebhookPayload(String);
+
+#[derive(Debug, PartialEq)]
+struct CreateUser(String);
+
+#[derive(Debug)]
+struct TranslateError;
+
+impl fmt::Display for TranslateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TranslateError")
+    }
+}
+
+impl Error for TranslateError {}
+
+struct WebhookTranslator;
+
+impl MessageTranslator<WebhookPayload> for WebhookTranslator {
+    type Command = CreateUser;
+    type Error = TranslateError;
+
+    fn translate(&self, message: &WebhookPayload) -> Result<Self::Command, Self::Error> {
+        match message.0.strip_prefix("user:") {
+            Some(name) => Ok(CreateUser(name.to_string())),
+            None => Err(TranslateError),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RecordingBus {
+    commands: Vec<CreateUser>,
+}
+
+impl CommandBus<CreateUser> for RecordingBus {
+    type Response = ();
+    type Error = TranslateError;
+
+    fn dispatch(&mut self, command: CreateUser) -> Result<Self::Response, Self::Error> {
+        self.commands.push(command);
+        Ok(())
+    }
+}
+
+fn message(key: &str, payload: &str) -> InboxMessage<WebhookPayload> {
+    InboxMessage {
+        dedup_key: key.to_string(),
+        message: WebhookPayload(payload.to_string()),
+    }
+}
+
+#[test]
+fn test_receive_deduplicates() {
+    let mut inbox = OnMemoryInbox::new();
+    assert!(inbox.receive(message("m-1", "user:alice")).unwrap());
+    assert!(!inbox.receive(message("m-1", "user:alice")).unwrap());
+    assert_eq!(inbox.pending().unwrap().len(), 1);
+}
+
+#[test]
+fn test_process() {
+    let mut inbox = OnMemoryInbox::new();
+    inbox.receive(message("m-1", "user:alice")).unwrap();
+    inbox.receive(message("m-2", "garbage")).unwrap();
+    let mut bus = RecordingBus::default();
+
+    let report = process(&mut inbox, &WebhookTranslator, &mut bus).unwrap();
+    assert_eq!(
+        report,
+        InboxReport {
+            processed: 1,
+            failed: 1
+        }
+    );
+    assert_eq!(bus.commands, vec![CreateUser("alice".to_string())]);
+    assert_eq!(inbox.status("m-1").unwrap(), Some(InboxStatus::Processed));
+    assert_eq!(
+        inbox.status("m-2").unwrap(),
+        Some(InboxStatus::Failed("TranslateError".to_string()))
+    );
+
+    // A redelivered message is not dispatched again.
+    assert!(!inbox.receive(message("m-1", "user:alice")).unwrap());
+    let report = process(&mut inbox, &WebhookTranslator, &mut bus).unwrap();
+    assert_eq!(report, InboxReport::default());
+    assert_eq!(bus.commands.len(), 1);
+}
+
+#[test]
+fn test_deduplicate_by_event_id() {
+    let mut inbox = OnMemoryInbox::new();
+    let envelope = Envelope::new("user-1", 1, "Webhook", "user:alice".to_string());
+    let id = envelope.id;
+
+    assert!(inbox
+        .receive(InboxMessage::from_envelope(envelope.clone()))
+        .unwrap());
+    assert!(!inbox
+        .receive(InboxMessage::for_event(id, envelope))
+        .unwrap());
+    assert_eq!(
+        inbox.status(&id.to_string()).unwrap(),
+        Some(InboxStatus::Pending)
+    );
+}
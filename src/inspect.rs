@@ -0,0 +1,335 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt::{self, Debug};
+
+use crate::envelope::{Envelope, OCCURRED_AT_METADATA_KEY};
+use crate::event_store::EventLoader;
+use crate::redact::{Redact, Redacted};
+use crate::snapshot::{Snapshot, SnapshotStore};
+use crate::summary::{AggregateSummary, SummaryValue};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const BOLD_CYAN: &str = "\x1b[1;36m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+
+/// Options of the rendering of [`dump`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DumpOptions {
+    /// Colorize the output with ANSI escape codes.
+    pub color: bool,
+    /// Render the metadata of the events.
+    pub metadata: bool,
+    /// Pretty-print the payloads over several lines.
+    pub pretty: bool,
+}
+
+/// Render the events as human-readable text, one event per line.
+pub fn dump<E: Debug>(events: &[Envelope<E>], options: &DumpOptions) -> String {
+    render(events, options, |payload| payload)
+}
+
+/// Render the events like [`dump`], with the sensitive fields of their payloads masked.
+pub fn dump_redacted<E: Redact + Debug>(events: &[Envelope<E>], options: &DumpOptions) -> String {
+    render(events, options, Redacted)
+}
+
+fn render<'a, E: 'a, D: Debug>(
+    events: &'a [Envelope<E>],
+    options: &DumpOptions,
+    view: impl Fn(&'a E) -> D,
+) -> String {
+    let paint = |code: &str, text: &str| {
+        if options.color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    };
+    let mut out = String::new();
+    for event in events {
+        let occurred_at = event
+            .metadata
+            .get(OCCURRED_AT_METADATA_KEY)
+            .map(String::as_str)
+            .unwrap_or("-");
+        let payload = if options.pretty {
+            format!("{:#?}", view(&event.payload))
+        } else {
+            format!("{:?}", view(&event.payload))
+        };
+        out.push_str(&format!(
+            "{} {} {} {} {}\n",
+            paint(DIM, &format!("#{}", event.position)),
+            paint(YELLOW, &format!("{}@{}", event.stream_id, event.version)),
+            paint(BOLD_CYAN, &event.event_type),
+            paint(DIM, occurred_at),
+            payload
+        ));
+        if options.metadata {
+            for (key, value) in &event.metadata {
+                out.push_str(&format!("    {}={}\n", paint(DIM, key), value));
+            }
+        }
+    }
+    out
+}
+
+/// Load the stream from the store and render it with [`dump`].
+pub fn dump_stream<S, E>(
+    store: &S,
+    stream_id: &S::StreamId,
+    options: &DumpOptions,
+) -> Result<String, S::Error>
+where
+    S: EventLoader<Persistable = Envelope<E>>,
+    E: Debug,
+{
+    Ok(dump(&store.load(stream_id)?, options))
+}
+
+/// Replay the events into the aggregate, describing its state at the version of each event.
+pub fn replay_summaries<A>(events: &[Envelope<A::Event>]) -> Vec<(u64, SummaryValue)>
+where
+    A: AggregateSummary + Default,
+{
+    let mut aggregate = A::default();
+    events
+        .iter()
+        .map(|event| {
+            aggregate.apply(&event.payload);
+            (event.version, aggregate.summary())
+        })
+        .collect()
+}
+
+/// Render the events with the state of the aggregate after each of them, one event per line.
+pub fn dump_replay<A>(events: &[Envelope<A::Event>]) -> String
+where
+    A: AggregateSummary + Default,
+{
+    events
+        .iter()
+        .zip(replay_summaries::<A>(events))
+        .map(|(event, (_, summary))| {
+            format!(
+                "{}@{} {} -> {}\n",
+                event.stream_id, event.version, event.event_type, summary
+            )
+        })
+        .collect()
+}
+
+/// A value of a snapshot differing from the state replayed from the events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotMismatch {
+    /// Path of the value in the summary, such as `address.city` or `items[2]`; empty for the
+    /// whole summary.
+    pub path: String,
+    /// The value in the snapshot, `None` if it has no such value.
+    pub snapshot: Option<SummaryValue>,
+    /// The replayed value, `None` if the replayed state has no such value.
+    pub replayed: Option<SummaryValue>,
+}
+
+/// A snapshot next to the state replayed from the events of its stream up to its version, to
+/// diagnose snapshots which drifted from the events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotPreview {
+    /// ID of the stream.
+    pub stream_id: String,
+    /// Version of the stream the snapshot was captured at.
+    pub version: u64,
+    /// Version the events were replayed to, below the version of the snapshot if the stream
+    /// has fewer events.
+    pub replayed_version: u64,
+    /// Summary of the snapshot.
+    pub snapshot: SummaryValue,
+    /// Summary of the replayed state.
+    pub replayed: SummaryValue,
+    /// Values differing between the snapshot and the replayed state.
+    pub mismatches: Vec<SnapshotMismatch>,
+}
+
+impl SnapshotPreview {
+    /// Whether the snapshot matches the replayed state at its version.
+    pub fn is_consistent(&self) -> bool {
+        self.version == self.replayed_version && self.mismatches.is_empty()
+    }
+}
+
+/// Replay the events of the stream up to the version of the snapshot, and compare the
+/// summaries of the snapshot and of the replayed state.
+pub fn preview_snapshot<A>(snapshot: &Snapshot<A>, events: &[Envelope<A::Event>]) -> SnapshotPreview
+where
+    A: AggregateSummary + Default,
+{
+    let mut replayed = A::default();
+    let mut replayed_version = 0;
+    for event in events.iter().take_while(|e| e.version <= snapshot.version) {
+        replayed.apply(&event.payload);
+        replayed_version = event.version;
+    }
+    let snapshot_summary = snapshot.state.summary();
+    let replayed = replayed.summary();
+    let mut mismatches = Vec::new();
+    diff(
+        String::new(),
+        Some(&snapshot_summary),
+        Some(&replayed),
+        &mut mismatches,
+    );
+    SnapshotPreview {
+        stream_id: snapshot.stream_id.clone(),
+        version: snapshot.version,
+        replayed_version,
+        snapshot: snapshot_summary,
+        replayed,
+        mismatches,
+    }
+}
+
+/// Load the latest snapshot of the stream and its events, and preview it with
+/// [`preview_snapshot`]. Returns `None` if the stream has no snapshot.
+pub fn preview_stream_snapshot<A, S, N>(
+    store: &S,
+    snapshots: &N,
+    stream_id: &String,
+) -> Result<Option<SnapshotPreview>, SnapshotPreviewError<S::Error, N::Error>>
+where
+    A: AggregateSummary + Default,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>>,
+    N: SnapshotStore<A>,
+{
+    let snapshot = match snapshots
+        .load(stream_id)
+        .map_err(SnapshotPreviewError::Snapshot)?
+    {
+        Some(snapshot) => snapshot,
+        None => return Ok(None),
+    };
+    let events = store.load(stream_id).map_err(SnapshotPreviewError::Store)?;
+    Ok(Some(preview_snapshot(&snapshot, &events)))
+}
+
+/// Render the preview, with the mismatching values highlighted.
+pub fn dump_snapshot_preview(preview: &SnapshotPreview, options: &DumpOptions) -> String {
+    let paint = |code: &str, text: &str| {
+        if options.color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    };
+    let describe = |value: &Option<SummaryValue>| {
+        value
+            .as_ref()
+            .map_or_else(|| "missing".to_string(), SummaryValue::to_json)
+    };
+    let mut out = format!(
+        "{} snapshot -> {}\n{} replayed -> {}\n",
+        paint(
+            YELLOW,
+            &format!("{}@{}", preview.stream_id, preview.version)
+        ),
+        preview.snapshot,
+        paint(
+            YELLOW,
+            &format!("{}@{}", preview.stream_id, preview.replayed_version)
+        ),
+        preview.replayed
+    );
+    if preview.replayed_version != preview.version {
+        out.push_str(&paint(
+            RED,
+            &format!(
+                "! the stream ends at version {}\n",
+                preview.replayed_version
+            ),
+        ));
+    }
+    for mismatch in &preview.mismatches {
+        let path = if mismatch.path.is_empty() {
+            "(root)"
+        } else {
+            &mismatch.path
+        };
+        out.push_str(&paint(
+            RED,
+            &format!(
+                "! {}: snapshot {}, replayed {}\n",
+                path,
+                describe(&mismatch.snapshot),
+                describe(&mismatch.replayed)
+            ),
+        ));
+    }
+    out
+}
+
+fn diff(
+    path: String,
+    snapshot: Option<&SummaryValue>,
+    replayed: Option<&SummaryValue>,
+    mismatches: &mut Vec<SnapshotMismatch>,
+) {
+    let join = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+    match (snapshot, replayed) {
+        (Some(SummaryValue::Object(left)), Some(SummaryValue::Object(right))) => {
+            for (name, value) in left {
+                let other = right.iter().find(|(n, _)| n == name).map(|(_, v)| v);
+                diff(join(name), Some(value), other, mismatches);
+            }
+            for (name, value) in right {
+                if !left.iter().any(|(n, _)| n == name) {
+                    diff(join(name), None, Some(value), mismatches);
+                }
+            }
+        }
+        (Some(SummaryValue::List(left)), Some(SummaryValue::List(right))) => {
+            for index in 0..left.len().max(right.len()) {
+                diff(
+                    format!("{}[{}]", path, index),
+                    left.get(index),
+                    right.get(index),
+                    mismatches,
+                );
+            }
+        }
+        (left, right) if left != right => mismatches.push(SnapshotMismatch {
+            path,
+            snapshot: left.cloned(),
+            replayed: right.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// Error returned by [`preview_stream_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotPreviewError<E, NE> {
+    /// The events could not be loaded.
+    Store(E),
+    /// The snapshot could not be loaded.
+    Snapshot(NE),
+}
+
+impl<E: Error, NE: Error> fmt::Display for SnapshotPreviewError<E, NE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotPreviewError::Store(e) => write!(f, "store error: {}", e),
+            SnapshotPreviewError::Snapshot(e) => write!(f, "snapshot store error: {}", e),
+        }
+    }
+}
+
+impl<E: Error, NE: Error> Error for SnapshotPreviewError<E, NE> {}
@@ -0,0 +1,172 @@
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+enum OrgEvent {
+    Created { name: String },
+    UserAdded(u32),
+}
+
+fn store() -> OnMemoryEventStore<OrgEvent> {
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[
+            Envelope::new(
+                "org-1",
+                0,
+                "Created",
+                OrgEvent::Created {
+                    name: "Acme".to_string(),
+                },
+            )
+            .with_metadata(OCCURRED_AT_METADATA_KEY, "2024-01-01T00:00:00Z")
+            .with_metadata("user_id", "admin"),
+            Envelope::new("org-1", 0, "UserAdded", OrgEvent::UserAdded(7)),
+        ])
+        .unwrap();
+    store
+}
+
+#[test]
+fn test_dump_stream() {
+    let output = dump_stream(&store(), &"org-1".to_string(), &DumpOptions::default()).unwrap();
+    assert_eq!(
+        output,
+        concat!(
+            "#0 org-1@1 Created 2024-01-01T00:00:00Z Created { name: \"Acme\" }\n",
+            "#1 org-1@2 UserAdded - UserAdded(7)\n",
+        )
+    );
+}
+
+#[test]
+fn test_dump_with_metadata_and_color() {
+    let events = store().load(&"org-1".to_string()).unwrap();
+    let output = dump(
+        &events[..1],
+        &DumpOptions {
+            color: true,
+            metadata: true,
+            pretty: false,
+        },
+    );
+    assert!(output.starts_with("\x1b[2m#0\x1b[0m \x1b[33morg-1@1\x1b[0m \x1b[1;36mCreated\x1b[0m"));
+    assert!(output.contains("    \x1b[2muser_id\x1b[0m=admin\n"));
+}
+
+#[derive(Debug, Clone, Default)]
+struct Org {
+    name: String,
+    users: Vec<u32>,
+}
+
+impl crate::aggregate::Aggregate for Org {
+    type Id = u32;
+    type Event = OrgEvent;
+
+    fn aggregate_type() -> &'static str {
+        "org"
+    }
+
+    fn event_type(event: &OrgEvent) -> String {
+        match event {
+            OrgEvent::Created { .. } => "Created".to_string(),
+            OrgEvent::UserAdded(_) => "UserAdded".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &OrgEvent) {
+        match event {
+            OrgEvent::Created { name } => self.name = name.clone(),
+            OrgEvent::UserAdded(user) => self.users.push(*user),
+        }
+    }
+}
+
+impl AggregateSummary for Org {
+    fn summary(&self) -> SummaryValue {
+        SummaryValue::object()
+            .field("name", self.name.as_str())
+            .field("users", self.users.len() as i64)
+    }
+}
+
+#[test]
+fn test_replay_summaries() {
+    let events = store().load(&"org-1".to_string()).unwrap();
+    let summaries = replay_summaries::<Org>(&events);
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[1].0, 2);
+    assert_eq!(summaries[1].1.get("users"), Some(&SummaryValue::Integer(1)));
+    assert_eq!(
+        dump_replay::<Org>(&events),
+        concat!(
+            "org-1@1 Created -> {\"name\":\"Acme\",\"users\":0}\n",
+            "org-1@2 UserAdded -> {\"name\":\"Acme\",\"users\":1}\n",
+        )
+    );
+}
+
+#[test]
+fn test_preview_snapshot() {
+    let store = store();
+    let mut snapshots = crate::snapshot::OnMemorySnapshotStore::new();
+    assert_eq!(
+        preview_stream_snapshot::<Org, _, _>(&store, &snapshots, &"org-1".to_string()),
+        Ok(None)
+    );
+    snapshots
+        .save(Snapshot {
+            stream_id: "org-1".to_string(),
+            version: 1,
+            state: Org {
+                name: "Acme".to_string(),
+                users: Vec::new(),
+            },
+        })
+        .unwrap();
+    let preview = preview_stream_snapshot::<Org, _, _>(&store, &snapshots, &"org-1".to_string())
+        .unwrap()
+        .unwrap();
+    assert!(preview.is_consistent());
+
+    // A snapshot captured while a user was counted twice.
+    let drifted = Snapshot {
+        stream_id: "org-1".to_string(),
+        version: 2,
+        state: Org {
+            name: "Acme".to_string(),
+            users: vec![7, 7],
+        },
+    };
+    let events = store.load(&"org-1".to_string()).unwrap();
+    let preview = preview_snapshot(&drifted, &events);
+    assert!(!preview.is_consistent());
+    assert_eq!(
+        preview.mismatches,
+        [SnapshotMismatch {
+            path: "users".to_string(),
+            snapshot: Some(SummaryValue::Integer(2)),
+            replayed: Some(SummaryValue::Integer(1)),
+        }]
+    );
+    assert_eq!(
+        dump_snapshot_preview(&preview, &DumpOptions::default()),
+        concat!(
+            "org-1@2 snapshot -> {\"name\":\"Acme\",\"users\":2}\n",
+            "org-1@2 replayed -> {\"name\":\"Acme\",\"users\":1}\n",
+            "! users: snapshot 2, replayed 1\n",
+        )
+    );
+
+    // A snapshot ahead of the stream, e.g. after events were lost.
+    let ahead = Snapshot {
+        version: 5,
+        ..drifted
+    };
+    let preview = preview_snapshot(&ahead, &events);
+    assert_eq!(preview.replayed_version, 2);
+    assert!(dump_snapshot_preview(&preview, &DumpOptions::default())
+        .contains("! the stream ends at version 2\n"));
+}
@@ -0,0 +1,290 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::crypto::{self, Sha512};
+use crate::envelope::Envelope;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+use crate::signing::Signable;
+
+/// Metadata key recording the hash of the envelope chained to the previous one of its stream.
+pub const HASH_METADATA_KEY: &str = "hash";
+
+const GENESIS: &str = "";
+
+/// Compute the hash of the envelope chained to the hash of the previous envelope of its
+/// stream, an empty string for the first one.
+///
+/// The hash covers the ID, stream ID, version, event type, payload and metadata, except the
+/// hash itself.
+pub fn chain_hash<P: Signable>(previous: &str, envelope: &Envelope<P>) -> String {
+    let mut hasher = Sha512::new();
+    let mut push = |bytes: &[u8]| {
+        hasher.update(&(bytes.len() as u64).to_be_bytes());
+        hasher.update(bytes);
+    };
+    push(previous.as_bytes());
+    push(&envelope.id.as_u128().to_be_bytes());
+    push(envelope.stream_id.as_bytes());
+    push(&envelope.version.to_be_bytes());
+    push(envelope.event_type.as_bytes());
+    push(&envelope.payload.signable_bytes());
+    for (key, value) in &envelope.metadata {
+        if key != HASH_METADATA_KEY {
+            push(key.as_bytes());
+            push(value.as_bytes());
+        }
+    }
+    crypto::to_hex(&hasher.finish())
+}
+
+/// Verify the hash chain of the events of a stream, which must start at version 1.
+pub fn verify_stream<P: Signable>(events: &[Envelope<P>]) -> Result<(), IntegrityError> {
+    let mut previous = GENESIS.to_string();
+    for (expected, event) in (1..).zip(events) {
+        if event.version != expected {
+            return Err(IntegrityError::Gap {
+                expected,
+                found: event.version,
+            });
+        }
+        let recorded = event
+            .metadata
+            .get(HASH_METADATA_KEY)
+            .ok_or(IntegrityError::Missing(event.version))?;
+        let hash = chain_hash(&previous, event);
+        if *recorded != hash {
+            return Err(IntegrityError::Mismatch(event.version));
+        }
+        previous = hash;
+    }
+    Ok(())
+}
+
+/// Integrity of a stream checked by [`verify_streams`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamIntegrity {
+    /// ID of the stream.
+    pub stream_id: String,
+    /// Result of the verification.
+    pub result: Result<(), IntegrityError>,
+}
+
+/// Verify the hash chains of the streams of the store.
+pub fn verify_streams<S, P>(
+    store: &S,
+    stream_ids: &[String],
+) -> Result<Vec<StreamIntegrity>, S::Error>
+where
+    S: EventLoader<StreamId = String, Persistable = Envelope<P>>,
+    P: Signable,
+{
+    let streams = store.read_multi(stream_ids)?;
+    Ok(stream_ids
+        .iter()
+        .zip(streams)
+        .map(|(stream_id, events)| StreamIntegrity {
+            stream_id: stream_id.clone(),
+            result: verify_stream(&events),
+        })
+        .collect())
+}
+
+/// Event store wrapper recording the chained hash of every appended envelope.
+///
+/// The version of each envelope is computed from the stream before it is saved, so the
+/// wrapped store must assign versions sequentially from the current length of the stream.
+/// Events saved earlier in an uncommitted transaction are not seen by the wrapped store's
+/// loader, so a stream should be saved at most once per transaction.
+pub struct ChainedEventStore<S> {
+    store: S,
+}
+
+impl<S> ChainedEventStore<S> {
+    /// Wrap the store.
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Unwrap the store.
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+type Heads = HashMap<String, (u64, String)>;
+
+impl<S, P> ChainedEventStore<S>
+where
+    S: EventLoader<StreamId = String, Persistable = Envelope<P>>,
+    P: Signable + Clone,
+{
+    /// Chain the event to the head of its stream, loading the head on first use. Fails when a
+    /// stream cannot be loaded, or ends with an event without hash, rather than forking its
+    /// chain.
+    fn chain(
+        &self,
+        heads: &mut Heads,
+        event: &Envelope<P>,
+    ) -> Result<Envelope<P>, ChainError<S::Error>> {
+        if !heads.contains_key(&event.stream_id) {
+            let stream = self
+                .store
+                .load(&event.stream_id)
+                .map_err(ChainError::Store)?;
+            let head = match stream.last() {
+                Some(last) => match last.metadata.get(HASH_METADATA_KEY) {
+                    Some(hash) => (last.version, hash.clone()),
+                    None => {
+                        return Err(ChainError::Integrity {
+                            stream_id: event.stream_id.clone(),
+                            error: IntegrityError::Missing(last.version),
+                        })
+                    }
+                },
+                None => (0, GENESIS.to_string()),
+            };
+            heads.insert(event.stream_id.clone(), head);
+        }
+        let head = heads.get_mut(&event.stream_id).unwrap();
+        let mut event = event.clone();
+        event.version = head.0 + 1;
+        event.metadata.remove(HASH_METADATA_KEY);
+        let hash = chain_hash(&head.1, &event);
+        event
+            .metadata
+            .insert(HASH_METADATA_KEY.to_string(), hash.clone());
+        *head = (event.version, hash);
+        Ok(event)
+    }
+}
+
+impl<S, P> EventStore for ChainedEventStore<S>
+where
+    S: EventStore<Persistable = Envelope<P>>
+        + EventLoader<StreamId = String, Persistable = Envelope<P>, Error = <S as EventStore>::Error>,
+    P: Signable + Clone,
+{
+    type Persistable = Envelope<P>;
+    type Error = ChainError<<S as EventStore>::Error>;
+
+    /// Chain and save the events. Fails without saving when a stream cannot be loaded, or ends
+    /// with an event without hash, rather than forking its chain.
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let mut heads = Heads::new();
+        let mut chained = Vec::with_capacity(events.len());
+        for event in events {
+            chained.push(self.chain(&mut heads, event)?);
+        }
+        self.store.save(&chained).map_err(ChainError::Store)
+    }
+
+    /// Chain the events like [`save`](Self::save), then append them at the expected versions.
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let mut heads = Heads::new();
+        let mut chained = Vec::with_capacity(appends.len());
+        for (stream_id, expected, events) in appends {
+            let mut stream = Vec::with_capacity(events.len());
+            for event in events {
+                stream.push(self.chain(&mut heads, event).map_err(AppendError::Store)?);
+            }
+            chained.push((stream_id.clone(), *expected, stream));
+        }
+        self.store
+            .append_multi(&chained)
+            .map_err(|e| e.map_store(ChainError::Store))
+    }
+}
+
+impl<S: EventLoader> EventLoader for ChainedEventStore<S> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = S::Error;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.store.read_multi(stream_ids)
+    }
+}
+
+/// Integrity violation found in a stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The event at the version has no recorded hash.
+    Missing(u64),
+    /// The recorded hash of the event at the version does not match its content or chain.
+    Mismatch(u64),
+    /// An event is missing from the stream.
+    Gap {
+        /// The version expected next.
+        expected: u64,
+        /// The version found.
+        found: u64,
+    },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::Missing(version) => write!(f, "no hash at version {}", version),
+            IntegrityError::Mismatch(version) => write!(f, "hash mismatch at version {}", version),
+            IntegrityError::Gap { expected, found } => {
+                write!(f, "expected version {}, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl Error for IntegrityError {}
+
+/// Error returned by the [`ChainedEventStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError<E> {
+    /// The wrapped store failed.
+    Store(E),
+    /// The stream cannot be extended without forking its chain.
+    Integrity {
+        /// ID of the stream.
+        stream_id: String,
+        /// The violation found at the head of the stream.
+        error: IntegrityError,
+    },
+}
+
+impl<E: Error> fmt::Display for ChainError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Store(e) => write!(f, "store error: {}", e),
+            ChainError::Integrity { stream_id, error } => {
+                write!(f, "broken chain in stream {}: {}", stream_id, error)
+            }
+        }
+    }
+}
+
+impl<E: Error> Error for ChainError<E> {}
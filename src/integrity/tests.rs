@@ -0,0 +1,134 @@
+use super::*;
+use crate::event_store::{OnMemoryEventStore, TransactionManager};
+
+fn event(stream_id: &str, payload: &str) -> Envelope<String> {
+    Envelope::new(stream_id, 0, "Posted", payload.to_string())
+}
+
+fn store() -> ChainedEventStore<OnMemoryEventStore<String>> {
+    let mut store = ChainedEventStore::new(OnMemoryEventStore::new());
+    store
+        .save(&[event("ledger-1", "100"), event("ledger-2", "5")])
+        .unwrap();
+    store
+        .save(&[event("ledger-1", "-40"), event("ledger-1", "-10")])
+        .unwrap();
+    store
+}
+
+#[test]
+fn test_chained_streams_verify() {
+    let store = store();
+    let events = store.load(&"ledger-1".to_string()).unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[1].metadata[HASH_METADATA_KEY],
+        chain_hash(&events[0].metadata[HASH_METADATA_KEY], &events[1])
+    );
+
+    let report = verify_streams(&store, &store.inner().stream_ids()).unwrap();
+    assert_eq!(report.len(), 2);
+    assert!(report.iter().all(|stream| stream.result.is_ok()));
+}
+
+#[test]
+fn test_detect_tampering() {
+    let store = store();
+    let mut events = store.load(&"ledger-1".to_string()).unwrap();
+
+    let mut tampered = events.clone();
+    tampered[1].payload = "-4".to_string();
+    assert_eq!(verify_stream(&tampered), Err(IntegrityError::Mismatch(2)));
+
+    // Rewriting the hash of a tampered event breaks the link of the next one.
+    let rewritten = chain_hash(&tampered[0].metadata[HASH_METADATA_KEY], &tampered[1]);
+    tampered[1]
+        .metadata
+        .insert(HASH_METADATA_KEY.to_string(), rewritten);
+    assert_eq!(verify_stream(&tampered), Err(IntegrityError::Mismatch(3)));
+
+    events.remove(1);
+    assert_eq!(
+        verify_stream(&events),
+        Err(IntegrityError::Gap {
+            expected: 2,
+            found: 3
+        })
+    );
+    events[0].metadata.remove(HASH_METADATA_KEY);
+    assert_eq!(verify_stream(&events), Err(IntegrityError::Missing(1)));
+}
+
+#[test]
+fn test_detect_corruption_in_store() {
+    let mut inner = store().into_inner();
+    inner.delete_where("ledger-1", |e| e.version == 2);
+    let report = verify_streams(&inner, &["ledger-1".to_string(), "ledger-2".to_string()]).unwrap();
+    assert_eq!(
+        report[0].result,
+        Err(IntegrityError::Gap {
+            expected: 2,
+            found: 3
+        })
+    );
+    assert_eq!(report[1].result, Ok(()));
+}
+
+#[test]
+fn test_chain_within_transaction() {
+    let mut store = store();
+    store.store.begin().unwrap();
+    store.save(&[event("ledger-2", "7")]).unwrap();
+    store.store.commit().unwrap();
+    assert_eq!(
+        verify_stream(&store.load(&"ledger-2".to_string()).unwrap()),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_refuse_to_fork_a_chain() {
+    let mut inner = OnMemoryEventStore::new();
+    inner.save(&[event("ledger-1", "100")]).unwrap();
+    let mut store = ChainedEventStore::new(inner);
+    assert_eq!(
+        store.save(&[event("ledger-1", "-40")]),
+        Err(ChainError::Integrity {
+            stream_id: "ledger-1".to_string(),
+            error: IntegrityError::Missing(1),
+        })
+    );
+    assert_eq!(store.inner().stream_len("ledger-1"), 1);
+}
+
+#[test]
+fn test_chain_multi_stream_appends() {
+    let mut store = store();
+    store
+        .append_multi(&[
+            (
+                "ledger-1".to_string(),
+                ExpectedVersion::Exact(3),
+                vec![event("ledger-1", "5")],
+            ),
+            (
+                "ledger-3".to_string(),
+                ExpectedVersion::NoStream,
+                vec![event("ledger-3", "1"), event("ledger-3", "2")],
+            ),
+        ])
+        .unwrap();
+    let report = verify_streams(&store, &store.inner().stream_ids()).unwrap();
+    assert_eq!(report.len(), 3);
+    assert!(report.iter().all(|stream| stream.result.is_ok()));
+    assert_eq!(store.inner().stream_len("ledger-1"), 4);
+
+    assert!(matches!(
+        store.append_multi(&[(
+            "ledger-2".to_string(),
+            ExpectedVersion::NoStream,
+            vec![event("ledger-2", "1")],
+        )]),
+        Err(AppendError::WrongExpectedVersion { actual: 1, .. })
+    ));
+}
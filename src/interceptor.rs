@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, TransactionManager,
+};
+
+/// Types which observe or alter the events going through an [`InterceptedEventStore`].
+///
+/// Every hook has a no-op default, so interceptors only implement the ones they need.
+pub trait Interceptor<P> {
+    /// Called before the events are saved. The events may be enriched, or the save rejected.
+    fn before_append(&self, _events: &mut Vec<P>) -> Result<(), String> {
+        Ok(())
+    }
+    /// Called after the events were saved.
+    fn after_append(&self, _events: &[P]) {}
+    /// Called after the events of a stream were loaded. The events may be altered.
+    fn after_load(&self, _events: &mut Vec<P>) {}
+}
+
+/// Event store wrapper running a chain of interceptors around the wrapped store.
+pub struct InterceptedEventStore<S, P> {
+    store: S,
+    interceptors: Vec<Box<dyn Interceptor<P>>>,
+}
+
+impl<S, P> InterceptedEventStore<S, P> {
+    /// Wrap the store, without interceptors.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            interceptors: Vec::new(),
+        }
+    }
+
+    /// Append an interceptor to the chain. Interceptors run in the order they were added.
+    pub fn with(mut self, interceptor: impl Interceptor<P> + 'static) -> Self {
+        self.interceptors.push(Box::new(interceptor));
+        self
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Unwrap the store.
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S, P> EventStore for InterceptedEventStore<S, P>
+where
+    S: EventStore<Persistable = P>,
+    P: Clone,
+{
+    type Persistable = P;
+    type Error = InterceptorError<<S as EventStore>::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let mut events = events.to_vec();
+        for interceptor in &self.interceptors {
+            interceptor
+                .before_append(&mut events)
+                .map_err(InterceptorError::Rejected)?;
+        }
+        self.store.save(&events).map_err(InterceptorError::Store)?;
+        for interceptor in &self.interceptors {
+            interceptor.after_append(&events);
+        }
+        Ok(())
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let mut appends = appends.to_vec();
+        for (_, _, events) in appends.iter_mut() {
+            for interceptor in &self.interceptors {
+                interceptor
+                    .before_append(events)
+                    .map_err(|reason| AppendError::Store(InterceptorError::Rejected(reason)))?;
+            }
+        }
+        self.store
+            .append_multi(&appends)
+            .map_err(|e| e.map_store(InterceptorError::Store))?;
+        for (_, _, events) in &appends {
+            for interceptor in &self.interceptors {
+                interceptor.after_append(events);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S, P> EventLoader for InterceptedEventStore<S, P>
+where
+    S: EventLoader<Persistable = P>,
+{
+    type StreamId = S::StreamId;
+    type Persistable = P;
+    type Error = InterceptorError<<S as EventLoader>::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let mut events = self
+            .store
+            .load(stream_id)
+            .map_err(InterceptorError::Store)?;
+        for interceptor in &self.interceptors {
+            interceptor.after_load(&mut events);
+        }
+        Ok(events)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        let mut streams = self
+            .store
+            .read_multi(stream_ids)
+            .map_err(InterceptorError::Store)?;
+        for events in &mut streams {
+            for interceptor in &self.interceptors {
+                interceptor.after_load(events);
+            }
+        }
+        Ok(streams)
+    }
+}
+
+impl<S, P> TransactionManager for InterceptedEventStore<S, P>
+where
+    S: TransactionManager,
+{
+    type Error = S::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.store.begin()
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.store.commit()
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.store.rollback()
+    }
+}
+
+/// Error returned by the [`InterceptedEventStore`].
+#[derive(Debug)]
+pub enum InterceptorError<E> {
+    /// The wrapped store failed.
+    Store(E),
+    /// An interceptor rejected the save.
+    Rejected(String),
+}
+
+impl<E: Error> fmt::Display for InterceptorError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterceptorError::Store(e) => write!(f, "store error: {}", e),
+            InterceptorError::Rejected(reason) => write!(f, "rejected by interceptor: {}", reason),
+        }
+    }
+}
+
+impl<E: Error> Error for InterceptorError<E> {}
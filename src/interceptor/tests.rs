@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+struct OrgEvent {
+    org_id: String,
+    name: String,
+    tenant: Option<String>,
+}
+
+#[derive(Default)]
+struct OnMemoryEventStore {
+    events: HashMap<String, Vec<OrgEvent>>,
+}
+
+impl EventStore for OnMemoryEventStore {
+    type Persistable = OrgEvent;
+    type Error = Infallible;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        for event in events {
+            self.events
+                .entry(event.org_id.clone())
+                .or_default()
+                .push(event.clone());
+        }
+        Ok(())
+    }
+}
+
+impl EventLoader for OnMemoryEventStore {
+    type StreamId = String;
+    type Persistable = OrgEvent;
+    type Error = Infallible;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        Ok(self.events.get(stream_id).cloned().unwrap_or_default())
+    }
+}
+
+struct TenantEnricher;
+
+impl Interceptor<OrgEvent> for TenantEnricher {
+    fn before_append(&self, events: &mut Vec<OrgEvent>) -> Result<(), String> {
+        for event in events.iter_mut() {
+            event.tenant.get_or_insert_with(|| "default".to_string());
+        }
+        Ok(())
+    }
+}
+
+struct Auditor(Rc<RefCell<Vec<String>>>);
+
+impl Interceptor<OrgEvent> for Auditor {
+    fn after_append(&self, events: &[OrgEvent]) {
+        let mut log = self.0.borrow_mut();
+        log.extend(events.iter().map(|e| format!("appended {}", e.name)));
+    }
+
+    fn after_load(&self, events: &mut Vec<OrgEvent>) {
+        self.0.borrow_mut().push(format!("loaded {}", events.len()));
+    }
+}
+
+struct NameValidator;
+
+impl Interceptor<OrgEvent> for NameValidator {
+    fn before_append(&self, events: &mut Vec<OrgEvent>) -> Result<(), String> {
+        if events.iter().any(|e| e.name.is_empty()) {
+            return Err("empty name".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn event(name: &str) -> OrgEvent {
+    OrgEvent {
+        org_id: "org-1".to_string(),
+        name: name.to_string(),
+        tenant: None,
+    }
+}
+
+#[test]
+fn test_interceptor_chain() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut store = InterceptedEventStore::new(OnMemoryEventStore::default())
+        .with(TenantEnricher)
+        .with(Auditor(log.clone()));
+
+    store.save(&[event("Created"), event("Renamed")]).unwrap();
+    let events = store.load(&"org-1".to_string()).unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|e| e.tenant.as_deref() == Some("default")));
+    assert_eq!(
+        *log.borrow(),
+        vec!["appended Created", "appended Renamed", "loaded 2"]
+    );
+}
+
+#[test]
+fn test_interceptor_read_multi() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut store = InterceptedEventStore::new(OnMemoryEventStore::default())
+        .with(TenantEnricher)
+        .with(Auditor(log.clone()));
+    store.save(&[event("Created"), event("Renamed")]).unwrap();
+
+    let streams = store
+        .read_multi(&["org-1".to_string(), "org-2".to_string()])
+        .unwrap();
+    assert_eq!(streams[0].len(), 2);
+    assert!(streams[1].is_empty());
+    assert_eq!(log.borrow()[2..], ["loaded 2", "loaded 0"]);
+}
+
+#[test]
+fn test_interceptor_rejects_save() {
+    let mut store = InterceptedEventStore::new(OnMemoryEventStore::default()).with(NameValidator);
+
+    let result = store.save(&[event("Created"), event("")]);
+    assert!(matches!(result, Err(InterceptorError::Rejected(reason)) if reason == "empty name"));
+    assert!(store.inner().events.is_empty());
+}
+
+#[test]
+fn test_interceptor_append_multi() {
+    use crate::envelope::Envelope;
+    use crate::event_store::OnMemoryEventStore;
+
+    struct Stamp;
+
+    impl Interceptor<Envelope<String>> for Stamp {
+        fn before_append(&self, events: &mut Vec<Envelope<String>>) -> Result<(), String> {
+            for event in events.iter_mut() {
+                event
+                    .metadata
+                    .insert("stamped".to_string(), "yes".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    let mut store = InterceptedEventStore::new(OnMemoryEventStore::new()).with(Stamp);
+    store
+        .append_multi(&[(
+            "org-1".to_string(),
+            ExpectedVersion::NoStream,
+            vec![Envelope::new("org-1", 0, "Created", "org".to_string())],
+        )])
+        .unwrap();
+    let events = store.load(&"org-1".to_string()).unwrap();
+    assert_eq!(events[0].metadata["stamped"], "yes");
+}
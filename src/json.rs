@@ -0,0 +1,219 @@
+use crate::summary::SummaryValue;
+
+/// Escape a string so that it can be embedded in a JSON document.
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Parse a JSON document. Objects keep the order of their fields, and numbers without a
+/// fraction or exponent are parsed as integers.
+pub(crate) fn parse(input: &str) -> Result<SummaryValue, String> {
+    let mut parser = Parser {
+        bytes: input.as_bytes(),
+        at: 0,
+    };
+    let value = parser.value()?;
+    parser.whitespace();
+    if parser.at < parser.bytes.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at byte {}", message, self.at)
+    }
+
+    fn whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.at)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.whitespace();
+        if self.bytes.get(self.at) != Some(&byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: SummaryValue) -> Result<SummaryValue, String> {
+        if !self.bytes[self.at..].starts_with(keyword.as_bytes()) {
+            return Err(self.error("unexpected character"));
+        }
+        self.at += keyword.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<SummaryValue, String> {
+        self.whitespace();
+        match self.bytes.get(self.at) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.list(),
+            Some(b'"') => self.string().map(SummaryValue::String),
+            Some(b't') => self.keyword("true", SummaryValue::Bool(true)),
+            Some(b'f') => self.keyword("false", SummaryValue::Bool(false)),
+            Some(b'n') => self.keyword("null", SummaryValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn object(&mut self) -> Result<SummaryValue, String> {
+        self.at += 1;
+        let mut fields = Vec::new();
+        self.whitespace();
+        if self.bytes.get(self.at) == Some(&b'}') {
+            self.at += 1;
+            return Ok(SummaryValue::Object(fields));
+        }
+        loop {
+            self.whitespace();
+            if self.bytes.get(self.at) != Some(&b'"') {
+                return Err(self.error("expected a field name"));
+            }
+            let name = self.string()?;
+            self.expect(b':')?;
+            fields.push((name, self.value()?));
+            self.whitespace();
+            match self.bytes.get(self.at) {
+                Some(b',') => self.at += 1,
+                Some(b'}') => {
+                    self.at += 1;
+                    return Ok(SummaryValue::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn list(&mut self) -> Result<SummaryValue, String> {
+        self.at += 1;
+        let mut items = Vec::new();
+        self.whitespace();
+        if self.bytes.get(self.at) == Some(&b']') {
+            self.at += 1;
+            return Ok(SummaryValue::List(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.whitespace();
+            match self.bytes.get(self.at) {
+                Some(b',') => self.at += 1,
+                Some(b']') => {
+                    self.at += 1;
+                    return Ok(SummaryValue::List(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.at += 1;
+        let mut value = String::new();
+        loop {
+            let start = self.at;
+            while self
+                .bytes
+                .get(self.at)
+                .is_some_and(|b| *b != b'"' && *b != b'\\')
+            {
+                self.at += 1;
+            }
+            value.push_str(
+                std::str::from_utf8(&self.bytes[start..self.at])
+                    .map_err(|_| self.error("invalid UTF-8"))?,
+            );
+            match self.bytes.get(self.at) {
+                Some(b'"') => {
+                    self.at += 1;
+                    return Ok(value);
+                }
+                Some(b'\\') => {
+                    let escaped = self.bytes.get(self.at + 1).copied();
+                    self.at += 2;
+                    value.push(match escaped {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    });
+                }
+                _ => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let mut code = self.hex4()?;
+        if (0xd800..0xdc00).contains(&code) && self.bytes[self.at..].starts_with(b"\\u") {
+            self.at += 2;
+            let low = self.hex4()?;
+            code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+        }
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .bytes
+            .get(self.at..self.at + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.at += 4;
+        Ok(digits)
+    }
+
+    fn number(&mut self) -> Result<SummaryValue, String> {
+        let start = self.at;
+        while self
+            .bytes
+            .get(self.at)
+            .is_some_and(|b| matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.at += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.at]).expect("ASCII digits");
+        if let Ok(integer) = text.parse::<i64>() {
+            return Ok(SummaryValue::Integer(integer));
+        }
+        text.parse::<f64>()
+            .map(SummaryValue::Number)
+            .map_err(|_| format!("invalid number at byte {}", start))
+    }
+}
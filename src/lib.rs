@@ -1,2 +1,92 @@
+pub mod acl;
+pub mod aggregate;
+pub mod archiving;
+pub mod audit;
 pub mod backlog;
+pub mod backpressure;
+pub mod baggage;
+pub mod batching;
+pub mod bootstrap;
+pub mod broker;
+pub mod circuit_breaker;
+pub mod clock;
+#[cfg(feature = "cloudevents")]
+pub mod cloudevents;
+pub mod codegen;
+pub mod command_bus;
+pub mod command_status;
+pub mod compaction;
+pub mod composite;
+pub mod consumer_group;
+mod crypto;
+pub mod dead_letter;
+pub mod dedup;
+pub mod dual_write;
+pub mod effect;
+pub mod enrich;
+pub mod envelope;
+pub mod equivalence;
+pub mod event;
+pub mod event_flow;
+pub mod event_id;
 pub mod event_store;
+pub mod fan_out;
+pub mod faulty;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod inbox;
+pub mod inspect;
+pub mod integrity;
+pub mod interceptor;
+mod json;
+#[cfg(feature = "ws")]
+pub mod live_feed;
+pub mod lookup;
+pub mod materializer;
+pub mod metadata_schema;
+pub mod ordering;
+pub mod page;
+pub mod persistable_set;
+pub mod precondition;
+pub mod priority;
+pub mod projection;
+pub mod projection_file;
+pub mod query;
+pub mod rate_limit;
+pub mod rate_monitor;
+pub mod redact;
+pub mod registry;
+pub mod rekey;
+pub mod relay;
+pub mod replica;
+pub mod repository;
+pub mod retention;
+mod rfc3339;
+pub mod rng;
+pub mod rollout;
+pub mod runtime;
+pub mod sandbox;
+pub mod serialization;
+pub mod services;
+pub mod settings;
+pub mod sharding;
+pub mod signing;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod snapshot;
+pub mod sql_projection;
+pub mod standby;
+pub mod state_machine;
+pub mod stream_lock;
+pub mod subscriber;
+pub mod summary;
+pub mod sync;
+pub mod testing;
+pub mod tiered;
+pub mod trace_context;
+pub mod undo;
+pub mod unique_index;
+pub mod visibility;
+pub mod wide_column;
+pub mod workflow;
+mod yaml;
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::crypto;
+use crate::envelope::Envelope;
+use crate::event_store::ReadOnlyEventStore;
+use crate::json;
+use crate::sharding::category_of;
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_REQUEST_LEN: usize = 8192;
+
+type Serializer<E> = Box<dyn Fn(&Envelope<E>) -> Option<String>>;
+
+/// Events a live feed client receives.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedFilter {
+    /// Event types received, all if empty.
+    pub event_types: Vec<String>,
+    /// Stream categories received, all if empty.
+    pub categories: Vec<String>,
+}
+
+impl FeedFilter {
+    /// Check whether the client receives the event.
+    pub fn matches<E>(&self, event: &Envelope<E>) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && (self.categories.is_empty()
+                || self
+                    .categories
+                    .iter()
+                    .any(|c| c == category_of(&event.stream_id)))
+    }
+}
+
+struct FeedClient<T> {
+    connection: T,
+    filter: FeedFilter,
+    position: u64,
+}
+
+/// Feed streaming the events of the log to WebSocket clients, for live dashboards.
+///
+/// Clients connect with `GET /?from=<position>&types=<a,b>&categories=<c,d>`, all parameters
+/// being optional: they receive the matching events from the position, the beginning of the
+/// log by default. Every event is sent as a text message
+/// `{"position":<position>,"event":<serialized event>}`, so that a reconnecting client resumes
+/// after the last position it received.
+pub struct LiveFeed<E, T> {
+    serialize: Serializer<E>,
+    clients: Vec<FeedClient<T>>,
+}
+
+impl<E, T: Read + Write> LiveFeed<E, T> {
+    /// Create a feed serializing the events as JSON with the function. Events it returns
+    /// `None` for, such as domain events internal to a service, are not streamed.
+    pub fn new(serialize: impl Fn(&Envelope<E>) -> Option<String> + 'static) -> Self {
+        Self {
+            serialize: Box::new(serialize),
+            clients: Vec::new(),
+        }
+    }
+
+    /// Get the number of connected clients.
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Complete the WebSocket handshake of the connection and add it to the clients.
+    pub fn accept(&mut self, mut connection: T) -> Result<(), LiveFeedError> {
+        let request = read_request(&mut connection)?;
+        let (filter, position) = handshake(&mut connection, &request)?;
+        self.clients.push(FeedClient {
+            connection,
+            filter,
+            position,
+        });
+        Ok(())
+    }
+
+    /// Send every client the events appended since its position, by batches of `batch_size`.
+    /// Clients whose connection fails are dropped. Returns the number of messages sent.
+    pub fn pump<S>(&mut self, store: &S, batch_size: usize) -> Result<usize, S::Error>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<E>>,
+    {
+        let mut sent = 0;
+        let mut clients = Vec::with_capacity(self.clients.len());
+        'clients: for mut client in std::mem::take(&mut self.clients) {
+            loop {
+                let events = match store.read_all(client.position, batch_size) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        clients.push(client);
+                        clients.append(&mut self.clients);
+                        self.clients = clients;
+                        return Err(e);
+                    }
+                };
+                let Some(last) = events.last() else { break };
+                let next = last.position + 1;
+                for event in events.iter().filter(|e| client.filter.matches(e)) {
+                    if let Some(payload) = (self.serialize)(event) {
+                        let message =
+                            format!("{{\"position\":{},\"event\":{}}}", event.position, payload);
+                        if write_text_frame(&mut client.connection, &message).is_err() {
+                            continue 'clients;
+                        }
+                        sent += 1;
+                    }
+                }
+                client.position = next;
+            }
+            clients.push(client);
+        }
+        self.clients = clients;
+        Ok(sent)
+    }
+}
+
+fn read_request(connection: &mut impl Read) -> Result<String, LiveFeedError> {
+    let mut request = Vec::new();
+    let mut byte = [0];
+    while !request.ends_with(b"\r\n\r\n") {
+        if request.len() >= MAX_REQUEST_LEN {
+            return Err(LiveFeedError::Handshake("request too large".to_string()));
+        }
+        if connection.read(&mut byte).map_err(LiveFeedError::Io)? == 0 {
+            return Err(LiveFeedError::Handshake("connection closed".to_string()));
+        }
+        request.push(byte[0]);
+    }
+    String::from_utf8(request).map_err(|_| LiveFeedError::Handshake("invalid request".to_string()))
+}
+
+fn handshake(
+    connection: &mut impl Write,
+    request: &str,
+) -> Result<(FeedFilter, u64), LiveFeedError> {
+    let mut lines = request.lines();
+    let target = lines
+        .next()
+        .and_then(|line| line.strip_prefix("GET "))
+        .and_then(|line| line.split(' ').next())
+        .ok_or_else(|| LiveFeedError::Handshake("expected a GET request".to_string()))?;
+    let key = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-key"))
+        .map(|(_, value)| value.trim())
+        .ok_or_else(|| LiveFeedError::Handshake("missing Sec-WebSocket-Key".to_string()))?;
+
+    let mut filter = FeedFilter::default();
+    let mut position = 0;
+    let query = target.split_once('?').map_or("", |(_, query)| query);
+    for (name, value) in query.split('&').filter_map(|p| p.split_once('=')) {
+        let list = || value.split(',').map(str::to_string).collect();
+        match name {
+            "from" => {
+                position = value
+                    .parse()
+                    .map_err(|_| LiveFeedError::Handshake(format!("invalid position: {}", value)))?
+            }
+            "types" => filter.event_types = list(),
+            "categories" => filter.categories = list(),
+            _ => {}
+        }
+    }
+
+    let accept = crypto::to_base64(&crypto::sha1(
+        format!("{}{}", key, WEBSOCKET_GUID).as_bytes(),
+    ));
+    write!(
+        connection,
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )
+    .and_then(|_| connection.flush())
+    .map_err(LiveFeedError::Io)?;
+    Ok((filter, position))
+}
+
+fn write_text_frame(connection: &mut impl Write, message: &str) -> io::Result<()> {
+    let payload = message.as_bytes();
+    let mut frame = vec![0x81];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    connection.write_all(&frame)?;
+    connection.flush()
+}
+
+/// Serialize the envelope as a JSON integration event with its ID, stream, version, type and
+/// the payload, already serialized as JSON.
+pub fn integration_event_json<E>(envelope: &Envelope<E>, payload: &str) -> String {
+    format!(
+        "{{\"id\":{},\"stream_id\":{},\"version\":{},\"type\":{},\"data\":{}}}",
+        json::escape(&envelope.id.to_string()),
+        json::escape(&envelope.stream_id),
+        envelope.version,
+        json::escape(&envelope.event_type),
+        payload
+    )
+}
+
+/// Error returned when accepting a live feed client.
+#[derive(Debug)]
+pub enum LiveFeedError {
+    /// The request is not a valid WebSocket handshake.
+    Handshake(String),
+    /// The connection failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for LiveFeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiveFeedError::Handshake(reason) => write!(f, "invalid handshake: {}", reason),
+            LiveFeedError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LiveFeedError {}
@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::io::Cursor;
+use std::rc::Rc;
+
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+/// Connection reading a scripted request and recording what the feed writes.
+struct TestConnection {
+    input: Cursor<Vec<u8>>,
+    output: Rc<RefCell<Vec<u8>>>,
+    broken: bool,
+}
+
+impl Read for TestConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for TestConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.broken && self.output.borrow().ends_with(b"\r\n\r\n") {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        self.output.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn connect(target: &str, broken: bool) -> (TestConnection, Rc<RefCell<Vec<u8>>>) {
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        target
+    );
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let connection = TestConnection {
+        input: Cursor::new(request.into_bytes()),
+        output: output.clone(),
+        broken,
+    };
+    (connection, output)
+}
+
+/// Split the output into the handshake response and the text messages.
+fn messages(output: &[u8]) -> (String, Vec<String>) {
+    let end = output.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    let response = String::from_utf8(output[..end].to_vec()).unwrap();
+    let mut frames = Vec::new();
+    let mut rest = &output[end..];
+    while !rest.is_empty() {
+        assert_eq!(rest[0], 0x81);
+        let (len, start) = match rest[1] {
+            126 => (u16::from_be_bytes([rest[2], rest[3]]) as usize, 4),
+            len => (len as usize, 2),
+        };
+        frames.push(String::from_utf8(rest[start..start + len].to_vec()).unwrap());
+        rest = &rest[start + len..];
+    }
+    (response, frames)
+}
+
+fn store() -> OnMemoryEventStore<u32> {
+    let mut store = OnMemoryEventStore::new();
+    for (i, stream_id) in ["order-1", "user-1", "order-2"].iter().enumerate() {
+        store
+            .save(&[Envelope::new(*stream_id, 0, "Created", i as u32)])
+            .unwrap();
+    }
+    store
+}
+
+fn feed() -> LiveFeed<u32, TestConnection> {
+    LiveFeed::new(|e: &Envelope<u32>| Some(e.payload.to_string()))
+}
+
+#[test]
+fn test_handshake_and_resume() {
+    let mut store = store();
+    let mut feed = feed();
+    let (connection, output) = connect("/feed?from=1&categories=order", false);
+    feed.accept(connection).unwrap();
+
+    assert_eq!(feed.pump(&store, 2).unwrap(), 1);
+    store
+        .save(&[Envelope::new("order-1", 0, "Shipped", 3)])
+        .unwrap();
+    assert_eq!(feed.pump(&store, 2).unwrap(), 1);
+    assert_eq!(feed.pump(&store, 2).unwrap(), 0);
+
+    let (response, frames) = messages(&output.borrow());
+    assert!(response.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+    assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+    assert_eq!(
+        frames,
+        vec![
+            "{\"position\":2,\"event\":2}".to_string(),
+            "{\"position\":3,\"event\":3}".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_filter_by_event_type_and_serializer() {
+    let mut store = store();
+    store
+        .save(&[Envelope::new("order-1", 0, "Shipped", 3)])
+        .unwrap();
+    let mut feed = LiveFeed::new(|e: &Envelope<u32>| {
+        (e.payload != 0).then(|| integration_event_json(e, &e.payload.to_string()))
+    });
+    let (connection, output) = connect("/?types=Created", false);
+    feed.accept(connection).unwrap();
+
+    assert_eq!(feed.pump(&store, 10).unwrap(), 2);
+    let (_, frames) = messages(&output.borrow());
+    assert!(frames[0].starts_with("{\"position\":1,\"event\":{\"id\":"));
+    assert!(frames[0]
+        .ends_with("\"stream_id\":\"user-1\",\"version\":1,\"type\":\"Created\",\"data\":1}}"));
+}
+
+#[test]
+fn test_broken_clients_are_dropped() {
+    let store = store();
+    let mut feed = feed();
+    feed.accept(connect("/", true).0).unwrap();
+    feed.accept(connect("/", false).0).unwrap();
+    assert_eq!(feed.clients(), 2);
+
+    assert_eq!(feed.pump(&store, 10).unwrap(), 3);
+    assert_eq!(feed.clients(), 1);
+
+    let (mut connection, _) = connect("/", false);
+    connection.input = Cursor::new(b"POST / HTTP/1.1\r\n\r\n".to_vec());
+    assert!(matches!(
+        feed.accept(connection),
+        Err(LiveFeedError::Handshake(_))
+    ));
+}
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::command_bus::CommandBus;
+
+/// Types which look values up in a read model, for validating commands.
+///
+/// Read models are eventually consistent: a value may not reflect the latest events yet.
+/// [`position`](Self::position) tells how far the read model is, so that commands can require
+/// it to be fresh enough. Validation against a read model narrows down invalid commands, but
+/// invariants must still be enforced by the aggregates.
+pub trait Lookup {
+    /// Associated Type representing the key type.
+    type Key;
+    /// Associated Type representing the value type.
+    type Value;
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Get the value of the key.
+    fn lookup(&self, key: &Self::Key) -> Result<Option<Self::Value>, Self::Error>;
+    /// Get the position of the next event the read model will apply.
+    fn position(&self) -> Result<u64, Self::Error>;
+}
+
+/// Freshness a command requires from the read models it is validated against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Staleness {
+    /// Any state of the read model is acceptable.
+    #[default]
+    Any,
+    /// The read model must have applied every event before the position, typically the
+    /// position of the last event the issuer of the command observed.
+    AtLeast(u64),
+}
+
+impl Staleness {
+    /// Check whether a read model at the position is fresh enough.
+    pub fn accepts(&self, position: u64) -> bool {
+        match self {
+            Staleness::Any => true,
+            Staleness::AtLeast(required) => position >= *required,
+        }
+    }
+}
+
+/// Types which represent a command validated against a read model before being dispatched.
+pub trait ValidateWith<L> {
+    /// Get the freshness required from the read model.
+    fn staleness(&self) -> Staleness {
+        Staleness::Any
+    }
+    /// Validate the command, returning the reason of the rejection.
+    fn validate(&self, lookup: &L) -> Result<(), String>;
+}
+
+/// Command bus middleware validating the commands against a read model.
+pub struct LookupValidatedBus<B, L> {
+    bus: B,
+    lookup: L,
+}
+
+impl<B, L> LookupValidatedBus<B, L> {
+    /// Wrap the bus, validating against the read model.
+    pub fn new(bus: B, lookup: L) -> Self {
+        Self { bus, lookup }
+    }
+
+    /// Get the wrapped bus.
+    pub fn inner(&self) -> &B {
+        &self.bus
+    }
+
+    /// Get the read model.
+    pub fn lookup(&self) -> &L {
+        &self.lookup
+    }
+
+    /// Get the read model mutably, to update it.
+    pub fn lookup_mut(&mut self) -> &mut L {
+        &mut self.lookup
+    }
+}
+
+impl<B, L, C> CommandBus<C> for LookupValidatedBus<B, L>
+where
+    B: CommandBus<C>,
+    L: Lookup,
+    C: ValidateWith<L>,
+{
+    type Response = B::Response;
+    type Error = LookupError<B::Error, L::Error>;
+
+    fn dispatch(&mut self, command: C) -> Result<Self::Response, Self::Error> {
+        let staleness = command.staleness();
+        let position = self.lookup.position().map_err(LookupError::Lookup)?;
+        if !staleness.accepts(position) {
+            return Err(LookupError::Stale {
+                required: staleness,
+                position,
+            });
+        }
+        command
+            .validate(&self.lookup)
+            .map_err(LookupError::Rejected)?;
+        self.bus.dispatch(command).map_err(LookupError::Bus)
+    }
+}
+
+/// Read model held in memory, updated by the application.
+#[derive(Debug, Clone)]
+pub struct OnMemoryLookup<K, V> {
+    values: HashMap<K, V>,
+    position: u64,
+}
+
+impl<K: Eq + Hash, V> OnMemoryLookup<K, V> {
+    /// Create an empty read model.
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+            position: 0,
+        }
+    }
+
+    /// Set the value of the key, from the event at the position.
+    pub fn upsert(&mut self, key: K, value: V, position: u64) {
+        self.values.insert(key, value);
+        self.position = self.position.max(position + 1);
+    }
+
+    /// Remove the key, from the event at the position.
+    pub fn remove(&mut self, key: &K, position: u64) {
+        self.values.remove(key);
+        self.position = self.position.max(position + 1);
+    }
+}
+
+impl<K: Eq + Hash, V> Default for OnMemoryLookup<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Lookup for OnMemoryLookup<K, V> {
+    type Key = K;
+    type Value = V;
+    type Error = Infallible;
+
+    fn lookup(&self, key: &K) -> Result<Option<V>, Self::Error> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn position(&self) -> Result<u64, Self::Error> {
+        Ok(self.position)
+    }
+}
+
+/// Error returned by the [`LookupValidatedBus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError<E, LE> {
+    /// The read model is staler than required by the command.
+    Stale { required: Staleness, position: u64 },
+    /// The command is invalid according to the read model.
+    Rejected(String),
+    /// The read model failed.
+    Lookup(LE),
+    /// The wrapped bus failed.
+    Bus(E),
+}
+
+impl<E: Error, LE: Error> fmt::Display for LookupError<E, LE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupError::Stale { required, position } => write!(
+                f,
+                "read model at position {} is staler than required: {:?}",
+                position, required
+            ),
+            LookupError::Rejected(reason) => write!(f, "command rejected: {}", reason),
+            LookupError::Lookup(e) => write!(f, "lookup error: {}", e),
+            LookupError::Bus(e) => write!(f, "bus error: {}", e),
+        }
+    }
+}
+
+impl<E: Error, LE: Error> Error for LookupError<E, LE> {}
@@ -0,0 +1,91 @@
+use super::*;
+
+#[derive(Debug, Clone)]
+struct OrgCapacity {
+    users: usize,
+    max_users: usize,
+}
+
+type Orgs = OnMemoryLookup<String, OrgCapacity>;
+
+struct AddUser {
+    org_id: String,
+    seen_position: Option<u64>,
+}
+
+impl ValidateWith<Orgs> for AddUser {
+    fn staleness(&self) -> Staleness {
+        self.seen_position
+            .map_or(Staleness::Any, |position| Staleness::AtLeast(position + 1))
+    }
+
+    fn validate(&self, orgs: &Orgs) -> Result<(), String> {
+        let org = orgs.lookup(&self.org_id).unwrap().ok_or("org not found")?;
+        if org.users >= org.max_users {
+            return Err("max users reached".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct CountingBus(usize);
+
+impl CommandBus<AddUser> for CountingBus {
+    type Response = ();
+    type Error = Infallible;
+
+    fn dispatch(&mut self, _command: AddUser) -> Result<Self::Response, Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+fn add_user(org_id: &str, seen_position: Option<u64>) -> AddUser {
+    AddUser {
+        org_id: org_id.to_string(),
+        seen_position,
+    }
+}
+
+#[test]
+fn test_lookup_validated_bus() {
+    let mut orgs = Orgs::new();
+    orgs.upsert(
+        "org-1".to_string(),
+        OrgCapacity {
+            users: 0,
+            max_users: 1,
+        },
+        0,
+    );
+    let mut bus = LookupValidatedBus::new(CountingBus::default(), orgs);
+
+    bus.dispatch(add_user("org-1", None)).unwrap();
+    assert_eq!(
+        bus.dispatch(add_user("org-2", None)),
+        Err(LookupError::Rejected("org not found".to_string()))
+    );
+
+    // The issuer saw the user being added at position 1, not applied to the read model yet.
+    assert_eq!(
+        bus.dispatch(add_user("org-1", Some(1))),
+        Err(LookupError::Stale {
+            required: Staleness::AtLeast(2),
+            position: 1,
+        })
+    );
+    bus.lookup_mut().upsert(
+        "org-1".to_string(),
+        OrgCapacity {
+            users: 1,
+            max_users: 1,
+        },
+        1,
+    );
+    assert_eq!(
+        bus.dispatch(add_user("org-1", Some(1))),
+        Err(LookupError::Rejected("max users reached".to_string()))
+    );
+    assert_eq!(bus.inner().0, 1);
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::{Duration, SystemTime};
+
+use crate::event_store::QueryHandler;
+use crate::projection::ProjectionRunner;
+
+/// Point-in-time copy of a read model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterializedSnapshot<R> {
+    /// Time the copy was taken at.
+    pub taken_at: SystemTime,
+    /// Global position the read model had reached when copied.
+    pub position: u64,
+    /// The copied read model.
+    pub read_model: R,
+}
+
+/// Query to run against the read model as it was at the given time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsOf<Q> {
+    /// Time to query the read model at.
+    pub at: SystemTime,
+    /// The query.
+    pub query: Q,
+}
+
+/// Materializer periodically taking point-in-time copies of a read model.
+#[derive(Debug)]
+pub struct ScheduledMaterializer<R> {
+    interval: Duration,
+    next_due: Option<SystemTime>,
+    snapshots: Vec<MaterializedSnapshot<R>>,
+}
+
+impl<R: Clone> ScheduledMaterializer<R> {
+    /// Create a materializer copying the read model every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_due: None,
+            snapshots: Vec::new(),
+        }
+    }
+
+    /// Copy the read model of the runner if a copy is due. Returns `true` if a copy was taken.
+    pub fn tick(&mut self, now: SystemTime, runner: &ProjectionRunner<R>) -> bool {
+        if self.next_due.is_some_and(|due| now < due) {
+            return false;
+        }
+        self.snapshots.push(MaterializedSnapshot {
+            taken_at: now,
+            position: runner.position(),
+            read_model: runner.projection().clone(),
+        });
+        self.next_due = Some(now + self.interval);
+        true
+    }
+
+    /// Get the latest copy taken at or before the time.
+    pub fn as_of(&self, at: SystemTime) -> Option<&MaterializedSnapshot<R>> {
+        self.snapshots.iter().rev().find(|s| s.taken_at <= at)
+    }
+
+    /// Get all the copies, oldest first.
+    pub fn snapshots(&self) -> &[MaterializedSnapshot<R>] {
+        &self.snapshots
+    }
+}
+
+impl<R, Q> QueryHandler<AsOf<Q>> for ScheduledMaterializer<R>
+where
+    R: Clone + QueryHandler<Q>,
+{
+    type Response = Option<R::Response>;
+    type Error = R::Error;
+
+    fn handle(&self, query: AsOf<Q>) -> Result<Self::Response, Self::Error> {
+        match self.as_of(query.at) {
+            Some(snapshot) => snapshot.read_model.handle(query.query).map(Some),
+            None => Ok(None),
+        }
+    }
+}
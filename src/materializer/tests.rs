@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use super::*;
+use crate::envelope::Envelope;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+use crate::projection::Projection;
+
+#[derive(Debug, Clone, Default)]
+struct Balances(HashMap<String, i64>);
+
+impl Projection for Balances {
+    type Event = i64;
+    type Error = Infallible;
+
+    fn apply(&mut self, event: &Envelope<Self::Event>) -> Result<(), Self::Error> {
+        *self.0.entry(event.stream_id.clone()).or_default() += event.payload;
+        Ok(())
+    }
+}
+
+struct BalanceQuery(String);
+
+impl QueryHandler<BalanceQuery> for Balances {
+    type Response = i64;
+    type Error = Infallible;
+
+    fn handle(&self, query: BalanceQuery) -> Result<Self::Response, Self::Error> {
+        Ok(self.0.get(&query.0).copied().unwrap_or_default())
+    }
+}
+
+#[test]
+fn test_scheduled_materializer() {
+    let day = Duration::from_secs(86_400);
+    let start = SystemTime::UNIX_EPOCH;
+    let mut store = OnMemoryEventStore::new();
+    let mut runner = ProjectionRunner::new(Balances::default());
+    let mut materializer = ScheduledMaterializer::new(day);
+
+    store
+        .save(&[Envelope::new("account-1", 0, "Deposited", 100)])
+        .unwrap();
+    runner.run_batch(&store, 10).unwrap();
+    assert!(materializer.tick(start, &runner));
+
+    store
+        .save(&[Envelope::new("account-1", 0, "Withdrawn", -30)])
+        .unwrap();
+    runner.run_batch(&store, 10).unwrap();
+    assert!(!materializer.tick(start + day / 2, &runner));
+    assert!(materializer.tick(start + day, &runner));
+
+    let query = |at| AsOf {
+        at,
+        query: BalanceQuery("account-1".to_string()),
+    };
+    assert_eq!(
+        materializer
+            .handle(query(start - Duration::from_secs(1)))
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        materializer.handle(query(start + day / 2)).unwrap(),
+        Some(100)
+    );
+    assert_eq!(
+        materializer.handle(query(start + day * 2)).unwrap(),
+        Some(70)
+    );
+    assert_eq!(materializer.as_of(start + day).unwrap().position, 2);
+}
@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Subscription;
+use crate::envelope::Envelope;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+use crate::sharding::category_of;
+
+/// Metadata keys required on the events of each stream category.
+#[derive(Debug, Default, Clone)]
+pub struct MetadataSchema {
+    required: HashMap<String, Vec<String>>,
+}
+
+impl MetadataSchema {
+    /// Create a schema requiring no key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the keys on the events of the category.
+    pub fn require<K: Into<String>>(
+        mut self,
+        category: impl Into<String>,
+        keys: impl IntoIterator<Item = K>,
+    ) -> Self {
+        let required = self.required.entry(category.into()).or_default();
+        for key in keys {
+            let key = key.into();
+            if !required.contains(&key) {
+                required.push(key);
+            }
+        }
+        self
+    }
+
+    /// Get the keys required on the events of the category.
+    pub fn required(&self, category: &str) -> &[String] {
+        self.required.get(category).map_or(&[], Vec::as_slice)
+    }
+
+    /// Check that the event carries every key required by its category.
+    pub fn validate<E>(&self, event: &Envelope<E>) -> Result<(), MetadataViolation> {
+        let missing = self
+            .required(category_of(&event.stream_id))
+            .iter()
+            .filter(|key| !event.metadata.contains_key(key.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MetadataViolation {
+                stream_id: event.stream_id.clone(),
+                version: event.version,
+                missing,
+            })
+        }
+    }
+}
+
+/// Event missing metadata keys required by its category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataViolation {
+    /// ID of the stream of the event.
+    pub stream_id: String,
+    /// Version of the event in its stream.
+    pub version: u64,
+    /// Required keys the event does not carry.
+    pub missing: Vec<String>,
+}
+
+impl fmt::Display for MetadataViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "event {}@{} is missing metadata: {}",
+            self.stream_id,
+            self.version,
+            self.missing.join(", ")
+        )
+    }
+}
+
+impl Error for MetadataViolation {}
+
+/// Event store rejecting appends of events missing required metadata.
+///
+/// Nothing of a batch is saved if any of its events is invalid.
+pub struct MetadataValidatedStore<S> {
+    inner: S,
+    schema: MetadataSchema,
+}
+
+impl<S> MetadataValidatedStore<S> {
+    /// Validate the appends to the store against the schema.
+    pub fn new(inner: S, schema: MetadataSchema) -> Self {
+        Self { inner, schema }
+    }
+
+    /// Get the schema.
+    pub fn schema(&self) -> &MetadataSchema {
+        &self.schema
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S, E> EventStore for MetadataValidatedStore<S>
+where
+    S: EventStore<Persistable = Envelope<E>>,
+{
+    type Persistable = Envelope<E>;
+    type Error = MetadataError<S::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        for event in events {
+            self.schema
+                .validate(event)
+                .map_err(MetadataError::Invalid)?;
+        }
+        self.inner.save(events).map_err(MetadataError::Inner)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        for event in appends.iter().flat_map(|(_, _, events)| events) {
+            self.schema
+                .validate(event)
+                .map_err(|e| AppendError::Store(MetadataError::Invalid(e)))?;
+        }
+        self.inner
+            .append_multi(appends)
+            .map_err(|e| e.map_store(MetadataError::Inner))
+    }
+}
+
+impl<S: EventLoader> EventLoader for MetadataValidatedStore<S> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = S::Error;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.inner.read_multi(stream_ids)
+    }
+}
+
+/// Subscription validating the metadata of the events it receives.
+///
+/// An invalid event is consumed and reported as an error, so that a misconfigured producer is
+/// caught by its consumers without blocking the subscription.
+pub struct MetadataValidatedSubscription<S> {
+    inner: S,
+    schema: MetadataSchema,
+}
+
+impl<S> MetadataValidatedSubscription<S> {
+    /// Validate the events received by the subscription against the schema.
+    pub fn new(inner: S, schema: MetadataSchema) -> Self {
+        Self { inner, schema }
+    }
+
+    /// Get the wrapped subscription.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S, E> Subscription<Envelope<E>> for MetadataValidatedSubscription<S>
+where
+    S: Subscription<Envelope<E>>,
+{
+    type Error = MetadataError<S::Error>;
+
+    fn poll(&mut self) -> Result<Option<Envelope<E>>, Self::Error> {
+        match self.inner.poll().map_err(MetadataError::Inner)? {
+            Some(event) => {
+                self.schema
+                    .validate(&event)
+                    .map_err(MetadataError::Invalid)?;
+                Ok(Some(event))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Error returned by the metadata validating store and subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataError<E> {
+    /// An event is missing required metadata.
+    Invalid(MetadataViolation),
+    /// The wrapped store or subscription failed.
+    Inner(E),
+}
+
+impl<E: Error> fmt::Display for MetadataError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataError::Invalid(e) => write!(f, "invalid metadata: {}", e),
+            MetadataError::Inner(e) => write!(f, "inner error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for MetadataError<E> {}
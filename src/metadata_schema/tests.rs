@@ -0,0 +1,81 @@
+use super::*;
+use crate::broker::{OnMemoryBroker, Publisher};
+use crate::event_store::OnMemoryEventStore;
+
+fn schema() -> MetadataSchema {
+    MetadataSchema::new()
+        .require("order", ["user_id", "tenant"])
+        .require("order", ["tenant"])
+}
+
+fn order(version: u64) -> Envelope<String> {
+    Envelope::new("order-1", version, "OrderPlaced", "placed".to_string())
+}
+
+#[test]
+fn test_validate() {
+    let schema = schema();
+    assert_eq!(schema.required("order"), ["user_id", "tenant"]);
+    assert!(schema.required("user").is_empty());
+
+    let event = order(1).with_metadata("tenant", "acme");
+    assert_eq!(
+        schema.validate(&event),
+        Err(MetadataViolation {
+            stream_id: "order-1".to_string(),
+            version: 1,
+            missing: vec!["user_id".to_string()],
+        })
+    );
+    assert_eq!(
+        schema.validate(&event.with_metadata("user_id", "alice")),
+        Ok(())
+    );
+    let other = Envelope::new("user-1", 1, "UserCreated", String::new());
+    assert_eq!(schema.validate(&other), Ok(()));
+}
+
+#[test]
+fn test_store_rejects_missing_metadata() {
+    let mut store = MetadataValidatedStore::new(OnMemoryEventStore::new(), schema());
+    let valid = order(1)
+        .with_metadata("user_id", "alice")
+        .with_metadata("tenant", "acme");
+    let result = store.save(&[valid.clone(), order(2)]);
+    assert!(matches!(result, Err(MetadataError::Invalid(v)) if v.version == 2));
+    assert!(store.load(&"order-1".to_string()).unwrap().is_empty());
+
+    let append = (
+        "order-1".to_string(),
+        ExpectedVersion::NoStream,
+        vec![order(1)],
+    );
+    assert!(matches!(
+        store.append_multi(&[append]),
+        Err(AppendError::Store(MetadataError::Invalid(_)))
+    ));
+
+    store.save(&[valid]).unwrap();
+    assert_eq!(store.load(&"order-1".to_string()).unwrap().len(), 1);
+}
+
+#[test]
+fn test_subscription_validates_on_read() {
+    let mut broker = OnMemoryBroker::new();
+    broker.publish(&order(1)).unwrap();
+    broker
+        .publish(
+            &order(2)
+                .with_metadata("user_id", "alice")
+                .with_metadata("tenant", "acme"),
+        )
+        .unwrap();
+
+    let mut subscription = MetadataValidatedSubscription::new(broker, schema());
+    assert!(matches!(
+        subscription.poll(),
+        Err(MetadataError::Invalid(v)) if v.missing == ["user_id", "tenant"]
+    ));
+    assert_eq!(subscription.poll().unwrap().unwrap().version, 2);
+    assert_eq!(subscription.poll().unwrap(), None);
+}
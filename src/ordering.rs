@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, ReadOnlyEventStore};
+
+/// Events of a single stream, ordered by version.
+///
+/// Nothing can be assumed about how they interleave with the events of other streams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerStreamOrdered<E> {
+    stream_id: String,
+    events: Vec<Envelope<E>>,
+}
+
+impl<E> PerStreamOrdered<E> {
+    /// Check that the events belong to the stream, by increasing version.
+    pub fn new(
+        stream_id: impl Into<String>,
+        events: Vec<Envelope<E>>,
+    ) -> Result<Self, OrderingError> {
+        let stream_id = stream_id.into();
+        let mut version = 0;
+        for event in &events {
+            if event.stream_id != stream_id {
+                return Err(OrderingError::ForeignStream {
+                    stream_id,
+                    found: event.stream_id.clone(),
+                });
+            }
+            if event.version <= version {
+                return Err(OrderingError::VersionNotIncreasing {
+                    stream_id,
+                    version: event.version,
+                });
+            }
+            version = event.version;
+        }
+        Ok(Self { stream_id, events })
+    }
+
+    /// Get the ID of the stream.
+    pub fn stream_id(&self) -> &str {
+        &self.stream_id
+    }
+
+    /// Get the events.
+    pub fn events(&self) -> &[Envelope<E>] {
+        &self.events
+    }
+
+    /// Get the version of the last event, 0 for an empty stream.
+    pub fn version(&self) -> u64 {
+        self.events.last().map_or(0, |e| e.version)
+    }
+
+    /// Unwrap the events.
+    pub fn into_events(self) -> Vec<Envelope<E>> {
+        self.events
+    }
+}
+
+/// Events of several streams, ordered by their position in the global log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GloballyOrdered<E> {
+    events: Vec<Envelope<E>>,
+}
+
+impl<E> GloballyOrdered<E> {
+    /// Check that the events are by increasing position.
+    pub fn new(events: Vec<Envelope<E>>) -> Result<Self, OrderingError> {
+        for pair in events.windows(2) {
+            if pair[1].position <= pair[0].position {
+                return Err(OrderingError::PositionNotIncreasing(pair[1].position));
+            }
+        }
+        Ok(Self { events })
+    }
+
+    /// Get the events.
+    pub fn events(&self) -> &[Envelope<E>] {
+        &self.events
+    }
+
+    /// Get the position following the last event, to resume reading from.
+    pub fn next_position(&self) -> Option<u64> {
+        self.events.last().map(|e| e.position + 1)
+    }
+
+    /// Split the events per stream, in the order the streams first appear.
+    pub fn by_stream(self) -> Vec<PerStreamOrdered<E>> {
+        let mut streams: Vec<PerStreamOrdered<E>> = Vec::new();
+        for event in self.events {
+            match streams.iter_mut().find(|s| s.stream_id == event.stream_id) {
+                Some(stream) => stream.events.push(event),
+                None => streams.push(PerStreamOrdered {
+                    stream_id: event.stream_id.clone(),
+                    events: vec![event],
+                }),
+            }
+        }
+        streams
+    }
+
+    /// Unwrap the events.
+    pub fn into_events(self) -> Vec<Envelope<E>> {
+        self.events
+    }
+}
+
+/// Types which read events with the ordering guarantee of the read operation in their type.
+pub trait OrderedReads<E>: EventLoader<StreamId = String, Persistable = Envelope<E>> {
+    /// Load the events of the stream, ordered by version.
+    fn load_ordered(
+        &self,
+        stream_id: &str,
+    ) -> Result<PerStreamOrdered<E>, OrderedReadError<Self::Error>> {
+        let events = self
+            .load(&stream_id.to_string())
+            .map_err(OrderedReadError::Store)?;
+        PerStreamOrdered::new(stream_id, events).map_err(OrderedReadError::Ordering)
+    }
+
+    /// Read the events of all the streams, ordered by position, starting at the position.
+    fn read_all_ordered(
+        &self,
+        from: u64,
+        limit: usize,
+    ) -> Result<GloballyOrdered<E>, OrderedReadError<Self::Error>>
+    where
+        Self: ReadOnlyEventStore,
+    {
+        let events = self
+            .read_all(from, limit)
+            .map_err(OrderedReadError::Store)?;
+        GloballyOrdered::new(events).map_err(OrderedReadError::Ordering)
+    }
+}
+
+impl<S, E> OrderedReads<E> for S where S: EventLoader<StreamId = String, Persistable = Envelope<E>> {}
+
+/// Error returned when events break the ordering guarantee.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderingError {
+    /// An event belongs to another stream.
+    ForeignStream { stream_id: String, found: String },
+    /// The version of an event does not follow the previous one.
+    VersionNotIncreasing { stream_id: String, version: u64 },
+    /// The position of an event does not follow the previous one.
+    PositionNotIncreasing(u64),
+}
+
+impl fmt::Display for OrderingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderingError::ForeignStream { stream_id, found } => {
+                write!(
+                    f,
+                    "event of stream {} read from stream {}",
+                    found, stream_id
+                )
+            }
+            OrderingError::VersionNotIncreasing { stream_id, version } => write!(
+                f,
+                "version {} of stream {} is out of order",
+                version, stream_id
+            ),
+            OrderingError::PositionNotIncreasing(position) => {
+                write!(f, "position {} is out of order", position)
+            }
+        }
+    }
+}
+
+impl Error for OrderingError {}
+
+/// Error returned by [`OrderedReads`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderedReadError<E> {
+    /// The store failed.
+    Store(E),
+    /// The store returned events breaking the ordering guarantee.
+    Ordering(OrderingError),
+}
+
+impl<E: Error> fmt::Display for OrderedReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderedReadError::Store(e) => write!(f, "store error: {}", e),
+            OrderedReadError::Ordering(e) => write!(f, "ordering error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for OrderedReadError<E> {}
@@ -0,0 +1,57 @@
+use super::*;
+
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+fn event(stream_id: &str, version: u64, position: u64) -> Envelope<u32> {
+    let mut event = Envelope::new(stream_id, version, "Incremented", 1);
+    event.position = position;
+    event
+}
+
+#[test]
+fn test_ordered_reads() {
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[
+            Envelope::new("counter-1", 0, "Incremented", 1),
+            Envelope::new("counter-2", 0, "Incremented", 2),
+            Envelope::new("counter-1", 0, "Incremented", 3),
+        ])
+        .unwrap();
+
+    let stream = store.load_ordered("counter-1").unwrap();
+    assert_eq!(stream.stream_id(), "counter-1");
+    assert_eq!(stream.version(), 2);
+
+    let log = store.read_all_ordered(0, 10).unwrap();
+    assert_eq!(log.next_position(), Some(3));
+    let streams = log.by_stream();
+    assert_eq!(streams.len(), 2);
+    assert_eq!(streams[0].events().len(), 2);
+    assert_eq!(streams[1].stream_id(), "counter-2");
+}
+
+#[test]
+fn test_ordering_violations() {
+    assert_eq!(
+        PerStreamOrdered::new(
+            "counter-1",
+            vec![event("counter-1", 2, 0), event("counter-1", 1, 1)]
+        ),
+        Err(OrderingError::VersionNotIncreasing {
+            stream_id: "counter-1".to_string(),
+            version: 1
+        })
+    );
+    assert_eq!(
+        PerStreamOrdered::new("counter-1", vec![event("counter-2", 1, 0)]),
+        Err(OrderingError::ForeignStream {
+            stream_id: "counter-1".to_string(),
+            found: "counter-2".to_string()
+        })
+    );
+    assert_eq!(
+        GloballyOrdered::new(vec![event("counter-1", 1, 4), event("counter-2", 1, 2)]),
+        Err(OrderingError::PositionNotIncreasing(2))
+    );
+}
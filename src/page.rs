@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests;
+
+use std::cmp::Ordering;
+
+/// Direction of a sort.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Smallest first.
+    #[default]
+    Ascending,
+    /// Largest first.
+    Descending,
+}
+
+/// Sort requested for a list query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    /// Name of the field to sort by, interpreted by the read model.
+    pub field: String,
+    /// Direction of the sort.
+    pub order: SortOrder,
+}
+
+/// Where a page starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageStart {
+    /// Skip the number of items.
+    Offset(usize),
+    /// Start after the item the cursor, returned by a previous page, points at.
+    Cursor(String),
+}
+
+/// Request of a page of a list query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    /// Where the page starts.
+    pub start: PageStart,
+    /// Maximum number of items of the page.
+    pub limit: usize,
+    /// Sort of the items, the natural order of the read model if `None`.
+    pub sort: Option<Sort>,
+}
+
+impl PageRequest {
+    /// Request the first page.
+    pub fn first(limit: usize) -> Self {
+        Self {
+            start: PageStart::Offset(0),
+            limit,
+            sort: None,
+        }
+    }
+
+    /// Request the page after the cursor.
+    pub fn after(cursor: impl Into<String>, limit: usize) -> Self {
+        Self {
+            start: PageStart::Cursor(cursor.into()),
+            limit,
+            sort: None,
+        }
+    }
+
+    /// Request the page skipping the number of items.
+    pub fn at_offset(offset: usize, limit: usize) -> Self {
+        Self {
+            start: PageStart::Offset(offset),
+            limit,
+            sort: None,
+        }
+    }
+
+    /// Sort the items by the field.
+    pub fn sorted_by(mut self, field: impl Into<String>, order: SortOrder) -> Self {
+        self.sort = Some(Sort {
+            field: field.into(),
+            order,
+        });
+        self
+    }
+
+    /// Get the request of the page following `page`, if there is one.
+    pub fn next<T>(&self, page: &Page<T>) -> Option<Self> {
+        page.next_cursor.as_ref().map(|cursor| Self {
+            start: PageStart::Cursor(cursor.clone()),
+            limit: self.limit,
+            sort: self.sort.clone(),
+        })
+    }
+}
+
+/// A page of the response of a list query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    /// Items of the page.
+    pub items: Vec<T>,
+    /// Cursor of the next page, `None` on the last page.
+    pub next_cursor: Option<String>,
+    /// Total number of items, if the read model knows it cheaply.
+    pub total: Option<usize>,
+}
+
+impl<T> Page<T> {
+    /// Get whether there is a page after this one.
+    pub fn has_more(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    /// Transform the items.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
+
+/// Paginate items held in memory.
+///
+/// `key` gives the unique key of an item, used as its cursor. `compare` orders two items by
+/// the sort field, and is only called when the request has a sort.
+pub fn paginate<T>(
+    mut items: Vec<T>,
+    request: &PageRequest,
+    key: impl Fn(&T) -> String,
+    compare: impl Fn(&str, &T, &T) -> Ordering,
+) -> Page<T> {
+    if let Some(sort) = &request.sort {
+        items.sort_by(|a, b| {
+            let ordering = compare(&sort.field, a, b);
+            match sort.order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        });
+    }
+    let total = items.len();
+    let start = match &request.start {
+        PageStart::Offset(offset) => (*offset).min(total),
+        PageStart::Cursor(cursor) => items
+            .iter()
+            .position(|item| key(item) == *cursor)
+            .map_or(total, |i| i + 1),
+    };
+    let end = start.saturating_add(request.limit).min(total);
+    let next_cursor = if end < total && end > start {
+        Some(key(&items[end - 1]))
+    } else {
+        None
+    };
+    let items = items.drain(start..end).collect();
+    Page {
+        items,
+        next_cursor,
+        total: Some(total),
+    }
+}
+
+/// Page items read by offset, encoding the offset of the next page in the cursor.
+///
+/// `items` holds up to `limit + 1` items read from `offset`, the extra item telling whether a
+/// next page exists.
+pub fn offset_page<T>(mut items: Vec<T>, offset: usize, limit: usize) -> Page<T> {
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        Some((offset + limit).to_string())
+    } else {
+        None
+    };
+    Page {
+        items,
+        next_cursor,
+        total: None,
+    }
+}
+
+/// Get the offset a request starts at, for read models whose cursors are offsets, as produced
+/// by [`offset_page`]. Returns `None` for a cursor which is not an offset.
+pub fn start_offset(request: &PageRequest) -> Option<usize> {
+    match &request.start {
+        PageStart::Offset(offset) => Some(*offset),
+        PageStart::Cursor(cursor) => cursor.parse().ok(),
+    }
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    id: u32,
+    name: &'static str,
+}
+
+fn items() -> Vec<Item> {
+    ["carol", "alice", "erin", "bob", "dave"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| Item { id: i as u32, name })
+        .collect()
+}
+
+fn page(request: &PageRequest) -> Page<Item> {
+    paginate(
+        items(),
+        request,
+        |item| item.id.to_string(),
+        |field, a, b| match field {
+            "name" => a.name.cmp(b.name),
+            _ => a.id.cmp(&b.id),
+        },
+    )
+}
+
+#[test]
+fn test_paginate_with_cursor_and_sort() {
+    let request = PageRequest::first(2).sorted_by("name", SortOrder::Ascending);
+    let mut names = Vec::new();
+    let mut next = Some(request);
+    while let Some(request) = next {
+        let page = page(&request);
+        assert_eq!(page.total, Some(5));
+        names.push(page.items.iter().map(|i| i.name).collect::<Vec<_>>());
+        next = request.next(&page);
+    }
+    assert_eq!(
+        names,
+        vec![vec!["alice", "bob"], vec!["carol", "dave"], vec!["erin"]]
+    );
+
+    let page = page(&PageRequest::first(10).sorted_by("name", SortOrder::Descending));
+    assert_eq!(page.items[0].name, "erin");
+    assert!(!page.has_more());
+}
+
+#[test]
+fn test_paginate_with_offset() {
+    let page = page(&PageRequest::at_offset(3, 10));
+    assert_eq!(page.map(|i| i.name).items, vec!["bob", "dave"]);
+    assert!(self::page(&PageRequest::at_offset(9, 10)).items.is_empty());
+    assert!(self::page(&PageRequest::after("unknown", 10))
+        .items
+        .is_empty());
+}
+
+#[test]
+fn test_offset_page() {
+    let page = offset_page(vec![1, 2, 3], 4, 2);
+    assert_eq!(page.items, vec![1, 2]);
+    assert_eq!(page.next_cursor.as_deref(), Some("6"));
+    let next = PageRequest::first(2).next(&page).unwrap();
+    assert_eq!(start_offset(&next), Some(6));
+    assert!(!offset_page(vec![1, 2], 6, 2).has_more());
+    assert_eq!(start_offset(&PageRequest::after("abc", 2)), None);
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests;
+
+/// Types which represent the umbrella of the events of several aggregates, so that one store
+/// can hold them all. Implemented by [`persistable_set!`](crate::persistable_set).
+pub trait PersistableSet {
+    /// Get the type of the aggregate the event belongs to.
+    fn aggregate_type(&self) -> &'static str;
+    /// Get the ID of the stream the event belongs to.
+    fn stream_id(&self) -> String;
+    /// Get the name of the event type.
+    fn event_type(&self) -> String;
+}
+
+/// Declare the umbrella enum over the events of the aggregates, with a variant per aggregate
+/// holding an [`AggregateEventEnvelope`](crate::aggregate::AggregateEventEnvelope).
+///
+/// It implements `From` the envelopes of the aggregates, `TryFrom` back to them, and
+/// [`PersistableSet`] to extract the stream ID:
+///
+/// ```ignore
+/// persistable_set! {
+///     #[derive(Debug, Clone)]
+///     pub enum ShopEvent {
+///         Order(Order),
+///         Payment(Payment),
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! persistable_set {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident($aggregate:ty)),+ $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis enum $name {
+            $($variant($crate::aggregate::AggregateEventEnvelope<$aggregate>)),+
+        }
+
+        $(
+            impl ::std::convert::From<$crate::aggregate::AggregateEventEnvelope<$aggregate>>
+                for $name
+            {
+                fn from(envelope: $crate::aggregate::AggregateEventEnvelope<$aggregate>) -> Self {
+                    $name::$variant(envelope)
+                }
+            }
+
+            impl ::std::convert::TryFrom<$name>
+                for $crate::aggregate::AggregateEventEnvelope<$aggregate>
+            {
+                type Error = $name;
+
+                #[allow(irrefutable_let_patterns, unreachable_patterns)]
+                fn try_from(event: $name) -> ::std::result::Result<Self, $name> {
+                    match event {
+                        $name::$variant(envelope) => Ok(envelope),
+                        other => Err(other),
+                    }
+                }
+            }
+        )+
+
+        impl $crate::persistable_set::PersistableSet for $name {
+            fn aggregate_type(&self) -> &'static str {
+                match self {
+                    $($name::$variant(_) => {
+                        <$aggregate as $crate::aggregate::Aggregate>::aggregate_type()
+                    })+
+                }
+            }
+
+            fn stream_id(&self) -> ::std::string::String {
+                match self {
+                    $($name::$variant(envelope) => envelope.stream_id()),+
+                }
+            }
+
+            fn event_type(&self) -> ::std::string::String {
+                match self {
+                    $($name::$variant(envelope) => {
+                        <$aggregate as $crate::aggregate::Aggregate>::event_type(&envelope.event)
+                    })+
+                }
+            }
+        }
+    };
+}
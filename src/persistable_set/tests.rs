@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::aggregate::{Aggregate, AggregateEventEnvelope};
+
+#[derive(Debug, Default)]
+struct Order;
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderEvent {
+    Created,
+    Shipped,
+}
+
+impl Aggregate for Order {
+    type Id = u32;
+    type Event = OrderEvent;
+
+    fn aggregate_type() -> &'static str {
+        "order"
+    }
+
+    fn event_type(event: &OrderEvent) -> String {
+        format!("{:?}", event)
+    }
+
+    fn apply(&mut self, _event: &OrderEvent) {}
+}
+
+#[derive(Debug, Default)]
+struct Payment;
+
+#[derive(Debug, Clone, PartialEq)]
+enum PaymentEvent {
+    Captured(i64),
+}
+
+impl Aggregate for Payment {
+    type Id = String;
+    type Event = PaymentEvent;
+
+    fn aggregate_type() -> &'static str {
+        "payment"
+    }
+
+    fn event_type(_event: &PaymentEvent) -> String {
+        "Captured".to_string()
+    }
+
+    fn apply(&mut self, _event: &PaymentEvent) {}
+}
+
+crate::persistable_set! {
+    #[derive(Debug, Clone)]
+    enum ShopEvent {
+        Order(Order),
+        Payment(Payment),
+    }
+}
+
+#[test]
+fn test_persistable_set() {
+    let events: Vec<ShopEvent> = vec![
+        AggregateEventEnvelope::<Order>::new(1, OrderEvent::Created).into(),
+        AggregateEventEnvelope::<Payment>::new("p-1".to_string(), PaymentEvent::Captured(10))
+            .into(),
+        AggregateEventEnvelope::<Order>::new(1, OrderEvent::Shipped).into(),
+    ];
+
+    let mut streams: HashMap<String, Vec<ShopEvent>> = HashMap::new();
+    for event in &events {
+        streams
+            .entry(event.stream_id())
+            .or_default()
+            .push(event.clone());
+    }
+    assert_eq!(streams["order-1"].len(), 2);
+    assert_eq!(streams["payment-p-1"][0].aggregate_type(), "payment");
+    assert_eq!(events[2].event_type(), "Shipped");
+
+    let order = AggregateEventEnvelope::<Order>::try_from(events[0].clone()).unwrap();
+    assert_eq!(order.event, OrderEvent::Created);
+    let payment = AggregateEventEnvelope::<Order>::try_from(events[1].clone());
+    assert!(matches!(payment, Err(ShopEvent::Payment(_))));
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+/// Check a single precondition of a command, e.g.
+/// `require(org.users.len() < org.max_users, OrgError::MaxUsers)?`.
+pub fn require<E>(condition: bool, error: E) -> Result<(), Rejected<E>> {
+    Preconditions::new().require(condition, error).check()
+}
+
+/// Preconditions of a command, checked together so that every violation is reported at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preconditions<E> {
+    violations: Vec<E>,
+}
+
+impl<E> Preconditions<E> {
+    /// Create a set of preconditions, all holding.
+    pub fn new() -> Self {
+        Self {
+            violations: Vec::new(),
+        }
+    }
+
+    /// Require the condition, reporting the error if it does not hold.
+    pub fn require(self, condition: bool, error: E) -> Self {
+        self.require_with(condition, || error)
+    }
+
+    /// Require the condition, building the error only if it does not hold.
+    pub fn require_with(mut self, condition: bool, error: impl FnOnce() -> E) -> Self {
+        if !condition {
+            self.violations.push(error());
+        }
+        self
+    }
+
+    /// Require the preconditions of another set, e.g. shared by several commands.
+    pub fn and(mut self, other: Preconditions<E>) -> Self {
+        self.violations.extend(other.violations);
+        self
+    }
+
+    /// Check whether every precondition holds.
+    pub fn holds(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Reject the command if any precondition does not hold.
+    pub fn check(self) -> Result<(), Rejected<E>> {
+        if self.violations.is_empty() {
+            return Ok(());
+        }
+        Err(Rejected::new(self.violations))
+    }
+}
+
+impl<E> Default for Preconditions<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned when preconditions of a command do not hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected<E> {
+    violations: Vec<E>,
+}
+
+impl<E> Rejected<E> {
+    /// Create a rejection for the violated preconditions.
+    pub fn new(violations: Vec<E>) -> Self {
+        Self { violations }
+    }
+
+    /// Get the violated preconditions, in the order they were required.
+    pub fn violations(&self) -> &[E] {
+        &self.violations
+    }
+
+    /// Unwrap the violated preconditions.
+    pub fn into_violations(self) -> Vec<E> {
+        self.violations
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Rejected<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "command rejected: ")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for Rejected<E> {}
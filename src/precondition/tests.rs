@@ -0,0 +1,79 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrgError {
+    MaxUsers { max: usize },
+    AlreadyMember(String),
+}
+
+impl fmt::Display for OrgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrgError::MaxUsers { max } => write!(f, "at most {} users", max),
+            OrgError::AlreadyMember(user) => write!(f, "{} is already a member", user),
+        }
+    }
+}
+
+struct Org {
+    users: Vec<String>,
+    max_users: usize,
+}
+
+impl Org {
+    fn add_user(&self, user: &str) -> Result<(), Rejected<OrgError>> {
+        Preconditions::new()
+            .require(
+                self.users.len() < self.max_users,
+                OrgError::MaxUsers {
+                    max: self.max_users,
+                },
+            )
+            .require_with(!self.users.iter().any(|u| u == user), || {
+                OrgError::AlreadyMember(user.to_string())
+            })
+            .check()
+    }
+}
+
+#[test]
+fn test_report_every_violation() {
+    let org = Org {
+        users: vec!["alice".to_string()],
+        max_users: 1,
+    };
+    assert_eq!(org.add_user("bob").unwrap_err().violations().len(), 1);
+
+    let rejected = org.add_user("alice").unwrap_err();
+    assert_eq!(
+        rejected.violations(),
+        [
+            OrgError::MaxUsers { max: 1 },
+            OrgError::AlreadyMember("alice".to_string())
+        ]
+    );
+    assert_eq!(
+        rejected.to_string(),
+        "command rejected: at most 1 users; alice is already a member"
+    );
+
+    let org = Org {
+        users: Vec::new(),
+        max_users: 1,
+    };
+    assert_eq!(org.add_user("alice"), Ok(()));
+}
+
+#[test]
+fn test_combine_preconditions() {
+    let shared = Preconditions::new().require(false, OrgError::MaxUsers { max: 0 });
+    let combined = Preconditions::new()
+        .require(true, OrgError::AlreadyMember("bob".to_string()))
+        .and(shared);
+    assert!(!combined.holds());
+    assert_eq!(
+        combined.check().unwrap_err().into_violations(),
+        vec![OrgError::MaxUsers { max: 0 }]
+    );
+    assert_eq!(require(true, OrgError::MaxUsers { max: 1 }), Ok(()));
+}
@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::command_bus::CommandBus;
+
+/// Priority class of a command, each class having its own queue and workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Commands issued by users, waiting for the response.
+    Interactive,
+    /// Bulk commands, such as imports and migrations.
+    Batch,
+}
+
+/// Types which tell the priority class of a command.
+pub trait Prioritized {
+    /// Get the priority class of the command.
+    fn priority(&self) -> Priority {
+        Priority::Interactive
+    }
+}
+
+/// Configuration of the lane of a priority class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaneConfig {
+    /// Number of worker threads.
+    pub workers: usize,
+    /// Maximum number of queued commands, beyond which submissions are rejected.
+    pub capacity: usize,
+}
+
+/// Configuration of the lanes of a [`PriorityBus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityBusConfig {
+    /// Lane of the interactive commands.
+    pub interactive: LaneConfig,
+    /// Lane of the batch commands.
+    pub batch: LaneConfig,
+}
+
+impl Default for PriorityBusConfig {
+    fn default() -> Self {
+        Self {
+            interactive: LaneConfig {
+                workers: 4,
+                capacity: 1024,
+            },
+            batch: LaneConfig {
+                workers: 1,
+                capacity: 1024,
+            },
+        }
+    }
+}
+
+type Job<C, R, E> = (C, Sender<Result<R, E>>);
+
+/// Command bus processing the commands on worker threads, with a separate queue and pool of
+/// workers per priority class, so that bulk commands cannot starve interactive ones.
+///
+/// Every worker dispatches to its own bus, created by the factory. Dropping the bus stops
+/// the workers once the queued commands are processed.
+pub struct PriorityBus<C, R, E> {
+    interactive: Option<SyncSender<Job<C, R, E>>>,
+    batch: Option<SyncSender<Job<C, R, E>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<C, R, E> PriorityBus<C, R, E>
+where
+    C: Send + 'static,
+    R: Send + 'static,
+    E: Send + 'static,
+{
+    /// Start the workers of the lanes.
+    pub fn start<B, F>(config: PriorityBusConfig, factory: F) -> Self
+    where
+        B: CommandBus<C, Response = R, Error = E>,
+        F: Fn() -> B + Send + Sync + 'static,
+    {
+        let factory = Arc::new(factory);
+        let mut workers = Vec::new();
+        let interactive = Self::lane(config.interactive, &factory, &mut workers);
+        let batch = Self::lane(config.batch, &factory, &mut workers);
+        Self {
+            interactive: Some(interactive),
+            batch: Some(batch),
+            workers,
+        }
+    }
+
+    fn lane<B, F>(
+        config: LaneConfig,
+        factory: &Arc<F>,
+        workers: &mut Vec<JoinHandle<()>>,
+    ) -> SyncSender<Job<C, R, E>>
+    where
+        B: CommandBus<C, Response = R, Error = E>,
+        F: Fn() -> B + Send + Sync + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel::<Job<C, R, E>>(config.capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..config.workers.max(1) {
+            let receiver = Arc::clone(&receiver);
+            let factory = Arc::clone(factory);
+            workers.push(thread::spawn(move || {
+                let mut bus = factory();
+                loop {
+                    let job = receiver.lock().unwrap().recv();
+                    let (command, reply) = match job {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    let _ = reply.send(bus.dispatch(command));
+                }
+            }));
+        }
+        sender
+    }
+
+    /// Queue the command in the lane of its priority class.
+    pub fn submit(&self, command: C) -> Result<Ticket<R, E>, PriorityBusError<E>>
+    where
+        C: Prioritized,
+    {
+        let priority = command.priority();
+        let lane = match priority {
+            Priority::Interactive => &self.interactive,
+            Priority::Batch => &self.batch,
+        };
+        let lane = lane.as_ref().ok_or(PriorityBusError::Stopped)?;
+        let (reply, response) = mpsc::channel();
+        lane.try_send((command, reply)).map_err(|e| match e {
+            TrySendError::Full(_) => PriorityBusError::Full(priority),
+            TrySendError::Disconnected(_) => PriorityBusError::Stopped,
+        })?;
+        Ok(Ticket(response))
+    }
+}
+
+impl<C, R, E> CommandBus<C> for PriorityBus<C, R, E>
+where
+    C: Prioritized + Send + 'static,
+    R: Send + 'static,
+    E: Error + Send + 'static,
+{
+    type Response = R;
+    type Error = PriorityBusError<E>;
+
+    fn dispatch(&mut self, command: C) -> Result<Self::Response, Self::Error> {
+        self.submit(command)?.wait()
+    }
+}
+
+impl<C, R, E> Drop for PriorityBus<C, R, E> {
+    fn drop(&mut self) {
+        self.interactive = None;
+        self.batch = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Handle on the response of a command submitted to a [`PriorityBus`].
+#[derive(Debug)]
+pub struct Ticket<R, E>(Receiver<Result<R, E>>);
+
+impl<R, E> Ticket<R, E> {
+    /// Wait for the response of the command.
+    pub fn wait(self) -> Result<R, PriorityBusError<E>> {
+        self.0
+            .recv()
+            .map_err(|_| PriorityBusError::Stopped)?
+            .map_err(PriorityBusError::Bus)
+    }
+}
+
+/// Error returned by the [`PriorityBus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriorityBusError<E> {
+    /// The queue of the priority class is full.
+    Full(Priority),
+    /// The workers stopped before processing the command.
+    Stopped,
+    /// The bus of the worker failed.
+    Bus(E),
+}
+
+impl<E: Error> fmt::Display for PriorityBusError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriorityBusError::Full(priority) => write!(f, "{:?} queue is full", priority),
+            PriorityBusError::Stopped => write!(f, "bus workers stopped"),
+            PriorityBusError::Bus(e) => write!(f, "bus error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for PriorityBusError<E> {}
@@ -0,0 +1,83 @@
+use std::convert::Infallible;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use super::*;
+
+enum Command {
+    Rename(String),
+    Import(usize),
+}
+
+impl Prioritized for Command {
+    fn priority(&self) -> Priority {
+        match self {
+            Command::Rename(_) => Priority::Interactive,
+            Command::Import(_) => Priority::Batch,
+        }
+    }
+}
+
+/// Bus blocking the imports until released.
+struct GatedBus(Arc<Mutex<Receiver<()>>>);
+
+impl CommandBus<Command> for GatedBus {
+    type Response = String;
+    type Error = Infallible;
+
+    fn dispatch(&mut self, command: Command) -> Result<Self::Response, Self::Error> {
+        match command {
+            Command::Rename(name) => Ok(format!("renamed to {}", name)),
+            Command::Import(n) => {
+                self.0.lock().unwrap().recv().unwrap();
+                Ok(format!("imported {}", n))
+            }
+        }
+    }
+}
+
+fn config() -> PriorityBusConfig {
+    PriorityBusConfig {
+        interactive: LaneConfig {
+            workers: 1,
+            capacity: 4,
+        },
+        batch: LaneConfig {
+            workers: 1,
+            capacity: 1,
+        },
+    }
+}
+
+#[test]
+fn test_batch_does_not_starve_interactive() {
+    let (release, gate) = mpsc::channel();
+    let gate = Arc::new(Mutex::new(gate));
+    let mut bus = PriorityBus::start(config(), move || GatedBus(Arc::clone(&gate)));
+
+    let first = bus.submit(Command::Import(1)).unwrap();
+    // Once the batch worker took the first import, the second one fills the batch queue.
+    let second = loop {
+        match bus.submit(Command::Import(2)) {
+            Ok(ticket) => break ticket,
+            Err(PriorityBusError::Full(_)) => thread::sleep(Duration::from_millis(1)),
+            Err(e) => panic!("{}", e),
+        }
+    };
+    assert!(matches!(
+        bus.submit(Command::Import(3)),
+        Err(PriorityBusError::Full(Priority::Batch))
+    ));
+
+    // The imports are blocked, interactive commands still go through.
+    assert_eq!(
+        bus.dispatch(Command::Rename("Acme".to_string())),
+        Ok("renamed to Acme".to_string())
+    );
+
+    for _ in 0..2 {
+        release.send(()).unwrap();
+    }
+    assert_eq!(first.wait(), Ok("imported 1".to_string()));
+    assert_eq!(second.wait(), Ok("imported 2".to_string()));
+}
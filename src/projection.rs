@@ -0,0 +1,732 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::clock::Clock;
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, LogHead, ReadOnlyEventStore,
+    TransactionManager,
+};
+
+/// Types which represent a read model built from events.
+pub trait Projection {
+    /// Associated Type representing the event type.
+    type Event;
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Get the schema version of the projection. Changing it triggers a rebuild.
+    fn version(&self) -> u32 {
+        1
+    }
+    /// Apply the event to the read model.
+    fn apply(&mut self, event: &Envelope<Self::Event>) -> Result<(), Self::Error>;
+}
+
+/// Projection fed from an event store, keeping track of its position.
+#[derive(Debug)]
+pub struct ProjectionRunner<P> {
+    projection: P,
+    position: u64,
+}
+
+impl<P> ProjectionRunner<P> {
+    /// Create a runner starting at the beginning of the log.
+    pub fn new(projection: P) -> Self {
+        Self::starting_at(projection, 0)
+    }
+
+    /// Create a runner starting at the position.
+    pub fn starting_at(projection: P, position: u64) -> Self {
+        Self {
+            projection,
+            position,
+        }
+    }
+
+    /// Get the projection.
+    pub fn projection(&self) -> &P {
+        &self.projection
+    }
+
+    /// Get the position of the next event to apply.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Unwrap the projection.
+    pub fn into_inner(self) -> P {
+        self.projection
+    }
+}
+
+impl<P: Projection> ProjectionRunner<P> {
+    /// Apply the next batch of events, returning the number of events applied.
+    pub fn run_batch<S>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<usize, ProjectionError<S::Error, P::Error>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<P::Event>>,
+    {
+        let events = store
+            .read_all(self.position, batch_size)
+            .map_err(ProjectionError::Store)?;
+        for event in &events {
+            self.projection
+                .apply(event)
+                .map_err(ProjectionError::Projection)?;
+            self.position = event.position + 1;
+        }
+        Ok(events.len())
+    }
+
+    /// Apply batches of events until the head of the log is reached, reporting the progress
+    /// to the observer after every batch. Returns the final progress.
+    pub fn catch_up<S, K>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+        clock: &K,
+        observer: &mut impl ProgressObserver,
+    ) -> Result<CatchUpProgress, ProjectionError<S::Error, P::Error>>
+    where
+        S: LogHead<Persistable = Envelope<P::Event>>,
+        K: Clock,
+    {
+        let started_at = clock.now();
+        let mut processed = 0;
+        loop {
+            let applied = self.run_batch(store, batch_size.max(1))?;
+            processed += applied as u64;
+            let head = store.head_position().map_err(ProjectionError::Store)?;
+            let elapsed = clock
+                .now()
+                .duration_since(started_at)
+                .unwrap_or_default()
+                .as_secs_f64();
+            let rate = if elapsed > 0.0 {
+                processed as f64 / elapsed
+            } else {
+                0.0
+            };
+            let mut progress = CatchUpProgress {
+                processed,
+                position: self.position,
+                head,
+                rate,
+                eta: None,
+            };
+            if rate > 0.0 {
+                progress.eta = Some(Duration::from_secs_f64(progress.remaining() as f64 / rate));
+            }
+            observer.on_progress(&progress);
+            if applied == 0 || progress.is_caught_up() {
+                return Ok(progress);
+            }
+        }
+    }
+}
+
+/// Progress of a projection catching up with the head of the log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CatchUpProgress {
+    /// Number of events applied since the catch-up started.
+    pub processed: u64,
+    /// Position of the next event to apply.
+    pub position: u64,
+    /// Position the next event will be written at.
+    pub head: u64,
+    /// Events applied per second since the catch-up started.
+    pub rate: f64,
+    /// Estimated time left to catch up, unknown until some time has elapsed.
+    pub eta: Option<Duration>,
+}
+
+impl CatchUpProgress {
+    /// Get the number of events left to apply.
+    pub fn remaining(&self) -> u64 {
+        self.head.saturating_sub(self.position)
+    }
+
+    /// Check whether the projection reached the head.
+    pub fn is_caught_up(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+/// Types which are told the progress of a catch-up, such as progress bars and alerts.
+pub trait ProgressObserver {
+    /// Observe the progress after a batch.
+    fn on_progress(&mut self, progress: &CatchUpProgress);
+}
+
+impl<F: FnMut(&CatchUpProgress)> ProgressObserver for F {
+    fn on_progress(&mut self, progress: &CatchUpProgress) {
+        self(progress)
+    }
+}
+
+/// Bounds and target of the batch size of an [`AdaptiveProjectionRunner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveBatching {
+    /// Batch size once caught up, for freshness.
+    pub min: usize,
+    /// Largest batch size while behind, for throughput.
+    pub max: usize,
+    /// Time a batch should take to apply; slower batches are halved.
+    pub target_latency: Duration,
+}
+
+impl Default for AdaptiveBatching {
+    fn default() -> Self {
+        Self {
+            min: 10,
+            max: 1000,
+            target_latency: Duration::from_millis(50),
+        }
+    }
+}
+
+impl AdaptiveBatching {
+    /// Get the bounds raised to at least one event, with the largest batch size raised to
+    /// the smallest one if below it.
+    pub fn normalized(self) -> Self {
+        let min = self.min.max(1);
+        Self {
+            min,
+            max: self.max.max(min),
+            ..self
+        }
+    }
+}
+
+/// Measures of the last batch applied by an [`AdaptiveProjectionRunner`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchMetrics {
+    /// Number of events applied.
+    pub applied: usize,
+    /// Time taken to apply the events.
+    pub apply_latency: Duration,
+    /// Number of events left to apply after the batch.
+    pub lag: u64,
+    /// Batch size chosen for the next batch.
+    pub next_batch_size: usize,
+}
+
+/// Projection runner adapting its batch size to its lag and apply latency: small batches
+/// when caught up, doubling while behind as long as batches apply within the target latency.
+#[derive(Debug)]
+pub struct AdaptiveProjectionRunner<P, K> {
+    runner: ProjectionRunner<P>,
+    clock: K,
+    batching: AdaptiveBatching,
+    batch_size: usize,
+    metrics: BatchMetrics,
+}
+
+impl<P: Projection, K: Clock> AdaptiveProjectionRunner<P, K> {
+    /// Wrap the runner, starting with the smallest batch size.
+    ///
+    /// The bounds are [normalized](AdaptiveBatching::normalized).
+    pub fn new(runner: ProjectionRunner<P>, clock: K, batching: AdaptiveBatching) -> Self {
+        let batching = batching.normalized();
+        Self {
+            runner,
+            clock,
+            batching,
+            batch_size: batching.min,
+            metrics: BatchMetrics::default(),
+        }
+    }
+
+    /// Get the wrapped runner.
+    pub fn runner(&self) -> &ProjectionRunner<P> {
+        &self.runner
+    }
+
+    /// Get the size of the next batch.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Get the measures of the last batch.
+    pub fn metrics(&self) -> BatchMetrics {
+        self.metrics
+    }
+
+    /// Apply the next batch of events and adapt the batch size, returning the measures.
+    pub fn run_batch<S>(
+        &mut self,
+        store: &S,
+    ) -> Result<BatchMetrics, ProjectionError<S::Error, P::Error>>
+    where
+        S: LogHead<Persistable = Envelope<P::Event>>,
+    {
+        let started_at = self.clock.now();
+        let applied = self.runner.run_batch(store, self.batch_size)?;
+        let apply_latency = self
+            .clock
+            .now()
+            .duration_since(started_at)
+            .unwrap_or_default();
+        let head = store.head_position().map_err(ProjectionError::Store)?;
+        let lag = head.saturating_sub(self.runner.position());
+        self.batch_size = if lag == 0 {
+            self.batching.min
+        } else if apply_latency > self.batching.target_latency {
+            self.batch_size / 2
+        } else {
+            self.batch_size.saturating_mul(2)
+        }
+        .clamp(self.batching.min, self.batching.max);
+        self.metrics = BatchMetrics {
+            applied,
+            apply_latency,
+            lag,
+            next_batch_size: self.batch_size,
+        };
+        Ok(self.metrics)
+    }
+}
+
+/// Manager serving a projection while a new version of it is rebuilt alongside.
+///
+/// When a projection with a different version is deployed, it is built from the beginning of
+/// the log while the current version keeps serving reads. Once the new version has caught up,
+/// readers are switched over to it.
+#[derive(Debug)]
+pub struct ProjectionManager<P> {
+    active: ProjectionRunner<P>,
+    next: Option<ProjectionRunner<P>>,
+}
+
+impl<P: Projection> ProjectionManager<P> {
+    /// Create a manager serving the projection.
+    pub fn new(projection: P) -> Self {
+        Self {
+            active: ProjectionRunner::new(projection),
+            next: None,
+        }
+    }
+
+    /// Get the projection serving reads.
+    pub fn active(&self) -> &P {
+        self.active.projection()
+    }
+
+    /// Get the version being rebuilt, if any.
+    pub fn rebuilding(&self) -> Option<u32> {
+        self.next.as_ref().map(|n| n.projection().version())
+    }
+
+    /// Deploy a projection. A rebuild starts if its version differs from the active one.
+    pub fn deploy(&mut self, projection: P) {
+        if projection.version() != self.active().version() {
+            self.next = Some(ProjectionRunner::new(projection));
+        }
+    }
+
+    /// Advance the active projection and the rebuild by a batch, switching over once the
+    /// rebuild has caught up. Returns `true` if the switch happened.
+    pub fn run_batch<S>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<bool, ProjectionError<S::Error, P::Error>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<P::Event>>,
+    {
+        self.active.run_batch(store, batch_size)?;
+        let next = match self.next.as_mut() {
+            Some(next) => next,
+            None => return Ok(false),
+        };
+        next.run_batch(store, batch_size)?;
+        if next.position() < self.active.position() {
+            return Ok(false);
+        }
+        if let Some(next) = self.next.take() {
+            self.active = next;
+        }
+        Ok(true)
+    }
+}
+
+/// Types which represent a read model that can be compared with another version of it.
+pub trait ComparableReadModel {
+    /// Get the rows of the read model, rendered as text, by key.
+    fn rows(&self) -> BTreeMap<String, String>;
+}
+
+/// Comparison of a shadow projection against the current one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShadowReport {
+    /// Position of the next event the current projection applies.
+    pub position: u64,
+    /// Whether the shadow projection has caught up with the current one.
+    pub caught_up: bool,
+    /// Number of rows equal in both projections.
+    pub matching: usize,
+    /// Keys of the rows only in the current projection.
+    pub missing: Vec<String>,
+    /// Keys of the rows only in the shadow projection.
+    pub unexpected: Vec<String>,
+    /// Rows differing, as their key with the current and the shadow rows.
+    pub different: Vec<(String, String, String)>,
+    /// Events the shadow projection failed to apply, as their position and the error.
+    pub shadow_errors: Vec<(u64, String)>,
+}
+
+impl ShadowReport {
+    /// Check whether the shadow projection caught up and agrees with the current one.
+    pub fn is_clean(&self) -> bool {
+        self.caught_up
+            && self.missing.is_empty()
+            && self.unexpected.is_empty()
+            && self.different.is_empty()
+            && self.shadow_errors.is_empty()
+    }
+}
+
+/// A new projection version running in shadow mode: it is fed the same events as the current
+/// version and writes to its own target, so that both can be compared before cutting over.
+///
+/// Failures of the shadow projection are recorded in the report instead of interrupting the
+/// current projection.
+#[derive(Debug)]
+pub struct ShadowProjection<P, Q> {
+    current: ProjectionRunner<P>,
+    shadow: ProjectionRunner<Q>,
+    shadow_errors: Vec<(u64, String)>,
+}
+
+impl<P, Q> ShadowProjection<P, Q>
+where
+    P: Projection + ComparableReadModel,
+    Q: Projection<Event = P::Event> + ComparableReadModel,
+{
+    /// Run the shadow projection alongside the current one.
+    pub fn new(current: ProjectionRunner<P>, shadow: ProjectionRunner<Q>) -> Self {
+        Self {
+            current,
+            shadow,
+            shadow_errors: Vec::new(),
+        }
+    }
+
+    /// Get the current projection, serving reads.
+    pub fn current(&self) -> &P {
+        self.current.projection()
+    }
+
+    /// Get the shadow projection.
+    pub fn shadow(&self) -> &Q {
+        self.shadow.projection()
+    }
+
+    /// Advance both projections by a batch, returning the number of events applied by the
+    /// current one.
+    pub fn run_batch<S>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<usize, ProjectionError<S::Error, P::Error>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<P::Event>>,
+    {
+        let applied = self.current.run_batch(store, batch_size)?;
+        let events = store
+            .read_all(self.shadow.position, batch_size)
+            .map_err(ProjectionError::Store)?;
+        for event in &events {
+            if let Err(e) = self.shadow.projection.apply(event) {
+                self.shadow_errors.push((event.position, e.to_string()));
+            }
+            self.shadow.position = event.position + 1;
+        }
+        Ok(applied)
+    }
+
+    /// Compare the read models of both projections.
+    pub fn compare(&self) -> ShadowReport {
+        let current = self.current().rows();
+        let mut shadow = self.shadow().rows();
+        let mut report = ShadowReport {
+            position: self.current.position(),
+            caught_up: self.shadow.position() >= self.current.position(),
+            shadow_errors: self.shadow_errors.clone(),
+            ..ShadowReport::default()
+        };
+        for (key, row) in current {
+            match shadow.remove(&key) {
+                Some(shadow_row) if shadow_row == row => report.matching += 1,
+                Some(shadow_row) => report.different.push((key, row, shadow_row)),
+                None => report.missing.push(key),
+            }
+        }
+        report.unexpected = shadow.into_keys().collect();
+        report
+    }
+
+    /// Cut over to the shadow projection, returning it.
+    pub fn cut_over(self) -> ProjectionRunner<Q> {
+        self.shadow
+    }
+}
+
+/// Event store updating a projection in the same transaction as each append, for read models
+/// which must be strongly consistent with the events.
+///
+/// The events are applied to a staged copy of the projection, and to the projection itself once
+/// the transaction commits, so that a failed append leaves both the store and the read model
+/// unchanged. The staged copy is only cloned again from the projection when a transaction is
+/// rolled back. SQL read models sharing the connection of the store are rolled back with it.
+/// The events are applied before the store assigns their positions, which such read models
+/// cannot rely on.
+///
+/// Appends join the transaction begun through the store, e.g. by a unit of work, or run in a
+/// transaction of their own otherwise. A failed append rolls back the transaction it ran in.
+pub struct SyncProjectionStore<S, P: Projection> {
+    inner: S,
+    projection: P,
+    staged: P,
+    pending: Vec<Envelope<P::Event>>,
+    active: bool,
+}
+
+impl<S, P: Projection + Clone> SyncProjectionStore<S, P> {
+    /// Update the projection on every append to the store.
+    pub fn new(inner: S, projection: P) -> Self {
+        Self {
+            inner,
+            staged: projection.clone(),
+            projection,
+            pending: Vec::new(),
+            active: false,
+        }
+    }
+}
+
+impl<S, P: Projection> SyncProjectionStore<S, P> {
+    /// Get the projection, as of the last committed transaction.
+    pub fn projection(&self) -> &P {
+        &self.projection
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S, P> SyncProjectionStore<S, P>
+where
+    S: TransactionManager,
+    P: Projection + Clone,
+    P::Event: Clone,
+{
+    fn stage<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a Envelope<P::Event>>,
+    ) -> Result<(), P::Error>
+    where
+        P::Event: 'a,
+    {
+        for event in events {
+            self.pending.push(event.clone());
+            self.staged.apply(event)?;
+        }
+        Ok(())
+    }
+
+    fn discard_staged(&mut self) {
+        self.active = false;
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.staged = self.projection.clone();
+        }
+    }
+
+    /// Run the append in the active transaction, or in one of its own, rolling the
+    /// transaction back if it fails.
+    fn append_with<T, F>(
+        &mut self,
+        append: impl FnOnce(&mut Self) -> Result<T, F>,
+        transaction: impl Fn(<S as TransactionManager>::Error) -> F,
+    ) -> Result<T, F> {
+        let own = !self.active;
+        if own {
+            self.begin().map_err(&transaction)?;
+        }
+        let result = match append(self) {
+            Ok(result) => result,
+            Err(e) => {
+                let _ = self.rollback();
+                return Err(e);
+            }
+        };
+        if own {
+            self.commit().map_err(&transaction)?;
+        }
+        Ok(result)
+    }
+}
+
+type SyncError<S, P> = SyncProjectionError<
+    <S as EventStore>::Error,
+    <S as TransactionManager>::Error,
+    <P as Projection>::Error,
+>;
+
+impl<S, P, E> EventStore for SyncProjectionStore<S, P>
+where
+    S: EventStore<Persistable = Envelope<E>> + TransactionManager,
+    P: Projection<Event = E> + Clone,
+    E: Clone,
+{
+    type Persistable = Envelope<E>;
+    type Error = SyncError<S, P>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.append_with(
+            |store| {
+                store
+                    .inner
+                    .save(events)
+                    .map_err(SyncProjectionError::Store)?;
+                store.stage(events).map_err(SyncProjectionError::Projection)
+            },
+            SyncProjectionError::Transaction,
+        )
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.append_with(
+            |store| {
+                store
+                    .inner
+                    .append_multi(appends)
+                    .map_err(|e| e.map_store(SyncProjectionError::Store))?;
+                store
+                    .stage(appends.iter().flat_map(|(_, _, events)| events))
+                    .map_err(|e| AppendError::Store(SyncProjectionError::Projection(e)))
+            },
+            |e| AppendError::Store(SyncProjectionError::Transaction(e)),
+        )
+    }
+}
+
+impl<S, P> TransactionManager for SyncProjectionStore<S, P>
+where
+    S: TransactionManager,
+    P: Projection + Clone,
+    P::Event: Clone,
+{
+    type Error = S::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.inner.begin()?;
+        self.active = true;
+        Ok(())
+    }
+
+    /// Commit the store, then apply the staged events to the projection.
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        if let Err(e) = self.inner.commit() {
+            self.discard_staged();
+            return Err(e);
+        }
+        self.active = false;
+        for event in std::mem::take(&mut self.pending) {
+            if self.projection.apply(&event).is_err() {
+                // Applying the events again is expected to succeed; fall back on the copy.
+                self.projection = self.staged.clone();
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.discard_staged();
+        self.inner.rollback()
+    }
+}
+
+impl<S: EventLoader, P: Projection> EventLoader for SyncProjectionStore<S, P> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = S::Error;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.inner.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.inner.read_multi(stream_ids)
+    }
+}
+
+/// Error returned by the [`SyncProjectionStore`]. The append is rolled back in every case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncProjectionError<E, TE, PE> {
+    /// The store failed to save the events.
+    Store(E),
+    /// The transaction could not be begun or committed.
+    Transaction(TE),
+    /// The projection failed to apply an event.
+    Projection(PE),
+}
+
+impl<E: Error, TE: Error, PE: Error> fmt::Display for SyncProjectionError<E, TE, PE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncProjectionError::Store(e) => write!(f, "store error: {}", e),
+            SyncProjectionError::Transaction(e) => write!(f, "transaction error: {}", e),
+            SyncProjectionError::Projection(e) => write!(f, "projection error: {}", e),
+        }
+    }
+}
+
+impl<E: Error, TE: Error, PE: Error> Error for SyncProjectionError<E, TE, PE> {}
+
+/// Error returned when running a projection.
+#[derive(Debug)]
+pub enum ProjectionError<SE, PE> {
+    /// The event store failed.
+    Store(SE),
+    /// The projection failed to apply an event.
+    Projection(PE),
+}
+
+impl<SE: Error, PE: Error> fmt::Display for ProjectionError<SE, PE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectionError::Store(e) => write!(f, "store error: {}", e),
+            ProjectionError::Projection(e) => write!(f, "projection error: {}", e),
+        }
+    }
+}
+
+impl<SE: Error, PE: Error> Error for ProjectionError<SE, PE> {}
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+
+use std::time::Duration;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore};
+
+#[derive(Debug, Clone)]
+enum OrgEvent {
+    UserAdded(String),
+}
+
+#[derive(Debug, Default, Clone)]
+struct OrgUserCount {
+    version: u32,
+    counts: HashMap<String, usize>,
+    users: Vec<String>,
+}
+
+impl Projection for OrgUserCount {
+    type Event = OrgEvent;
+    type Error = Infallible;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn apply(&mut self, event: &Envelope<Self::Event>) -> Result<(), Self::Error> {
+        let OrgEvent::UserAdded(user) = &event.payload;
+        self.users.push(user.clone());
+        // Version 2 fixes the key to be the org instead of the event type.
+        let key = if self.version >= 2 {
+            event.stream_id.clone()
+        } else {
+            event.event_type.clone()
+        };
+        *self.counts.entry(key).or_default() += 1;
+        Ok(())
+    }
+}
+
+fn user_added(org: &str, user: &str) -> Envelope<OrgEvent> {
+    Envelope::new(org, 0, "UserAdded", OrgEvent::UserAdded(user.to_string()))
+}
+
+#[test]
+fn test_projection_runner() {
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[user_added("org-1", "u1"), user_added("org-2", "u2")])
+        .unwrap();
+
+    let mut runner = ProjectionRunner::new(OrgUserCount {
+        version: 2,
+        ..Default::default()
+    });
+    assert_eq!(runner.run_batch(&store, 1).unwrap(), 1);
+    assert_eq!(runner.run_batch(&store, 10).unwrap(), 1);
+    assert_eq!(runner.run_batch(&store, 10).unwrap(), 0);
+    assert_eq!(runner.position(), 2);
+    assert_eq!(runner.projection().counts["org-2"], 1);
+    assert_eq!(runner.projection().users, vec!["u1", "u2"]);
+}
+
+#[test]
+fn test_blue_green_rebuild() {
+    let mut store = OnMemoryEventStore::new();
+    for i in 0..4 {
+        store
+            .save(&[user_added("org-1", &format!("u{}", i))])
+            .unwrap();
+    }
+
+    let mut manager = ProjectionManager::new(OrgUserCount {
+        version: 1,
+        ..Default::default()
+    });
+    assert!(!manager.run_batch(&store, 10).unwrap());
+    assert_eq!(manager.active().counts["UserAdded"], 4);
+
+    manager.deploy(OrgUserCount {
+        version: 2,
+        ..Default::default()
+    });
+    assert_eq!(manager.rebuilding(), Some(2));
+
+    store.save(&[user_added("org-1", "u4")]).unwrap();
+    // The rebuild is behind: version 1 keeps serving.
+    assert!(!manager.run_batch(&store, 2).unwrap());
+    assert_eq!(manager.active().version(), 1);
+    assert_eq!(manager.active().counts["UserAdded"], 5);
+
+    assert!(!manager.run_batch(&store, 2).unwrap());
+    assert!(manager.run_batch(&store, 2).unwrap());
+    assert_eq!(manager.active().version(), 2);
+    assert_eq!(manager.active().counts["org-1"], 5);
+    assert_eq!(manager.rebuilding(), None);
+}
+
+impl ComparableReadModel for OrgUserCount {
+    fn rows(&self) -> BTreeMap<String, String> {
+        self.counts
+            .iter()
+            .map(|(key, count)| (key.clone(), count.to_string()))
+            .collect()
+    }
+}
+
+#[test]
+fn test_shadow_projection() {
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[user_added("org-1", "u1"), user_added("org-2", "u2")])
+        .unwrap();
+
+    let current = OrgUserCount {
+        version: 2,
+        ..Default::default()
+    };
+    let mut shadow = ShadowProjection::new(
+        ProjectionRunner::new(current),
+        ProjectionRunner::new(OrgUserCount {
+            version: 2,
+            ..Default::default()
+        }),
+    );
+    assert_eq!(shadow.run_batch(&store, 10).unwrap(), 2);
+    let report = shadow.compare();
+    assert!(report.is_clean());
+    assert_eq!(report.matching, 2);
+
+    // A shadow keyed by event type, as version 1 was, disagrees with the current one.
+    let mut shadow = ShadowProjection::new(
+        ProjectionRunner::new(OrgUserCount {
+            version: 2,
+            ..Default::default()
+        }),
+        ProjectionRunner::new(OrgUserCount {
+            version: 1,
+            ..Default::default()
+        }),
+    );
+    shadow.run_batch(&store, 1).unwrap();
+    let report = shadow.compare();
+    assert_eq!(report.missing, ["org-1"]);
+    assert_eq!(report.unexpected, ["UserAdded"]);
+
+    store.save(&[user_added("org-1", "u3")]).unwrap();
+    shadow.run_batch(&store, 10).unwrap();
+    let report = shadow.compare();
+    assert!(report.caught_up);
+    assert_eq!(report.position, 3);
+    assert_eq!(report.missing, ["org-1", "org-2"]);
+    assert!(!report.is_clean());
+    assert_eq!(shadow.cut_over().projection().counts["UserAdded"], 3);
+}
+
+/// Projection whose events take a set time to apply.
+struct SlowProjection {
+    clock: ManualClock,
+    per_event: Duration,
+    applied: usize,
+}
+
+impl Projection for SlowProjection {
+    type Event = OrgEvent;
+    type Error = Infallible;
+
+    fn apply(&mut self, _event: &Envelope<Self::Event>) -> Result<(), Self::Error> {
+        self.clock.advance(self.per_event);
+        self.applied += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_adaptive_batching() {
+    let mut store = OnMemoryEventStore::new();
+    for i in 0..100 {
+        store
+            .save(&[user_added("org-1", &format!("u{}", i))])
+            .unwrap();
+    }
+    let clock = ManualClock::default();
+    let projection = SlowProjection {
+        clock: clock.clone(),
+        per_event: Duration::from_millis(1),
+        applied: 0,
+    };
+    let mut runner = AdaptiveProjectionRunner::new(
+        ProjectionRunner::new(projection),
+        clock,
+        AdaptiveBatching {
+            min: 4,
+            max: 32,
+            target_latency: Duration::from_millis(20),
+        },
+    );
+
+    // Behind: the batch size doubles while batches apply within the target latency.
+    let sizes = (0..4)
+        .map(|_| runner.run_batch(&store).unwrap())
+        .map(|m| (m.applied, m.lag, m.next_batch_size))
+        .collect::<Vec<_>>();
+    assert_eq!(sizes, [(4, 96, 8), (8, 88, 16), (16, 72, 32), (32, 40, 16)]);
+    assert_eq!(runner.metrics().apply_latency, Duration::from_millis(32));
+
+    // Caught up: back to the smallest batches.
+    let metrics = runner.run_batch(&store).unwrap();
+    assert_eq!((metrics.applied, metrics.lag), (16, 24));
+    runner.run_batch(&store).unwrap();
+    let metrics = runner.run_batch(&store).unwrap();
+    assert_eq!(metrics.lag, 0);
+    assert_eq!(runner.batch_size(), 4);
+    assert_eq!(runner.runner().projection().applied, 100);
+}
+
+#[test]
+fn test_adaptive_batching_with_inverted_bounds() {
+    let mut store = OnMemoryEventStore::new();
+    for i in 0..10 {
+        store
+            .save(&[user_added("org-1", &format!("u{}", i))])
+            .unwrap();
+    }
+    let clock = ManualClock::default();
+    let projection = SlowProjection {
+        clock: clock.clone(),
+        per_event: Duration::from_millis(1),
+        applied: 0,
+    };
+    let batching = AdaptiveBatching {
+        min: 8,
+        max: 2,
+        target_latency: Duration::from_millis(20),
+    };
+    assert_eq!(
+        (batching.normalized().min, batching.normalized().max),
+        (8, 8)
+    );
+    let mut runner =
+        AdaptiveProjectionRunner::new(ProjectionRunner::new(projection), clock, batching);
+    let metrics = runner.run_batch(&store).unwrap();
+    assert_eq!((metrics.applied, metrics.next_batch_size), (8, 8));
+    runner.run_batch(&store).unwrap();
+    assert_eq!(runner.runner().projection().applied, 10);
+}
+
+#[test]
+fn test_catch_up_progress() {
+    let mut store = OnMemoryEventStore::new();
+    for i in 0..100 {
+        store
+            .save(&[user_added("org-1", &format!("u{}", i))])
+            .unwrap();
+    }
+    let clock = ManualClock::default();
+    let projection = SlowProjection {
+        clock: clock.clone(),
+        per_event: Duration::from_millis(10),
+        applied: 0,
+    };
+    let mut runner = ProjectionRunner::new(projection);
+
+    let mut reports = Vec::new();
+    let progress = runner
+        .catch_up(&store, 40, &clock, &mut |p: &CatchUpProgress| {
+            reports.push(*p)
+        })
+        .unwrap();
+
+    assert_eq!(reports.len(), 3);
+    assert_eq!((reports[0].processed, reports[0].remaining()), (40, 60));
+    assert_eq!(reports[0].rate, 100.0);
+    assert_eq!(reports[0].eta, Some(Duration::from_millis(600)));
+    assert!(progress.is_caught_up());
+    assert_eq!((progress.processed, progress.position), (100, 100));
+    assert_eq!(progress.eta, Some(Duration::ZERO));
+
+    // Already caught up: a single empty batch is reported.
+    let progress = runner
+        .catch_up(&store, 40, &clock, &mut |_: &CatchUpProgress| {})
+        .unwrap();
+    assert_eq!(
+        (progress.processed, progress.rate, progress.eta),
+        (0, 0.0, None)
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct OrgFull(String);
+
+impl fmt::Display for OrgFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is full", self.0)
+    }
+}
+
+impl Error for OrgFull {}
+
+/// Read model of the users of each org, rejecting a third user in an org.
+#[derive(Debug, Default, Clone)]
+struct OrgMembers(BTreeMap<String, Vec<String>>);
+
+impl Projection for OrgMembers {
+    type Event = OrgEvent;
+    type Error = OrgFull;
+
+    fn apply(&mut self, event: &Envelope<Self::Event>) -> Result<(), Self::Error> {
+        let OrgEvent::UserAdded(user) = &event.payload;
+        let members = self.0.entry(event.stream_id.clone()).or_default();
+        if members.len() >= 2 {
+            return Err(OrgFull(event.stream_id.clone()));
+        }
+        members.push(user.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sync_projection_store() {
+    let mut store = SyncProjectionStore::new(OnMemoryEventStore::new(), OrgMembers::default());
+    store
+        .save(&[user_added("org-1", "u1"), user_added("org-2", "u2")])
+        .unwrap();
+    assert_eq!(store.projection().0["org-1"], ["u1"]);
+
+    // The read model rejecting an event rolls the whole append back.
+    assert_eq!(
+        store.save(&[user_added("org-1", "u3"), user_added("org-1", "u4")]),
+        Err(SyncProjectionError::Projection(OrgFull(
+            "org-1".to_string()
+        )))
+    );
+    assert_eq!(store.load(&"org-1".to_string()).unwrap().len(), 1);
+    assert_eq!(store.projection().0["org-1"], ["u1"]);
+
+    store.save(&[user_added("org-1", "u3")]).unwrap();
+    assert_eq!(store.inner().stream_len("org-1"), 2);
+    assert_eq!(store.projection().0["org-1"], ["u1", "u3"]);
+}
+
+#[test]
+fn test_sync_projection_joins_transaction() {
+    let inner = crate::event_store::require_transaction(OnMemoryEventStore::new());
+    let mut store = SyncProjectionStore::new(inner, OrgMembers::default());
+    store.save(&[user_added("org-1", "u1")]).unwrap();
+
+    store.begin().unwrap();
+    store.save(&[user_added("org-2", "u2")]).unwrap();
+    store
+        .append_multi(&[(
+            "org-3".to_string(),
+            ExpectedVersion::NoStream,
+            vec![user_added("org-3", "u3")],
+        )])
+        .unwrap();
+    // Readers only see the projection once the transaction commits.
+    assert!(!store.projection().0.contains_key("org-2"));
+    store.commit().unwrap();
+    assert_eq!(store.projection().0["org-2"], ["u2"]);
+    assert_eq!(store.projection().0["org-3"], ["u3"]);
+
+    store.begin().unwrap();
+    store.save(&[user_added("org-1", "u4")]).unwrap();
+    store.rollback().unwrap();
+    assert_eq!(store.projection().0["org-1"], ["u1"]);
+    store.save(&[user_added("org-1", "u5")]).unwrap();
+    assert_eq!(store.projection().0["org-1"], ["u1", "u5"]);
+    assert_eq!(store.load(&"org-1".to_string()).unwrap().len(), 2);
+
+    // A failed append rolls back the transaction it joined.
+    store.begin().unwrap();
+    store.save(&[user_added("org-2", "u6")]).unwrap();
+    assert!(store.save(&[user_added("org-1", "u7")]).is_err());
+    assert_eq!(store.load(&"org-2".to_string()).unwrap().len(), 1);
+    assert_eq!(store.projection().0["org-2"], ["u2"]);
+}
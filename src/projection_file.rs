@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::envelope::Envelope;
+use crate::event_store::ReadOnlyEventStore;
+use crate::projection::{Projection, ProjectionError, ProjectionRunner};
+use crate::serialization::Codec;
+
+const MAGIC: &str = "crux-projection";
+
+/// In-memory projection saved to a file together with its position, so that a restarted
+/// process resumes from the file instead of replaying the whole log.
+///
+/// The file is replaced atomically, and is ignored on start when it was written by another
+/// version of the projection, which is then rebuilt from the beginning.
+pub struct PersistedProjection<P, C> {
+    runner: ProjectionRunner<P>,
+    path: PathBuf,
+    codec: C,
+    every: u64,
+    saved_at: u64,
+}
+
+impl<P: Projection, C: Codec<P>> PersistedProjection<P, C> {
+    /// Restore the projection from the file if it exists, or start `initial` from the
+    /// beginning of the log. The file is saved every 1000 events by default.
+    pub fn open(
+        path: impl Into<PathBuf>,
+        codec: C,
+        initial: P,
+    ) -> Result<Self, ProjectionFileError> {
+        let path = path.into();
+        let runner = match read(&path, &codec, initial.version())? {
+            Some((position, projection)) => ProjectionRunner::starting_at(projection, position),
+            None => ProjectionRunner::new(initial),
+        };
+        Ok(Self {
+            saved_at: runner.position(),
+            runner,
+            path,
+            codec,
+            every: 1000,
+        })
+    }
+
+    /// Save the file once the number of events were applied since it was last saved.
+    pub fn save_every(mut self, events: u64) -> Self {
+        self.every = events.max(1);
+        self
+    }
+
+    /// Get the runner of the projection.
+    pub fn runner(&self) -> &ProjectionRunner<P> {
+        &self.runner
+    }
+
+    /// Get the projection.
+    pub fn projection(&self) -> &P {
+        self.runner.projection()
+    }
+
+    /// Apply the next batch of events, returning the number of events applied.
+    pub fn run_batch<S>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<usize, ProjectionError<S::Error, P::Error>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<P::Event>>,
+    {
+        self.runner.run_batch(store, batch_size)
+    }
+
+    /// Save the file if enough events were applied since it was last saved. Returns whether
+    /// it was saved.
+    pub fn checkpoint(&mut self) -> Result<bool, ProjectionFileError> {
+        if self.runner.position() < self.saved_at + self.every {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Save the file now, e.g. on shutdown.
+    pub fn save(&mut self) -> Result<(), ProjectionFileError> {
+        let position = self.runner.position();
+        let mut bytes = format!(
+            "{} {} {}\n",
+            MAGIC,
+            self.runner.projection().version(),
+            position
+        )
+        .into_bytes();
+        bytes.extend(
+            self.codec
+                .encode(self.runner.projection())
+                .map_err(ProjectionFileError::Codec)?,
+        );
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, bytes).map_err(ProjectionFileError::Io)?;
+        fs::rename(&temporary, &self.path).map_err(ProjectionFileError::Io)?;
+        self.saved_at = position;
+        Ok(())
+    }
+}
+
+fn read<P, C: Codec<P>>(
+    path: &Path,
+    codec: &C,
+    version: u32,
+) -> Result<Option<(u64, P)>, ProjectionFileError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ProjectionFileError::Io(e)),
+    };
+    let corrupt = || ProjectionFileError::Corrupt(path.to_path_buf());
+    let newline = bytes.iter().position(|&b| b == b'\n').ok_or_else(corrupt)?;
+    let header = std::str::from_utf8(&bytes[..newline]).map_err(|_| corrupt())?;
+    let (saved_version, position) = match header.split(' ').collect::<Vec<_>>()[..] {
+        [MAGIC, saved_version, position] => (saved_version, position),
+        _ => return Err(corrupt()),
+    };
+    if saved_version.parse::<u32>().map_err(|_| corrupt())? != version {
+        return Ok(None);
+    }
+    let position = position.parse().map_err(|_| corrupt())?;
+    let projection = codec
+        .decode(&bytes[newline + 1..])
+        .map_err(ProjectionFileError::Codec)?;
+    Ok(Some((position, projection)))
+}
+
+/// Error returned when reading or writing the file of a [`PersistedProjection`].
+#[derive(Debug)]
+pub enum ProjectionFileError {
+    /// The file could not be read or written.
+    Io(io::Error),
+    /// The projection could not be serialized or deserialized.
+    Codec(String),
+    /// The file is not a saved projection.
+    Corrupt(PathBuf),
+}
+
+impl fmt::Display for ProjectionFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectionFileError::Io(e) => write!(f, "{}", e),
+            ProjectionFileError::Codec(e) => write!(f, "codec error: {}", e),
+            ProjectionFileError::Corrupt(path) => {
+                write!(f, "not a saved projection: {}", path.display())
+            }
+        }
+    }
+}
+
+impl Error for ProjectionFileError {}
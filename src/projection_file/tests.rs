@@ -0,0 +1,93 @@
+use std::convert::Infallible;
+use std::env;
+use std::process;
+
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+/// Projection counting the events.
+#[derive(Debug, Default, PartialEq)]
+struct Counter {
+    version: u32,
+    count: u64,
+}
+
+impl Projection for Counter {
+    type Event = u32;
+    type Error = Infallible;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn apply(&mut self, _event: &Envelope<u32>) -> Result<(), Infallible> {
+        self.count += 1;
+        Ok(())
+    }
+}
+
+struct CounterCodec;
+
+impl Codec<Counter> for CounterCodec {
+    fn name(&self) -> &'static str {
+        "counter"
+    }
+
+    fn encode(&self, value: &Counter) -> Result<Vec<u8>, String> {
+        Ok(format!("{} {}", value.version, value.count).into_bytes())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Counter, String> {
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        let (version, count) = text.split_once(' ').ok_or("invalid counter")?;
+        Ok(Counter {
+            version: version.parse().map_err(|_| "invalid version")?,
+            count: count.parse().map_err(|_| "invalid count")?,
+        })
+    }
+}
+
+fn counter(version: u32) -> Counter {
+    Counter { version, count: 0 }
+}
+
+#[test]
+fn test_restore_after_restart() {
+    let path = env::temp_dir().join(format!("crux-projection-{}", process::id()));
+    let _ = fs::remove_file(&path);
+    let mut store = OnMemoryEventStore::new();
+    for i in 0..10 {
+        store
+            .save(&[Envelope::new("counter-1", 0, "Counted", i)])
+            .unwrap();
+    }
+
+    let mut projection = PersistedProjection::open(&path, CounterCodec, counter(1))
+        .unwrap()
+        .save_every(4);
+    projection.run_batch(&store, 3).unwrap();
+    assert!(!projection.checkpoint().unwrap());
+    projection.run_batch(&store, 3).unwrap();
+    assert!(projection.checkpoint().unwrap());
+    projection.run_batch(&store, 3).unwrap();
+    drop(projection);
+
+    // Restarted from the last checkpoint, not from the beginning.
+    let mut projection = PersistedProjection::open(&path, CounterCodec, counter(1)).unwrap();
+    assert_eq!(projection.runner().position(), 6);
+    assert_eq!(projection.projection().count, 6);
+    projection.run_batch(&store, 10).unwrap();
+    assert_eq!(projection.projection().count, 10);
+    projection.save().unwrap();
+
+    // A new version of the projection is rebuilt.
+    let projection = PersistedProjection::open(&path, CounterCodec, counter(2)).unwrap();
+    assert_eq!(projection.runner().position(), 0);
+
+    fs::write(&path, "garbage").unwrap();
+    assert!(matches!(
+        PersistedProjection::open(&path, CounterCodec, counter(1)),
+        Err(ProjectionFileError::Corrupt(_))
+    ));
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,307 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::event_store::{QueryHandler, ReadConsistency};
+
+/// Types which represent an asynchronous handler for a query.
+pub trait AsyncQueryHandler<Query> {
+    /// Associated Type representing the response type.
+    type Response;
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Handle the query.
+    fn handle(&self, query: Query) -> impl Future<Output = Result<Self::Response, Self::Error>>;
+}
+
+/// Types which represent an asynchronous sequence of rows, produced without buffering the
+/// whole result set.
+pub trait ResponseStream {
+    /// Associated Type representing a row of the response.
+    type Item;
+
+    /// Attempt to get the next row, returning `Poll::Ready(None)` once exhausted.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+
+    /// Get the next row.
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin + Sized,
+    {
+        Next(self)
+    }
+}
+
+/// Future returned by [`ResponseStream::next`].
+#[derive(Debug)]
+pub struct Next<'a, S>(&'a mut S);
+
+impl<S: ResponseStream + Unpin> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.0).poll_next(cx)
+    }
+}
+
+/// Stream yielding the items of an iterator.
+#[derive(Debug)]
+pub struct IterStream<I>(I);
+
+impl<I: Iterator> IterStream<I> {
+    /// Create a stream from the iterator.
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        Self(iter.into_iter())
+    }
+}
+
+impl<I: Iterator + Unpin> ResponseStream for IterStream<I> {
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.next())
+    }
+}
+
+/// Adapter exposing a synchronous [`QueryHandler`] as an [`AsyncQueryHandler`].
+#[derive(Debug)]
+pub struct Blocking<H>(pub H);
+
+impl<H, Query> AsyncQueryHandler<Query> for Blocking<H>
+where
+    H: QueryHandler<Query>,
+{
+    type Response = H::Response;
+    type Error = H::Error;
+
+    fn handle(&self, query: Query) -> impl Future<Output = Result<Self::Response, Self::Error>> {
+        std::future::ready(self.0.handle(query))
+    }
+}
+
+/// Query handler combining the responses of several handlers, such as an organization and its
+/// users, so that API layers do not duplicate cross-read-model composition.
+///
+/// The composite starts from a handler and is extended with [`join`](Self::join),
+/// [`join_many`](Self::join_many) and [`map`](Self::map).
+#[derive(Debug)]
+pub struct CompositeQueryHandler<H>(H);
+
+impl<H> CompositeQueryHandler<H> {
+    /// Start from the handler.
+    pub fn new(handler: H) -> Self {
+        Self(handler)
+    }
+
+    /// Query `right` with the query derived from the current response, and combine both
+    /// responses.
+    pub fn join<R, F, G>(
+        self,
+        right: R,
+        to_query: F,
+        combine: G,
+    ) -> CompositeQueryHandler<Join<H, R, F, G>> {
+        CompositeQueryHandler(Join {
+            left: self.0,
+            right,
+            to_query,
+            combine,
+        })
+    }
+
+    /// Query `right` with every query derived from the current response, and combine the
+    /// response with all the responses of `right`, in order.
+    pub fn join_many<R, F, G>(
+        self,
+        right: R,
+        to_queries: F,
+        combine: G,
+    ) -> CompositeQueryHandler<JoinMany<H, R, F, G>> {
+        CompositeQueryHandler(JoinMany {
+            left: self.0,
+            right,
+            to_queries,
+            combine,
+        })
+    }
+
+    /// Transform the response.
+    pub fn map<F>(self, f: F) -> CompositeQueryHandler<Map<H, F>> {
+        CompositeQueryHandler(Map { inner: self.0, f })
+    }
+}
+
+impl<H, Query> QueryHandler<Query> for CompositeQueryHandler<H>
+where
+    H: QueryHandler<Query>,
+{
+    type Response = H::Response;
+    type Error = H::Error;
+
+    fn handle(&self, query: Query) -> Result<Self::Response, Self::Error> {
+        self.0.handle(query)
+    }
+}
+
+/// Handler built by [`CompositeQueryHandler::join`].
+#[derive(Debug)]
+pub struct Join<L, R, F, G> {
+    left: L,
+    right: R,
+    to_query: F,
+    combine: G,
+}
+
+impl<L, R, F, G, Query, RightQuery, Response> QueryHandler<Query> for Join<L, R, F, G>
+where
+    L: QueryHandler<Query>,
+    R: QueryHandler<RightQuery>,
+    F: Fn(&L::Response) -> RightQuery,
+    G: Fn(L::Response, R::Response) -> Response,
+{
+    type Response = Response;
+    type Error = JoinError<L::Error, R::Error>;
+
+    fn handle(&self, query: Query) -> Result<Self::Response, Self::Error> {
+        let left = self.left.handle(query).map_err(JoinError::Left)?;
+        let right = self
+            .right
+            .handle((self.to_query)(&left))
+            .map_err(JoinError::Right)?;
+        Ok((self.combine)(left, right))
+    }
+}
+
+/// Handler built by [`CompositeQueryHandler::join_many`].
+#[derive(Debug)]
+pub struct JoinMany<L, R, F, G> {
+    left: L,
+    right: R,
+    to_queries: F,
+    combine: G,
+}
+
+impl<L, R, F, G, Query, RightQuery, Response> QueryHandler<Query> for JoinMany<L, R, F, G>
+where
+    L: QueryHandler<Query>,
+    R: QueryHandler<RightQuery>,
+    F: Fn(&L::Response) -> Vec<RightQuery>,
+    G: Fn(L::Response, Vec<R::Response>) -> Response,
+{
+    type Response = Response;
+    type Error = JoinError<L::Error, R::Error>;
+
+    fn handle(&self, query: Query) -> Result<Self::Response, Self::Error> {
+        let left = self.left.handle(query).map_err(JoinError::Left)?;
+        let right = (self.to_queries)(&left)
+            .into_iter()
+            .map(|query| self.right.handle(query))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(JoinError::Right)?;
+        Ok((self.combine)(left, right))
+    }
+}
+
+/// Handler built by [`CompositeQueryHandler::map`].
+#[derive(Debug)]
+pub struct Map<H, F> {
+    inner: H,
+    f: F,
+}
+
+impl<H, F, Query, Response> QueryHandler<Query> for Map<H, F>
+where
+    H: QueryHandler<Query>,
+    F: Fn(H::Response) -> Response,
+{
+    type Response = Response;
+    type Error = H::Error;
+
+    fn handle(&self, query: Query) -> Result<Self::Response, Self::Error> {
+        self.inner.handle(query).map(&self.f)
+    }
+}
+
+/// A query with the freshness its response must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Consistent<Q> {
+    /// The query.
+    pub query: Q,
+    /// The freshness of the response.
+    pub consistency: ReadConsistency,
+}
+
+impl<Q> Consistent<Q> {
+    /// Require the response to reflect every event up to the fencing position of the caller.
+    pub fn strong(query: Q) -> Self {
+        Self {
+            query,
+            consistency: ReadConsistency::Strong,
+        }
+    }
+
+    /// Accept a response missing recent events.
+    pub fn eventual(query: Q) -> Self {
+        Self {
+            query,
+            consistency: ReadConsistency::Eventual,
+        }
+    }
+}
+
+/// Query handler serving strong queries with a handler reading the primary, and eventual
+/// queries with one reading a replica, such as a read model and its copy.
+#[derive(Debug)]
+pub struct ConsistencyRouter<P, R> {
+    primary: P,
+    replica: R,
+}
+
+impl<P, R> ConsistencyRouter<P, R> {
+    /// Route the queries to the handlers.
+    pub fn new(primary: P, replica: R) -> Self {
+        Self { primary, replica }
+    }
+}
+
+impl<P, R, Query> QueryHandler<Consistent<Query>> for ConsistencyRouter<P, R>
+where
+    P: QueryHandler<Query>,
+    R: QueryHandler<Query, Response = P::Response, Error = P::Error>,
+{
+    type Response = P::Response;
+    type Error = P::Error;
+
+    fn handle(&self, query: Consistent<Query>) -> Result<Self::Response, Self::Error> {
+        match query.consistency {
+            ReadConsistency::Strong => self.primary.handle(query.query),
+            ReadConsistency::Eventual => self.replica.handle(query.query),
+        }
+    }
+}
+
+/// Error returned by joined query handlers.
+#[derive(Debug, PartialEq, Eq)]
+pub enum JoinError<LE, RE> {
+    /// The handler on the left of the join failed.
+    Left(LE),
+    /// The handler on the right of the join failed.
+    Right(RE),
+}
+
+impl<LE: Error, RE: Error> fmt::Display for JoinError<LE, RE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinError::Left(e) => write!(f, "left query error: {}", e),
+            JoinError::Right(e) => write!(f, "right query error: {}", e),
+        }
+    }
+}
+
+impl<LE: Error, RE: Error> Error for JoinError<LE, RE> {}
@@ -0,0 +1,190 @@
+use std::convert::Infallible;
+use std::task::Waker;
+
+use super::*;
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+struct UserList {
+    users: Vec<String>,
+}
+
+struct ListUsers {
+    prefix: String,
+}
+
+impl AsyncQueryHandler<ListUsers> for UserList {
+    type Response = IterStream<std::vec::IntoIter<String>>;
+    type Error = Infallible;
+
+    async fn handle(&self, query: ListUsers) -> Result<Self::Response, Self::Error> {
+        let users = self
+            .users
+            .iter()
+            .filter(|u| u.starts_with(&query.prefix))
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok(IterStream::new(users))
+    }
+}
+
+struct CountUsers;
+
+struct UserCounter(usize);
+
+impl QueryHandler<CountUsers> for UserCounter {
+    type Response = usize;
+    type Error = Infallible;
+
+    fn handle(&self, _query: CountUsers) -> Result<Self::Response, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+#[test]
+fn test_streaming_query() {
+    let list = UserList {
+        users: vec!["alice".to_string(), "bob".to_string(), "anna".to_string()],
+    };
+    let query = ListUsers {
+        prefix: "a".to_string(),
+    };
+    let rows = block_on(async {
+        let mut stream = list.handle(query).await.unwrap();
+        let mut rows = Vec::new();
+        while let Some(row) = stream.next().await {
+            rows.push(row);
+        }
+        rows
+    });
+    assert_eq!(rows, vec!["alice", "anna"]);
+}
+
+#[test]
+fn test_blocking_adapter() {
+    let handler = Blocking(UserCounter(1));
+    assert_eq!(block_on(handler.handle(CountUsers)).unwrap(), 1);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OrgView {
+    name: String,
+    user_ids: Vec<u32>,
+}
+
+struct GetOrg;
+
+struct OrgReadModel(OrgView);
+
+impl QueryHandler<GetOrg> for OrgReadModel {
+    type Response = OrgView;
+    type Error = Infallible;
+
+    fn handle(&self, _query: GetOrg) -> Result<Self::Response, Self::Error> {
+        Ok(self.0.clone())
+    }
+}
+
+struct GetUser(u32);
+
+#[derive(Debug, PartialEq)]
+struct UserNotFound(u32);
+
+impl fmt::Display for UserNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "user not found: {}", self.0)
+    }
+}
+
+impl Error for UserNotFound {}
+
+struct UserReadModel(Vec<(u32, String)>);
+
+impl QueryHandler<GetUser> for UserReadModel {
+    type Response = String;
+    type Error = UserNotFound;
+
+    fn handle(&self, query: GetUser) -> Result<Self::Response, Self::Error> {
+        self.0
+            .iter()
+            .find(|(id, _)| *id == query.0)
+            .map(|(_, name)| name.clone())
+            .ok_or(UserNotFound(query.0))
+    }
+}
+
+fn org(user_ids: Vec<u32>) -> OrgReadModel {
+    OrgReadModel(OrgView {
+        name: "Acme".to_string(),
+        user_ids,
+    })
+}
+
+fn users() -> UserReadModel {
+    UserReadModel(vec![(1, "alice".to_string()), (2, "bob".to_string())])
+}
+
+#[test]
+fn test_composite_join_many() {
+    let handler = CompositeQueryHandler::new(org(vec![2, 1]))
+        .join_many(
+            users(),
+            |org: &OrgView| org.user_ids.iter().map(|id| GetUser(*id)).collect(),
+            |org: OrgView, users: Vec<String>| (org.name, users),
+        )
+        .map(|(org, users): (String, Vec<String>)| format!("{}: {}", org, users.join(", ")));
+    assert_eq!(handler.handle(GetOrg).unwrap(), "Acme: bob, alice");
+
+    let handler = CompositeQueryHandler::new(org(vec![1, 3])).join_many(
+        users(),
+        |org: &OrgView| org.user_ids.iter().map(|id| GetUser(*id)).collect(),
+        |_: OrgView, users: Vec<String>| users,
+    );
+    assert_eq!(
+        handler.handle(GetOrg),
+        Err(JoinError::Right(UserNotFound(3)))
+    );
+}
+
+#[test]
+fn test_composite_join_chain() {
+    let handler = CompositeQueryHandler::new(org(vec![1]))
+        .join(
+            users(),
+            |org: &OrgView| GetUser(org.user_ids[0]),
+            |org: OrgView, owner: String| (org, owner),
+        )
+        .join(
+            UserCounter(2),
+            |_: &(OrgView, String)| CountUsers,
+            |(org, owner): (OrgView, String), count: usize| {
+                format!("{} owned by {} of {}", org.name, owner, count)
+            },
+        );
+    assert_eq!(handler.handle(GetOrg).unwrap(), "Acme owned by alice of 2");
+}
+
+#[test]
+fn test_consistency_router() {
+    // The replica of the read model has not seen the second user yet.
+    let router = ConsistencyRouter::new(org(vec![1, 2]), org(vec![1]));
+    assert_eq!(
+        router.handle(Consistent::strong(GetOrg)).unwrap().user_ids,
+        [1, 2]
+    );
+    assert_eq!(
+        router
+            .handle(Consistent::eventual(GetOrg))
+            .unwrap()
+            .user_ids,
+        [1]
+    );
+}
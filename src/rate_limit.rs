@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::Clock;
+use crate::command_bus::CommandBus;
+
+/// Types which tell the keys a command is rate limited by.
+pub trait RateLimitKeys {
+    /// Get the ID of the aggregate targeted by the command.
+    fn aggregate_key(&self) -> Option<String> {
+        None
+    }
+    /// Get the principal issuing the command.
+    fn principal_key(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Rate allowed for a key: bursts of up to `capacity` commands, refilled by one every
+/// `refill_every`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Maximum number of commands in a burst.
+    pub capacity: u32,
+    /// Time to regain one command.
+    pub refill_every: Duration,
+}
+
+/// Token bucket tracking the commands allowed for a key.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    limit: RateLimit,
+    tokens: u32,
+    refilled_at: SystemTime,
+}
+
+impl TokenBucket {
+    /// Create a full bucket.
+    pub fn new(limit: RateLimit, now: SystemTime) -> Self {
+        Self {
+            limit,
+            tokens: limit.capacity,
+            refilled_at: now,
+        }
+    }
+
+    /// Take a token. On failure, returns the time until a token is available.
+    pub fn try_acquire(&mut self, now: SystemTime) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens > 0 {
+            self.tokens -= 1;
+            return Ok(());
+        }
+        let elapsed = now.duration_since(self.refilled_at).unwrap_or_default();
+        Err(self.limit.refill_every.saturating_sub(elapsed))
+    }
+
+    fn refill(&mut self, now: SystemTime) {
+        if self.limit.refill_every.is_zero() {
+            self.tokens = self.limit.capacity;
+            return;
+        }
+        let elapsed = now.duration_since(self.refilled_at).unwrap_or_default();
+        let refills = u32::try_from(elapsed.as_nanos() / self.limit.refill_every.as_nanos())
+            .unwrap_or(u32::MAX);
+        if refills == 0 {
+            return;
+        }
+        self.tokens = self.tokens.saturating_add(refills).min(self.limit.capacity);
+        self.refilled_at = if self.tokens == self.limit.capacity {
+            now
+        } else {
+            self.refilled_at + self.limit.refill_every * refills
+        };
+    }
+
+    /// Check whether the bucket is refilled to capacity, so that it behaves like a new one.
+    fn is_full(&mut self, now: SystemTime) -> bool {
+        self.refill(now);
+        self.tokens >= self.limit.capacity
+    }
+}
+
+/// Number of buckets below which idle ones are not evicted.
+const MIN_EVICTION_BUCKETS: usize = 64;
+
+/// Command bus middleware rejecting commands exceeding the per-aggregate and per-principal
+/// rate limits.
+///
+/// Buckets refilled to capacity are evicted whenever their number doubles, so that keys which
+/// stopped issuing commands do not accumulate.
+pub struct RateLimitedBus<B, K> {
+    bus: B,
+    clock: K,
+    per_aggregate: Option<RateLimit>,
+    per_principal: Option<RateLimit>,
+    buckets: HashMap<String, TokenBucket>,
+    evict_at: usize,
+}
+
+impl<B, K: Clock> RateLimitedBus<B, K> {
+    /// Wrap the bus, without limits.
+    pub fn new(bus: B, clock: K) -> Self {
+        Self {
+            bus,
+            clock,
+            per_aggregate: None,
+            per_principal: None,
+            buckets: HashMap::new(),
+            evict_at: MIN_EVICTION_BUCKETS,
+        }
+    }
+
+    /// Limit the commands targeting a single aggregate.
+    pub fn per_aggregate(mut self, limit: RateLimit) -> Self {
+        self.per_aggregate = Some(limit);
+        self
+    }
+
+    /// Limit the commands issued by a single principal.
+    pub fn per_principal(mut self, limit: RateLimit) -> Self {
+        self.per_principal = Some(limit);
+        self
+    }
+
+    /// Get the wrapped bus.
+    pub fn inner(&self) -> &B {
+        &self.bus
+    }
+
+    /// Get the number of keys whose bucket is tracked.
+    pub fn tracked_keys(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn acquire(&mut self, keys: &[(String, RateLimit)]) -> Result<(), (String, Duration)> {
+        let now = self.clock.now();
+        // Check every bucket before taking tokens, so that a rejected command consumes none.
+        for (key, limit) in keys {
+            let mut bucket = self
+                .buckets
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| TokenBucket::new(*limit, now));
+            bucket
+                .try_acquire(now)
+                .map_err(|retry_after| (key.clone(), retry_after))?;
+        }
+        for (key, limit) in keys {
+            let bucket = self
+                .buckets
+                .entry(key.clone())
+                .or_insert_with(|| TokenBucket::new(*limit, now));
+            let _ = bucket.try_acquire(now);
+        }
+        self.evict_idle(now);
+        Ok(())
+    }
+
+    fn evict_idle(&mut self, now: SystemTime) {
+        if self.buckets.len() < self.evict_at {
+            return;
+        }
+        self.buckets.retain(|_, bucket| !bucket.is_full(now));
+        self.evict_at = (self.buckets.len() * 2).max(MIN_EVICTION_BUCKETS);
+    }
+}
+
+impl<B, K, C> CommandBus<C> for RateLimitedBus<B, K>
+where
+    B: CommandBus<C>,
+    K: Clock,
+    C: RateLimitKeys,
+{
+    type Response = B::Response;
+    type Error = RateLimitError<B::Error>;
+
+    fn dispatch(&mut self, command: C) -> Result<Self::Response, Self::Error> {
+        let mut keys = Vec::new();
+        if let (Some(limit), Some(key)) = (self.per_aggregate, command.aggregate_key()) {
+            keys.push((format!("aggregate:{}", key), limit));
+        }
+        if let (Some(limit), Some(key)) = (self.per_principal, command.principal_key()) {
+            keys.push((format!("principal:{}", key), limit));
+        }
+        self.acquire(&keys)
+            .map_err(|(key, retry_after)| RateLimitError::RateLimited { key, retry_after })?;
+        self.bus.dispatch(command).map_err(RateLimitError::Bus)
+    }
+}
+
+/// Error returned by the [`RateLimitedBus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitError<E> {
+    /// The command exceeds the rate limit of the key.
+    RateLimited { key: String, retry_after: Duration },
+    /// The wrapped bus failed.
+    Bus(E),
+}
+
+impl<E: Error> fmt::Display for RateLimitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::RateLimited { key, retry_after } => {
+                write!(f, "rate limited on {}, retry after {:?}", key, retry_after)
+            }
+            RateLimitError::Bus(e) => write!(f, "bus error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for RateLimitError<E> {}
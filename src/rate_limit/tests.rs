@@ -0,0 +1,136 @@
+use std::convert::Infallible;
+
+use super::*;
+use crate::clock::ManualClock;
+
+struct RenameOrg {
+    org_id: String,
+    user: String,
+}
+
+impl RateLimitKeys for RenameOrg {
+    fn aggregate_key(&self) -> Option<String> {
+        Some(self.org_id.clone())
+    }
+
+    fn principal_key(&self) -> Option<String> {
+        Some(self.user.clone())
+    }
+}
+
+#[derive(Default)]
+struct CountingBus(usize);
+
+impl CommandBus<RenameOrg> for CountingBus {
+    type Response = ();
+    type Error = Infallible;
+
+    fn dispatch(&mut self, _command: RenameOrg) -> Result<Self::Response, Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+fn rename(org_id: &str, user: &str) -> RenameOrg {
+    RenameOrg {
+        org_id: org_id.to_string(),
+        user: user.to_string(),
+    }
+}
+
+#[test]
+fn test_token_bucket() {
+    let now = SystemTime::UNIX_EPOCH;
+    let second = Duration::from_secs(1);
+    let mut bucket = TokenBucket::new(
+        RateLimit {
+            capacity: 2,
+            refill_every: second,
+        },
+        now,
+    );
+    assert_eq!(bucket.try_acquire(now), Ok(()));
+    assert_eq!(bucket.try_acquire(now), Ok(()));
+    assert_eq!(bucket.try_acquire(now + second / 4), Err(second * 3 / 4));
+    assert_eq!(bucket.try_acquire(now + second), Ok(()));
+    assert!(bucket.try_acquire(now + second).is_err());
+}
+
+#[test]
+fn test_rate_limited_bus() {
+    let clock = ManualClock::default();
+    let mut bus = RateLimitedBus::new(CountingBus::default(), clock.clone())
+        .per_aggregate(RateLimit {
+            capacity: 2,
+            refill_every: Duration::from_secs(10),
+        })
+        .per_principal(RateLimit {
+            capacity: 3,
+            refill_every: Duration::from_secs(10),
+        });
+
+    bus.dispatch(rename("org-1", "alice")).unwrap();
+    bus.dispatch(rename("org-1", "alice")).unwrap();
+    assert_eq!(
+        bus.dispatch(rename("org-1", "bob")),
+        Err(RateLimitError::RateLimited {
+            key: "aggregate:org-1".to_string(),
+            retry_after: Duration::from_secs(10),
+        })
+    );
+
+    // The rejected command did not consume bob's tokens, alice has one left.
+    bus.dispatch(rename("org-2", "alice")).unwrap();
+    assert!(matches!(
+        bus.dispatch(rename("org-3", "alice")),
+        Err(RateLimitError::RateLimited { key, .. }) if key == "principal:alice"
+    ));
+    bus.dispatch(rename("org-3", "bob")).unwrap();
+
+    clock.advance(Duration::from_secs(10));
+    bus.dispatch(rename("org-1", "alice")).unwrap();
+    assert_eq!(bus.inner().0, 5);
+}
+
+#[test]
+fn test_token_bucket_after_long_idle() {
+    let now = SystemTime::UNIX_EPOCH;
+    let mut bucket = TokenBucket::new(
+        RateLimit {
+            capacity: 2,
+            refill_every: Duration::from_nanos(1),
+        },
+        now,
+    );
+    bucket.try_acquire(now).unwrap();
+    bucket.try_acquire(now).unwrap();
+    // More refills than a u32 holds.
+    let later = now + Duration::from_secs(10);
+    assert_eq!(bucket.try_acquire(later), Ok(()));
+    assert_eq!(bucket.try_acquire(later), Ok(()));
+    assert!(bucket.try_acquire(later).is_err());
+}
+
+#[test]
+fn test_evict_idle_buckets() {
+    let clock = ManualClock::default();
+    let mut bus =
+        RateLimitedBus::new(CountingBus::default(), clock.clone()).per_aggregate(RateLimit {
+            capacity: 1,
+            refill_every: Duration::from_secs(10),
+        });
+    for org in 0..63 {
+        bus.dispatch(rename(&format!("org-{}", org), "alice"))
+            .unwrap();
+    }
+    assert_eq!(bus.tracked_keys(), 63);
+
+    // Once refilled, the buckets are evicted, except the one just used.
+    clock.advance(Duration::from_secs(10));
+    bus.dispatch(rename("org-63", "alice")).unwrap();
+    assert_eq!(bus.tracked_keys(), 1);
+    assert!(matches!(
+        bus.dispatch(rename("org-63", "alice")),
+        Err(RateLimitError::RateLimited { .. })
+    ));
+}
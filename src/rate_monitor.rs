@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::Clock;
+use crate::envelope::Envelope;
+use crate::interceptor::Interceptor;
+use crate::sharding::category_of;
+
+/// What a rate is measured over.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RateSubject {
+    /// Every stream of the category.
+    Category(String),
+    /// A single stream.
+    Stream(String),
+}
+
+/// Alert raised when the rate of events of a subject crosses its threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateAlert {
+    /// The subject whose rate crossed the threshold.
+    pub subject: RateSubject,
+    /// Events per second over the window of the monitor.
+    pub rate: f64,
+    /// The crossed threshold, in events per second.
+    pub threshold: f64,
+    /// When the threshold was crossed.
+    pub at: SystemTime,
+}
+
+/// Types which are told the alerts of a [`RateMonitor`], such as pagers and logs.
+pub trait AlertSink {
+    /// Called once when the rate of the subject crosses its threshold.
+    fn on_alert(&self, alert: &RateAlert);
+    /// Called once when the rate of an alerted subject is back under its threshold.
+    fn on_recovered(&self, _subject: &RateSubject) {}
+}
+
+impl<F: Fn(&RateAlert)> AlertSink for F {
+    fn on_alert(&self, alert: &RateAlert) {
+        self(alert)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rates {
+    appended: HashMap<RateSubject, VecDeque<SystemTime>>,
+    alerted: HashSet<RateSubject>,
+}
+
+/// Monitor of the rate of events per category and per stream, alerting when a threshold is
+/// crossed, to catch runaway loops such as a saga retrying forever.
+///
+/// Rates are measured over a sliding window. Events are recorded explicitly, or by installing
+/// the monitor as an [`Interceptor`] of the store.
+pub struct RateMonitor<K, A> {
+    clock: K,
+    sink: A,
+    window: Duration,
+    categories: HashMap<String, f64>,
+    streams: Option<f64>,
+    rates: Mutex<Rates>,
+}
+
+impl<K: Clock, A: AlertSink> RateMonitor<K, A> {
+    /// Create a monitor measuring rates over the window, without thresholds.
+    pub fn new(clock: K, window: Duration, sink: A) -> Self {
+        Self {
+            clock,
+            sink,
+            window,
+            categories: HashMap::new(),
+            streams: None,
+            rates: Mutex::new(Rates::default()),
+        }
+    }
+
+    /// Alert when the events of the category exceed the rate, in events per second.
+    pub fn per_category(mut self, category: impl Into<String>, max_per_second: f64) -> Self {
+        self.categories.insert(category.into(), max_per_second);
+        self
+    }
+
+    /// Alert when the events of any single stream exceed the rate, in events per second.
+    pub fn per_stream(mut self, max_per_second: f64) -> Self {
+        self.streams = Some(max_per_second);
+        self
+    }
+
+    /// Record events appended to the stream, checking the thresholds of its category and of
+    /// the stream.
+    pub fn record(&self, stream_id: &str, count: usize) {
+        let now = self.clock.now();
+        let mut rates = self.rates.lock().unwrap();
+        let category = category_of(stream_id);
+        if let Some(&threshold) = self.categories.get(category) {
+            let subject = RateSubject::Category(category.to_string());
+            self.check(&mut rates, subject, threshold, count, now);
+        }
+        if let Some(threshold) = self.streams {
+            let subject = RateSubject::Stream(stream_id.to_string());
+            self.check(&mut rates, subject, threshold, count, now);
+        }
+    }
+
+    /// Get the current rate of the subject, in events per second. Subjects without a
+    /// threshold are not measured.
+    pub fn rate(&self, subject: &RateSubject) -> f64 {
+        let now = self.clock.now();
+        let mut rates = self.rates.lock().unwrap();
+        match rates.appended.get_mut(subject) {
+            Some(times) => {
+                self.expire(times, now);
+                times.len() as f64 / self.window.as_secs_f64()
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Get the subjects currently over their threshold, sorted.
+    pub fn alerted(&self) -> Vec<RateSubject> {
+        let mut alerted = self
+            .rates
+            .lock()
+            .unwrap()
+            .alerted
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        alerted.sort();
+        alerted
+    }
+
+    /// Tell the recovery of the alerted subjects which received no events since their rate
+    /// fell under the threshold. Call it periodically, as a stopped loop records nothing.
+    pub fn tick(&self) {
+        let now = self.clock.now();
+        let mut rates = self.rates.lock().unwrap();
+        let alerted = rates.alerted.iter().cloned().collect::<Vec<_>>();
+        for subject in alerted {
+            self.check(
+                &mut rates,
+                subject.clone(),
+                self.threshold(&subject),
+                0,
+                now,
+            );
+        }
+    }
+
+    fn threshold(&self, subject: &RateSubject) -> f64 {
+        match subject {
+            RateSubject::Category(category) => self.categories[category],
+            RateSubject::Stream(_) => self.streams.unwrap_or(f64::INFINITY),
+        }
+    }
+
+    fn check(
+        &self,
+        rates: &mut Rates,
+        subject: RateSubject,
+        threshold: f64,
+        count: usize,
+        now: SystemTime,
+    ) {
+        let times = rates.appended.entry(subject.clone()).or_default();
+        self.expire(times, now);
+        times.extend(std::iter::repeat_n(now, count));
+        let rate = times.len() as f64 / self.window.as_secs_f64();
+        if rate > threshold {
+            if rates.alerted.insert(subject.clone()) {
+                self.sink.on_alert(&RateAlert {
+                    subject,
+                    rate,
+                    threshold,
+                    at: now,
+                });
+            }
+        } else if rates.alerted.remove(&subject) {
+            self.sink.on_recovered(&subject);
+        }
+    }
+
+    fn expire(&self, times: &mut VecDeque<SystemTime>, now: SystemTime) {
+        while times.front().is_some_and(|at| *at + self.window <= now) {
+            times.pop_front();
+        }
+    }
+}
+
+impl<K: Clock, A: AlertSink, E> Interceptor<Envelope<E>> for RateMonitor<K, A> {
+    fn after_append(&self, events: &[Envelope<E>]) {
+        let mut counts = Vec::<(&str, usize)>::new();
+        for event in events {
+            match counts.iter_mut().find(|(s, _)| *s == event.stream_id) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&event.stream_id, 1)),
+            }
+        }
+        for (stream_id, count) in counts {
+            self.record(stream_id, count);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+use crate::interceptor::InterceptedEventStore;
+
+#[derive(Clone, Default)]
+struct Pager {
+    alerts: Rc<RefCell<Vec<RateAlert>>>,
+    recovered: Rc<RefCell<Vec<RateSubject>>>,
+}
+
+impl AlertSink for Pager {
+    fn on_alert(&self, alert: &RateAlert) {
+        self.alerts.borrow_mut().push(alert.clone());
+    }
+
+    fn on_recovered(&self, subject: &RateSubject) {
+        self.recovered.borrow_mut().push(subject.clone());
+    }
+}
+
+#[test]
+fn test_alert_on_runaway_stream() {
+    let clock = ManualClock::default();
+    let pager = Pager::default();
+    let monitor = RateMonitor::new(clock.clone(), Duration::from_secs(10), pager.clone())
+        .per_category("payment", 2.0)
+        .per_stream(1.0);
+
+    monitor.record("payment-1", 5);
+    monitor.record("payment-2", 5);
+    assert!(pager.alerts.borrow().is_empty());
+    assert_eq!(monitor.rate(&RateSubject::Stream("payment-1".into())), 0.5);
+
+    // A saga retrying in a loop.
+    for _ in 0..11 {
+        clock.advance(Duration::from_millis(100));
+        monitor.record("payment-3", 1);
+    }
+    assert_eq!(
+        pager.alerts.borrow()[1],
+        RateAlert {
+            subject: RateSubject::Stream("payment-3".into()),
+            rate: 1.1,
+            threshold: 1.0,
+            at: SystemTime::UNIX_EPOCH + Duration::from_secs(1) + Duration::from_millis(100),
+        }
+    );
+    assert_eq!(pager.alerts.borrow().len(), 2);
+    assert_eq!(
+        pager.alerts.borrow()[0].subject,
+        RateSubject::Category("payment".into())
+    );
+    assert_eq!(
+        monitor.alerted(),
+        [
+            RateSubject::Category("payment".into()),
+            RateSubject::Stream("payment-3".into())
+        ]
+    );
+    // Streams of categories without a threshold are measured per stream only.
+    monitor.record("order-1", 3);
+    assert_eq!(monitor.rate(&RateSubject::Category("order".into())), 0.0);
+
+    clock.advance(Duration::from_secs(10));
+    monitor.tick();
+    assert!(monitor.alerted().is_empty());
+    assert_eq!(pager.recovered.borrow().len(), 2);
+}
+
+#[test]
+fn test_monitor_as_interceptor() {
+    let clock = ManualClock::default();
+    let alerts = Rc::new(RefCell::new(Vec::new()));
+    let sink = {
+        let alerts = alerts.clone();
+        move |alert: &RateAlert| alerts.borrow_mut().push(alert.subject.clone())
+    };
+    let monitor = RateMonitor::new(clock, Duration::from_secs(1), sink).per_category("org", 2.0);
+    let mut store = InterceptedEventStore::new(OnMemoryEventStore::new()).with(monitor);
+
+    let event = |stream_id: &str| Envelope::new(stream_id, 0, "UserAdded", ());
+    store.save(&[event("org-1"), event("org-2")]).unwrap();
+    assert!(alerts.borrow().is_empty());
+    store.save(&[event("org-1"), event("user-1")]).unwrap();
+    assert_eq!(*alerts.borrow(), [RateSubject::Category("org".into())]);
+}
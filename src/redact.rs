@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::interceptor::Interceptor;
+
+/// Placeholder rendered instead of a sensitive value.
+pub const REDACTED: &str = "[redacted]";
+
+/// Types which render themselves for logs and the inspector with their sensitive fields
+/// masked, while their stored form stays complete.
+///
+/// Types without sensitive fields can implement it with no method, falling back to `Debug`.
+pub trait Redact {
+    /// Render the value with its sensitive fields replaced by [`REDACTED`].
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    where
+        Self: fmt::Debug,
+    {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Wrapper rendering the value with [`Redact::fmt_redacted`], through both `Debug` and
+/// `Display`.
+pub struct Redacted<'a, T: ?Sized>(pub &'a T);
+
+impl<T: Redact + fmt::Debug + ?Sized> fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_redacted(f)
+    }
+}
+
+impl<T: Redact + fmt::Debug + ?Sized> fmt::Display for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_redacted(f)
+    }
+}
+
+/// Interceptor logging the appended events with their payloads redacted, one line per event.
+pub struct LoggingInterceptor<F> {
+    sink: F,
+    redacted_metadata: Vec<String>,
+}
+
+impl<F: Fn(&str)> LoggingInterceptor<F> {
+    /// Log the lines to the sink, such as a logger or standard error.
+    pub fn new(sink: F) -> Self {
+        Self {
+            sink,
+            redacted_metadata: Vec::new(),
+        }
+    }
+
+    /// Mask the value of the metadata key too.
+    pub fn redact_metadata(mut self, key: impl Into<String>) -> Self {
+        self.redacted_metadata.push(key.into());
+        self
+    }
+}
+
+impl<F, E> Interceptor<Envelope<E>> for LoggingInterceptor<F>
+where
+    F: Fn(&str),
+    E: Redact + fmt::Debug,
+{
+    fn after_append(&self, events: &[Envelope<E>]) {
+        for event in events {
+            let mut line = format!(
+                "appended {} {} {:?}",
+                event.stream_id,
+                event.event_type,
+                Redacted(&event.payload)
+            );
+            for (key, value) in &event.metadata {
+                let value = if self.redacted_metadata.contains(key) {
+                    REDACTED
+                } else {
+                    value
+                };
+                line.push_str(&format!(" {}={}", key, value));
+            }
+            (self.sink)(&line);
+        }
+    }
+}
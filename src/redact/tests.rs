@@ -0,0 +1,76 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::*;
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore};
+use crate::inspect::{dump_redacted, DumpOptions};
+use crate::interceptor::InterceptedEventStore;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+enum UserEvent {
+    Registered { name: String, email: String },
+    Deleted,
+}
+
+impl Redact for UserEvent {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserEvent::Registered { name, .. } => f
+                .debug_struct("Registered")
+                .field("name", name)
+                .field("email", &format_args!("{}", REDACTED))
+                .finish(),
+            other => fmt::Debug::fmt(other, f),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct Ping(u32);
+
+impl Redact for Ping {}
+
+fn registered() -> Envelope<UserEvent> {
+    let payload = UserEvent::Registered {
+        name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+    };
+    Envelope::new("user-1", 0, "Registered", payload)
+        .with_metadata("ip", "10.0.0.1")
+        .with_metadata("user_id", "admin")
+}
+
+#[test]
+fn test_redacted_rendering() {
+    let event = registered();
+    assert_eq!(
+        format!("{}", Redacted(&event.payload)),
+        "Registered { name: \"Ada\", email: [redacted] }"
+    );
+    assert_eq!(format!("{:?}", Redacted(&UserEvent::Deleted)), "Deleted");
+    assert_eq!(format!("{:?}", Redacted(&Ping(3))), "Ping(3)");
+
+    let output = dump_redacted(&[event], &DumpOptions::default());
+    assert!(output.ends_with("Registered { name: \"Ada\", email: [redacted] }\n"));
+}
+
+#[test]
+fn test_logging_interceptor() {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let sink = lines.clone();
+    let logging =
+        LoggingInterceptor::new(move |line: &str| sink.borrow_mut().push(line.to_string()))
+            .redact_metadata("ip");
+    let mut store = InterceptedEventStore::new(OnMemoryEventStore::new()).with(logging);
+    store.save(&[registered()]).unwrap();
+
+    assert_eq!(
+        *lines.borrow(),
+        ["appended user-1 Registered Registered { name: \"Ada\", email: [redacted] } ip=[redacted] user_id=admin"]
+    );
+    // The stored payload stays complete.
+    let stored = store.inner().load(&"user-1".to_string()).unwrap();
+    assert!(format!("{:?}", stored[0].payload).contains("ada@example.com"));
+}
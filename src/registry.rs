@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::json;
+
+/// Types which represent an event type that can be registered to the registry.
+pub trait RegisteredEvent {
+    /// Get the name of the event type.
+    fn event_type() -> &'static str;
+    /// Get the version of the event type.
+    fn version() -> u32 {
+        1
+    }
+    /// Get the JSON Schema of the event type.
+    fn schema() -> String;
+}
+
+/// Description of a registered event type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventTypeDescriptor {
+    /// Name of the event type.
+    pub name: String,
+    /// Version of the event type.
+    pub version: u32,
+    /// JSON Schema of the event type.
+    pub schema: String,
+}
+
+impl EventTypeDescriptor {
+    /// Create a descriptor from a registered event type.
+    pub fn of<E: RegisteredEvent>() -> Self {
+        Self {
+            name: E::event_type().to_string(),
+            version: E::version(),
+            schema: E::schema(),
+        }
+    }
+}
+
+/// Registry of the event types known to the application.
+#[derive(Debug, Default, Clone)]
+pub struct EventTypeRegistry {
+    descriptors: Vec<EventTypeDescriptor>,
+}
+
+impl EventTypeRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an event type.
+    pub fn register<E: RegisteredEvent>(&mut self) -> Result<(), RegistryError> {
+        self.register_descriptor(EventTypeDescriptor::of::<E>())
+    }
+
+    /// Register an event type from its descriptor.
+    pub fn register_descriptor(
+        &mut self,
+        descriptor: EventTypeDescriptor,
+    ) -> Result<(), RegistryError> {
+        if self.get(&descriptor.name, descriptor.version).is_some() {
+            return Err(RegistryError::AlreadyRegistered {
+                name: descriptor.name,
+                version: descriptor.version,
+            });
+        }
+        self.descriptors.push(descriptor);
+        Ok(())
+    }
+
+    /// Get the descriptor of the given event type and version.
+    pub fn get(&self, name: &str, version: u32) -> Option<&EventTypeDescriptor> {
+        self.descriptors
+            .iter()
+            .find(|d| d.name == name && d.version == version)
+    }
+
+    /// Get the latest version of the given event type.
+    pub fn latest(&self, name: &str) -> Option<&EventTypeDescriptor> {
+        self.descriptors
+            .iter()
+            .filter(|d| d.name == name)
+            .max_by_key(|d| d.version)
+    }
+
+    /// Get all the registered descriptors, in registration order.
+    pub fn descriptors(&self) -> &[EventTypeDescriptor] {
+        &self.descriptors
+    }
+
+    /// Generate a machine-readable JSON catalog of the registered event types.
+    pub fn catalog(&self) -> String {
+        let events = self
+            .sorted()
+            .iter()
+            .map(|d| {
+                format!(
+                    "{{\"name\":{},\"version\":{},\"schema\":{}}}",
+                    json::escape(&d.name),
+                    d.version,
+                    d.schema
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("{{\"events\":[{}]}}", events.join(","))
+    }
+
+    /// Generate an AsyncAPI document describing the registered event types as messages.
+    pub fn asyncapi(&self, title: &str, version: &str) -> String {
+        let messages = self
+            .sorted()
+            .iter()
+            .map(|d| {
+                format!(
+                    "{}:{{\"name\":{},\"payload\":{}}}",
+                    json::escape(&format!("{}.v{}", d.name, d.version)),
+                    json::escape(&d.name),
+                    d.schema
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"asyncapi\":\"2.6.0\",\"info\":{{\"title\":{},\"version\":{}}},\"components\":{{\"messages\":{{{}}}}}}}",
+            json::escape(title),
+            json::escape(version),
+            messages.join(",")
+        )
+    }
+
+    fn sorted(&self) -> Vec<&EventTypeDescriptor> {
+        let mut descriptors = self.descriptors.iter().collect::<Vec<_>>();
+        descriptors.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+        descriptors
+    }
+}
+
+/// Error returned by the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The event type is already registered with the same version.
+    AlreadyRegistered { name: String, version: u32 },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::AlreadyRegistered { name, version } => {
+                write!(f, "event type {} v{} is already registered", name, version)
+            }
+        }
+    }
+}
+
+impl Error for RegistryError {}
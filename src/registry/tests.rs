@@ -0,0 +1,89 @@
+use super::*;
+
+struct UserAddCreated;
+
+impl RegisteredEvent for UserAddCreated {
+    fn event_type() -> &'static str {
+        "UserAddCreated"
+    }
+
+    fn schema() -> String {
+        r#"{"type":"object","properties":{"org_id":{"type":"string"}}}"#.to_string()
+    }
+}
+
+struct UserAddCreatedV2;
+
+impl RegisteredEvent for UserAddCreatedV2 {
+    fn event_type() -> &'static str {
+        "UserAddCreated"
+    }
+
+    fn version() -> u32 {
+        2
+    }
+
+    fn schema() -> String {
+        r#"{"type":"object"}"#.to_string()
+    }
+}
+
+struct OrderShipped;
+
+impl RegisteredEvent for OrderShipped {
+    fn event_type() -> &'static str {
+        "OrderShipped"
+    }
+
+    fn schema() -> String {
+        r#"{"type":"object"}"#.to_string()
+    }
+}
+
+#[test]
+fn test_register_and_lookup() {
+    let mut registry = EventTypeRegistry::new();
+    registry.register::<UserAddCreated>().unwrap();
+    registry.register::<UserAddCreatedV2>().unwrap();
+
+    assert_eq!(registry.get("UserAddCreated", 1).unwrap().version, 1);
+    assert_eq!(registry.latest("UserAddCreated").unwrap().version, 2);
+    assert_eq!(
+        registry.register::<UserAddCreated>(),
+        Err(RegistryError::AlreadyRegistered {
+            name: "UserAddCreated".to_string(),
+            version: 1,
+        })
+    );
+}
+
+#[test]
+fn test_catalog() {
+    let mut registry = EventTypeRegistry::new();
+    registry.register::<UserAddCreated>().unwrap();
+    registry.register::<OrderShipped>().unwrap();
+
+    assert_eq!(
+        registry.catalog(),
+        concat!(
+            r#"{"events":["#,
+            r#"{"name":"OrderShipped","version":1,"schema":{"type":"object"}},"#,
+            r#"{"name":"UserAddCreated","version":1,"schema":{"type":"object","properties":{"org_id":{"type":"string"}}}}"#,
+            r#"]}"#
+        )
+    );
+}
+
+#[test]
+fn test_asyncapi() {
+    let mut registry = EventTypeRegistry::new();
+    registry.register::<OrderShipped>().unwrap();
+
+    assert_eq!(
+        registry.asyncapi("Orders", "1.0.0"),
+        concat!(
+            r#"{"asyncapi":"2.6.0","info":{"title":"Orders","version":"1.0.0"},"#,
+            r#""components":{"messages":{"OrderShipped.v1":{"name":"OrderShipped","payload":{"type":"object"}}}}}"#
+        )
+    );
+}
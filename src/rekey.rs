@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, EventStore};
+
+/// Type of the event closing the stream of an aggregate whose ID changed.
+pub const REKEYED_EVENT_TYPE: &str = "Rekeyed";
+/// Metadata key of the [`REKEYED_EVENT_TYPE`] event, redirecting to the new stream.
+pub const REKEYED_TO_METADATA_KEY: &str = "rekeyed_to";
+/// Metadata key of the copied events, recording the stream they were copied from.
+pub const REKEYED_FROM_METADATA_KEY: &str = "rekeyed_from";
+
+/// Types which represent an aggregate whose ID can change, e.g. to merge duplicates.
+pub trait Rekeyable: Aggregate {
+    /// Get the event recording that the aggregate moved from the ID to the other, which
+    /// projections handle to update their keys.
+    fn rekeyed_event(from: &Self::Id, to: &Self::Id) -> Self::Event;
+}
+
+/// Change the ID of the aggregate: its events are copied to the stream of the new ID, through
+/// `transform` to rewrite the IDs they embed, and a [`REKEYED_EVENT_TYPE`] event redirecting
+/// to the new stream closes the old one. Returns the number of events copied.
+///
+/// The events are appended after those of the new stream if it exists, merging both
+/// aggregates. Everything is written in one save.
+///
+/// The copies are new events of the global log, carrying the [`REKEYED_FROM_METADATA_KEY`]
+/// metadata. Consumers of the global log which already handled the originals should skip
+/// them with [`is_rekeyed_copy`], and update their keys on the [`REKEYED_EVENT_TYPE`] event
+/// instead.
+pub fn rekey<A, S, E>(
+    store: &mut S,
+    from: &A::Id,
+    to: &A::Id,
+    transform: impl Fn(A::Event) -> A::Event,
+) -> Result<usize, RekeyError<E>>
+where
+    A: Rekeyable,
+    A::Event: Clone,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>, Error = E>
+        + EventStore<Persistable = Envelope<A::Event>, Error = E>,
+{
+    let from_stream = A::stream_id(from);
+    let to_stream = A::stream_id(to);
+    if from_stream == to_stream {
+        return Err(RekeyError::SameStream(from_stream));
+    }
+    let events = store.load(&from_stream).map_err(RekeyError::Store)?;
+    match events.last() {
+        None => return Err(RekeyError::NotFound(from_stream)),
+        Some(last) if last.event_type == REKEYED_EVENT_TYPE => {
+            return Err(RekeyError::AlreadyRekeyed {
+                stream_id: from_stream,
+                to: last
+                    .metadata
+                    .get(REKEYED_TO_METADATA_KEY)
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+        }
+        _ => {}
+    }
+
+    let copied = events.len();
+    let mut writes = events
+        .into_iter()
+        .map(|event| {
+            let mut copy = Envelope::new(
+                to_stream.clone(),
+                0,
+                event.event_type,
+                transform(event.payload),
+            );
+            copy.metadata = event.metadata;
+            copy.with_metadata(REKEYED_FROM_METADATA_KEY, from_stream.clone())
+        })
+        .collect::<Vec<_>>();
+    let rekeyed = A::rekeyed_event(from, to);
+    writes.push(
+        Envelope::new(from_stream, 0, A::event_type(&rekeyed), rekeyed)
+            .with_metadata(REKEYED_TO_METADATA_KEY, to_stream),
+    );
+    store.save(&writes).map_err(RekeyError::Store)?;
+    Ok(copied)
+}
+
+/// Check whether the event is a copy written by [`rekey`], of an event still in the global
+/// log under its former stream.
+pub fn is_rekeyed_copy<E>(event: &Envelope<E>) -> bool {
+    event.metadata.contains_key(REKEYED_FROM_METADATA_KEY)
+}
+
+/// Get the stream the events of the stream live in, following the redirects left by
+/// [`rekey`].
+pub fn resolve<S, E>(store: &S, stream_id: &str) -> Result<String, RekeyError<S::Error>>
+where
+    S: EventLoader<StreamId = String, Persistable = Envelope<E>>,
+{
+    let mut visited = vec![stream_id.to_string()];
+    loop {
+        let current = visited.last().unwrap();
+        let events = store.load(current).map_err(RekeyError::Store)?;
+        let next = match events.last() {
+            Some(last) if last.event_type == REKEYED_EVENT_TYPE => {
+                last.metadata.get(REKEYED_TO_METADATA_KEY).cloned()
+            }
+            _ => None,
+        };
+        match next {
+            Some(next) if visited.contains(&next) => return Err(RekeyError::Cycle(visited)),
+            Some(next) => visited.push(next),
+            None => return Ok(visited.pop().unwrap()),
+        }
+    }
+}
+
+/// Error returned when changing the ID of an aggregate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RekeyError<E> {
+    /// The store failed.
+    Store(E),
+    /// The stream has no event.
+    NotFound(String),
+    /// The aggregate would be moved to its own stream.
+    SameStream(String),
+    /// The stream was already moved to another stream.
+    AlreadyRekeyed { stream_id: String, to: String },
+    /// The redirects between the streams form a cycle.
+    Cycle(Vec<String>),
+}
+
+impl<E: Error> fmt::Display for RekeyError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RekeyError::Store(e) => write!(f, "store error: {}", e),
+            RekeyError::NotFound(stream_id) => write!(f, "stream not found: {}", stream_id),
+            RekeyError::SameStream(stream_id) => {
+                write!(f, "stream {} cannot be rekeyed to itself", stream_id)
+            }
+            RekeyError::AlreadyRekeyed { stream_id, to } => {
+                write!(f, "stream {} was already rekeyed to {}", stream_id, to)
+            }
+            RekeyError::Cycle(stream_ids) => {
+                write!(
+                    f,
+                    "stream redirects form a cycle: {}",
+                    stream_ids.join(" -> ")
+                )
+            }
+        }
+    }
+}
+
+impl<E: Error> Error for RekeyError<E> {}
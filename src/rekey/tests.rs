@@ -0,0 +1,142 @@
+use super::*;
+use crate::event_store::OnMemoryEventStore;
+use crate::repository::Repository;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Customer {
+    orders: Vec<String>,
+    moved_to: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CustomerEvent {
+    Ordered { customer: u32, order: String },
+    Rekeyed { from: u32, to: u32 },
+}
+
+impl Aggregate for Customer {
+    type Id = u32;
+    type Event = CustomerEvent;
+
+    fn aggregate_type() -> &'static str {
+        "customer"
+    }
+
+    fn event_type(event: &CustomerEvent) -> String {
+        match event {
+            CustomerEvent::Ordered { .. } => "Ordered".to_string(),
+            CustomerEvent::Rekeyed { .. } => REKEYED_EVENT_TYPE.to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &CustomerEvent) {
+        match event {
+            CustomerEvent::Ordered { order, .. } => self.orders.push(order.clone()),
+            CustomerEvent::Rekeyed { to, .. } => self.moved_to = Some(*to),
+        }
+    }
+}
+
+impl Rekeyable for Customer {
+    fn rekeyed_event(from: &u32, to: &u32) -> CustomerEvent {
+        CustomerEvent::Rekeyed {
+            from: *from,
+            to: *to,
+        }
+    }
+}
+
+fn ordered(customer: u32, order: &str) -> CustomerEvent {
+    CustomerEvent::Ordered {
+        customer,
+        order: order.to_string(),
+    }
+}
+
+fn save(store: &mut OnMemoryEventStore<CustomerEvent>, id: u32, events: &[CustomerEvent]) {
+    let envelopes = events
+        .iter()
+        .map(|e| {
+            Envelope::new(
+                Customer::stream_id(&id),
+                0,
+                Customer::event_type(e),
+                e.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    store.save(&envelopes).unwrap();
+}
+
+fn move_to(to: u32) -> impl Fn(CustomerEvent) -> CustomerEvent {
+    move |event| match event {
+        CustomerEvent::Ordered { order, .. } => CustomerEvent::Ordered {
+            customer: to,
+            order,
+        },
+        other => other,
+    }
+}
+
+#[test]
+fn test_merge_duplicate_customers() {
+    let mut store = OnMemoryEventStore::new();
+    save(&mut store, 1, &[ordered(1, "o-1"), ordered(1, "o-2")]);
+    save(&mut store, 2, &[ordered(2, "o-3")]);
+
+    assert_eq!(
+        rekey::<Customer, _, _>(&mut store, &1, &2, move_to(2)),
+        Ok(2)
+    );
+
+    let merged = store.load(&"customer-2".to_string()).unwrap();
+    assert_eq!(merged.len(), 3);
+    assert!(!is_rekeyed_copy(&merged[0]));
+    assert!(is_rekeyed_copy(&merged[2]));
+    assert_eq!(merged[2].payload, ordered(2, "o-2"));
+    assert_eq!(merged[2].metadata[REKEYED_FROM_METADATA_KEY], "customer-1");
+    let old = store.load(&"customer-1".to_string()).unwrap();
+    assert_eq!(
+        old.last().unwrap().payload,
+        CustomerEvent::Rekeyed { from: 1, to: 2 }
+    );
+    assert_eq!(
+        old.last().unwrap().metadata[REKEYED_TO_METADATA_KEY],
+        "customer-2"
+    );
+
+    let mut repository = Repository::<Customer, _>::new(store);
+    let customer = &repository.load(&2).unwrap().unwrap().state;
+    assert_eq!(customer.orders, ["o-3", "o-1", "o-2"]);
+    assert_eq!(
+        repository.load(&1).unwrap().unwrap().state.moved_to,
+        Some(2)
+    );
+}
+
+#[test]
+fn test_rekey_errors_and_redirects() {
+    let mut store = OnMemoryEventStore::new();
+    assert_eq!(
+        rekey::<Customer, _, _>(&mut store, &1, &2, move_to(2)),
+        Err(RekeyError::NotFound("customer-1".to_string()))
+    );
+    save(&mut store, 1, &[ordered(1, "o-1")]);
+    assert_eq!(
+        rekey::<Customer, _, _>(&mut store, &1, &1, move_to(1)),
+        Err(RekeyError::SameStream("customer-1".to_string()))
+    );
+    assert_eq!(store.stream_len("customer-1"), 1);
+    rekey::<Customer, _, _>(&mut store, &1, &2, move_to(2)).unwrap();
+    rekey::<Customer, _, _>(&mut store, &2, &3, move_to(3)).unwrap();
+    assert_eq!(
+        rekey::<Customer, _, _>(&mut store, &1, &4, move_to(4)),
+        Err(RekeyError::AlreadyRekeyed {
+            stream_id: "customer-1".to_string(),
+            to: "customer-2".to_string()
+        })
+    );
+    assert_eq!(resolve(&store, "customer-1").unwrap(), "customer-3");
+    assert_eq!(resolve(&store, "customer-3").unwrap(), "customer-3");
+    assert_eq!(resolve(&store, "customer-9").unwrap(), "customer-9");
+}
@@ -0,0 +1,246 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::ReadOnlyEventStore;
+
+type KeyFn<E> = Box<dyn Fn(&Envelope<E>) -> String>;
+
+/// An event handed out by an [`OrderedRelay`], to publish under its partitioning key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outgoing<E> {
+    /// Position of the event, to acknowledge it.
+    pub position: u64,
+    /// Key the broker partitions the event by.
+    pub key: String,
+    /// The event.
+    pub event: Envelope<E>,
+}
+
+/// Relay from the event log to a broker partitioning by key, such as Kafka, which hands out
+/// events to publish concurrently while keeping the events of a key in order.
+///
+/// At most `per_key_in_flight` events of a key are published at once, one by default, and an
+/// event is only handed out once the events of its key before it were. A failed event is handed
+/// out again before the later events of its key, along with those already in flight, which may
+/// then be delivered twice. Raising the per-key limit is only safe with brokers which keep the
+/// order of the pipelined messages of a partition.
+///
+/// Every event of a stream must map to the same key, or the stream would be interleaved across
+/// partitions: the relay fails instead of handing out such an event.
+pub struct OrderedRelay<E> {
+    key: KeyFn<E>,
+    max_in_flight: usize,
+    per_key_in_flight: usize,
+    cursor: u64,
+    waiting: BTreeMap<u64, Outgoing<E>>,
+    in_flight: BTreeMap<u64, Outgoing<E>>,
+    keys_in_flight: HashMap<String, usize>,
+    /// Key and number of pending events of the streams with events waiting or in flight.
+    streams: HashMap<String, (String, usize)>,
+}
+
+impl<E: Clone> OrderedRelay<E> {
+    /// Create a relay publishing at most `max_in_flight` events at once, keyed by stream ID,
+    /// from the start of the log.
+    pub fn new(max_in_flight: usize) -> Self {
+        Self::starting_at(max_in_flight, 0)
+    }
+
+    /// Create a relay resuming from the position, such as a saved
+    /// [`committed`](Self::committed) position.
+    pub fn starting_at(max_in_flight: usize, position: u64) -> Self {
+        Self {
+            key: Box::new(|event| event.stream_id.clone()),
+            max_in_flight: max_in_flight.max(1),
+            per_key_in_flight: 1,
+            cursor: position,
+            waiting: BTreeMap::new(),
+            in_flight: BTreeMap::new(),
+            keys_in_flight: HashMap::new(),
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Set how the partitioning key of an event is extracted, such as the tenant of its stream.
+    pub fn key(mut self, key: impl Fn(&Envelope<E>) -> String + 'static) -> Self {
+        self.key = Box::new(key);
+        self
+    }
+
+    /// Set the number of events of a key published at once.
+    pub fn per_key_in_flight(mut self, limit: usize) -> Self {
+        self.per_key_in_flight = limit.max(1);
+        self
+    }
+
+    /// Get the number of events handed out and not acknowledged yet.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Get the position below which every event was acknowledged, to save and resume from.
+    pub fn committed(&self) -> u64 {
+        [self.waiting.keys().next(), self.in_flight.keys().next()]
+            .into_iter()
+            .flatten()
+            .copied()
+            .fold(self.cursor, u64::min)
+    }
+
+    /// Read up to `batch_size` events from the store when few are waiting, and hand out the
+    /// events which can be published now.
+    pub fn poll<S>(
+        &mut self,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<Vec<Outgoing<E>>, RelayError<S::Error>>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<E>>,
+    {
+        if self.waiting.len() < batch_size {
+            for event in store
+                .read_all(self.cursor, batch_size)
+                .map_err(RelayError::Store)?
+            {
+                let key = (self.key)(&event);
+                match self.streams.get_mut(&event.stream_id) {
+                    Some((previous, _)) if *previous != key => {
+                        return Err(RelayError::SplitStream {
+                            stream_id: event.stream_id,
+                            key,
+                            previous: previous.clone(),
+                        })
+                    }
+                    Some((_, pending)) => *pending += 1,
+                    None => {
+                        self.streams
+                            .insert(event.stream_id.clone(), (key.clone(), 1));
+                    }
+                }
+                self.cursor = event.position + 1;
+                self.waiting.insert(
+                    event.position,
+                    Outgoing {
+                        position: event.position,
+                        key,
+                        event,
+                    },
+                );
+            }
+        }
+        Ok(self.dispatch())
+    }
+
+    /// Acknowledge the published event, freeing its slot. Unknown positions are ignored.
+    pub fn ack(&mut self, position: u64) {
+        let outgoing = match self.in_flight.remove(&position) {
+            Some(outgoing) => outgoing,
+            None => return,
+        };
+        self.release_key(&outgoing.key);
+        if let Some((_, pending)) = self.streams.get_mut(&outgoing.event.stream_id) {
+            *pending -= 1;
+            if *pending == 0 {
+                self.streams.remove(&outgoing.event.stream_id);
+            }
+        }
+    }
+
+    /// Report that the event could not be published. It is handed out again, before the later
+    /// events of its key, which are taken back from the flight too.
+    pub fn nack(&mut self, position: u64) {
+        let key = match self.in_flight.get(&position) {
+            Some(outgoing) => outgoing.key.clone(),
+            None => return,
+        };
+        let retried = self
+            .in_flight
+            .range(position..)
+            .filter(|(_, outgoing)| outgoing.key == key)
+            .map(|(position, _)| *position)
+            .collect::<Vec<_>>();
+        for position in retried {
+            let outgoing = self.in_flight.remove(&position).expect("in flight");
+            self.release_key(&key);
+            self.waiting.insert(position, outgoing);
+        }
+    }
+
+    fn dispatch(&mut self) -> Vec<Outgoing<E>> {
+        let mut blocked = HashSet::new();
+        let mut ready = Vec::new();
+        for (position, outgoing) in &self.waiting {
+            if self.in_flight.len() + ready.len() >= self.max_in_flight {
+                break;
+            }
+            if blocked.contains(&outgoing.key) {
+                continue;
+            }
+            let in_flight = self.keys_in_flight.entry(outgoing.key.clone()).or_default();
+            if *in_flight < self.per_key_in_flight {
+                *in_flight += 1;
+                ready.push(*position);
+            } else {
+                blocked.insert(outgoing.key.clone());
+            }
+        }
+        ready
+            .into_iter()
+            .map(|position| {
+                let outgoing = self.waiting.remove(&position).expect("waiting");
+                self.in_flight.insert(position, outgoing.clone());
+                outgoing
+            })
+            .collect()
+    }
+
+    fn release_key(&mut self, key: &str) {
+        if let Some(in_flight) = self.keys_in_flight.get_mut(key) {
+            *in_flight -= 1;
+            if *in_flight == 0 {
+                self.keys_in_flight.remove(key);
+            }
+        }
+    }
+}
+
+/// Error returned by the [`OrderedRelay`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayError<E> {
+    /// The event store failed.
+    Store(E),
+    /// An event of the stream maps to another key than its pending events, which would
+    /// interleave the stream across partitions.
+    SplitStream {
+        /// ID of the stream.
+        stream_id: String,
+        /// Key of the event.
+        key: String,
+        /// Key of the pending events of the stream.
+        previous: String,
+    },
+}
+
+impl<E: Error> fmt::Display for RelayError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayError::Store(e) => write!(f, "store error: {}", e),
+            RelayError::SplitStream {
+                stream_id,
+                key,
+                previous,
+            } => write!(
+                f,
+                "stream {} maps to key {} while its events of key {} are pending",
+                stream_id, key, previous
+            ),
+        }
+    }
+}
+
+impl<E: Error> Error for RelayError<E> {}
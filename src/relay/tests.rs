@@ -0,0 +1,87 @@
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+
+fn store(streams: &[&str]) -> OnMemoryEventStore<u32> {
+    let mut store = OnMemoryEventStore::new();
+    for (i, stream_id) in streams.iter().enumerate() {
+        store
+            .save(&[Envelope::new(
+                stream_id.to_string(),
+                0,
+                "Added".to_string(),
+                i as u32,
+            )])
+            .unwrap();
+    }
+    store
+}
+
+fn positions(outgoing: &[Outgoing<u32>]) -> Vec<u64> {
+    outgoing.iter().map(|o| o.position).collect()
+}
+
+#[test]
+fn test_keep_streams_in_order_under_concurrency() {
+    let store = store(&["org-1", "org-2", "org-1", "org-3", "org-1"]);
+    let mut relay = OrderedRelay::new(8);
+
+    let first = relay.poll(&store, 10).unwrap();
+    assert_eq!(positions(&first), [0, 1, 3]);
+    assert_eq!(first[0].key, "org-1");
+    assert!(relay.poll(&store, 10).unwrap().is_empty());
+
+    relay.ack(1);
+    relay.ack(3);
+    assert!(relay.poll(&store, 10).unwrap().is_empty());
+    assert_eq!(relay.committed(), 0);
+
+    relay.ack(0);
+    assert_eq!(relay.committed(), 2);
+    assert_eq!(positions(&relay.poll(&store, 10).unwrap()), [2]);
+    relay.ack(2);
+    assert_eq!(positions(&relay.poll(&store, 10).unwrap()), [4]);
+    relay.ack(4);
+    assert_eq!(relay.in_flight(), 0);
+    assert_eq!(relay.committed(), 5);
+}
+
+#[test]
+fn test_retry_failed_events_before_later_ones() {
+    let store = store(&["org-1", "org-1", "org-1", "org-2"]);
+    let mut relay = OrderedRelay::new(3).per_key_in_flight(2);
+
+    assert_eq!(positions(&relay.poll(&store, 10).unwrap()), [0, 1, 3]);
+    relay.nack(0);
+    assert_eq!(relay.in_flight(), 1);
+    assert_eq!(relay.committed(), 0);
+
+    // The late acknowledgement of the taken back event is ignored.
+    relay.ack(1);
+    assert_eq!(positions(&relay.poll(&store, 10).unwrap()), [0, 1]);
+    relay.ack(0);
+    relay.ack(1);
+    relay.ack(3);
+    assert_eq!(positions(&relay.poll(&store, 10).unwrap()), [2]);
+}
+
+#[test]
+fn test_key_extraction() {
+    let store = store(&["acme/org-1", "acme/org-2", "globex/org-3"]);
+    let mut relay = OrderedRelay::starting_at(8, 1)
+        .key(|event: &Envelope<u32>| event.stream_id.split('/').next().unwrap().to_string());
+
+    let outgoing = relay.poll(&store, 10).unwrap();
+    assert_eq!(positions(&outgoing), [1, 2]);
+    assert_eq!(outgoing[1].key, "globex");
+
+    let store = self::store(&["org-1", "org-1"]);
+    let mut relay = OrderedRelay::new(8).key(|event: &Envelope<u32>| event.payload.to_string());
+    assert_eq!(
+        relay.poll(&store, 10),
+        Err(RelayError::SplitStream {
+            stream_id: "org-1".to_string(),
+            key: "1".to_string(),
+            previous: "0".to_string(),
+        })
+    );
+}
@@ -0,0 +1,250 @@
+#[cfg(test)]
+mod tests;
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+
+use crate::event_store::{
+    AppendError, ConsistentLoader, EventLoader, EventStore, ExpectedVersion, LogHead,
+    ReadConsistency, ReadOnlyEventStore,
+};
+
+/// Event store appending to a primary and reading from a replica of it, such as a database
+/// with a read replica.
+///
+/// Reads are served by the replica only once it has replicated the position required by the
+/// caller, which is the head of the primary after the last append through this store, so that
+/// writers read their own writes. Reads fall back to the primary while the replica lags behind.
+#[derive(Debug)]
+pub struct ReplicatedEventStore<P, R> {
+    primary: P,
+    replica: R,
+    min_position: u64,
+    fallbacks: Cell<u64>,
+}
+
+impl<P, R> ReplicatedEventStore<P, R> {
+    /// Compose the primary and its replica.
+    pub fn new(primary: P, replica: R) -> Self {
+        Self {
+            primary,
+            replica,
+            min_position: 0,
+            fallbacks: Cell::new(0),
+        }
+    }
+
+    /// Get the primary.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Get the replica.
+    pub fn replica(&self) -> &R {
+        &self.replica
+    }
+
+    /// Get the replica mutably.
+    pub fn replica_mut(&mut self) -> &mut R {
+        &mut self.replica
+    }
+
+    /// Require the replica to have replicated up to the position before serving reads, e.g.
+    /// the position returned to a client by another writer. Lower positions are ignored.
+    pub fn require_position(&mut self, position: u64) {
+        self.min_position = self.min_position.max(position);
+    }
+
+    /// Get the position the replica must have replicated to serve reads.
+    pub fn min_position(&self) -> u64 {
+        self.min_position
+    }
+
+    /// Get the number of reads served by the primary because the replica lagged behind.
+    pub fn fallbacks(&self) -> u64 {
+        self.fallbacks.get()
+    }
+}
+
+impl<P, R> ReplicatedEventStore<P, R>
+where
+    P: EventLoader,
+    R: LogHead,
+{
+    fn replica_is_fresh(&self) -> Result<bool, ReplicaError<P::Error, R::Error>> {
+        let head = self
+            .replica
+            .head_position()
+            .map_err(ReplicaError::Replica)?;
+        if head >= self.min_position {
+            return Ok(true);
+        }
+        self.fallbacks.set(self.fallbacks.get() + 1);
+        Ok(false)
+    }
+}
+
+impl<P, R> EventStore for ReplicatedEventStore<P, R>
+where
+    P: EventStore + LogHead<Error = <P as EventStore>::Error>,
+    R: EventLoader,
+{
+    type Persistable = <P as EventStore>::Persistable;
+    type Error = ReplicaError<<P as EventStore>::Error, R::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.primary.save(events).map_err(ReplicaError::Primary)?;
+        self.require_primary_head()
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.primary
+            .append_multi(appends)
+            .map_err(|e| e.map_store(ReplicaError::Primary))?;
+        self.require_primary_head().map_err(AppendError::Store)
+    }
+}
+
+impl<P, R> ReplicatedEventStore<P, R>
+where
+    P: EventStore + LogHead<Error = <P as EventStore>::Error>,
+    R: EventLoader,
+{
+    /// Require the replica to have replicated the head of the primary, after an append.
+    fn require_primary_head(
+        &mut self,
+    ) -> Result<(), ReplicaError<<P as EventStore>::Error, R::Error>> {
+        let head = self
+            .primary
+            .head_position()
+            .map_err(ReplicaError::Primary)?;
+        self.require_position(head);
+        Ok(())
+    }
+}
+
+impl<P, R> EventLoader for ReplicatedEventStore<P, R>
+where
+    P: EventLoader,
+    R: LogHead<StreamId = P::StreamId, Persistable = P::Persistable>,
+{
+    type StreamId = P::StreamId;
+    type Persistable = P::Persistable;
+    type Error = ReplicaError<P::Error, R::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        if self.replica_is_fresh()? {
+            self.replica.load(stream_id).map_err(ReplicaError::Replica)
+        } else {
+            self.primary.load(stream_id).map_err(ReplicaError::Primary)
+        }
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        if self.replica_is_fresh()? {
+            self.replica
+                .load_after(stream_id, version)
+                .map_err(ReplicaError::Replica)
+        } else {
+            self.primary
+                .load_after(stream_id, version)
+                .map_err(ReplicaError::Primary)
+        }
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        if self.replica_is_fresh()? {
+            self.replica
+                .read_multi(stream_ids)
+                .map_err(ReplicaError::Replica)
+        } else {
+            self.primary
+                .read_multi(stream_ids)
+                .map_err(ReplicaError::Primary)
+        }
+    }
+}
+
+impl<P, R> ConsistentLoader for ReplicatedEventStore<P, R>
+where
+    P: EventLoader,
+    R: LogHead<StreamId = P::StreamId, Persistable = P::Persistable>,
+{
+    /// Strong reads are served like [`load`](EventLoader::load), by the replica once it has
+    /// replicated the required position. Eventual reads are always served by the replica.
+    fn load_consistent(
+        &self,
+        stream_id: &Self::StreamId,
+        consistency: ReadConsistency,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        match consistency {
+            ReadConsistency::Strong => self.load(stream_id),
+            ReadConsistency::Eventual => {
+                self.replica.load(stream_id).map_err(ReplicaError::Replica)
+            }
+        }
+    }
+}
+
+impl<P, R> ReadOnlyEventStore for ReplicatedEventStore<P, R>
+where
+    P: ReadOnlyEventStore,
+    R: LogHead<StreamId = P::StreamId, Persistable = P::Persistable>,
+{
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        if self.replica_is_fresh()? {
+            self.replica
+                .read_all(from, limit)
+                .map_err(ReplicaError::Replica)
+        } else {
+            self.primary
+                .read_all(from, limit)
+                .map_err(ReplicaError::Primary)
+        }
+    }
+}
+
+impl<P, R> LogHead for ReplicatedEventStore<P, R>
+where
+    P: LogHead,
+    R: LogHead<StreamId = P::StreamId, Persistable = P::Persistable>,
+{
+    fn head_position(&self) -> Result<u64, Self::Error> {
+        if self.replica_is_fresh()? {
+            self.replica.head_position().map_err(ReplicaError::Replica)
+        } else {
+            self.primary.head_position().map_err(ReplicaError::Primary)
+        }
+    }
+}
+
+/// Error returned by the [`ReplicatedEventStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicaError<PE, RE> {
+    /// The primary failed.
+    Primary(PE),
+    /// The replica failed.
+    Replica(RE),
+}
+
+impl<PE: Error, RE: Error> fmt::Display for ReplicaError<PE, RE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplicaError::Primary(e) => write!(f, "primary store error: {}", e),
+            ReplicaError::Replica(e) => write!(f, "replica store error: {}", e),
+        }
+    }
+}
+
+impl<PE: Error, RE: Error> Error for ReplicaError<PE, RE> {}
@@ -0,0 +1,121 @@
+use super::*;
+use crate::envelope::Envelope;
+use crate::event_store::OnMemoryEventStore;
+
+type Store = ReplicatedEventStore<OnMemoryEventStore<u32>, OnMemoryEventStore<u32>>;
+
+fn store() -> Store {
+    ReplicatedEventStore::new(OnMemoryEventStore::new(), OnMemoryEventStore::new())
+}
+
+fn save(store: &mut Store, stream_id: &str, payload: u32) {
+    store
+        .save(&[Envelope::new(stream_id, 0, "Happened", payload)])
+        .unwrap();
+}
+
+/// Copy the events the replica misses from the primary.
+fn replicate(store: &mut Store) {
+    let from = store.replica().head();
+    let events = store.primary().read_all(from, usize::MAX).unwrap();
+    store.replica_mut().save(&events).unwrap();
+}
+
+fn payloads(events: &[Envelope<u32>]) -> Vec<u32> {
+    events.iter().map(|e| e.payload).collect()
+}
+
+#[test]
+fn test_read_own_writes() {
+    let mut store = store();
+    save(&mut store, "order-1", 1);
+    assert_eq!(store.min_position(), 1);
+
+    // The replica lags behind: reads fall back to the primary.
+    let order = "order-1".to_string();
+    assert_eq!(payloads(&store.load(&order).unwrap()), vec![1]);
+    assert_eq!(store.head_position(), Ok(1));
+    assert_eq!(store.fallbacks(), 2);
+
+    replicate(&mut store);
+    save(&mut store, "order-1", 2);
+    replicate(&mut store);
+    assert_eq!(payloads(&store.load(&order).unwrap()), vec![1, 2]);
+    assert_eq!(payloads(&store.read_all(0, 10).unwrap()), vec![1, 2]);
+    assert_eq!(store.fallbacks(), 2);
+}
+
+#[test]
+fn test_append_multi_to_primary() {
+    let mut store = store();
+    store
+        .append_multi(&[
+            (
+                "order-1".to_string(),
+                ExpectedVersion::NoStream,
+                vec![Envelope::new("order-1", 0, "Happened", 1)],
+            ),
+            (
+                "order-2".to_string(),
+                ExpectedVersion::NoStream,
+                vec![Envelope::new("order-2", 0, "Happened", 2)],
+            ),
+        ])
+        .unwrap();
+    assert_eq!(store.primary().head(), 2);
+    assert_eq!(store.replica().head(), 0);
+    assert_eq!(store.min_position(), 2);
+
+    // Reads are served by the primary until the replica catches up.
+    let order = "order-2".to_string();
+    assert_eq!(payloads(&store.load(&order).unwrap()), vec![2]);
+    assert_eq!(store.fallbacks(), 1);
+    replicate(&mut store);
+    assert_eq!(payloads(&store.load(&order).unwrap()), vec![2]);
+    assert_eq!(store.fallbacks(), 1);
+}
+
+#[test]
+fn test_require_position() {
+    // Another writer appended to the primary, not replicated yet, and handed over its position.
+    let mut primary = OnMemoryEventStore::new();
+    let mut replica = OnMemoryEventStore::new();
+    for payload in [1, 2] {
+        primary
+            .save(&[Envelope::new("order-1", 0, "Happened", payload)])
+            .unwrap();
+    }
+    replica.save(&primary.read_all(0, 1).unwrap()).unwrap();
+    let mut store = ReplicatedEventStore::new(primary, replica);
+
+    store.require_position(1);
+    assert_eq!(payloads(&store.read_all(0, 10).unwrap()), vec![1]);
+    store.require_position(2);
+    assert_eq!(payloads(&store.read_all(0, 10).unwrap()), vec![1, 2]);
+    store.require_position(0);
+    assert_eq!(store.min_position(), 2);
+    assert_eq!(store.fallbacks(), 1);
+}
+
+#[test]
+fn test_load_consistent() {
+    let mut store = store();
+    save(&mut store, "order-1", 1);
+    let order = "order-1".to_string();
+
+    // Eventual reads accept the lagging replica, strong reads fall back to the primary.
+    assert!(store
+        .load_consistent(&order, ReadConsistency::Eventual)
+        .unwrap()
+        .is_empty());
+    assert_eq!(store.fallbacks(), 0);
+    assert_eq!(
+        payloads(
+            &store
+                .load_consistent(&order, ReadConsistency::Strong)
+                .unwrap()
+        ),
+        vec![1]
+    );
+    assert_eq!(store.fallbacks(), 1);
+}
@@ -0,0 +1,714 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, ConsistentLoader, EventLoader, EventStore, ExpectedVersion,
+    OnMemoryEventStoreError, ReadConsistency, TransactionManager, TransactionalError,
+};
+use crate::faulty::FaultError;
+use crate::snapshot::SnapshotStore;
+use crate::stream_lock::{LockError, StreamLock};
+use crate::summary::AggregateSummary;
+use crate::unique_index::ReservationError;
+
+/// An aggregate rebuilt from its stream, with the version it was rebuilt at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loaded<A> {
+    /// Version of the stream the state reflects.
+    pub version: u64,
+    /// The state of the aggregate.
+    pub state: A,
+}
+
+/// Repository rebuilding aggregates from an event store, and caching them.
+///
+/// Cached aggregates are kept up to date with the events saved through the repository. Events
+/// appended to the store by other writers are only seen once the aggregate is evicted.
+pub struct Repository<A: Aggregate, S> {
+    store: S,
+    cache: HashMap<String, Loaded<A>>,
+    lock: Option<Arc<dyn StreamLock>>,
+    verify_snapshot: Option<fn(&A, &A) -> bool>,
+}
+
+impl<A: Aggregate, S> Repository<A, S> {
+    /// Create a repository over the store, with an empty cache.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            cache: HashMap::new(),
+            lock: None,
+            verify_snapshot: None,
+        }
+    }
+
+    /// Lock the stream of the aggregate in [`execute`](Repository::execute), so that writers of
+    /// the same stream wait for each other instead of failing on a version conflict.
+    pub fn with_stream_lock(mut self, lock: Arc<dyn StreamLock>) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    /// Verify the aggregates loaded from snapshots against a full replay of their stream,
+    /// comparing them with `PartialEq`. Meant for debug builds and CI, to catch snapshot drift.
+    pub fn with_snapshot_verification(mut self) -> Self
+    where
+        A: PartialEq,
+    {
+        self.verify_snapshot = Some(|snapshot, replay| snapshot == replay);
+        self
+    }
+
+    /// Verify the aggregates loaded from snapshots against a full replay of their stream,
+    /// comparing their summaries, for aggregates which are not `PartialEq`.
+    pub fn with_snapshot_summary_verification(mut self) -> Self
+    where
+        A: AggregateSummary,
+    {
+        self.verify_snapshot = Some(|snapshot, replay| snapshot.summary() == replay.summary());
+        self
+    }
+
+    /// Get the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the store mutably, e.g. to run the effects it persists. Events appended through it
+    /// are only seen by cached aggregates once evicted.
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Get the cached aggregate, without loading it.
+    pub fn cached(&self, id: &A::Id) -> Option<&Loaded<A>> {
+        self.cache.get(&A::stream_id(id))
+    }
+
+    /// Drop the aggregate from the cache, so that it is rebuilt on the next load.
+    pub fn evict(&mut self, id: &A::Id) {
+        self.cache.remove(&A::stream_id(id));
+    }
+
+    /// Start collecting the events of aggregates, to save them in one transaction.
+    pub fn unit_of_work(&mut self) -> UnitOfWork<'_, A, S> {
+        UnitOfWork {
+            repository: self,
+            working: HashMap::new(),
+            versions: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<A, S> Repository<A, S>
+where
+    A: Aggregate + Default,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>>,
+{
+    /// Get the aggregate, rebuilding it from the store unless cached. Returns `None` for an
+    /// aggregate without events.
+    pub fn load(&mut self, id: &A::Id) -> Result<Option<&Loaded<A>>, S::Error> {
+        let stream_id = A::stream_id(id);
+        if !self.cache.contains_key(&stream_id) {
+            let events = self.store.load(&stream_id)?;
+            if let Some(loaded) = Self::rebuild(&events) {
+                self.cache.insert(stream_id.clone(), loaded);
+            }
+        }
+        Ok(self.cache.get(&stream_id))
+    }
+
+    /// Rebuild and cache the aggregates which are not cached yet, reading their streams in one
+    /// [`read_multi`](EventLoader::read_multi) call. Returns the number of aggregates loaded.
+    pub fn preload(&mut self, ids: &[A::Id]) -> Result<usize, S::Error> {
+        let mut stream_ids = ids
+            .iter()
+            .map(A::stream_id)
+            .filter(|stream_id| !self.cache.contains_key(stream_id))
+            .collect::<Vec<_>>();
+        stream_ids.sort();
+        stream_ids.dedup();
+        if stream_ids.is_empty() {
+            return Ok(0);
+        }
+        let streams = self.store.read_multi(&stream_ids)?;
+        let mut loaded = 0;
+        for (stream_id, events) in stream_ids.into_iter().zip(streams) {
+            if let Some(aggregate) = Self::rebuild(&events) {
+                self.cache.insert(stream_id, aggregate);
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Get the aggregate, rebuilding it from its latest snapshot and the events after it unless
+    /// cached. Returns `None` for an aggregate without snapshot nor events.
+    ///
+    /// Only the events after the snapshot are read, with [`EventLoader::load_after`]. With
+    /// snapshot verification, the whole stream is also read to replay the aggregate, and
+    /// [`SnapshotLoadError::Drift`] is returned when both states differ.
+    pub fn load_from_snapshot<N, NE>(
+        &mut self,
+        id: &A::Id,
+        snapshots: &N,
+    ) -> Result<Option<&Loaded<A>>, SnapshotLoadError<S::Error, NE>>
+    where
+        N: SnapshotStore<A, Error = NE>,
+    {
+        let stream_id = A::stream_id(id);
+        if self.cache.contains_key(&stream_id) {
+            return Ok(self.cache.get(&stream_id));
+        }
+        let snapshot = match snapshots
+            .load(&stream_id)
+            .map_err(SnapshotLoadError::Snapshot)?
+        {
+            Some(snapshot) => snapshot,
+            None => return self.load(id).map_err(SnapshotLoadError::Store),
+        };
+        let events = self
+            .store
+            .load_after(&stream_id, snapshot.version)
+            .map_err(SnapshotLoadError::Store)?;
+        let mut loaded = Loaded {
+            version: snapshot.version,
+            state: snapshot.state,
+        };
+        for event in events.iter().filter(|e| e.version > snapshot.version) {
+            loaded.state.apply(&event.payload);
+            loaded.version = event.version;
+        }
+        if let Some(verify) = self.verify_snapshot {
+            let events = self
+                .store
+                .load(&stream_id)
+                .map_err(SnapshotLoadError::Store)?;
+            let replay = Self::rebuild(&events)
+                .map(|replay| replay.state)
+                .unwrap_or_default();
+            if !verify(&loaded.state, &replay) {
+                return Err(SnapshotLoadError::Drift {
+                    stream_id,
+                    version: snapshot.version,
+                });
+            }
+        }
+        self.cache.insert(stream_id.clone(), loaded);
+        Ok(self.cache.get(&stream_id))
+    }
+
+    fn rebuild(events: &[Envelope<A::Event>]) -> Option<Loaded<A>> {
+        let last = events.last()?;
+        let mut state = A::default();
+        for event in events {
+            state.apply(&event.payload);
+        }
+        Some(Loaded {
+            version: last.version,
+            state,
+        })
+    }
+}
+
+impl<A, S> Repository<A, S>
+where
+    A: Aggregate + Default,
+    S: EventStore<Persistable = Envelope<A::Event>>,
+    A::Event: Clone,
+{
+    /// Save the events of the aggregate, and apply them to the cached aggregate.
+    ///
+    /// The events of a cached aggregate are appended at its version, so that they are rejected
+    /// with [`AppendError::WrongExpectedVersion`] if other writers appended to the stream since
+    /// it was loaded; the aggregate is then evicted. Other aggregates are appended at any version.
+    pub fn save(&mut self, id: &A::Id, events: &[A::Event]) -> Result<(), AppendError<S::Error>> {
+        let stream_id = A::stream_id(id);
+        let expected = self
+            .cache
+            .get(&stream_id)
+            .map_or(ExpectedVersion::Any, |cached| {
+                ExpectedVersion::Exact(cached.version)
+            });
+        self.append(stream_id, expected, events)
+    }
+
+    fn append(
+        &mut self,
+        stream_id: String,
+        expected: ExpectedVersion,
+        events: &[A::Event],
+    ) -> Result<(), AppendError<S::Error>> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let envelopes = events
+            .iter()
+            .map(|event| Envelope::new(stream_id.clone(), 0, A::event_type(event), event.clone()))
+            .collect::<Vec<_>>();
+        let appended = self
+            .store
+            .append_multi(&[(stream_id.clone(), expected, envelopes)]);
+        if let Err(e) = appended {
+            if matches!(e, AppendError::WrongExpectedVersion { .. }) {
+                self.cache.remove(&stream_id);
+            }
+            return Err(e);
+        }
+        if let Some(cached) = self.cache.get_mut(&stream_id) {
+            for event in events {
+                cached.state.apply(event);
+                cached.version += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A, S> Repository<A, S>
+where
+    A: Aggregate + Default + Clone,
+    S: ConsistentLoader<StreamId = String, Persistable = Envelope<A::Event>>,
+{
+    /// Get the aggregate, as fresh as the consistency requires. Returns `None` for an
+    /// aggregate without events.
+    ///
+    /// Strong reads rebuild the aggregate from the store and refresh the cache, so that they
+    /// see the events of other writers. Eventual reads are served by the cache, or rebuilt
+    /// without being cached since they may be stale.
+    pub fn load_with(
+        &mut self,
+        id: &A::Id,
+        consistency: ReadConsistency,
+    ) -> Result<Option<Loaded<A>>, S::Error> {
+        let stream_id = A::stream_id(id);
+        if consistency == ReadConsistency::Eventual {
+            if let Some(cached) = self.cache.get(&stream_id) {
+                return Ok(Some(cached.clone()));
+            }
+        }
+        let loaded = Self::rebuild(&self.store.load_consistent(&stream_id, consistency)?);
+        if consistency == ReadConsistency::Strong {
+            match &loaded {
+                Some(loaded) => self.cache.insert(stream_id, loaded.clone()),
+                None => self.cache.remove(&stream_id),
+            };
+        }
+        Ok(loaded)
+    }
+}
+
+impl<A, S, E> Repository<A, S>
+where
+    A: Aggregate + Default,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>, Error = E>
+        + EventStore<Persistable = Envelope<A::Event>, Error = E>,
+    A::Event: Clone,
+{
+    /// Decide the events of the aggregate from its state, `None` for an aggregate without
+    /// events, and save them. Returns the saved events.
+    ///
+    /// With a stream lock, the stream stays locked from loading to saving and the aggregate is
+    /// rebuilt from the store, so that writers of the same stream are serialized.
+    pub fn execute(
+        &mut self,
+        id: &A::Id,
+        decide: impl FnOnce(Option<&A>) -> Vec<A::Event>,
+    ) -> Result<Vec<A::Event>, RepositoryError<E>> {
+        let lock = match &self.lock {
+            Some(lock) => lock.clone(),
+            None => return self.decide_and_save(id, decide),
+        };
+        let guard = StreamLockGuard::lock(lock, A::stream_id(id)).map_err(RepositoryError::Lock)?;
+        self.cache.remove(&guard.stream_id);
+        let result = self.decide_and_save(id, decide);
+        // The failure to unlock is only reported when executing succeeded, so that it does not
+        // mask the failure of the store.
+        let unlocked = guard.unlock();
+        let events = result?;
+        unlocked.map_err(RepositoryError::Lock)?;
+        Ok(events)
+    }
+
+    /// Execute like [`execute`](Repository::execute), translating the failures into domain
+    /// errors with the mapper.
+    pub fn execute_mapped<M: ErrorMapper<E>>(
+        &mut self,
+        id: &A::Id,
+        decide: impl FnOnce(Option<&A>) -> Vec<A::Event>,
+        mapper: &M,
+    ) -> Result<Vec<A::Event>, M::Error> {
+        self.execute(id, decide).map_err(|e| mapper.map_error(e))
+    }
+
+    fn decide_and_save(
+        &mut self,
+        id: &A::Id,
+        decide: impl FnOnce(Option<&A>) -> Vec<A::Event>,
+    ) -> Result<Vec<A::Event>, RepositoryError<E>> {
+        let state = self.load(id).map_err(RepositoryError::Store)?;
+        let expected = ExpectedVersion::Exact(state.map_or(0, |loaded| loaded.version));
+        let events = decide(state.map(|loaded| &loaded.state));
+        self.append(A::stream_id(id), expected, &events)
+            .map_err(RepositoryError::Append)?;
+        Ok(events)
+    }
+}
+
+/// Guard of a locked stream, unlocking it when dropped so that a panic while deciding does
+/// not leave the stream locked.
+struct StreamLockGuard {
+    lock: Arc<dyn StreamLock>,
+    stream_id: String,
+    locked: bool,
+}
+
+impl StreamLockGuard {
+    fn lock(lock: Arc<dyn StreamLock>, stream_id: String) -> Result<Self, LockError> {
+        lock.lock_stream(&stream_id)?;
+        Ok(Self {
+            lock,
+            stream_id,
+            locked: true,
+        })
+    }
+
+    /// Unlock the stream, reporting the failure to unlock it.
+    fn unlock(mut self) -> Result<(), LockError> {
+        self.locked = false;
+        self.lock.unlock_stream(&self.stream_id)
+    }
+}
+
+impl Drop for StreamLockGuard {
+    fn drop(&mut self) {
+        if self.locked {
+            let _ = self.lock.unlock_stream(&self.stream_id);
+        }
+    }
+}
+
+/// Events of aggregates collected in a transaction scope, and saved at once on commit.
+///
+/// Commands executed in the scope see the events recorded before them. Nothing is saved until
+/// [`commit`](UnitOfWork::commit), and dropping the unit of work discards the events. Streams
+/// are not locked: each stream is appended at the version it was loaded at, so that the commit
+/// fails if other writers appended to it in the meantime.
+pub struct UnitOfWork<'r, A: Aggregate, S> {
+    repository: &'r mut Repository<A, S>,
+    working: HashMap<String, Option<Loaded<A>>>,
+    versions: HashMap<String, u64>,
+    pending: Vec<Envelope<A::Event>>,
+}
+
+impl<A, S, E> UnitOfWork<'_, A, S>
+where
+    A: Aggregate + Default + Clone,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>, Error = E>
+        + EventStore<Persistable = Envelope<A::Event>, Error = E>,
+    A::Event: Clone,
+{
+    /// Decide the events of the aggregate from its state including the events recorded so far,
+    /// `None` for an aggregate without events, and record them. Returns the recorded events.
+    pub fn execute(
+        &mut self,
+        id: &A::Id,
+        decide: impl FnOnce(Option<&A>) -> Vec<A::Event>,
+    ) -> Result<Vec<A::Event>, E> {
+        let stream_id = self.working_copy(id)?;
+        let state = self.working[&stream_id].as_ref().map(|l| &l.state);
+        let events = decide(state);
+        self.push(stream_id, &events);
+        Ok(events)
+    }
+
+    /// Record the events of the aggregate.
+    pub fn record(&mut self, id: &A::Id, events: &[A::Event]) -> Result<(), E> {
+        let stream_id = self.working_copy(id)?;
+        self.push(stream_id, events);
+        Ok(())
+    }
+
+    /// Get the events recorded so far, in order.
+    pub fn pending(&self) -> &[Envelope<A::Event>] {
+        &self.pending
+    }
+
+    /// Save the recorded events in one transaction, rolled back if saving or committing fails,
+    /// and apply them to the cached aggregates. Returns the number of events saved.
+    ///
+    /// The events are appended with [`append_multi`](EventStore::append_multi), each stream at
+    /// the version it was loaded at. A stream appended to by other writers since then fails the
+    /// commit with [`AppendError::WrongExpectedVersion`], and its aggregate is evicted.
+    pub fn commit<TE>(self) -> Result<usize, UnitOfWorkError<E, TE>>
+    where
+        S: TransactionManager<Error = TE>,
+    {
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let mut appends = Vec::<(String, ExpectedVersion, Vec<_>)>::new();
+        for envelope in &self.pending {
+            match appends
+                .iter_mut()
+                .find(|(id, _, _)| *id == envelope.stream_id)
+            {
+                Some((_, _, events)) => events.push(envelope.clone()),
+                None => appends.push((
+                    envelope.stream_id.clone(),
+                    ExpectedVersion::Exact(self.versions[&envelope.stream_id]),
+                    vec![envelope.clone()],
+                )),
+            }
+        }
+        let store = &mut self.repository.store;
+        store.begin().map_err(UnitOfWorkError::Transaction)?;
+        if let Err(e) = store.append_multi(&appends) {
+            let _ = store.rollback();
+            if let AppendError::WrongExpectedVersion { stream_id, .. } = &e {
+                self.repository.cache.remove(stream_id);
+            }
+            return Err(UnitOfWorkError::Append(e));
+        }
+        if let Err(e) = store.commit() {
+            let _ = store.rollback();
+            return Err(UnitOfWorkError::Transaction(e));
+        }
+        for (stream_id, loaded) in self.working {
+            if let Some(loaded) = loaded {
+                self.repository.cache.insert(stream_id, loaded);
+            }
+        }
+        Ok(self.pending.len())
+    }
+
+    fn working_copy(&mut self, id: &A::Id) -> Result<String, E> {
+        let stream_id = A::stream_id(id);
+        if !self.working.contains_key(&stream_id) {
+            let loaded = self.repository.load(id)?.cloned();
+            let version = loaded.as_ref().map_or(0, |loaded| loaded.version);
+            self.versions.insert(stream_id.clone(), version);
+            self.working.insert(stream_id.clone(), loaded);
+        }
+        Ok(stream_id)
+    }
+
+    fn push(&mut self, stream_id: String, events: &[A::Event]) {
+        if events.is_empty() {
+            return;
+        }
+        let loaded = self
+            .working
+            .entry(stream_id.clone())
+            .or_default()
+            .get_or_insert_with(|| Loaded {
+                version: 0,
+                state: A::default(),
+            });
+        for event in events {
+            loaded.state.apply(event);
+            loaded.version += 1;
+            self.pending.push(Envelope::new(
+                stream_id.clone(),
+                0,
+                A::event_type(event),
+                event.clone(),
+            ));
+        }
+    }
+}
+
+/// Kind of an infrastructure failure, telling how it can be handled regardless of the backend
+/// it comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreErrorKind {
+    /// A concurrent writer modified the stream first.
+    Conflict,
+    /// A value required to be unique is already taken.
+    UniqueViolation,
+    /// The backend did not answer in time.
+    Timeout,
+    /// The backend could not be reached.
+    Unavailable,
+    /// Any other failure.
+    Other,
+}
+
+impl StoreErrorKind {
+    /// Check whether the operation may succeed if tried again.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            StoreErrorKind::Conflict | StoreErrorKind::Timeout | StoreErrorKind::Unavailable
+        )
+    }
+}
+
+/// Types which represent an infrastructure error able to tell its kind.
+pub trait ClassifiedError {
+    /// Get the kind of the failure.
+    fn kind(&self) -> StoreErrorKind;
+}
+
+impl ClassifiedError for OnMemoryEventStoreError {
+    fn kind(&self) -> StoreErrorKind {
+        StoreErrorKind::Other
+    }
+}
+
+impl ClassifiedError for LockError {
+    fn kind(&self) -> StoreErrorKind {
+        match self {
+            LockError::Timeout(_) => StoreErrorKind::Timeout,
+            LockError::NotLocked(_) => StoreErrorKind::Other,
+            LockError::Backend(_) => StoreErrorKind::Unavailable,
+        }
+    }
+}
+
+impl<E: ClassifiedError> ClassifiedError for AppendError<E> {
+    fn kind(&self) -> StoreErrorKind {
+        match self {
+            AppendError::Unsupported => StoreErrorKind::Other,
+            AppendError::WrongExpectedVersion { .. } => StoreErrorKind::Conflict,
+            AppendError::Store(e) => e.kind(),
+        }
+    }
+}
+
+impl<E: ClassifiedError> ClassifiedError for TransactionalError<E> {
+    fn kind(&self) -> StoreErrorKind {
+        match self {
+            TransactionalError::State(_) => StoreErrorKind::Other,
+            TransactionalError::Store(e) => e.kind(),
+        }
+    }
+}
+
+impl<E: ClassifiedError> ClassifiedError for FaultError<E> {
+    fn kind(&self) -> StoreErrorKind {
+        match self {
+            FaultError::Injected | FaultError::PartialBatch { .. } => StoreErrorKind::Unavailable,
+            FaultError::Inner(e) => e.kind(),
+        }
+    }
+}
+
+impl<E: ClassifiedError> ClassifiedError for ReservationError<E> {
+    fn kind(&self) -> StoreErrorKind {
+        match self {
+            ReservationError::Taken(_) => StoreErrorKind::UniqueViolation,
+            ReservationError::NotHeld(_) => StoreErrorKind::Other,
+            ReservationError::Store(e) => e.kind(),
+            ReservationError::Append(e) => e.kind(),
+        }
+    }
+}
+
+impl<E: ClassifiedError> ClassifiedError for RepositoryError<E> {
+    fn kind(&self) -> StoreErrorKind {
+        match self {
+            RepositoryError::Store(e) => e.kind(),
+            RepositoryError::Append(e) => e.kind(),
+            RepositoryError::Lock(e) => e.kind(),
+        }
+    }
+}
+
+/// Types which translate the failures of a repository into domain errors, such as a unique
+/// violation into `DuplicateUser` or a timeout into `TryAgainLater`.
+pub trait ErrorMapper<E> {
+    /// Associated Type representing the domain error type.
+    type Error;
+
+    /// Translate the failure.
+    fn map_error(&self, error: RepositoryError<E>) -> Self::Error;
+}
+
+impl<E, D, F: Fn(RepositoryError<E>) -> D> ErrorMapper<E> for F {
+    type Error = D;
+
+    fn map_error(&self, error: RepositoryError<E>) -> Self::Error {
+        self(error)
+    }
+}
+
+/// Error returned by [`UnitOfWork::commit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnitOfWorkError<E, TE> {
+    /// The events could not be appended, e.g. because other writers appended to a stream, and
+    /// the transaction was rolled back.
+    Append(AppendError<E>),
+    /// The transaction could not be begun or committed.
+    Transaction(TE),
+}
+
+impl<E: Error, TE: Error> fmt::Display for UnitOfWorkError<E, TE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitOfWorkError::Append(e) => write!(f, "{}", e),
+            UnitOfWorkError::Transaction(e) => write!(f, "transaction error: {}", e),
+        }
+    }
+}
+
+impl<E: Error, TE: Error> Error for UnitOfWorkError<E, TE> {}
+
+/// Error returned by [`Repository::execute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepositoryError<E> {
+    /// The store failed.
+    Store(E),
+    /// The events could not be appended, e.g. because other writers appended to the stream
+    /// since it was loaded.
+    Append(AppendError<E>),
+    /// The stream could not be locked or unlocked.
+    Lock(LockError),
+}
+
+impl<E: Error> fmt::Display for RepositoryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepositoryError::Store(e) => write!(f, "{}", e),
+            RepositoryError::Append(e) => write!(f, "{}", e),
+            RepositoryError::Lock(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for RepositoryError<E> {}
+
+/// Error returned by [`Repository::load_from_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotLoadError<E, NE> {
+    /// The store failed.
+    Store(E),
+    /// The snapshot store failed.
+    Snapshot(NE),
+    /// The aggregate rebuilt from the snapshot differs from the one replayed from the stream.
+    Drift { stream_id: String, version: u64 },
+}
+
+impl<E: Error, NE: Error> fmt::Display for SnapshotLoadError<E, NE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotLoadError::Store(e) => write!(f, "{}", e),
+            SnapshotLoadError::Snapshot(e) => write!(f, "snapshot store error: {}", e),
+            SnapshotLoadError::Drift { stream_id, version } => write!(
+                f,
+                "snapshot of {} at version {} drifted from the replay",
+                stream_id, version
+            ),
+        }
+    }
+}
+
+impl<E: Error, NE: Error> Error for SnapshotLoadError<E, NE> {}
@@ -0,0 +1,550 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use super::*;
+use crate::event_store::{OnMemoryEventStore, OnMemoryEventStoreError};
+use crate::faulty::{FaultConfig, FaultyEventStore};
+use crate::replica::ReplicatedEventStore;
+use crate::snapshot::{OnMemorySnapshotStore, Snapshot};
+use crate::stream_lock::InProcessLockMap;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Account {
+    balance: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AccountEvent {
+    Deposited(i64),
+    Withdrawn(i64),
+}
+
+impl Aggregate for Account {
+    type Id = u32;
+    type Event = AccountEvent;
+
+    fn aggregate_type() -> &'static str {
+        "account"
+    }
+
+    fn event_type(event: &AccountEvent) -> String {
+        match event {
+            AccountEvent::Deposited(_) => "Deposited".to_string(),
+            AccountEvent::Withdrawn(_) => "Withdrawn".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &AccountEvent) {
+        match event {
+            AccountEvent::Deposited(amount) => self.balance += amount,
+            AccountEvent::Withdrawn(amount) => self.balance -= amount,
+        }
+    }
+}
+
+/// Store counting the round trips made to it.
+#[derive(Default)]
+struct CountingStore {
+    inner: OnMemoryEventStore<AccountEvent>,
+    loads: Cell<usize>,
+    ranged_loads: Cell<usize>,
+    multi_reads: Cell<usize>,
+    failing_commit: bool,
+}
+
+impl EventStore for CountingStore {
+    type Persistable = Envelope<AccountEvent>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.inner.save(events)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.inner.append_multi(appends)
+    }
+}
+
+impl EventLoader for CountingStore {
+    type StreamId = String;
+    type Persistable = Envelope<AccountEvent>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.loads.set(self.loads.get() + 1);
+        self.inner.load(stream_id)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &String,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.ranged_loads.set(self.ranged_loads.get() + 1);
+        self.inner.load_after(stream_id, version)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[String],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.multi_reads.set(self.multi_reads.get() + 1);
+        self.inner.read_multi(stream_ids)
+    }
+}
+
+impl TransactionManager for CountingStore {
+    type Error = OnMemoryEventStoreError;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.inner.begin()
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        if self.failing_commit {
+            return Err(OnMemoryEventStoreError::NoActiveTransaction);
+        }
+        self.inner.commit()
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.inner.rollback()
+    }
+}
+
+#[test]
+fn test_load_and_save() {
+    let mut repository = Repository::<Account, _>::new(OnMemoryEventStore::new());
+    assert_eq!(repository.load(&1).unwrap(), None);
+
+    repository
+        .save(
+            &1,
+            &[AccountEvent::Deposited(100), AccountEvent::Withdrawn(30)],
+        )
+        .unwrap();
+    let loaded = repository.load(&1).unwrap().unwrap();
+    assert_eq!(loaded.version, 2);
+    assert_eq!(loaded.state.balance, 70);
+
+    // The cached aggregate follows the saved events.
+    repository.save(&1, &[AccountEvent::Deposited(5)]).unwrap();
+    let cached = repository.cached(&1).unwrap();
+    assert_eq!((cached.version, cached.state.balance), (3, 75));
+    repository.evict(&1);
+    assert!(repository.cached(&1).is_none());
+    assert_eq!(repository.load(&1).unwrap().unwrap().state.balance, 75);
+}
+
+#[test]
+fn test_preload_reads_streams_in_one_call() {
+    let mut repository = Repository::<Account, _>::new(CountingStore::default());
+    for id in 0..5 {
+        repository
+            .save(&id, &[AccountEvent::Deposited(id as i64 * 10)])
+            .unwrap();
+    }
+
+    assert_eq!(repository.preload(&[0, 1, 2, 2, 3, 4, 99]).unwrap(), 5);
+    assert_eq!(repository.store().multi_reads.get(), 1);
+    // Cached aggregates are neither read again nor loaded one by one.
+    assert_eq!(repository.preload(&[1, 2]).unwrap(), 0);
+    assert_eq!(repository.store().multi_reads.get(), 1);
+    assert_eq!(repository.load(&4).unwrap().unwrap().state.balance, 40);
+    assert_eq!(repository.store().loads.get(), 0);
+}
+
+#[test]
+fn test_execute_with_stream_lock() {
+    let locks = Arc::new(InProcessLockMap::new().with_timeout(Duration::from_millis(10)));
+    let mut repository =
+        Repository::<Account, _>::new(OnMemoryEventStore::new()).with_stream_lock(locks.clone());
+
+    let saved = repository
+        .execute(&1, |account| {
+            assert!(account.is_none());
+            vec![AccountEvent::Deposited(100)]
+        })
+        .unwrap();
+    assert_eq!(saved, vec![AccountEvent::Deposited(100)]);
+    assert!(!locks.is_locked("account-1"));
+
+    repository
+        .execute(&1, |account| {
+            vec![AccountEvent::Withdrawn(account.unwrap().balance / 2)]
+        })
+        .unwrap();
+    assert_eq!(repository.cached(&1).unwrap().state.balance, 50);
+
+    locks.lock_stream("account-1").unwrap();
+    assert_eq!(
+        repository.execute(&1, |_| vec![AccountEvent::Deposited(1)]),
+        Err(RepositoryError::Lock(LockError::Timeout(
+            "account-1".to_string()
+        )))
+    );
+    assert_eq!(repository.load(&1).unwrap().unwrap().version, 2);
+    locks.unlock_stream("account-1").unwrap();
+
+    // A panic while deciding does not leave the stream locked.
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        repository.execute(&1, |_| panic!("decide failed"))
+    }));
+    assert!(panicked.is_err());
+    assert!(!locks.is_locked("account-1"));
+}
+
+fn account_store() -> OnMemoryEventStore<AccountEvent> {
+    let mut store = OnMemoryEventStore::new();
+    let events = [
+        AccountEvent::Deposited(10),
+        AccountEvent::Withdrawn(3),
+        AccountEvent::Deposited(5),
+    ];
+    for event in events {
+        let event_type = Account::event_type(&event);
+        store
+            .save(&[Envelope::new("account-1", 0, event_type, event)])
+            .unwrap();
+    }
+    store
+}
+
+#[test]
+fn test_load_from_snapshot_with_verification() {
+    let mut snapshots = OnMemorySnapshotStore::new();
+    snapshots
+        .save(Snapshot {
+            stream_id: "account-1".to_string(),
+            version: 2,
+            state: Account { balance: 7 },
+        })
+        .unwrap();
+    let mut repository =
+        Repository::<Account, _>::new(account_store()).with_snapshot_verification();
+    assert_eq!(
+        repository.load_from_snapshot(&1, &snapshots).unwrap(),
+        Some(&Loaded {
+            version: 3,
+            state: Account { balance: 12 }
+        })
+    );
+
+    // A snapshot which drifted from the events is flagged instead of being served.
+    snapshots
+        .save(Snapshot {
+            stream_id: "account-1".to_string(),
+            version: 3,
+            state: Account { balance: 15 },
+        })
+        .unwrap();
+    repository.evict(&1);
+    assert_eq!(
+        repository.load_from_snapshot(&1, &snapshots),
+        Err(SnapshotLoadError::Drift {
+            stream_id: "account-1".to_string(),
+            version: 3
+        })
+    );
+    let mut unverified = Repository::<Account, _>::new(account_store());
+    assert_eq!(
+        unverified
+            .load_from_snapshot(&1, &snapshots)
+            .unwrap()
+            .unwrap()
+            .state,
+        Account { balance: 15 }
+    );
+}
+
+#[derive(Debug, PartialEq)]
+enum AccountError {
+    TryAgainLater,
+    Internal(String),
+}
+
+fn to_domain<E: ClassifiedError + Error>(error: RepositoryError<E>) -> AccountError {
+    if error.kind().is_transient() {
+        AccountError::TryAgainLater
+    } else {
+        AccountError::Internal(error.to_string())
+    }
+}
+
+#[test]
+fn test_execute_mapped() {
+    let store = FaultyEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultConfig {
+            error_rate: 1.0,
+            ..FaultConfig::default()
+        },
+    );
+    let mut repository = Repository::<Account, _>::new(store);
+    assert_eq!(
+        repository.execute_mapped(&1, |_| vec![AccountEvent::Deposited(1)], &to_domain),
+        Err(AccountError::TryAgainLater)
+    );
+
+    let locks = Arc::new(InProcessLockMap::new().with_timeout(Duration::from_millis(10)));
+    let mut repository =
+        Repository::<Account, _>::new(OnMemoryEventStore::new()).with_stream_lock(locks.clone());
+    locks.lock_stream("account-1").unwrap();
+    assert_eq!(
+        repository.execute_mapped(&1, |_| Vec::new(), &to_domain),
+        Err(AccountError::TryAgainLater)
+    );
+    assert_eq!(
+        RepositoryError::<OnMemoryEventStoreError>::Lock(LockError::NotLocked(
+            "account-1".to_string()
+        ))
+        .kind(),
+        StoreErrorKind::Other
+    );
+    assert_eq!(
+        to_domain(RepositoryError::Store(
+            OnMemoryEventStoreError::NoActiveTransaction
+        )),
+        AccountError::Internal("no transaction is active".to_string())
+    );
+}
+
+#[test]
+fn test_unit_of_work() {
+    let mut repository = Repository::<Account, _>::new(OnMemoryEventStore::new());
+    repository.save(&1, &[AccountEvent::Deposited(10)]).unwrap();
+
+    let mut unit = repository.unit_of_work();
+    unit.record(&2, &[AccountEvent::Deposited(50)]).unwrap();
+    // The transfer sees the deposit recorded before it.
+    unit.execute(&2, |account| {
+        vec![AccountEvent::Withdrawn(account.unwrap().balance / 2)]
+    })
+    .unwrap();
+    unit.execute(&1, |account| {
+        vec![AccountEvent::Deposited(account.unwrap().balance + 25)]
+    })
+    .unwrap();
+    assert_eq!(unit.pending().len(), 3);
+    assert_eq!(unit.commit(), Ok(3));
+
+    assert_eq!(
+        repository.cached(&1),
+        Some(&Loaded {
+            version: 2,
+            state: Account { balance: 45 }
+        })
+    );
+    assert_eq!(repository.cached(&2).unwrap().state.balance, 25);
+    assert_eq!(repository.store().stream_len("account-2"), 2);
+
+    // Events of a dropped unit of work are never saved.
+    let mut unit = repository.unit_of_work();
+    unit.record(&1, &[AccountEvent::Withdrawn(45)]).unwrap();
+    drop(unit);
+    assert_eq!(repository.store().stream_len("account-1"), 2);
+    assert_eq!(
+        repository
+            .unit_of_work()
+            .commit::<OnMemoryEventStoreError>(),
+        Ok(0)
+    );
+}
+
+#[test]
+fn test_load_with_consistency() {
+    let mut repository = Repository::<Account, _>::new(ReplicatedEventStore::new(
+        OnMemoryEventStore::new(),
+        OnMemoryEventStore::new(),
+    ));
+    repository.save(&1, &[AccountEvent::Deposited(10)]).unwrap();
+
+    // Not replicated yet: only the strong read sees the deposit.
+    assert_eq!(
+        repository.load_with(&1, ReadConsistency::Eventual).unwrap(),
+        None
+    );
+    assert!(repository.cached(&1).is_none());
+    let strong = repository.load_with(&1, ReadConsistency::Strong).unwrap();
+    assert_eq!(
+        strong,
+        Some(Loaded {
+            version: 1,
+            state: Account { balance: 10 }
+        })
+    );
+    // Eventual reads are then served by the refreshed cache.
+    assert_eq!(
+        repository.load_with(&1, ReadConsistency::Eventual).unwrap(),
+        strong
+    );
+    assert_eq!(repository.store().fallbacks(), 1);
+}
+
+#[test]
+fn test_unit_of_work_rolls_back_failed_commit() {
+    let mut repository = Repository::<Account, _>::new(CountingStore {
+        failing_commit: true,
+        ..CountingStore::default()
+    });
+    let mut unit = repository.unit_of_work();
+    unit.record(&1, &[AccountEvent::Deposited(10)]).unwrap();
+    assert_eq!(
+        unit.commit(),
+        Err(UnitOfWorkError::Transaction(
+            OnMemoryEventStoreError::NoActiveTransaction
+        ))
+    );
+    assert_eq!(repository.cached(&1), None);
+
+    // The transaction was rolled back, so the next one can begin.
+    repository.store_mut().failing_commit = false;
+    let mut unit = repository.unit_of_work();
+    unit.record(&1, &[AccountEvent::Deposited(20)]).unwrap();
+    assert_eq!(unit.commit(), Ok(1));
+    assert_eq!(repository.store().inner.stream_len("account-1"), 1);
+}
+
+#[test]
+fn test_stale_writers_conflict() {
+    let mut repository = Repository::<Account, _>::new(OnMemoryEventStore::new());
+    repository.save(&1, &[AccountEvent::Deposited(10)]).unwrap();
+    assert_eq!(repository.load(&1).unwrap().unwrap().version, 1);
+    // Another writer appends to the stream behind the cached aggregate.
+    repository
+        .store_mut()
+        .save(&[Envelope::new(
+            "account-1",
+            0,
+            "Withdrawn",
+            AccountEvent::Withdrawn(10),
+        )])
+        .unwrap();
+
+    let mut unit = repository.unit_of_work();
+    unit.execute(&1, |account| {
+        vec![AccountEvent::Withdrawn(account.unwrap().balance)]
+    })
+    .unwrap();
+    assert_eq!(
+        unit.commit::<OnMemoryEventStoreError>(),
+        Err(UnitOfWorkError::Append(AppendError::WrongExpectedVersion {
+            stream_id: "account-1".to_string(),
+            expected: ExpectedVersion::Exact(1),
+            actual: 2,
+        }))
+    );
+    assert_eq!(repository.store().stream_len("account-1"), 2);
+    // The stale aggregate was evicted, so the retry sees the other writer's events.
+    assert_eq!(repository.cached(&1), None);
+    let mut unit = repository.unit_of_work();
+    unit.execute(&1, |account| {
+        assert_eq!(account.unwrap().balance, 0);
+        vec![AccountEvent::Deposited(5)]
+    })
+    .unwrap();
+    assert_eq!(unit.commit::<OnMemoryEventStoreError>(), Ok(1));
+
+    repository
+        .store_mut()
+        .save(&[Envelope::new(
+            "account-1",
+            0,
+            "Deposited",
+            AccountEvent::Deposited(1),
+        )])
+        .unwrap();
+    assert_eq!(
+        repository.save(&1, &[AccountEvent::Withdrawn(5)]),
+        Err(AppendError::WrongExpectedVersion {
+            stream_id: "account-1".to_string(),
+            expected: ExpectedVersion::Exact(3),
+            actual: 4,
+        })
+    );
+    assert_eq!(repository.cached(&1), None);
+    assert_eq!(
+        repository.execute(&1, |account| {
+            vec![AccountEvent::Withdrawn(account.unwrap().balance)]
+        }),
+        Ok(vec![AccountEvent::Withdrawn(6)])
+    );
+    assert_eq!(repository.store().stream_len("account-1"), 5);
+}
+
+#[test]
+fn test_load_from_snapshot_reads_events_after_it() {
+    let mut snapshots = OnMemorySnapshotStore::new();
+    snapshots
+        .save(Snapshot {
+            stream_id: "account-1".to_string(),
+            version: 2,
+            state: Account { balance: 7 },
+        })
+        .unwrap();
+    let mut repository = Repository::<Account, _>::new(CountingStore {
+        inner: account_store(),
+        ..CountingStore::default()
+    });
+    assert_eq!(
+        repository.load_from_snapshot(&1, &snapshots).unwrap(),
+        Some(&Loaded {
+            version: 3,
+            state: Account { balance: 12 }
+        })
+    );
+    assert_eq!(repository.store().ranged_loads.get(), 1);
+    assert_eq!(repository.store().loads.get(), 0);
+
+    // Only the verification replays the whole stream.
+    let mut repository = Repository::<Account, _>::new(CountingStore {
+        inner: account_store(),
+        ..CountingStore::default()
+    })
+    .with_snapshot_verification();
+    repository.load_from_snapshot(&1, &snapshots).unwrap();
+    assert_eq!(repository.store().ranged_loads.get(), 1);
+    assert_eq!(repository.store().loads.get(), 1);
+}
+
+/// Lock whose streams cannot be unlocked.
+struct StuckLock;
+
+impl StreamLock for StuckLock {
+    fn lock_stream(&self, _stream_id: &str) -> Result<(), LockError> {
+        Ok(())
+    }
+
+    fn unlock_stream(&self, _stream_id: &str) -> Result<(), LockError> {
+        Err(LockError::Backend("connection lost".to_string()))
+    }
+}
+
+#[test]
+fn test_execute_reports_store_failure_before_unlock_failure() {
+    let store = FaultyEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultConfig {
+            error_rate: 1.0,
+            ..FaultConfig::default()
+        },
+    );
+    let mut repository = Repository::<Account, _>::new(store).with_stream_lock(Arc::new(StuckLock));
+    assert_eq!(
+        repository.execute(&1, |_| vec![AccountEvent::Deposited(1)]),
+        Err(RepositoryError::Store(FaultError::Injected))
+    );
+
+    let mut repository = Repository::<Account, _>::new(OnMemoryEventStore::new())
+        .with_stream_lock(Arc::new(StuckLock));
+    assert_eq!(
+        repository.execute(&1, |_| vec![AccountEvent::Deposited(1)]),
+        Err(RepositoryError::Lock(LockError::Backend(
+            "connection lost".to_string()
+        )))
+    );
+    assert_eq!(repository.store().stream_len("account-1"), 1);
+}
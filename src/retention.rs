@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, OnMemoryEventStore};
+use crate::runtime::HousekeepingJob;
+use crate::sharding::category_of;
+
+/// Retention rules of a stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Delete the events older than the duration.
+    pub max_age: Option<Duration>,
+    /// Keep only the latest events.
+    pub max_count: Option<usize>,
+    /// Delete the whole stream.
+    pub tombstoned: bool,
+}
+
+/// Types which represent an event store able to physically delete events.
+pub trait PrunableEventStore<E> {
+    /// Get the IDs of the streams holding events.
+    fn stream_ids(&self) -> Vec<String>;
+    /// Delete the events of the stream matching the predicate, returning them.
+    fn delete_where(
+        &mut self,
+        stream_id: &str,
+        predicate: &mut dyn FnMut(&Envelope<E>) -> bool,
+    ) -> Vec<Envelope<E>>;
+    /// Get the number of events of the stream.
+    fn event_count(&self, stream_id: &str) -> usize;
+}
+
+impl<E: Clone> PrunableEventStore<E> for OnMemoryEventStore<E> {
+    fn stream_ids(&self) -> Vec<String> {
+        OnMemoryEventStore::stream_ids(self)
+    }
+
+    fn delete_where(
+        &mut self,
+        stream_id: &str,
+        predicate: &mut dyn FnMut(&Envelope<E>) -> bool,
+    ) -> Vec<Envelope<E>> {
+        OnMemoryEventStore::delete_where(self, stream_id, predicate)
+    }
+
+    fn event_count(&self, stream_id: &str) -> usize {
+        self.stream_len(stream_id)
+    }
+}
+
+/// Types which receive the events removed by the [`RetentionEnforcer`].
+pub trait Archive<E> {
+    /// Archive the events.
+    fn archive(&mut self, events: Vec<Envelope<E>>) -> Result<(), String>;
+}
+
+impl<E> Archive<E> for Vec<Envelope<E>> {
+    fn archive(&mut self, events: Vec<Envelope<E>>) -> Result<(), String> {
+        self.extend(events);
+        Ok(())
+    }
+}
+
+type TimestampFn<E> = Box<dyn Fn(&Envelope<E>) -> Option<SystemTime>>;
+
+/// Housekeeping job enforcing retention policies by deleting, or archiving, expired events.
+///
+/// Policies set on a stream take precedence over the policies set on its category.
+pub struct RetentionEnforcer<E> {
+    stream_policies: HashMap<String, RetentionPolicy>,
+    category_policies: HashMap<String, RetentionPolicy>,
+    timestamp: TimestampFn<E>,
+    archive: Option<Box<dyn Archive<E>>>,
+}
+
+impl<E> RetentionEnforcer<E> {
+    /// Create an enforcer reading the time of the events with `timestamp`, used by `max_age`.
+    pub fn new(timestamp: impl Fn(&Envelope<E>) -> Option<SystemTime> + 'static) -> Self {
+        Self {
+            stream_policies: HashMap::new(),
+            category_policies: HashMap::new(),
+            timestamp: Box::new(timestamp),
+            archive: None,
+        }
+    }
+
+    /// Set the policy of the stream.
+    pub fn stream_policy(mut self, stream_id: impl Into<String>, policy: RetentionPolicy) -> Self {
+        self.stream_policies.insert(stream_id.into(), policy);
+        self
+    }
+
+    /// Set the policy of the streams of the category.
+    pub fn category_policy(mut self, category: impl Into<String>, policy: RetentionPolicy) -> Self {
+        self.category_policies.insert(category.into(), policy);
+        self
+    }
+
+    /// Move the expired events to the archive instead of discarding them.
+    pub fn archive_to(mut self, archive: impl Archive<E> + 'static) -> Self {
+        self.archive = Some(Box::new(archive));
+        self
+    }
+
+    /// Get the policy applying to the stream.
+    pub fn policy_of(&self, stream_id: &str) -> Option<&RetentionPolicy> {
+        self.stream_policies
+            .get(stream_id)
+            .or_else(|| self.category_policies.get(category_of(stream_id)))
+    }
+
+    /// Enforce the policies on the store, returning the number of events removed.
+    ///
+    /// Expired events are archived before being deleted, so a failing archive leaves them in
+    /// the store.
+    pub fn enforce<S>(&mut self, store: &mut S, now: SystemTime) -> Result<usize, String>
+    where
+        E: Clone,
+        S: PrunableEventStore<E> + EventLoader<StreamId = String, Persistable = Envelope<E>>,
+    {
+        let mut removed = 0;
+        for stream_id in store.stream_ids() {
+            let policy = match self.policy_of(&stream_id) {
+                Some(policy) => *policy,
+                None => continue,
+            };
+            let events = EventLoader::load(store, &stream_id).map_err(|e| e.to_string())?;
+            let keep_from = policy
+                .max_count
+                .map_or(0, |max| events.len().saturating_sub(max));
+            let timestamp = &self.timestamp;
+            let expired = events
+                .into_iter()
+                .enumerate()
+                .filter(|(index, event)| {
+                    let too_old = policy.max_age.is_some_and(|max_age| {
+                        timestamp(event).is_some_and(|t| t + max_age <= now)
+                    });
+                    policy.tombstoned || *index < keep_from || too_old
+                })
+                .map(|(_, event)| event)
+                .collect::<Vec<_>>();
+            if expired.is_empty() {
+                continue;
+            }
+            let positions = expired.iter().map(|e| e.position).collect::<HashSet<_>>();
+            if let Some(archive) = self.archive.as_mut() {
+                archive.archive(expired)?;
+            }
+            removed += store
+                .delete_where(&stream_id, &mut |event| positions.contains(&event.position))
+                .len();
+        }
+        Ok(removed)
+    }
+}
+
+impl<E, S> HousekeepingJob<S> for RetentionEnforcer<E>
+where
+    E: Clone,
+    S: PrunableEventStore<E> + EventLoader<StreamId = String, Persistable = Envelope<E>>,
+{
+    fn name(&self) -> &'static str {
+        "retention"
+    }
+
+    fn run(&mut self, store: &mut S, now: SystemTime) -> Result<usize, String> {
+        self.enforce(store, now)
+    }
+}
@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::UNIX_EPOCH;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::event_store::{EventLoader, EventStore};
+use crate::runtime::CruxRuntime;
+
+struct SharedArchive(Rc<RefCell<Vec<Envelope<u64>>>>);
+
+impl Archive<u64> for SharedArchive {
+    fn archive(&mut self, events: Vec<Envelope<u64>>) -> Result<(), String> {
+        self.0.borrow_mut().extend(events);
+        Ok(())
+    }
+}
+
+// The payload is the time the event occurred at, in seconds.
+fn enforcer() -> RetentionEnforcer<u64> {
+    RetentionEnforcer::new(|event: &Envelope<u64>| {
+        Some(UNIX_EPOCH + Duration::from_secs(event.payload))
+    })
+}
+
+fn store() -> OnMemoryEventStore<u64> {
+    let mut store = OnMemoryEventStore::new();
+    for at in 0..5 {
+        for stream_id in ["order-1", "order-2", "user-1", "session-1"] {
+            store
+                .save(&[Envelope::new(stream_id, 0, "Happened", at * 10)])
+                .unwrap();
+        }
+    }
+    store
+}
+
+fn payloads(store: &OnMemoryEventStore<u64>, stream_id: &str) -> Vec<u64> {
+    store
+        .load(&stream_id.to_string())
+        .unwrap()
+        .into_iter()
+        .map(|e| e.payload)
+        .collect()
+}
+
+#[test]
+fn test_enforce_policies() {
+    let mut store = store();
+    let mut enforcer = enforcer()
+        .category_policy(
+            "order",
+            RetentionPolicy {
+                max_count: Some(2),
+                ..RetentionPolicy::default()
+            },
+        )
+        .stream_policy(
+            "order-2",
+            RetentionPolicy {
+                max_age: Some(Duration::from_secs(25)),
+                ..RetentionPolicy::default()
+            },
+        )
+        .stream_policy(
+            "session-1",
+            RetentionPolicy {
+                tombstoned: true,
+                ..RetentionPolicy::default()
+            },
+        );
+
+    let removed = enforcer
+        .enforce(&mut store, UNIX_EPOCH + Duration::from_secs(50))
+        .unwrap();
+
+    assert_eq!(removed, 3 + 3 + 5);
+    assert_eq!(payloads(&store, "order-1"), vec![30, 40]);
+    assert_eq!(payloads(&store, "order-2"), vec![30, 40]);
+    assert_eq!(payloads(&store, "user-1"), vec![0, 10, 20, 30, 40]);
+    assert!(payloads(&store, "session-1").is_empty());
+    assert_eq!(store.stream_ids(), vec!["order-1", "order-2", "user-1"]);
+    // Versions survive the deletion.
+    assert_eq!(store.version("order-1"), 5);
+}
+
+#[test]
+fn test_archive_expired_events() {
+    let archived = Rc::new(RefCell::new(Vec::new()));
+    let mut store = store();
+    let mut enforcer = enforcer()
+        .stream_policy(
+            "user-1",
+            RetentionPolicy {
+                max_count: Some(4),
+                ..RetentionPolicy::default()
+            },
+        )
+        .archive_to(SharedArchive(archived.clone()));
+
+    assert_eq!(enforcer.enforce(&mut store, UNIX_EPOCH).unwrap(), 1);
+    assert_eq!(enforcer.enforce(&mut store, UNIX_EPOCH).unwrap(), 0);
+    let archived = archived.borrow();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].stream_id, "user-1");
+    assert_eq!(archived[0].version, 1);
+}
+
+#[test]
+fn test_run_as_housekeeping_job() {
+    let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(35));
+    let enforcer = enforcer().category_policy(
+        "user",
+        RetentionPolicy {
+            max_age: Some(Duration::from_secs(20)),
+            ..RetentionPolicy::default()
+        },
+    );
+    let mut runtime = CruxRuntime::new(store(), clock.clone()).with_job(enforcer);
+
+    let reports = runtime.run_housekeeping();
+    assert_eq!(reports[0].name, "retention");
+    assert_eq!(reports[0].result, Ok(2));
+    assert_eq!(payloads(runtime.store(), "user-1"), vec![20, 30, 40]);
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(runtime.run_housekeeping()[0].result, Ok(1));
+}
+
+struct FailingArchive;
+
+impl Archive<u64> for FailingArchive {
+    fn archive(&mut self, _: Vec<Envelope<u64>>) -> Result<(), String> {
+        Err("archive unavailable".to_string())
+    }
+}
+
+#[test]
+fn test_keep_expired_events_when_archive_fails() {
+    let mut store = store();
+    let mut enforcer = enforcer()
+        .stream_policy(
+            "user-1",
+            RetentionPolicy {
+                tombstoned: true,
+                ..RetentionPolicy::default()
+            },
+        )
+        .archive_to(FailingArchive);
+
+    assert_eq!(
+        enforcer.enforce(&mut store, UNIX_EPOCH),
+        Err("archive unavailable".to_string())
+    );
+    assert_eq!(payloads(&store, "user-1"), vec![0, 10, 20, 30, 40]);
+}
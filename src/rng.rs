@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests;
+
+/// Small deterministic pseudo-random number generator (xorshift64*), for simulations and
+/// fault injection. Not suitable for cryptography.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Create a generator from the seed. The same seed always yields the same sequence.
+    pub fn new(seed: u64) -> Self {
+        // Mix the seed with splitmix64, so that close seeds yield unrelated sequences.
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        // A zero state would only ever yield zeros, so the one seed mixed into it is replaced.
+        if state == 0 {
+            state = 0x9e37_79b9_7f4a_7c15;
+        }
+        Self { state }
+    }
+
+    /// Get the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Get a random number in `0..bound`. Returns 0 if `bound` is 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        self.next_u64() % bound
+    }
+
+    /// Get `true` with the probability, between 0 and 1.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        let sample = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < probability
+    }
+}
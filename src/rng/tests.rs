@@ -0,0 +1,31 @@
+use super::*;
+
+#[test]
+fn test_seeded_rng_is_deterministic() {
+    let mut a = SeededRng::new(42);
+    let mut b = SeededRng::new(42);
+    let mut c = SeededRng::new(43);
+    let a = (0..10).map(|_| a.next_u64()).collect::<Vec<_>>();
+    let b = (0..10).map(|_| b.next_u64()).collect::<Vec<_>>();
+    let c = (0..10).map(|_| c.next_u64()).collect::<Vec<_>>();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn test_seeded_rng_ranges() {
+    let mut rng = SeededRng::new(0);
+    assert!((0..1000).all(|_| rng.below(6) < 6));
+    assert!((0..1000).all(|_| !rng.chance(0.0)));
+    assert!((0..1000).all(|_| rng.chance(1.0)));
+    let hits = (0..10_000).filter(|_| rng.chance(0.25)).count();
+    assert!((2_000..3_000).contains(&hits));
+}
+
+#[test]
+fn test_seeded_rng_never_stuck_at_zero() {
+    for seed in [0, 0x9e37_79b9_7f4a_7c15, 0x61c8_8646_80b5_83eb, u64::MAX] {
+        let mut rng = SeededRng::new(seed);
+        assert!((0..10).any(|_| rng.next_u64() != 0), "seed {:#x}", seed);
+    }
+}
@@ -0,0 +1,268 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+use crate::registry::EventTypeRegistry;
+use crate::sharding::stable_hash;
+
+/// Metadata key recording the version of the shape the payload was written in.
+pub const SCHEMA_VERSION_METADATA_KEY: &str = "schema_version";
+/// Metadata key recording the tenant the event belongs to.
+pub const TENANT_METADATA_KEY: &str = "tenant";
+
+type ConvertFn<E> = Box<dyn Fn(E) -> E>;
+
+/// Runtime flag deciding which aggregates a rollout is enabled for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RolloutFlag {
+    /// Enabled for no aggregate.
+    Disabled,
+    /// Enabled for every aggregate.
+    Enabled,
+    /// Enabled for the aggregates of the tenants.
+    Tenants(HashSet<String>),
+    /// Enabled for the percentage of the aggregates, chosen by a stable hash of their stream
+    /// so that an aggregate never goes back to the old shape as the percentage grows.
+    Percentage(u8),
+}
+
+impl RolloutFlag {
+    /// Check whether the flag is enabled for the stream of the tenant.
+    pub fn is_enabled(&self, tenant: Option<&str>, stream_id: &str) -> bool {
+        match self {
+            RolloutFlag::Disabled => false,
+            RolloutFlag::Enabled => true,
+            RolloutFlag::Tenants(tenants) => tenant.is_some_and(|t| tenants.contains(t)),
+            RolloutFlag::Percentage(percentage) => {
+                stable_hash(stream_id) % 100 < u64::from(*percentage)
+            }
+        }
+    }
+}
+
+/// Gradual rollout of a new shape of an event type.
+///
+/// The application produces the new shape only. Until the flag is enabled for an aggregate,
+/// its events are written in the old shape, which every deployed version can read; events
+/// read in the old shape are upcast, so the application handles the new shape only too.
+pub struct EventShapeRollout<E> {
+    event_type: String,
+    old_version: u32,
+    new_version: u32,
+    flag: RolloutFlag,
+    downcast: ConvertFn<E>,
+    upcast: ConvertFn<E>,
+}
+
+impl<E> EventShapeRollout<E> {
+    /// Roll out the new version of the event type, converting payloads to the old shape with
+    /// `downcast` and back with `upcast`. Both versions must be registered.
+    pub fn new(
+        registry: &EventTypeRegistry,
+        event_type: impl Into<String>,
+        (old_version, new_version): (u32, u32),
+        downcast: impl Fn(E) -> E + 'static,
+        upcast: impl Fn(E) -> E + 'static,
+    ) -> Result<Self, RolloutError> {
+        let event_type = event_type.into();
+        for version in [old_version, new_version] {
+            if registry.get(&event_type, version).is_none() {
+                return Err(RolloutError::NotRegistered {
+                    name: event_type,
+                    version,
+                });
+            }
+        }
+        Ok(Self {
+            event_type,
+            old_version,
+            new_version,
+            flag: RolloutFlag::Disabled,
+            downcast: Box::new(downcast),
+            upcast: Box::new(upcast),
+        })
+    }
+
+    /// Get the flag.
+    pub fn flag(&self) -> &RolloutFlag {
+        &self.flag
+    }
+
+    /// Change the flag, affecting the events written from now on.
+    pub fn set_flag(&mut self, flag: RolloutFlag) {
+        self.flag = flag;
+    }
+
+    /// Get the envelope to write for the event of the new shape, recording its version.
+    pub fn emit(&self, envelope: Envelope<E>) -> Envelope<E> {
+        if envelope.event_type != self.event_type {
+            return envelope;
+        }
+        let tenant = envelope
+            .metadata
+            .get(TENANT_METADATA_KEY)
+            .map(String::as_str);
+        if self.flag.is_enabled(tenant, &envelope.stream_id) {
+            envelope.with_metadata(SCHEMA_VERSION_METADATA_KEY, self.new_version.to_string())
+        } else {
+            envelope
+                .map(&self.downcast)
+                .with_metadata(SCHEMA_VERSION_METADATA_KEY, self.old_version.to_string())
+        }
+    }
+
+    /// Get the event read from the envelope in the new shape. Events without a recorded
+    /// version are written before the rollout, so in the old shape.
+    pub fn upcast(&self, envelope: Envelope<E>) -> Envelope<E> {
+        if envelope.event_type != self.event_type {
+            return envelope;
+        }
+        let version = envelope
+            .metadata
+            .get(SCHEMA_VERSION_METADATA_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(self.old_version);
+        if version != self.old_version {
+            return envelope;
+        }
+        envelope
+            .map(&self.upcast)
+            .with_metadata(SCHEMA_VERSION_METADATA_KEY, self.new_version.to_string())
+    }
+}
+
+/// Event store applying rollouts: events are saved in the shape the flags choose, and loaded
+/// in the new shape.
+pub struct RolloutEventStore<S, E> {
+    inner: S,
+    rollouts: Vec<EventShapeRollout<E>>,
+}
+
+impl<S, E> RolloutEventStore<S, E> {
+    /// Apply no rollout to the store yet.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            rollouts: Vec::new(),
+        }
+    }
+
+    /// Apply the rollout.
+    pub fn with_rollout(mut self, rollout: EventShapeRollout<E>) -> Self {
+        self.rollouts.push(rollout);
+        self
+    }
+
+    /// Get the rollout of the event type, to change its flag.
+    pub fn rollout_mut(&mut self, event_type: &str) -> Option<&mut EventShapeRollout<E>> {
+        self.rollouts
+            .iter_mut()
+            .find(|r| r.event_type == event_type)
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn emit(&self, envelope: &Envelope<E>) -> Envelope<E>
+    where
+        E: Clone,
+    {
+        self.rollouts
+            .iter()
+            .fold(envelope.clone(), |envelope, rollout| rollout.emit(envelope))
+    }
+
+    fn upcast(&self, envelope: Envelope<E>) -> Envelope<E> {
+        self.rollouts
+            .iter()
+            .fold(envelope, |envelope, rollout| rollout.upcast(envelope))
+    }
+}
+
+impl<S, E> EventStore for RolloutEventStore<S, E>
+where
+    S: EventStore<Persistable = Envelope<E>>,
+    E: Clone,
+{
+    type Persistable = Envelope<E>;
+    type Error = S::Error;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let events = events.iter().map(|e| self.emit(e)).collect::<Vec<_>>();
+        self.inner.save(&events)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let appends = appends
+            .iter()
+            .map(|(stream_id, expected, events)| {
+                let events = events.iter().map(|e| self.emit(e)).collect();
+                (stream_id.clone(), *expected, events)
+            })
+            .collect::<Vec<_>>();
+        self.inner.append_multi(&appends)
+    }
+}
+
+impl<S, E> EventLoader for RolloutEventStore<S, E>
+where
+    S: EventLoader<Persistable = Envelope<E>>,
+{
+    type StreamId = S::StreamId;
+    type Persistable = Envelope<E>;
+    type Error = S::Error;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let events = self.inner.load(stream_id)?;
+        Ok(events.into_iter().map(|e| self.upcast(e)).collect())
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let events = self.inner.load_after(stream_id, version)?;
+        Ok(events.into_iter().map(|e| self.upcast(e)).collect())
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        let streams = self.inner.read_multi(stream_ids)?;
+        Ok(streams
+            .into_iter()
+            .map(|events| events.into_iter().map(|e| self.upcast(e)).collect())
+            .collect())
+    }
+}
+
+/// Error returned when creating a rollout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RolloutError {
+    /// A version of the event type is not registered.
+    NotRegistered { name: String, version: u32 },
+}
+
+impl fmt::Display for RolloutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RolloutError::NotRegistered { name, version } => {
+                write!(f, "event type {} v{} is not registered", name, version)
+            }
+        }
+    }
+}
+
+impl Error for RolloutError {}
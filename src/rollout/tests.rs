@@ -0,0 +1,134 @@
+use super::*;
+use crate::event_store::OnMemoryEventStore;
+use crate::registry::{EventTypeDescriptor, EventTypeRegistry};
+
+#[derive(Debug, Clone, PartialEq)]
+enum UserEvent {
+    /// Version 1 of `NameChanged`.
+    NameChangedV1 {
+        name: String,
+    },
+    /// Version 2 of `NameChanged`, splitting the name.
+    NameChanged {
+        first: String,
+        last: String,
+    },
+    Deleted,
+}
+
+fn downcast(event: UserEvent) -> UserEvent {
+    match event {
+        UserEvent::NameChanged { first, last } => UserEvent::NameChangedV1 {
+            name: format!("{} {}", first, last),
+        },
+        other => other,
+    }
+}
+
+fn upcast(event: UserEvent) -> UserEvent {
+    match event {
+        UserEvent::NameChangedV1 { name } => {
+            let (first, last) = name.split_once(' ').unwrap_or((&name, ""));
+            UserEvent::NameChanged {
+                first: first.to_string(),
+                last: last.to_string(),
+            }
+        }
+        other => other,
+    }
+}
+
+fn registry() -> EventTypeRegistry {
+    let mut registry = EventTypeRegistry::new();
+    for version in [1, 2] {
+        registry
+            .register_descriptor(EventTypeDescriptor {
+                name: "NameChanged".to_string(),
+                version,
+                schema: r#"{"type":"object"}"#.to_string(),
+            })
+            .unwrap();
+    }
+    registry
+}
+
+fn name_changed(stream_id: &str, tenant: &str) -> Envelope<UserEvent> {
+    let payload = UserEvent::NameChanged {
+        first: "Ada".to_string(),
+        last: "Lovelace".to_string(),
+    };
+    Envelope::new(stream_id, 0, "NameChanged", payload).with_metadata(TENANT_METADATA_KEY, tenant)
+}
+
+#[test]
+fn test_rollout_per_tenant() {
+    let rollout =
+        EventShapeRollout::new(&registry(), "NameChanged", (1, 2), downcast, upcast).unwrap();
+    let mut store = RolloutEventStore::new(OnMemoryEventStore::new()).with_rollout(rollout);
+    store.save(&[name_changed("user-1", "acme")]).unwrap();
+
+    store
+        .rollout_mut("NameChanged")
+        .unwrap()
+        .set_flag(RolloutFlag::Tenants(HashSet::from(["acme".to_string()])));
+    store
+        .save(&[
+            name_changed("user-1", "acme"),
+            name_changed("user-2", "globex"),
+            Envelope::new("user-1", 0, "Deleted", UserEvent::Deleted),
+        ])
+        .unwrap();
+
+    // Written in the shape the flag chose at the time.
+    let written = store.inner().load(&"user-1".to_string()).unwrap();
+    assert_eq!(
+        written[0].payload,
+        UserEvent::NameChangedV1 {
+            name: "Ada Lovelace".to_string()
+        }
+    );
+    assert_eq!(written[0].metadata[SCHEMA_VERSION_METADATA_KEY], "1");
+    assert_eq!(written[1].metadata[SCHEMA_VERSION_METADATA_KEY], "2");
+    assert!(!written[2]
+        .metadata
+        .contains_key(SCHEMA_VERSION_METADATA_KEY));
+    let written = store.inner().load(&"user-2".to_string()).unwrap();
+    assert_eq!(written[0].metadata[SCHEMA_VERSION_METADATA_KEY], "1");
+
+    // Read in the new shape only.
+    let loaded = store.load(&"user-1".to_string()).unwrap();
+    assert_eq!(loaded[0].payload, loaded[1].payload);
+    assert_eq!(loaded[0].metadata[SCHEMA_VERSION_METADATA_KEY], "2");
+    assert_eq!(loaded[2].payload, UserEvent::Deleted);
+}
+
+#[test]
+fn test_percentage_flag() {
+    let streams = (0..1000).map(|i| format!("user-{}", i)).collect::<Vec<_>>();
+    let enabled = |flag: &RolloutFlag| {
+        streams
+            .iter()
+            .filter(|s| flag.is_enabled(None, s))
+            .cloned()
+            .collect::<HashSet<_>>()
+    };
+    let quarter = enabled(&RolloutFlag::Percentage(25));
+    let half = enabled(&RolloutFlag::Percentage(50));
+    assert!((200..300).contains(&quarter.len()));
+    assert!(quarter.is_subset(&half));
+    assert!(enabled(&RolloutFlag::Percentage(0)).is_empty());
+    assert_eq!(enabled(&RolloutFlag::Percentage(100)).len(), 1000);
+    assert!(!RolloutFlag::Tenants(HashSet::new()).is_enabled(None, "user-1"));
+}
+
+#[test]
+fn test_both_shapes_must_be_registered() {
+    let result = EventShapeRollout::new(&registry(), "NameChanged", (2, 3), downcast, upcast);
+    assert_eq!(
+        result.err(),
+        Some(RolloutError::NotRegistered {
+            name: "NameChanged".to_string(),
+            version: 3
+        })
+    );
+}
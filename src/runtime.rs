@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::SystemTime;
+
+use crate::clock::Clock;
+
+/// Types which represent a periodic maintenance job run by the [`CruxRuntime`].
+pub trait HousekeepingJob<S> {
+    /// Get the name of the job, used in reports.
+    fn name(&self) -> &'static str;
+    /// Run the job against the store, returning the number of items processed.
+    fn run(&mut self, store: &mut S, now: SystemTime) -> Result<usize, String>;
+}
+
+/// Report of a job run by [`CruxRuntime::run_housekeeping`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobReport {
+    /// Name of the job.
+    pub name: &'static str,
+    /// Number of items processed, or the failure of the job.
+    pub result: Result<usize, String>,
+}
+
+/// Runtime owning an event store and the housekeeping jobs maintaining it.
+pub struct CruxRuntime<S, K> {
+    store: S,
+    clock: K,
+    jobs: Vec<Box<dyn HousekeepingJob<S>>>,
+}
+
+impl<S, K: Clock> CruxRuntime<S, K> {
+    /// Create a runtime without jobs.
+    pub fn new(store: S, clock: K) -> Self {
+        Self {
+            store,
+            clock,
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Register a housekeeping job.
+    pub fn with_job(mut self, job: impl HousekeepingJob<S> + 'static) -> Self {
+        self.jobs.push(Box::new(job));
+        self
+    }
+
+    /// Get the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the store mutably.
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Get the clock.
+    pub fn clock(&self) -> &K {
+        &self.clock
+    }
+
+    /// Run every housekeeping job once, in registration order. A failing job does not prevent
+    /// the next ones from running.
+    pub fn run_housekeeping(&mut self) -> Vec<JobReport> {
+        let now = self.clock.now();
+        self.jobs
+            .iter_mut()
+            .map(|job| JobReport {
+                name: job.name(),
+                result: job.run(&mut self.store, now),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,50 @@
+use super::*;
+use crate::clock::ManualClock;
+
+struct Counter;
+
+impl HousekeepingJob<Vec<u32>> for Counter {
+    fn name(&self) -> &'static str {
+        "counter"
+    }
+
+    fn run(&mut self, store: &mut Vec<u32>, _now: SystemTime) -> Result<usize, String> {
+        store.push(store.len() as u32);
+        Ok(store.len())
+    }
+}
+
+struct Failing;
+
+impl HousekeepingJob<Vec<u32>> for Failing {
+    fn name(&self) -> &'static str {
+        "failing"
+    }
+
+    fn run(&mut self, _store: &mut Vec<u32>, _now: SystemTime) -> Result<usize, String> {
+        Err("backend unavailable".to_string())
+    }
+}
+
+#[test]
+fn test_run_housekeeping() {
+    let mut runtime = CruxRuntime::new(Vec::new(), ManualClock::default())
+        .with_job(Failing)
+        .with_job(Counter);
+
+    assert_eq!(
+        runtime.run_housekeeping(),
+        vec![
+            JobReport {
+                name: "failing",
+                result: Err("backend unavailable".to_string()),
+            },
+            JobReport {
+                name: "counter",
+                result: Ok(1),
+            },
+        ]
+    );
+    runtime.run_housekeeping();
+    assert_eq!(*runtime.store(), vec![0, 1]);
+}
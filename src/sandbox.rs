@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, EventStore, OnMemoryEventStore, ReadOnlyEventStore};
+use crate::repository::Repository;
+
+const FORK_BATCH_SIZE: usize = 1000;
+
+/// Isolated in-memory copy of a store, or of a stream, forked at a point of its history, to
+/// run hypothetical commands against real history without touching the source.
+///
+/// Events keep their IDs and metadata; versions and positions are renumbered by the sandbox.
+#[derive(Debug)]
+pub struct Sandbox<E> {
+    store: OnMemoryEventStore<E>,
+    forked_at: u64,
+}
+
+impl<E: Clone> Sandbox<E> {
+    /// Fork the whole store, with the events before the position.
+    pub fn fork_store<S>(source: &S, position: u64) -> Result<Self, S::Error>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<E>>,
+    {
+        let mut store = OnMemoryEventStore::new();
+        let mut from = 0;
+        while from < position {
+            let limit = FORK_BATCH_SIZE.min((position - from) as usize);
+            let mut events = source.read_all(from, limit)?;
+            events.retain(|e| e.position < position);
+            let last = match events.last() {
+                Some(last) => last.position,
+                None => break,
+            };
+            store
+                .save(&events)
+                .expect("the sandbox has no active transaction");
+            from = last + 1;
+        }
+        Ok(Self::new(store))
+    }
+
+    /// Fork the stream, with its events up to the version, or all of them.
+    pub fn fork_stream<S>(
+        source: &S,
+        stream_id: &String,
+        version: Option<u64>,
+    ) -> Result<Self, S::Error>
+    where
+        S: EventLoader<StreamId = String, Persistable = Envelope<E>>,
+    {
+        let mut events = source.load(stream_id)?;
+        if let Some(version) = version {
+            events.retain(|e| e.version <= version);
+        }
+        let mut store = OnMemoryEventStore::new();
+        store
+            .save(&events)
+            .expect("the sandbox has no active transaction");
+        Ok(Self::new(store))
+    }
+
+    fn new(store: OnMemoryEventStore<E>) -> Self {
+        Self {
+            forked_at: store.head(),
+            store,
+        }
+    }
+
+    /// Get the position the hypothetical events start at.
+    pub fn forked_at(&self) -> u64 {
+        self.forked_at
+    }
+
+    /// Get the store of the sandbox.
+    pub fn store(&self) -> &OnMemoryEventStore<E> {
+        &self.store
+    }
+
+    /// Get the store of the sandbox mutably, to append hypothetical events.
+    pub fn store_mut(&mut self) -> &mut OnMemoryEventStore<E> {
+        &mut self.store
+    }
+
+    /// Get the hypothetical events appended since the fork.
+    pub fn hypothetical(&self) -> Vec<Envelope<E>> {
+        self.store
+            .read_all(self.forked_at, usize::MAX)
+            .expect("reading the in-memory log never fails")
+    }
+
+    /// Get a repository over the sandbox, to execute hypothetical commands.
+    pub fn repository<A>(self) -> Repository<A, OnMemoryEventStore<E>>
+    where
+        A: Aggregate<Event = E>,
+    {
+        Repository::new(self.store)
+    }
+}
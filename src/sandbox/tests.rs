@@ -0,0 +1,104 @@
+use super::*;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Account {
+    balance: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AccountEvent {
+    Deposited(i64),
+    Withdrawn(i64),
+}
+
+impl Aggregate for Account {
+    type Id = u32;
+    type Event = AccountEvent;
+
+    fn aggregate_type() -> &'static str {
+        "account"
+    }
+
+    fn event_type(event: &AccountEvent) -> String {
+        match event {
+            AccountEvent::Deposited(_) => "Deposited".to_string(),
+            AccountEvent::Withdrawn(_) => "Withdrawn".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &AccountEvent) {
+        match event {
+            AccountEvent::Deposited(amount) => self.balance += amount,
+            AccountEvent::Withdrawn(amount) => self.balance -= amount,
+        }
+    }
+}
+
+fn production() -> OnMemoryEventStore<AccountEvent> {
+    let mut store = OnMemoryEventStore::new();
+    for (account, event) in [
+        (1, AccountEvent::Deposited(100)),
+        (2, AccountEvent::Deposited(50)),
+        (1, AccountEvent::Withdrawn(30)),
+        (1, AccountEvent::Deposited(5)),
+    ] {
+        let event_type = Account::event_type(&event);
+        store
+            .save(&[Envelope::new(
+                Account::stream_id(&account),
+                0,
+                event_type,
+                event,
+            )])
+            .unwrap();
+    }
+    store
+}
+
+#[test]
+fn test_fork_store() {
+    let source = production();
+    let sandbox = Sandbox::fork_store(&source, 3).unwrap();
+    assert_eq!(sandbox.forked_at(), 3);
+    assert_eq!(
+        sandbox.store().load(&"account-1".to_string()).unwrap()[0].id,
+        source.load(&"account-1".to_string()).unwrap()[0].id
+    );
+
+    // What if account 1 had withdrawn everything before its last deposit?
+    let mut repository = sandbox.repository::<Account>();
+    repository
+        .execute(&1, |account| {
+            vec![AccountEvent::Withdrawn(account.unwrap().balance)]
+        })
+        .unwrap();
+    assert_eq!(repository.load(&1).unwrap().unwrap().state.balance, 0);
+    assert_eq!(repository.load(&2).unwrap().unwrap().state.balance, 50);
+    assert_eq!(source.stream_len("account-1"), 3);
+}
+
+#[test]
+fn test_fork_stream() {
+    let source = production();
+    let mut sandbox = Sandbox::fork_stream(&source, &"account-1".to_string(), Some(2)).unwrap();
+    assert_eq!(sandbox.store().stream_ids(), ["account-1"]);
+    assert_eq!(sandbox.forked_at(), 2);
+
+    sandbox
+        .store_mut()
+        .save(&[Envelope::new(
+            "account-1",
+            0,
+            "Withdrawn",
+            AccountEvent::Withdrawn(70),
+        )])
+        .unwrap();
+    let hypothetical = sandbox.hypothetical();
+    assert_eq!(hypothetical.len(), 1);
+    assert_eq!(hypothetical[0].version, 3);
+    assert!(Sandbox::fork_store(&source, 0)
+        .unwrap()
+        .store()
+        .stream_ids()
+        .is_empty());
+}
@@ -0,0 +1,420 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+
+/// Metadata key recording the codec used to serialize the payload.
+pub const CODEC_METADATA_KEY: &str = "codec";
+/// Metadata key recording the compression applied to the serialized payload.
+pub const COMPRESSION_METADATA_KEY: &str = "compression";
+
+/// Types which serialize events of type `T` in a given format.
+pub trait Codec<T> {
+    /// Get the name of the format, recorded in the envelope metadata.
+    fn name(&self) -> &'static str;
+    /// Serialize the event.
+    fn encode(&self, value: &T) -> Result<Vec<u8>, String>;
+    /// Deserialize the event.
+    fn decode(&self, bytes: &[u8]) -> Result<T, String>;
+}
+
+/// Types which compress serialized payloads.
+pub trait Compression {
+    /// Get the name of the compression, recorded in the envelope metadata.
+    fn name(&self) -> &'static str;
+    /// Compress the bytes.
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, String>;
+    /// Decompress the bytes.
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// Types which represent a backend advertising the formats it supports, by order of
+/// preference.
+pub trait CodecSupport {
+    /// Get the names of the supported codecs.
+    fn supported_codecs(&self) -> Vec<&'static str>;
+    /// Get the names of the supported compressions.
+    fn supported_compressions(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+}
+
+/// Formats agreed on between a [`SerializerRegistry`] and a backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiation {
+    /// Codecs both support, by order of preference of the backend.
+    pub codecs: Vec<&'static str>,
+    /// Compression both support, if any.
+    pub compression: Option<&'static str>,
+}
+
+/// Registry choosing the codec used for each event type.
+///
+/// Envelopes are decoded with the codec recorded in their metadata, so changing the codec of
+/// an event type only affects newly serialized events.
+pub struct SerializerRegistry<T> {
+    codecs: HashMap<&'static str, Box<dyn Codec<T>>>,
+    compressions: HashMap<&'static str, Box<dyn Compression>>,
+    assignments: HashMap<String, &'static str>,
+    default_codec: &'static str,
+}
+
+impl<T> SerializerRegistry<T> {
+    /// Create a registry using the codec for every event type by default.
+    pub fn new(default_codec: impl Codec<T> + 'static) -> Self {
+        let name = default_codec.name();
+        let mut codecs: HashMap<&'static str, Box<dyn Codec<T>>> = HashMap::new();
+        codecs.insert(name, Box::new(default_codec));
+        Self {
+            codecs,
+            compressions: HashMap::new(),
+            assignments: HashMap::new(),
+            default_codec: name,
+        }
+    }
+
+    /// Register a codec, available for decoding and for assignment to event types.
+    pub fn register_codec(&mut self, codec: impl Codec<T> + 'static) {
+        self.codecs.insert(codec.name(), Box::new(codec));
+    }
+
+    /// Register a compression, available for decompressing and for negotiation.
+    pub fn register_compression(&mut self, compression: impl Compression + 'static) {
+        self.compressions
+            .insert(compression.name(), Box::new(compression));
+    }
+
+    /// Use the registered codec to serialize the event type.
+    pub fn assign(
+        &mut self,
+        event_type: impl Into<String>,
+        codec: &str,
+    ) -> Result<(), SerializationError> {
+        let (name, _) = self
+            .codecs
+            .get_key_value(codec)
+            .ok_or_else(|| SerializationError::UnknownCodec(codec.to_string()))?;
+        self.assignments.insert(event_type.into(), name);
+        Ok(())
+    }
+
+    /// Get the name of the codec used to serialize the event type.
+    pub fn codec_for(&self, event_type: &str) -> &'static str {
+        self.assignments
+            .get(event_type)
+            .copied()
+            .unwrap_or(self.default_codec)
+    }
+
+    /// Serialize the payload of the envelope, recording the codec in its metadata.
+    pub fn serialize(
+        &self,
+        envelope: &Envelope<T>,
+    ) -> Result<Envelope<Vec<u8>>, SerializationError> {
+        self.serialize_with(envelope, self.codec_for(&envelope.event_type), None)
+    }
+
+    /// Agree on the formats to use with the backend.
+    ///
+    /// The codecs assigned to event types are kept when the backend supports them, the
+    /// preferred codec of the backend is used otherwise.
+    pub fn negotiate(
+        &self,
+        backend: &impl CodecSupport,
+    ) -> Result<Negotiation, SerializationError> {
+        let codecs = backend
+            .supported_codecs()
+            .into_iter()
+            .filter(|name| self.codecs.contains_key(name))
+            .collect::<Vec<_>>();
+        if codecs.is_empty() {
+            return Err(SerializationError::NoCommonCodec);
+        }
+        let compression = backend
+            .supported_compressions()
+            .into_iter()
+            .find(|name| self.compressions.contains_key(name));
+        Ok(Negotiation {
+            codecs,
+            compression,
+        })
+    }
+
+    /// Serialize the payload of the envelope with the negotiated formats, recording them in
+    /// its metadata.
+    pub fn serialize_negotiated(
+        &self,
+        envelope: &Envelope<T>,
+        negotiation: &Negotiation,
+    ) -> Result<Envelope<Vec<u8>>, SerializationError> {
+        let assigned = self.codec_for(&envelope.event_type);
+        let codec = if negotiation.codecs.contains(&assigned) {
+            assigned
+        } else {
+            *negotiation
+                .codecs
+                .first()
+                .ok_or(SerializationError::NoCommonCodec)?
+        };
+        self.serialize_with(envelope, codec, negotiation.compression)
+    }
+
+    fn serialize_with(
+        &self,
+        envelope: &Envelope<T>,
+        codec: &'static str,
+        compression: Option<&'static str>,
+    ) -> Result<Envelope<Vec<u8>>, SerializationError> {
+        let mut bytes = self
+            .codecs
+            .get(codec)
+            .ok_or_else(|| SerializationError::UnknownCodec(codec.to_string()))?
+            .encode(&envelope.payload)
+            .map_err(SerializationError::Codec)?;
+        if let Some(compression) = compression {
+            bytes = self
+                .compressions
+                .get(compression)
+                .ok_or_else(|| SerializationError::UnknownCompression(compression.to_string()))?
+                .compress(&bytes)
+                .map_err(SerializationError::Codec)?;
+        }
+        let mut serialized = Envelope {
+            id: envelope.id,
+            stream_id: envelope.stream_id.clone(),
+            version: envelope.version,
+            position: envelope.position,
+            event_type: envelope.event_type.clone(),
+            payload: bytes,
+            metadata: envelope.metadata.clone(),
+        };
+        serialized
+            .metadata
+            .insert(CODEC_METADATA_KEY.to_string(), codec.to_string());
+        // A compression recorded on the input does not apply to the bytes encoded here.
+        match compression {
+            Some(compression) => serialized.metadata.insert(
+                COMPRESSION_METADATA_KEY.to_string(),
+                compression.to_string(),
+            ),
+            None => serialized.metadata.remove(COMPRESSION_METADATA_KEY),
+        };
+        Ok(serialized)
+    }
+
+    /// Deserialize the payload of the envelope with the codec recorded in its metadata.
+    ///
+    /// Envelopes without codec metadata are decoded with the default codec. Compressed
+    /// payloads are decompressed first.
+    pub fn deserialize(
+        &self,
+        mut envelope: Envelope<Vec<u8>>,
+    ) -> Result<Envelope<T>, SerializationError> {
+        if let Some(name) = envelope.metadata.remove(COMPRESSION_METADATA_KEY) {
+            let compression = self
+                .compressions
+                .get(name.as_str())
+                .ok_or(SerializationError::UnknownCompression(name))?;
+            envelope.payload = compression
+                .decompress(&envelope.payload)
+                .map_err(SerializationError::Codec)?;
+        }
+        let name = envelope
+            .metadata
+            .get(CODEC_METADATA_KEY)
+            .map(String::as_str)
+            .unwrap_or(self.default_codec);
+        let codec = self
+            .codecs
+            .get(name)
+            .ok_or_else(|| SerializationError::UnknownCodec(name.to_string()))?;
+        let payload = codec
+            .decode(&envelope.payload)
+            .map_err(SerializationError::Codec)?;
+        let mut envelope = envelope.map(|_| payload);
+        envelope.metadata.remove(CODEC_METADATA_KEY);
+        Ok(envelope)
+    }
+}
+
+/// Event store serializing events with the formats negotiated with the backend, so that
+/// the same events can be stored in backends supporting different formats.
+pub struct NegotiatedEventStore<S, T> {
+    inner: S,
+    registry: SerializerRegistry<T>,
+    negotiation: Negotiation,
+}
+
+impl<S: CodecSupport, T> NegotiatedEventStore<S, T> {
+    /// Wrap the backend, negotiating the formats with the registry.
+    pub fn new(inner: S, registry: SerializerRegistry<T>) -> Result<Self, SerializationError> {
+        let negotiation = registry.negotiate(&inner)?;
+        Ok(Self {
+            inner,
+            registry,
+            negotiation,
+        })
+    }
+
+    /// Get the negotiated formats.
+    pub fn negotiation(&self) -> &Negotiation {
+        &self.negotiation
+    }
+
+    /// Get the wrapped backend.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S, T> NegotiatedEventStore<S, T> {
+    fn serialize_all(
+        &self,
+        events: &[Envelope<T>],
+    ) -> Result<Vec<Envelope<Vec<u8>>>, SerializationError> {
+        events
+            .iter()
+            .map(|e| self.registry.serialize_negotiated(e, &self.negotiation))
+            .collect()
+    }
+
+    fn deserialize_all(
+        &self,
+        events: Vec<Envelope<Vec<u8>>>,
+    ) -> Result<Vec<Envelope<T>>, SerializationError> {
+        events
+            .into_iter()
+            .map(|e| self.registry.deserialize(e))
+            .collect()
+    }
+}
+
+impl<S, T> EventStore for NegotiatedEventStore<S, T>
+where
+    S: EventStore<Persistable = Envelope<Vec<u8>>>,
+{
+    type Persistable = Envelope<T>;
+    type Error = NegotiatedStoreError<S::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let serialized = self
+            .serialize_all(events)
+            .map_err(NegotiatedStoreError::Serialization)?;
+        self.inner
+            .save(&serialized)
+            .map_err(NegotiatedStoreError::Store)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let serialized = appends
+            .iter()
+            .map(|(stream_id, expected, events)| {
+                Ok((stream_id.clone(), *expected, self.serialize_all(events)?))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppendError::Store(NegotiatedStoreError::Serialization(e)))?;
+        self.inner
+            .append_multi(&serialized)
+            .map_err(|e| e.map_store(NegotiatedStoreError::Store))
+    }
+}
+
+impl<S, T> EventLoader for NegotiatedEventStore<S, T>
+where
+    S: EventLoader<Persistable = Envelope<Vec<u8>>>,
+{
+    type StreamId = S::StreamId;
+    type Persistable = Envelope<T>;
+    type Error = NegotiatedStoreError<S::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let events = self
+            .inner
+            .load(stream_id)
+            .map_err(NegotiatedStoreError::Store)?;
+        self.deserialize_all(events)
+            .map_err(NegotiatedStoreError::Serialization)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let events = self
+            .inner
+            .load_after(stream_id, version)
+            .map_err(NegotiatedStoreError::Store)?;
+        self.deserialize_all(events)
+            .map_err(NegotiatedStoreError::Serialization)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.inner
+            .read_multi(stream_ids)
+            .map_err(NegotiatedStoreError::Store)?
+            .into_iter()
+            .map(|events| self.deserialize_all(events))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(NegotiatedStoreError::Serialization)
+    }
+}
+
+/// Error returned by the [`NegotiatedEventStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NegotiatedStoreError<E> {
+    /// The events failed to be serialized or deserialized.
+    Serialization(SerializationError),
+    /// The backend failed.
+    Store(E),
+}
+
+impl<E: Error> fmt::Display for NegotiatedStoreError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NegotiatedStoreError::Serialization(e) => write!(f, "serialization error: {}", e),
+            NegotiatedStoreError::Store(e) => write!(f, "store error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for NegotiatedStoreError<E> {}
+
+/// Error returned by the [`SerializerRegistry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializationError {
+    /// No codec is registered with the name.
+    UnknownCodec(String),
+    /// No compression is registered with the name.
+    UnknownCompression(String),
+    /// The codec failed to encode or decode the payload.
+    Codec(String),
+    /// The backend supports none of the registered codecs.
+    NoCommonCodec,
+}
+
+impl fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializationError::UnknownCodec(name) => write!(f, "unknown codec: {}", name),
+            SerializationError::UnknownCompression(name) => {
+                write!(f, "unknown compression: {}", name)
+            }
+            SerializationError::Codec(reason) => write!(f, "codec error: {}", reason),
+            SerializationError::NoCommonCodec => write!(f, "no codec supported by the backend"),
+        }
+    }
+}
+
+impl Error for SerializationError {}
@@ -0,0 +1,340 @@
+use super::*;
+use crate::event_store::{OnMemoryEventStore, OnMemoryEventStoreError};
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrgEvent {
+    Renamed(String),
+    UserAdded(u32),
+}
+
+struct TextCodec;
+
+impl Codec<OrgEvent> for TextCodec {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn encode(&self, value: &OrgEvent) -> Result<Vec<u8>, String> {
+        Ok(match value {
+            OrgEvent::Renamed(name) => format!("renamed:{}", name),
+            OrgEvent::UserAdded(id) => format!("user_added:{}", id),
+        }
+        .into_bytes())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<OrgEvent, String> {
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        match text.split_once(':') {
+            Some(("renamed", name)) => Ok(OrgEvent::Renamed(name.to_string())),
+            Some(("user_added", id)) => id
+                .parse()
+                .map(OrgEvent::UserAdded)
+                .map_err(|_| format!("invalid id: {}", id)),
+            _ => Err(format!("invalid event: {}", text)),
+        }
+    }
+}
+
+struct BinaryCodec;
+
+impl Codec<OrgEvent> for BinaryCodec {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn encode(&self, value: &OrgEvent) -> Result<Vec<u8>, String> {
+        match value {
+            OrgEvent::UserAdded(id) => Ok(id.to_be_bytes().to_vec()),
+            OrgEvent::Renamed(_) => Err("unsupported event".to_string()),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<OrgEvent, String> {
+        let bytes: [u8; 4] = bytes.try_into().map_err(|_| "invalid length".to_string())?;
+        Ok(OrgEvent::UserAdded(u32::from_be_bytes(bytes)))
+    }
+}
+
+#[test]
+fn test_codec_per_event_type() {
+    let mut registry = SerializerRegistry::new(TextCodec);
+    registry.register_codec(BinaryCodec);
+    registry.assign("UserAdded", "binary").unwrap();
+
+    let renamed = Envelope::new("org-1", 1, "Renamed", OrgEvent::Renamed("Acme".to_string()));
+    let serialized = registry.serialize(&renamed).unwrap();
+    assert_eq!(serialized.payload, b"renamed:Acme".to_vec());
+    assert_eq!(serialized.metadata[CODEC_METADATA_KEY], "text");
+    assert_eq!(registry.deserialize(serialized).unwrap(), renamed);
+
+    let added = Envelope::new("org-1", 2, "UserAdded", OrgEvent::UserAdded(7))
+        .with_metadata("user_id", "admin");
+    let serialized = registry.serialize(&added).unwrap();
+    assert_eq!(serialized.payload, vec![0, 0, 0, 7]);
+    assert_eq!(serialized.metadata[CODEC_METADATA_KEY], "binary");
+    assert_eq!(registry.deserialize(serialized).unwrap(), added);
+}
+
+#[test]
+fn test_serialize_keeps_position_and_drops_stale_compression() {
+    let registry = SerializerRegistry::new(TextCodec);
+    let mut envelope = Envelope::new("org-1", 3, "UserAdded", OrgEvent::UserAdded(7))
+        .with_metadata(COMPRESSION_METADATA_KEY, "gzip")
+        .with_metadata(CODEC_METADATA_KEY, "binary");
+    envelope.position = 42;
+
+    let serialized = registry.serialize(&envelope).unwrap();
+    assert_eq!(serialized.position, 42);
+    assert_eq!(serialized.metadata[CODEC_METADATA_KEY], "text");
+    assert!(!serialized.metadata.contains_key(COMPRESSION_METADATA_KEY));
+
+    let deserialized = registry.deserialize(serialized).unwrap();
+    assert_eq!(deserialized.position, 42);
+    assert_eq!(deserialized.payload, envelope.payload);
+    assert!(deserialized.metadata.is_empty());
+}
+
+#[test]
+fn test_gradual_migration_decodes_with_recorded_codec() {
+    let mut registry = SerializerRegistry::new(TextCodec);
+    registry.register_codec(BinaryCodec);
+    let added = Envelope::new("org-1", 1, "UserAdded", OrgEvent::UserAdded(7));
+    let old = registry.serialize(&added).unwrap();
+
+    registry.assign("UserAdded", "binary").unwrap();
+    let new = registry.serialize(&added).unwrap();
+
+    assert_eq!(registry.deserialize(old).unwrap(), added);
+    assert_eq!(registry.deserialize(new).unwrap(), added);
+}
+
+#[test]
+fn test_unknown_codec() {
+    let mut registry = SerializerRegistry::new(TextCodec);
+    assert_eq!(
+        registry.assign("UserAdded", "proto"),
+        Err(SerializationError::UnknownCodec("proto".to_string()))
+    );
+
+    let envelope =
+        Envelope::new("org-1", 1, "UserAdded", vec![]).with_metadata(CODEC_METADATA_KEY, "proto");
+    assert_eq!(
+        registry.deserialize(envelope),
+        Err(SerializationError::UnknownCodec("proto".to_string()))
+    );
+}
+
+struct RunLength;
+
+impl Compression for RunLength {
+    fn name(&self) -> &'static str {
+        "rle"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        for chunk in bytes.chunk_by(|a, b| a == b) {
+            for run in chunk.chunks(u8::MAX as usize) {
+                out.extend([run.len() as u8, run[0]]);
+            }
+        }
+        Ok(out)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        if !bytes.len().is_multiple_of(2) {
+            return Err("invalid length".to_string());
+        }
+        Ok(bytes
+            .chunks(2)
+            .flat_map(|pair| std::iter::repeat_n(pair[1], pair[0] as usize))
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct Backend {
+    store: OnMemoryEventStore<Vec<u8>>,
+    codecs: Vec<&'static str>,
+    compressions: Vec<&'static str>,
+}
+
+impl CodecSupport for Backend {
+    fn supported_codecs(&self) -> Vec<&'static str> {
+        self.codecs.clone()
+    }
+
+    fn supported_compressions(&self) -> Vec<&'static str> {
+        self.compressions.clone()
+    }
+}
+
+impl EventStore for Backend {
+    type Persistable = Envelope<Vec<u8>>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        self.store.save(events)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.store.append_multi(appends)
+    }
+}
+
+impl EventLoader for Backend {
+    type StreamId = String;
+    type Persistable = Envelope<Vec<u8>>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load(stream_id)
+    }
+}
+
+fn registry() -> SerializerRegistry<OrgEvent> {
+    let mut registry = SerializerRegistry::new(TextCodec);
+    registry.register_codec(BinaryCodec);
+    registry.register_compression(RunLength);
+    registry.assign("UserAdded", "binary").unwrap();
+    registry
+}
+
+#[test]
+fn test_negotiate() {
+    let registry = registry();
+    let backend = Backend {
+        codecs: vec!["proto", "binary", "text"],
+        compressions: vec!["zstd", "rle"],
+        ..Default::default()
+    };
+    assert_eq!(
+        registry.negotiate(&backend),
+        Ok(Negotiation {
+            codecs: vec!["binary", "text"],
+            compression: Some("rle"),
+        })
+    );
+    let backend = Backend {
+        codecs: vec!["proto"],
+        ..Default::default()
+    };
+    assert_eq!(
+        registry.negotiate(&backend),
+        Err(SerializationError::NoCommonCodec)
+    );
+}
+
+#[test]
+fn test_negotiated_event_store() {
+    // A text-only backend gets the assigned binary codec replaced, without compression.
+    let backend = Backend {
+        codecs: vec!["text"],
+        ..Default::default()
+    };
+    let mut store = NegotiatedEventStore::new(backend, registry()).unwrap();
+    let added = Envelope::new("org-1", 1, "UserAdded", OrgEvent::UserAdded(7));
+    store.save(std::slice::from_ref(&added)).unwrap();
+    let raw = store.inner().load(&"org-1".to_string()).unwrap();
+    assert_eq!(raw[0].payload, b"user_added:7".to_vec());
+    assert!(!raw[0].metadata.contains_key(COMPRESSION_METADATA_KEY));
+    assert_eq!(
+        store.load(&"org-1".to_string()).unwrap()[0].payload,
+        added.payload
+    );
+
+    let backend = Backend {
+        codecs: vec!["text", "binary"],
+        compressions: vec!["rle"],
+        ..Default::default()
+    };
+    let mut store = NegotiatedEventStore::new(backend, registry()).unwrap();
+    let renamed = Envelope::new("org-1", 1, "Renamed", OrgEvent::Renamed("aaaa".to_string()));
+    store.save(&[renamed.clone(), added.clone()]).unwrap();
+    let raw = store.inner().load(&"org-1".to_string()).unwrap();
+    assert_eq!(raw[0].metadata[CODEC_METADATA_KEY], "text");
+    assert_eq!(raw[0].metadata[COMPRESSION_METADATA_KEY], "rle");
+    assert_eq!(raw[1].metadata[CODEC_METADATA_KEY], "binary");
+    assert_eq!(raw[1].payload, vec![3, 0, 1, 7]);
+
+    let loaded = store.load(&"org-1".to_string()).unwrap();
+    assert_eq!(loaded[0].payload, renamed.payload);
+    assert_eq!(loaded[1].payload, added.payload);
+    assert_eq!(loaded[1].metadata, added.metadata);
+}
+
+#[test]
+fn test_reject_formats_not_registered() {
+    let registry = registry();
+    let added = Envelope::new("org-1", 1, "UserAdded", OrgEvent::UserAdded(7));
+    let negotiation = |codecs, compression| Negotiation {
+        codecs,
+        compression,
+    };
+    assert_eq!(
+        registry.serialize_negotiated(&added, &negotiation(vec![], None)),
+        Err(SerializationError::NoCommonCodec)
+    );
+    assert_eq!(
+        registry.serialize_negotiated(&added, &negotiation(vec!["proto"], None)),
+        Err(SerializationError::UnknownCodec("proto".to_string()))
+    );
+    assert_eq!(
+        registry.serialize_negotiated(&added, &negotiation(vec!["binary"], Some("zstd"))),
+        Err(SerializationError::UnknownCompression("zstd".to_string()))
+    );
+
+    let serialized = registry
+        .serialize(&added)
+        .unwrap()
+        .with_metadata(COMPRESSION_METADATA_KEY, "zstd");
+    assert_eq!(
+        registry.deserialize(serialized),
+        Err(SerializationError::UnknownCompression("zstd".to_string()))
+    );
+}
+
+#[test]
+fn test_negotiated_multi_stream_operations() {
+    let backend = Backend {
+        codecs: vec!["text"],
+        compressions: vec!["rle"],
+        ..Default::default()
+    };
+    let mut store = NegotiatedEventStore::new(backend, registry()).unwrap();
+    let added = Envelope::new("org-1", 0, "UserAdded", OrgEvent::UserAdded(7));
+    let renamed = Envelope::new("org-2", 0, "Renamed", OrgEvent::Renamed("Acme".to_string()));
+    store
+        .append_multi(&[
+            (
+                "org-1".to_string(),
+                ExpectedVersion::NoStream,
+                vec![added.clone()],
+            ),
+            (
+                "org-2".to_string(),
+                ExpectedVersion::NoStream,
+                vec![renamed.clone()],
+            ),
+        ])
+        .unwrap();
+    let raw = store.inner().load(&"org-1".to_string()).unwrap();
+    assert_eq!(raw[0].metadata[COMPRESSION_METADATA_KEY], "rle");
+
+    let streams = store
+        .read_multi(&["org-1".to_string(), "org-2".to_string()])
+        .unwrap();
+    assert_eq!(streams[0][0].payload, added.payload);
+    assert_eq!(streams[1][0].payload, renamed.payload);
+    assert_eq!(
+        store.append_multi(&[("org-1".to_string(), ExpectedVersion::NoStream, vec![added])]),
+        Err(AppendError::WrongExpectedVersion {
+            stream_id: "org-1".to_string(),
+            expected: ExpectedVersion::NoStream,
+            actual: 1,
+        })
+    );
+}
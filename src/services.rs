@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests;
+
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::command_bus::CommandBus;
+
+/// Typed container of the services command handlers and sagas depend on, such as the clock,
+/// the ID generator, lookups and domain services, so that handlers take the container instead
+/// of one parameter per service, and tests swap services for doubles.
+///
+/// Services are found by their type. Register trait objects boxed, e.g. as
+/// `Box<dyn Clock>`, so that handlers depend on the trait and not on the implementation.
+#[derive(Default)]
+pub struct Services {
+    services: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Services {
+    /// Create an empty container.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the service, replacing the one of the same type.
+    pub fn with<T: 'static>(mut self, service: T) -> Self {
+        self.insert(service);
+        self
+    }
+
+    /// Register the service, returning the one of the same type it replaced, e.g. to swap a
+    /// service for a test double.
+    pub fn insert<T: 'static>(&mut self, service: T) -> Option<T> {
+        self.services
+            .insert(TypeId::of::<T>(), Box::new(service))
+            .map(|replaced| {
+                *replaced
+                    .downcast()
+                    .expect("services are keyed by their type")
+            })
+    }
+
+    /// Unregister the service, returning it.
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.services.remove(&TypeId::of::<T>()).map(|removed| {
+            *removed
+                .downcast()
+                .expect("services are keyed by their type")
+        })
+    }
+
+    /// Check whether a service of the type is registered.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.services.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Get the service of the type.
+    pub fn get<T: 'static>(&self) -> Result<&T, MissingService> {
+        self.services
+            .get(&TypeId::of::<T>())
+            .and_then(|service| service.downcast_ref())
+            .ok_or(MissingService(type_name::<T>()))
+    }
+
+    /// Get the service of the type mutably.
+    pub fn get_mut<T: 'static>(&mut self) -> Result<&mut T, MissingService> {
+        self.services
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|service| service.downcast_mut())
+            .ok_or(MissingService(type_name::<T>()))
+    }
+}
+
+impl fmt::Debug for Services {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Services")
+            .field("len", &self.services.len())
+            .finish()
+    }
+}
+
+/// Command bus dispatching the commands to a handler together with the services of the
+/// container.
+pub struct ServiceBus<C, F> {
+    services: Services,
+    handler: F,
+    command: PhantomData<fn(C)>,
+}
+
+impl<C, F> ServiceBus<C, F> {
+    /// Dispatch the commands to the handler, with the services.
+    pub fn new(services: Services, handler: F) -> Self {
+        Self {
+            services,
+            handler,
+            command: PhantomData,
+        }
+    }
+
+    /// Get the services.
+    pub fn services(&self) -> &Services {
+        &self.services
+    }
+
+    /// Get the services mutably, e.g. to swap one between commands.
+    pub fn services_mut(&mut self) -> &mut Services {
+        &mut self.services
+    }
+}
+
+impl<C, R, E, F> CommandBus<C> for ServiceBus<C, F>
+where
+    F: FnMut(C, &mut Services) -> Result<R, E>,
+    E: Error,
+{
+    type Response = R;
+    type Error = E;
+
+    fn dispatch(&mut self, command: C) -> Result<Self::Response, Self::Error> {
+        (self.handler)(command, &mut self.services)
+    }
+}
+
+/// Error returned when a [`Services`] container has no service of the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingService(pub &'static str);
+
+impl fmt::Display for MissingService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no service registered for {}", self.0)
+    }
+}
+
+impl Error for MissingService {}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use super::*;
+use crate::clock::{Clock, ManualClock, SystemClock};
+
+trait IdGenerator {
+    fn next_id(&mut self) -> u64;
+}
+
+/// Generator of sequential IDs, as a test double of a random generator.
+struct Sequence(u64);
+
+impl IdGenerator for Sequence {
+    fn next_id(&mut self) -> u64 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Registered {
+    id: u64,
+    at: SystemTime,
+}
+
+#[derive(Debug, PartialEq)]
+enum RegisterError {
+    Missing(MissingService),
+    NameTaken,
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterError::Missing(e) => write!(f, "{}", e),
+            RegisterError::NameTaken => write!(f, "name taken"),
+        }
+    }
+}
+
+impl Error for RegisterError {}
+
+impl From<MissingService> for RegisterError {
+    fn from(error: MissingService) -> Self {
+        RegisterError::Missing(error)
+    }
+}
+
+fn register(name: &str, services: &mut Services) -> Result<Registered, RegisterError> {
+    if !services
+        .get_mut::<HashSet<String>>()?
+        .insert(name.to_string())
+    {
+        return Err(RegisterError::NameTaken);
+    }
+    let id = services.get_mut::<Box<dyn IdGenerator>>()?.next_id();
+    let at = services.get::<Box<dyn Clock>>()?.now();
+    Ok(Registered { id, at })
+}
+
+#[test]
+fn test_services() {
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+    let mut services = Services::new()
+        .with::<Box<dyn Clock>>(Box::new(SystemClock))
+        .with::<Box<dyn IdGenerator>>(Box::new(Sequence(0)))
+        .with(HashSet::<String>::new());
+    // Swap the system clock for a double.
+    assert!(services
+        .insert::<Box<dyn Clock>>(Box::new(clock.clone()))
+        .is_some());
+
+    assert_eq!(
+        register("alice", &mut services),
+        Ok(Registered {
+            id: 1,
+            at: clock.now()
+        })
+    );
+    assert_eq!(
+        register("alice", &mut services),
+        Err(RegisterError::NameTaken)
+    );
+    assert!(services.contains::<HashSet<String>>());
+    assert_eq!(services.get::<HashSet<String>>().unwrap().len(), 1);
+
+    services.remove::<Box<dyn IdGenerator>>().unwrap();
+    assert!(matches!(
+        register("bob", &mut services),
+        Err(RegisterError::Missing(MissingService(name))) if name.contains("IdGenerator")
+    ));
+}
+
+#[test]
+fn test_service_bus() {
+    let services = Services::new()
+        .with::<Box<dyn Clock>>(Box::new(ManualClock::default()))
+        .with::<Box<dyn IdGenerator>>(Box::new(Sequence(41)))
+        .with(HashSet::<String>::new());
+    let mut bus = ServiceBus::new(services, |name: &str, services: &mut Services| {
+        register(name, services)
+    });
+    assert_eq!(bus.dispatch("alice").unwrap().id, 42);
+    bus.services_mut()
+        .insert::<Box<dyn IdGenerator>>(Box::new(Sequence(99)));
+    assert_eq!(bus.dispatch("bob").unwrap().id, 100);
+    assert_eq!(bus.services().get::<HashSet<String>>().unwrap().len(), 2);
+}
@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::time::SystemTime;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::{Envelope, Metadata};
+use crate::event::Event;
+use crate::projection::Projection;
+
+/// Event of the [`Settings`] aggregate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsEvent {
+    /// The key was set to the value.
+    Set { key: String, value: String },
+    /// The key was removed.
+    Unset { key: String },
+}
+
+/// Aggregate holding the key-value configuration of a namespace, such as feature flags.
+///
+/// The ID of the aggregate is the namespace, so that its stream is `settings-{namespace}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    values: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// Get the value of the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Check whether the flag is set to `true`.
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.get(flag) == Some("true")
+    }
+
+    /// Get every value, by key.
+    pub fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+
+    /// Decide the events setting the key to the value, none if it already has the value.
+    ///
+    /// Meant to be passed to [`Repository::execute`](crate::repository::Repository::execute).
+    pub fn set(
+        state: Option<&Self>,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Vec<SettingsEvent> {
+        let (key, value) = (key.into(), value.into());
+        if state.and_then(|s| s.get(&key)) == Some(value.as_str()) {
+            return Vec::new();
+        }
+        vec![SettingsEvent::Set { key, value }]
+    }
+
+    /// Decide the events removing the key, none if it is not set.
+    pub fn unset(state: Option<&Self>, key: impl Into<String>) -> Vec<SettingsEvent> {
+        let key = key.into();
+        if state.and_then(|s| s.get(&key)).is_none() {
+            return Vec::new();
+        }
+        vec![SettingsEvent::Unset { key }]
+    }
+}
+
+impl Aggregate for Settings {
+    type Id = String;
+    type Event = SettingsEvent;
+
+    fn aggregate_type() -> &'static str {
+        "settings"
+    }
+
+    fn event_type(event: &SettingsEvent) -> String {
+        match event {
+            SettingsEvent::Set { .. } => "SettingSet".to_string(),
+            SettingsEvent::Unset { .. } => "SettingUnset".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &SettingsEvent) {
+        match event {
+            SettingsEvent::Set { key, value } => {
+                self.values.insert(key.clone(), value.clone());
+            }
+            SettingsEvent::Unset { key } => {
+                self.values.remove(key);
+            }
+        }
+    }
+}
+
+/// Change of a setting, as recorded by the [`SettingsHistory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    /// The changed key.
+    pub key: String,
+    /// The new value, `None` once removed.
+    pub value: Option<String>,
+    /// Version of the settings stream after the change.
+    pub version: u64,
+    /// Time the change occurred at, if recorded in the metadata.
+    pub occurred_at: Option<SystemTime>,
+    /// Metadata of the event, such as the principal who made the change.
+    pub metadata: Metadata,
+}
+
+/// Projection keeping the history of the changes of every settings namespace, to audit them
+/// and to read the settings as they were at a point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsHistory {
+    changes: HashMap<String, Vec<SettingChange>>,
+}
+
+impl SettingsHistory {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the changes of the namespace, oldest first.
+    pub fn changes(&self, namespace: &str) -> &[SettingChange] {
+        self.changes
+            .get(&Settings::stream_id(&namespace.to_string()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Get the changes of the key in the namespace, oldest first.
+    pub fn changes_of<'a>(
+        &'a self,
+        namespace: &str,
+        key: &'a str,
+    ) -> impl Iterator<Item = &'a SettingChange> + 'a {
+        self.changes(namespace)
+            .iter()
+            .filter(move |change| change.key == key)
+    }
+
+    /// Get the settings of the namespace as they were at the version of its stream.
+    pub fn as_of_version(&self, namespace: &str, version: u64) -> BTreeMap<String, String> {
+        Self::replay(
+            self.changes(namespace)
+                .iter()
+                .take_while(|change| change.version <= version),
+        )
+    }
+
+    /// Get the settings of the namespace as they were at the time. Changes without an
+    /// occurrence time are ignored.
+    pub fn as_of(&self, namespace: &str, at: SystemTime) -> BTreeMap<String, String> {
+        Self::replay(
+            self.changes(namespace)
+                .iter()
+                .filter(|change| change.occurred_at.is_some_and(|t| t <= at)),
+        )
+    }
+
+    fn replay<'a>(changes: impl Iterator<Item = &'a SettingChange>) -> BTreeMap<String, String> {
+        let mut values = BTreeMap::new();
+        for change in changes {
+            match &change.value {
+                Some(value) => values.insert(change.key.clone(), value.clone()),
+                None => values.remove(&change.key),
+            };
+        }
+        values
+    }
+}
+
+impl Projection for SettingsHistory {
+    type Event = SettingsEvent;
+    type Error = Infallible;
+
+    fn apply(&mut self, event: &Envelope<SettingsEvent>) -> Result<(), Self::Error> {
+        let (key, value) = match &event.payload {
+            SettingsEvent::Set { key, value } => (key.clone(), Some(value.clone())),
+            SettingsEvent::Unset { key } => (key.clone(), None),
+        };
+        self.changes
+            .entry(event.stream_id.clone())
+            .or_default()
+            .push(SettingChange {
+                key,
+                value,
+                version: event.version,
+                occurred_at: event.occurred_at(),
+                metadata: event.metadata.clone(),
+            });
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+use crate::projection::ProjectionRunner;
+use crate::repository::Repository;
+
+#[test]
+fn test_settings_aggregate() {
+    let mut repository = Repository::<Settings, _>::new(OnMemoryEventStore::new());
+    let checkout = "checkout".to_string();
+
+    let events = repository
+        .execute(&checkout, |s| Settings::set(s, "new_cart", "true"))
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(repository
+        .execute(&checkout, |s| Settings::set(s, "new_cart", "true"))
+        .unwrap()
+        .is_empty());
+    assert!(repository
+        .execute(&checkout, |s| Settings::unset(s, "missing"))
+        .unwrap()
+        .is_empty());
+
+    let loaded = repository.load(&checkout).unwrap().unwrap();
+    assert_eq!(loaded.version, 1);
+    assert!(loaded.state.is_enabled("new_cart"));
+    assert!(!loaded.state.is_enabled("missing"));
+}
+
+#[test]
+fn test_settings_history() {
+    let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    let change = |version, event: SettingsEvent, secs| {
+        Envelope::new(
+            "settings-checkout",
+            version,
+            Settings::event_type(&event),
+            event,
+        )
+        .with_occurred_at(at(secs))
+        .with_metadata("principal", "alice")
+    };
+    let mut store = OnMemoryEventStore::new();
+    store
+        .save(&[
+            change(
+                1,
+                SettingsEvent::Set {
+                    key: "new_cart".to_string(),
+                    value: "false".to_string(),
+                },
+                10,
+            ),
+            change(
+                2,
+                SettingsEvent::Set {
+                    key: "currency".to_string(),
+                    value: "JPY".to_string(),
+                },
+                20,
+            ),
+            change(
+                3,
+                SettingsEvent::Set {
+                    key: "new_cart".to_string(),
+                    value: "true".to_string(),
+                },
+                30,
+            ),
+            change(
+                4,
+                SettingsEvent::Unset {
+                    key: "currency".to_string(),
+                },
+                40,
+            ),
+        ])
+        .unwrap();
+    let mut runner = ProjectionRunner::new(SettingsHistory::new());
+    runner.run_batch(&store, 100).unwrap();
+    let history = runner.projection();
+
+    assert_eq!(history.changes("checkout").len(), 4);
+    assert_eq!(history.changes_of("checkout", "new_cart").count(), 2);
+    assert_eq!(
+        history.changes("checkout")[0].metadata.get("principal"),
+        Some(&"alice".to_string())
+    );
+
+    let values = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    assert_eq!(history.as_of("checkout", at(5)), values(&[]));
+    assert_eq!(
+        history.as_of("checkout", at(25)),
+        values(&[("new_cart", "false"), ("currency", "JPY")])
+    );
+    assert_eq!(
+        history.as_of("checkout", at(40)),
+        values(&[("new_cart", "true")])
+    );
+    assert_eq!(
+        history.as_of_version("checkout", 3),
+        values(&[("new_cart", "true"), ("currency", "JPY")])
+    );
+    assert!(history.changes("billing").is_empty());
+}
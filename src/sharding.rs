@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests;
+
+use crate::envelope::Envelope;
+use crate::event_store::ReadOnlyEventStore;
+
+/// Get the category of a stream: the part of its ID before the first `-`.
+pub fn category_of(stream_id: &str) -> &str {
+    stream_id.split_once('-').map_or(stream_id, |(c, _)| c)
+}
+
+/// Hash a stream ID with FNV-1a, which is stable across builds and platforms.
+pub(crate) fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A category whose index is split into a fixed number of shards.
+///
+/// Every stream of the category belongs to exactly one shard, so that each shard can be
+/// replayed in parallel while the events of a stream stay in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardedCategory {
+    category: String,
+    shards: u32,
+}
+
+impl ShardedCategory {
+    /// Split the category into `shards` shards.
+    pub fn new(category: impl Into<String>, shards: u32) -> Self {
+        Self {
+            category: category.into(),
+            shards: shards.max(1),
+        }
+    }
+
+    /// Get the number of shards.
+    pub fn shards(&self) -> u32 {
+        self.shards
+    }
+
+    /// Get the shard of the stream.
+    pub fn shard_of(&self, stream_id: &str) -> u32 {
+        (stable_hash(stream_id) % self.shards as u64) as u32
+    }
+
+    /// Get the name of the shard, as `category-N`.
+    pub fn shard_name(&self, shard: u32) -> String {
+        format!("{}-{}", self.category, shard)
+    }
+
+    /// Check whether the event belongs to the shard.
+    pub fn contains<E>(&self, shard: u32, event: &Envelope<E>) -> bool {
+        category_of(&event.stream_id) == self.category && self.shard_of(&event.stream_id) == shard
+    }
+
+    /// Read the next batch of the shard's events from the global log, starting at the position.
+    ///
+    /// Returns the events and the position to resume from.
+    pub fn read_shard<S, E>(
+        &self,
+        store: &S,
+        shard: u32,
+        from: u64,
+        batch_size: usize,
+    ) -> Result<(Vec<Envelope<E>>, u64), S::Error>
+    where
+        S: ReadOnlyEventStore<Persistable = Envelope<E>>,
+    {
+        let events = store.read_all(from, batch_size)?;
+        let next = events.last().map_or(from, |e| e.position + 1);
+        let events = events
+            .into_iter()
+            .filter(|e| self.contains(shard, e))
+            .collect();
+        Ok((events, next))
+    }
+}
+
+/// Merge events read from several shards back into global order.
+///
+/// Each shard must be in global order, which keeps the events of every stream in order.
+pub fn merge_shards<E>(shards: Vec<Vec<Envelope<E>>>) -> Vec<Envelope<E>> {
+    let mut merged = shards.into_iter().flatten().collect::<Vec<_>>();
+    merged.sort_by_key(|e| e.position);
+    merged
+}
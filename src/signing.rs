@@ -0,0 +1,574 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Subscription;
+use crate::crypto;
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, LogHead, ReadOnlyEventStore,
+    TransactionManager,
+};
+
+/// Metadata key recording the signature of the envelope, in hexadecimal.
+pub const SIGNATURE_METADATA_KEY: &str = "signature";
+/// Metadata key recording the ID of the key which signed the envelope.
+pub const SIGNING_KEY_METADATA_KEY: &str = "signing_key";
+
+/// Ed25519 secret key.
+#[derive(Clone)]
+pub struct SigningKey {
+    seed: [u8; 32],
+    verifying_key: VerifyingKey,
+}
+
+impl SigningKey {
+    /// Create the key from its 32-byte secret seed.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            verifying_key: VerifyingKey(crypto::ed25519_public_key(&seed)),
+        }
+    }
+
+    /// Get the public key matching the key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key
+    }
+
+    /// Sign the message.
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        crypto::ed25519_sign(&self.seed, message)
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("verifying_key", &self.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Ed25519 public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VerifyingKey([u8; 32]);
+
+impl VerifyingKey {
+    /// Create the key from its 32 bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get the 32 bytes of the key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Verify the signature of the message.
+    pub fn verify(&self, message: &[u8], signature: &[u8; 64]) -> bool {
+        crypto::ed25519_verify(&self.0, message, signature)
+    }
+}
+
+/// Types whose bytes can be signed.
+pub trait Signable {
+    /// Get the bytes covered by the signature.
+    fn signable_bytes(&self) -> Vec<u8>;
+}
+
+impl Signable for Vec<u8> {
+    fn signable_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+impl Signable for String {
+    fn signable_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+/// Types which provide the keys signing and verifying envelopes.
+pub trait KeyProvider {
+    /// Get the ID and the key signing new envelopes, if any.
+    fn signing_key(&self) -> Option<(&str, &SigningKey)>;
+    /// Get the public key with the ID.
+    fn verifying_key(&self, key_id: &str) -> Option<VerifyingKey>;
+}
+
+/// Key provider keeping the keys in memory.
+///
+/// Keys retired from signing remain registered for verification, so that keys can be rotated.
+#[derive(Debug, Default)]
+pub struct OnMemoryKeyProvider {
+    signing: Option<(String, SigningKey)>,
+    verifying: HashMap<String, VerifyingKey>,
+}
+
+impl OnMemoryKeyProvider {
+    /// Create a provider without keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sign new envelopes with the key, and register it for verification.
+    pub fn with_signing_key(mut self, key_id: impl Into<String>, key: SigningKey) -> Self {
+        let key_id = key_id.into();
+        self.verifying.insert(key_id.clone(), key.verifying_key());
+        self.signing = Some((key_id, key));
+        self
+    }
+
+    /// Register the public key for verification.
+    pub fn with_verifying_key(mut self, key_id: impl Into<String>, key: VerifyingKey) -> Self {
+        self.verifying.insert(key_id.into(), key);
+        self
+    }
+}
+
+impl KeyProvider for OnMemoryKeyProvider {
+    fn signing_key(&self) -> Option<(&str, &SigningKey)> {
+        self.signing.as_ref().map(|(id, key)| (id.as_str(), key))
+    }
+
+    fn verifying_key(&self, key_id: &str) -> Option<VerifyingKey> {
+        self.verifying.get(key_id).copied()
+    }
+}
+
+// Length-prefixed encoding of the signed parts of the envelope, including its version and
+// position, so that an event cannot be moved within its stream or the log.
+fn signed_message<P: Signable>(envelope: &Envelope<P>, key_id: &str) -> Vec<u8> {
+    let mut message = Vec::new();
+    let mut push = |bytes: &[u8]| {
+        message.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+        message.extend_from_slice(bytes);
+    };
+    push(key_id.as_bytes());
+    push(&envelope.id.as_u128().to_be_bytes());
+    push(envelope.stream_id.as_bytes());
+    push(&envelope.version.to_be_bytes());
+    push(&envelope.position.to_be_bytes());
+    push(envelope.event_type.as_bytes());
+    push(&envelope.payload.signable_bytes());
+    for (key, value) in &envelope.metadata {
+        if key != SIGNATURE_METADATA_KEY && key != SIGNING_KEY_METADATA_KEY {
+            push(key.as_bytes());
+            push(value.as_bytes());
+        }
+    }
+    message
+}
+
+/// Sign the envelope with the current key of the provider, recording the signature in its
+/// metadata.
+///
+/// The signature covers the version and position of the envelope, which must be the ones it
+/// is stored at.
+pub fn sign<P: Signable>(
+    envelope: &mut Envelope<P>,
+    keys: &impl KeyProvider,
+) -> Result<(), SignatureError> {
+    let (key_id, key) = keys.signing_key().ok_or(SignatureError::NoSigningKey)?;
+    let signature = key.sign(&signed_message(envelope, key_id));
+    envelope
+        .metadata
+        .insert(SIGNING_KEY_METADATA_KEY.to_string(), key_id.to_string());
+    envelope.metadata.insert(
+        SIGNATURE_METADATA_KEY.to_string(),
+        crypto::to_hex(&signature),
+    );
+    Ok(())
+}
+
+/// Verify the signature recorded in the metadata of the envelope.
+pub fn verify<P: Signable>(
+    envelope: &Envelope<P>,
+    keys: &impl KeyProvider,
+) -> Result<(), SignatureError> {
+    let id = envelope.id.to_string();
+    let (key_id, signature) = match (
+        envelope.metadata.get(SIGNING_KEY_METADATA_KEY),
+        envelope.metadata.get(SIGNATURE_METADATA_KEY),
+    ) {
+        (Some(key_id), Some(signature)) => (key_id, signature),
+        _ => return Err(SignatureError::Missing(id)),
+    };
+    let key = keys
+        .verifying_key(key_id)
+        .ok_or_else(|| SignatureError::UnknownKey(key_id.clone()))?;
+    let signature: [u8; 64] = crypto::from_hex(signature)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SignatureError::Invalid(id.clone()))?;
+    if !key.verify(&signed_message(envelope, key_id), &signature) {
+        return Err(SignatureError::Invalid(id));
+    }
+    Ok(())
+}
+
+/// Event store wrapper signing the envelopes before they are appended.
+///
+/// The signature covers the version and position of the envelopes, so they are assigned before
+/// signing: the version following the last event of the stream, and the position of the head of
+/// the log. In a transaction, the envelopes saved since it began are counted too, as the wrapped
+/// store only assigns them on commit. The wrapped store must assign the same ones, which holds
+/// for a single writer. An envelope stored at another version or position fails verification
+/// rather than going unnoticed.
+pub struct SigningEventStore<S, K> {
+    store: S,
+    keys: K,
+    pending: Option<PendingWrites>,
+}
+
+/// Envelopes saved in the current transaction, not committed to the wrapped store yet.
+#[derive(Default)]
+struct PendingWrites {
+    count: u64,
+    versions: HashMap<String, u64>,
+}
+
+impl<S, K> SigningEventStore<S, K> {
+    /// Wrap the store, signing with the keys.
+    pub fn new(store: S, keys: K) -> Self {
+        Self {
+            store,
+            keys,
+            pending: None,
+        }
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Unwrap the store.
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S, K, P> SigningEventStore<S, K>
+where
+    S: EventStore<Persistable = Envelope<P>>
+        + LogHead<StreamId = String, Persistable = Envelope<P>, Error = <S as EventStore>::Error>,
+    K: KeyProvider,
+    P: Signable + Clone,
+{
+    /// Sign the envelopes at the versions and positions they will be stored at.
+    fn sign_all<'a>(
+        &self,
+        events: impl IntoIterator<Item = (&'a String, &'a Envelope<P>)>,
+    ) -> Result<Vec<Envelope<P>>, SigningError<<S as EventStore>::Error>>
+    where
+        P: 'a,
+    {
+        let pending = self.pending.as_ref();
+        let head = self.store.head_position().map_err(SigningError::Inner)?
+            + pending.map_or(0, |p| p.count);
+        let mut versions = pending.map(|p| p.versions.clone()).unwrap_or_default();
+        let mut signed = Vec::new();
+        for (position, (stream_id, event)) in (head..).zip(events) {
+            let version = match versions.get_mut(stream_id) {
+                Some(version) => version,
+                None => {
+                    let stream = self.store.load(stream_id).map_err(SigningError::Inner)?;
+                    let last = stream.last().map_or(0, |e| e.version);
+                    versions.entry(stream_id.clone()).or_insert(last)
+                }
+            };
+            *version += 1;
+            let mut event = event.clone();
+            event.stream_id = stream_id.clone();
+            event.version = *version;
+            event.position = position;
+            sign(&mut event, &self.keys).map_err(SigningError::Signature)?;
+            signed.push(event);
+        }
+        Ok(signed)
+    }
+
+    /// Count the envelopes saved in the current transaction, if any.
+    fn record(&mut self, signed: &[Envelope<P>]) {
+        if let Some(pending) = &mut self.pending {
+            pending.count += signed.len() as u64;
+            for event in signed {
+                pending
+                    .versions
+                    .insert(event.stream_id.clone(), event.version);
+            }
+        }
+    }
+}
+
+impl<S, K, P> EventStore for SigningEventStore<S, K>
+where
+    S: EventStore<Persistable = Envelope<P>>
+        + LogHead<StreamId = String, Persistable = Envelope<P>, Error = <S as EventStore>::Error>,
+    K: KeyProvider,
+    P: Signable + Clone,
+{
+    type Persistable = Envelope<P>;
+    type Error = SigningError<<S as EventStore>::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let signed = self.sign_all(events.iter().map(|e| (&e.stream_id, e)))?;
+        self.store.save(&signed).map_err(SigningError::Inner)?;
+        self.record(&signed);
+        Ok(())
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let mut signed = self
+            .sign_all(
+                appends
+                    .iter()
+                    .flat_map(|(stream_id, _, events)| events.iter().map(move |e| (stream_id, e))),
+            )
+            .map_err(AppendError::Store)?;
+        let mut signed_appends = Vec::with_capacity(appends.len());
+        for (stream_id, expected, events) in appends {
+            let rest = signed.split_off(events.len());
+            signed_appends.push((stream_id.clone(), *expected, signed));
+            signed = rest;
+        }
+        self.store
+            .append_multi(&signed_appends)
+            .map_err(|e| e.map_store(SigningError::Inner))?;
+        for (_, _, events) in &signed_appends {
+            self.record(events);
+        }
+        Ok(())
+    }
+}
+
+/// Begun transactions track the envelopes saved in them, until committed or rolled back.
+impl<S: TransactionManager, K> TransactionManager for SigningEventStore<S, K> {
+    type Error = S::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.store.begin()?;
+        self.pending = Some(PendingWrites::default());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.store.commit()?;
+        self.pending = None;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.pending = None;
+        self.store.rollback()
+    }
+}
+
+impl<S: EventLoader, K> EventLoader for SigningEventStore<S, K> {
+    type StreamId = S::StreamId;
+    type Persistable = S::Persistable;
+    type Error = SigningError<S::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load(stream_id).map_err(SigningError::Inner)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store
+            .load_after(stream_id, version)
+            .map_err(SigningError::Inner)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.store
+            .read_multi(stream_ids)
+            .map_err(SigningError::Inner)
+    }
+}
+
+impl<S: ReadOnlyEventStore, K> ReadOnlyEventStore for SigningEventStore<S, K> {
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store
+            .read_all(from, limit)
+            .map_err(SigningError::Inner)
+    }
+}
+
+/// Event store wrapper verifying the signatures of the loaded envelopes.
+pub struct VerifyingEventStore<S, K> {
+    store: S,
+    keys: K,
+}
+
+impl<S, K> VerifyingEventStore<S, K> {
+    /// Wrap the store, verifying with the keys.
+    pub fn new(store: S, keys: K) -> Self {
+        Self { store, keys }
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S, K, P> VerifyingEventStore<S, K>
+where
+    S: EventLoader<Persistable = Envelope<P>>,
+    K: KeyProvider,
+    P: Signable,
+{
+    fn verify_all(
+        &self,
+        events: Vec<Envelope<P>>,
+    ) -> Result<Vec<Envelope<P>>, VerificationError<S::Error>> {
+        for event in &events {
+            verify(event, &self.keys).map_err(VerificationError::Signature)?;
+        }
+        Ok(events)
+    }
+}
+
+impl<S, K, P> EventLoader for VerifyingEventStore<S, K>
+where
+    S: EventLoader<Persistable = Envelope<P>>,
+    K: KeyProvider,
+    P: Signable,
+{
+    type StreamId = S::StreamId;
+    type Persistable = Envelope<P>;
+    type Error = VerificationError<S::Error>;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let events = self
+            .store
+            .load(stream_id)
+            .map_err(VerificationError::Inner)?;
+        self.verify_all(events)
+    }
+}
+
+impl<S, K, P> ReadOnlyEventStore for VerifyingEventStore<S, K>
+where
+    S: ReadOnlyEventStore<Persistable = Envelope<P>>,
+    K: KeyProvider,
+    P: Signable,
+{
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let events = self
+            .store
+            .read_all(from, limit)
+            .map_err(VerificationError::Inner)?;
+        self.verify_all(events)
+    }
+}
+
+/// Subscription wrapper verifying the signatures of the received envelopes.
+pub struct VerifyingSubscription<S, K> {
+    subscription: S,
+    keys: K,
+}
+
+impl<S, K> VerifyingSubscription<S, K> {
+    /// Wrap the subscription, verifying with the keys.
+    pub fn new(subscription: S, keys: K) -> Self {
+        Self { subscription, keys }
+    }
+}
+
+impl<S, K, P> Subscription<Envelope<P>> for VerifyingSubscription<S, K>
+where
+    S: Subscription<Envelope<P>>,
+    K: KeyProvider,
+    P: Signable,
+{
+    type Error = VerificationError<S::Error>;
+
+    fn poll(&mut self) -> Result<Option<Envelope<P>>, Self::Error> {
+        let event = self.subscription.poll().map_err(VerificationError::Inner)?;
+        if let Some(event) = &event {
+            verify(event, &self.keys).map_err(VerificationError::Signature)?;
+        }
+        Ok(event)
+    }
+}
+
+/// Error returned when signing or verifying an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The key provider has no key to sign with.
+    NoSigningKey,
+    /// The envelope with the ID is not signed.
+    Missing(String),
+    /// No public key is registered with the ID.
+    UnknownKey(String),
+    /// The signature of the envelope with the ID does not match its content.
+    Invalid(String),
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::NoSigningKey => write!(f, "no signing key"),
+            SignatureError::Missing(id) => write!(f, "event {} is not signed", id),
+            SignatureError::UnknownKey(key_id) => write!(f, "unknown signing key: {}", key_id),
+            SignatureError::Invalid(id) => write!(f, "invalid signature of event {}", id),
+        }
+    }
+}
+
+impl Error for SignatureError {}
+
+/// Error returned by the [`SigningEventStore`].
+#[derive(Debug)]
+pub enum SigningError<E> {
+    /// The wrapped store failed.
+    Inner(E),
+    /// An envelope could not be signed.
+    Signature(SignatureError),
+}
+
+impl<E: Error> fmt::Display for SigningError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigningError::Inner(e) => write!(f, "{}", e),
+            SigningError::Signature(e) => write!(f, "signing error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for SigningError<E> {}
+
+/// Error returned by the verifying wrappers.
+#[derive(Debug)]
+pub enum VerificationError<E> {
+    /// The wrapped store or subscription failed.
+    Inner(E),
+    /// An envelope failed verification.
+    Signature(SignatureError),
+}
+
+impl<E: Error> fmt::Display for VerificationError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::Inner(e) => write!(f, "{}", e),
+            VerificationError::Signature(e) => write!(f, "verification error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for VerificationError<E> {}
@@ -0,0 +1,211 @@
+use super::*;
+use crate::aggregate::Aggregate;
+use crate::broker::{OnMemoryBroker, Publisher};
+use crate::event_store::{EventStore, OnMemoryEventStore, TransactionManager};
+use crate::repository::Repository;
+
+fn keys(seed: u8) -> OnMemoryKeyProvider {
+    OnMemoryKeyProvider::new()
+        .with_signing_key(format!("key-{}", seed), SigningKey::from_seed([seed; 32]))
+}
+
+fn event(payload: &str) -> Envelope<String> {
+    Envelope::new("ledger-1", 0, "Posted", payload.to_string()).with_metadata("user_id", "alice")
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let keys = keys(1);
+    let mut envelope = event("100");
+    assert_eq!(
+        verify(&envelope, &keys),
+        Err(SignatureError::Missing(envelope.id.to_string()))
+    );
+    sign(&mut envelope, &keys).unwrap();
+    assert_eq!(envelope.metadata[SIGNING_KEY_METADATA_KEY], "key-1");
+    assert_eq!(verify(&envelope, &keys), Ok(()));
+
+    // The version and position are covered, so an event cannot be moved.
+    let mut moved = envelope.clone();
+    moved.version = 7;
+    assert!(verify(&moved, &keys).is_err());
+    let mut moved = envelope.clone();
+    moved.position = 7;
+    assert!(verify(&moved, &keys).is_err());
+
+    let mut tampered = envelope.clone();
+    tampered.payload = "1000".to_string();
+    assert_eq!(
+        verify(&tampered, &keys),
+        Err(SignatureError::Invalid(envelope.id.to_string()))
+    );
+    let mut tampered = envelope.clone();
+    tampered
+        .metadata
+        .insert("user_id".to_string(), "mallory".to_string());
+    assert!(verify(&tampered, &keys).is_err());
+
+    assert_eq!(
+        verify(&envelope, &OnMemoryKeyProvider::new()),
+        Err(SignatureError::UnknownKey("key-1".to_string()))
+    );
+    assert_eq!(
+        sign(&mut event("1"), &OnMemoryKeyProvider::new()),
+        Err(SignatureError::NoSigningKey)
+    );
+}
+
+#[test]
+fn test_key_rotation() {
+    let old = SigningKey::from_seed([1; 32]);
+    let mut envelope = event("100");
+    sign(
+        &mut envelope,
+        &OnMemoryKeyProvider::new().with_signing_key("old", old.clone()),
+    )
+    .unwrap();
+
+    let rotated = OnMemoryKeyProvider::new()
+        .with_signing_key("new", SigningKey::from_seed([2; 32]))
+        .with_verifying_key("old", old.verifying_key());
+    assert_eq!(verify(&envelope, &rotated), Ok(()));
+}
+
+#[test]
+fn test_sign_on_append_and_verify_on_load() {
+    let mut store = SigningEventStore::new(OnMemoryEventStore::new(), keys(1));
+    store.save(&[event("100"), event("-40")]).unwrap();
+    store
+        .save(&[
+            Envelope::new("ledger-2", 0, "Posted", "5".to_string()),
+            event("-10"),
+        ])
+        .unwrap();
+    let stored = store.inner().read_all(0, 10).unwrap();
+    assert_eq!(
+        stored
+            .iter()
+            .map(|e| (e.version, e.position))
+            .collect::<Vec<_>>(),
+        [(1, 0), (2, 1), (1, 2), (3, 3)]
+    );
+
+    let verified = VerifyingEventStore::new(store.into_inner(), keys(1));
+    assert_eq!(verified.read_all(0, 10).unwrap(), stored);
+
+    let mut tampered = OnMemoryEventStore::new();
+    let mut forged = stored[1].clone();
+    forged.payload = "40".to_string();
+    tampered.save(&[stored[0].clone(), forged]).unwrap();
+    let verified = VerifyingEventStore::new(tampered, keys(1));
+    assert!(matches!(
+        verified.read_all(0, 10),
+        Err(VerificationError::Signature(SignatureError::Invalid(_)))
+    ));
+}
+
+#[test]
+fn test_sign_several_saves_in_a_transaction() {
+    let mut store = SigningEventStore::new(OnMemoryEventStore::new(), keys(1));
+    store.save(&[event("100")]).unwrap();
+    store.begin().unwrap();
+    store.save(&[event("-40")]).unwrap();
+    store
+        .save(&[
+            event("-10"),
+            Envelope::new("ledger-2", 0, "Posted", "5".to_string()),
+        ])
+        .unwrap();
+    store.commit().unwrap();
+
+    // A rolled back transaction leaves nothing to account for.
+    store.begin().unwrap();
+    store.save(&[event("7")]).unwrap();
+    store.rollback().unwrap();
+    store.save(&[event("-20")]).unwrap();
+
+    let stored = store.inner().read_all(0, 10).unwrap();
+    assert_eq!(
+        stored
+            .iter()
+            .map(|e| (e.version, e.position))
+            .collect::<Vec<_>>(),
+        [(1, 0), (2, 1), (3, 2), (1, 3), (4, 4)]
+    );
+    let verified = VerifyingEventStore::new(store.into_inner(), keys(1));
+    assert_eq!(verified.read_all(0, 10).unwrap(), stored);
+}
+
+#[derive(Debug, Clone, Default)]
+struct Ledger {
+    balance: i64,
+}
+
+impl Aggregate for Ledger {
+    type Id = u32;
+    type Event = String;
+
+    fn aggregate_type() -> &'static str {
+        "ledger"
+    }
+
+    fn event_type(_event: &String) -> String {
+        "Posted".to_string()
+    }
+
+    fn apply(&mut self, event: &String) {
+        self.balance += event.parse::<i64>().unwrap();
+    }
+}
+
+#[test]
+fn test_sign_in_unit_of_work() {
+    let mut repository =
+        Repository::<Ledger, _>::new(SigningEventStore::new(OnMemoryEventStore::new(), keys(1)));
+    repository.save(&1, &["100".to_string()]).unwrap();
+
+    let mut unit = repository.unit_of_work();
+    unit.execute(
+        &1,
+        |ledger| vec![(-ledger.unwrap().balance / 2).to_string()],
+    )
+    .unwrap();
+    unit.record(&2, &["50".to_string()]).unwrap();
+    unit.record(&1, &["-10".to_string()]).unwrap();
+    assert_eq!(unit.commit().unwrap(), 3);
+
+    let store = repository.store().inner();
+    let stored = store.read_all(0, 10).unwrap();
+    assert_eq!(
+        stored
+            .iter()
+            .map(|e| (e.stream_id.as_str(), e.version, e.position))
+            .collect::<Vec<_>>(),
+        [
+            ("ledger-1", 1, 0),
+            ("ledger-1", 2, 1),
+            ("ledger-1", 3, 2),
+            ("ledger-2", 1, 3)
+        ]
+    );
+    for event in &stored {
+        assert_eq!(verify(event, &keys(1)), Ok(()));
+    }
+}
+
+#[test]
+fn test_verify_on_subscription() {
+    let mut broker = OnMemoryBroker::new();
+    let mut signed = event("100");
+    sign(&mut signed, &keys(1)).unwrap();
+    broker.publish(&signed).unwrap();
+    broker.publish(&event("unsigned")).unwrap();
+
+    let mut subscription = VerifyingSubscription::new(broker, keys(1));
+    assert_eq!(subscription.poll().unwrap(), Some(signed));
+    assert!(matches!(
+        subscription.poll(),
+        Err(VerificationError::Signature(SignatureError::Missing(_)))
+    ));
+    assert_eq!(subscription.poll().unwrap(), None);
+}
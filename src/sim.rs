@@ -0,0 +1,350 @@
+#[cfg(test)]
+mod tests;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+use crate::broker::{Publisher, Subscription};
+use crate::clock::{Clock, ManualClock};
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, OnMemoryEventStore,
+    OnMemoryEventStoreError, ReadOnlyEventStore, TransactionManager,
+};
+use crate::rng::SeededRng;
+use crate::workflow::{Workflow, WorkflowEvent, WorkflowInstance};
+
+/// Probabilities of the faults injected by the simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultPlan {
+    /// Probability that saving events fails.
+    pub append_failure: f64,
+    /// Probability that a polled message is delivered again later.
+    pub duplicate_delivery: f64,
+}
+
+/// Deterministic runtime for simulation testing: a seeded random generator, a virtual clock,
+/// and in-memory components injecting faults according to the plan.
+///
+/// Running the same scenario with the same seed always yields the same faults.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    rng: Rc<RefCell<SeededRng>>,
+    clock: ManualClock,
+    faults: FaultPlan,
+}
+
+impl Simulation {
+    /// Create a simulation from the seed, without faults.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rc::new(RefCell::new(SeededRng::new(seed))),
+            clock: ManualClock::new(SystemTime::UNIX_EPOCH),
+            faults: FaultPlan::default(),
+        }
+    }
+
+    /// Inject the faults of the plan in the components created afterwards.
+    pub fn with_faults(mut self, faults: FaultPlan) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Get the virtual clock.
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    /// Get a random number in `0..bound`, from the simulation's generator.
+    pub fn below(&self, bound: u64) -> u64 {
+        self.rng.borrow_mut().below(bound)
+    }
+
+    /// Create an event store injecting append failures.
+    pub fn event_store<E: Clone>(&self) -> SimEventStore<E> {
+        SimEventStore {
+            store: OnMemoryEventStore::new(),
+            rng: self.rng.clone(),
+            append_failure: self.faults.append_failure,
+        }
+    }
+
+    /// Create a broker injecting duplicate deliveries.
+    pub fn broker<M: Clone>(&self) -> SimBroker<M> {
+        SimBroker {
+            queue: VecDeque::new(),
+            rng: self.rng.clone(),
+            duplicate_delivery: self.faults.duplicate_delivery,
+        }
+    }
+
+    /// Create a harness running the workflow instance on the virtual clock.
+    pub fn saga<'w, S, C, E>(
+        &self,
+        workflow: &'w Workflow<S, C, E>,
+        instance: WorkflowInstance<S>,
+    ) -> SagaHarness<'w, S, C, E> {
+        SagaHarness {
+            workflow,
+            instance,
+            clock: self.clock.clone(),
+            dispatched: Vec::new(),
+        }
+    }
+}
+
+impl Clock for Simulation {
+    fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+}
+
+/// Event store of a [`Simulation`].
+#[derive(Debug)]
+pub struct SimEventStore<E> {
+    store: OnMemoryEventStore<E>,
+    rng: Rc<RefCell<SeededRng>>,
+    append_failure: f64,
+}
+
+impl<E: Clone> SimEventStore<E> {
+    /// Get the underlying store, bypassing fault injection.
+    pub fn inner(&self) -> &OnMemoryEventStore<E> {
+        &self.store
+    }
+}
+
+impl<E: Clone> EventStore for SimEventStore<E> {
+    type Persistable = Envelope<E>;
+    type Error = SimError;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        if self.rng.borrow_mut().chance(self.append_failure) {
+            return Err(SimError::InjectedFault("append"));
+        }
+        self.store.save(events).map_err(SimError::Store)
+    }
+
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        if self.rng.borrow_mut().chance(self.append_failure) {
+            return Err(AppendError::Store(SimError::InjectedFault("append")));
+        }
+        self.store
+            .append_multi(appends)
+            .map_err(|e| e.map_store(SimError::Store))
+    }
+}
+
+impl<E: Clone> EventLoader for SimEventStore<E> {
+    type StreamId = String;
+    type Persistable = Envelope<E>;
+    type Error = SimError;
+
+    fn load(&self, stream_id: &Self::StreamId) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.load(stream_id).map_err(SimError::Store)
+    }
+
+    fn load_after(
+        &self,
+        stream_id: &Self::StreamId,
+        version: u64,
+    ) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store
+            .load_after(stream_id, version)
+            .map_err(SimError::Store)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[Self::StreamId],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.store.read_multi(stream_ids).map_err(SimError::Store)
+    }
+}
+
+impl<E: Clone> ReadOnlyEventStore for SimEventStore<E> {
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.store.read_all(from, limit).map_err(SimError::Store)
+    }
+}
+
+impl<E: Clone> TransactionManager for SimEventStore<E> {
+    type Error = SimError;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.store.begin().map_err(SimError::Store)
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.store.commit().map_err(SimError::Store)
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        self.store.rollback().map_err(SimError::Store)
+    }
+}
+
+/// Broker of a [`Simulation`].
+#[derive(Debug)]
+pub struct SimBroker<M> {
+    queue: VecDeque<M>,
+    rng: Rc<RefCell<SeededRng>>,
+    duplicate_delivery: f64,
+}
+
+impl<M: Clone> Publisher<M> for SimBroker<M> {
+    type Error = SimError;
+
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error> {
+        self.queue.push_back(message.clone());
+        Ok(())
+    }
+}
+
+impl<M: Clone> Subscription<M> for SimBroker<M> {
+    type Error = SimError;
+
+    fn poll(&mut self) -> Result<Option<M>, Self::Error> {
+        let message = match self.queue.pop_front() {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+        let mut rng = self.rng.borrow_mut();
+        if rng.chance(self.duplicate_delivery) {
+            let at = rng.below(self.queue.len() as u64 + 1) as usize;
+            self.queue.insert(at, message.clone());
+        }
+        Ok(Some(message))
+    }
+}
+
+/// Harness driving a workflow instance with the virtual clock of a [`Simulation`], to test
+/// that its timeouts fire the expected compensations.
+///
+/// The instance is ticked whenever the clock is advanced, and the dispatched commands are kept.
+pub struct SagaHarness<'w, S, C, E> {
+    workflow: &'w Workflow<S, C, E>,
+    instance: WorkflowInstance<S>,
+    clock: ManualClock,
+    dispatched: Vec<C>,
+}
+
+impl<S, C: Clone, E> SagaHarness<'_, S, C, E> {
+    /// Get the workflow instance.
+    pub fn instance(&self) -> &WorkflowInstance<S> {
+        &self.instance
+    }
+
+    /// Get all the commands dispatched so far, in order.
+    pub fn dispatched(&self) -> &[C] {
+        &self.dispatched
+    }
+
+    /// Start the instance, returning the commands to dispatch.
+    pub fn start(&mut self) -> Vec<C> {
+        let commands = self.workflow.start(&mut self.instance, self.clock.now());
+        self.record(commands)
+    }
+
+    /// Handle an event of the domain, returning the commands to dispatch.
+    pub fn handle(&mut self, event: &E) -> Vec<C> {
+        let commands = self
+            .workflow
+            .handle(&mut self.instance, event, self.clock.now());
+        self.record(commands)
+    }
+
+    /// Move the clock forward and tick the instance, returning the commands to dispatch.
+    pub fn advance(&mut self, duration: Duration) -> Vec<C> {
+        self.clock.advance(duration);
+        let commands = self.workflow.tick(&mut self.instance, self.clock.now());
+        self.record(commands)
+    }
+
+    /// Move the clock to the deadline of the running step and tick the instance, returning the
+    /// commands to dispatch. Nothing happens if the running step has no timeout.
+    pub fn advance_to_deadline(&mut self) -> Vec<C> {
+        match self.workflow.deadline(&self.instance) {
+            Some(deadline) => {
+                let now = self.clock.now();
+                self.advance(deadline.duration_since(now).unwrap_or_default())
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Assert that the named step timed out.
+    #[track_caller]
+    pub fn assert_timed_out(&self, step: &str) {
+        let index = self.step_index(step);
+        assert!(
+            self.instance
+                .history()
+                .contains(&WorkflowEvent::StepFailed {
+                    step: index,
+                    timed_out: true
+                }),
+            "step {} did not time out, history: {:?}",
+            step,
+            self.instance.history()
+        );
+    }
+
+    /// Assert that the named steps were compensated, in order, and no other step was.
+    #[track_caller]
+    pub fn assert_compensated(&self, steps: &[&str]) {
+        let compensated = self
+            .instance
+            .history()
+            .iter()
+            .filter_map(|e| match e {
+                WorkflowEvent::StepCompensated { step } => {
+                    Some(self.workflow.steps()[*step].name())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(compensated, steps, "unexpected compensated steps");
+    }
+
+    fn record(&mut self, commands: Vec<C>) -> Vec<C> {
+        self.dispatched.extend(commands.iter().cloned());
+        commands
+    }
+
+    #[track_caller]
+    fn step_index(&self, step: &str) -> usize {
+        self.workflow
+            .steps()
+            .iter()
+            .position(|s| s.name() == step)
+            .unwrap_or_else(|| panic!("no step named {}", step))
+    }
+}
+
+/// Error returned by the components of a [`Simulation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimError {
+    /// A fault was injected at the named point.
+    InjectedFault(&'static str),
+    /// The underlying store failed.
+    Store(OnMemoryEventStoreError),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::InjectedFault(point) => write!(f, "injected fault: {}", point),
+            SimError::Store(e) => write!(f, "store error: {}", e),
+        }
+    }
+}
+
+impl Error for SimError {}
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use super::*;
+
+/// Publishes ten transfers, consumes them idempotently and saves one event per transfer,
+/// retrying failed appends. Returns the trace of what happened.
+fn run_scenario(seed: u64) -> (Vec<String>, Vec<Envelope<u32>>) {
+    let sim = Simulation::new(seed).with_faults(FaultPlan {
+        append_failure: 0.3,
+        duplicate_delivery: 0.3,
+    });
+    let mut store = sim.event_store::<u32>();
+    let mut broker = sim.broker::<u32>();
+    let mut processed = HashSet::new();
+    let mut trace = Vec::new();
+
+    for transfer in 0..10 {
+        broker.publish(&transfer).unwrap();
+    }
+    while let Some(transfer) = broker.poll().unwrap() {
+        sim.clock().advance(Duration::from_millis(sim.below(100)));
+        if !processed.insert(transfer) {
+            trace.push(format!("duplicate {}", transfer));
+            continue;
+        }
+        let event = Envelope::new(format!("transfer-{}", transfer), 0, "Done", transfer);
+        while let Err(e) = store.save(std::slice::from_ref(&event)) {
+            trace.push(format!("{} on {}", e, transfer));
+        }
+    }
+    trace.push(format!("{:?}", sim.now()));
+    (trace, store.inner().read_all(0, usize::MAX).unwrap())
+}
+
+#[test]
+fn test_simulation_is_deterministic() {
+    let (trace, _) = run_scenario(7);
+    let (replayed, _) = run_scenario(7);
+    assert_eq!(trace, replayed);
+    assert!(trace.iter().any(|t| t.starts_with("duplicate")));
+    assert!(trace
+        .iter()
+        .any(|t| t.starts_with("injected fault: append")));
+}
+
+#[test]
+fn test_simulation_invariants_hold_under_faults() {
+    for seed in 0..20 {
+        let (_, events) = run_scenario(seed);
+        assert_eq!(events.len(), 10, "seed {}", seed);
+        assert!(events.iter().all(|e| e.version == 1), "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_append_multi_injects_faults() {
+    let sim = Simulation::new(3).with_faults(FaultPlan {
+        append_failure: 0.5,
+        ..FaultPlan::default()
+    });
+    let mut store = sim.event_store::<u32>();
+    let mut failures = 0;
+    for transfer in 0..10 {
+        let stream_id = format!("transfer-{}", transfer);
+        let event = Envelope::new(stream_id.clone(), 0, "Done", transfer);
+        let appends = [(stream_id, ExpectedVersion::NoStream, vec![event])];
+        while let Err(e) = store.append_multi(&appends) {
+            assert_eq!(e, AppendError::Store(SimError::InjectedFault("append")));
+            failures += 1;
+        }
+    }
+    assert!(failures > 0);
+    assert_eq!(store.inner().read_all(0, usize::MAX).unwrap().len(), 10);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PaymentCommand {
+    Reserve,
+    Release,
+    Charge,
+    Escalate,
+}
+
+#[derive(Debug)]
+enum PaymentEvent {
+    Reserved,
+    Charged,
+}
+
+fn payment_workflow() -> Workflow<(), PaymentCommand, PaymentEvent> {
+    use crate::workflow::Step;
+
+    Workflow::new("payment")
+        .step(
+            Step::new("reserve", |_: &()| PaymentCommand::Reserve)
+                .succeeds_on(|e| matches!(e, PaymentEvent::Reserved))
+                .compensate_with(|_| PaymentCommand::Release),
+        )
+        .step(
+            Step::new("charge", |_: &()| PaymentCommand::Charge)
+                .succeeds_on(|e| matches!(e, PaymentEvent::Charged))
+                .compensate_with(|_| PaymentCommand::Escalate)
+                .timeout(Duration::from_secs(60)),
+        )
+}
+
+#[test]
+fn test_saga_timeout_fires_compensations() {
+    let sim = Simulation::new(7);
+    let workflow = payment_workflow();
+    let mut saga = sim.saga(&workflow, WorkflowInstance::new("payment-1", ()));
+    saga.start();
+    saga.handle(&PaymentEvent::Reserved);
+
+    assert!(saga.advance(Duration::from_secs(59)).is_empty());
+    assert_eq!(saga.advance_to_deadline(), [PaymentCommand::Release]);
+    assert_eq!(sim.now(), SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+    saga.assert_timed_out("charge");
+    saga.assert_compensated(&["reserve"]);
+    assert_eq!(
+        saga.dispatched(),
+        [
+            PaymentCommand::Reserve,
+            PaymentCommand::Charge,
+            PaymentCommand::Release
+        ]
+    );
+    assert!(saga.advance_to_deadline().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "step charge did not time out")]
+fn test_saga_completed_before_timeout() {
+    let sim = Simulation::new(7);
+    let workflow = payment_workflow();
+    let mut saga = sim.saga(&workflow, WorkflowInstance::new("payment-1", ()));
+    saga.start();
+    saga.handle(&PaymentEvent::Reserved);
+    saga.advance(Duration::from_secs(30));
+    saga.handle(&PaymentEvent::Charged);
+    saga.advance(Duration::from_secs(60));
+    saga.assert_compensated(&[]);
+    saga.assert_timed_out("charge");
+}
@@ -0,0 +1,461 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::marker::PhantomData;
+use std::time::{Duration, SystemTime};
+
+use crate::runtime::HousekeepingJob;
+use crate::sharding::category_of;
+
+/// State of a stream captured at a given version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot<S> {
+    /// ID of the stream.
+    pub stream_id: String,
+    /// Version of the stream the state was captured at.
+    pub version: u64,
+    /// The captured state.
+    pub state: S,
+}
+
+/// Types which represent a store of snapshots.
+pub trait SnapshotStore<S> {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Save the snapshot.
+    fn save(&mut self, snapshot: Snapshot<S>) -> Result<(), Self::Error>;
+    /// Load the latest snapshot of the stream.
+    fn load(&self, stream_id: &str) -> Result<Option<Snapshot<S>>, Self::Error>;
+}
+
+/// Snapshot store keeping every snapshot in memory.
+#[derive(Debug)]
+pub struct OnMemorySnapshotStore<S> {
+    snapshots: HashMap<String, Vec<Snapshot<S>>>,
+}
+
+impl<S> OnMemorySnapshotStore<S> {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self {
+            snapshots: HashMap::new(),
+        }
+    }
+
+    /// Get all the snapshots of the stream, oldest first.
+    pub fn history(&self, stream_id: &str) -> &[Snapshot<S>] {
+        self.snapshots
+            .get(stream_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl<S> Default for OnMemorySnapshotStore<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Clone> SnapshotStore<S> for OnMemorySnapshotStore<S> {
+    type Error = Infallible;
+
+    fn save(&mut self, snapshot: Snapshot<S>) -> Result<(), Self::Error> {
+        self.snapshots
+            .entry(snapshot.stream_id.clone())
+            .or_default()
+            .push(snapshot);
+        Ok(())
+    }
+
+    fn load(&self, stream_id: &str) -> Result<Option<Snapshot<S>>, Self::Error> {
+        Ok(self.history(stream_id).last().cloned())
+    }
+}
+
+/// Types whose changes can be captured as deltas.
+pub trait Diffable {
+    /// Associated Type representing the change between two states.
+    type Delta;
+
+    /// Compute the change turning `previous` into `self`.
+    fn diff(&self, previous: &Self) -> Self::Delta;
+    /// Apply the change to the state.
+    fn apply_delta(&mut self, delta: &Self::Delta);
+}
+
+/// Entry persisted by the [`DeltaSnapshotStore`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotEntry<S: Diffable> {
+    /// The whole state.
+    Full(u64, S),
+    /// The change against the previous entry.
+    Delta(u64, S::Delta),
+}
+
+/// Snapshot store persisting deltas against the previous snapshot, with periodic full snapshots.
+pub struct DeltaSnapshotStore<S: Diffable> {
+    full_every: usize,
+    entries: HashMap<String, Vec<SnapshotEntry<S>>>,
+}
+
+impl<S: Diffable + Clone> DeltaSnapshotStore<S> {
+    /// Create a store writing a full snapshot every `full_every` snapshots of a stream.
+    pub fn new(full_every: usize) -> Self {
+        Self {
+            full_every: full_every.max(1),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Get the persisted entries of the stream, oldest first.
+    pub fn entries(&self, stream_id: &str) -> &[SnapshotEntry<S>] {
+        self.entries
+            .get(stream_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn reassemble(entries: &[SnapshotEntry<S>]) -> Option<(u64, S)> {
+        let start = entries
+            .iter()
+            .rposition(|e| matches!(e, SnapshotEntry::Full(..)))?;
+        let mut reassembled = match &entries[start] {
+            SnapshotEntry::Full(version, state) => (*version, state.clone()),
+            SnapshotEntry::Delta(..) => unreachable!(),
+        };
+        for entry in &entries[start + 1..] {
+            if let SnapshotEntry::Delta(version, delta) = entry {
+                reassembled.0 = *version;
+                reassembled.1.apply_delta(delta);
+            }
+        }
+        Some(reassembled)
+    }
+}
+
+impl<S: Diffable + Clone> SnapshotStore<S> for DeltaSnapshotStore<S> {
+    type Error = Infallible;
+
+    fn save(&mut self, snapshot: Snapshot<S>) -> Result<(), Self::Error> {
+        let entries = self.entries.entry(snapshot.stream_id).or_default();
+        let since_full = entries
+            .iter()
+            .rev()
+            .take_while(|e| matches!(e, SnapshotEntry::Delta(..)))
+            .count();
+        let entry = match Self::reassemble(entries) {
+            Some((_, previous)) if since_full + 1 < self.full_every => {
+                SnapshotEntry::Delta(snapshot.version, snapshot.state.diff(&previous))
+            }
+            _ => SnapshotEntry::Full(snapshot.version, snapshot.state),
+        };
+        entries.push(entry);
+        Ok(())
+    }
+
+    fn load(&self, stream_id: &str) -> Result<Option<Snapshot<S>>, Self::Error> {
+        Ok(
+            Self::reassemble(self.entries(stream_id)).map(|(version, state)| Snapshot {
+                stream_id: stream_id.to_string(),
+                version,
+                state,
+            }),
+        )
+    }
+}
+
+/// Types which represent a snapshot store able to delete old snapshots.
+pub trait PrunableSnapshotStore<S>: SnapshotStore<S> {
+    /// Get the IDs of the streams holding snapshots.
+    fn stream_ids(&self) -> Vec<String>;
+    /// Get all the snapshots of the stream, oldest first.
+    fn snapshots(&self, stream_id: &str) -> Result<Vec<Snapshot<S>>, Self::Error>;
+    /// Delete the snapshots of the stream not taken at one of the versions, returning the
+    /// number deleted.
+    fn retain_versions(&mut self, stream_id: &str, versions: &[u64]) -> Result<usize, Self::Error>;
+}
+
+impl<S: Clone> PrunableSnapshotStore<S> for OnMemorySnapshotStore<S> {
+    fn stream_ids(&self) -> Vec<String> {
+        self.snapshots.keys().cloned().collect()
+    }
+
+    fn snapshots(&self, stream_id: &str) -> Result<Vec<Snapshot<S>>, Self::Error> {
+        Ok(self.history(stream_id).to_vec())
+    }
+
+    fn retain_versions(&mut self, stream_id: &str, versions: &[u64]) -> Result<usize, Self::Error> {
+        let history = match self.snapshots.get_mut(stream_id) {
+            Some(history) => history,
+            None => return Ok(0),
+        };
+        let before = history.len();
+        history.retain(|snapshot| versions.contains(&snapshot.version));
+        Ok(before - history.len())
+    }
+}
+
+impl<S: Diffable + Clone> PrunableSnapshotStore<S> for DeltaSnapshotStore<S> {
+    fn stream_ids(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    fn snapshots(&self, stream_id: &str) -> Result<Vec<Snapshot<S>>, Self::Error> {
+        let mut snapshots: Vec<Snapshot<S>> = Vec::new();
+        for entry in self.entries(stream_id) {
+            let (version, state) = match entry {
+                SnapshotEntry::Full(version, state) => (*version, state.clone()),
+                SnapshotEntry::Delta(version, delta) => {
+                    let mut state = match snapshots.last() {
+                        Some(previous) => previous.state.clone(),
+                        None => continue,
+                    };
+                    state.apply_delta(delta);
+                    (*version, state)
+                }
+            };
+            snapshots.push(Snapshot {
+                stream_id: stream_id.to_string(),
+                version,
+                state,
+            });
+        }
+        Ok(snapshots)
+    }
+
+    /// The kept snapshots are re-encoded, since deleting an entry breaks the deltas after it.
+    fn retain_versions(&mut self, stream_id: &str, versions: &[u64]) -> Result<usize, Self::Error> {
+        let snapshots = self.snapshots(stream_id)?;
+        let before = snapshots.len();
+        self.entries.remove(stream_id);
+        let mut kept = 0;
+        for snapshot in snapshots {
+            if versions.contains(&snapshot.version) {
+                self.save(snapshot)?;
+                kept += 1;
+            }
+        }
+        Ok(before - kept)
+    }
+}
+
+/// Types which decide when the state of a stream should be snapshotted.
+pub trait SnapshotPolicy {
+    /// Decide whether to snapshot the stream at `version`, given the number of events and the
+    /// time elapsed since its last snapshot.
+    fn should_snapshot(&self, version: u64, events_since: u64, elapsed: Duration) -> bool;
+
+    /// Snapshot only when both policies agree.
+    fn and<P: SnapshotPolicy>(self, other: P) -> Both<Self, P>
+    where
+        Self: Sized,
+    {
+        Both(self, other)
+    }
+
+    /// Snapshot when either policy agrees.
+    fn or<P: SnapshotPolicy>(self, other: P) -> Either<Self, P>
+    where
+        Self: Sized,
+    {
+        Either(self, other)
+    }
+}
+
+/// Snapshot once the number of events since the last snapshot reaches the count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EveryNEvents(pub u64);
+
+impl SnapshotPolicy for EveryNEvents {
+    fn should_snapshot(&self, _version: u64, events_since: u64, _elapsed: Duration) -> bool {
+        events_since >= self.0.max(1)
+    }
+}
+
+/// Snapshot once the duration has elapsed since the last snapshot, if events were appended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EveryInterval(pub Duration);
+
+impl SnapshotPolicy for EveryInterval {
+    fn should_snapshot(&self, _version: u64, events_since: u64, elapsed: Duration) -> bool {
+        events_since > 0 && elapsed >= self.0
+    }
+}
+
+/// Snapshot only streams holding at least the number of events, if events were appended.
+///
+/// Combine it with another policy to snapshot only the streams expensive to replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinStreamSize(pub u64);
+
+impl SnapshotPolicy for MinStreamSize {
+    fn should_snapshot(&self, version: u64, events_since: u64, _elapsed: Duration) -> bool {
+        events_since > 0 && version >= self.0
+    }
+}
+
+/// Never snapshot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NeverSnapshot;
+
+impl SnapshotPolicy for NeverSnapshot {
+    fn should_snapshot(&self, _version: u64, _events_since: u64, _elapsed: Duration) -> bool {
+        false
+    }
+}
+
+/// Policy built by [`SnapshotPolicy::and`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Both<A, B>(A, B);
+
+impl<A: SnapshotPolicy, B: SnapshotPolicy> SnapshotPolicy for Both<A, B> {
+    fn should_snapshot(&self, version: u64, events_since: u64, elapsed: Duration) -> bool {
+        self.0.should_snapshot(version, events_since, elapsed)
+            && self.1.should_snapshot(version, events_since, elapsed)
+    }
+}
+
+/// Policy built by [`SnapshotPolicy::or`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Either<A, B>(A, B);
+
+impl<A: SnapshotPolicy, B: SnapshotPolicy> SnapshotPolicy for Either<A, B> {
+    fn should_snapshot(&self, version: u64, events_since: u64, elapsed: Duration) -> bool {
+        self.0.should_snapshot(version, events_since, elapsed)
+            || self.1.should_snapshot(version, events_since, elapsed)
+    }
+}
+
+/// Snapshot policies per stream category, so that aggregates stored together can be
+/// snapshotted differently.
+pub struct SnapshotPolicies {
+    default: Box<dyn SnapshotPolicy>,
+    categories: HashMap<String, Box<dyn SnapshotPolicy>>,
+}
+
+impl SnapshotPolicies {
+    /// Create a set applying the policy to the categories without a policy of their own.
+    pub fn new(default: impl SnapshotPolicy + 'static) -> Self {
+        Self {
+            default: Box::new(default),
+            categories: HashMap::new(),
+        }
+    }
+
+    /// Set the policy of the streams of the category.
+    pub fn category(
+        mut self,
+        category: impl Into<String>,
+        policy: impl SnapshotPolicy + 'static,
+    ) -> Self {
+        self.categories.insert(category.into(), Box::new(policy));
+        self
+    }
+
+    /// Get the policy applying to the stream.
+    pub fn policy_for(&self, stream_id: &str) -> &dyn SnapshotPolicy {
+        self.categories
+            .get(category_of(stream_id))
+            .map_or(&*self.default, |policy| &**policy)
+    }
+}
+
+impl Default for SnapshotPolicies {
+    fn default() -> Self {
+        Self::new(NeverSnapshot)
+    }
+}
+
+/// Retention rules of the snapshots of a stream. The latest snapshot is always kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotRetention {
+    /// Keep the latest snapshots.
+    KeepLast(usize),
+    /// Keep the latest snapshots of every schema version, so that the state can still be
+    /// loaded by code reading an older schema.
+    KeepLastPerSchemaVersion(usize),
+}
+
+type SchemaVersionFn<S> = Box<dyn Fn(&S) -> u32>;
+
+/// Housekeeping job deleting the snapshots no longer needed under the retention rules.
+///
+/// The job owns the snapshot store and ignores the store of the runtime.
+pub struct SnapshotCollector<S, N> {
+    snapshots: N,
+    retention: SnapshotRetention,
+    schema_version: SchemaVersionFn<S>,
+    state: PhantomData<S>,
+}
+
+impl<S, N: PrunableSnapshotStore<S>> SnapshotCollector<S, N> {
+    /// Create a collector of the snapshots of the store.
+    ///
+    /// Every snapshot is of the same schema version until [`Self::schema_version`] is set.
+    pub fn new(snapshots: N, retention: SnapshotRetention) -> Self {
+        Self {
+            snapshots,
+            retention,
+            schema_version: Box::new(|_| 0),
+            state: PhantomData,
+        }
+    }
+
+    /// Read the schema version of the snapshots with `schema_version`.
+    pub fn schema_version(mut self, schema_version: impl Fn(&S) -> u32 + 'static) -> Self {
+        self.schema_version = Box::new(schema_version);
+        self
+    }
+
+    /// Get the snapshot store.
+    pub fn snapshots(&self) -> &N {
+        &self.snapshots
+    }
+
+    /// Get the snapshot store mutably.
+    pub fn snapshots_mut(&mut self) -> &mut N {
+        &mut self.snapshots
+    }
+
+    /// Delete the stale snapshots of every stream, returning the number deleted.
+    pub fn collect(&mut self) -> Result<usize, N::Error> {
+        let mut deleted = 0;
+        for stream_id in self.snapshots.stream_ids() {
+            let snapshots = self.snapshots.snapshots(&stream_id)?;
+            let mut kept: HashMap<u32, usize> = HashMap::new();
+            let mut versions = Vec::new();
+            for snapshot in snapshots.iter().rev() {
+                let (schema_version, keep) = match self.retention {
+                    SnapshotRetention::KeepLast(keep) => (0, keep),
+                    SnapshotRetention::KeepLastPerSchemaVersion(keep) => {
+                        ((self.schema_version)(&snapshot.state), keep)
+                    }
+                };
+                let count = kept.entry(schema_version).or_default();
+                if *count < keep.max(1) {
+                    *count += 1;
+                    versions.push(snapshot.version);
+                }
+            }
+            if versions.len() < snapshots.len() {
+                deleted += self.snapshots.retain_versions(&stream_id, &versions)?;
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+impl<S, N: PrunableSnapshotStore<S>, T> HousekeepingJob<T> for SnapshotCollector<S, N> {
+    fn name(&self) -> &'static str {
+        "snapshot_gc"
+    }
+
+    fn run(&mut self, _store: &mut T, _now: SystemTime) -> Result<usize, String> {
+        self.collect().map_err(|e| e.to_string())
+    }
+}
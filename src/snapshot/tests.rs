@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::runtime::{CruxRuntime, JobReport};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Org {
+    users: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OrgDelta {
+    upserted: Vec<(String, String)>,
+    removed: Vec<String>,
+}
+
+impl Diffable for Org {
+    type Delta = OrgDelta;
+
+    fn diff(&self, previous: &Self) -> Self::Delta {
+        OrgDelta {
+            upserted: self
+                .users
+                .iter()
+                .filter(|(k, v)| previous.users.get(*k) != Some(v))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            removed: previous
+                .users
+                .keys()
+                .filter(|k| !self.users.contains_key(*k))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn apply_delta(&mut self, delta: &Self::Delta) {
+        for (k, v) in &delta.upserted {
+            self.users.insert(k.clone(), v.clone());
+        }
+        for k in &delta.removed {
+            self.users.remove(k);
+        }
+    }
+}
+
+fn org(users: &[(&str, &str)]) -> Org {
+    Org {
+        users: users
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    }
+}
+
+fn snapshot(version: u64, state: Org) -> Snapshot<Org> {
+    Snapshot {
+        stream_id: "org-1".to_string(),
+        version,
+        state,
+    }
+}
+
+#[test]
+fn test_on_memory_snapshot_store() {
+    let mut store = OnMemorySnapshotStore::new();
+    assert_eq!(store.load("org-1").unwrap(), None);
+
+    store.save(snapshot(1, org(&[("u1", "alice")]))).unwrap();
+    store.save(snapshot(5, org(&[("u2", "bob")]))).unwrap();
+    assert_eq!(store.load("org-1").unwrap().unwrap().version, 5);
+    assert_eq!(store.history("org-1").len(), 2);
+}
+
+#[test]
+fn test_delta_snapshot_store() {
+    let mut store = DeltaSnapshotStore::new(3);
+    let states = [
+        org(&[("u1", "alice")]),
+        org(&[("u1", "alice"), ("u2", "bob")]),
+        org(&[("u2", "bobby")]),
+        org(&[("u2", "bobby"), ("u3", "carol")]),
+    ];
+    for (i, state) in states.iter().enumerate() {
+        store.save(snapshot(i as u64 + 1, state.clone())).unwrap();
+        let loaded = store.load("org-1").unwrap().unwrap();
+        assert_eq!(loaded.version, i as u64 + 1);
+        assert_eq!(loaded.state, *state);
+    }
+
+    let entries = store.entries("org-1");
+    assert!(matches!(entries[0], SnapshotEntry::Full(1, _)));
+    assert_eq!(
+        entries[2],
+        SnapshotEntry::Delta(
+            3,
+            OrgDelta {
+                upserted: vec![("u2".to_string(), "bobby".to_string())],
+                removed: vec!["u1".to_string()],
+            }
+        )
+    );
+    assert!(matches!(entries[3], SnapshotEntry::Full(4, _)));
+}
+
+#[test]
+fn test_built_in_policies() {
+    let minute = Duration::from_secs(60);
+    assert!(!EveryNEvents(3).should_snapshot(2, 2, minute));
+    assert!(EveryNEvents(3).should_snapshot(3, 3, Duration::ZERO));
+    assert!(!EveryInterval(minute).should_snapshot(5, 0, minute));
+    assert!(EveryInterval(minute).should_snapshot(5, 1, minute));
+    assert!(!MinStreamSize(100).should_snapshot(99, 99, minute));
+    assert!(MinStreamSize(100).should_snapshot(100, 1, Duration::ZERO));
+    assert!(!NeverSnapshot.should_snapshot(1000, 1000, minute));
+
+    let large_streams = MinStreamSize(100).and(EveryNEvents(10));
+    assert!(!large_streams.should_snapshot(50, 50, minute));
+    assert!(!large_streams.should_snapshot(105, 5, minute));
+    assert!(large_streams.should_snapshot(110, 10, minute));
+
+    let either = EveryNEvents(10).or(EveryInterval(minute));
+    assert!(either.should_snapshot(3, 3, minute));
+    assert!(either.should_snapshot(10, 10, Duration::ZERO));
+}
+
+#[test]
+fn test_policies_per_category() {
+    let policies = SnapshotPolicies::new(EveryNEvents(100))
+        .category("org", EveryNEvents(2))
+        .category("audit", NeverSnapshot);
+    let zero = Duration::ZERO;
+    assert!(policies.policy_for("org-1").should_snapshot(2, 2, zero));
+    assert!(!policies.policy_for("user-1").should_snapshot(2, 2, zero));
+    assert!(policies
+        .policy_for("user-1")
+        .should_snapshot(100, 100, zero));
+    assert!(!policies
+        .policy_for("audit-1")
+        .should_snapshot(100, 100, zero));
+    assert!(!SnapshotPolicies::default()
+        .policy_for("org-1")
+        .should_snapshot(100, 100, zero));
+}
+
+fn versions<N: PrunableSnapshotStore<Org>>(store: &N) -> Vec<u64> {
+    store
+        .snapshots("org-1")
+        .unwrap()
+        .into_iter()
+        .map(|s| s.version)
+        .collect()
+}
+
+#[test]
+fn test_collect_keep_last() {
+    let mut collector =
+        SnapshotCollector::new(DeltaSnapshotStore::new(2), SnapshotRetention::KeepLast(2));
+    for version in 1..=5 {
+        let state = org(&[("u1", &format!("alice{}", version))]);
+        collector
+            .snapshots_mut()
+            .save(snapshot(version, state))
+            .unwrap();
+    }
+
+    assert_eq!(collector.collect(), Ok(3));
+    assert_eq!(versions(collector.snapshots()), vec![4, 5]);
+    assert!(matches!(
+        collector.snapshots().entries("org-1")[0],
+        SnapshotEntry::Full(4, _)
+    ));
+    assert_eq!(
+        collector.snapshots().load("org-1").unwrap().unwrap().state,
+        org(&[("u1", "alice5")])
+    );
+    assert_eq!(collector.collect(), Ok(0));
+}
+
+#[test]
+fn test_collect_per_schema_version() {
+    // Snapshots written before the migration hold no user.
+    let mut collector = SnapshotCollector::new(
+        OnMemorySnapshotStore::new(),
+        SnapshotRetention::KeepLastPerSchemaVersion(1),
+    )
+    .schema_version(|org: &Org| if org.users.is_empty() { 1 } else { 2 });
+    for version in 1..=3 {
+        collector
+            .snapshots_mut()
+            .save(snapshot(version, Org::default()))
+            .unwrap();
+    }
+    for version in 4..=5 {
+        collector
+            .snapshots_mut()
+            .save(snapshot(version, org(&[("u1", "alice")])))
+            .unwrap();
+    }
+
+    let mut runtime = CruxRuntime::new((), ManualClock::default()).with_job(collector);
+    assert_eq!(
+        runtime.run_housekeeping(),
+        vec![JobReport {
+            name: "snapshot_gc",
+            result: Ok(3),
+        }]
+    );
+}
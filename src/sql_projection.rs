@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+
+use crate::envelope::Envelope;
+use crate::projection::Projection;
+
+/// Table recording the position each SQL projection has reached.
+pub const CHECKPOINT_TABLE: &str = "crux_projection_checkpoints";
+
+/// A value bound to a SQL statement or read from a result row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    /// `NULL`.
+    Null,
+    /// An integer.
+    Integer(i64),
+    /// A floating point number.
+    Real(f64),
+    /// A string.
+    Text(String),
+}
+
+/// A SQL statement with its positional parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlStatement {
+    /// The SQL text, with `?` placeholders.
+    pub sql: String,
+    /// Values bound to the placeholders, in order.
+    pub params: Vec<SqlValue>,
+}
+
+impl SqlStatement {
+    /// Create a statement without parameters.
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Bind the next parameter.
+    pub fn bind(mut self, value: SqlValue) -> Self {
+        self.params.push(value);
+        self
+    }
+}
+
+/// Types which represent a connection to a SQL database, such as an in-memory DuckDB or
+/// SQLite database for analytical read models.
+pub trait SqlConnection {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Execute the statement, returning the number of rows changed.
+    fn execute(&mut self, statement: &SqlStatement) -> Result<usize, Self::Error>;
+    /// Run the query, returning the result rows.
+    fn query(&mut self, statement: &SqlStatement) -> Result<Vec<Vec<SqlValue>>, Self::Error>;
+}
+
+/// Types which translate events into SQL statements maintaining a read model.
+pub trait SqlProjector {
+    /// Associated Type representing the event type.
+    type Event;
+
+    /// Get the name of the projection, recorded in the checkpoint table.
+    fn name(&self) -> &str;
+    /// Get the statements creating the tables of the read model if they do not exist.
+    fn schema(&self) -> Vec<SqlStatement>;
+    /// Get the statements applying the event to the read model.
+    fn statements(&self, event: &Envelope<Self::Event>) -> Vec<SqlStatement>;
+}
+
+/// Projection maintaining a SQL read model incrementally, to be queried with SQL.
+///
+/// The statements of each event are executed in a transaction together with the update of the
+/// checkpoint, so that a restarted projection resumes from [`position`](Self::position)
+/// without applying an event twice. Events before the checkpoint, e.g. replayed by a runner
+/// started at an older position, are skipped.
+pub struct SqlProjection<P, C> {
+    projector: P,
+    connection: C,
+}
+
+impl<P: SqlProjector, C: SqlConnection> SqlProjection<P, C> {
+    /// Create the tables of the read model and of the checkpoints.
+    pub fn new(projector: P, mut connection: C) -> Result<Self, C::Error> {
+        connection.execute(&SqlStatement::new(format!(
+            "CREATE TABLE IF NOT EXISTS {} (name TEXT PRIMARY KEY, position BIGINT NOT NULL)",
+            CHECKPOINT_TABLE
+        )))?;
+        for statement in projector.schema() {
+            connection.execute(&statement)?;
+        }
+        Ok(Self {
+            projector,
+            connection,
+        })
+    }
+
+    /// Get the position of the next event to apply, to start a
+    /// [`ProjectionRunner`](crate::projection::ProjectionRunner) at.
+    pub fn position(&mut self) -> Result<u64, C::Error> {
+        let rows = self.connection.query(
+            &SqlStatement::new(format!(
+                "SELECT position FROM {} WHERE name = ?",
+                CHECKPOINT_TABLE
+            ))
+            .bind(SqlValue::Text(self.projector.name().to_string())),
+        )?;
+        Ok(match rows.first().and_then(|row| row.first()) {
+            Some(SqlValue::Integer(position)) => *position as u64,
+            _ => 0,
+        })
+    }
+
+    /// Query the read model.
+    pub fn query(&mut self, statement: &SqlStatement) -> Result<Vec<Vec<SqlValue>>, C::Error> {
+        self.connection.query(statement)
+    }
+
+    /// Get the projector.
+    pub fn projector(&self) -> &P {
+        &self.projector
+    }
+
+    fn apply_in_transaction(&mut self, event: &Envelope<P::Event>) -> Result<(), C::Error> {
+        if event.position < self.position()? {
+            return Ok(());
+        }
+        for statement in self.projector.statements(event) {
+            self.connection.execute(&statement)?;
+        }
+        self.connection.execute(
+            &SqlStatement::new(format!(
+                "INSERT INTO {} (name, position) VALUES (?, ?) \
+                 ON CONFLICT (name) DO UPDATE SET position = excluded.position",
+                CHECKPOINT_TABLE
+            ))
+            .bind(SqlValue::Text(self.projector.name().to_string()))
+            .bind(SqlValue::Integer(event.position as i64 + 1)),
+        )?;
+        Ok(())
+    }
+}
+
+impl<P: SqlProjector, C: SqlConnection> Projection for SqlProjection<P, C> {
+    type Event = P::Event;
+    type Error = C::Error;
+
+    fn apply(&mut self, event: &Envelope<P::Event>) -> Result<(), C::Error> {
+        self.connection.execute(&SqlStatement::new("BEGIN"))?;
+        match self.apply_in_transaction(event) {
+            Ok(()) => self
+                .connection
+                .execute(&SqlStatement::new("COMMIT"))
+                .map(|_| ()),
+            Err(e) => {
+                // The error of the statement is more telling than a failure to roll back.
+                let _ = self.connection.execute(&SqlStatement::new("ROLLBACK"));
+                Err(e)
+            }
+        }
+    }
+}
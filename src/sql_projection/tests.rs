@@ -0,0 +1,147 @@
+use std::fmt;
+
+use super::*;
+use crate::event_store::{EventStore, OnMemoryEventStore};
+use crate::projection::ProjectionRunner;
+
+#[derive(Debug, PartialEq)]
+struct ConnectionError(&'static str);
+
+impl fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection error: {}", self.0)
+    }
+}
+
+impl Error for ConnectionError {}
+
+/// Connection recording the statements, answering checkpoint queries from the last commit.
+#[derive(Default)]
+struct RecordingConnection {
+    log: Vec<String>,
+    pending_checkpoint: Option<i64>,
+    checkpoint: Option<i64>,
+    failing_rollback: bool,
+}
+
+impl SqlConnection for RecordingConnection {
+    type Error = ConnectionError;
+
+    fn execute(&mut self, statement: &SqlStatement) -> Result<usize, ConnectionError> {
+        if statement.params.contains(&SqlValue::Integer(-1)) {
+            return Err(ConnectionError("invalid amount"));
+        }
+        if self.failing_rollback && statement.sql == "ROLLBACK" {
+            return Err(ConnectionError("rollback"));
+        }
+        self.log.push(statement.sql.clone());
+        match statement.sql.as_str() {
+            "COMMIT" => self.checkpoint = self.pending_checkpoint.take().or(self.checkpoint),
+            "ROLLBACK" => self.pending_checkpoint = None,
+            sql if sql.starts_with("INSERT INTO crux_projection_checkpoints") => {
+                if let SqlValue::Integer(position) = statement.params[1] {
+                    self.pending_checkpoint = Some(position);
+                }
+            }
+            _ => {}
+        }
+        Ok(1)
+    }
+
+    fn query(&mut self, _: &SqlStatement) -> Result<Vec<Vec<SqlValue>>, ConnectionError> {
+        Ok(self
+            .checkpoint
+            .map(|position| vec![vec![SqlValue::Integer(position)]])
+            .unwrap_or_default())
+    }
+}
+
+struct SalesProjector;
+
+impl SqlProjector for SalesProjector {
+    type Event = i64;
+
+    fn name(&self) -> &str {
+        "sales"
+    }
+
+    fn schema(&self) -> Vec<SqlStatement> {
+        vec![SqlStatement::new(
+            "CREATE TABLE IF NOT EXISTS sales (stream TEXT, amount BIGINT)",
+        )]
+    }
+
+    fn statements(&self, event: &Envelope<i64>) -> Vec<SqlStatement> {
+        vec![SqlStatement::new("INSERT INTO sales VALUES (?, ?)")
+            .bind(SqlValue::Text(event.stream_id.clone()))
+            .bind(SqlValue::Integer(event.payload))]
+    }
+}
+
+#[test]
+fn test_incremental_updates() {
+    let mut store = OnMemoryEventStore::new();
+    for amount in [10, 20] {
+        store
+            .save(&[Envelope::new("shop-1", 0, "Sold", amount)])
+            .unwrap();
+    }
+    let mut projection =
+        SqlProjection::new(SalesProjector, RecordingConnection::default()).unwrap();
+    assert_eq!(projection.position(), Ok(0));
+
+    let mut runner = ProjectionRunner::new(projection);
+    assert_eq!(runner.run_batch(&store, 10).unwrap(), 2);
+    let mut projection = runner.into_inner();
+    assert_eq!(projection.position(), Ok(2));
+    assert_eq!(
+        projection.connection.log[2..6],
+        [
+            "BEGIN",
+            "INSERT INTO sales VALUES (?, ?)",
+            "INSERT INTO crux_projection_checkpoints (name, position) VALUES (?, ?) \
+             ON CONFLICT (name) DO UPDATE SET position = excluded.position",
+            "COMMIT",
+        ]
+    );
+
+    store
+        .save(&[Envelope::new("shop-1", 0, "Sold", 30)])
+        .unwrap();
+    let position = projection.position().unwrap();
+    let mut runner = ProjectionRunner::starting_at(projection, position);
+    assert_eq!(runner.run_batch(&store, 10).unwrap(), 1);
+    assert_eq!(runner.position(), 3);
+}
+
+#[test]
+fn test_failed_event_rolls_back() {
+    let mut projection =
+        SqlProjection::new(SalesProjector, RecordingConnection::default()).unwrap();
+    let result = projection.apply(&Envelope::new("shop-1", 1, "Sold", -1));
+    assert_eq!(result, Err(ConnectionError("invalid amount")));
+    assert_eq!(projection.connection.log.last().unwrap(), "ROLLBACK");
+    assert_eq!(projection.position(), Ok(0));
+
+    projection.connection.failing_rollback = true;
+    let result = projection.apply(&Envelope::new("shop-1", 1, "Sold", -1));
+    assert_eq!(result, Err(ConnectionError("invalid amount")));
+}
+
+#[test]
+fn test_skip_events_before_checkpoint() {
+    let mut projection =
+        SqlProjection::new(SalesProjector, RecordingConnection::default()).unwrap();
+    let mut event = Envelope::new("shop-1", 1, "Sold", 10);
+    projection.apply(&event).unwrap();
+    assert_eq!(projection.position(), Ok(1));
+
+    // A runner restarted before the checkpoint replays the event.
+    projection.connection.log.clear();
+    projection.apply(&event).unwrap();
+    assert_eq!(projection.connection.log, ["BEGIN", "COMMIT"]);
+
+    event.position = 1;
+    projection.apply(&event).unwrap();
+    assert_eq!(projection.position(), Ok(2));
+}
@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::serialization::Codec;
+use crate::snapshot::{PrunableSnapshotStore, Snapshot, SnapshotStore};
+
+const MAGIC: &str = "crux-standby 1";
+
+/// Latest snapshots of every stream together with the checkpoints of the projections, exported
+/// from one environment to stand up another, e.g. a warm standby restored from a backup,
+/// without replaying the whole log first.
+///
+/// Checkpoints are the positions of projections whose read models are restored alongside, so
+/// that their runners resume from there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandbyBundle<S> {
+    snapshots: Vec<Snapshot<S>>,
+    checkpoints: BTreeMap<String, u64>,
+}
+
+impl<S> StandbyBundle<S> {
+    /// Create an empty bundle.
+    pub fn new() -> Self {
+        Self {
+            snapshots: Vec::new(),
+            checkpoints: BTreeMap::new(),
+        }
+    }
+
+    /// Export the latest snapshot of every stream of the store, by stream ID.
+    pub fn export<N: PrunableSnapshotStore<S>>(snapshots: &N) -> Result<Self, N::Error> {
+        let mut stream_ids = snapshots.stream_ids();
+        stream_ids.sort();
+        let mut bundle = Self::new();
+        for stream_id in stream_ids {
+            if let Some(snapshot) = snapshots.load(&stream_id)? {
+                bundle.snapshots.push(snapshot);
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Add the checkpoint of the projection, the position of the next event it applies.
+    pub fn checkpoint(mut self, projection: impl Into<String>, position: u64) -> Self {
+        self.checkpoints.insert(projection.into(), position);
+        self
+    }
+
+    /// Get the snapshots.
+    pub fn snapshots(&self) -> &[Snapshot<S>] {
+        &self.snapshots
+    }
+
+    /// Get the checkpoints, by projection name.
+    pub fn checkpoints(&self) -> &BTreeMap<String, u64> {
+        &self.checkpoints
+    }
+
+    /// Get the checkpoint of the projection, to start its runner at.
+    pub fn position(&self, projection: &str) -> Option<u64> {
+        self.checkpoints.get(projection).copied()
+    }
+
+    /// Save the snapshots into the store of the new environment, returning their number.
+    pub fn import<N: SnapshotStore<S>>(self, store: &mut N) -> Result<usize, N::Error> {
+        let imported = self.snapshots.len();
+        for snapshot in self.snapshots {
+            store.save(snapshot)?;
+        }
+        Ok(imported)
+    }
+
+    /// Serialize the bundle, the snapshots with the codec.
+    pub fn encode<C: Codec<S>>(&self, codec: &C) -> Result<Vec<u8>, StandbyError> {
+        let mut bytes = format!("{}\n", MAGIC).into_bytes();
+        for (projection, position) in &self.checkpoints {
+            check_name(projection)?;
+            bytes.extend(format!("checkpoint {} {}\n", position, projection).into_bytes());
+        }
+        for snapshot in &self.snapshots {
+            check_name(&snapshot.stream_id)?;
+            let state = codec.encode(&snapshot.state).map_err(StandbyError::Codec)?;
+            bytes.extend(
+                format!(
+                    "snapshot {} {} {}\n",
+                    snapshot.version,
+                    state.len(),
+                    snapshot.stream_id
+                )
+                .into_bytes(),
+            );
+            bytes.extend(state);
+            bytes.push(b'\n');
+        }
+        Ok(bytes)
+    }
+
+    /// Deserialize a bundle serialized by [`encode`](Self::encode).
+    pub fn decode<C: Codec<S>>(bytes: &[u8], codec: &C) -> Result<Self, StandbyError> {
+        let mut bundle = Self::new();
+        let mut rest = bytes;
+        if next_line(&mut rest)? != MAGIC {
+            return Err(StandbyError::Corrupt);
+        }
+        while !rest.is_empty() {
+            let line = next_line(&mut rest)?;
+            let (kind, fields) = line.split_once(' ').ok_or(StandbyError::Corrupt)?;
+            match kind {
+                "checkpoint" => {
+                    let (position, projection) =
+                        fields.split_once(' ').ok_or(StandbyError::Corrupt)?;
+                    let position = position.parse().map_err(|_| StandbyError::Corrupt)?;
+                    bundle.checkpoints.insert(projection.to_string(), position);
+                }
+                "snapshot" => {
+                    let (version, length, stream_id) =
+                        match fields.splitn(3, ' ').collect::<Vec<_>>()[..] {
+                            [version, length, stream_id] => (version, length, stream_id),
+                            _ => return Err(StandbyError::Corrupt),
+                        };
+                    let version = version.parse().map_err(|_| StandbyError::Corrupt)?;
+                    let length = length.parse::<usize>().map_err(|_| StandbyError::Corrupt)?;
+                    if rest.get(length) != Some(&b'\n') {
+                        return Err(StandbyError::Corrupt);
+                    }
+                    let state = codec.decode(&rest[..length]).map_err(StandbyError::Codec)?;
+                    rest = &rest[length + 1..];
+                    bundle.snapshots.push(Snapshot {
+                        stream_id: stream_id.to_string(),
+                        version,
+                        state,
+                    });
+                }
+                _ => return Err(StandbyError::Corrupt),
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Write the bundle to the file, replacing it atomically.
+    pub fn write<C: Codec<S>>(&self, path: &Path, codec: &C) -> Result<(), StandbyError> {
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.encode(codec)?).map_err(StandbyError::Io)?;
+        fs::rename(&temporary, path).map_err(StandbyError::Io)
+    }
+
+    /// Read a bundle written by [`write`](Self::write).
+    pub fn read<C: Codec<S>>(path: &Path, codec: &C) -> Result<Self, StandbyError> {
+        Self::decode(&fs::read(path).map_err(StandbyError::Io)?, codec)
+    }
+}
+
+impl<S> Default for StandbyBundle<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_name(name: &str) -> Result<(), StandbyError> {
+    if name.is_empty() || name.contains('\n') {
+        return Err(StandbyError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+fn next_line<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, StandbyError> {
+    let newline = bytes
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or(StandbyError::Corrupt)?;
+    let line = std::str::from_utf8(&bytes[..newline]).map_err(|_| StandbyError::Corrupt)?;
+    *bytes = &bytes[newline + 1..];
+    Ok(line)
+}
+
+/// Error returned when serializing or deserializing a [`StandbyBundle`].
+#[derive(Debug)]
+pub enum StandbyError {
+    /// The file could not be read or written.
+    Io(io::Error),
+    /// A snapshot could not be serialized or deserialized.
+    Codec(String),
+    /// The bytes are not a bundle.
+    Corrupt,
+    /// The stream ID or projection name is empty or spans several lines, which the bundle
+    /// cannot hold.
+    InvalidName(String),
+}
+
+impl fmt::Display for StandbyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StandbyError::Io(e) => write!(f, "{}", e),
+            StandbyError::Codec(e) => write!(f, "codec error: {}", e),
+            StandbyError::Corrupt => write!(f, "not a standby bundle"),
+            StandbyError::InvalidName(name) => write!(f, "invalid name in bundle: {:?}", name),
+        }
+    }
+}
+
+impl Error for StandbyError {}
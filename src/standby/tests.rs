@@ -0,0 +1,93 @@
+use std::env;
+use std::process;
+
+use super::*;
+use crate::snapshot::OnMemorySnapshotStore;
+
+struct TextCodec;
+
+impl Codec<String> for TextCodec {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn encode(&self, value: &String) -> Result<Vec<u8>, String> {
+        Ok(value.clone().into_bytes())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string())
+    }
+}
+
+fn snapshot(stream_id: &str, version: u64, state: &str) -> Snapshot<String> {
+    Snapshot {
+        stream_id: stream_id.to_string(),
+        version,
+        state: state.to_string(),
+    }
+}
+
+#[test]
+fn test_export_and_import() {
+    let mut production = OnMemorySnapshotStore::new();
+    for snapshot in [
+        snapshot("org-2", 3, "Globex"),
+        snapshot("org-1", 10, "Acme"),
+        snapshot("org-1", 20, "Acme Corp\nsince 1949"),
+    ] {
+        production.save(snapshot).unwrap();
+    }
+    let bundle = StandbyBundle::export(&production)
+        .unwrap()
+        .checkpoint("org-directory", 42)
+        .checkpoint("user count", 40);
+    assert_eq!(
+        bundle.snapshots(),
+        [
+            snapshot("org-1", 20, "Acme Corp\nsince 1949"),
+            snapshot("org-2", 3, "Globex"),
+        ]
+    );
+
+    let path = env::temp_dir().join(format!("crux-standby-{}", process::id()));
+    bundle.write(&path, &TextCodec).unwrap();
+    let restored = StandbyBundle::read(&path, &TextCodec).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(restored, bundle);
+    assert_eq!(restored.position("user count"), Some(40));
+    assert_eq!(restored.position("billing"), None);
+
+    let mut standby = OnMemorySnapshotStore::new();
+    assert_eq!(restored.import(&mut standby).unwrap(), 2);
+    assert_eq!(
+        standby.load("org-1").unwrap(),
+        Some(snapshot("org-1", 20, "Acme Corp\nsince 1949"))
+    );
+    assert_eq!(standby.history("org-1").len(), 1);
+}
+
+#[test]
+fn test_reject_invalid_bundles() {
+    let bundle = StandbyBundle::<String>::new().checkpoint("two\nlines", 1);
+    assert!(matches!(
+        bundle.encode(&TextCodec),
+        Err(StandbyError::InvalidName(name)) if name == "two\nlines"
+    ));
+
+    let bytes = StandbyBundle::new()
+        .checkpoint("org-directory", 7)
+        .encode(&TextCodec)
+        .unwrap();
+    assert_eq!(bytes, b"crux-standby 1\ncheckpoint 7 org-directory\n");
+    for corrupt in [
+        &b"crux-projection 1 0\n"[..],
+        b"crux-standby 1\ncheckpoint seven org-directory\n",
+        b"crux-standby 1\nsnapshot 1 10 org-1\nAcme\n",
+    ] {
+        assert!(matches!(
+            StandbyBundle::decode(corrupt, &TextCodec),
+            Err(StandbyError::Corrupt)
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::aggregate::Aggregate;
+use crate::precondition::{Preconditions, Rejected};
+
+/// Types which declare an aggregate as a state machine: its states, and the transitions
+/// commands trigger between them.
+pub trait StateMachine {
+    /// Associated Type representing the ID of the aggregate.
+    type Id: fmt::Display;
+    /// Associated Type representing the states.
+    type State: Clone + PartialEq + fmt::Debug;
+    /// Associated Type representing the commands.
+    type Command: Clone + fmt::Debug;
+    /// Associated Type representing the transition events.
+    type Event;
+
+    /// Get the name of the aggregate type, used as the category of its streams.
+    fn aggregate_type() -> &'static str;
+    /// Get the state of a new aggregate.
+    fn initial_state() -> Self::State;
+    /// Get the name of the event type.
+    fn event_type(event: &Self::Event) -> String;
+    /// Get the allowed transitions.
+    fn transitions() -> Vec<Transition<Self>>;
+}
+
+/// A transition allowed from one state to another.
+pub struct Transition<M: StateMachine + ?Sized> {
+    /// State the transition starts from.
+    pub from: M::State,
+    /// State the transition leads to.
+    pub to: M::State,
+    /// Type of the event recording the transition.
+    pub event_type: &'static str,
+    /// Get the event recording the transition if the command triggers it.
+    pub on: fn(&M::Command) -> Option<M::Event>,
+}
+
+impl<M: StateMachine + ?Sized> Transition<M> {
+    /// Allow the transition.
+    pub fn new(
+        from: M::State,
+        to: M::State,
+        event_type: &'static str,
+        on: fn(&M::Command) -> Option<M::Event>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            event_type,
+            on,
+        }
+    }
+}
+
+/// Aggregate driven by the transitions declared by the state machine `M`.
+pub struct StateMachineAggregate<M: StateMachine> {
+    state: M::State,
+    transitions: Vec<Transition<M>>,
+}
+
+impl<M: StateMachine> StateMachineAggregate<M> {
+    /// Create an aggregate in the initial state.
+    pub fn new() -> Self {
+        Self {
+            state: M::initial_state(),
+            transitions: M::transitions(),
+        }
+    }
+
+    /// Get the current state.
+    pub fn state(&self) -> &M::State {
+        &self.state
+    }
+
+    /// Get the event of the transition the command triggers from the current state.
+    pub fn handle(
+        &self,
+        command: &M::Command,
+    ) -> Result<M::Event, InvalidTransition<M::State, M::Command>> {
+        self.transitions
+            .iter()
+            .filter(|t| t.from == self.state)
+            .find_map(|t| (t.on)(command))
+            .ok_or_else(|| InvalidTransition {
+                from: self.state.clone(),
+                command: command.clone(),
+            })
+    }
+
+    /// Get the event of the transition the command triggers from the current state, once the
+    /// preconditions hold, reporting a missing transition as a violation too.
+    pub fn handle_checked<E>(
+        &self,
+        command: &M::Command,
+        preconditions: impl FnOnce(&M::State, &M::Command) -> Preconditions<E>,
+    ) -> Result<M::Event, Rejected<E>>
+    where
+        E: From<InvalidTransition<M::State, M::Command>>,
+    {
+        preconditions(&self.state, command).check()?;
+        self.handle(command)
+            .map_err(|e| Rejected::new(vec![E::from(e)]))
+    }
+}
+
+impl<M: StateMachine> Default for StateMachineAggregate<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: StateMachine> Clone for StateMachineAggregate<M> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            transitions: M::transitions(),
+        }
+    }
+}
+
+impl<M: StateMachine> fmt::Debug for StateMachineAggregate<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateMachineAggregate")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<M: StateMachine> Aggregate for StateMachineAggregate<M> {
+    type Id = M::Id;
+    type Event = M::Event;
+
+    fn aggregate_type() -> &'static str {
+        M::aggregate_type()
+    }
+
+    fn event_type(event: &M::Event) -> String {
+        M::event_type(event)
+    }
+
+    /// Move to the target state of the transition recorded by the event. Events of no
+    /// transition from the current state are ignored.
+    fn apply(&mut self, event: &M::Event) {
+        let event_type = M::event_type(event);
+        if let Some(transition) = self
+            .transitions
+            .iter()
+            .find(|t| t.from == self.state && t.event_type == event_type)
+        {
+            self.state = transition.to.clone();
+        }
+    }
+}
+
+/// Error returned when a command triggers no transition from the current state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTransition<S, C> {
+    /// The current state.
+    pub from: S,
+    /// The rejected command.
+    pub command: C,
+}
+
+impl<S: fmt::Debug, C: fmt::Debug> fmt::Display for InvalidTransition<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid transition: {:?} is not allowed from {:?}",
+            self.command, self.from
+        )
+    }
+}
+
+impl<S: fmt::Debug, C: fmt::Debug> Error for InvalidTransition<S, C> {}
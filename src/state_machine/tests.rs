@@ -0,0 +1,178 @@
+use super::*;
+use crate::event_store::OnMemoryEventStore;
+use crate::repository::Repository;
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderStatus {
+    Pending,
+    Shipped,
+    Delivered,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderCommand {
+    Ship { carrier: String },
+    Deliver,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderEvent {
+    Shipped { carrier: String },
+    Delivered,
+}
+
+struct Order;
+
+impl StateMachine for Order {
+    type Id = u32;
+    type State = OrderStatus;
+    type Command = OrderCommand;
+    type Event = OrderEvent;
+
+    fn aggregate_type() -> &'static str {
+        "order"
+    }
+
+    fn initial_state() -> OrderStatus {
+        OrderStatus::Pending
+    }
+
+    fn event_type(event: &OrderEvent) -> String {
+        match event {
+            OrderEvent::Shipped { .. } => "Shipped".to_string(),
+            OrderEvent::Delivered => "Delivered".to_string(),
+        }
+    }
+
+    fn transitions() -> Vec<Transition<Self>> {
+        vec![
+            Transition::new(
+                OrderStatus::Pending,
+                OrderStatus::Shipped,
+                "Shipped",
+                |command| match command {
+                    OrderCommand::Ship { carrier } => Some(OrderEvent::Shipped {
+                        carrier: carrier.clone(),
+                    }),
+                    _ => None,
+                },
+            ),
+            Transition::new(
+                OrderStatus::Shipped,
+                OrderStatus::Delivered,
+                "Delivered",
+                |command| matches!(command, OrderCommand::Deliver).then_some(OrderEvent::Delivered),
+            ),
+        ]
+    }
+}
+
+#[test]
+fn test_transitions() {
+    let mut order = StateMachineAggregate::<Order>::new();
+    assert_eq!(order.state(), &OrderStatus::Pending);
+
+    assert_eq!(
+        order.handle(&OrderCommand::Deliver),
+        Err(InvalidTransition {
+            from: OrderStatus::Pending,
+            command: OrderCommand::Deliver,
+        })
+    );
+
+    let ship = OrderCommand::Ship {
+        carrier: "acme".to_string(),
+    };
+    let event = order.handle(&ship).unwrap();
+    assert_eq!(
+        event,
+        OrderEvent::Shipped {
+            carrier: "acme".to_string()
+        }
+    );
+    order.apply(&event);
+    assert_eq!(order.state(), &OrderStatus::Shipped);
+
+    let error = order.handle(&ship).unwrap_err();
+    assert_eq!(error.from, OrderStatus::Shipped);
+    assert_eq!(
+        error.to_string(),
+        "invalid transition: Ship { carrier: \"acme\" } is not allowed from Shipped"
+    );
+
+    let event = order.handle(&OrderCommand::Deliver).unwrap();
+    order.apply(&event);
+    assert_eq!(order.state(), &OrderStatus::Delivered);
+}
+
+#[test]
+fn test_rebuild_from_store() {
+    let mut repository =
+        Repository::<StateMachineAggregate<Order>, _>::new(OnMemoryEventStore::new());
+    repository
+        .save(
+            &7,
+            &[OrderEvent::Shipped {
+                carrier: "acme".to_string(),
+            }],
+        )
+        .unwrap();
+
+    repository.evict(&7);
+    let loaded = repository.load(&7).unwrap().unwrap();
+    assert_eq!(loaded.state.state(), &OrderStatus::Shipped);
+    assert_eq!(loaded.version, 1);
+
+    repository.save(&7, &[OrderEvent::Delivered]).unwrap();
+    let loaded = repository.cached(&7).unwrap();
+    assert_eq!(loaded.state.state(), &OrderStatus::Delivered);
+}
+
+#[derive(Debug, PartialEq)]
+enum ShippingError {
+    UnknownCarrier(String),
+    NotAllowed(InvalidTransition<OrderStatus, OrderCommand>),
+}
+
+impl From<InvalidTransition<OrderStatus, OrderCommand>> for ShippingError {
+    fn from(e: InvalidTransition<OrderStatus, OrderCommand>) -> Self {
+        ShippingError::NotAllowed(e)
+    }
+}
+
+fn known_carrier(_: &OrderStatus, command: &OrderCommand) -> Preconditions<ShippingError> {
+    match command {
+        OrderCommand::Ship { carrier } => Preconditions::new()
+            .require_with(carrier == "acme", || {
+                ShippingError::UnknownCarrier(carrier.clone())
+            }),
+        OrderCommand::Deliver => Preconditions::new(),
+    }
+}
+
+#[test]
+fn test_handle_checked() {
+    let order = StateMachineAggregate::<Order>::new();
+    let ship = |carrier: &str| OrderCommand::Ship {
+        carrier: carrier.to_string(),
+    };
+
+    assert_eq!(
+        order
+            .handle_checked(&ship("unknown"), known_carrier)
+            .unwrap_err()
+            .into_violations(),
+        vec![ShippingError::UnknownCarrier("unknown".to_string())]
+    );
+    assert_eq!(
+        order
+            .handle_checked(&OrderCommand::Deliver, known_carrier)
+            .unwrap_err()
+            .into_violations(),
+        vec![ShippingError::NotAllowed(InvalidTransition {
+            from: OrderStatus::Pending,
+            command: OrderCommand::Deliver,
+        })]
+    );
+    assert!(order.handle_checked(&ship("acme"), known_carrier).is_ok());
+}
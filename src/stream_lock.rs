@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Types which represent pessimistic locks on streams, for backends without optimistic
+/// concurrency control or applications preferring to serialize writers.
+///
+/// Locks are advisory: only writers taking them are serialized.
+pub trait StreamLock {
+    /// Wait until the stream is locked by the caller.
+    fn lock_stream(&self, stream_id: &str) -> Result<(), LockError>;
+    /// Release the lock on the stream.
+    fn unlock_stream(&self, stream_id: &str) -> Result<(), LockError>;
+}
+
+impl<L: StreamLock + ?Sized> StreamLock for Arc<L> {
+    fn lock_stream(&self, stream_id: &str) -> Result<(), LockError> {
+        (**self).lock_stream(stream_id)
+    }
+
+    fn unlock_stream(&self, stream_id: &str) -> Result<(), LockError> {
+        (**self).unlock_stream(stream_id)
+    }
+}
+
+/// Map of the streams locked within the process, shared by the threads through an [`Arc`].
+#[derive(Debug, Default)]
+pub struct InProcessLockMap {
+    locked: Mutex<HashSet<String>>,
+    released: Condvar,
+    timeout: Option<Duration>,
+}
+
+impl InProcessLockMap {
+    /// Create a lock map waiting without limit for locks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up waiting for a lock after the timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Check whether the stream is locked.
+    pub fn is_locked(&self, stream_id: &str) -> bool {
+        self.locked.lock().unwrap().contains(stream_id)
+    }
+}
+
+impl StreamLock for InProcessLockMap {
+    fn lock_stream(&self, stream_id: &str) -> Result<(), LockError> {
+        let locked = self.locked.lock().unwrap();
+        let is_locked = |locked: &mut HashSet<String>| locked.contains(stream_id);
+        let mut locked = match self.timeout {
+            Some(timeout) => {
+                let (locked, result) = self
+                    .released
+                    .wait_timeout_while(locked, timeout, is_locked)
+                    .unwrap();
+                if result.timed_out() {
+                    return Err(LockError::Timeout(stream_id.to_string()));
+                }
+                locked
+            }
+            None => self.released.wait_while(locked, is_locked).unwrap(),
+        };
+        locked.insert(stream_id.to_string());
+        Ok(())
+    }
+
+    fn unlock_stream(&self, stream_id: &str) -> Result<(), LockError> {
+        if !self.locked.lock().unwrap().remove(stream_id) {
+            return Err(LockError::NotLocked(stream_id.to_string()));
+        }
+        self.released.notify_all();
+        Ok(())
+    }
+}
+
+/// Error returned by a [`StreamLock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockError {
+    /// The stream stayed locked by another writer until the timeout.
+    Timeout(String),
+    /// The stream was not locked.
+    NotLocked(String),
+    /// The locking backend failed.
+    Backend(String),
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::Timeout(id) => write!(f, "timed out locking stream {}", id),
+            LockError::NotLocked(id) => write!(f, "stream {} is not locked", id),
+            LockError::Backend(reason) => write!(f, "lock backend error: {}", reason),
+        }
+    }
+}
+
+impl Error for LockError {}
@@ -0,0 +1,52 @@
+use std::thread;
+
+use super::*;
+
+#[test]
+fn test_lock_and_unlock() {
+    let locks = InProcessLockMap::new().with_timeout(Duration::from_millis(10));
+    locks.lock_stream("order-1").unwrap();
+    assert!(locks.is_locked("order-1"));
+    assert_eq!(
+        locks.lock_stream("order-1"),
+        Err(LockError::Timeout("order-1".to_string()))
+    );
+    locks.lock_stream("order-2").unwrap();
+
+    locks.unlock_stream("order-1").unwrap();
+    assert!(!locks.is_locked("order-1"));
+    assert_eq!(
+        locks.unlock_stream("order-1"),
+        Err(LockError::NotLocked("order-1".to_string()))
+    );
+    locks.lock_stream("order-1").unwrap();
+}
+
+#[test]
+fn test_writers_are_serialized() {
+    let locks = Arc::new(InProcessLockMap::new());
+    let counter = Arc::new(Mutex::new(Vec::new()));
+    let writers = (0..4)
+        .map(|i| {
+            let locks = locks.clone();
+            let counter = counter.clone();
+            thread::spawn(move || {
+                for _ in 0..50 {
+                    locks.lock_stream("order-1").unwrap();
+                    // Nobody else holds the lock between the two pushes.
+                    counter.lock().unwrap().push(i);
+                    thread::yield_now();
+                    counter.lock().unwrap().push(i);
+                    locks.unlock_stream("order-1").unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    let pushes = counter.lock().unwrap();
+    assert_eq!(pushes.len(), 400);
+    assert!(pushes.chunks(2).all(|pair| pair[0] == pair[1]));
+}
@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Subscription;
+use crate::dead_letter::{DeadLetter, DeadLetterStore};
+
+/// What the runner does with a message the subscriber did not process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NackAction {
+    /// Deliver the message again.
+    Redeliver,
+    /// Drop the message.
+    Skip,
+    /// Park the message in the dead letter store.
+    Park,
+}
+
+/// Answer of a subscriber to the delivery of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Acknowledgement {
+    /// The message was processed.
+    Ack,
+    /// The message was not processed, for the reason.
+    Nack { reason: String, action: NackAction },
+}
+
+impl Acknowledgement {
+    /// Request the message to be delivered again.
+    pub fn redeliver(reason: impl Into<String>) -> Self {
+        Self::nack(reason, NackAction::Redeliver)
+    }
+
+    /// Request the message to be dropped.
+    pub fn skip(reason: impl Into<String>) -> Self {
+        Self::nack(reason, NackAction::Skip)
+    }
+
+    /// Request the message to be parked in the dead letter store.
+    pub fn park(reason: impl Into<String>) -> Self {
+        Self::nack(reason, NackAction::Park)
+    }
+
+    fn nack(reason: impl Into<String>, action: NackAction) -> Self {
+        Acknowledgement::Nack {
+            reason: reason.into(),
+            action,
+        }
+    }
+}
+
+/// Types which process the messages delivered by a [`SubscriptionRunner`].
+pub trait Subscriber<M> {
+    /// Process the message, telling the runner whether it was processed.
+    fn handle(&mut self, message: &M) -> Acknowledgement;
+}
+
+impl<M, F: FnMut(&M) -> Acknowledgement> Subscriber<M> for F {
+    fn handle(&mut self, message: &M) -> Acknowledgement {
+        self(message)
+    }
+}
+
+/// Outcome of delivering a single message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    /// No message was available.
+    Idle,
+    /// The message was processed.
+    Acked,
+    /// The message will be delivered again.
+    Redelivering,
+    /// The message was dropped.
+    Skipped,
+    /// The message was parked in the dead letter store.
+    Parked,
+}
+
+/// Runner delivering the messages of a subscription at least once to a subscriber.
+///
+/// Messages the subscriber asks to be redelivered are delivered again before new messages are
+/// polled, up to the maximum number of redeliveries after which they are parked.
+pub struct SubscriptionRunner<S, D, M> {
+    subscription: S,
+    dead_letters: D,
+    redeliveries: VecDeque<(M, u32)>,
+    max_redeliveries: u32,
+}
+
+impl<S, D, M> SubscriptionRunner<S, D, M> {
+    /// Create a runner redelivering a message at most 3 times.
+    pub fn new(subscription: S, dead_letters: D) -> Self {
+        Self {
+            subscription,
+            dead_letters,
+            redeliveries: VecDeque::new(),
+            max_redeliveries: 3,
+        }
+    }
+
+    /// Set the maximum number of redeliveries of a message.
+    pub fn with_max_redeliveries(mut self, max_redeliveries: u32) -> Self {
+        self.max_redeliveries = max_redeliveries;
+        self
+    }
+
+    /// Get the dead letter store.
+    pub fn dead_letters(&self) -> &D {
+        &self.dead_letters
+    }
+
+    /// Deliver the next message to the subscriber.
+    pub fn run_once(
+        &mut self,
+        subscriber: &mut impl Subscriber<M>,
+    ) -> Result<DeliveryOutcome, SubscriberError<S::Error, D::Error>>
+    where
+        S: Subscription<M>,
+        D: DeadLetterStore<M>,
+    {
+        let (message, redelivered) = match self.redeliveries.pop_front() {
+            Some(redelivery) => redelivery,
+            None => match self
+                .subscription
+                .poll()
+                .map_err(SubscriberError::Subscription)?
+            {
+                Some(message) => (message, 0),
+                None => return Ok(DeliveryOutcome::Idle),
+            },
+        };
+        let (reason, action) = match subscriber.handle(&message) {
+            Acknowledgement::Ack => return Ok(DeliveryOutcome::Acked),
+            Acknowledgement::Nack { reason, action } => (reason, action),
+        };
+        match action {
+            NackAction::Redeliver if redelivered < self.max_redeliveries => {
+                self.redeliveries.push_back((message, redelivered + 1));
+                Ok(DeliveryOutcome::Redelivering)
+            }
+            NackAction::Skip => Ok(DeliveryOutcome::Skipped),
+            NackAction::Redeliver | NackAction::Park => {
+                self.dead_letters
+                    .put(DeadLetter { message, reason })
+                    .map_err(SubscriberError::DeadLetter)?;
+                Ok(DeliveryOutcome::Parked)
+            }
+        }
+    }
+
+    /// Deliver messages until the subscription is idle, returning the number of deliveries.
+    pub fn run_until_idle(
+        &mut self,
+        subscriber: &mut impl Subscriber<M>,
+    ) -> Result<usize, SubscriberError<S::Error, D::Error>>
+    where
+        S: Subscription<M>,
+        D: DeadLetterStore<M>,
+    {
+        let mut delivered = 0;
+        while self.run_once(subscriber)? != DeliveryOutcome::Idle {
+            delivered += 1;
+        }
+        Ok(delivered)
+    }
+}
+
+/// Error returned by the [`SubscriptionRunner`].
+#[derive(Debug)]
+pub enum SubscriberError<SE, DE> {
+    /// The subscription failed.
+    Subscription(SE),
+    /// The dead letter store failed.
+    DeadLetter(DE),
+}
+
+impl<SE: Error, DE: Error> fmt::Display for SubscriberError<SE, DE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubscriberError::Subscription(e) => write!(f, "subscription error: {}", e),
+            SubscriberError::DeadLetter(e) => write!(f, "dead letter store error: {}", e),
+        }
+    }
+}
+
+impl<SE: Error, DE: Error> Error for SubscriberError<SE, DE> {}
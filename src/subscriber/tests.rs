@@ -0,0 +1,80 @@
+use super::*;
+use crate::broker::{OnMemoryBroker, Publisher};
+use crate::dead_letter::OnMemoryDeadLetterStore;
+
+fn runner(
+    messages: &[&'static str],
+) -> SubscriptionRunner<
+    OnMemoryBroker<&'static str>,
+    OnMemoryDeadLetterStore<&'static str>,
+    &'static str,
+> {
+    let mut broker = OnMemoryBroker::new();
+    for message in messages {
+        broker.publish(message).unwrap();
+    }
+    SubscriptionRunner::new(broker, OnMemoryDeadLetterStore::new()).with_max_redeliveries(2)
+}
+
+#[test]
+fn test_acknowledgements() {
+    let mut runner = runner(&["ok", "skip", "park", "flaky"]);
+    let mut attempts = 0;
+    let mut subscriber = |message: &&str| match *message {
+        "skip" => Acknowledgement::skip("irrelevant"),
+        "park" => Acknowledgement::park("malformed"),
+        "flaky" => {
+            attempts += 1;
+            if attempts < 3 {
+                Acknowledgement::redeliver("busy")
+            } else {
+                Acknowledgement::Ack
+            }
+        }
+        _ => Acknowledgement::Ack,
+    };
+
+    let mut outcomes = Vec::new();
+    loop {
+        match runner.run_once(&mut subscriber).unwrap() {
+            DeliveryOutcome::Idle => break,
+            outcome => outcomes.push(outcome),
+        }
+    }
+    assert_eq!(
+        outcomes,
+        vec![
+            DeliveryOutcome::Acked,
+            DeliveryOutcome::Skipped,
+            DeliveryOutcome::Parked,
+            DeliveryOutcome::Redelivering,
+            DeliveryOutcome::Redelivering,
+            DeliveryOutcome::Acked,
+        ]
+    );
+    assert_eq!(
+        runner.dead_letters().letters(),
+        [DeadLetter {
+            message: "park",
+            reason: "malformed".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_redeliveries_are_bounded() {
+    let mut runner = runner(&["poison", "ok"]);
+    let mut delivered = Vec::new();
+    let mut subscriber = |message: &&'static str| {
+        delivered.push(*message);
+        if *message == "poison" {
+            Acknowledgement::redeliver("always fails")
+        } else {
+            Acknowledgement::Ack
+        }
+    };
+
+    assert_eq!(runner.run_until_idle(&mut subscriber).unwrap(), 4);
+    assert_eq!(delivered, vec!["poison", "poison", "poison", "ok"]);
+    assert_eq!(runner.dead_letters().letters()[0].reason, "always fails");
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::aggregate::Aggregate;
+use crate::json;
+
+/// A JSON-like value describing the state of an aggregate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SummaryValue {
+    /// No value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Integer(i64),
+    /// A floating point number.
+    Number(f64),
+    /// A string.
+    String(String),
+    /// A list of values.
+    List(Vec<SummaryValue>),
+    /// Named values, in order.
+    Object(Vec<(String, SummaryValue)>),
+}
+
+impl SummaryValue {
+    /// Create an empty object.
+    pub fn object() -> Self {
+        SummaryValue::Object(Vec::new())
+    }
+
+    /// Add a field to the object. Values which are not objects are left unchanged.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<SummaryValue>) -> Self {
+        if let SummaryValue::Object(fields) = &mut self {
+            fields.push((name.into(), value.into()));
+        }
+        self
+    }
+
+    /// Get the field of the object.
+    pub fn get(&self, name: &str) -> Option<&SummaryValue> {
+        match self {
+            SummaryValue::Object(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Serialize the value as JSON.
+    pub fn to_json(&self) -> String {
+        match self {
+            SummaryValue::Null => "null".to_string(),
+            SummaryValue::Bool(b) => b.to_string(),
+            SummaryValue::Integer(i) => i.to_string(),
+            SummaryValue::Number(n) if n.is_finite() => n.to_string(),
+            SummaryValue::Number(_) => "null".to_string(),
+            SummaryValue::String(s) => json::escape(s),
+            SummaryValue::List(items) => {
+                let items = items.iter().map(Self::to_json).collect::<Vec<_>>();
+                format!("[{}]", items.join(","))
+            }
+            SummaryValue::Object(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(name, value)| format!("{}:{}", json::escape(name), value.to_json()))
+                    .collect::<Vec<_>>();
+                format!("{{{}}}", fields.join(","))
+            }
+        }
+    }
+}
+
+impl fmt::Display for SummaryValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl From<bool> for SummaryValue {
+    fn from(value: bool) -> Self {
+        SummaryValue::Bool(value)
+    }
+}
+
+impl From<i64> for SummaryValue {
+    fn from(value: i64) -> Self {
+        SummaryValue::Integer(value)
+    }
+}
+
+impl From<u32> for SummaryValue {
+    fn from(value: u32) -> Self {
+        SummaryValue::Integer(value.into())
+    }
+}
+
+impl From<f64> for SummaryValue {
+    fn from(value: f64) -> Self {
+        SummaryValue::Number(value)
+    }
+}
+
+impl From<&str> for SummaryValue {
+    fn from(value: &str) -> Self {
+        SummaryValue::String(value.to_string())
+    }
+}
+
+impl From<String> for SummaryValue {
+    fn from(value: String) -> Self {
+        SummaryValue::String(value)
+    }
+}
+
+impl<T: Into<SummaryValue>> From<Option<T>> for SummaryValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(SummaryValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<SummaryValue>> From<Vec<T>> for SummaryValue {
+    fn from(value: Vec<T>) -> Self {
+        SummaryValue::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Types which represent an aggregate able to describe its state to ops tooling, such as the
+/// inspector, without exposing its internals.
+pub trait AggregateSummary: Aggregate {
+    /// Get a human-friendly description of the state.
+    fn summary(&self) -> SummaryValue;
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn test_to_json() {
+    let summary = SummaryValue::object()
+        .field("name", "Acme \"Inc\"")
+        .field("users", vec![7u32, 9])
+        .field("owner", None::<String>)
+        .field("active", true)
+        .field("score", 0.5)
+        .field("address", SummaryValue::object().field("city", "Tokyo"));
+    assert_eq!(
+        summary.to_json(),
+        "{\"name\":\"Acme \\\"Inc\\\"\",\"users\":[7,9],\"owner\":null,\"active\":true,\
+         \"score\":0.5,\"address\":{\"city\":\"Tokyo\"}}"
+    );
+    assert_eq!(
+        summary.get("users"),
+        Some(&SummaryValue::from(vec![7u32, 9]))
+    );
+    assert_eq!(summary.get("missing"), None);
+    assert_eq!(SummaryValue::Number(f64::NAN).to_string(), "null");
+}
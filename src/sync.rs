@@ -0,0 +1,432 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, ReadOnlyEventStore,
+};
+use crate::retention::PrunableEventStore;
+
+const OPEN_PAGE_SIZE: usize = 256;
+
+/// Local events of a stream appended on top of a divergent remote history.
+#[derive(Debug)]
+pub struct Conflict<'a, E> {
+    /// ID of the stream.
+    pub stream_id: &'a str,
+    /// Version of the stream the local events were appended at.
+    pub base_version: u64,
+    /// Local events not pushed yet.
+    pub local: &'a [Envelope<E>],
+    /// Remote events appended since the base version.
+    pub remote: &'a [Envelope<E>],
+}
+
+/// Decision taken on a [`Conflict`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution<E> {
+    /// Push the local events after the remote ones.
+    Rebase,
+    /// Drop the local events.
+    Discard,
+    /// Push the events instead of the local ones, after the remote ones.
+    Replace(Vec<Envelope<E>>),
+}
+
+/// Types which resolve sync conflicts.
+pub trait ConflictResolver<E> {
+    /// Decide what to do with the local events.
+    fn resolve(&mut self, conflict: &Conflict<'_, E>) -> Resolution<E>;
+}
+
+impl<E, F> ConflictResolver<E> for F
+where
+    F: FnMut(&Conflict<'_, E>) -> Resolution<E>,
+{
+    fn resolve(&mut self, conflict: &Conflict<'_, E>) -> Resolution<E> {
+        self(conflict)
+    }
+}
+
+/// Summary of a [`SyncClient::sync`] run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Number of remote events pulled into the replica, including the pushed ones.
+    pub pulled: usize,
+    /// Number of local events pushed.
+    pub pushed: usize,
+    /// Number of conflicts resolved.
+    pub conflicts: usize,
+    /// Number of local events discarded by the resolver.
+    pub discarded: usize,
+}
+
+/// ID of the local stream persisting the events not pushed yet.
+pub const PENDING_STREAM: &str = "$sync-pending";
+/// Metadata key recording the stream of a pending event.
+pub const SYNC_STREAM_METADATA_KEY: &str = "sync_stream_id";
+/// Metadata key recording the version of the stream a pending event was appended at.
+pub const SYNC_BASE_VERSION_METADATA_KEY: &str = "sync_base_version";
+/// Metadata key recording the position in the remote log of a pulled event.
+pub const REMOTE_POSITION_METADATA_KEY: &str = "remote_position";
+
+#[derive(Debug)]
+struct PendingStream<E> {
+    stream_id: String,
+    base_version: u64,
+    events: Vec<Envelope<E>>,
+}
+
+impl<E: Clone> PendingStream<E> {
+    /// Get the events persisting the pending ones in the [`PENDING_STREAM`].
+    fn persisted(&self) -> Vec<Envelope<E>> {
+        self.events
+            .iter()
+            .map(|event| Envelope {
+                stream_id: PENDING_STREAM.to_string(),
+                version: 0,
+                ..event.clone()
+            })
+            .map(|event| {
+                event
+                    .with_metadata(SYNC_STREAM_METADATA_KEY, self.stream_id.clone())
+                    .with_metadata(
+                        SYNC_BASE_VERSION_METADATA_KEY,
+                        self.base_version.to_string(),
+                    )
+            })
+            .collect()
+    }
+}
+
+/// Client of an offline-first application, keeping a local replica of a remote store.
+///
+/// Local appends are kept pending until [`sync`](Self::sync) pushes them, and are visible in
+/// [`load`](Self::load) meanwhile. Both the pending events, in the [`PENDING_STREAM`], and the
+/// pulled ones, tagged with their [`REMOTE_POSITION_METADATA_KEY`], are persisted in the local
+/// store, so that a restarted client resumes with [`open`](Self::open).
+pub struct SyncClient<L, E> {
+    replica: L,
+    pending: Vec<PendingStream<E>>,
+    pulled_position: u64,
+}
+
+impl<L, E> SyncClient<L, E>
+where
+    L: EventStore<Persistable = Envelope<E>>
+        + EventLoader<StreamId = String, Persistable = Envelope<E>, Error = <L as EventStore>::Error>
+        + PrunableEventStore<E>,
+    E: Clone,
+{
+    /// Create a client over an empty replica.
+    pub fn new(replica: L) -> Self {
+        Self {
+            replica,
+            pending: Vec::new(),
+            pulled_position: 0,
+        }
+    }
+
+    /// Resume a client over the replica persisted by a previous one, restoring its pending
+    /// events and the position of its next pull.
+    pub fn open(replica: L) -> Result<Self, <L as EventStore>::Error>
+    where
+        L: ReadOnlyEventStore<Persistable = Envelope<E>, Error = <L as EventStore>::Error>,
+    {
+        let mut pulled_position = 0;
+        let mut from = 0;
+        loop {
+            let events = replica.read_all(from, OPEN_PAGE_SIZE)?;
+            let last = match events.last() {
+                Some(last) => last.position,
+                None => break,
+            };
+            for event in &events {
+                if let Some(position) = event
+                    .metadata
+                    .get(REMOTE_POSITION_METADATA_KEY)
+                    .and_then(|p| p.parse::<u64>().ok())
+                {
+                    pulled_position = pulled_position.max(position + 1);
+                }
+            }
+            from = last + 1;
+        }
+
+        let mut pending: Vec<PendingStream<E>> = Vec::new();
+        for mut event in replica.load(&PENDING_STREAM.to_string())? {
+            let stream_id = event
+                .metadata
+                .remove(SYNC_STREAM_METADATA_KEY)
+                .unwrap_or_default();
+            let base_version = event
+                .metadata
+                .remove(SYNC_BASE_VERSION_METADATA_KEY)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            let index = match pending.iter().position(|p| p.stream_id == stream_id) {
+                Some(index) => index,
+                None => {
+                    pending.push(PendingStream {
+                        stream_id: stream_id.clone(),
+                        base_version,
+                        events: Vec::new(),
+                    });
+                    pending.len() - 1
+                }
+            };
+            let stream = &mut pending[index];
+            event.version = stream.base_version + stream.events.len() as u64 + 1;
+            event.stream_id = stream_id;
+            stream.events.push(event);
+        }
+        Ok(Self {
+            replica,
+            pending,
+            pulled_position,
+        })
+    }
+
+    /// Get the replica.
+    pub fn replica(&self) -> &L {
+        &self.replica
+    }
+
+    /// Get the position of the remote log the next pull starts at.
+    pub fn pulled_position(&self) -> u64 {
+        self.pulled_position
+    }
+
+    /// Get the local events not pushed yet, in order of appending.
+    pub fn pending(&self) -> Vec<&Envelope<E>> {
+        self.pending.iter().flat_map(|p| &p.events).collect()
+    }
+
+    /// Append events locally, persisting them in the [`PENDING_STREAM`]. Their versions follow
+    /// the local view of their streams.
+    pub fn append(&mut self, events: &[Envelope<E>]) -> Result<(), <L as EventStore>::Error> {
+        let mut appended: Vec<PendingStream<E>> = Vec::new();
+        for event in events {
+            let queued = self.pending.iter().find(|p| p.stream_id == event.stream_id);
+            let index = match appended.iter().position(|p| p.stream_id == event.stream_id) {
+                Some(index) => index,
+                None => {
+                    let base_version = match queued {
+                        Some(queued) => queued.base_version,
+                        None => self.replica_version(&event.stream_id)?,
+                    };
+                    appended.push(PendingStream {
+                        stream_id: event.stream_id.clone(),
+                        base_version,
+                        events: Vec::new(),
+                    });
+                    appended.len() - 1
+                }
+            };
+            let queued = queued.map_or(0, |p| p.events.len());
+            let stream = &mut appended[index];
+            let mut event = event.clone();
+            event.version = stream.base_version + (queued + stream.events.len()) as u64 + 1;
+            stream.events.push(event);
+        }
+        let persisted = appended
+            .iter()
+            .flat_map(PendingStream::persisted)
+            .collect::<Vec<_>>();
+        self.replica.save(&persisted)?;
+        for stream in appended {
+            match self
+                .pending
+                .iter_mut()
+                .find(|p| p.stream_id == stream.stream_id)
+            {
+                Some(pending) => pending.events.extend(stream.events),
+                None => self.pending.push(stream),
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the local view of the stream: the replicated events, then the pending ones.
+    pub fn load(&self, stream_id: &str) -> Result<Vec<Envelope<E>>, <L as EventStore>::Error> {
+        let mut events = self.replica.load(&stream_id.to_string())?;
+        if let Some(pending) = self.pending.iter().find(|p| p.stream_id == stream_id) {
+            events.extend(pending.events.iter().cloned());
+        }
+        Ok(events)
+    }
+
+    /// Pull the remote events, push the pending ones, resolving conflicts, then pull the
+    /// pushed events back.
+    ///
+    /// Local events rejected by a concurrent remote append stay pending for the next sync, as
+    /// do the streams not pushed yet when the sync fails.
+    pub fn sync<R>(
+        &mut self,
+        remote: &mut R,
+        resolver: &mut impl ConflictResolver<E>,
+    ) -> Result<SyncReport, SyncError<<L as EventStore>::Error, <R as EventStore>::Error>>
+    where
+        R: EventStore<Persistable = Envelope<E>>
+            + ReadOnlyEventStore<Persistable = Envelope<E>, Error = <R as EventStore>::Error>,
+    {
+        let mut report = SyncReport {
+            pulled: self.pull(remote)?,
+            ..SyncReport::default()
+        };
+        let mut kept = Vec::new();
+        let mut queue = std::mem::take(&mut self.pending).into_iter();
+        while let Some(mut pending) = queue.next() {
+            match self.push(&mut pending, remote, resolver, &mut report) {
+                Ok(true) => {}
+                Ok(false) => kept.push(pending),
+                Err(e) => {
+                    kept.push(pending);
+                    kept.extend(queue);
+                    self.pending = kept;
+                    return Err(e);
+                }
+            }
+        }
+        self.pending = kept;
+        report.pulled += self.pull(remote)?;
+        Ok(report)
+    }
+
+    /// Push the pending events of a stream, returning whether they left the pending ones.
+    fn push<R>(
+        &mut self,
+        pending: &mut PendingStream<E>,
+        remote: &mut R,
+        resolver: &mut impl ConflictResolver<E>,
+        report: &mut SyncReport,
+    ) -> Result<bool, SyncError<<L as EventStore>::Error, <R as EventStore>::Error>>
+    where
+        R: EventStore<Persistable = Envelope<E>>,
+    {
+        let current = self
+            .replica_version(&pending.stream_id)
+            .map_err(SyncError::Local)?;
+        if current != pending.base_version {
+            let remote_events = self
+                .replica
+                .load(&pending.stream_id)
+                .map_err(SyncError::Local)?
+                .into_iter()
+                .filter(|e| e.version > pending.base_version)
+                .collect::<Vec<_>>();
+            // Events pushed by a sync interrupted before forgetting them.
+            if pending
+                .events
+                .iter()
+                .all(|p| remote_events.iter().any(|e| e.id == p.id))
+            {
+                self.forget(&pending.stream_id);
+                return Ok(true);
+            }
+            let resolution = resolver.resolve(&Conflict {
+                stream_id: &pending.stream_id,
+                base_version: pending.base_version,
+                local: &pending.events,
+                remote: &remote_events,
+            });
+            report.conflicts += 1;
+            match resolution {
+                Resolution::Rebase => {}
+                Resolution::Discard => {
+                    report.discarded += pending.events.len();
+                    self.forget(&pending.stream_id);
+                    return Ok(true);
+                }
+                Resolution::Replace(events) => pending.events = events,
+            }
+            pending.base_version = current;
+            for (i, event) in pending.events.iter_mut().enumerate() {
+                event.version = current + i as u64 + 1;
+            }
+            self.forget(&pending.stream_id);
+            self.replica
+                .save(&pending.persisted())
+                .map_err(SyncError::Local)?;
+        }
+        let append = (
+            pending.stream_id.clone(),
+            ExpectedVersion::Exact(pending.base_version),
+            pending.events.clone(),
+        );
+        match remote.append_multi(std::slice::from_ref(&append)) {
+            Ok(()) => {
+                report.pushed += pending.events.len();
+                self.forget(&pending.stream_id);
+                Ok(true)
+            }
+            Err(AppendError::WrongExpectedVersion { .. }) => Ok(false),
+            Err(e) => Err(SyncError::Remote(e)),
+        }
+    }
+
+    /// Delete the persisted pending events of the stream.
+    fn forget(&mut self, stream_id: &str) {
+        self.replica.delete_where(PENDING_STREAM, &mut |event| {
+            event
+                .metadata
+                .get(SYNC_STREAM_METADATA_KEY)
+                .is_some_and(|id| id == stream_id)
+        });
+    }
+
+    fn pull<R>(
+        &mut self,
+        remote: &R,
+    ) -> Result<usize, SyncError<<L as EventStore>::Error, <R as EventStore>::Error>>
+    where
+        R: EventStore<Persistable = Envelope<E>>
+            + ReadOnlyEventStore<Persistable = Envelope<E>, Error = <R as EventStore>::Error>,
+    {
+        let events = remote
+            .read_all(self.pulled_position, usize::MAX)
+            .map_err(|e| SyncError::Remote(AppendError::Store(e)))?
+            .into_iter()
+            .map(|e| {
+                let position = e.position.to_string();
+                e.with_metadata(REMOTE_POSITION_METADATA_KEY, position)
+            })
+            .collect::<Vec<_>>();
+        self.replica.save(&events).map_err(SyncError::Local)?;
+        if let Some(last) = events.last() {
+            self.pulled_position = last.position + 1;
+        }
+        Ok(events.len())
+    }
+
+    fn replica_version(&self, stream_id: &str) -> Result<u64, <L as EventStore>::Error> {
+        Ok(self
+            .replica
+            .load(&stream_id.to_string())?
+            .last()
+            .map_or(0, |e| e.version))
+    }
+}
+
+/// Error returned by the [`SyncClient`].
+#[derive(Debug)]
+pub enum SyncError<LE, RE> {
+    /// The local replica failed.
+    Local(LE),
+    /// The remote store failed.
+    Remote(AppendError<RE>),
+}
+
+impl<LE: Error, RE: Error> fmt::Display for SyncError<LE, RE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Local(e) => write!(f, "local store error: {}", e),
+            SyncError::Remote(e) => write!(f, "remote store error: {}", e),
+        }
+    }
+}
+
+impl<LE: Error, RE: Error> Error for SyncError<LE, RE> {}
@@ -0,0 +1,215 @@
+use super::*;
+use crate::event_store::{OnMemoryEventStore, OnMemoryEventStoreError};
+
+type Client = SyncClient<OnMemoryEventStore<String>, String>;
+
+fn client() -> Client {
+    SyncClient::new(OnMemoryEventStore::new())
+}
+
+fn edit(text: &str) -> Envelope<String> {
+    Envelope::new("doc-1", 0, "Edited", text.to_string())
+}
+
+fn texts(events: &[Envelope<String>]) -> Vec<(u64, &str)> {
+    events
+        .iter()
+        .map(|e| (e.version, e.payload.as_str()))
+        .collect()
+}
+
+fn never(_: &Conflict<'_, String>) -> Resolution<String> {
+    panic!("unexpected conflict")
+}
+
+#[test]
+fn test_push_and_pull() {
+    let mut remote = OnMemoryEventStore::new();
+    let mut alice = client();
+    let mut bob = client();
+
+    alice.append(&[edit("a1"), edit("a2")]).unwrap();
+    assert_eq!(
+        texts(&alice.load("doc-1").unwrap()),
+        vec![(1, "a1"), (2, "a2")]
+    );
+    assert!(remote.read_all(0, 10).unwrap().is_empty());
+
+    let report = alice.sync(&mut remote, &mut never).unwrap();
+    assert_eq!(
+        report,
+        SyncReport {
+            pulled: 2,
+            pushed: 2,
+            ..SyncReport::default()
+        }
+    );
+    assert!(alice.pending().is_empty());
+    assert_eq!(alice.replica().read_all(0, 10).unwrap().len(), 2);
+
+    bob.sync(&mut remote, &mut never).unwrap();
+    assert_eq!(
+        texts(&bob.load("doc-1").unwrap()),
+        vec![(1, "a1"), (2, "a2")]
+    );
+    assert_eq!(bob.pulled_position(), 2);
+}
+
+#[test]
+fn test_resolve_conflicts() {
+    let mut remote = OnMemoryEventStore::new();
+    let mut alice = client();
+    let mut bob = client();
+
+    alice.append(&[edit("a1")]).unwrap();
+    bob.append(&[edit("b1")]).unwrap();
+    alice.sync(&mut remote, &mut never).unwrap();
+
+    let mut seen = Vec::new();
+    let report = bob
+        .sync(&mut remote, &mut |conflict: &Conflict<'_, String>| {
+            seen.push((
+                conflict.base_version,
+                texts(conflict.local)[0].1.to_string(),
+                texts(conflict.remote)[0].1.to_string(),
+            ));
+            Resolution::Rebase
+        })
+        .unwrap();
+    assert_eq!(seen, vec![(0, "b1".to_string(), "a1".to_string())]);
+    assert_eq!((report.conflicts, report.pushed), (1, 1));
+    assert_eq!(
+        texts(&remote.load(&"doc-1".to_string()).unwrap()),
+        vec![(1, "a1"), (2, "b1")]
+    );
+
+    alice.append(&[edit("a3")]).unwrap();
+    bob.append(&[edit("b3")]).unwrap();
+    bob.sync(&mut remote, &mut never).unwrap();
+    let report = alice
+        .sync(&mut remote, &mut |_: &Conflict<'_, String>| {
+            Resolution::Discard
+        })
+        .unwrap();
+    assert_eq!((report.conflicts, report.discarded), (1, 1));
+    assert_eq!(
+        texts(&alice.load("doc-1").unwrap()),
+        vec![(1, "a1"), (2, "b1"), (3, "b3")]
+    );
+
+    bob.append(&[edit("b4")]).unwrap();
+    alice.append(&[edit("a4")]).unwrap();
+    alice.sync(&mut remote, &mut never).unwrap();
+    bob.sync(&mut remote, &mut |c: &Conflict<'_, String>| {
+        Resolution::Replace(vec![edit(&format!(
+            "{}+{}",
+            c.remote[0].payload, c.local[0].payload
+        ))])
+    })
+    .unwrap();
+    assert_eq!(
+        texts(&remote.load(&"doc-1".to_string()).unwrap())[3..],
+        [(4, "a4"), (5, "a4+b4")]
+    );
+}
+
+/// Remote accepting reads but rejecting appends.
+struct ReadOnlyRemote(OnMemoryEventStore<String>);
+
+impl EventStore for ReadOnlyRemote {
+    type Persistable = Envelope<String>;
+    type Error = OnMemoryEventStoreError;
+
+    fn save(&mut self, _: &[Self::Persistable]) -> Result<(), Self::Error> {
+        Err(OnMemoryEventStoreError::NoActiveTransaction)
+    }
+
+    fn append_multi(
+        &mut self,
+        _: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        Err(AppendError::Store(
+            OnMemoryEventStoreError::NoActiveTransaction,
+        ))
+    }
+}
+
+impl EventLoader for ReadOnlyRemote {
+    type StreamId = String;
+    type Persistable = Envelope<String>;
+    type Error = OnMemoryEventStoreError;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.load(stream_id)
+    }
+}
+
+impl ReadOnlyEventStore for ReadOnlyRemote {
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.0.read_all(from, limit)
+    }
+}
+
+#[test]
+fn test_keep_pending_streams_when_remote_fails() {
+    let mut remote = ReadOnlyRemote(OnMemoryEventStore::new());
+    let mut alice = client();
+    alice
+        .append(&[
+            edit("a1"),
+            Envelope::new("doc-2", 0, "Edited", "a2".to_string()),
+        ])
+        .unwrap();
+
+    assert!(matches!(
+        alice.sync(&mut remote, &mut never),
+        Err(SyncError::Remote(AppendError::Store(_)))
+    ));
+    assert_eq!(alice.pending().len(), 2);
+}
+
+#[test]
+fn test_resume_after_restart() {
+    let mut remote = OnMemoryEventStore::new();
+    let mut alice = client();
+    alice.append(&[edit("a1")]).unwrap();
+    alice.sync(&mut remote, &mut never).unwrap();
+    alice.append(&[edit("a2"), edit("a3")]).unwrap();
+
+    let SyncClient { replica, .. } = alice;
+    let mut alice = Client::open(replica).unwrap();
+    assert_eq!(alice.pulled_position(), 1);
+    assert_eq!(
+        texts(&alice.load("doc-1").unwrap()),
+        vec![(1, "a1"), (2, "a2"), (3, "a3")]
+    );
+    assert!(alice.pending()[0].metadata.is_empty());
+
+    alice.sync(&mut remote, &mut never).unwrap();
+    assert_eq!(
+        texts(&remote.load(&"doc-1".to_string()).unwrap()),
+        vec![(1, "a1"), (2, "a2"), (3, "a3")]
+    );
+    let alice = Client::open(alice.replica).unwrap();
+    assert!(alice.pending().is_empty());
+    assert_eq!(alice.pulled_position(), 3);
+}
+
+#[test]
+fn test_forget_events_pushed_before_a_crash() {
+    let mut remote = OnMemoryEventStore::new();
+    let mut alice = client();
+    alice.append(&[edit("a1")]).unwrap();
+    // The push reached the remote, but the client stopped before forgetting the events.
+    let pushed = alice.pending()[0].clone();
+    remote
+        .append_multi(&[("doc-1".to_string(), ExpectedVersion::NoStream, vec![pushed])])
+        .unwrap();
+
+    let SyncClient { replica, .. } = alice;
+    let mut alice = Client::open(replica).unwrap();
+    let report = alice.sync(&mut remote, &mut never).unwrap();
+    assert_eq!((report.pushed, report.conflicts), (0, 0));
+    assert!(alice.pending().is_empty());
+    assert_eq!(texts(&alice.load("doc-1").unwrap()), vec![(1, "a1")]);
+}
@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::envelope::{Envelope, Metadata};
+use crate::event_store::EventStore;
+use crate::json;
+use crate::summary::SummaryValue;
+use crate::yaml;
+
+/// An event declared in a [`Fixture`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureEvent {
+    /// Type of the event.
+    pub event_type: String,
+    /// Payload of the event, decoded when seeding a store.
+    pub payload: SummaryValue,
+    /// Metadata of the event.
+    pub metadata: Metadata,
+}
+
+/// A stream declared in a [`Fixture`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureStream {
+    /// ID of the stream.
+    pub stream_id: String,
+    /// Events of the stream, in order.
+    pub events: Vec<FixtureEvent>,
+}
+
+/// Streams and events declared in a file, so that integration tests and demos share readable
+/// fixtures instead of building events in code.
+///
+/// A fixture is a list of streams, each with its events:
+///
+/// ```yaml
+/// - stream: org-1
+///   events:
+///     - type: Created
+///       payload: {"name": "Acme"}
+///       metadata:
+///         user_id: admin
+///     - type: UserAdded
+///       payload: 7
+/// ```
+///
+/// The same document can be written as JSON. Metadata values which are not strings are stored
+/// as JSON.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fixture {
+    streams: Vec<FixtureStream>,
+}
+
+impl Fixture {
+    /// Parse a fixture written as JSON.
+    pub fn from_json(text: &str) -> Result<Self, FixtureError> {
+        Self::from_value(&json::parse(text).map_err(FixtureError::Parse)?)
+    }
+
+    /// Parse a fixture written as YAML. Only block collections, scalars and JSON-like flow
+    /// collections are supported.
+    pub fn from_yaml(text: &str) -> Result<Self, FixtureError> {
+        Self::from_value(&yaml::parse(text).map_err(FixtureError::Parse)?)
+    }
+
+    /// Read a fixture from a JSON file.
+    pub fn read_json(path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        Self::from_json(&fs::read_to_string(path).map_err(FixtureError::Io)?)
+    }
+
+    /// Read a fixture from a YAML file.
+    pub fn read_yaml(path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        Self::from_yaml(&fs::read_to_string(path).map_err(FixtureError::Io)?)
+    }
+
+    /// Get the declared streams.
+    pub fn streams(&self) -> &[FixtureStream] {
+        &self.streams
+    }
+
+    /// Save the events into the store, a stream at a time in the declared order, with the
+    /// payloads decoded from their type. Returns the number of events saved.
+    pub fn seed<S, E>(
+        &self,
+        store: &mut S,
+        decode: impl Fn(&str, &SummaryValue) -> Result<E, String>,
+    ) -> Result<usize, SeedError<S::Error>>
+    where
+        S: EventStore<Persistable = Envelope<E>>,
+    {
+        let mut saved = 0;
+        for stream in &self.streams {
+            let envelopes = stream
+                .events
+                .iter()
+                .enumerate()
+                .map(|(index, event)| {
+                    let payload = decode(&event.event_type, &event.payload).map_err(|message| {
+                        SeedError::Decode {
+                            stream_id: stream.stream_id.clone(),
+                            index,
+                            message,
+                        }
+                    })?;
+                    let mut envelope =
+                        Envelope::new(stream.stream_id.clone(), 0, &event.event_type, payload);
+                    envelope.metadata = event.metadata.clone();
+                    Ok(envelope)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            store.save(&envelopes).map_err(SeedError::Store)?;
+            saved += envelopes.len();
+        }
+        Ok(saved)
+    }
+
+    fn from_value(value: &SummaryValue) -> Result<Self, FixtureError> {
+        let invalid = |message: &str| FixtureError::Invalid(message.to_string());
+        let streams = match value {
+            SummaryValue::List(streams) => streams,
+            SummaryValue::Null => return Ok(Self::default()),
+            _ => return Err(invalid("a fixture is a list of streams")),
+        };
+        let mut fixture = Self::default();
+        for stream in streams {
+            let stream_id = match stream.get("stream") {
+                Some(SummaryValue::String(stream_id)) => stream_id.clone(),
+                _ => return Err(invalid("every stream needs a string `stream`")),
+            };
+            let events = match stream.get("events") {
+                Some(SummaryValue::List(events)) => events,
+                None | Some(SummaryValue::Null) => &Vec::new(),
+                _ => return Err(invalid("the `events` of a stream must be a list")),
+            };
+            let events = events
+                .iter()
+                .map(|event| {
+                    let event_type = match event.get("type") {
+                        Some(SummaryValue::String(event_type)) => event_type.clone(),
+                        _ => return Err(invalid("every event needs a string `type`")),
+                    };
+                    let metadata = match event.get("metadata") {
+                        Some(SummaryValue::Object(fields)) => fields
+                            .iter()
+                            .map(|(key, value)| {
+                                let value = match value {
+                                    SummaryValue::String(value) => value.clone(),
+                                    value => value.to_json(),
+                                };
+                                (key.clone(), value)
+                            })
+                            .collect(),
+                        None | Some(SummaryValue::Null) => Metadata::new(),
+                        _ => return Err(invalid("the `metadata` of an event must be a map")),
+                    };
+                    Ok(FixtureEvent {
+                        event_type,
+                        payload: event.get("payload").cloned().unwrap_or(SummaryValue::Null),
+                        metadata,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            fixture.streams.push(FixtureStream { stream_id, events });
+        }
+        Ok(fixture)
+    }
+}
+
+/// Read the JSON fixture and save its events into the store, returning their number.
+///
+/// See [`Fixture`] for the format, and [`Fixture::seed`] for the decoding of the payloads.
+pub fn seed_store_from_json<S, E>(
+    store: &mut S,
+    path: impl AsRef<Path>,
+    decode: impl Fn(&str, &SummaryValue) -> Result<E, String>,
+) -> Result<usize, SeedError<S::Error>>
+where
+    S: EventStore<Persistable = Envelope<E>>,
+{
+    Fixture::read_json(path)
+        .map_err(SeedError::Fixture)?
+        .seed(store, decode)
+}
+
+/// Read the YAML fixture and save its events into the store, returning their number.
+///
+/// See [`Fixture`] for the format, and [`Fixture::seed`] for the decoding of the payloads.
+pub fn seed_store_from_yaml<S, E>(
+    store: &mut S,
+    path: impl AsRef<Path>,
+    decode: impl Fn(&str, &SummaryValue) -> Result<E, String>,
+) -> Result<usize, SeedError<S::Error>>
+where
+    S: EventStore<Persistable = Envelope<E>>,
+{
+    Fixture::read_yaml(path)
+        .map_err(SeedError::Fixture)?
+        .seed(store, decode)
+}
+
+/// Error returned when reading a [`Fixture`].
+#[derive(Debug)]
+pub enum FixtureError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The document is not valid JSON or YAML.
+    Parse(String),
+    /// The document does not declare streams and events.
+    Invalid(String),
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureError::Io(e) => write!(f, "{}", e),
+            FixtureError::Parse(e) => write!(f, "invalid document: {}", e),
+            FixtureError::Invalid(e) => write!(f, "invalid fixture: {}", e),
+        }
+    }
+}
+
+impl Error for FixtureError {}
+
+/// Error returned when seeding a store from a [`Fixture`].
+#[derive(Debug)]
+pub enum SeedError<E> {
+    /// The fixture could not be read.
+    Fixture(FixtureError),
+    /// The payload of the event at the index of the stream could not be decoded.
+    Decode {
+        /// ID of the stream.
+        stream_id: String,
+        /// Index of the event in the stream of the fixture.
+        index: usize,
+        /// Why the payload could not be decoded.
+        message: String,
+    },
+    /// The store failed; the streams before the failing one were saved.
+    Store(E),
+}
+
+impl<E: Error> fmt::Display for SeedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedError::Fixture(e) => write!(f, "{}", e),
+            SeedError::Decode {
+                stream_id,
+                index,
+                message,
+            } => write!(
+                f,
+                "failed to decode event {} of {}: {}",
+                index, stream_id, message
+            ),
+            SeedError::Store(e) => write!(f, "store error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for SeedError<E> {}
@@ -0,0 +1,118 @@
+use std::env;
+use std::process;
+
+use super::*;
+use crate::event_store::{EventLoader, OnMemoryEventStore, ReadOnlyEventStore};
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrgEvent {
+    Created { name: String },
+    UserAdded(i64),
+}
+
+fn decode(event_type: &str, payload: &SummaryValue) -> Result<OrgEvent, String> {
+    match (event_type, payload) {
+        ("Created", payload) => match payload.get("name") {
+            Some(SummaryValue::String(name)) => Ok(OrgEvent::Created { name: name.clone() }),
+            _ => Err("missing name".to_string()),
+        },
+        ("UserAdded", SummaryValue::Integer(user_id)) => Ok(OrgEvent::UserAdded(*user_id)),
+        _ => Err(format!("unknown event {}", event_type)),
+    }
+}
+
+const YAML: &str = r#"
+# Two organizations, the first with a user.
+- stream: org-1
+  events:
+    - type: Created
+      payload:
+        name: "Acme: the company"
+      metadata:
+        user_id: admin
+        retries: 2
+    - type: UserAdded
+      payload: 7 # alice
+- stream: 'org-2'
+  events:
+  - type: Created
+    payload: {"name": "Globex", "tags": ["a", "b"]}
+"#;
+
+#[test]
+fn test_seed_store_from_yaml() {
+    let path = env::temp_dir().join(format!("crux-fixture-{}.yaml", process::id()));
+    fs::write(&path, YAML).unwrap();
+    let mut store = OnMemoryEventStore::new();
+    let seeded = seed_store_from_yaml(&mut store, &path, decode);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(seeded.unwrap(), 3);
+
+    let org = store.load(&"org-1".to_string()).unwrap();
+    assert_eq!(
+        org.iter().map(|e| e.payload.clone()).collect::<Vec<_>>(),
+        [
+            OrgEvent::Created {
+                name: "Acme: the company".to_string()
+            },
+            OrgEvent::UserAdded(7)
+        ]
+    );
+    assert_eq!(org[0].metadata["user_id"], "admin");
+    assert_eq!(org[0].metadata["retries"], "2");
+    assert_eq!(org[1].version, 2);
+    assert_eq!(store.read_all(2, 1).unwrap()[0].stream_id, "org-2");
+}
+
+#[test]
+fn test_json_and_yaml_fixtures_match() {
+    let json = r#"[
+        {"stream": "org-1", "events": [
+            {"type": "Created", "payload": {"name": "Acme: the company"},
+             "metadata": {"user_id": "admin", "retries": 2}},
+            {"type": "UserAdded", "payload": 7}
+        ]},
+        {"stream": "org-2", "events": [
+            {"type": "Created", "payload": {"name": "Globex", "tags": ["a", "b"]}}
+        ]}
+    ]"#;
+    let fixture = Fixture::from_json(json).unwrap();
+    assert_eq!(fixture, Fixture::from_yaml(YAML).unwrap());
+    assert_eq!(
+        fixture.streams()[1].events[0].payload.to_json(),
+        r#"{"name":"Globex","tags":["a","b"]}"#
+    );
+
+    let path = env::temp_dir().join(format!("crux-fixture-{}.json", process::id()));
+    fs::write(&path, json).unwrap();
+    let mut store = OnMemoryEventStore::new();
+    let seeded = seed_store_from_json(&mut store, &path, decode);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(seeded.unwrap(), 3);
+}
+
+#[test]
+fn test_invalid_fixtures() {
+    assert!(matches!(
+        Fixture::from_json(r#"[{"stream": "org-1""#),
+        Err(FixtureError::Parse(_))
+    ));
+    assert!(matches!(
+        Fixture::from_yaml("- stream: org-1\n  events:\n    - payload: 1\n"),
+        Err(FixtureError::Invalid(message)) if message.contains("`type`")
+    ));
+    assert!(matches!(
+        Fixture::from_yaml("- stream: org-1\n    events: []\n"),
+        Err(FixtureError::Parse(message)) if message == "line 2: unexpected indentation"
+    ));
+    assert_eq!(Fixture::from_yaml("# empty\n").unwrap().streams(), []);
+
+    let fixture = Fixture::from_yaml("- stream: org-1\n  events:\n    - type: Renamed\n").unwrap();
+    let mut store = OnMemoryEventStore::new();
+    assert!(matches!(
+        fixture.seed(&mut store, decode),
+        Err(SeedError::Decode { stream_id, index: 0, message })
+            if stream_id == "org-1" && message == "unknown event Renamed"
+    ));
+    assert_eq!(store.head(), 0);
+}
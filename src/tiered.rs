@@ -0,0 +1,330 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, ReadOnlyEventStore,
+};
+use crate::retention::PrunableEventStore;
+use crate::runtime::HousekeepingJob;
+use crate::sharding::category_of;
+
+/// Metadata key recording the position in the hot log of an event demoted to the cold store.
+pub const HOT_POSITION_METADATA_KEY: &str = "hot_position";
+
+const READ_PAGE_SIZE: usize = 256;
+
+type TimestampFn<E> = Box<dyn Fn(&Envelope<E>) -> Option<SystemTime>>;
+
+/// Event store composing a hot store for recent events and a cold store for history, such as
+/// a database in front of object storage.
+///
+/// Events of the cold categories are appended to the cold store directly. Other events are
+/// appended to the hot store, and moved to the cold store by [`demote`](Self::demote) once
+/// older than the hot retention. Reads merge both tiers transparently.
+pub struct TieredEventStore<E, H, C> {
+    hot: H,
+    cold: C,
+    cold_categories: Vec<String>,
+    hot_for: Option<Duration>,
+    timestamp: TimestampFn<E>,
+}
+
+impl<E, H, C> TieredEventStore<E, H, C> {
+    /// Compose the tiers, reading the time of the events with `timestamp`.
+    pub fn new(
+        hot: H,
+        cold: C,
+        timestamp: impl Fn(&Envelope<E>) -> Option<SystemTime> + 'static,
+    ) -> Self {
+        Self {
+            hot,
+            cold,
+            cold_categories: Vec::new(),
+            hot_for: None,
+            timestamp: Box::new(timestamp),
+        }
+    }
+
+    /// Append the events of the category to the cold store.
+    pub fn cold_category(mut self, category: impl Into<String>) -> Self {
+        self.cold_categories.push(category.into());
+        self
+    }
+
+    /// Keep events in the hot store for the duration.
+    pub fn hot_for(mut self, duration: Duration) -> Self {
+        self.hot_for = Some(duration);
+        self
+    }
+
+    /// Get the hot store.
+    pub fn hot(&self) -> &H {
+        &self.hot
+    }
+
+    /// Get the cold store.
+    pub fn cold(&self) -> &C {
+        &self.cold
+    }
+
+    fn is_cold(&self, stream_id: &str) -> bool {
+        self.cold_categories
+            .iter()
+            .any(|c| c == category_of(stream_id))
+    }
+
+    /// Move the events older than the hot retention to the cold store, returning the number
+    /// moved.
+    ///
+    /// Events are copied to the cold store, recording their position in the hot log, before
+    /// being deleted from the hot store, so a failing cold store leaves them in the hot one.
+    pub fn demote(
+        &mut self,
+        now: SystemTime,
+    ) -> Result<usize, TieredError<<H as EventStore>::Error, <C as EventStore>::Error>>
+    where
+        H: PrunableEventStore<E>
+            + EventStore<Persistable = Envelope<E>>
+            + EventLoader<
+                StreamId = String,
+                Persistable = Envelope<E>,
+                Error = <H as EventStore>::Error,
+            >,
+        C: EventStore<Persistable = Envelope<E>>,
+    {
+        let hot_for = match self.hot_for {
+            Some(hot_for) => hot_for,
+            None => return Ok(0),
+        };
+        let mut moved = 0;
+        for stream_id in self.hot.stream_ids() {
+            let expired = EventLoader::load(&self.hot, &stream_id)
+                .map_err(TieredError::Hot)?
+                .into_iter()
+                .filter(|event| (self.timestamp)(event).is_some_and(|at| at + hot_for <= now))
+                .map(|event| {
+                    let position = event.position.to_string();
+                    event.with_metadata(HOT_POSITION_METADATA_KEY, position)
+                })
+                .collect::<Vec<_>>();
+            if expired.is_empty() {
+                continue;
+            }
+            self.cold.save(&expired).map_err(TieredError::Cold)?;
+            let positions = expired.iter().map(|e| e.position).collect::<HashSet<_>>();
+            self.hot
+                .delete_where(&stream_id, &mut |event| positions.contains(&event.position));
+            moved += expired.len();
+        }
+        Ok(moved)
+    }
+}
+
+/// Restore the position in the hot log of a demoted event.
+fn restore<E>(mut event: Envelope<E>) -> Envelope<E> {
+    if let Some(position) = event.metadata.remove(HOT_POSITION_METADATA_KEY) {
+        event.position = position.parse().unwrap_or(event.position);
+    }
+    event
+}
+
+impl<E, H, C> EventStore for TieredEventStore<E, H, C>
+where
+    H: EventStore<Persistable = Envelope<E>>,
+    C: EventStore<Persistable = Envelope<E>>,
+    E: Clone,
+{
+    type Persistable = Envelope<E>;
+    type Error = TieredError<H::Error, C::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let (cold, hot): (Vec<_>, Vec<_>) = events
+            .iter()
+            .cloned()
+            .partition(|e| self.is_cold(&e.stream_id));
+        if !cold.is_empty() {
+            self.cold.save(&cold).map_err(TieredError::Cold)?;
+        }
+        if !hot.is_empty() {
+            self.hot.save(&hot).map_err(TieredError::Hot)?;
+        }
+        Ok(())
+    }
+
+    /// Append to the tier of the streams. Appends spanning both tiers cannot be atomic, and
+    /// return [`AppendError::Unsupported`].
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let cold = appends.iter().filter(|(id, _, _)| self.is_cold(id)).count();
+        if cold == 0 {
+            self.hot
+                .append_multi(appends)
+                .map_err(|e| e.map_store(TieredError::Hot))
+        } else if cold == appends.len() {
+            self.cold
+                .append_multi(appends)
+                .map_err(|e| e.map_store(TieredError::Cold))
+        } else {
+            Err(AppendError::Unsupported)
+        }
+    }
+}
+
+impl<E, H, C> EventLoader for TieredEventStore<E, H, C>
+where
+    H: EventLoader<StreamId = String, Persistable = Envelope<E>>,
+    C: EventLoader<StreamId = String, Persistable = Envelope<E>>,
+{
+    type StreamId = String;
+    type Persistable = Envelope<E>;
+    type Error = TieredError<H::Error, C::Error>;
+
+    /// Load the stream from both tiers, ordered by version.
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let cold = self.cold.load(stream_id).map_err(TieredError::Cold)?;
+        let hot = if self.is_cold(stream_id) {
+            Vec::new()
+        } else {
+            self.hot.load(stream_id).map_err(TieredError::Hot)?
+        };
+        Ok(merge(cold, hot))
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[String],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        let cold = self
+            .cold
+            .read_multi(stream_ids)
+            .map_err(TieredError::Cold)?;
+        let hot_ids = stream_ids
+            .iter()
+            .filter(|id| !self.is_cold(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut hot = self
+            .hot
+            .read_multi(&hot_ids)
+            .map_err(TieredError::Hot)?
+            .into_iter();
+        Ok(stream_ids
+            .iter()
+            .zip(cold)
+            .map(|(id, cold)| {
+                let hot = if self.is_cold(id) {
+                    Vec::new()
+                } else {
+                    hot.next().unwrap_or_default()
+                };
+                merge(cold, hot)
+            })
+            .collect())
+    }
+}
+
+impl<E, H, C> ReadOnlyEventStore for TieredEventStore<E, H, C>
+where
+    H: ReadOnlyEventStore<StreamId = String, Persistable = Envelope<E>>,
+    C: ReadOnlyEventStore<StreamId = String, Persistable = Envelope<E>>,
+{
+    /// Read the log of the hot store, including the events demoted to the cold store at their
+    /// original position. Events of the cold categories never enter that log, and are only read
+    /// by stream.
+    ///
+    /// Reading a range of the log with gaps scans the cold log for the demoted events.
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let mut events = self.hot.read_all(from, limit).map_err(TieredError::Hot)?;
+        let demoted_until = match events.last() {
+            Some(last) if events.len() == limit => {
+                if last.position - from + 1 == limit as u64 {
+                    return Ok(events);
+                }
+                last.position
+            }
+            _ => u64::MAX,
+        };
+        let mut cursor = 0;
+        loop {
+            let page = self
+                .cold
+                .read_all(cursor, READ_PAGE_SIZE)
+                .map_err(TieredError::Cold)?;
+            let last = match page.last() {
+                Some(last) => last.position,
+                None => break,
+            };
+            events.extend(
+                page.into_iter()
+                    .filter(|e| e.metadata.contains_key(HOT_POSITION_METADATA_KEY))
+                    .map(restore)
+                    .filter(|e| from <= e.position && e.position < demoted_until),
+            );
+            cursor = last + 1;
+        }
+        events.sort_by_key(|e| e.position);
+        events.truncate(limit);
+        Ok(events)
+    }
+}
+
+/// Merge the events of a stream read from both tiers, ordered by version.
+fn merge<E>(cold: Vec<Envelope<E>>, hot: Vec<Envelope<E>>) -> Vec<Envelope<E>> {
+    let mut events = cold.into_iter().map(restore).collect::<Vec<_>>();
+    events.extend(hot);
+    events.sort_by_key(|e| e.version);
+    events.dedup_by_key(|e| e.version);
+    events
+}
+
+/// Housekeeping job demoting the expired events of a [`TieredEventStore`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TieringJob;
+
+impl<E, H, C> HousekeepingJob<TieredEventStore<E, H, C>> for TieringJob
+where
+    H: PrunableEventStore<E>
+        + EventStore<Persistable = Envelope<E>>
+        + EventLoader<StreamId = String, Persistable = Envelope<E>, Error = <H as EventStore>::Error>,
+    C: EventStore<Persistable = Envelope<E>>,
+{
+    fn name(&self) -> &'static str {
+        "tiering"
+    }
+
+    fn run(
+        &mut self,
+        store: &mut TieredEventStore<E, H, C>,
+        now: SystemTime,
+    ) -> Result<usize, String> {
+        store.demote(now).map_err(|e| e.to_string())
+    }
+}
+
+/// Error returned by the [`TieredEventStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TieredError<HE, CE> {
+    /// The hot store failed.
+    Hot(HE),
+    /// The cold store failed.
+    Cold(CE),
+}
+
+impl<HE: Error, CE: Error> fmt::Display for TieredError<HE, CE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TieredError::Hot(e) => write!(f, "hot store error: {}", e),
+            TieredError::Cold(e) => write!(f, "cold store error: {}", e),
+        }
+    }
+}
+
+impl<HE: Error, CE: Error> Error for TieredError<HE, CE> {}
@@ -0,0 +1,154 @@
+use std::time::UNIX_EPOCH;
+
+use super::*;
+use crate::clock::ManualClock;
+use crate::event_store::OnMemoryEventStore;
+use crate::faulty::{FaultConfig, FaultError, FaultyEventStore};
+use crate::runtime::CruxRuntime;
+
+type Store = TieredEventStore<u64, OnMemoryEventStore<u64>, OnMemoryEventStore<u64>>;
+
+// The payload is the time the event occurred at, in seconds.
+fn store() -> Store {
+    TieredEventStore::new(
+        OnMemoryEventStore::new(),
+        OnMemoryEventStore::new(),
+        |event: &Envelope<u64>| Some(UNIX_EPOCH + Duration::from_secs(event.payload)),
+    )
+    .cold_category("audit")
+    .hot_for(Duration::from_secs(100))
+}
+
+fn save(store: &mut Store, stream_id: &str, at: u64) {
+    store
+        .save(&[Envelope::new(stream_id, 0, "Happened", at)])
+        .unwrap();
+}
+
+fn payloads(events: &[Envelope<u64>]) -> Vec<(u64, u64)> {
+    events.iter().map(|e| (e.version, e.payload)).collect()
+}
+
+#[test]
+fn test_route_by_category() {
+    let mut store = store();
+    save(&mut store, "audit-1", 0);
+    save(&mut store, "order-1", 0);
+
+    assert_eq!(store.cold().stream_ids(), vec!["audit-1".to_string()]);
+    assert_eq!(store.hot().stream_ids(), vec!["order-1".to_string()]);
+    assert_eq!(
+        payloads(&store.load(&"audit-1".to_string()).unwrap()),
+        vec![(1, 0)]
+    );
+}
+
+#[test]
+fn test_demote_and_merge_reads() {
+    let mut store = store();
+    for at in [0, 50, 120, 180] {
+        save(&mut store, "order-1", at);
+    }
+
+    assert_eq!(store.demote(UNIX_EPOCH + Duration::from_secs(200)), Ok(2));
+    assert_eq!(store.cold().stream_len("order-1"), 2);
+    assert_eq!(store.hot().stream_len("order-1"), 2);
+    assert_eq!(
+        payloads(&store.load(&"order-1".to_string()).unwrap()),
+        vec![(1, 0), (2, 50), (3, 120), (4, 180)]
+    );
+
+    save(&mut store, "order-1", 210);
+    let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(400));
+    let mut runtime = CruxRuntime::new(store, clock).with_job(TieringJob);
+    assert_eq!(runtime.run_housekeeping()[0].result, Ok(3));
+    assert_eq!(
+        payloads(&runtime.store().load(&"order-1".to_string()).unwrap()),
+        vec![(1, 0), (2, 50), (3, 120), (4, 180), (5, 210)]
+    );
+}
+
+#[test]
+fn test_keep_events_when_cold_store_fails() {
+    let config = FaultConfig {
+        error_rate: 1.0,
+        ..FaultConfig::default()
+    };
+    let mut store = TieredEventStore::new(
+        OnMemoryEventStore::new(),
+        FaultyEventStore::new(OnMemoryEventStore::new(), config),
+        |event: &Envelope<u64>| Some(UNIX_EPOCH + Duration::from_secs(event.payload)),
+    )
+    .hot_for(Duration::from_secs(100));
+    store
+        .save(&[Envelope::new("order-1", 0, "Happened", 0)])
+        .unwrap();
+
+    assert_eq!(
+        store.demote(UNIX_EPOCH + Duration::from_secs(200)),
+        Err(TieredError::Cold(FaultError::Injected))
+    );
+    assert_eq!(store.hot().stream_len("order-1"), 1);
+}
+
+#[test]
+fn test_read_all_across_tiers() {
+    let mut store = store();
+    for (stream_id, at) in [
+        ("order-1", 0),
+        ("order-2", 150),
+        ("audit-1", 0),
+        ("order-1", 50),
+    ] {
+        save(&mut store, stream_id, at);
+    }
+    store.demote(UNIX_EPOCH + Duration::from_secs(200)).unwrap();
+
+    let events = store.read_all(0, 10).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.position, e.stream_id.as_str(), e.payload))
+            .collect::<Vec<_>>(),
+        [(0, "order-1", 0), (1, "order-2", 150), (2, "order-1", 50)]
+    );
+    assert!(events[0].metadata.is_empty());
+    assert_eq!(store.read_all(1, 1).unwrap()[0].payload, 150);
+    assert_eq!(store.read_all(2, 10).unwrap()[0].payload, 50);
+    assert_eq!(
+        store
+            .read_multi(&["order-1".to_string(), "audit-1".to_string()])
+            .unwrap()
+            .iter()
+            .map(|events| payloads(events))
+            .collect::<Vec<_>>(),
+        [vec![(1, 0), (2, 50)], vec![(1, 0)]]
+    );
+}
+
+#[test]
+fn test_append_multi_within_a_tier() {
+    let mut store = store();
+    store
+        .append_multi(&[
+            (
+                "order-1".to_string(),
+                ExpectedVersion::NoStream,
+                vec![Envelope::new("order-1", 0, "Happened", 0)],
+            ),
+            (
+                "order-2".to_string(),
+                ExpectedVersion::NoStream,
+                vec![Envelope::new("order-2", 0, "Happened", 0)],
+            ),
+        ])
+        .unwrap();
+    assert_eq!(store.hot().stream_ids(), ["order-1", "order-2"]);
+    assert_eq!(
+        store.append_multi(&[
+            ("order-3".to_string(), ExpectedVersion::Any, Vec::new()),
+            ("audit-1".to_string(), ExpectedVersion::Any, Vec::new()),
+        ]),
+        Err(AppendError::Unsupported)
+    );
+}
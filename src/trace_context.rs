@@ -0,0 +1,329 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Publisher;
+use crate::envelope::Envelope;
+use crate::event_id::EventId;
+
+/// Metadata key carrying the W3C `traceparent` header.
+pub const TRACEPARENT_METADATA_KEY: &str = "traceparent";
+/// Metadata key carrying the W3C `tracestate` header.
+pub const TRACESTATE_METADATA_KEY: &str = "tracestate";
+
+/// W3C trace context, identifying a span within a distributed trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// ID of the trace.
+    pub trace_id: u128,
+    /// ID of the span.
+    pub span_id: u64,
+    /// Whether the trace is sampled.
+    pub sampled: bool,
+    /// Vendor-specific `tracestate` header.
+    pub state: Option<String>,
+}
+
+impl TraceContext {
+    /// Start a new sampled trace.
+    pub fn root() -> Self {
+        Self {
+            trace_id: EventId::generate().as_u128(),
+            span_id: Self::span_id(),
+            sampled: true,
+            state: None,
+        }
+    }
+
+    /// Create the context of a span child of this one.
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: Self::span_id(),
+            ..self.clone()
+        }
+    }
+
+    /// Parse a `traceparent` header.
+    pub fn parse(traceparent: &str) -> Result<Self, TraceContextError> {
+        let invalid = || TraceContextError::Invalid(traceparent.to_string());
+        let parts = traceparent.split('-').collect::<Vec<_>>();
+        let [version, trace_id, span_id, flags] = parts[..] else {
+            return Err(invalid());
+        };
+        if version.len() != 2 || trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+            return Err(invalid());
+        }
+        if version == "ff"
+            || !traceparent
+                .bytes()
+                .all(|b| b == b'-' || b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        {
+            return Err(invalid());
+        }
+        let trace_id = u128::from_str_radix(trace_id, 16).map_err(|_| invalid())?;
+        let span_id = u64::from_str_radix(span_id, 16).map_err(|_| invalid())?;
+        let flags = u8::from_str_radix(flags, 16).map_err(|_| invalid())?;
+        if trace_id == 0 || span_id == 0 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            trace_id,
+            span_id,
+            sampled: flags & 1 == 1,
+            state: None,
+        })
+    }
+
+    /// Format the context as a `traceparent` header.
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.span_id, self.sampled as u8
+        )
+    }
+
+    fn span_id() -> u64 {
+        (EventId::generate().as_u128() as u64).max(1)
+    }
+}
+
+/// Types which carry a trace context, such as envelopes through their metadata.
+pub trait TraceCarrier {
+    /// Extract the trace context, if any valid one is carried.
+    fn trace_context(&self) -> Option<TraceContext>;
+    /// Inject the trace context.
+    fn set_trace_context(&mut self, context: &TraceContext);
+}
+
+impl<E> TraceCarrier for Envelope<E> {
+    fn trace_context(&self) -> Option<TraceContext> {
+        let traceparent = self.metadata.get(TRACEPARENT_METADATA_KEY)?;
+        let mut context = TraceContext::parse(traceparent).ok()?;
+        context.state = self.metadata.get(TRACESTATE_METADATA_KEY).cloned();
+        Some(context)
+    }
+
+    fn set_trace_context(&mut self, context: &TraceContext) {
+        self.metadata
+            .insert(TRACEPARENT_METADATA_KEY.to_string(), context.traceparent());
+        match &context.state {
+            Some(state) => {
+                self.metadata
+                    .insert(TRACESTATE_METADATA_KEY.to_string(), state.clone());
+            }
+            None => {
+                self.metadata.remove(TRACESTATE_METADATA_KEY);
+            }
+        }
+    }
+}
+
+/// Types which record spans, bridging to the tracing system of the application.
+pub trait Tracer {
+    /// Start a span, child of the parent context or the root of a new trace.
+    fn start_span(&mut self, name: &str, parent: Option<&TraceContext>) -> TraceContext;
+    /// End the span, with the error it failed with, if any.
+    fn end_span(&mut self, span: &TraceContext, error: Option<&str>);
+}
+
+/// A span recorded by the [`OnMemoryTracer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanRecord {
+    /// Name of the span.
+    pub name: String,
+    /// Context of the span.
+    pub context: TraceContext,
+    /// ID of the parent span, if any.
+    pub parent_span_id: Option<u64>,
+    /// Whether the span ended.
+    pub ended: bool,
+    /// Error the span failed with.
+    pub error: Option<String>,
+}
+
+/// Tracer keeping the spans in memory.
+#[derive(Debug, Default)]
+pub struct OnMemoryTracer {
+    spans: Vec<SpanRecord>,
+}
+
+impl OnMemoryTracer {
+    /// Create a tracer without spans.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the recorded spans, in order of start.
+    pub fn spans(&self) -> &[SpanRecord] {
+        &self.spans
+    }
+}
+
+impl Tracer for OnMemoryTracer {
+    fn start_span(&mut self, name: &str, parent: Option<&TraceContext>) -> TraceContext {
+        let context = parent.map_or_else(TraceContext::root, TraceContext::child);
+        self.spans.push(SpanRecord {
+            name: name.to_string(),
+            context: context.clone(),
+            parent_span_id: parent.map(|p| p.span_id),
+            ended: false,
+            error: None,
+        });
+        context
+    }
+
+    fn end_span(&mut self, span: &TraceContext, error: Option<&str>) {
+        if let Some(record) = self.spans.iter_mut().find(|r| r.context == *span) {
+            record.ended = true;
+            record.error = error.map(str::to_string);
+        }
+    }
+}
+
+/// Counts of the spans started through a [`SamplingTracer`], sampled or not.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanCounts {
+    /// Number of spans started.
+    pub started: u64,
+    /// Number of spans recorded by the wrapped tracer.
+    pub sampled: u64,
+    /// Number of spans which ended with an error.
+    pub failed: u64,
+}
+
+/// Tracer recording only a share of the traces in the wrapped tracer, while counting every
+/// span, so that detailed recording stays cheap at high event rates.
+///
+/// The decision is taken once per trace, from its ID, and followed by every span of the trace,
+/// so that correlated events are either all recorded or none. Spans with a parent follow the
+/// `sampled` flag of the parent.
+#[derive(Debug)]
+pub struct SamplingTracer<T> {
+    inner: T,
+    threshold: u64,
+    counts: SpanCounts,
+}
+
+impl<T: Tracer> SamplingTracer<T> {
+    /// Wrap the tracer, sampling the given ratio of the traces, between 0 and 1.
+    pub fn new(inner: T, ratio: f64) -> Self {
+        Self {
+            inner,
+            threshold: (ratio.clamp(0.0, 1.0) * u64::MAX as f64) as u64,
+            counts: SpanCounts::default(),
+        }
+    }
+
+    /// Get the counts of the spans started so far.
+    pub fn counts(&self) -> SpanCounts {
+        self.counts
+    }
+
+    /// Get the wrapped tracer.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn samples(&self, trace_id: u128) -> bool {
+        // Trace IDs are UUIDv7, whose bits are not uniform: mix them (splitmix64 finalizer).
+        let mut x = trace_id as u64 ^ (trace_id >> 64) as u64;
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^= x >> 31;
+        self.threshold == u64::MAX || x < self.threshold
+    }
+}
+
+impl<T: Tracer> Tracer for SamplingTracer<T> {
+    fn start_span(&mut self, name: &str, parent: Option<&TraceContext>) -> TraceContext {
+        self.counts.started += 1;
+        let context = match parent {
+            Some(parent) => parent.child(),
+            None => {
+                let mut root = TraceContext::root();
+                root.sampled = self.samples(root.trace_id);
+                root
+            }
+        };
+        if !context.sampled {
+            return context;
+        }
+        self.counts.sampled += 1;
+        self.inner.start_span(name, parent)
+    }
+
+    fn end_span(&mut self, span: &TraceContext, error: Option<&str>) {
+        if error.is_some() {
+            self.counts.failed += 1;
+        }
+        if span.sampled {
+            self.inner.end_span(span, error);
+        }
+    }
+}
+
+/// Publisher propagating the current trace context in the metadata of the envelopes.
+///
+/// Envelopes already carrying a trace context keep it.
+pub struct TracingPublisher<P> {
+    inner: P,
+    context: Option<TraceContext>,
+}
+
+impl<P> TracingPublisher<P> {
+    /// Wrap the publisher, without a current trace context.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            context: None,
+        }
+    }
+
+    /// Set the trace context of the current operation, e.g. of the handled request.
+    pub fn set_context(&mut self, context: Option<TraceContext>) {
+        self.context = context;
+    }
+
+    /// Get the wrapped publisher.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P, E> Publisher<Envelope<E>> for TracingPublisher<P>
+where
+    P: Publisher<Envelope<E>>,
+    E: Clone,
+{
+    type Error = P::Error;
+
+    fn publish(&mut self, message: &Envelope<E>) -> Result<(), Self::Error> {
+        match &self.context {
+            Some(context) if message.trace_context().is_none() => {
+                let mut message = message.clone();
+                message.set_trace_context(context);
+                self.inner.publish(&message)
+            }
+            _ => self.inner.publish(message),
+        }
+    }
+}
+
+/// Error returned when parsing a trace context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceContextError {
+    /// The `traceparent` header is malformed.
+    Invalid(String),
+}
+
+impl fmt::Display for TraceContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceContextError::Invalid(header) => write!(f, "invalid traceparent: {}", header),
+        }
+    }
+}
+
+impl Error for TraceContextError {}
@@ -0,0 +1,156 @@
+use std::convert::Infallible;
+
+use super::*;
+use crate::broker::{OnMemoryBroker, Subscription};
+use crate::command_bus::CommandBus;
+use crate::inbox::{
+    process_traced, Inbox, InboxMessage, InboxReport, MessageTranslator, OnMemoryInbox,
+};
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+#[test]
+fn test_parse_and_format_traceparent() {
+    let context = TraceContext::parse(TRACEPARENT).unwrap();
+    assert_eq!(context.trace_id, 0x4bf92f3577b34da6a3ce929d0e0e4736);
+    assert_eq!(context.span_id, 0x00f067aa0ba902b7);
+    assert!(context.sampled);
+    assert_eq!(context.traceparent(), TRACEPARENT);
+
+    let child = context.child();
+    assert_eq!(child.trace_id, context.trace_id);
+    assert_ne!(child.span_id, context.span_id);
+
+    for invalid in [
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+        "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+        "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+    ] {
+        assert_eq!(
+            TraceContext::parse(invalid),
+            Err(TraceContextError::Invalid(invalid.to_string()))
+        );
+    }
+}
+
+struct Translator;
+
+impl MessageTranslator<Envelope<String>> for Translator {
+    type Command = String;
+    type Error = Infallible;
+
+    fn translate(&self, message: &Envelope<String>) -> Result<Self::Command, Self::Error> {
+        Ok(message.payload.clone())
+    }
+}
+
+#[derive(Default)]
+struct RecordingBus(Vec<String>);
+
+impl CommandBus<String> for RecordingBus {
+    type Response = ();
+    type Error = Infallible;
+
+    fn dispatch(&mut self, command: String) -> Result<Self::Response, Self::Error> {
+        self.0.push(command);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_propagate_from_publisher_to_inbox() {
+    let mut context = TraceContext::parse(TRACEPARENT).unwrap();
+    context.state = Some("vendor=value".to_string());
+    let mut publisher = TracingPublisher::new(OnMemoryBroker::new());
+    publisher.set_context(Some(context.clone()));
+    publisher
+        .publish(&Envelope::new(
+            "user-1",
+            1,
+            "UserCreated",
+            "alice".to_string(),
+        ))
+        .unwrap();
+    publisher.set_context(None);
+    publisher
+        .publish(&Envelope::new(
+            "user-2",
+            1,
+            "UserCreated",
+            "bob".to_string(),
+        ))
+        .unwrap();
+
+    // Consumer side.
+    let mut broker = publisher.inner;
+    let mut inbox = OnMemoryInbox::new();
+    while let Some(envelope) = broker.poll().unwrap() {
+        inbox
+            .receive(InboxMessage::from_envelope(envelope))
+            .unwrap();
+    }
+    let mut tracer = OnMemoryTracer::new();
+    let mut bus = RecordingBus::default();
+    let report = process_traced(&mut inbox, &Translator, &mut bus, &mut tracer).unwrap();
+    assert_eq!(
+        report,
+        InboxReport {
+            processed: 2,
+            failed: 0
+        }
+    );
+    assert_eq!(bus.0, ["alice", "bob"]);
+
+    let spans = tracer.spans();
+    assert_eq!(spans[0].name, "inbox.process");
+    assert_eq!(spans[0].parent_span_id, Some(context.span_id));
+    assert_eq!(spans[0].context.trace_id, context.trace_id);
+    assert_eq!(spans[0].context.state, Some("vendor=value".to_string()));
+    assert!(spans[0].ended);
+    // Without a context, the consumer starts a new trace.
+    assert_eq!(spans[1].parent_span_id, None);
+    assert_ne!(spans[1].context.trace_id, context.trace_id);
+}
+
+#[test]
+fn test_sampling_tracer() {
+    let mut tracer = SamplingTracer::new(OnMemoryTracer::new(), 0.25);
+    for _ in 0..1000 {
+        let root = tracer.start_span("handle", None);
+        let child = tracer.start_span("apply", Some(&root));
+        assert_eq!(child.sampled, root.sampled);
+        tracer.end_span(&child, Some("conflict"));
+        tracer.end_span(&root, None);
+    }
+
+    let counts = tracer.counts();
+    assert_eq!(counts.started, 2000);
+    assert_eq!(counts.failed, 1000);
+    assert!((300..700).contains(&counts.sampled), "{:?}", counts);
+    let spans = tracer.inner().spans();
+    assert_eq!(spans.len() as u64, counts.sampled);
+    assert!(spans.iter().all(|span| span.ended));
+    assert_eq!(
+        spans.iter().filter(|s| s.parent_span_id.is_none()).count() * 2,
+        spans.len()
+    );
+}
+
+#[test]
+fn test_sampling_tracer_follows_parent() {
+    let mut tracer = SamplingTracer::new(OnMemoryTracer::new(), 0.0);
+    assert!(!tracer.start_span("handle", None).sampled);
+
+    let upstream = TraceContext::parse(TRACEPARENT).unwrap();
+    let span = tracer.start_span("handle", Some(&upstream));
+    assert!(span.sampled);
+    assert_eq!(span.trace_id, upstream.trace_id);
+
+    let mut tracer = SamplingTracer::new(OnMemoryTracer::new(), 1.0);
+    let mut unsampled = upstream.clone();
+    unsampled.sampled = false;
+    assert!(!tracer.start_span("handle", Some(&unsampled)).sampled);
+    assert!(tracer.start_span("handle", None).sampled);
+    assert_eq!(tracer.inner().spans().len(), 1);
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::aggregate::Aggregate;
+use crate::envelope::Envelope;
+use crate::event_store::{EventLoader, EventStore};
+
+/// Metadata key marking a compensating event with the version of the event it reverts.
+pub const UNDOES_METADATA_KEY: &str = "undoes";
+/// Metadata key marking a replayed event with the version of the event it replays.
+pub const REDOES_METADATA_KEY: &str = "redoes";
+
+/// Types which represent an aggregate whose events can be reverted by an inverse event.
+pub trait Invertible: Aggregate {
+    /// Get the event reverting the event, given the state before it was applied, or `None` if
+    /// it cannot be reverted.
+    fn invert(&self, event: &Self::Event) -> Option<Self::Event>;
+}
+
+/// Revert the last `count` user actions of the aggregate by appending compensating events,
+/// most recent first. Returns the number of actions reverted, less than `count` if the history
+/// is shorter.
+///
+/// Every event appended without undo metadata counts as a user action. Undone actions can be
+/// replayed by [`redo`] until a new action is appended.
+pub fn undo<A, S>(
+    store: &mut S,
+    id: &A::Id,
+    count: usize,
+) -> Result<usize, UndoError<<S as EventLoader>::Error>>
+where
+    A: Invertible + Default + Clone,
+    A::Event: Clone,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>>
+        + EventStore<Persistable = Envelope<A::Event>, Error = <S as EventLoader>::Error>,
+{
+    let stream_id = A::stream_id(id);
+    let events = EventLoader::load(store, &stream_id).map_err(UndoError::Store)?;
+    let history = History::of(&events);
+    let mut state = A::default();
+    let mut states = Vec::with_capacity(events.len());
+    for event in &events {
+        states.push(state.clone());
+        state.apply(&event.payload);
+    }
+
+    let mut compensations = Vec::new();
+    for version in history.done.iter().rev().take(count) {
+        let index =
+            History::index_of(&events, *version).ok_or(UndoError::UnknownVersion(*version))?;
+        let inverse = states[index]
+            .invert(&events[index].payload)
+            .ok_or(UndoError::NotInvertible(*version))?;
+        compensations.push(
+            Envelope::new(stream_id.clone(), 0, A::event_type(&inverse), inverse)
+                .with_metadata(UNDOES_METADATA_KEY, version.to_string()),
+        );
+    }
+    store.save(&compensations).map_err(UndoError::Store)?;
+    Ok(compensations.len())
+}
+
+/// Replay the last `count` actions reverted by [`undo`], most recently reverted first.
+/// Returns the number of actions replayed.
+pub fn redo<A, S>(
+    store: &mut S,
+    id: &A::Id,
+    count: usize,
+) -> Result<usize, UndoError<<S as EventLoader>::Error>>
+where
+    A: Aggregate,
+    A::Event: Clone,
+    S: EventLoader<StreamId = String, Persistable = Envelope<A::Event>>
+        + EventStore<Persistable = Envelope<A::Event>, Error = <S as EventLoader>::Error>,
+{
+    let stream_id = A::stream_id(id);
+    let events = EventLoader::load(store, &stream_id).map_err(UndoError::Store)?;
+    let history = History::of(&events);
+    let replays = history
+        .undone
+        .iter()
+        .rev()
+        .take(count)
+        .map(|version| {
+            let index =
+                History::index_of(&events, *version).ok_or(UndoError::UnknownVersion(*version))?;
+            let event = &events[index].payload;
+            Ok(
+                Envelope::new(stream_id.clone(), 0, A::event_type(event), event.clone())
+                    .with_metadata(REDOES_METADATA_KEY, version.to_string()),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    store.save(&replays).map_err(UndoError::Store)?;
+    Ok(replays.len())
+}
+
+/// Undo and redo stacks of a stream, as versions of events.
+struct History {
+    done: Vec<u64>,
+    undone: Vec<u64>,
+}
+
+impl History {
+    fn of<E>(events: &[Envelope<E>]) -> Self {
+        let mut history = Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+        };
+        for event in events {
+            let marker = |key| {
+                event
+                    .metadata
+                    .get(key)
+                    .and_then(|v: &String| v.parse::<u64>().ok())
+            };
+            if let Some(version) = marker(UNDOES_METADATA_KEY) {
+                history.done.retain(|v| *v != version);
+                history.undone.push(version);
+            } else if let Some(version) = marker(REDOES_METADATA_KEY) {
+                history.undone.retain(|v| *v != version);
+                history.done.push(event.version);
+            } else {
+                history.done.push(event.version);
+                history.undone.clear();
+            }
+        }
+        history
+    }
+
+    /// Get the index of the event at the version, or `None` if the metadata of an event
+    /// names a version missing from the stream.
+    fn index_of<E>(events: &[Envelope<E>], version: u64) -> Option<usize> {
+        events.binary_search_by_key(&version, |e| e.version).ok()
+    }
+}
+
+/// Error returned by [`undo`] and [`redo`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UndoError<E> {
+    /// The event at the version has no inverse.
+    NotInvertible(u64),
+    /// The undo or redo metadata of an event names a version missing from the stream.
+    UnknownVersion(u64),
+    /// The store failed.
+    Store(E),
+}
+
+impl<E: Error> fmt::Display for UndoError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndoError::NotInvertible(version) => {
+                write!(f, "event at version {} cannot be undone", version)
+            }
+            UndoError::UnknownVersion(version) => {
+                write!(f, "no event at version {} to undo or redo", version)
+            }
+            UndoError::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for UndoError<E> {}
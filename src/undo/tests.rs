@@ -0,0 +1,147 @@
+use super::*;
+use crate::event_store::OnMemoryEventStore;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Document {
+    title: String,
+    locked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DocumentEvent {
+    Retitled(String),
+    Locked,
+}
+
+impl Aggregate for Document {
+    type Id = u32;
+    type Event = DocumentEvent;
+
+    fn aggregate_type() -> &'static str {
+        "document"
+    }
+
+    fn event_type(event: &DocumentEvent) -> String {
+        match event {
+            DocumentEvent::Retitled(_) => "Retitled".to_string(),
+            DocumentEvent::Locked => "Locked".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &DocumentEvent) {
+        match event {
+            DocumentEvent::Retitled(title) => self.title = title.clone(),
+            DocumentEvent::Locked => self.locked = true,
+        }
+    }
+}
+
+impl Invertible for Document {
+    fn invert(&self, event: &DocumentEvent) -> Option<DocumentEvent> {
+        match event {
+            DocumentEvent::Retitled(_) => Some(DocumentEvent::Retitled(self.title.clone())),
+            DocumentEvent::Locked => None,
+        }
+    }
+}
+
+fn retitle(store: &mut OnMemoryEventStore<DocumentEvent>, title: &str) {
+    let event = DocumentEvent::Retitled(title.to_string());
+    store
+        .save(&[Envelope::new("document-1", 0, "Retitled", event)])
+        .unwrap();
+}
+
+fn title(store: &OnMemoryEventStore<DocumentEvent>) -> String {
+    let mut document = Document::default();
+    for event in EventLoader::load(store, &"document-1".to_string()).unwrap() {
+        document.apply(&event.payload);
+    }
+    document.title
+}
+
+#[test]
+fn test_undo_and_redo() {
+    let mut store = OnMemoryEventStore::new();
+    for t in ["a", "b", "c"] {
+        retitle(&mut store, t);
+    }
+
+    assert_eq!(undo::<Document, _>(&mut store, &1, 2), Ok(2));
+    assert_eq!(title(&store), "a");
+    let events = EventLoader::load(&store, &"document-1".to_string()).unwrap();
+    assert_eq!(events.len(), 5);
+    assert_eq!(events[3].metadata[UNDOES_METADATA_KEY], "3");
+
+    assert_eq!(redo::<Document, _>(&mut store, &1, 1), Ok(1));
+    assert_eq!(title(&store), "b");
+    assert_eq!(undo::<Document, _>(&mut store, &1, 1), Ok(1));
+    assert_eq!(title(&store), "a");
+    assert_eq!(redo::<Document, _>(&mut store, &1, 5), Ok(2));
+    assert_eq!(title(&store), "c");
+    assert_eq!(redo::<Document, _>(&mut store, &1, 1), Ok(0));
+
+    assert_eq!(undo::<Document, _>(&mut store, &1, 10), Ok(3));
+    assert_eq!(title(&store), "");
+    assert_eq!(undo::<Document, _>(&mut store, &1, 1), Ok(0));
+}
+
+#[test]
+fn test_new_action_clears_redo() {
+    let mut store = OnMemoryEventStore::new();
+    retitle(&mut store, "a");
+    retitle(&mut store, "b");
+    undo::<Document, _>(&mut store, &1, 1).unwrap();
+    retitle(&mut store, "c");
+
+    assert_eq!(redo::<Document, _>(&mut store, &1, 1), Ok(0));
+    assert_eq!(undo::<Document, _>(&mut store, &1, 1), Ok(1));
+    assert_eq!(title(&store), "a");
+}
+
+#[test]
+fn test_not_invertible() {
+    let mut store = OnMemoryEventStore::new();
+    retitle(&mut store, "a");
+    store
+        .save(&[Envelope::new(
+            "document-1",
+            0,
+            "Locked",
+            DocumentEvent::Locked,
+        )])
+        .unwrap();
+
+    assert_eq!(
+        undo::<Document, _>(&mut store, &1, 1),
+        Err(UndoError::NotInvertible(2))
+    );
+    assert_eq!(
+        EventLoader::load(&store, &"document-1".to_string())
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_marker_of_missing_version() {
+    let mut store = OnMemoryEventStore::new();
+    retitle(&mut store, "a");
+    let event = DocumentEvent::Retitled(String::new());
+    store
+        .save(&[Envelope::new("document-1", 0, "Retitled", event)
+            .with_metadata(UNDOES_METADATA_KEY, "9")])
+        .unwrap();
+
+    assert_eq!(
+        redo::<Document, _>(&mut store, &1, 1),
+        Err(UndoError::UnknownVersion(9))
+    );
+    assert_eq!(
+        EventLoader::load(&store, &"document-1".to_string())
+            .unwrap()
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::Clock;
+use crate::envelope::Envelope;
+use crate::event_store::{AppendError, EventLoader, EventStore, ExpectedVersion};
+
+/// Event of the stream of a unique value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReservationEvent {
+    /// The value was reserved by the owner until the time.
+    Reserved {
+        owner: String,
+        expires_at: SystemTime,
+    },
+    /// The reservation of the owner was made permanent.
+    Confirmed { owner: String },
+    /// The owner gave the value up.
+    Released { owner: String },
+}
+
+impl ReservationEvent {
+    /// Get the name of the event type.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            ReservationEvent::Reserved { .. } => "UniqueValueReserved",
+            ReservationEvent::Confirmed { .. } => "UniqueValueConfirmed",
+            ReservationEvent::Released { .. } => "UniqueValueReleased",
+        }
+    }
+}
+
+/// State of a unique value, folded from its stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReservationState {
+    /// Nobody holds the value, or its reservation expired.
+    Free,
+    /// The value is reserved by the owner until the time.
+    Reserved {
+        owner: String,
+        expires_at: SystemTime,
+    },
+    /// The value belongs to the owner.
+    Confirmed { owner: String },
+}
+
+impl ReservationState {
+    /// Get the owner holding the value.
+    pub fn owner(&self) -> Option<&str> {
+        match self {
+            ReservationState::Free => None,
+            ReservationState::Reserved { owner, .. } | ReservationState::Confirmed { owner } => {
+                Some(owner)
+            }
+        }
+    }
+}
+
+/// Uniqueness constraint spanning aggregates, such as unique usernames.
+///
+/// Every value of the index is a stream named `{index}-{value}`. A command handler first
+/// [`reserve`](Self::reserve)s the value, then [`confirm`](Self::confirm)s it once its own
+/// events are saved, or [`release`](Self::release)s it when failing. Reservations not confirmed
+/// within the time to live expire, so that crashed handlers do not hold values forever.
+pub struct UniqueIndexReservation<S, K> {
+    store: S,
+    clock: K,
+    index: String,
+    ttl: Duration,
+}
+
+impl<S, K> UniqueIndexReservation<S, K>
+where
+    S: EventStore<Persistable = Envelope<ReservationEvent>>
+        + EventLoader<
+            StreamId = String,
+            Persistable = Envelope<ReservationEvent>,
+            Error = <S as EventStore>::Error,
+        >,
+    K: Clock,
+{
+    /// Create the index, reservations expiring after the time to live.
+    pub fn new(store: S, clock: K, index: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            store,
+            clock,
+            index: index.into(),
+            ttl,
+        }
+    }
+
+    /// Get the underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the ID of the stream of the value.
+    pub fn stream_id(&self, value: &str) -> String {
+        format!("{}-{}", self.index, value)
+    }
+
+    /// Get the state of the value at the current time.
+    pub fn state(&self, value: &str) -> Result<ReservationState, <S as EventStore>::Error> {
+        Ok(self.load(value)?.0)
+    }
+
+    /// Get the owner holding the value.
+    pub fn owner(&self, value: &str) -> Result<Option<String>, <S as EventStore>::Error> {
+        Ok(self.state(value)?.owner().map(str::to_string))
+    }
+
+    /// Reserve the value for the owner, returning when the reservation expires.
+    ///
+    /// Reserving again a value reserved by the same owner extends the reservation.
+    pub fn reserve(
+        &mut self,
+        value: &str,
+        owner: &str,
+    ) -> Result<SystemTime, ReservationError<<S as EventStore>::Error>> {
+        let (state, version) = self.load(value).map_err(ReservationError::Store)?;
+        match state.owner() {
+            Some(current) if current != owner => {
+                return Err(ReservationError::Taken(value.to_string()))
+            }
+            _ => {}
+        }
+        if let ReservationState::Confirmed { .. } = state {
+            return Err(ReservationError::Taken(value.to_string()));
+        }
+        let expires_at = self.clock.now() + self.ttl;
+        self.append(
+            value,
+            version,
+            ReservationEvent::Reserved {
+                owner: owner.to_string(),
+                expires_at,
+            },
+        )?;
+        Ok(expires_at)
+    }
+
+    /// Make the reservation of the owner permanent.
+    pub fn confirm(
+        &mut self,
+        value: &str,
+        owner: &str,
+    ) -> Result<(), ReservationError<<S as EventStore>::Error>> {
+        let (state, version) = self.load(value).map_err(ReservationError::Store)?;
+        match state {
+            ReservationState::Reserved { owner: current, .. } if current == owner => self.append(
+                value,
+                version,
+                ReservationEvent::Confirmed {
+                    owner: owner.to_string(),
+                },
+            ),
+            ReservationState::Confirmed { owner: current } if current == owner => Ok(()),
+            _ => Err(ReservationError::NotHeld(value.to_string())),
+        }
+    }
+
+    /// Give up the value held by the owner, whether reserved or confirmed.
+    pub fn release(
+        &mut self,
+        value: &str,
+        owner: &str,
+    ) -> Result<(), ReservationError<<S as EventStore>::Error>> {
+        let (state, version) = self.load(value).map_err(ReservationError::Store)?;
+        if state.owner() != Some(owner) {
+            return Err(ReservationError::NotHeld(value.to_string()));
+        }
+        self.append(
+            value,
+            version,
+            ReservationEvent::Released {
+                owner: owner.to_string(),
+            },
+        )
+    }
+
+    fn load(&self, value: &str) -> Result<(ReservationState, u64), <S as EventStore>::Error> {
+        let events = self.store.load(&self.stream_id(value))?;
+        let version = events.last().map_or(0, |e| e.version);
+        let state =
+            events
+                .into_iter()
+                .fold(ReservationState::Free, |_, event| match event.payload {
+                    ReservationEvent::Reserved { owner, expires_at } => {
+                        ReservationState::Reserved { owner, expires_at }
+                    }
+                    ReservationEvent::Confirmed { owner } => ReservationState::Confirmed { owner },
+                    ReservationEvent::Released { .. } => ReservationState::Free,
+                });
+        let state = match state {
+            ReservationState::Reserved { expires_at, .. } if expires_at <= self.clock.now() => {
+                ReservationState::Free
+            }
+            state => state,
+        };
+        Ok((state, version))
+    }
+
+    fn append(
+        &mut self,
+        value: &str,
+        version: u64,
+        event: ReservationEvent,
+    ) -> Result<(), ReservationError<<S as EventStore>::Error>> {
+        let stream_id = self.stream_id(value);
+        let envelope = Envelope::new(stream_id.clone(), version + 1, event.event_type(), event);
+        let expected = ExpectedVersion::Exact(version);
+        match self
+            .store
+            .append_multi(&[(stream_id, expected, vec![envelope])])
+        {
+            Ok(()) => Ok(()),
+            Err(AppendError::WrongExpectedVersion { .. }) => {
+                Err(ReservationError::Taken(value.to_string()))
+            }
+            Err(e) => Err(ReservationError::Append(e)),
+        }
+    }
+}
+
+/// Error returned by [`UniqueIndexReservation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReservationError<E> {
+    /// The value is held by another owner, or was concurrently modified.
+    Taken(String),
+    /// The value is not held by the owner.
+    NotHeld(String),
+    /// The store failed to load the stream of the value.
+    Store(E),
+    /// The store failed to append to the stream of the value.
+    Append(AppendError<E>),
+}
+
+impl<E: Error> fmt::Display for ReservationError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationError::Taken(value) => write!(f, "value already taken: {}", value),
+            ReservationError::NotHeld(value) => write!(f, "value not held by the owner: {}", value),
+            ReservationError::Store(e) => write!(f, "store error: {}", e),
+            ReservationError::Append(e) => write!(f, "append error: {}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for ReservationError<E> {}
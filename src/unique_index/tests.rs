@@ -0,0 +1,78 @@
+use super::*;
+
+use crate::clock::ManualClock;
+use crate::event_store::OnMemoryEventStore;
+
+fn index(
+    clock: &ManualClock,
+) -> UniqueIndexReservation<OnMemoryEventStore<ReservationEvent>, ManualClock> {
+    UniqueIndexReservation::new(
+        OnMemoryEventStore::new(),
+        clock.clone(),
+        "username",
+        Duration::from_secs(60),
+    )
+}
+
+#[test]
+fn test_reserve_confirm_release() {
+    let clock = ManualClock::default();
+    let mut index = index(&clock);
+
+    index.reserve("alice", "user-1").unwrap();
+    assert_eq!(
+        index.reserve("alice", "user-2"),
+        Err(ReservationError::Taken("alice".to_string()))
+    );
+    assert_eq!(
+        index.confirm("alice", "user-2"),
+        Err(ReservationError::NotHeld("alice".to_string()))
+    );
+    index.confirm("alice", "user-1").unwrap();
+    assert_eq!(index.owner("alice").unwrap(), Some("user-1".to_string()));
+    assert_eq!(
+        index.reserve("alice", "user-1"),
+        Err(ReservationError::Taken("alice".to_string()))
+    );
+
+    index.release("alice", "user-1").unwrap();
+    assert_eq!(index.state("alice").unwrap(), ReservationState::Free);
+    index.reserve("alice", "user-2").unwrap();
+
+    let events = index.store().load(&"username-alice".to_string()).unwrap();
+    let types = events
+        .iter()
+        .map(|e| e.event_type.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            "UniqueValueReserved",
+            "UniqueValueConfirmed",
+            "UniqueValueReleased",
+            "UniqueValueReserved"
+        ]
+    );
+}
+
+#[test]
+fn test_reservation_expires() {
+    let clock = ManualClock::default();
+    let mut index = index(&clock);
+
+    let expires_at = index.reserve("bob", "user-1").unwrap();
+    assert_eq!(expires_at, SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+    clock.advance(Duration::from_secs(30));
+    index.reserve("bob", "user-1").unwrap();
+    clock.advance(Duration::from_secs(45));
+    assert_eq!(index.owner("bob").unwrap(), Some("user-1".to_string()));
+
+    clock.advance(Duration::from_secs(15));
+    assert_eq!(index.state("bob").unwrap(), ReservationState::Free);
+    assert_eq!(
+        index.confirm("bob", "user-1"),
+        Err(ReservationError::NotHeld("bob".to_string()))
+    );
+    index.reserve("bob", "user-2").unwrap();
+    assert_eq!(index.owner("bob").unwrap(), Some("user-2".to_string()));
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::broker::Publisher;
+use crate::envelope::Envelope;
+
+/// Whether an event may leave its bounded context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// The event is only known to its bounded context.
+    Internal,
+    /// The event is part of the published language of its bounded context.
+    Public,
+}
+
+/// Types which represent events classified as internal or public.
+///
+/// Implement it by hand when the visibility depends on the event, or mark whole types with the
+/// [`public_events!`](crate::public_events) and [`internal_events!`](crate::internal_events)
+/// macros.
+pub trait EventVisibility {
+    /// Get the visibility of the event.
+    fn visibility(&self) -> Visibility;
+}
+
+/// Types whose events are all public, so that publishing them externally is checked at
+/// compile time.
+pub trait PublicEvent: EventVisibility {}
+
+impl<E: EventVisibility> EventVisibility for Envelope<E> {
+    fn visibility(&self) -> Visibility {
+        self.payload.visibility()
+    }
+}
+
+impl<E: PublicEvent> PublicEvent for Envelope<E> {}
+
+/// Mark the types as public events.
+#[macro_export]
+macro_rules! public_events {
+    ($($event:ty),+ $(,)?) => {
+        $(
+            impl $crate::visibility::EventVisibility for $event {
+                fn visibility(&self) -> $crate::visibility::Visibility {
+                    $crate::visibility::Visibility::Public
+                }
+            }
+
+            impl $crate::visibility::PublicEvent for $event {}
+        )+
+    };
+}
+
+/// Mark the types as internal events.
+#[macro_export]
+macro_rules! internal_events {
+    ($($event:ty),+ $(,)?) => {
+        $(
+            impl $crate::visibility::EventVisibility for $event {
+                fn visibility(&self) -> $crate::visibility::Visibility {
+                    $crate::visibility::Visibility::Internal
+                }
+            }
+        )+
+    };
+}
+
+/// Publisher to the outside of the bounded context, refusing to publish internal events.
+pub struct ExternalPublisher<P> {
+    publisher: P,
+}
+
+impl<P> ExternalPublisher<P> {
+    /// Wrap the publisher.
+    pub fn new(publisher: P) -> Self {
+        Self { publisher }
+    }
+
+    /// Get the wrapped publisher.
+    pub fn inner(&self) -> &P {
+        &self.publisher
+    }
+
+    /// Publish an event of a type whose events are all public.
+    pub fn publish_public<M: PublicEvent>(&mut self, message: &M) -> Result<(), P::Error>
+    where
+        P: Publisher<M>,
+    {
+        self.publisher.publish(message)
+    }
+}
+
+impl<M: EventVisibility, P: Publisher<M>> Publisher<M> for ExternalPublisher<P> {
+    type Error = VisibilityError<P::Error>;
+
+    fn publish(&mut self, message: &M) -> Result<(), Self::Error> {
+        if message.visibility() == Visibility::Internal {
+            return Err(VisibilityError::Internal);
+        }
+        self.publisher
+            .publish(message)
+            .map_err(VisibilityError::Inner)
+    }
+}
+
+/// Error returned by the [`ExternalPublisher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VisibilityError<E> {
+    /// The event is internal to the bounded context.
+    Internal,
+    /// The wrapped publisher failed.
+    Inner(E),
+}
+
+impl<E: Error> fmt::Display for VisibilityError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisibilityError::Internal => write!(f, "internal events cannot be published"),
+            VisibilityError::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error> Error for VisibilityError<E> {}
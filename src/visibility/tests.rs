@@ -0,0 +1,58 @@
+use super::*;
+use crate::broker::{OnMemoryBroker, PublisherExt};
+
+#[derive(Debug, Clone, PartialEq)]
+struct OrderPlaced(u32);
+
+#[derive(Debug, Clone, PartialEq)]
+struct StockReserved(u32);
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderEvent {
+    Placed(u32),
+    PriceRecomputed(u32),
+}
+
+crate::public_events!(OrderPlaced);
+crate::internal_events!(StockReserved);
+
+impl EventVisibility for OrderEvent {
+    fn visibility(&self) -> Visibility {
+        match self {
+            OrderEvent::Placed(_) => Visibility::Public,
+            OrderEvent::PriceRecomputed(_) => Visibility::Internal,
+        }
+    }
+}
+
+#[test]
+fn test_internal_events_are_refused() {
+    let mut publisher = OnMemoryBroker::new().external();
+    publisher.publish(&OrderEvent::Placed(1)).unwrap();
+    assert_eq!(
+        publisher.publish(&OrderEvent::PriceRecomputed(1)),
+        Err(VisibilityError::Internal)
+    );
+    assert_eq!(publisher.inner().len(), 1);
+
+    let mut publisher = OnMemoryBroker::new().external();
+    assert_eq!(
+        publisher.publish(&Envelope::new("stock-1", 1, "Reserved", StockReserved(1))),
+        Err(VisibilityError::Internal)
+    );
+    assert!(publisher.inner().is_empty());
+}
+
+#[test]
+fn test_publish_public() {
+    let mut publisher = ExternalPublisher::new(OnMemoryBroker::new());
+    publisher.publish_public(&OrderPlaced(1)).unwrap();
+    assert_eq!(publisher.inner().len(), 1);
+
+    let mut publisher = ExternalPublisher::new(OnMemoryBroker::new());
+    publisher
+        .publish_public(&Envelope::new("order-1", 1, "Placed", OrderPlaced(2)))
+        .unwrap();
+    assert_eq!(OrderPlaced(1).visibility(), Visibility::Public);
+    assert_eq!(StockReserved(1).visibility(), Visibility::Internal);
+}
@@ -0,0 +1,357 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+use crate::envelope::Envelope;
+use crate::event_store::{
+    AppendError, EventLoader, EventStore, ExpectedVersion, ReadOnlyEventStore,
+};
+
+/// CQL schema of the tables behind a [`WideColumnSession`] on Cassandra or ScyllaDB.
+///
+/// Events are partitioned by stream and clustered by version, so that a stream is read from a
+/// single partition. The global log is a separate table, its head a single row updated with
+/// lightweight transactions.
+pub const CQL_SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS events (
+    stream_id text, version bigint, position bigint, id uuid, event_type text,
+    payload blob, metadata map<text, text>,
+    PRIMARY KEY ((stream_id), version)
+) WITH CLUSTERING ORDER BY (version ASC);
+CREATE TABLE IF NOT EXISTS global_log (
+    bucket bigint, position bigint, stream_id text, version bigint,
+    PRIMARY KEY ((bucket), position)
+);
+CREATE TABLE IF NOT EXISTS global_log_head (id int PRIMARY KEY, position bigint);
+";
+
+/// An entry of the global log, pointing to an event of a stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Position of the event in the global log.
+    pub position: u64,
+    /// ID of the stream of the event.
+    pub stream_id: String,
+    /// Version of the event in its stream.
+    pub version: u64,
+}
+
+type Partition = Vec<Envelope<Vec<u8>>>;
+
+/// Types which represent a session on a wide-column database, running the queries of the
+/// [`WideColumnEventStore`] against the tables of [`CQL_SCHEMA`].
+pub trait WideColumnSession {
+    /// Associated Type representing the error type.
+    type Error: Error;
+
+    /// Insert the event in its stream partition unless its version exists, as a lightweight
+    /// transaction (`INSERT ... IF NOT EXISTS`). Returns whether the event was inserted.
+    fn insert_event_if_absent(&mut self, event: &Envelope<Vec<u8>>) -> Result<bool, Self::Error>;
+    /// Read the events of the stream partition, in version order.
+    fn stream_events(&self, stream_id: &str) -> Result<Vec<Envelope<Vec<u8>>>, Self::Error>;
+    /// Read the events of several stream partitions, e.g. with `WHERE stream_id IN (...)`.
+    fn streams_events(&self, stream_ids: &[String]) -> Result<Vec<Partition>, Self::Error> {
+        stream_ids.iter().map(|id| self.stream_events(id)).collect()
+    }
+    /// Get the next free position of the global log.
+    fn log_head(&self) -> Result<u64, Self::Error>;
+    /// Move the head of the global log if it is at `expected`, as a lightweight transaction
+    /// (`UPDATE ... IF position = ?`). Returns whether the head moved.
+    fn advance_log_head(&mut self, expected: u64, new: u64) -> Result<bool, Self::Error>;
+    /// Insert or replace the entry in the global log.
+    fn insert_log_entry(&mut self, entry: &LogEntry) -> Result<(), Self::Error>;
+    /// Read the entries of the global log from the position, in order.
+    fn log_entries(&self, from: u64, limit: usize) -> Result<Vec<LogEntry>, Self::Error>;
+}
+
+/// Event store on a wide-column database such as Cassandra or ScyllaDB, for very high write
+/// throughput.
+///
+/// Appends to different streams only contend on the head of the global log. Concurrent appends
+/// to the same stream are detected by the lightweight transaction inserting the event.
+///
+/// A position is logged before its event is inserted, and [`read_all`](Self::read_all) stops at
+/// the first position not logged yet or whose event is not inserted yet, so that readers never
+/// move past an event still being appended. A writer dying in between leaves a gap at which
+/// readers wait, to be closed with [`close_gap`](Self::close_gap).
+pub struct WideColumnEventStore<C> {
+    session: C,
+}
+
+impl<C: WideColumnSession> WideColumnEventStore<C> {
+    /// Create a store over the session.
+    pub fn new(session: C) -> Self {
+        Self { session }
+    }
+
+    /// Get the session.
+    pub fn session(&self) -> &C {
+        &self.session
+    }
+
+    /// Close a gap of the global log left by a writer which died before logging or inserting
+    /// its event, so that readers move past it. Must only be called once the writer is known to
+    /// be gone, as its event would be skipped by readers.
+    pub fn close_gap(&mut self, position: u64) -> Result<(), WideColumnError<C::Error>> {
+        self.session
+            .insert_log_entry(&LogEntry {
+                position,
+                stream_id: String::new(),
+                version: 0,
+            })
+            .map_err(WideColumnError::Session)
+    }
+
+    fn reserve_position(&mut self) -> Result<u64, WideColumnError<C::Error>> {
+        loop {
+            let head = self.session.log_head().map_err(WideColumnError::Session)?;
+            if self
+                .session
+                .advance_log_head(head, head + 1)
+                .map_err(WideColumnError::Session)?
+            {
+                return Ok(head);
+            }
+        }
+    }
+}
+
+impl<C: WideColumnSession> EventStore for WideColumnEventStore<C> {
+    type Persistable = Envelope<Vec<u8>>;
+    type Error = WideColumnError<C::Error>;
+
+    fn save(&mut self, events: &[Self::Persistable]) -> Result<(), Self::Error> {
+        let mut versions = HashMap::new();
+        for event in events {
+            let version = match versions.get(&event.stream_id) {
+                Some(version) => *version,
+                None => self
+                    .session
+                    .stream_events(&event.stream_id)
+                    .map_err(WideColumnError::Session)?
+                    .last()
+                    .map_or(0, |e| e.version),
+            } + 1;
+            let mut event = event.clone();
+            event.version = version;
+            event.position = self.reserve_position()?;
+            self.session
+                .insert_log_entry(&LogEntry {
+                    position: event.position,
+                    stream_id: event.stream_id.clone(),
+                    version,
+                })
+                .map_err(WideColumnError::Session)?;
+            // Readers skip the logged position when another writer wins the version.
+            if !self
+                .session
+                .insert_event_if_absent(&event)
+                .map_err(WideColumnError::Session)?
+            {
+                return Err(WideColumnError::Conflict {
+                    stream_id: event.stream_id,
+                    version,
+                });
+            }
+            versions.insert(event.stream_id, version);
+        }
+        Ok(())
+    }
+
+    /// Append to a single stream at its expected version. The events of several streams live
+    /// in different partitions, which cannot be written atomically, so appending to several
+    /// streams returns [`AppendError::Unsupported`].
+    fn append_multi(
+        &mut self,
+        appends: &[(String, ExpectedVersion, Vec<Self::Persistable>)],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let (stream_id, expected, events) = match appends {
+            [] => return Ok(()),
+            [append] => append,
+            _ => return Err(AppendError::Unsupported),
+        };
+        let actual = self
+            .session
+            .stream_events(stream_id)
+            .map_err(|e| AppendError::Store(WideColumnError::Session(e)))?
+            .last()
+            .map_or(0, |e| e.version);
+        if !expected.matches(actual) {
+            return Err(AppendError::WrongExpectedVersion {
+                stream_id: stream_id.clone(),
+                expected: *expected,
+                actual,
+            });
+        }
+        let events = events
+            .iter()
+            .map(|e| Envelope {
+                stream_id: stream_id.clone(),
+                ..e.clone()
+            })
+            .collect::<Vec<_>>();
+        // A concurrent append makes the lightweight transaction of the first event fail.
+        match self.save(&events) {
+            Err(WideColumnError::Conflict { version, .. }) if version == actual + 1 => {
+                Err(AppendError::WrongExpectedVersion {
+                    stream_id: stream_id.clone(),
+                    expected: *expected,
+                    actual: version,
+                })
+            }
+            result => result.map_err(AppendError::Store),
+        }
+    }
+}
+
+impl<C: WideColumnSession> EventLoader for WideColumnEventStore<C> {
+    type StreamId = String;
+    type Persistable = Envelope<Vec<u8>>;
+    type Error = WideColumnError<C::Error>;
+
+    fn load(&self, stream_id: &String) -> Result<Vec<Self::Persistable>, Self::Error> {
+        self.session
+            .stream_events(stream_id)
+            .map_err(WideColumnError::Session)
+    }
+
+    fn read_multi(
+        &self,
+        stream_ids: &[String],
+    ) -> Result<Vec<Vec<Self::Persistable>>, Self::Error> {
+        self.session
+            .streams_events(stream_ids)
+            .map_err(WideColumnError::Session)
+    }
+}
+
+impl<C: WideColumnSession> ReadOnlyEventStore for WideColumnEventStore<C> {
+    /// Read the global log up to the first position not committed yet. Positions whose version
+    /// was won by another writer, and closed gaps, are skipped.
+    fn read_all(&self, from: u64, limit: usize) -> Result<Vec<Self::Persistable>, Self::Error> {
+        let mut streams: HashMap<String, Vec<Envelope<Vec<u8>>>> = HashMap::new();
+        let mut events = Vec::new();
+        let mut next = from;
+        while events.len() < limit {
+            let entries = self
+                .session
+                .log_entries(next, limit - events.len())
+                .map_err(WideColumnError::Session)?;
+            if entries.is_empty() {
+                break;
+            }
+            for entry in entries {
+                if entry.position != next {
+                    return Ok(events);
+                }
+                next += 1;
+                if entry.version == 0 {
+                    continue;
+                }
+                if !streams.contains_key(&entry.stream_id) {
+                    let stream = self.load(&entry.stream_id)?;
+                    streams.insert(entry.stream_id.clone(), stream);
+                }
+                match streams[&entry.stream_id]
+                    .iter()
+                    .find(|e| e.version == entry.version)
+                {
+                    Some(event) if event.position == entry.position => events.push(event.clone()),
+                    Some(_) => {}
+                    None => return Ok(events),
+                }
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Wide-column session keeping the tables in memory, for tests.
+#[derive(Debug, Default)]
+pub struct OnMemoryWideColumnSession {
+    events: HashMap<String, BTreeMap<u64, Envelope<Vec<u8>>>>,
+    log: BTreeMap<u64, LogEntry>,
+    head: u64,
+}
+
+impl OnMemoryWideColumnSession {
+    /// Create empty tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl WideColumnSession for OnMemoryWideColumnSession {
+    type Error = Infallible;
+
+    fn insert_event_if_absent(&mut self, event: &Envelope<Vec<u8>>) -> Result<bool, Self::Error> {
+        let partition = self.events.entry(event.stream_id.clone()).or_default();
+        if partition.contains_key(&event.version) {
+            return Ok(false);
+        }
+        partition.insert(event.version, event.clone());
+        Ok(true)
+    }
+
+    fn stream_events(&self, stream_id: &str) -> Result<Vec<Envelope<Vec<u8>>>, Self::Error> {
+        Ok(self
+            .events
+            .get(stream_id)
+            .map(|partition| partition.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn log_head(&self) -> Result<u64, Self::Error> {
+        Ok(self.head)
+    }
+
+    fn advance_log_head(&mut self, expected: u64, new: u64) -> Result<bool, Self::Error> {
+        if self.head != expected {
+            return Ok(false);
+        }
+        self.head = new;
+        Ok(true)
+    }
+
+    fn insert_log_entry(&mut self, entry: &LogEntry) -> Result<(), Self::Error> {
+        self.log.insert(entry.position, entry.clone());
+        Ok(())
+    }
+
+    fn log_entries(&self, from: u64, limit: usize) -> Result<Vec<LogEntry>, Self::Error> {
+        Ok(self
+            .log
+            .range(from..)
+            .take(limit)
+            .map(|(_, e)| e.clone())
+            .collect())
+    }
+}
+
+/// Error returned by the [`WideColumnEventStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WideColumnError<E> {
+    /// The session failed.
+    Session(E),
+    /// Another writer appended the version of the stream concurrently.
+    Conflict { stream_id: String, version: u64 },
+}
+
+impl<E: Error> fmt::Display for WideColumnError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WideColumnError::Session(e) => write!(f, "session error: {}", e),
+            WideColumnError::Conflict { stream_id, version } => write!(
+                f,
+                "version {} of stream {} was appended concurrently",
+                version, stream_id
+            ),
+        }
+    }
+}
+
+impl<E: Error> Error for WideColumnError<E> {}
@@ -0,0 +1,160 @@
+use super::*;
+
+fn event(stream_id: &str, payload: &[u8]) -> Envelope<Vec<u8>> {
+    Envelope::new(stream_id, 0, "Happened", payload.to_vec())
+}
+
+#[test]
+fn test_append_and_read() {
+    let mut store = WideColumnEventStore::new(OnMemoryWideColumnSession::new());
+    store
+        .save(&[event("a-1", b"1"), event("b-1", b"2"), event("a-1", b"3")])
+        .unwrap();
+    store.save(&[event("a-1", b"4")]).unwrap();
+
+    let a = store.load(&"a-1".to_string()).unwrap();
+    assert_eq!(
+        a.iter()
+            .map(|e| (e.version, e.position, e.payload.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, 0, b"1".to_vec()),
+            (2, 2, b"3".to_vec()),
+            (3, 3, b"4".to_vec())
+        ]
+    );
+
+    let all = store.read_all(1, 2).unwrap();
+    assert_eq!(
+        all.iter()
+            .map(|e| (e.stream_id.as_str(), e.version))
+            .collect::<Vec<_>>(),
+        vec![("b-1", 1), ("a-1", 2)]
+    );
+}
+
+/// Session where another writer always wins the version of a stream.
+struct RacingSession(OnMemoryWideColumnSession);
+
+impl WideColumnSession for RacingSession {
+    type Error = Infallible;
+
+    fn insert_event_if_absent(&mut self, event: &Envelope<Vec<u8>>) -> Result<bool, Infallible> {
+        let mut other = event.clone();
+        other.payload = b"other".to_vec();
+        other.position += 100;
+        self.0.insert_event_if_absent(&other)?;
+        self.0.insert_event_if_absent(event)
+    }
+
+    fn stream_events(&self, stream_id: &str) -> Result<Vec<Envelope<Vec<u8>>>, Infallible> {
+        self.0.stream_events(stream_id)
+    }
+
+    fn log_head(&self) -> Result<u64, Infallible> {
+        self.0.log_head()
+    }
+
+    fn advance_log_head(&mut self, expected: u64, new: u64) -> Result<bool, Infallible> {
+        self.0.advance_log_head(expected, new)
+    }
+
+    fn insert_log_entry(&mut self, entry: &LogEntry) -> Result<(), Infallible> {
+        self.0.insert_log_entry(entry)
+    }
+
+    fn log_entries(&self, from: u64, limit: usize) -> Result<Vec<LogEntry>, Infallible> {
+        self.0.log_entries(from, limit)
+    }
+}
+
+#[test]
+fn test_concurrent_append_conflicts() {
+    let mut store = WideColumnEventStore::new(RacingSession(OnMemoryWideColumnSession::new()));
+    assert_eq!(
+        store.save(&[event("a-1", b"1")]),
+        Err(WideColumnError::Conflict {
+            stream_id: "a-1".to_string(),
+            version: 1
+        })
+    );
+    // The logged position is skipped by readers, as its version belongs to the other writer.
+    assert!(store.read_all(0, 10).unwrap().is_empty());
+    assert_eq!(
+        store.append_multi(&[(
+            "a-2".to_string(),
+            ExpectedVersion::NoStream,
+            vec![event("a-2", b"2")]
+        )]),
+        Err(AppendError::WrongExpectedVersion {
+            stream_id: "a-2".to_string(),
+            expected: ExpectedVersion::NoStream,
+            actual: 1
+        })
+    );
+}
+
+#[test]
+fn test_read_only_committed_positions() {
+    let mut session = OnMemoryWideColumnSession::new();
+    // A writer reserved and logged position 0, but did not insert its event yet.
+    session.advance_log_head(0, 1).unwrap();
+    session
+        .insert_log_entry(&LogEntry {
+            position: 0,
+            stream_id: "a-1".to_string(),
+            version: 1,
+        })
+        .unwrap();
+    let mut store = WideColumnEventStore::new(session);
+    store.save(&[event("b-1", b"1")]).unwrap();
+    assert!(store.read_all(0, 10).unwrap().is_empty());
+
+    store.close_gap(0).unwrap();
+    let all = store.read_all(0, 10).unwrap();
+    assert_eq!(
+        all.iter()
+            .map(|e| (e.stream_id.as_str(), e.position))
+            .collect::<Vec<_>>(),
+        vec![("b-1", 1)]
+    );
+}
+
+#[test]
+fn test_append_multi() {
+    let mut store = WideColumnEventStore::new(OnMemoryWideColumnSession::new());
+    let append = |stream_id: &str, expected| {
+        (
+            stream_id.to_string(),
+            expected,
+            vec![event(stream_id, b"1")],
+        )
+    };
+    store
+        .append_multi(&[append("a-1", ExpectedVersion::NoStream)])
+        .unwrap();
+    assert_eq!(
+        store.append_multi(&[append("a-1", ExpectedVersion::NoStream)]),
+        Err(AppendError::WrongExpectedVersion {
+            stream_id: "a-1".to_string(),
+            expected: ExpectedVersion::NoStream,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        store.append_multi(&[
+            append("a-1", ExpectedVersion::Exact(1)),
+            append("b-1", ExpectedVersion::NoStream)
+        ]),
+        Err(AppendError::Unsupported)
+    );
+    assert_eq!(
+        store
+            .read_multi(&["a-1".to_string(), "b-1".to_string()])
+            .unwrap()
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>(),
+        vec![1, 0]
+    );
+}
@@ -0,0 +1,731 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+type CommandFn<S, C> = Box<dyn Fn(&S) -> C>;
+type PredicateFn<E> = Box<dyn Fn(&E) -> bool>;
+type RecordFn<S, E> = Box<dyn Fn(&mut S, &E)>;
+
+/// How a parallel step waits for its branches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Join {
+    /// Complete once every branch succeeded, fail as soon as one fails.
+    All,
+    /// Complete as soon as one branch succeeds, fail once every branch failed.
+    Any,
+}
+
+/// A step of a workflow: a command to dispatch and the events telling its outcome.
+///
+/// A parallel step instead dispatches the commands of its branches at once, and waits for
+/// their outcomes as set by its [`Join`].
+pub struct Step<S, C, E> {
+    name: &'static str,
+    command: Option<CommandFn<S, C>>,
+    succeeds_on: PredicateFn<E>,
+    fails_on: PredicateFn<E>,
+    record: Option<RecordFn<S, E>>,
+    compensation: Option<CommandFn<S, C>>,
+    timeout: Option<Duration>,
+    branches: Vec<Step<S, C, E>>,
+    join: Join,
+}
+
+impl<S, C, E> Step<S, C, E> {
+    /// Create a step dispatching the command built from the workflow data.
+    pub fn new(name: &'static str, command: impl Fn(&S) -> C + 'static) -> Self {
+        Self {
+            command: Some(Box::new(command)),
+            ..Self::parallel(name, Join::All)
+        }
+    }
+
+    /// Create a step running its branches concurrently, joined as set. A step without branches
+    /// succeeds as soon as it starts.
+    pub fn parallel(name: &'static str, join: Join) -> Self {
+        Self {
+            name,
+            command: None,
+            succeeds_on: Box::new(|_| false),
+            fails_on: Box::new(|_| false),
+            record: None,
+            compensation: None,
+            timeout: None,
+            branches: Vec::new(),
+            join,
+        }
+    }
+
+    /// Add a branch to the parallel step. Succeeded branches are compensated when the step
+    /// or a later step fails, and branches succeeding once the step already succeeded or
+    /// failed are compensated at once.
+    pub fn branch(mut self, branch: Step<S, C, E>) -> Self {
+        self.branches.push(branch);
+        self
+    }
+
+    /// Set the predicate recognizing the event which completes the step.
+    pub fn succeeds_on(mut self, predicate: impl Fn(&E) -> bool + 'static) -> Self {
+        self.succeeds_on = Box::new(predicate);
+        self
+    }
+
+    /// Set the predicate recognizing the event which fails the step.
+    pub fn fails_on(mut self, predicate: impl Fn(&E) -> bool + 'static) -> Self {
+        self.fails_on = Box::new(predicate);
+        self
+    }
+
+    /// Record data from the success event into the workflow data, for use by the next steps.
+    pub fn record(mut self, record: impl Fn(&mut S, &E) + 'static) -> Self {
+        self.record = Some(Box::new(record));
+        self
+    }
+
+    /// Set the command undoing the step when a later step fails.
+    pub fn compensate_with(mut self, command: impl Fn(&S) -> C + 'static) -> Self {
+        self.compensation = Some(Box::new(command));
+        self
+    }
+
+    /// Fail the step if it does not complete within the duration.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Get the name of the step.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Event recording the progress of a workflow instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowEvent {
+    /// The step's command was dispatched.
+    StepStarted { step: usize, at: SystemTime },
+    /// The step completed.
+    StepSucceeded { step: usize },
+    /// The step failed, or timed out.
+    StepFailed { step: usize, timed_out: bool },
+    /// The compensation of the step was dispatched.
+    StepCompensated { step: usize },
+    /// The branch of the parallel step completed.
+    BranchSucceeded { step: usize, branch: usize },
+    /// The branch of the parallel step failed.
+    BranchFailed { step: usize, branch: usize },
+    /// The compensation of the branch of the parallel step was dispatched.
+    BranchCompensated { step: usize, branch: usize },
+    /// All the steps completed.
+    Completed,
+    /// All the compensations of a failed workflow were dispatched.
+    Compensated,
+    /// An operator dispatched the command of the running step again.
+    StepRetried { step: usize, at: SystemTime },
+    /// An operator completed the instance without waiting for the running step.
+    ForceCompleted { step: usize },
+    /// An operator aborted the instance at the running step, compensating the previous steps.
+    Aborted { step: usize },
+}
+
+/// State of a workflow instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowState {
+    /// The instance was created and no step has started.
+    Created,
+    /// The step is waiting for its outcome.
+    Running { step: usize, since: SystemTime },
+    /// A step failed and the previous steps are being compensated.
+    Compensating { failed_step: usize },
+    /// All the steps completed.
+    Completed,
+    /// The workflow failed and was compensated.
+    Compensated,
+}
+
+/// A running instance of a workflow, built from its events.
+#[derive(Debug, Clone)]
+pub struct WorkflowInstance<S> {
+    id: String,
+    data: S,
+    state: WorkflowState,
+    history: Vec<WorkflowEvent>,
+}
+
+impl<S> WorkflowInstance<S> {
+    /// Create an instance which has not started yet.
+    pub fn new(id: impl Into<String>, data: S) -> Self {
+        Self {
+            id: id.into(),
+            data,
+            state: WorkflowState::Created,
+            history: Vec::new(),
+        }
+    }
+
+    /// Get the ID of the instance.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get the workflow data.
+    pub fn data(&self) -> &S {
+        &self.data
+    }
+
+    /// Get the state of the instance.
+    pub fn state(&self) -> &WorkflowState {
+        &self.state
+    }
+
+    /// Get the events applied to the instance.
+    pub fn history(&self) -> &[WorkflowEvent] {
+        &self.history
+    }
+
+    /// Apply an event to the instance.
+    pub fn apply(&mut self, event: WorkflowEvent) {
+        self.state = match &event {
+            WorkflowEvent::StepStarted { step, at } => WorkflowState::Running {
+                step: *step,
+                since: *at,
+            },
+            WorkflowEvent::StepSucceeded { .. } => self.state.clone(),
+            WorkflowEvent::StepFailed { step, .. } => {
+                WorkflowState::Compensating { failed_step: *step }
+            }
+            WorkflowEvent::StepCompensated { .. }
+            | WorkflowEvent::BranchSucceeded { .. }
+            | WorkflowEvent::BranchFailed { .. }
+            | WorkflowEvent::BranchCompensated { .. } => self.state.clone(),
+            WorkflowEvent::Completed | WorkflowEvent::ForceCompleted { .. } => {
+                WorkflowState::Completed
+            }
+            WorkflowEvent::Compensated => WorkflowState::Compensated,
+            WorkflowEvent::StepRetried { step, at } => WorkflowState::Running {
+                step: *step,
+                since: *at,
+            },
+            WorkflowEvent::Aborted { step } => WorkflowState::Compensating { failed_step: *step },
+        };
+        self.history.push(event);
+    }
+
+    /// Get the events since the step last started, if it did.
+    fn since_started(&self, step: usize) -> Option<&[WorkflowEvent]> {
+        let started = self
+            .history
+            .iter()
+            .rposition(|e| matches!(e, WorkflowEvent::StepStarted { step: s, .. } if *s == step))?;
+        Some(&self.history[started..])
+    }
+
+    /// Check whether the step succeeded, failed or was aborted since it last started.
+    fn is_settled(&self, step: usize) -> bool {
+        self.since_started(step).is_some_and(|events| {
+            events.iter().any(|e| match e {
+                WorkflowEvent::StepSucceeded { step: s }
+                | WorkflowEvent::StepFailed { step: s, .. }
+                | WorkflowEvent::Aborted { step: s } => *s == step,
+                _ => false,
+            })
+        })
+    }
+
+    /// Check whether the branch of the step was compensated since the step last started.
+    fn is_branch_compensated(&self, step: usize, branch: usize) -> bool {
+        self.since_started(step).is_some_and(|events| {
+            events.contains(&WorkflowEvent::BranchCompensated { step, branch })
+        })
+    }
+
+    /// Get the branches of the step which succeeded and failed since it last started.
+    fn branch_outcomes(&self, step: usize) -> (Vec<usize>, Vec<usize>) {
+        let mut succeeded = Vec::new();
+        let mut failed = Vec::new();
+        for event in self.since_started(step).unwrap_or_default() {
+            match event {
+                WorkflowEvent::BranchSucceeded { step: s, branch } if *s == step => {
+                    succeeded.push(*branch)
+                }
+                WorkflowEvent::BranchFailed { step: s, branch } if *s == step => {
+                    failed.push(*branch)
+                }
+                _ => {}
+            }
+        }
+        (succeeded, failed)
+    }
+}
+
+/// A workflow definition: an ordered list of steps run as a saga.
+pub struct Workflow<S, C, E> {
+    name: &'static str,
+    steps: Vec<Step<S, C, E>>,
+}
+
+impl<S, C, E> Workflow<S, C, E> {
+    /// Create a workflow without steps.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Append a step to the workflow.
+    pub fn step(mut self, step: Step<S, C, E>) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Get the name of the workflow.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the steps of the workflow.
+    pub fn steps(&self) -> &[Step<S, C, E>] {
+        &self.steps
+    }
+
+    /// Start the instance, returning the commands to dispatch.
+    pub fn start(&self, instance: &mut WorkflowInstance<S>, now: SystemTime) -> Vec<C> {
+        if instance.state != WorkflowState::Created {
+            return Vec::new();
+        }
+        self.start_step(instance, 0, now)
+    }
+
+    /// Handle an event of the domain, returning the commands to dispatch.
+    pub fn handle(&self, instance: &mut WorkflowInstance<S>, event: &E, now: SystemTime) -> Vec<C> {
+        let mut commands = self.compensate_late_branches(instance, event);
+        if let WorkflowState::Running { step, .. } = instance.state {
+            commands.extend(self.handle_step(instance, step, event, now));
+        }
+        commands
+    }
+
+    fn handle_step(
+        &self,
+        instance: &mut WorkflowInstance<S>,
+        step: usize,
+        event: &E,
+        now: SystemTime,
+    ) -> Vec<C> {
+        let definition = &self.steps[step];
+        if definition.command.is_none() {
+            return self.handle_branches(instance, step, event, now);
+        }
+        if (definition.succeeds_on)(event) {
+            if let Some(record) = &definition.record {
+                record(&mut instance.data, event);
+            }
+            instance.apply(WorkflowEvent::StepSucceeded { step });
+            self.start_step(instance, step + 1, now)
+        } else if (definition.fails_on)(event) {
+            self.fail_step(instance, step, false)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Fail the running step if its timeout has elapsed, returning the commands to dispatch.
+    pub fn tick(&self, instance: &mut WorkflowInstance<S>, now: SystemTime) -> Vec<C> {
+        let (step, since) = match instance.state {
+            WorkflowState::Running { step, since } => (step, since),
+            _ => return Vec::new(),
+        };
+        match self.steps[step].timeout {
+            Some(timeout) if since.checked_add(timeout).is_some_and(|at| at <= now) => {
+                self.fail_step(instance, step, true)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the time the running step times out at, if it has a timeout which does not
+    /// overflow the time.
+    pub fn deadline(&self, instance: &WorkflowInstance<S>) -> Option<SystemTime> {
+        match instance.state {
+            WorkflowState::Running { step, since } => {
+                self.steps[step].timeout.and_then(|t| since.checked_add(t))
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_branches(
+        &self,
+        instance: &mut WorkflowInstance<S>,
+        step: usize,
+        event: &E,
+        now: SystemTime,
+    ) -> Vec<C> {
+        let definition = &self.steps[step];
+        let (succeeded, failed) = instance.branch_outcomes(step);
+        let pending = (0..definition.branches.len())
+            .filter(|b| !succeeded.contains(b) && !failed.contains(b));
+        for branch in pending {
+            let branch_definition = &definition.branches[branch];
+            if (branch_definition.succeeds_on)(event) {
+                if let Some(record) = &branch_definition.record {
+                    record(&mut instance.data, event);
+                }
+                instance.apply(WorkflowEvent::BranchSucceeded { step, branch });
+                if definition.join == Join::Any || succeeded.len() + 1 == definition.branches.len()
+                {
+                    instance.apply(WorkflowEvent::StepSucceeded { step });
+                    return self.start_step(instance, step + 1, now);
+                }
+                return Vec::new();
+            } else if (branch_definition.fails_on)(event) {
+                instance.apply(WorkflowEvent::BranchFailed { step, branch });
+                if definition.join == Join::All || failed.len() + 1 == definition.branches.len() {
+                    return self.fail_step(instance, step, false);
+                }
+                return Vec::new();
+            }
+        }
+        Vec::new()
+    }
+
+    /// Compensate the branches succeeding after their parallel step succeeded or failed, such
+    /// as the slower branches of a step joining any, since nothing waits for them anymore.
+    fn compensate_late_branches(&self, instance: &mut WorkflowInstance<S>, event: &E) -> Vec<C> {
+        let mut commands = Vec::new();
+        for (step, definition) in self.steps.iter().enumerate() {
+            if definition.command.is_some() || !instance.is_settled(step) {
+                continue;
+            }
+            let (succeeded, failed) = instance.branch_outcomes(step);
+            for (branch, branch_definition) in definition.branches.iter().enumerate() {
+                if succeeded.contains(&branch)
+                    || failed.contains(&branch)
+                    || !(branch_definition.succeeds_on)(event)
+                {
+                    continue;
+                }
+                instance.apply(WorkflowEvent::BranchSucceeded { step, branch });
+                if let Some(compensation) = &branch_definition.compensation {
+                    commands.push(compensation(&instance.data));
+                    instance.apply(WorkflowEvent::BranchCompensated { step, branch });
+                }
+            }
+        }
+        commands
+    }
+
+    fn start_step(
+        &self,
+        instance: &mut WorkflowInstance<S>,
+        step: usize,
+        now: SystemTime,
+    ) -> Vec<C> {
+        match self.steps.get(step) {
+            Some(definition) => {
+                instance.apply(WorkflowEvent::StepStarted { step, at: now });
+                if definition.command.is_none() && definition.branches.is_empty() {
+                    instance.apply(WorkflowEvent::StepSucceeded { step });
+                    return self.start_step(instance, step + 1, now);
+                }
+                match &definition.command {
+                    Some(command) => vec![command(&instance.data)],
+                    None => definition
+                        .branches
+                        .iter()
+                        .filter_map(|b| b.command.as_ref())
+                        .map(|command| command(&instance.data))
+                        .collect(),
+                }
+            }
+            None => {
+                instance.apply(WorkflowEvent::Completed);
+                Vec::new()
+            }
+        }
+    }
+
+    fn fail_step(
+        &self,
+        instance: &mut WorkflowInstance<S>,
+        step: usize,
+        timed_out: bool,
+    ) -> Vec<C> {
+        instance.apply(WorkflowEvent::StepFailed { step, timed_out });
+        self.compensate(instance, step)
+    }
+
+    /// Compensate the failed step's succeeded branches, then the previous steps.
+    fn compensate(&self, instance: &mut WorkflowInstance<S>, step: usize) -> Vec<C> {
+        let mut commands = Vec::new();
+        self.compensate_branches(instance, step, &mut commands);
+        for compensated in (0..step).rev() {
+            if let Some(compensation) = &self.steps[compensated].compensation {
+                commands.push(compensation(&instance.data));
+                instance.apply(WorkflowEvent::StepCompensated { step: compensated });
+            }
+            self.compensate_branches(instance, compensated, &mut commands);
+        }
+        instance.apply(WorkflowEvent::Compensated);
+        commands
+    }
+
+    fn compensate_branches(
+        &self,
+        instance: &mut WorkflowInstance<S>,
+        step: usize,
+        commands: &mut Vec<C>,
+    ) {
+        let (succeeded, _) = instance.branch_outcomes(step);
+        for branch in succeeded.into_iter().rev() {
+            if instance.is_branch_compensated(step, branch) {
+                continue;
+            }
+            if let Some(compensation) = &self.steps[step].branches[branch].compensation {
+                commands.push(compensation(&instance.data));
+                instance.apply(WorkflowEvent::BranchCompensated { step, branch });
+            }
+        }
+    }
+}
+
+/// Declare a [`Workflow`] from its steps, in order.
+///
+/// Each step starts with its command, followed by any of the builder methods of [`Step`]
+/// setting its outcome events, data recorded, compensation and timeout. Parallel steps list
+/// their branches, declared as steps:
+///
+/// ```ignore
+/// let workflow = workflow! {
+///     "fulfillment";
+///     parallel "prepare" (Join::All) {
+///         step "stock" {
+///             command: |_: &Order| Command::ReserveStock,
+///             succeeds_on: |e| matches!(e, Event::StockReserved),
+///             compensate_with: |_| Command::ReleaseStock,
+///         }
+///         step "payment" {
+///             command: |_: &Order| Command::ChargePayment,
+///             succeeds_on: |e| matches!(e, Event::PaymentCharged),
+///             fails_on: |e| matches!(e, Event::PaymentDeclined),
+///         }
+///     }
+///     step "ship" {
+///         command: |_: &Order| Command::Ship,
+///         succeeds_on: |e| matches!(e, Event::Shipped),
+///         timeout: Duration::from_secs(60),
+///     }
+/// };
+/// ```
+#[macro_export]
+macro_rules! workflow {
+    (@steps $workflow:expr;) => {
+        $workflow
+    };
+    (@steps $workflow:expr; step $step:literal { $($body:tt)* } $($rest:tt)*) => {
+        $crate::workflow!(
+            @steps $workflow.step($crate::workflow!(@step $step { $($body)* }));
+            $($rest)*
+        )
+    };
+    (@steps $workflow:expr; parallel $step:literal ($join:expr) { $($branches:tt)* } $($rest:tt)*) => {
+        $crate::workflow!(
+            @steps $workflow.step($crate::workflow!(
+                @branches $crate::workflow::Step::parallel($step, $join); $($branches)*
+            ));
+            $($rest)*
+        )
+    };
+    (@branches $parallel:expr;) => {
+        $parallel
+    };
+    (@branches $parallel:expr; step $step:literal { $($body:tt)* } $($rest:tt)*) => {
+        $crate::workflow!(
+            @branches $parallel.branch($crate::workflow!(@step $step { $($body)* }));
+            $($rest)*
+        )
+    };
+    (@step $step:literal { command: $command:expr $(, $option:ident: $value:expr)* $(,)? }) => {
+        $crate::workflow::Step::new($step, $command)$(.$option($value))*
+    };
+    ($name:expr; $($steps:tt)*) => {
+        $crate::workflow!(@steps $crate::workflow::Workflow::new($name); $($steps)*)
+    };
+}
+
+/// Instances of a workflow, driven by the events of the domain, with the escape hatches
+/// operators need when an instance gets stuck.
+///
+/// Every intervention is recorded in the history of the instance.
+pub struct WorkflowManager<S, C, E> {
+    workflow: Workflow<S, C, E>,
+    instances: BTreeMap<String, WorkflowInstance<S>>,
+}
+
+impl<S, C, E> WorkflowManager<S, C, E> {
+    /// Create a manager of the workflow without instances.
+    pub fn new(workflow: Workflow<S, C, E>) -> Self {
+        Self {
+            workflow,
+            instances: BTreeMap::new(),
+        }
+    }
+
+    /// Get the workflow.
+    pub fn workflow(&self) -> &Workflow<S, C, E> {
+        &self.workflow
+    }
+
+    /// Start a new instance, returning the commands to dispatch. An instance with the same ID
+    /// is replaced.
+    pub fn start(&mut self, id: impl Into<String>, data: S, now: SystemTime) -> Vec<C> {
+        let mut instance = WorkflowInstance::new(id, data);
+        let commands = self.workflow.start(&mut instance, now);
+        self.instances.insert(instance.id.clone(), instance);
+        commands
+    }
+
+    /// Add an instance rebuilt from its history.
+    pub fn restore(&mut self, instance: WorkflowInstance<S>) {
+        self.instances.insert(instance.id.clone(), instance);
+    }
+
+    /// Handle an event of the domain in the instance it belongs to, returning the commands to
+    /// dispatch. The caller correlates the event with the instance, e.g. from an ID it
+    /// carries, since the predicates of the steps cannot tell instances apart.
+    pub fn handle(
+        &mut self,
+        id: &str,
+        event: &E,
+        now: SystemTime,
+    ) -> Result<Vec<C>, InterventionError> {
+        let instance = self
+            .instances
+            .get_mut(id)
+            .ok_or_else(|| InterventionError::NotFound(id.to_string()))?;
+        Ok(self.workflow.handle(instance, event, now))
+    }
+
+    /// Time out the running steps whose timeout elapsed, returning the commands to dispatch.
+    pub fn tick(&mut self, now: SystemTime) -> Vec<C> {
+        let workflow = &self.workflow;
+        self.instances
+            .values_mut()
+            .flat_map(|instance| workflow.tick(instance, now))
+            .collect()
+    }
+
+    /// Get the instance, to inspect its state, data and history.
+    pub fn get(&self, id: &str) -> Option<&WorkflowInstance<S>> {
+        self.instances.get(id)
+    }
+
+    /// List the instances waiting for the outcome of a step, by ID.
+    pub fn running(&self) -> Vec<&WorkflowInstance<S>> {
+        self.instances
+            .values()
+            .filter(|instance| matches!(instance.state, WorkflowState::Running { .. }))
+            .collect()
+    }
+
+    /// List the instances whose running step started at least `after` before `now`, by ID.
+    /// Steps without a timeout never fail on their own, so these are left to operators.
+    pub fn stuck(&self, now: SystemTime, after: Duration) -> Vec<&WorkflowInstance<S>> {
+        self.instances
+            .values()
+            .filter(|instance| match instance.state {
+                WorkflowState::Running { since, .. } => {
+                    since.checked_add(after).is_some_and(|at| at <= now)
+                }
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Get the name of the step the instance is waiting for.
+    pub fn running_step(&self, id: &str) -> Option<&'static str> {
+        match self.instances.get(id)?.state {
+            WorkflowState::Running { step, .. } => Some(self.workflow.steps[step].name),
+            _ => None,
+        }
+    }
+
+    /// Complete the instance without waiting for its running step nor starting the next
+    /// ones, e.g. after fixing the outcome by hand. Nothing is compensated.
+    pub fn force_complete(&mut self, id: &str) -> Result<(), InterventionError> {
+        let (instance, step) = running_instance(&mut self.instances, id)?;
+        instance.apply(WorkflowEvent::ForceCompleted { step });
+        Ok(())
+    }
+
+    /// Dispatch the command of the running step again, restarting its timeout, e.g. when the
+    /// command was lost. Only the branches of a parallel step without an outcome are retried.
+    pub fn retry(&mut self, id: &str, now: SystemTime) -> Result<Vec<C>, InterventionError> {
+        let (instance, step) = running_instance(&mut self.instances, id)?;
+        instance.apply(WorkflowEvent::StepRetried { step, at: now });
+        let definition = &self.workflow.steps[step];
+        Ok(match &definition.command {
+            Some(command) => vec![command(&instance.data)],
+            None => {
+                let (succeeded, failed) = instance.branch_outcomes(step);
+                definition
+                    .branches
+                    .iter()
+                    .enumerate()
+                    .filter(|(b, _)| !succeeded.contains(b) && !failed.contains(b))
+                    .filter_map(|(_, branch)| branch.command.as_ref())
+                    .map(|command| command(&instance.data))
+                    .collect()
+            }
+        })
+    }
+
+    /// Abort the instance at its running step, returning the compensations to dispatch, as
+    /// if the step failed.
+    pub fn abort(&mut self, id: &str) -> Result<Vec<C>, InterventionError> {
+        let (instance, step) = running_instance(&mut self.instances, id)?;
+        instance.apply(WorkflowEvent::Aborted { step });
+        Ok(self.workflow.compensate(instance, step))
+    }
+}
+
+fn running_instance<'a, S>(
+    instances: &'a mut BTreeMap<String, WorkflowInstance<S>>,
+    id: &str,
+) -> Result<(&'a mut WorkflowInstance<S>, usize), InterventionError> {
+    let instance = instances
+        .get_mut(id)
+        .ok_or_else(|| InterventionError::NotFound(id.to_string()))?;
+    match instance.state {
+        WorkflowState::Running { step, .. } => Ok((instance, step)),
+        _ => Err(InterventionError::NotRunning(id.to_string())),
+    }
+}
+
+/// Error returned by the [`WorkflowManager`] when handling events and intervening.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterventionError {
+    /// No instance has the ID.
+    NotFound(String),
+    /// The instance is not waiting for a step, so there is nothing to intervene on.
+    NotRunning(String),
+}
+
+impl fmt::Display for InterventionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterventionError::NotFound(id) => write!(f, "workflow instance {} not found", id),
+            InterventionError::NotRunning(id) => {
+                write!(f, "workflow instance {} is not running", id)
+            }
+        }
+    }
+}
+
+impl Error for InterventionError {}
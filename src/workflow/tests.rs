@@ -0,0 +1,599 @@
+use super::*;
+
+#[derive(Debug, Clone, Default)]
+struct UserAddData {
+    name: String,
+    org_id: String,
+    user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    ReserveSeat(String),
+    ReleaseSeat(String),
+    CreateUser(String),
+    DeleteUser(String),
+    AddUser(String, String),
+}
+
+#[derive(Debug, Clone)]
+enum Event {
+    SeatReserved,
+    SeatUnavailable,
+    UserCreated(String),
+    UserAdded,
+    AddRejected,
+}
+
+fn user_add_workflow() -> Workflow<UserAddData, Command, Event> {
+    Workflow::new("user-add")
+        .step(
+            Step::new("reserve", |d: &UserAddData| {
+                Command::ReserveSeat(d.org_id.clone())
+            })
+            .succeeds_on(|e| matches!(e, Event::SeatReserved))
+            .fails_on(|e| matches!(e, Event::SeatUnavailable))
+            .compensate_with(|d| Command::ReleaseSeat(d.org_id.clone())),
+        )
+        .step(
+            Step::new("create", |d: &UserAddData| {
+                Command::CreateUser(d.name.clone())
+            })
+            .succeeds_on(|e| matches!(e, Event::UserCreated(_)))
+            .record(|d, e| {
+                if let Event::UserCreated(id) = e {
+                    d.user_id = Some(id.clone());
+                }
+            })
+            .compensate_with(|d| Command::DeleteUser(d.user_id.clone().unwrap())),
+        )
+        .step(
+            Step::new("add", |d: &UserAddData| {
+                Command::AddUser(d.org_id.clone(), d.user_id.clone().unwrap())
+            })
+            .succeeds_on(|e| matches!(e, Event::UserAdded))
+            .fails_on(|e| matches!(e, Event::AddRejected))
+            .timeout(Duration::from_secs(30)),
+        )
+}
+
+fn instance() -> WorkflowInstance<UserAddData> {
+    WorkflowInstance::new(
+        "user-add-1",
+        UserAddData {
+            name: "alice".to_string(),
+            org_id: "org-1".to_string(),
+            user_id: None,
+        },
+    )
+}
+
+#[test]
+fn test_workflow_completes() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = user_add_workflow();
+    let mut instance = instance();
+
+    assert_eq!(
+        workflow.start(&mut instance, now),
+        vec![Command::ReserveSeat("org-1".to_string())]
+    );
+    assert_eq!(
+        workflow.handle(&mut instance, &Event::SeatReserved, now),
+        vec![Command::CreateUser("alice".to_string())]
+    );
+    assert_eq!(
+        workflow.handle(
+            &mut instance,
+            &Event::UserCreated("user-1".to_string()),
+            now
+        ),
+        vec![Command::AddUser("org-1".to_string(), "user-1".to_string())]
+    );
+    assert!(workflow
+        .handle(&mut instance, &Event::UserAdded, now)
+        .is_empty());
+    assert_eq!(*instance.state(), WorkflowState::Completed);
+}
+
+#[test]
+fn test_workflow_compensates_on_failure() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = user_add_workflow();
+    let mut instance = instance();
+    workflow.start(&mut instance, now);
+    workflow.handle(&mut instance, &Event::SeatReserved, now);
+    workflow.handle(
+        &mut instance,
+        &Event::UserCreated("user-1".to_string()),
+        now,
+    );
+
+    assert_eq!(
+        workflow.handle(&mut instance, &Event::AddRejected, now),
+        vec![
+            Command::DeleteUser("user-1".to_string()),
+            Command::ReleaseSeat("org-1".to_string()),
+        ]
+    );
+    assert_eq!(*instance.state(), WorkflowState::Compensated);
+    assert_eq!(
+        instance.history()[instance.history().len() - 4..],
+        [
+            WorkflowEvent::StepFailed {
+                step: 2,
+                timed_out: false
+            },
+            WorkflowEvent::StepCompensated { step: 1 },
+            WorkflowEvent::StepCompensated { step: 0 },
+            WorkflowEvent::Compensated,
+        ]
+    );
+}
+
+#[test]
+fn test_workflow_times_out() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = user_add_workflow();
+    let mut instance = instance();
+    workflow.start(&mut instance, now);
+    workflow.handle(&mut instance, &Event::SeatReserved, now);
+    workflow.handle(
+        &mut instance,
+        &Event::UserCreated("user-1".to_string()),
+        now,
+    );
+
+    assert!(workflow
+        .tick(&mut instance, now + Duration::from_secs(29))
+        .is_empty());
+    assert_eq!(
+        workflow.tick(&mut instance, now + Duration::from_secs(30)),
+        vec![
+            Command::DeleteUser("user-1".to_string()),
+            Command::ReleaseSeat("org-1".to_string()),
+        ]
+    );
+    assert!(instance.history().contains(&WorkflowEvent::StepFailed {
+        step: 2,
+        timed_out: true
+    }));
+}
+
+#[test]
+fn test_workflow_instance_replays_history() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = user_add_workflow();
+    let mut instance = instance();
+    workflow.start(&mut instance, now);
+    workflow.handle(&mut instance, &Event::SeatReserved, now);
+
+    let mut replayed = WorkflowInstance::new("user-add-1", UserAddData::default());
+    for event in instance.history().to_vec() {
+        replayed.apply(event);
+    }
+    assert_eq!(replayed.state(), instance.state());
+}
+
+#[test]
+fn test_workflow_fails_on_first_step() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = user_add_workflow();
+    let mut instance = instance();
+    workflow.start(&mut instance, now);
+
+    assert!(workflow
+        .handle(&mut instance, &Event::SeatUnavailable, now)
+        .is_empty());
+    assert_eq!(*instance.state(), WorkflowState::Compensated);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FulfillmentCommand {
+    ReserveStock,
+    ReleaseStock,
+    ChargePayment,
+    RefundPayment,
+    BookCarrier(&'static str),
+    CancelCarrier(&'static str),
+    Ship,
+}
+
+#[derive(Debug, Clone)]
+enum FulfillmentEvent {
+    StockReserved,
+    PaymentCharged,
+    PaymentDeclined,
+    CarrierBooked(&'static str),
+    CarrierUnavailable(&'static str),
+    ShipmentRejected,
+}
+
+fn fulfillment_workflow() -> Workflow<Vec<&'static str>, FulfillmentCommand, FulfillmentEvent> {
+    use FulfillmentCommand as C;
+    use FulfillmentEvent as E;
+    let carrier = |name: &'static str| {
+        Step::new(name, move |_: &Vec<&'static str>| C::BookCarrier(name))
+            .succeeds_on(move |e| matches!(e, E::CarrierBooked(n) if *n == name))
+            .fails_on(move |e| matches!(e, E::CarrierUnavailable(n) if *n == name))
+            .record(move |d, _| d.push(name))
+    };
+    Workflow::new("fulfillment")
+        .step(
+            Step::parallel("prepare", Join::All)
+                .branch(
+                    Step::new("stock", |_| C::ReserveStock)
+                        .succeeds_on(|e| matches!(e, E::StockReserved))
+                        .compensate_with(|_| C::ReleaseStock),
+                )
+                .branch(
+                    Step::new("payment", |_| C::ChargePayment)
+                        .succeeds_on(|e| matches!(e, E::PaymentCharged))
+                        .fails_on(|e| matches!(e, E::PaymentDeclined))
+                        .compensate_with(|_| C::RefundPayment),
+                ),
+        )
+        .step(
+            Step::parallel("carrier", Join::Any)
+                .branch(carrier("post"))
+                .branch(carrier("courier")),
+        )
+        .step(Step::new("ship", |_| C::Ship).fails_on(|e| matches!(e, E::ShipmentRejected)))
+}
+
+#[test]
+fn test_parallel_steps_join() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = fulfillment_workflow();
+    let mut instance = WorkflowInstance::new("fulfillment-1", Vec::new());
+
+    assert_eq!(
+        workflow.start(&mut instance, now),
+        vec![
+            FulfillmentCommand::ReserveStock,
+            FulfillmentCommand::ChargePayment
+        ]
+    );
+    // Joining all: the step waits for every branch, in any order.
+    assert!(workflow
+        .handle(&mut instance, &FulfillmentEvent::PaymentCharged, now)
+        .is_empty());
+    assert_eq!(
+        workflow.handle(&mut instance, &FulfillmentEvent::StockReserved, now),
+        vec![
+            FulfillmentCommand::BookCarrier("post"),
+            FulfillmentCommand::BookCarrier("courier")
+        ]
+    );
+
+    // Joining any: one failure is tolerated, the first success continues.
+    assert!(workflow
+        .handle(
+            &mut instance,
+            &FulfillmentEvent::CarrierUnavailable("post"),
+            now
+        )
+        .is_empty());
+    assert_eq!(
+        workflow.handle(
+            &mut instance,
+            &FulfillmentEvent::CarrierBooked("courier"),
+            now
+        ),
+        vec![FulfillmentCommand::Ship]
+    );
+    assert_eq!(instance.data(), &vec!["courier"]);
+
+    // A later failure compensates the succeeded branches, most recently completed first.
+    assert_eq!(
+        workflow.handle(&mut instance, &FulfillmentEvent::ShipmentRejected, now),
+        vec![
+            FulfillmentCommand::ReleaseStock,
+            FulfillmentCommand::RefundPayment
+        ]
+    );
+    assert_eq!(instance.state(), &WorkflowState::Compensated);
+}
+
+#[test]
+fn test_parallel_step_partial_failure() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = fulfillment_workflow();
+    let mut instance = WorkflowInstance::new("fulfillment-1", Vec::new());
+    workflow.start(&mut instance, now);
+
+    workflow.handle(&mut instance, &FulfillmentEvent::StockReserved, now);
+    assert_eq!(
+        workflow.handle(&mut instance, &FulfillmentEvent::PaymentDeclined, now),
+        vec![FulfillmentCommand::ReleaseStock]
+    );
+    assert_eq!(
+        instance.history()[1..],
+        [
+            WorkflowEvent::BranchSucceeded { step: 0, branch: 0 },
+            WorkflowEvent::BranchFailed { step: 0, branch: 1 },
+            WorkflowEvent::StepFailed {
+                step: 0,
+                timed_out: false
+            },
+            WorkflowEvent::BranchCompensated { step: 0, branch: 0 },
+            WorkflowEvent::Compensated,
+        ]
+    );
+}
+
+#[test]
+fn test_manager_lists_stuck_instances() {
+    let now = SystemTime::UNIX_EPOCH;
+    let mut manager = WorkflowManager::new(user_add_workflow());
+    for (id, name) in [("user-add-1", "alice"), ("user-add-2", "bob")] {
+        let data = UserAddData {
+            name: name.to_string(),
+            org_id: "org-1".to_string(),
+            user_id: None,
+        };
+        manager.start(id, data, now);
+        manager.handle(id, &Event::SeatReserved, now).unwrap();
+    }
+    assert_eq!(manager.running().len(), 2);
+    assert_eq!(manager.running_step("user-add-1"), Some("create"));
+
+    // Creating the users never completes, and the step has no timeout.
+    let later = now + Duration::from_secs(600);
+    manager.start("user-add-3", instance().data().clone(), later);
+    manager.restore(WorkflowInstance::new("user-add-4", UserAddData::default()));
+    let stuck = manager.stuck(later, Duration::from_secs(300));
+    assert_eq!(
+        stuck.iter().map(|i| i.id()).collect::<Vec<_>>(),
+        ["user-add-1", "user-add-2"]
+    );
+    assert!(manager.stuck(later, Duration::from_secs(601)).is_empty());
+    assert_eq!(manager.running_step("user-add-3"), Some("reserve"));
+    assert_eq!(
+        manager.get("user-add-4").unwrap().state(),
+        &WorkflowState::Created
+    );
+    assert_eq!(manager.running().len(), 3);
+}
+
+#[test]
+fn test_manager_interventions() {
+    let now = SystemTime::UNIX_EPOCH;
+    let mut manager = WorkflowManager::new(user_add_workflow());
+    manager.start("user-add-1", instance().data().clone(), now);
+    manager.start("user-add-2", instance().data().clone(), now);
+    for id in ["user-add-1", "user-add-2"] {
+        manager.handle(id, &Event::SeatReserved, now).unwrap();
+        manager
+            .handle(id, &Event::UserCreated("user-1".to_string()), now)
+            .unwrap();
+    }
+
+    // Retrying restarts the timeout of the step.
+    let later = now + Duration::from_secs(20);
+    assert_eq!(
+        manager.retry("user-add-1", later),
+        Ok(vec![Command::AddUser(
+            "org-1".to_string(),
+            "user-1".to_string()
+        )])
+    );
+    assert_eq!(
+        manager.tick(now + Duration::from_secs(30)),
+        vec![
+            Command::DeleteUser("user-1".to_string()),
+            Command::ReleaseSeat("org-1".to_string()),
+        ]
+    );
+    assert_eq!(manager.running_step("user-add-1"), Some("add"));
+
+    assert_eq!(manager.force_complete("user-add-1"), Ok(()));
+    let completed = manager.get("user-add-1").unwrap();
+    assert_eq!(completed.state(), &WorkflowState::Completed);
+    assert_eq!(
+        completed.history().last(),
+        Some(&WorkflowEvent::ForceCompleted { step: 2 })
+    );
+    assert_eq!(
+        manager.abort("user-add-1"),
+        Err(InterventionError::NotRunning("user-add-1".to_string()))
+    );
+    assert_eq!(
+        manager.retry("user-add-9", later),
+        Err(InterventionError::NotFound("user-add-9".to_string()))
+    );
+
+    manager.start("user-add-3", instance().data().clone(), now);
+    manager
+        .handle("user-add-3", &Event::SeatReserved, now)
+        .unwrap();
+    assert_eq!(
+        manager.abort("user-add-3"),
+        Ok(vec![Command::ReleaseSeat("org-1".to_string())])
+    );
+    assert_eq!(
+        manager.get("user-add-3").unwrap().history()[3..],
+        [
+            WorkflowEvent::Aborted { step: 1 },
+            WorkflowEvent::StepCompensated { step: 0 },
+            WorkflowEvent::Compensated,
+        ]
+    );
+}
+
+#[test]
+fn test_manager_retries_pending_branches() {
+    let now = SystemTime::UNIX_EPOCH;
+    let mut manager = WorkflowManager::new(fulfillment_workflow());
+    manager.start("fulfillment-1", Vec::new(), now);
+    manager
+        .handle("fulfillment-1", &FulfillmentEvent::StockReserved, now)
+        .unwrap();
+
+    assert_eq!(
+        manager.retry("fulfillment-1", now),
+        Ok(vec![FulfillmentCommand::ChargePayment])
+    );
+    // The outcome of the branch before the retry still counts.
+    assert_eq!(
+        manager.handle("fulfillment-1", &FulfillmentEvent::PaymentCharged, now),
+        Ok(vec![
+            FulfillmentCommand::BookCarrier("post"),
+            FulfillmentCommand::BookCarrier("courier")
+        ])
+    );
+}
+
+#[test]
+fn test_manager_delivers_events_to_their_instance() {
+    let now = SystemTime::UNIX_EPOCH;
+    let mut manager = WorkflowManager::new(user_add_workflow());
+    manager.start("user-add-1", instance().data().clone(), now);
+    manager.start("user-add-2", instance().data().clone(), now);
+
+    assert_eq!(
+        manager.handle("user-add-1", &Event::SeatReserved, now),
+        Ok(vec![Command::CreateUser("alice".to_string())])
+    );
+    assert_eq!(manager.running_step("user-add-1"), Some("create"));
+    assert_eq!(manager.running_step("user-add-2"), Some("reserve"));
+
+    assert_eq!(
+        manager.handle("user-add-2", &Event::SeatUnavailable, now),
+        Ok(Vec::new())
+    );
+    assert_eq!(
+        manager.get("user-add-2").unwrap().state(),
+        &WorkflowState::Compensated
+    );
+    assert_eq!(manager.running_step("user-add-1"), Some("create"));
+    assert_eq!(
+        manager.handle("user-add-9", &Event::SeatReserved, now),
+        Err(InterventionError::NotFound("user-add-9".to_string()))
+    );
+}
+
+fn booking_workflow() -> Workflow<Vec<&'static str>, FulfillmentCommand, FulfillmentEvent> {
+    use FulfillmentCommand as C;
+    use FulfillmentEvent as E;
+    crate::workflow! {
+        "booking";
+        parallel "prepare" (Join::All) {
+            step "stock" {
+                command: |_: &Vec<&'static str>| C::ReserveStock,
+                succeeds_on: |e| matches!(e, E::StockReserved),
+                compensate_with: |_| C::ReleaseStock,
+            }
+            step "payment" {
+                command: |_: &Vec<&'static str>| C::ChargePayment,
+                succeeds_on: |e| matches!(e, E::PaymentCharged),
+                fails_on: |e| matches!(e, E::PaymentDeclined),
+                compensate_with: |_| C::RefundPayment,
+            }
+        }
+        parallel "carrier" (Join::Any) {
+            step "post" {
+                command: |_: &Vec<&'static str>| C::BookCarrier("post"),
+                succeeds_on: |e| matches!(e, E::CarrierBooked("post")),
+                record: |d, _| d.push("post"),
+                compensate_with: |_| C::CancelCarrier("post"),
+            }
+            step "courier" {
+                command: |_: &Vec<&'static str>| C::BookCarrier("courier"),
+                succeeds_on: |e| matches!(e, E::CarrierBooked("courier")),
+                record: |d, _| d.push("courier"),
+                compensate_with: |_| C::CancelCarrier("courier"),
+            }
+        }
+        parallel "notify" (Join::All) {}
+        step "ship" {
+            command: |_: &Vec<&'static str>| C::Ship,
+            fails_on: |e| matches!(e, E::ShipmentRejected),
+            timeout: Duration::MAX,
+        }
+    }
+}
+
+#[test]
+fn test_workflow_macro() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = booking_workflow();
+    assert_eq!(workflow.name(), "booking");
+    assert_eq!(
+        workflow.steps().iter().map(Step::name).collect::<Vec<_>>(),
+        ["prepare", "carrier", "notify", "ship"]
+    );
+
+    let mut instance = WorkflowInstance::new("booking-1", Vec::new());
+    workflow.start(&mut instance, now);
+    workflow.handle(&mut instance, &FulfillmentEvent::StockReserved, now);
+    workflow.handle(&mut instance, &FulfillmentEvent::PaymentCharged, now);
+    // The step without branches succeeds as soon as it starts.
+    assert_eq!(
+        workflow.handle(&mut instance, &FulfillmentEvent::CarrierBooked("post"), now),
+        vec![FulfillmentCommand::Ship]
+    );
+    assert!(instance
+        .history()
+        .contains(&WorkflowEvent::StepSucceeded { step: 2 }));
+    assert_eq!(
+        instance.state(),
+        &WorkflowState::Running {
+            step: 3,
+            since: now
+        }
+    );
+
+    // The timeout of the step overflows the time, so it never elapses.
+    assert_eq!(workflow.deadline(&instance), None);
+    assert!(workflow
+        .tick(&mut instance, now + Duration::from_secs(3600))
+        .is_empty());
+    let mut manager = WorkflowManager::new(booking_workflow());
+    manager.restore(instance);
+    assert!(manager.stuck(now, Duration::MAX).is_empty());
+}
+
+#[test]
+fn test_compensate_late_branches() {
+    let now = SystemTime::UNIX_EPOCH;
+    let workflow = booking_workflow();
+    let mut instance = WorkflowInstance::new("booking-1", Vec::new());
+    workflow.start(&mut instance, now);
+    workflow.handle(&mut instance, &FulfillmentEvent::StockReserved, now);
+    workflow.handle(&mut instance, &FulfillmentEvent::PaymentCharged, now);
+    workflow.handle(&mut instance, &FulfillmentEvent::CarrierBooked("post"), now);
+
+    // The step joining any already continued, so the slower carrier is cancelled.
+    assert_eq!(
+        workflow.handle(
+            &mut instance,
+            &FulfillmentEvent::CarrierBooked("courier"),
+            now
+        ),
+        vec![FulfillmentCommand::CancelCarrier("courier")]
+    );
+    assert_eq!(instance.data(), &vec!["post"]);
+    // A later failure does not cancel it again.
+    assert_eq!(
+        workflow.handle(&mut instance, &FulfillmentEvent::ShipmentRejected, now),
+        vec![
+            FulfillmentCommand::CancelCarrier("post"),
+            FulfillmentCommand::RefundPayment,
+            FulfillmentCommand::ReleaseStock,
+        ]
+    );
+
+    // The step joining all already failed, so the stock reserved late is released.
+    let mut instance = WorkflowInstance::new("booking-2", Vec::new());
+    workflow.start(&mut instance, now);
+    workflow.handle(&mut instance, &FulfillmentEvent::PaymentDeclined, now);
+    assert_eq!(instance.state(), &WorkflowState::Compensated);
+    assert_eq!(
+        workflow.handle(&mut instance, &FulfillmentEvent::StockReserved, now),
+        vec![FulfillmentCommand::ReleaseStock]
+    );
+    assert!(workflow
+        .handle(&mut instance, &FulfillmentEvent::StockReserved, now)
+        .is_empty());
+}
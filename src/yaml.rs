@@ -0,0 +1,176 @@
+use crate::json;
+use crate::summary::SummaryValue;
+
+/// Parse the subset of YAML written by hand for fixtures: block mappings and sequences, plain
+/// and quoted scalars, full-line comments, and flow collections written as JSON. Anchors, tags,
+/// block scalars and multiple documents are not supported.
+pub(crate) fn parse(input: &str) -> Result<SummaryValue, String> {
+    let mut lines = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let content = line.trim_start_matches(' ');
+        if content.starts_with('\t') {
+            return Err(format!("line {}: tabs cannot indent", index + 1));
+        }
+        let content = content.trim_end();
+        if content.is_empty() || content.starts_with('#') || content == "---" {
+            continue;
+        }
+        lines.push(Line {
+            number: index + 1,
+            indent: line.len() - line.trim_start_matches(' ').len(),
+            content: content.to_string(),
+        });
+    }
+    let indent = match lines.first() {
+        Some(line) => line.indent,
+        None => return Ok(SummaryValue::Null),
+    };
+    let mut parser = Parser { lines, at: 0 };
+    let value = parser.node(indent)?;
+    match parser.lines.get(parser.at) {
+        Some(line) => Err(format!("line {}: unexpected indentation", line.number)),
+        None => Ok(value),
+    }
+}
+
+struct Line {
+    number: usize,
+    indent: usize,
+    content: String,
+}
+
+struct Parser {
+    lines: Vec<Line>,
+    at: usize,
+}
+
+impl Parser {
+    fn node(&mut self, indent: usize) -> Result<SummaryValue, String> {
+        let line = &self.lines[self.at];
+        if is_item(&line.content) {
+            self.sequence(indent)
+        } else if split_key(&line.content, line.number)?.is_some() {
+            self.mapping(indent)
+        } else {
+            self.at += 1;
+            scalar(&line.content, line.number)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<SummaryValue, String> {
+        let mut items = Vec::new();
+        while let Some(line) = self.lines.get_mut(self.at) {
+            if line.indent != indent || !is_item(&line.content) {
+                break;
+            }
+            let rest = line.content[1..].trim_start().to_string();
+            if rest.is_empty() {
+                self.at += 1;
+                items.push(self.child(indent)?);
+            } else {
+                // The item starts on the line of its dash: parse it as a node at its column.
+                let column = indent + line.content.len() - rest.len();
+                line.indent = column;
+                line.content = rest;
+                items.push(self.node(column)?);
+            }
+        }
+        Ok(SummaryValue::List(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<SummaryValue, String> {
+        let mut fields = Vec::new();
+        while let Some(line) = self.lines.get(self.at) {
+            if line.indent != indent {
+                break;
+            }
+            let number = line.number;
+            let (key, value) = split_key(&line.content, number)?
+                .ok_or_else(|| format!("line {}: expected a key", number))?;
+            let value = value.to_string();
+            self.at += 1;
+            let value = if value.is_empty() {
+                match self.lines.get(self.at) {
+                    Some(next) if next.indent == indent && is_item(&next.content) => {
+                        self.sequence(indent)?
+                    }
+                    _ => self.child(indent)?,
+                }
+            } else {
+                scalar(&value, number)?
+            };
+            fields.push((key, value));
+        }
+        Ok(SummaryValue::Object(fields))
+    }
+
+    fn child(&mut self, indent: usize) -> Result<SummaryValue, String> {
+        match self.lines.get(self.at) {
+            Some(next) if next.indent > indent => self.node(next.indent),
+            _ => Ok(SummaryValue::Null),
+        }
+    }
+}
+
+fn is_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Split a `key: value` line, returning `None` if the line is not one.
+fn split_key(content: &str, number: usize) -> Result<Option<(String, &str)>, String> {
+    let (key, rest) = if let Some(quote @ ('"' | '\'')) = content.chars().next() {
+        let end = match content[1..].find(quote) {
+            Some(end) => end + 2,
+            None => return Ok(None),
+        };
+        let key = match scalar(&content[..end], number)? {
+            SummaryValue::String(key) => key,
+            _ => return Ok(None),
+        };
+        (key, &content[end..])
+    } else if content.starts_with(['{', '[', '-']) {
+        return Ok(None);
+    } else {
+        match content
+            .find(": ")
+            .or_else(|| content.ends_with(':').then(|| content.len() - 1))
+        {
+            Some(colon) => (content[..colon].trim_end().to_string(), &content[colon..]),
+            None => return Ok(None),
+        }
+    };
+    match rest.strip_prefix(':') {
+        Some(value) if value.is_empty() || value.starts_with(' ') => Ok(Some((key, value.trim()))),
+        _ => Ok(None),
+    }
+}
+
+fn scalar(text: &str, number: usize) -> Result<SummaryValue, String> {
+    if text.starts_with(['"', '{', '[']) {
+        return json::parse(text).map_err(|e| format!("line {}: {}", number, e));
+    }
+    if let Some(quoted) = text.strip_prefix('\'') {
+        return match quoted.strip_suffix('\'') {
+            Some(inner) => Ok(SummaryValue::String(inner.replace("''", "'"))),
+            None => Err(format!("line {}: unterminated string", number)),
+        };
+    }
+    let text = text
+        .split_once(" #")
+        .map_or(text, |(value, _)| value)
+        .trim();
+    Ok(match text {
+        "" | "~" | "null" => SummaryValue::Null,
+        "true" => SummaryValue::Bool(true),
+        "false" => SummaryValue::Bool(false),
+        _ => match text.parse::<i64>() {
+            Ok(integer) => SummaryValue::Integer(integer),
+            Err(_) if text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') => {
+                text.parse::<f64>()
+                    .map(SummaryValue::Number)
+                    .unwrap_or_else(|_| SummaryValue::String(text.to_string()))
+            }
+            Err(_) => SummaryValue::String(text.to_string()),
+        },
+    })
+}